@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use crate::config::HOST_FIELDS;
+use crate::utils::{ Result, SshcError };
+
+pub const USAGE: &str = "\
+Usage: ssht [OPTIONS] [COMMAND]
+
+Commands:
+  export-csv [FILE]  Write all hosts as CSV to FILE (or stdout) and exit
+  import-csv FILE    Open the import review for the hosts in a CSV file
+  key-report [FORMAT]
+                     Print the public keys used by hosts as table, json or csv and exit
+
+Options:
+  --home <DIR>   Use DIR instead of the detected home directory
+  --read-only    Browse and connect only; config management is disabled
+  --plain        Render without colors (also when NO_COLOR is set)
+  --edit         Start in config management
+  --add [NAME]   Start in the add-host form, pre-filled with NAME or an ssh command
+                 such as \"root@10.0.0.5 -p 2222\"
+  --print        Print the name of the host chosen with Enter instead of connecting
+  --print-format <FORMAT>
+                 Print the chosen host as name or json (implies --print)
+  --fields <LIST>
+                 Print comma-separated fields of the chosen host, tab separated (implies --print)
+  -h, --help     Print this help";
+
+/// 子命令的名称
+const COMMANDS: [&str; 3] = ["export-csv", "import-csv", "key-report"];
+
+/// 子命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    ExportCsv(Option<PathBuf>),  // None 时输出到 stdout
+    ImportCsv(PathBuf),
+    KeyReport(KeyReportFormat),
+}
+
+/// key-report 的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeyReportFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+impl KeyReportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "table" => Some(Self::Table),
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// --edit 或 --add 指定的启动界面
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartIn {
+    Config,
+    /// 新建主机表单；可以预填名称或 ssh 命令（如 "root@10.0.0.5 -p 2222"）
+    AddHost(Option<String>),
+}
+
+/// --print 选中主机后的输出格式
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrintFormat {
+    Name,
+    Json,
+    Fields(Vec<String>),
+}
+
+impl PrintFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "name" => Some(Self::Name),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// 解析 --fields 的逗号分隔列表，字段名须在 HOST_FIELDS 中
+    pub fn parse_fields(value: &str) -> Result<Self> {
+        let fields: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect();
+        if fields.is_empty() {
+            return Err(SshcError::Usage(format!("--fields requires at least one field\n\n{}", USAGE)));
+        }
+        if let Some(unknown) = fields.iter().find(|field| !HOST_FIELDS.contains(&field.as_str())) {
+            return Err(SshcError::Usage(format!("Unknown field: {} (available: {})", unknown, HOST_FIELDS.join(", "))));
+        }
+        Ok(Self::Fields(fields))
+    }
+}
+
+/// 命令行参数
+#[derive(Debug, Clone, Default)]
+pub struct CliOptions {
+    pub home: Option<PathBuf>,
+    pub help: bool,
+    pub read_only: bool,
+    /// 单色界面；NO_COLOR 环境变量有同样效果
+    pub plain: bool,
+    pub command: Option<CliCommand>,
+    pub start: Option<StartIn>,
+    pub print: Option<PrintFormat>,
+    /// 隐藏选项：退出后把启动各阶段的耗时打印到 stderr
+    pub profile_startup: bool,
+}
+
+/// 设置输出格式；--print-format 和 --fields 只能给出一种
+fn set_print_format(options: &mut CliOptions, format: PrintFormat) -> Result<()> {
+    if let Some(current) = &options.print && *current != PrintFormat::Name && *current != format {
+        return Err(SshcError::Usage(format!("Use either --print-format or --fields, not both\n\n{}", USAGE)));
+    }
+    options.print = Some(format);
+    Ok(())
+}
+
+/// --edit 和 --add 只能给出一个
+fn set_start(options: &mut CliOptions, start: StartIn) -> Result<()> {
+    if options.start.is_some() {
+        return Err(SshcError::Usage(format!("Use either --edit or --add, not both\n\n{}", USAGE)));
+    }
+    options.start = Some(start);
+    Ok(())
+}
+
+fn parse_print_format(value: &str) -> Result<PrintFormat> {
+    PrintFormat::parse(value)
+        .ok_or_else(|| SshcError::Usage(format!("Unknown print format: {} (use name or json)\n\n{}", value, USAGE)))
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliOptions> {
+    let mut options = CliOptions::default();
+    let mut args = args.into_iter().peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--home" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| SshcError::Usage(format!("--home requires a directory\n\n{}", USAGE)))?;
+                options.home = Some(PathBuf::from(value));
+            }
+            "-h" | "--help" => options.help = true,
+            "--read-only" => options.read_only = true,
+            "--plain" => options.plain = true,
+            "--profile-startup" => options.profile_startup = true,
+            "--edit" => set_start(&mut options, StartIn::Config)?,
+            "--add" => {
+                // 名称可选：下一个参数不是选项或子命令时作为预填内容
+                let value = args.next_if(|next| !next.starts_with('-') && !COMMANDS.contains(&next.as_str()));
+                set_start(&mut options, StartIn::AddHost(value))?;
+            }
+            "--print" => {
+                options.print.get_or_insert(PrintFormat::Name);
+            }
+            "--print-format" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| SshcError::Usage(format!("--print-format requires name or json\n\n{}", USAGE)))?;
+                set_print_format(&mut options, parse_print_format(&value)?)?;
+            }
+            "--fields" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| SshcError::Usage(format!("--fields requires a list of fields\n\n{}", USAGE)))?;
+                set_print_format(&mut options, PrintFormat::parse_fields(&value)?)?;
+            }
+            "export-csv" | "import-csv" | "key-report" if options.command.is_some() => {
+                return Err(SshcError::Usage(format!("Only one command can be given\n\n{}", USAGE)));
+            }
+            "export-csv" => {
+                options.command = Some(CliCommand::ExportCsv(None));
+            }
+            "import-csv" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| SshcError::Usage(format!("import-csv requires a file\n\n{}", USAGE)))?;
+                options.command = Some(CliCommand::ImportCsv(PathBuf::from(value)));
+            }
+            "key-report" => {
+                options.command = Some(CliCommand::KeyReport(KeyReportFormat::default()));
+            }
+            other => {
+                if let Some(value) = other.strip_prefix("--home=") {
+                    options.home = Some(PathBuf::from(value));
+                } else if let Some(value) = other.strip_prefix("--add=") {
+                    set_start(&mut options, StartIn::AddHost(Some(value.to_string()).filter(|value| !value.is_empty())))?;
+                } else if let Some(value) = other.strip_prefix("--print-format=") {
+                    set_print_format(&mut options, parse_print_format(value)?)?;
+                } else if let Some(value) = other.strip_prefix("--fields=") {
+                    set_print_format(&mut options, PrintFormat::parse_fields(value)?)?;
+                } else if let Some(CliCommand::ExportCsv(file @ None)) = &mut options.command && !other.starts_with('-') {
+                    *file = Some(PathBuf::from(other));
+                } else if let Some(CliCommand::KeyReport(format)) = &mut options.command && !other.starts_with('-') {
+                    *format = KeyReportFormat::parse(other).ok_or_else(|| {
+                        SshcError::Usage(format!("Unknown key-report format: {} (use table, json or csv)\n\n{}", other, USAGE))
+                    })?;
+                } else {
+                    return Err(SshcError::Usage(format!("Unknown argument: {}\n\n{}", other, USAGE)));
+                }
+            }
+        }
+    }
+
+    Ok(options)
+}
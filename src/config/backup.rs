@@ -0,0 +1,113 @@
+use anyhow::{ Context, Result };
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::config::create_parent_dir;
+
+/// 备份文件名的前缀和后缀：config-<Unix 时间戳>.bak，同一秒内的再加 -2、-3
+const BACKUP_PREFIX: &str = "config-";
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// 保存前自动备份的主配置文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    /// 备份时间（Unix 时间戳），取自文件名
+    pub created: u64,
+    pub size: u64,
+}
+
+/// 从文件名解析备份时间和同一秒内的序号，不是备份文件时返回 None
+fn backup_stamp(name: &str) -> Option<(u64, u32)> {
+    let stamp = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(BACKUP_SUFFIX)?;
+    match stamp.split_once('-') {
+        Some((seconds, counter)) => Some((seconds.parse().ok()?, counter.parse().ok()?)),
+        None => Some((stamp.parse().ok()?, 1)),
+    }
+}
+
+/// 目录中的备份，最新的在前；目录不存在时为空
+pub fn list_backups(dir: &Path) -> Vec<BackupEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<((u64, u32), BackupEntry)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let stamp = backup_stamp(&entry.file_name().to_string_lossy())?;
+            let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+            Some((stamp, BackupEntry { path: entry.path(), created: stamp.0, size: metadata.len() }))
+        })
+        .collect();
+    backups.sort_by(|(a, _), (b, _)| b.cmp(a));
+    backups.into_iter().map(|(_, backup)| backup).collect()
+}
+
+/// 把配置文件复制为一个新备份。文件不存在，或与最新的备份内容相同时不备份，返回 None
+pub fn create_backup(dir: &Path, config_path: &Path, now: u64) -> Result<Option<PathBuf>> {
+    let Ok(content) = fs::read(config_path) else {
+        return Ok(None);
+    };
+    if list_backups(dir).first().and_then(|latest| fs::read(&latest.path).ok()).as_ref() == Some(&content) {
+        return Ok(None);
+    }
+
+    let mut path = dir.join(format!("{}{}{}", BACKUP_PREFIX, now, BACKUP_SUFFIX));
+    let mut counter = 2;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}{}", BACKUP_PREFIX, now, counter, BACKUP_SUFFIX));
+        counter += 1;
+    }
+    create_parent_dir(&path)?;
+    fs::write(&path, &content).with_context(|| format!("Unable to write backup {:?}", path))?;
+    // 备份可能包含内部主机名，和配置文件一样只有本人可读
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(Some(path))
+}
+
+/// 按保留规则删除旧备份：只保留最新的 keep 个，并删除超过 max_age_days 天的；0 表示不限制。返回删除的文件
+pub fn prune_backups(dir: &Path, keep: usize, max_age_days: u64, now: u64) -> Result<Vec<PathBuf>> {
+    let max_age = max_age_days.saturating_mul(24 * 60 * 60);
+    let mut removed = Vec::new();
+    for (index, backup) in list_backups(dir).into_iter().enumerate() {
+        let too_many = keep > 0 && index >= keep;
+        let too_old = max_age > 0 && now.saturating_sub(backup.created) > max_age;
+        if too_many || too_old {
+            fs::remove_file(&backup.path).with_context(|| format!("Unable to remove old backup {:?}", backup.path))?;
+            removed.push(backup.path);
+        }
+    }
+    Ok(removed)
+}
+
+/// Unix 时间戳格式化为 UTC 的 "2024-05-01 13:45"
+pub fn format_utc(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // 由天数换算公历日期（Howard Hinnant 的 civil_from_days）
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60)
+}
+
+/// 文件大小，如 "812 B"、"4.2 KiB"
+pub fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config::{ option_display_name, row_style_choice, SshHost };
+
+/// 两台主机对照的一行，未设置的值为空
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareRow {
+    pub field: String,
+    pub left: String,
+    pub right: String,
+    pub same: bool,
+}
+
+impl CompareRow {
+    fn new(field: &str, left: String, right: String) -> Self {
+        let same = left == right;
+        Self { field: field.to_string(), left, right, same }
+    }
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+/// 逐项对照两台主机：先是 Host 行、元数据和常用字段，再是两边其他选项的并集（按键名排序）。
+/// 可重复的选项按顺序以逗号连接后比较
+pub fn compare_hosts(left: &SshHost, right: &SshHost) -> Vec<CompareRow> {
+    let env = |host: &SshHost| host.env.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>().join(", ");
+    let source = |host: &SshHost| host.source.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+    let mut rows = vec![
+        CompareRow::new("Host", left.name.clone(), right.name.clone()),
+        CompareRow::new("Display name", text(&left.display_name), text(&right.display_name)),
+        CompareRow::new("Folder", text(&left.folder), text(&right.folder)),
+        CompareRow::new("Description", text(&left.description), text(&right.description)),
+        CompareRow::new("Visible", left.visible.to_string(), right.visible.to_string()),
+        CompareRow::new("Tags", left.tags.join(", "), right.tags.join(", ")),
+        CompareRow::new("Aliases", left.aliases.join(", "), right.aliases.join(", ")),
+        CompareRow::new("Row style", row_style_choice(left), row_style_choice(right)),
+        CompareRow::new("Env", env(left), env(right)),
+        CompareRow::new("Config file", source(left), source(right)),
+        CompareRow::new("HostName", text(&left.hostname), text(&right.hostname)),
+        CompareRow::new("User", text(&left.user), text(&right.user)),
+        CompareRow::new("Port", text(&left.port), text(&right.port)),
+        CompareRow::new("IdentityFile", text(&left.identity_file), text(&right.identity_file)),
+    ];
+
+    let mut keys: Vec<&String> = left.other_options.keys().chain(right.other_options.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let values = |host: &SshHost| host.other_options.get_all(key).cloned().collect::<Vec<_>>().join(", ");
+        // 额外的 IdentityFile 与表单中的区分开
+        let field = if key == "identityfile" { "IdentityFile (more)".to_string() } else { option_display_name(key) };
+        rows.push(CompareRow::new(&field, values(left), values(right)));
+    }
+    rows
+}
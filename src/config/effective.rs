@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use crate::config::SshHost;
+
+/// ssh -G 输出与 sshc 解析结果对照的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveRow {
+    pub key: String,
+    pub effective: String,
+    pub configured: Option<String>,
+    pub differs: bool,
+}
+
+/// 解析 ssh -G 的 "key value" 输出；同一键出现多次（如 identityfile）时合并为逗号分隔，保持首次出现的顺序
+pub fn parse_ssh_g_output(output: &str) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let key = key.to_lowercase();
+        let value = value.trim();
+
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => {
+                existing.push_str(", ");
+                existing.push_str(value);
+            }
+            None => entries.push((key, value.to_string())),
+        }
+    }
+
+    entries
+}
+
+/// sshc 从主机块中解析到的选项，键名小写
+fn configured_options(host: &SshHost) -> Vec<(String, String)> {
+    let mut options = Vec::new();
+    // ssh -G 输出的是展开 %h 后的 HostName
+    if let Some(hostname) = host.resolved_hostname() {
+        options.push(("hostname".to_string(), hostname));
+    }
+    if let Some(user) = &host.user {
+        options.push(("user".to_string(), user.clone()));
+    }
+    if let Some(port) = &host.port {
+        options.push(("port".to_string(), port.clone()));
+    }
+    if let Some(identity_file) = &host.identity_file {
+        options.push(("identityfile".to_string(), identity_file.clone()));
+    }
+    let mut others: Vec<(String, String)> = host.other_options
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    others.sort();
+    options.extend(others);
+    options
+}
+
+fn normalize(value: &str, home: Option<&Path>) -> String {
+    let value = value.trim();
+    let value = match (value.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ => value.to_string(),
+    };
+    value.to_lowercase()
+}
+
+/// 对照有效配置和主机块：块中设置过的选项排在前面，值不一致时标记 differs
+pub fn compare_effective(host: &SshHost, effective: &[(String, String)], home: Option<&Path>) -> Vec<EffectiveRow> {
+    let configured = configured_options(host);
+    let mut rows = Vec::new();
+
+    for (key, value) in &configured {
+        let effective_value = effective
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default();
+        // 多值选项只要包含配置的值即视为一致
+        let wanted = normalize(value, home);
+        let differs = !effective_value.split(", ").any(|v| normalize(v, home) == wanted);
+        rows.push(EffectiveRow {
+            key: key.clone(),
+            effective: effective_value,
+            configured: Some(value.clone()),
+            differs,
+        });
+    }
+
+    for (key, value) in effective {
+        if !configured.iter().any(|(k, _)| k == key) {
+            rows.push(EffectiveRow {
+                key: key.clone(),
+                effective: value.clone(),
+                configured: None,
+                differs: false,
+            });
+        }
+    }
+
+    rows
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+use crate::config::{ home_dir, serialize_host, SshHost };
+
+/// 分享用配置片段的生成选项
+#[derive(Debug, Clone, Copy)]
+pub struct SnippetOptions {
+    pub include_metadata: bool,
+    pub tilde_paths: bool,
+}
+
+/// 用与写配置文件相同的序列化逻辑生成可直接粘贴的配置片段
+pub fn export_snippet(hosts: &[SshHost], options: SnippetOptions) -> Result<String> {
+    let home = home_dir().map(|h| h.to_string_lossy().to_string());
+
+    hosts
+        .iter()
+        .map(|host| {
+            let mut host = host.clone();
+            if options.tilde_paths
+                && let (Some(home), Some(identity_file)) = (&home, &host.identity_file)
+            {
+                host.identity_file = Some(tilde_relative(identity_file, home));
+            }
+            serialize_host(&host, options.include_metadata)
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|blocks| blocks.join("\n"))
+}
+
+/// 把 home 目录下的绝对路径改写成 ~/ 开头，便于在其他机器上使用
+pub fn tilde_relative(path: &str, home: &str) -> String {
+    let home = home.trim_end_matches('/');
+    if home.is_empty() {
+        return path.to_string();
+    }
+    match path.strip_prefix(home) {
+        Some("") => "~".to_string(),
+        Some(rest) if rest.starts_with('/') => format!("~{}", rest),
+        _ => path.to_string(),
+    }
+}
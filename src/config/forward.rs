@@ -0,0 +1,82 @@
+use crate::config::{ OptionMap, SshHost };
+use crate::utils::resolve_port;
+
+/// 端口转发选项（LocalForward / DynamicForward），按 ssh_config 的格式读写
+#[derive(Debug, Clone, PartialEq)]
+pub enum Forward {
+    /// LocalForward [bind:]port host:hostport
+    Local { listen: String, target: String },
+    /// DynamicForward [bind:]port
+    Dynamic { listen: String },
+}
+
+impl Forward {
+    /// 校验监听端口后构造本地转发；target 为空时转发到 localhost 的同一端口
+    pub fn local(listen: &str, target: &str) -> Result<Self, String> {
+        let listen = check_listen(listen)?;
+        let target = match target.trim() {
+            "" => format!("localhost:{}", listen.rsplit(':').next().unwrap_or(&listen)),
+            target if target.rsplit_once(':').is_some_and(|(_, port)| resolve_port(port).is_some()) => target.to_string(),
+            target => return Err(format!("'{}' is not host:port", target)),
+        };
+        Ok(Forward::Local { listen, target })
+    }
+
+    pub fn dynamic(listen: &str) -> Result<Self, String> {
+        Ok(Forward::Dynamic { listen: check_listen(listen)? })
+    }
+
+    /// other_options 中的小写键名
+    pub fn option_key(&self) -> &'static str {
+        match self {
+            Forward::Local { .. } => "localforward",
+            Forward::Dynamic { .. } => "dynamicforward",
+        }
+    }
+
+    pub fn option_value(&self) -> String {
+        match self {
+            Forward::Local { listen, target } => format!("{} {}", listen, target),
+            Forward::Dynamic { listen } => listen.clone(),
+        }
+    }
+
+    /// 对应的 ssh 命令行参数，例如 "-L 5432:localhost:5432"
+    pub fn command_line(&self) -> String {
+        match self {
+            Forward::Local { listen, target } => format!("-L {}:{}", listen, target),
+            Forward::Dynamic { listen } => format!("-D {}", listen),
+        }
+    }
+
+    pub fn add_to(&self, options: &mut OptionMap) {
+        options.push(self.option_key().to_string(), self.option_value());
+    }
+}
+
+/// 监听地址为 port 或 bind:port，端口必须是数字
+fn check_listen(listen: &str) -> Result<String, String> {
+    let listen = listen.trim();
+    let port = listen.rsplit(':').next().unwrap_or(listen);
+    match port.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(listen.to_string()),
+        _ => Err(format!("'{}' is not a valid local port", listen)),
+    }
+}
+
+impl SshHost {
+    /// 主机上配置的所有转发，按出现顺序
+    pub fn forwards(&self) -> Vec<Forward> {
+        self.other_options
+            .iter()
+            .filter_map(|(key, value)| match key.as_str() {
+                "localforward" => {
+                    let (listen, target) = value.split_once(char::is_whitespace)?;
+                    Some(Forward::Local { listen: listen.to_string(), target: target.trim().to_string() })
+                }
+                "dynamicforward" => Some(Forward::Dynamic { listen: value.clone() }),
+                _ => None,
+            })
+            .collect()
+    }
+}
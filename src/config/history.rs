@@ -0,0 +1,85 @@
+use anyhow::{ Context, Result };
+use serde::{ Deserialize, Serialize };
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+
+use crate::config::{ create_parent_dir, sshc_file, SshcDir };
+
+/// 单个主机的连接统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostHistory {
+    pub connections: u64,
+    pub total_seconds: u64,
+    pub last_connected: Option<u64>,  // Unix 时间戳
+    pub last_exit_code: Option<i32>,
+    pub last_duration_seconds: Option<u64>,
+    /// 最近一次连接失败，成功连接后清除；只在开启 remember_failures 时写入
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<HostFailure>,
+}
+
+/// 一次失败的连接：退出码、分类后的原因和发生时间
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostFailure {
+    pub exit_code: Option<i32>,
+    pub reason: String,
+    pub at: u64,  // Unix 时间戳
+}
+
+/// 连接历史，按主机名保存在状态目录下的 history.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    pub hosts: BTreeMap<String, HostHistory>,
+}
+
+impl History {
+    pub fn path() -> Option<PathBuf> {
+        sshc_file(SshcDir::State, "history.toml")
+    }
+
+    /// 文件不存在或损坏时返回空历史，历史记录不应该阻止启动
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("Unable to locate the sshc state directory")?;
+        create_parent_dir(&path)?;
+        let content = toml::to_string(self).context("Unable to serialize history")?;
+        fs::write(&path, content).with_context(|| format!("Unable to write history file: {:?}", path))
+    }
+
+    /// 合并较新的记录（例如文件加载完成前本次会话记下的连接）：次数和时长相加，最近一次以较新的为准
+    pub fn merge(&mut self, newer: &History) {
+        for (host, newer) in &newer.hosts {
+            let entry = self.hosts.entry(host.clone()).or_default();
+            entry.connections += newer.connections;
+            entry.total_seconds += newer.total_seconds;
+            if newer.last_connected.is_some() {
+                entry.last_connected = newer.last_connected;
+                entry.last_exit_code = newer.last_exit_code;
+                entry.last_duration_seconds = newer.last_duration_seconds;
+                entry.last_failure = newer.last_failure.clone();
+            }
+        }
+    }
+
+    pub fn record_session(&mut self, host: &str, duration: Duration, exit_code: Option<i32>) {
+        let entry = self.hosts.entry(host.to_string()).or_default();
+        entry.connections += 1;
+        entry.total_seconds += duration.as_secs();
+        entry.last_connected = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        entry.last_exit_code = exit_code;
+        entry.last_duration_seconds = Some(duration.as_secs());
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::{ Context, Result };
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::config::{
+    home_dir, parse_ssh_config_file, wildcard_match, parse_ssh_config_file_with_limits, serialize_hosts_with_style, write_ssh_config_file_with_style, ParseLimits,
+    SshHost, WriteStyle,
+};
+
+/// 与 ssh 相同的 Include 最大嵌套深度
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// 全局部分中 Include 行列出的模式（一行可有多个，空白分隔）。
+/// Host 块内的 Include 只对该 Host 生效，不在这里跟随
+pub fn include_patterns(hosts: &[SshHost]) -> Vec<String> {
+    hosts
+        .iter()
+        .filter_map(|host| host.prelude.as_deref())
+        .flat_map(str::lines)
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(char::is_whitespace)?;
+            key.eq_ignore_ascii_case("include").then(|| value.trim())
+        })
+        .flat_map(str::split_whitespace)
+        .map(|pattern| pattern.trim_matches('"').to_string())
+        .collect()
+}
+
+/// 把模式展开为绝对路径形式：~ 按 home 展开，相对路径相对于 base_dir（ssh 中为 ~/.ssh）
+fn absolute_pattern(pattern: &str, base_dir: &Path) -> PathBuf {
+    if let Some(rest) = pattern.strip_prefix("~/")
+        && let Some(home) = home_dir()
+    {
+        return home.join(rest);
+    }
+    base_dir.join(pattern)
+}
+
+/// Include 模式匹配到的现有文件，按文件名排序；通配符只支持最后一段
+pub fn resolve_include(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let path = absolute_pattern(pattern, base_dir);
+    let Some(file_pattern) = path.file_name().map(|name| name.to_string_lossy().chars().collect::<Vec<char>>()) else {
+        return Vec::new();
+    };
+    if !file_pattern.iter().any(|c| matches!(c, '*' | '?')) {
+        return if path.is_file() { vec![path] } else { Vec::new() };
+    }
+
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|file| file.is_file())
+        .filter(|file| {
+            let name: Vec<char> = file.file_name().unwrap_or_default().to_string_lossy().chars().collect();
+            wildcard_match(&file_pattern, &name)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// path 是否被 hosts 中某个 Include 模式覆盖（文件不必已存在）
+pub fn include_covers(hosts: &[SshHost], base_dir: &Path, path: &Path) -> bool {
+    let path: Vec<char> = path.to_string_lossy().chars().collect();
+    include_patterns(hosts).iter().any(|pattern| {
+        let pattern: Vec<char> = absolute_pattern(pattern, base_dir).to_string_lossy().chars().collect();
+        wildcard_match(&pattern, &path)
+    })
+}
+
+/// 按限制解析时被跳过的内容；不为空时界面以只读方式打开，避免保存时丢掉它们
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LimitReport {
+    /// 因过长被跳过的行：文件和行号
+    pub long_lines: Vec<(PathBuf, usize)>,
+    /// 超出 max_hosts 未加载的主机数
+    pub hidden_hosts: usize,
+    pub max_hosts: usize,
+}
+
+impl LimitReport {
+    pub fn is_empty(&self) -> bool {
+        self.long_lines.is_empty() && self.hidden_hosts == 0
+    }
+
+    /// 顶栏横幅的文字
+    pub fn banner(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.hidden_hosts > 0 {
+            parts.push(format!("only the first {} of {} hosts are shown (max_hosts)", self.max_hosts, self.max_hosts + self.hidden_hosts));
+        }
+        if let Some((path, line)) = self.long_lines.first() {
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let more = match self.long_lines.len() {
+                1 => String::new(),
+                count => format!(" and {} more", count - 1),
+            };
+            parts.push(format!("skipped over-long line {}:{}{} (max_config_line_length)", name, line, more));
+        }
+        (!parts.is_empty()).then(|| format!("⚠ {} — read-only", parts.join("; ")))
+    }
+}
+
+/// 读取主配置以及它（递归）Include 的文件。被包含文件中的条目 source 为该文件，
+/// 排在主配置的条目之后；同时返回发现的被包含文件
+pub fn parse_config_tree(main: &Path) -> Result<(Vec<SshHost>, Vec<PathBuf>)> {
+    parse_config_tree_with_limits(main, &ParseLimits::default()).map(|(hosts, files, _)| (hosts, files))
+}
+
+/// 同 parse_config_tree，按 limits 跳过过长的行，并只保留前 max_hosts 台主机
+pub fn parse_config_tree_with_limits(main: &Path, limits: &ParseLimits) -> Result<(Vec<SshHost>, Vec<PathBuf>, LimitReport)> {
+    let mut report = LimitReport { max_hosts: limits.max_hosts, ..LimitReport::default() };
+    let mut parse = |file: &Path| -> Result<Vec<SshHost>> {
+        let (hosts, skipped) = parse_ssh_config_file_with_limits(file, limits)?;
+        report.long_lines.extend(skipped.into_iter().map(|line| (file.to_path_buf(), line)));
+        Ok(hosts)
+    };
+    let mut hosts = parse(main)?;
+    let base_dir = main.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut queue: Vec<(Vec<String>, usize)> = vec![(include_patterns(&hosts), 1)];
+
+    while let Some((patterns, depth)) = queue.pop() {
+        if depth > MAX_INCLUDE_DEPTH {
+            continue;
+        }
+        for pattern in patterns {
+            for file in resolve_include(&pattern, &base_dir) {
+                if file == main || files.contains(&file) {
+                    continue;
+                }
+                let mut included = parse(&file)?;
+                let locked = !file_writable(&file);
+                for host in &mut included {
+                    host.source = Some(file.clone());
+                    host.locked = locked;
+                }
+                queue.push((include_patterns(&included), depth + 1));
+                hosts.extend(included);
+                files.push(file);
+            }
+        }
+    }
+
+    // 超出数量的主机不加载；Match 块等原样条目不计数
+    let mut count = 0;
+    hosts.retain(|host| {
+        if host.is_verbatim() {
+            return true;
+        }
+        count += 1;
+        count <= limits.max_hosts
+    });
+    report.hidden_hosts = count.saturating_sub(limits.max_hosts);
+    Ok((hosts, files, report))
+}
+
+/// 当前用户能否写入 path：权限位标为只读，或无法以写方式打开（例如属于 root）时为 false。
+/// 文件不存在时为 true，保存时会创建
+pub fn file_writable(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.permissions().readonly() => false,
+        // 只打开不写入，不改变内容和修改时间
+        Ok(_) => fs::OpenOptions::new().append(true).open(path).is_ok(),
+        Err(_) => true,
+    }
+}
+
+/// 读取 system_config_paths 中的只读配置，主机标记为 system、source 为所在文件。
+/// 不跟随其中的 Include，Match 块和全局部分不作为主机；同名主机按 patterns 的顺序取第一个。
+/// 读不了的文件跳过
+pub fn parse_system_configs(patterns: &[String], limits: &ParseLimits) -> (Vec<SshHost>, Vec<PathBuf>) {
+    let mut hosts: Vec<SshHost> = Vec::new();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        for file in resolve_include(pattern, Path::new("/")) {
+            if files.contains(&file) {
+                continue;
+            }
+            let Ok((parsed, _)) = parse_ssh_config_file_with_limits(&file, limits) else {
+                continue;
+            };
+            for mut host in parsed.into_iter().filter(|host| !host.is_verbatim()) {
+                if hosts.iter().any(|other| other.name == host.name) {
+                    continue;
+                }
+                host.system = true;
+                host.source = Some(file.clone());
+                hosts.push(host);
+            }
+            files.push(file);
+        }
+    }
+    (hosts, files)
+}
+
+/// 把系统主机放到 hosts 末尾，替换之前放入的。用户配置中有同名主机时不加入：
+/// 与 ssh 先读用户配置、每个选项取第一个值的规则一致，用户的条目优先
+pub fn merge_system_hosts(hosts: &mut Vec<SshHost>, system: &[SshHost]) {
+    hosts.retain(|host| !host.system);
+    let shadowed: Vec<String> = hosts.iter().filter(|host| !host.is_verbatim()).map(|host| host.name.clone()).collect();
+    hosts.extend(system.iter().filter(|host| !shadowed.contains(&host.name)).cloned());
+}
+
+/// 按 source 把条目写回各自的文件。被包含文件中的条目没有变化时不写，
+/// files 之外新出现的目标文件会被创建
+pub fn write_config_tree(main: &Path, files: &[PathBuf], hosts: &[SshHost], style: WriteStyle) -> Result<()> {
+    let in_file = |file: Option<&Path>| -> Vec<SshHost> {
+        hosts.iter().filter(|host| host.source.as_deref() == file).cloned().collect()
+    };
+    write_ssh_config_file_with_style(main, &in_file(None), style)?;
+
+    let mut targets: Vec<&PathBuf> = files.iter().collect();
+    for source in hosts.iter().filter_map(|host| host.source.as_ref()) {
+        if !targets.contains(&source) {
+            targets.push(source);
+        }
+    }
+    for file in targets {
+        let file_hosts = in_file(Some(file));
+        // 与文件当前解析结果的序列化相同时保留原有格式
+        let current = parse_ssh_config_file(file).ok().filter(|_| file.is_file());
+        let unchanged = match current {
+            Some(current) => serialize_hosts_with_style(&current, style).ok() == Some(serialize_hosts_with_style(&file_hosts, style)?),
+            None => false,
+        };
+        if unchanged {
+            continue;
+        }
+        write_ssh_config_file_with_style(file, &file_hosts, style).with_context(|| format!("Unable to write included config: {:?}", file))?;
+    }
+    Ok(())
+}
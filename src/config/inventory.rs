@@ -0,0 +1,268 @@
+use anyhow::{ bail, Context, Result };
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+use std::collections::{ BTreeMap, HashSet };
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::process::Command;
+
+use crate::config::{ create_parent_dir, home_dir, imported_port_problem, imported_value_problem, sanitize_host_name, sshc_file, SshcDir, SshHost };
+
+/// 远程主机清单的来源，写在 settings.toml 的 [inventory] 中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventorySource {
+    /// http(s):// 地址用 curl 获取；其他值按本地文件路径读取（可以用 file:// 或 ~ 开头）
+    pub url: String,
+    /// 同步进来的主机所在的文件夹
+    #[serde(default = "default_inventory_folder")]
+    pub folder: String,
+    /// 条目数组在 JSON 中的位置（点分隔的键），空表示顶层就是数组
+    #[serde(default)]
+    pub list: String,
+    #[serde(default)]
+    pub fields: InventoryFields,
+    #[serde(default = "default_inventory_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_inventory_folder() -> String {
+    "Inventory".to_string()
+}
+
+fn default_inventory_timeout() -> u64 {
+    15
+}
+
+/// 条目中各字段的键名（点分隔表示嵌套）；user 和 port 为空表示不映射
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InventoryFields {
+    pub name: String,
+    pub hostname: String,
+    pub user: String,
+    pub port: String,
+}
+
+impl Default for InventoryFields {
+    fn default() -> Self {
+        Self {
+            name: "name".to_string(),
+            hostname: "hostname".to_string(),
+            user: "user".to_string(),
+            port: "port".to_string(),
+        }
+    }
+}
+
+/// 清单中的一台机器，也是快照中保存的内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryEntry {
+    pub hostname: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<String>,
+}
+
+/// 上次同步并保存后的清单，按主机名保存在数据目录下的 inventory_snapshot.toml
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InventorySnapshot {
+    pub hosts: BTreeMap<String, InventoryEntry>,
+}
+
+impl InventorySnapshot {
+    pub fn path() -> Option<PathBuf> {
+        sshc_file(SshcDir::Data, "inventory_snapshot.toml")
+    }
+
+    /// 文件不存在或损坏时返回空快照，清单中的机器都会作为新机器列出
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        create_parent_dir(path)?;
+        let content = toml::to_string(self).context("Unable to serialize the inventory snapshot")?;
+        fs::write(path, content).with_context(|| format!("Unable to write inventory snapshot: {:?}", path))
+    }
+}
+
+/// 获取清单原文。只在用户主动同步时调用，http(s) 会阻塞到 curl 结束或超时
+pub fn fetch_inventory(source: &InventorySource) -> Result<String> {
+    let url = source.url.trim();
+    if url.starts_with("http://") || url.starts_with("https://") {
+        let output = Command::new("curl")
+            .args(["-fsSL", "--max-time", &source.timeout_secs.max(1).to_string(), "--", url])
+            .output()
+            .context("Unable to run curl to fetch the inventory")?;
+        if !output.status.success() {
+            bail!("Fetching {} failed: {}", url, String::from_utf8_lossy(&output.stderr).trim());
+        }
+        return String::from_utf8(output.stdout).context("The inventory is not valid UTF-8");
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    let path = match path.strip_prefix("~/").zip(home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    };
+    fs::read_to_string(&path).with_context(|| format!("Unable to read inventory file: {:?}", path))
+}
+
+/// 按点分隔的键取值
+fn value_at<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|key| !key.is_empty()).try_fold(value, |value, key| value.get(key))
+}
+
+/// 字符串和数字都作为文本；空字符串视为缺失
+fn text_at(value: &Value, path: &str) -> Option<String> {
+    if path.trim().is_empty() {
+        return None;
+    }
+    let text = match value_at(value, path.trim())? {
+        Value::String(text) => text.trim().to_string(),
+        Value::Number(number) => number.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// 按字段映射解析清单 JSON。缺少名称或地址的条目、重名条目跳过并给出警告；
+/// 名称、地址、用户含空白或控制字符，或端口既不是数字也不是服务名的条目同样跳过，以免写出额外的配置行
+pub fn parse_inventory(content: &str, source: &InventorySource) -> Result<(BTreeMap<String, InventoryEntry>, Vec<String>)> {
+    let root: Value = serde_json::from_str(content).context("The inventory is not valid JSON")?;
+    let list = if source.list.trim().is_empty() { Some(&root) } else { value_at(&root, source.list.trim()) };
+    let Some(Value::Array(items)) = list else {
+        bail!("No array found at '{}' in the inventory", if source.list.trim().is_empty() { "<top level>" } else { source.list.trim() });
+    };
+
+    let fields = &source.fields;
+    let mut entries = BTreeMap::new();
+    let mut warnings = Vec::new();
+    for (number, item) in items.iter().enumerate() {
+        let Some(name) = text_at(item, &fields.name) else {
+            warnings.push(format!("Entry {}: no '{}' field, skipped", number + 1, fields.name));
+            continue;
+        };
+        if let Some(problem) = imported_value_problem(&name) {
+            warnings.push(format!("Entry {}: '{}' {}, skipped", number + 1, fields.name, problem));
+            continue;
+        }
+        let name = sanitize_host_name(&name);
+        let Some(hostname) = text_at(item, &fields.hostname) else {
+            warnings.push(format!("{}: no '{}' field, skipped", name, fields.hostname));
+            continue;
+        };
+        let user = text_at(item, &fields.user);
+        let port = text_at(item, &fields.port);
+        let problem = [(&fields.hostname, Some(&hostname)), (&fields.user, user.as_ref())]
+            .into_iter()
+            .find_map(|(field, value)| value.and_then(|value| imported_value_problem(value)).map(|problem| (field, problem)))
+            .or_else(|| port.as_deref().and_then(imported_port_problem).map(|problem| (&fields.port, problem)));
+        if let Some((field, problem)) = problem {
+            warnings.push(format!("{}: '{}' {}, skipped", name, field, problem));
+            continue;
+        }
+        if entries.contains_key(&name) {
+            warnings.push(format!("{}: listed more than once, keeping the first", name));
+            continue;
+        }
+        let entry = InventoryEntry { hostname, user, port };
+        entries.insert(name, entry);
+    }
+    Ok((entries, warnings))
+}
+
+/// 本次同步相对上次快照的变化
+#[derive(Debug, Clone, Default)]
+pub struct InventoryDelta {
+    pub added: Vec<SshHost>,
+    /// (当前主机, 更新后的主机)
+    pub modified: Vec<(SshHost, SshHost)>,
+    /// 清单中已不存在的机器，删除前需要确认
+    pub removed: Vec<SshHost>,
+    pub warnings: Vec<String>,
+}
+
+impl InventoryDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 把清单中变化了的字段写到主机上；其他字段（包括本地做的修改）保持不变
+fn apply_entry(host: &SshHost, entry: &InventoryEntry, previous: Option<&InventoryEntry>) -> SshHost {
+    let mut host = host.clone();
+    let changed = |new: &Option<String>, old: Option<&Option<String>>| old.is_none_or(|old| old != new);
+    if previous.is_none_or(|previous| previous.hostname != entry.hostname) {
+        host.hostname = Some(entry.hostname.clone());
+    }
+    if changed(&entry.user, previous.map(|previous| &previous.user)) {
+        host.user = entry.user.clone();
+    }
+    if changed(&entry.port, previous.map(|previous| &previous.port)) {
+        host.port = entry.port.clone();
+    }
+    host
+}
+
+/// 与上次快照比较：新出现的机器添加到清单文件夹，字段变化的机器修改，消失的机器删除。
+/// 快照中有但本地已删除的机器不会重新添加；文件夹外的同名主机不会被接管
+pub fn inventory_delta(
+    entries: &BTreeMap<String, InventoryEntry>,
+    snapshot: &InventorySnapshot,
+    hosts: &[SshHost],
+    folder: &str
+) -> InventoryDelta {
+    let mut delta = InventoryDelta::default();
+    let find = |name: &str| hosts.iter().find(|host| host.name == name && !host.is_verbatim() && !host.temporary && !host.system);
+    let in_folder = |host: &SshHost| host.folder.as_deref().is_some_and(|f| f.trim().eq_ignore_ascii_case(folder.trim()));
+
+    for (name, entry) in entries {
+        let previous = snapshot.hosts.get(name);
+        match (find(name), previous) {
+            (None, None) => {
+                let mut host = SshHost::new(name.clone());
+                host.hostname = Some(entry.hostname.clone());
+                host.user = entry.user.clone();
+                host.port = entry.port.clone();
+                host.folder = Some(folder.to_string());
+                delta.added.push(host);
+            }
+            (None, Some(_)) => {}
+            (Some(host), None) if !in_folder(host) => {
+                delta.warnings.push(format!("{}: a host with this name already exists outside '{}', skipped", name, folder));
+            }
+            (Some(host), previous) => {
+                if previous == Some(entry) {
+                    continue;
+                }
+                let updated = apply_entry(host, entry, previous);
+                if updated != *host {
+                    delta.modified.push((host.clone(), updated));
+                }
+            }
+        }
+    }
+
+    let seen: HashSet<&String> = entries.keys().collect();
+    delta.removed = snapshot.hosts
+        .keys()
+        .filter(|name| !seen.contains(name))
+        .filter_map(|name| find(name).cloned())
+        .collect();
+    delta
+}
+
+/// 获取、解析并比较：返回变化和同步保存后应写入的新快照
+pub fn sync_inventory(source: &InventorySource, snapshot: &InventorySnapshot, hosts: &[SshHost]) -> Result<(InventoryDelta, InventorySnapshot)> {
+    let content = fetch_inventory(source)?;
+    let (entries, warnings) = parse_inventory(&content, source)?;
+    let mut delta = inventory_delta(&entries, snapshot, hosts, &source.folder);
+    delta.warnings.splice(0..0, warnings);
+    Ok((delta, InventorySnapshot { hosts: entries }))
+}
@@ -0,0 +1,147 @@
+use anyhow::{ Context, Result };
+use base64::Engine;
+use base64::engine::general_purpose::{ STANDARD, STANDARD_NO_PAD };
+use serde::Serialize;
+use sha2::{ Digest, Sha256 };
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::config::{ identity_files, normalize_key_path, SshHost };
+
+/// 公钥文件中的信息，指纹与 ssh-keygen -l 的 SHA256 格式一致
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKeyInfo {
+    pub key_type: String,
+    pub fingerprint: String,
+    pub comment: String,
+}
+
+/// 解析一行 OpenSSH 公钥："<类型> <base64> [注释]"；base64 无效时返回 None
+pub fn parse_public_key(line: &str) -> Option<PublicKeyInfo> {
+    let mut parts = line.trim().splitn(3, char::is_whitespace);
+    let key_type = parts.next().filter(|key_type| !key_type.is_empty())?;
+    let blob = STANDARD.decode(parts.next()?.trim()).ok()?;
+    let comment = parts.next().unwrap_or("").trim();
+    Some(PublicKeyInfo {
+        key_type: key_type.to_string(),
+        fingerprint: format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(&blob))),
+        comment: comment.to_string(),
+    })
+}
+
+/// 私钥对应的公钥文件：与 ssh-keygen 一样在路径后加 .pub
+pub fn public_key_path(identity_file: &Path) -> PathBuf {
+    let mut path = identity_file.as_os_str().to_owned();
+    path.push(".pub");
+    PathBuf::from(path)
+}
+
+/// 读取公钥文件中第一个有效的密钥行
+pub fn read_public_key(path: &Path) -> Option<PublicKeyInfo> {
+    let content = fs::read_to_string(path).ok()?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .find_map(parse_public_key)
+}
+
+/// 报告中的一把密钥及使用它的主机；找不到 .pub 时只有路径
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyReportEntry {
+    pub identity_files: Vec<String>,  // 配置中引用这把密钥的写法（同一公钥的多个副本也合并在这里）
+    pub public_key: String,
+    pub key_type: Option<String>,
+    pub fingerprint: Option<String>,
+    pub comment: Option<String>,
+    pub missing_pub: bool,
+    pub hosts: Vec<String>,
+}
+
+/// 按密钥汇总主机引用的所有 IdentityFile：能读到公钥的按指纹合并，读不到的按路径合并
+pub fn build_key_report(hosts: &[SshHost], home: Option<&Path>) -> Vec<KeyReportEntry> {
+    let mut entries: Vec<KeyReportEntry> = Vec::new();
+
+    for host in hosts.iter().filter(|host| !host.is_verbatim() && !host.temporary) {
+        for identity_file in identity_files(host) {
+            let public_key = public_key_path(Path::new(&normalize_key_path(&identity_file, home)));
+            let info = read_public_key(&public_key);
+            let public_key = public_key.display().to_string();
+            let existing = entries.iter_mut().find(|entry| match &info {
+                Some(info) => entry.fingerprint.as_ref() == Some(&info.fingerprint),
+                None => entry.missing_pub && entry.public_key == public_key,
+            });
+            let entry = match existing {
+                Some(entry) => entry,
+                None => {
+                    entries.push(KeyReportEntry {
+                        identity_files: Vec::new(),
+                        public_key,
+                        key_type: info.as_ref().map(|info| info.key_type.clone()),
+                        fingerprint: info.as_ref().map(|info| info.fingerprint.clone()),
+                        comment: info.as_ref().map(|info| info.comment.clone()).filter(|comment| !comment.is_empty()),
+                        missing_pub: info.is_none(),
+                        hosts: Vec::new(),
+                    });
+                    entries.last_mut().unwrap()
+                }
+            };
+            if !entry.identity_files.contains(&identity_file) {
+                entry.identity_files.push(identity_file);
+            }
+            if !entry.hosts.contains(&host.name) {
+                entry.hosts.push(host.name.clone());
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| a.identity_files.cmp(&b.identity_files));
+    entries
+}
+
+/// 屏幕上显示的表格：每把密钥一行，下面缩进列出主机
+pub fn key_report_table(entries: &[KeyReportEntry]) -> Vec<String> {
+    if entries.is_empty() {
+        return vec!["No host references an IdentityFile".to_string()];
+    }
+    let mut lines = Vec::new();
+    for entry in entries {
+        let key = entry.identity_files.join(", ");
+        match (&entry.key_type, &entry.fingerprint) {
+            (Some(key_type), Some(fingerprint)) => {
+                let comment = entry.comment.as_deref().map(|comment| format!("  {}", comment)).unwrap_or_default();
+                lines.push(format!("{}  {}  {}{}", key, key_type, fingerprint, comment));
+            }
+            _ => lines.push(format!("{}  MISSING {}", key, entry.public_key)),
+        }
+        lines.push(format!("    {} hosts: {}", entry.hosts.len(), entry.hosts.join(", ")));
+    }
+    let missing = entries.iter().filter(|entry| entry.missing_pub).count();
+    if missing > 0 {
+        lines.push(String::new());
+        lines.push(format!("{} referenced keys have no .pub file", missing));
+    }
+    lines
+}
+
+pub fn key_report_json(entries: &[KeyReportEntry]) -> Result<String> {
+    serde_json::to_string_pretty(entries).context("Unable to write the key report as JSON")
+}
+
+/// CSV 中多个路径和主机以 ", " 连接在同一列中
+pub fn key_report_csv(entries: &[KeyReportEntry]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["identity files", "public key", "type", "fingerprint", "comment", "missing pub", "hosts"])?;
+    for entry in entries {
+        writer.write_record([
+            entry.identity_files.join(", ").as_str(),
+            entry.public_key.as_str(),
+            entry.key_type.as_deref().unwrap_or(""),
+            entry.fingerprint.as_deref().unwrap_or(""),
+            entry.comment.as_deref().unwrap_or(""),
+            if entry.missing_pub { "yes" } else { "no" },
+            entry.hosts.join(", ").as_str(),
+        ])?;
+    }
+    let bytes = writer.into_inner().context("Unable to finish CSV output")?;
+    String::from_utf8(bytes).context("CSV output is not valid UTF-8")
+}
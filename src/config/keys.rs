@@ -0,0 +1,189 @@
+use std::fs;
+use std::io::{ BufRead, BufReader, Read };
+use std::path::Path;
+
+use crate::config::SshHost;
+
+/// ~/.ssh 中不是私钥的常见文件
+const NON_KEY_FILES: [&str; 6] = ["config", "known_hosts", "known_hosts.old", "authorized_keys", "authorized_keys2", "environment"];
+
+/// 按第一行判断的密钥文件类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyFileKind {
+    /// OpenSSH 或 PEM 私钥
+    Private,
+    /// OpenSSH 单行公钥（含证书）、RFC 4716 或 PEM 公钥
+    Public,
+    Other,
+}
+
+/// 公钥文件第一行可能的开头
+const PUBLIC_KEY_PREFIXES: [&str; 6] = [
+    "ssh-",
+    "ecdsa-sha2-",
+    "sk-ssh-",
+    "sk-ecdsa-sha2-",
+    "---- BEGIN SSH2 PUBLIC KEY ----",
+    "-----BEGIN PUBLIC KEY-----",
+];
+
+/// 由文件的第一行判断密钥类型
+pub fn sniff_key_line(first_line: &str) -> KeyFileKind {
+    let line = first_line.trim();
+    if line.starts_with("-----BEGIN") && line.contains("PRIVATE KEY") {
+        KeyFileKind::Private
+    } else if PUBLIC_KEY_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+        KeyFileKind::Public
+    } else {
+        KeyFileKind::Other
+    }
+}
+
+/// 只读取第一行（最多 4 KiB）判断密钥类型，不读取也不返回密钥内容；无法读取时返回 None
+pub fn sniff_key_file(path: &Path) -> Option<KeyFileKind> {
+    let file = fs::File::open(path).ok()?;
+    let mut first_line = Vec::new();
+    BufReader::new(file.take(4096)).read_until(b'\n', &mut first_line).ok()?;
+    Some(sniff_key_line(&String::from_utf8_lossy(&first_line)))
+}
+
+pub fn looks_like_private_key(path: &Path) -> bool {
+    sniff_key_file(path) == Some(KeyFileKind::Private)
+}
+
+/// IdentityFile 指向的文件看起来不是私钥时的提示；不存在、无法读取或是私钥时返回 None。
+/// 只说明类型，不包含文件内容
+pub fn identity_file_warning(identity_file: &str, path: &Path) -> Option<String> {
+    match sniff_key_file(path)? {
+        KeyFileKind::Private => None,
+        KeyFileKind::Public => Some(match identity_file.strip_suffix(".pub") {
+            Some(private) => format!(
+                "this looks like a public key — did you mean {} without .pub? (fine if the private key is in an agent)",
+                private.rsplit(['/', '\\']).next().unwrap_or(private)
+            ),
+            None => "this looks like a public key, not a private key (fine if the private key is in an agent)".to_string(),
+        }),
+        KeyFileKind::Other => Some("this does not look like a private key (no OpenSSH or PEM private key header)".to_string()),
+    }
+}
+
+/// 列出 ssh 目录中的私钥，返回 "~/.ssh/<name>" 形式的路径并排序
+pub fn list_private_keys(ssh_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(ssh_dir) else {
+        return Vec::new();
+    };
+
+    let mut keys: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let skip = name.ends_with(".pub") || name.starts_with('.') || NON_KEY_FILES.contains(&name.as_str());
+            (!skip && looks_like_private_key(&entry.path())).then(|| format!("~/.ssh/{}", name))
+        })
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// 私钥文件权限问题：mode 为当前权限位，foreign_owner 表示文件不属于当前用户
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPermissionIssue {
+    pub mode: u32,
+    pub foreign_owner: bool,
+}
+
+/// 组或其他用户可访问私钥时返回问题；文件不存在或非 Unix 平台返回 None
+#[cfg(unix)]
+pub fn check_key_permissions(path: &Path) -> Option<KeyPermissionIssue> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path).ok()?;
+    let mode = metadata.mode() & 0o777;
+    if mode & 0o077 == 0 {
+        return None;
+    }
+    // SAFETY: geteuid 没有前置条件，总是成功
+    let uid = unsafe { libc::geteuid() };
+    Some(KeyPermissionIssue { mode, foreign_owner: metadata.uid() != uid })
+}
+
+#[cfg(not(unix))]
+pub fn check_key_permissions(_path: &Path) -> Option<KeyPermissionIssue> {
+    None
+}
+
+/// 去掉组和其他用户的全部权限，并最多保留所有者的读写（即不超过 0600），返回新的权限位。
+/// 只会收紧权限；不属于当前用户的文件不处理
+#[cfg(unix)]
+pub fn tighten_key_permissions(path: &Path) -> Result<u32, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Some(issue) = check_key_permissions(path) else {
+        return Err("permissions are already private".to_string());
+    };
+    if issue.foreign_owner {
+        return Err("owned by another user; skipped".to_string());
+    }
+    let new_mode = issue.mode & 0o600;
+    fs::set_permissions(path, fs::Permissions::from_mode(new_mode)).map_err(|e| e.to_string())?;
+    Ok(new_mode)
+}
+
+#[cfg(not(unix))]
+pub fn tighten_key_permissions(_path: &Path) -> Result<u32, String> {
+    Err("not supported on this platform".to_string())
+}
+
+/// 比较 IdentityFile 路径用的形式：去掉引号、末尾的空白和 /，~ 按 home 展开
+pub fn normalize_key_path(path: &str, home: Option<&Path>) -> String {
+    let path = path.trim().trim_matches('"').trim_end();
+    let path = match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home.display(), rest),
+        _ => path.to_string(),
+    };
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() { path } else { trimmed.to_string() }
+}
+
+/// 主机的所有 IdentityFile，按 ssh 尝试的顺序（第一个在表单字段中，其余在其他选项中）
+pub fn identity_files(host: &SshHost) -> Vec<String> {
+    host.identity_file.iter().chain(host.other_options.get_all("identityfile")).cloned().collect()
+}
+
+/// 把指向 old 的 IdentityFile 换成 new；append 时保留 old 并把 new 追加为最后一个。
+/// 主机没有引用 old 或结果不变时返回 None
+pub fn rotate_identity(host: &SshHost, old: &str, new: &str, append: bool, home: Option<&Path>) -> Option<SshHost> {
+    let (old, new) = (old.trim(), new.trim());
+    if old.is_empty() || new.is_empty() {
+        return None;
+    }
+    let normalized_old = normalize_key_path(old, home);
+    let normalized_new = normalize_key_path(new, home);
+    let files = identity_files(host);
+    if !files.iter().any(|file| normalize_key_path(file, home) == normalized_old) {
+        return None;
+    }
+
+    let mut rotated: Vec<String> = Vec::new();
+    for file in files {
+        let normalized = normalize_key_path(&file, home);
+        let file = if !append && normalized == normalized_old { new.to_string() } else { file };
+        // 替换后可能与已有的 new 重复
+        if !rotated.iter().any(|existing| normalize_key_path(existing, home) == normalize_key_path(&file, home)) {
+            rotated.push(file);
+        }
+    }
+    if append && !rotated.iter().any(|file| normalize_key_path(file, home) == normalized_new) {
+        rotated.push(new.to_string());
+    }
+
+    let mut updated = host.clone();
+    let mut rotated = rotated.into_iter();
+    updated.identity_file = rotated.next();
+    updated.other_options.remove("identityfile");
+    for file in rotated {
+        updated.other_options.push("identityfile".to_string(), file);
+    }
+    (updated != *host).then_some(updated)
+}
@@ -0,0 +1,254 @@
+use std::path::{ Path, PathBuf };
+
+use crate::config::{ check_key_permissions, identity_file_warning, identity_files, normalize_key_path, row_style_problems, SshHost };
+use crate::utils::{ resolve_port, split_host_port };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+/// 一条检查结果，host 为主机别名
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub host: String,
+    pub rule: &'static str,
+    pub severity: LintSeverity,
+    pub message: String,
+    pub path: Option<PathBuf>,  // 与文件相关的检查指向的文件
+}
+
+/// 拆分 ProxyJump 的各跳，返回每跳的主机部分（去掉 ssh://、user@ 和 :port）
+pub fn jump_targets(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(|hop| jump_host_part(hop.trim()).1)
+        .filter(|target| !target.is_empty() && !target.eq_ignore_ascii_case("none"))
+        .collect()
+}
+
+/// 把一跳拆成 (前缀, 主机, 后缀)，改写时保留用户名和端口
+fn jump_host_part(hop: &str) -> (&str, &str, &str) {
+    let scheme_end = if hop.starts_with("ssh://") { 6 } else { 0 };
+    let host_start = hop[scheme_end..].rfind('@').map(|i| scheme_end + i + 1).unwrap_or(scheme_end);
+    let (host, _) = split_host_port(&hop[host_start..]);
+    // [v6]:port 的方括号归入前后缀，主机部分只保留地址
+    let host_start = if hop[host_start..].starts_with('[') { host_start + 1 } else { host_start };
+    let host_end = host_start + host.len();
+    (&hop[..host_start], &hop[host_start..host_end], &hop[host_end..])
+}
+
+/// ProxyCommand 中按空白分词后等于 name（或 user@name）的词
+fn command_references(command: &str, name: &str) -> bool {
+    command
+        .split_whitespace()
+        .any(|word| word == name || word.rsplit_once('@').is_some_and(|(_, host)| host == name))
+}
+
+/// 主机是否通过 ProxyJump 或 ProxyCommand 引用了 name
+pub fn references_host(host: &SshHost, name: &str) -> bool {
+    host.name != name &&
+        (host.other_options.get("proxyjump").is_some_and(|value| jump_targets(value).contains(&name)) ||
+            host.other_options.get("proxycommand").is_some_and(|command| command_references(command, name)))
+}
+
+/// 引用了 name 的其他主机下标
+pub fn find_dependents(hosts: &[SshHost], name: &str) -> Vec<usize> {
+    hosts
+        .iter()
+        .enumerate()
+        .filter(|(_, host)| references_host(host, name))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// 把对 old 的引用改为 new；new 为 None 时移除该跳，ProxyCommand 无法安全改写时整条移除
+pub fn retarget_references(host: &SshHost, old: &str, new: Option<&str>) -> SshHost {
+    let mut updated = host.clone();
+
+    if let Some(value) = host.other_options.get("proxyjump") {
+        let hops: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter_map(|hop| {
+                let (prefix, target, suffix) = jump_host_part(hop);
+                match (target == old, new) {
+                    (false, _) => Some(hop.to_string()),
+                    (true, Some(new)) => Some(format!("{}{}{}", prefix, new, suffix)),
+                    (true, None) => None,
+                }
+            })
+            .collect();
+        if hops.is_empty() {
+            updated.other_options.remove("proxyjump");
+        } else {
+            updated.other_options.insert("proxyjump".to_string(), hops.join(","));
+        }
+    }
+
+    if let Some(command) = host.other_options.get("proxycommand") && command_references(command, old) {
+        match new {
+            Some(new) => {
+                let words: Vec<String> = command
+                    .split_whitespace()
+                    .map(|word| match word.rsplit_once('@') {
+                        _ if word == old => new.to_string(),
+                        Some((user, target)) if target == old => format!("{}@{}", user, new),
+                        _ => word.to_string(),
+                    })
+                    .collect();
+                updated.other_options.insert("proxycommand".to_string(), words.join(" "));
+            }
+            None => {
+                updated.other_options.remove("proxycommand");
+            }
+        }
+    }
+
+    updated
+}
+
+/// 看起来是别名的跳板：不是 IP、不含点号的主机名才按别名检查，避免误报直接写的域名
+fn looks_like_alias(target: &str) -> bool {
+    !target.contains('.') && !target.contains(':') && !target.starts_with('[')
+}
+
+/// 对全部主机运行检查规则，按主机名排序
+pub fn lint_hosts(hosts: &[SshHost]) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+
+    for host in hosts {
+        // Match 块原样保留，只提示 sshc 不会按它计算主机的设置
+        if host.is_match_block() {
+            findings.push(LintFinding {
+                host: format!("Match {}", host.name),
+                rule: "match-block",
+                severity: LintSeverity::Warning,
+                message: "kept as written; sshc does not evaluate Match rules, so the hosts it applies to may get other settings than shown".to_string(),
+                path: None,
+            });
+            continue;
+        }
+        // 停用的主机已被注释掉，ssh 不会读取
+        if host.is_prelude() || host.disabled {
+            continue;
+        }
+
+        for problem in row_style_problems(host) {
+            findings.push(LintFinding {
+                host: host.name.clone(),
+                rule: "unknown-row-style",
+                severity: LintSeverity::Warning,
+                message: problem,
+                path: None,
+            });
+        }
+
+        for entry in &host.invalid_env {
+            findings.push(LintFinding {
+                host: host.name.clone(),
+                rule: "malformed-env",
+                severity: LintSeverity::Warning,
+                message: format!("'# @env: {}' is not KEY=value and is not applied", entry),
+                path: None,
+            });
+        }
+
+        // 非数字端口：能按服务名解析的提示一下，解析不了的 ssh 会拒绝
+        if let Some(port) = host.port.as_deref().filter(|port| port.parse::<u16>().is_err()) {
+            let (severity, message) = match resolve_port(port) {
+                Some(number) => (LintSeverity::Warning, format!("Port '{}' is a service name (resolves to {} on this machine)", port, number)),
+                None => (LintSeverity::Error, format!("Port '{}' is neither a number nor a known service; ssh will refuse it", port)),
+            };
+            findings.push(LintFinding {
+                host: host.name.clone(),
+                rule: "non-numeric-port",
+                severity,
+                message,
+                path: None,
+            });
+        }
+
+        if host.host_key_checking_disabled() {
+            let known_hosts = host.other_options
+                .get("userknownhostsfile")
+                .map(|file| format!(", known hosts go to {}", file))
+                .unwrap_or_default();
+            findings.push(LintFinding {
+                host: host.name.clone(),
+                rule: "host-key-checking-disabled",
+                severity: LintSeverity::Warning,
+                message: format!("StrictHostKeyChecking is off{}; changed host keys are accepted silently", known_hosts),
+                path: None,
+            });
+        }
+
+        if let Some(value) = host.other_options.get("proxyjump") {
+            for target in jump_targets(value) {
+                if looks_like_alias(target) && !hosts.iter().any(|h| h.name == target && !h.disabled) {
+                    let reason = if hosts.iter().any(|h| h.name == target) { "is disabled" } else { "is not a Host in this config" };
+                    findings.push(LintFinding {
+                        host: host.name.clone(),
+                        rule: "missing-jump-host",
+                        severity: LintSeverity::Error,
+                        message: format!("ProxyJump references '{}', which {}", target, reason),
+                        path: None,
+                    });
+                }
+            }
+        }
+    }
+
+    findings.sort_by(|a, b| a.host.cmp(&b.host));
+    findings
+}
+
+/// 检查各主机引用的私钥文件是否存在及其权限（会访问文件系统）
+pub fn lint_key_files(hosts: &[SshHost], home: Option<&Path>) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+
+    for host in hosts {
+        // 含 %d、${HOME} 等展开符号的路径无法在这里判断是否存在
+        for identity_file in identity_files(host).iter().filter(|file| !file.contains(['%', '$'])) {
+            let path = PathBuf::from(normalize_key_path(identity_file, home));
+            if !path.exists() {
+                findings.push(LintFinding {
+                    host: host.name.clone(),
+                    rule: "missing-identity-file",
+                    severity: LintSeverity::Warning,
+                    message: format!("IdentityFile {} does not exist", identity_file),
+                    path: Some(path),
+                });
+            } else if let Some(warning) = identity_file_warning(identity_file, &path) {
+                findings.push(LintFinding {
+                    host: host.name.clone(),
+                    rule: "identity-file-not-private-key",
+                    severity: LintSeverity::Warning,
+                    message: format!("IdentityFile {}: {}", identity_file, warning),
+                    path: Some(path),
+                });
+            }
+        }
+
+        let Some(identity_file) = &host.identity_file else {
+            continue;
+        };
+        let path = match (identity_file.strip_prefix("~/"), home) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(identity_file),
+        };
+        if let Some(issue) = check_key_permissions(&path) {
+            let note = if issue.foreign_owner { "; owned by another user, fix manually" } else { "" };
+            findings.push(LintFinding {
+                host: host.name.clone(),
+                rule: "insecure-key-permissions",
+                severity: LintSeverity::Error,
+                message: format!("{} is accessible by other users ({:04o}){}", identity_file, issue.mode, note),
+                path: Some(path),
+            });
+        }
+    }
+
+    findings
+}
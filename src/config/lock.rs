@@ -0,0 +1,95 @@
+use anyhow::{ bail, Context, Result };
+use std::fs::{ self, OpenOptions };
+use std::io::{ ErrorKind, Write };
+use std::path::{ Path, PathBuf };
+
+/// 配置锁文件位于 SSH 配置旁边，例如 ~/.ssh/.sshc.lock
+pub fn lock_path(config_path: &Path) -> PathBuf {
+    config_path.parent().unwrap_or(Path::new("")).join(".sshc.lock")
+}
+
+/// 建议性锁：文件内容为持有者的 PID。只约束 sshc 实例之间，编辑器等其他程序不受影响。
+/// 丢弃时如果文件仍属于本进程就删除
+#[derive(Debug)]
+pub struct ConfigLock {
+    pub path: PathBuf,
+    pub pid: u32,
+}
+
+/// 获取锁的结果
+#[derive(Debug)]
+pub enum LockStatus {
+    Acquired(ConfigLock),
+    /// 另一个仍在运行的实例持有锁
+    HeldBy(u32),
+}
+
+/// 锁文件中记录的 PID；文件不存在或内容无法解析时返回 None
+pub fn lock_holder(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: 信号 0 只检查进程是否存在，不会发送信号
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    // 属于其他用户的进程同样存在
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// 无法检查时当作仍在运行，宁可多问一次也不抢走别人的锁
+#[cfg(not(unix))]
+pub fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+impl ConfigLock {
+    /// 创建锁文件；已被本进程持有时直接成功，持有者已退出（或文件损坏）时回收
+    pub fn acquire(path: &Path) -> Result<LockStatus> {
+        let pid = std::process::id();
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", pid).with_context(|| format!("Unable to write lock file: {:?}", path))?;
+                    return Ok(LockStatus::Acquired(ConfigLock { path: path.to_path_buf(), pid }));
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e).with_context(|| format!("Unable to create lock file: {:?}", path)),
+            }
+            match lock_holder(path) {
+                Some(holder) if holder == pid => return Ok(LockStatus::Acquired(ConfigLock { path: path.to_path_buf(), pid })),
+                Some(holder) if process_alive(holder) => return Ok(LockStatus::HeldBy(holder)),
+                _ => {
+                    // 失效的锁：删除后重试一次，期间被别人抢先创建时按对方持有处理
+                    let _ = fs::remove_file(path);
+                }
+            }
+        }
+        bail!("Unable to take over the stale lock file {:?}", path)
+    }
+
+    /// 无视当前持有者，把锁改写为本进程
+    pub fn steal(path: &Path) -> Result<ConfigLock> {
+        let pid = std::process::id();
+        fs::write(path, format!("{}\n", pid)).with_context(|| format!("Unable to write lock file: {:?}", path))?;
+        Ok(ConfigLock { path: path.to_path_buf(), pid })
+    }
+
+    /// 锁文件是否仍记录着本进程（可能已被其他实例抢走）
+    pub fn is_held(&self) -> bool {
+        lock_holder(&self.path) == Some(self.pid)
+    }
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        if self.is_held() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
@@ -1,3 +1,47 @@
+pub mod backup;
+pub mod compare;
+pub mod effective;
+pub mod export;
+pub mod forward;
+pub mod history;
+pub mod include;
+pub mod inventory;
+pub mod key_report;
+pub mod keys;
+pub mod lint;
+pub mod lock;
+pub mod options;
+pub mod paths;
+pub mod pattern;
+pub mod print;
+pub mod row_style;
+pub mod settings;
+pub mod spreadsheet;
 pub mod ssh_config;
+pub mod theme;
+pub mod tokens;
+pub mod transfer;
 
-pub use ssh_config::*;
\ No newline at end of file
+pub use backup::*;
+pub use compare::*;
+pub use effective::*;
+pub use export::*;
+pub use forward::*;
+pub use history::*;
+pub use include::*;
+pub use inventory::*;
+pub use key_report::*;
+pub use keys::*;
+pub use lint::*;
+pub use lock::*;
+pub use options::*;
+pub use paths::*;
+pub use pattern::*;
+pub use print::*;
+pub use row_style::*;
+pub use settings::*;
+pub use spreadsheet::*;
+pub use ssh_config::*;
+pub use theme::*;
+pub use tokens::*;
+pub use transfer::*;
\ No newline at end of file
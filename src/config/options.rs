@@ -0,0 +1,136 @@
+use serde::ser::{ Serialize, SerializeMap, Serializer };
+use std::ops::Index;
+
+/// 主机的其他 ssh 选项，按解析顺序保存（键名小写）。
+/// 同一个键可以出现多次（LocalForward、多个 IdentityFile 等），读取时与 ssh 一样以第一个为准
+#[derive(Debug, Clone, Default)]
+pub struct OptionMap {
+    entries: Vec<(String, String)>,
+}
+
+impl OptionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// 某个键的所有值，按出现顺序
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        self.entries.iter().filter(move |(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    /// 设置键的值：已存在时原位替换第一个并去掉重复项，保持位置不变；否则追加到末尾
+    pub fn insert(&mut self, key: String, value: String) -> Option<String> {
+        match self.entries.iter().position(|(k, _)| *k == key) {
+            Some(index) => {
+                let old = std::mem::replace(&mut self.entries[index].1, value);
+                let mut seen = 0;
+                self.entries.retain(|(k, _)| {
+                    if *k != key {
+                        return true;
+                    }
+                    seen += 1;
+                    seen == 1
+                });
+                Some(old)
+            }
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// 追加一项，不替换已有的同名项（解析可重复的选项时使用）
+    pub fn push(&mut self, key: String, value: String) {
+        self.entries.push((key, value));
+    }
+
+    /// 删除键的所有值，返回第一个
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let first = self.get(key).cloned();
+        self.entries.retain(|(k, _)| k != key);
+        first
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// 不重复的键名，按首次出现顺序
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(i, (k, _))| !self.entries[..*i].iter().any(|(earlier, _)| earlier == k))
+            .map(|(_, (k, _))| k)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// 同一键的值顺序有意义，不同键之间的顺序不影响相等性
+impl PartialEq for OptionMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries.len() == other.entries.len()
+            && self.keys().all(|key| self.get_all(key).eq(other.get_all(key)))
+    }
+}
+
+/// 序列化为键到值列表的对象，键按首次出现顺序，同一键的多个值按原顺序
+impl Serialize for OptionMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let keys: Vec<&String> = self.keys().collect();
+        let mut map = serializer.serialize_map(Some(keys.len()))?;
+        for key in keys {
+            map.serialize_entry(key, &self.get_all(key).collect::<Vec<&String>>())?;
+        }
+        map.end()
+    }
+}
+
+impl Index<&str> for OptionMap {
+    type Output = String;
+
+    fn index(&self, key: &str) -> &String {
+        self.get(key).unwrap_or_else(|| panic!("no option '{}'", key))
+    }
+}
+
+impl<'a> IntoIterator for &'a OptionMap {
+    type Item = (&'a String, &'a String);
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (String, String)>, fn(&'a (String, String)) -> (&'a String, &'a String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl Extend<(String, String)> for OptionMap {
+    fn extend<T: IntoIterator<Item = (String, String)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl FromIterator<(String, String)> for OptionMap {
+    fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::{ Context, Result };
+use std::ffi::OsString;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::OnceLock;
+
+use crate::utils::SshcError;
+
+/// --home 命令行参数指定的 home 目录，优先级最高
+static HOME_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn set_home_override(path: PathBuf) {
+    let _ = HOME_OVERRIDE.set(path);
+}
+
+/// 明确指定的 home 目录及其来源：--home 优先于 $SSHC_HOME
+fn home_override() -> Option<(&'static str, PathBuf)> {
+    if let Some(path) = HOME_OVERRIDE.get() {
+        return Some(("--home", path.clone()));
+    }
+    std::env::var_os("SSHC_HOME")
+        .filter(|v| !v.is_empty())
+        .map(|value| ("SSHC_HOME", PathBuf::from(value)))
+}
+
+/// 按优先级列出 home 目录候选：明确指定时只有 --home 或 $SSHC_HOME，
+/// 否则依次为系统查询结果、$HOME、%USERPROFILE%
+pub fn home_candidates() -> Vec<PathBuf> {
+    if let Some((_, path)) = home_override() {
+        return vec![path];
+    }
+    let mut candidates = Vec::new();
+    if let Some(path) = home::home_dir() {
+        candidates.push(path);
+    }
+    for var in ["HOME", "USERPROFILE"] {
+        if let Some(value) = std::env::var_os(var).filter(|v| !v.is_empty()) {
+            candidates.push(PathBuf::from(value));
+        }
+    }
+    candidates.dedup();
+    candidates
+}
+
+/// 解析 home 目录：明确指定的目录不存在时报错，不退回到真实的 home；
+/// 否则取第一个存在的候选，都不存在时返回尝试过的路径
+pub fn resolve_home() -> Result<PathBuf, SshcError> {
+    if let Some((source, path)) = home_override() {
+        return if path.is_dir() { Ok(path) } else { Err(SshcError::HomeNotDirectory { source, path }) };
+    }
+    let attempted = home_candidates();
+    attempted
+        .iter()
+        .find(|path| path.is_dir())
+        .cloned()
+        .ok_or(SshcError::ConfigNotFound { attempted })
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    resolve_home().ok()
+}
+
+/// sshc 自己的文件所在的目录类别
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SshcDir {
+    /// 设置（settings.toml）
+    Config,
+    /// 可以重新生成的状态：连接历史、传输路径、日志
+    State,
+    /// 需要保留的数据，例如回收站和附加元数据
+    Data,
+}
+
+impl SshcDir {
+    pub const ALL: [SshcDir; 3] = [SshcDir::Config, SshcDir::State, SshcDir::Data];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Config => "Config",
+            Self::State => "State",
+            Self::Data => "Data",
+        }
+    }
+
+    fn xdg_var(&self) -> &'static str {
+        match self {
+            Self::Config => "XDG_CONFIG_HOME",
+            Self::State => "XDG_STATE_HOME",
+            Self::Data => "XDG_DATA_HOME",
+        }
+    }
+
+    /// 没有设置 XDG 变量时相对 home 的默认位置
+    fn xdg_fallback(&self) -> &'static [&'static str] {
+        match self {
+            Self::Config => &[".config"],
+            Self::State => &[".local", "state"],
+            Self::Data => &[".local", "share"],
+        }
+    }
+}
+
+/// 按 XDG 规范解析 sshc 的目录，var 用于读取环境变量（测试中可替换）。
+/// 顺序：非空的绝对路径 $XDG_*_HOME；macOS 和 Windows 上若旧的 ~/.config 风格目录不存在则用平台目录；
+/// 否则 ~/.config、~/.local/state、~/.local/share
+pub fn sshc_dir_from(kind: SshcDir, home: &Path, var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    if let Some(base) = var(kind.xdg_var()).map(PathBuf::from).filter(|path| path.is_absolute()) {
+        return base.join("sshc");
+    }
+    let fallback = kind.xdg_fallback().iter().fold(home.to_path_buf(), |path, part| path.join(part)).join("sshc");
+    if fallback.is_dir() {
+        return fallback;
+    }
+    platform_dir(kind, home, &var).unwrap_or(fallback)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_dir(kind: SshcDir, home: &Path, _var: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let support = home.join("Library").join("Application Support").join("sshc");
+    Some(if kind == SshcDir::State { support.join("state") } else { support })
+}
+
+#[cfg(windows)]
+fn platform_dir(kind: SshcDir, home: &Path, var: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    // 设置随漫游配置同步，状态和数据留在本机
+    let (name, default) = match kind {
+        SshcDir::Config => ("APPDATA", ["AppData", "Roaming"]),
+        SshcDir::State | SshcDir::Data => ("LOCALAPPDATA", ["AppData", "Local"]),
+    };
+    let base = var(name).map(PathBuf::from).unwrap_or_else(|| home.join(default[0]).join(default[1]));
+    Some(if kind == SshcDir::State { base.join("sshc").join("state") } else { base.join("sshc") })
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn platform_dir(_kind: SshcDir, _home: &Path, _var: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    None
+}
+
+/// 当前环境下 sshc 的目录；home 目录找不到且没有设置 XDG 变量时返回 None
+pub fn sshc_dir(kind: SshcDir) -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    match home_dir() {
+        Some(home) => Some(sshc_dir_from(kind, &home, var)),
+        None => var(kind.xdg_var()).map(PathBuf::from).filter(|path| path.is_absolute()).map(|base| base.join("sshc")),
+    }
+}
+
+/// sshc 目录下的文件路径
+pub fn sshc_file(kind: SshcDir, name: &str) -> Option<PathBuf> {
+    sshc_dir(kind).map(|dir| dir.join(name))
+}
+
+/// 写入前创建文件所在目录；新建的目录在 Unix 上只有本人可访问（0700）
+pub fn create_parent_dir(path: &Path) -> Result<()> {
+    let Some(dir) = path.parent() else {
+        return Ok(());
+    };
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir).with_context(|| format!("Unable to create {:?}", dir))
+}
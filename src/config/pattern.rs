@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+
+use crate::config::{ option_display_name, SshHost };
+
+/// 可以出现多次且每次都生效的选项；其余选项与 ssh 一样以第一个值为准
+const CUMULATIVE_OPTIONS: [&str; 6] = ["identityfile", "certificatefile", "localforward", "remoteforward", "dynamicforward", "sendenv"];
+
+/// * 匹配任意个字符，? 匹配一个字符，其余逐字比较
+pub fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| wildcard_match(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && wildcard_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && wildcard_match(rest, &text[1..]),
+    }
+}
+
+/// 单个 Host 模式是否匹配目标，与 ssh 一样不区分大小写
+pub fn host_pattern_matches(pattern: &str, destination: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let destination: Vec<char> = destination.to_lowercase().chars().collect();
+    wildcard_match(&pattern, &destination)
+}
+
+/// Host 行的模式列表，按空白分隔，双引号内的空白不分隔
+pub fn split_host_patterns(patterns: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in patterns.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    result.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+/// 按 ssh 的规则判断 Host 行是否适用：任一否定模式（!pattern）匹配则不适用，否则任一普通模式匹配即适用
+pub fn host_line_matches(patterns: &str, destination: &str) -> bool {
+    let mut matched = false;
+    for pattern in split_host_patterns(patterns) {
+        match pattern.strip_prefix('!') {
+            Some(negated) if host_pattern_matches(negated, destination) => return false,
+            Some(_) => {}
+            None => matched |= host_pattern_matches(&pattern, destination),
+        }
+    }
+    matched
+}
+
+/// 适用块中的一个选项；applies 为 false 表示已被前面的块设置，这里的值不生效
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedOption {
+    pub key: String,
+    pub value: String,
+    pub applies: bool,
+}
+
+/// 对某个目标适用的一个块
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchingBlock {
+    /// Host 行的模式；全局选项为 None
+    pub patterns: Option<String>,
+    pub options: Vec<MatchedOption>,
+}
+
+/// 目标在配置中会用到的块
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DestinationMatch {
+    pub blocks: Vec<MatchingBlock>,
+    /// 没有求值的 Match 块数
+    pub skipped_match_blocks: usize,
+}
+
+/// 全局部分的 "键 值" 行，Include 和注释除外
+fn prelude_options(prelude: &str) -> Vec<(String, String)> {
+    prelude
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once(|c: char| c.is_whitespace() || c == '=')?;
+            let key = key.to_lowercase();
+            (key != "include").then(|| (key, value.trim_start_matches(|c: char| c.is_whitespace() || c == '=').trim().to_string()))
+        })
+        .collect()
+}
+
+fn host_options(host: &SshHost) -> Vec<(String, String)> {
+    let fields = [("hostname", &host.hostname), ("user", &host.user), ("port", &host.port), ("identityfile", &host.identity_file)];
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| (key.to_string(), value.clone())))
+        .chain(host.other_options.iter().map(|(key, value)| (key.clone(), value.clone())))
+        .collect()
+}
+
+/// 按文件顺序列出对 destination 适用的块，并标出每个选项由哪个块决定。
+/// 停用的块和临时主机不参与；Match 块的条件无法离线求值，只计数
+pub fn match_destination(hosts: &[SshHost], destination: &str) -> DestinationMatch {
+    let mut result = DestinationMatch::default();
+    let mut seen: HashSet<String> = HashSet::new();
+    for host in hosts.iter().filter(|host| !host.disabled && !host.temporary) {
+        let (patterns, options) = if host.is_match_block() {
+            result.skipped_match_blocks += 1;
+            continue;
+        } else if let Some(prelude) = &host.prelude {
+            (None, prelude_options(prelude))
+        } else if host_line_matches(&host.name, destination) {
+            (Some(host.name.clone()), host_options(host))
+        } else {
+            continue;
+        };
+        if options.is_empty() && patterns.is_none() {
+            continue;
+        }
+        let options = options
+            .into_iter()
+            .map(|(key, value)| {
+                let applies = CUMULATIVE_OPTIONS.contains(&key.as_str()) || seen.insert(key.clone());
+                MatchedOption { key: option_display_name(&key), value, applies }
+            })
+            .collect();
+        result.blocks.push(MatchingBlock { patterns, options });
+    }
+    result
+}
+
+/// 主机自己没有写、由其他块（通配 Host 块或全局选项）提供的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct InheritedOption {
+    pub key: String,
+    pub value: String,
+    /// 提供该值的 Host 行模式；全局选项为 "global options"
+    pub from: String,
+}
+
+/// 按 ssh 的规则（文件顺序、先到先得）计算连接主机时从其他块继承的值。
+/// 只列出主机自己没有设置的选项；Match 块不参与，通配块本身不计算
+pub fn inherited_options(hosts: &[SshHost], host: &SshHost) -> Vec<InheritedOption> {
+    if host.is_verbatim() || host.name.contains(['*', '?']) {
+        return Vec::new();
+    }
+    let own: HashSet<String> = host_options(host).into_iter().map(|(key, _)| key.to_lowercase()).collect();
+    match_destination(hosts, host.connect_target())
+        .blocks
+        .into_iter()
+        .filter(|block| block.patterns.as_deref() != Some(host.name.as_str()))
+        .flat_map(|block| {
+            let from = block.patterns.unwrap_or_else(|| "global options".to_string());
+            block.options
+                .into_iter()
+                .filter(|option| option.applies)
+                .map(move |option| InheritedOption { key: option.key, value: option.value, from: from.clone() })
+        })
+        .filter(|option| !own.contains(&option.key.to_lowercase()))
+        .collect()
+}
+
+/// 继承的某个选项（键不区分大小写）
+pub fn inherited_value<'a>(inherited: &'a [InheritedOption], key: &str) -> Option<&'a InheritedOption> {
+    inherited.iter().find(|option| option.key.eq_ignore_ascii_case(key))
+}
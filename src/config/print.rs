@@ -0,0 +1,57 @@
+use anyhow::{ Context, Result };
+
+use crate::config::SshHost;
+
+/// --fields 可用的字段名，按 README 中的说明顺序
+pub const HOST_FIELDS: [&str; 13] = [
+    "name",
+    "hostname",
+    "address",
+    "user",
+    "port",
+    "identity_file",
+    "folder",
+    "display_name",
+    "description",
+    "tags",
+    "aliases",
+    "visible",
+    "source",
+];
+
+/// 选中主机的完整 JSON（单个对象、单行），供脚本组合其他命令
+pub fn host_json(host: &SshHost) -> Result<String> {
+    serde_json::to_string(host).context("Unable to write the host as JSON")
+}
+
+/// 字段的文本值：未设置为空，列表以逗号连接；未知字段返回 None
+pub fn host_field(host: &SshHost, field: &str) -> Option<String> {
+    let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+    let value = match field {
+        "name" => host.name.clone(),
+        "hostname" => optional(&host.hostname),
+        // 展开 %h 后实际连接的地址
+        "address" => host.resolved_hostname().unwrap_or_else(|| host.connect_target().to_string()),
+        "user" => optional(&host.user),
+        "port" => optional(&host.port),
+        "identity_file" => optional(&host.identity_file),
+        "folder" => optional(&host.folder),
+        "display_name" => optional(&host.display_name),
+        "description" => optional(&host.description),
+        "tags" => host.tags.join(","),
+        "aliases" => host.aliases.join(","),
+        "visible" => host.visible.to_string(),
+        "source" => host.source.as_ref().map(|path| path.display().to_string()).unwrap_or_default(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// 以制表符分隔的字段值；值中的制表符和换行替换为空格，保证输出只有一行
+pub fn host_fields_line(host: &SshHost, fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| host_field(host, field).unwrap_or_default().replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
@@ -0,0 +1,64 @@
+use ratatui::style::Color;
+
+use crate::config::SshHost;
+
+/// 表单中行样式字段可选的值：空为默认，dim 写入 # @style，其余写入 # @color
+pub const ROW_STYLE_CHOICES: [&str; 8] = ["", "dim", "red", "yellow", "green", "blue", "magenta", "cyan"];
+
+/// # @style 认识的值
+const KNOWN_STYLES: [&str; 1] = ["dim"];
+
+/// 单台主机行的样式（# @style: dim、# @color: red），与环境徽章互不影响
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RowStyle {
+    /// 已弃用的机器：暗色斜体
+    pub dim: bool,
+    pub color: Option<Color>,
+}
+
+impl RowStyle {
+    pub fn is_plain(&self) -> bool {
+        !self.dim && self.color.is_none()
+    }
+}
+
+/// 主机的行样式；无法识别的值忽略（由 lint 报告）
+pub fn row_style(host: &SshHost) -> RowStyle {
+    RowStyle {
+        dim: host.style.as_deref().is_some_and(|style| style.trim().eq_ignore_ascii_case("dim")),
+        color: host.color.as_deref().and_then(|color| color.trim().parse::<Color>().ok()),
+    }
+}
+
+/// 不会生效的 # @style / # @color 值，供 lint 报告
+pub fn row_style_problems(host: &SshHost) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(style) = &host.style
+        && !KNOWN_STYLES.iter().any(|known| known.eq_ignore_ascii_case(style.trim()))
+    {
+        problems.push(format!("'# @style: {}' is not a known style (use dim) and is ignored", style));
+    }
+    if let Some(color) = &host.color
+        && color.trim().parse::<Color>().is_err()
+    {
+        problems.push(format!("'# @color: {}' is not a color name or #rrggbb and is ignored", color));
+    }
+    problems
+}
+
+/// 表单中行样式字段的值：有 # @style 时显示它，否则显示颜色
+pub fn row_style_choice(host: &SshHost) -> String {
+    host.style.clone().or_else(|| host.color.clone()).unwrap_or_default()
+}
+
+/// 把表单中选定的值写回主机：dim 为 # @style，其他非空值为 # @color
+pub fn apply_row_style_choice(host: &mut SshHost, choice: &str) {
+    let choice = choice.trim();
+    let (style, color) = match choice {
+        "" => (None, None),
+        _ if KNOWN_STYLES.iter().any(|known| known.eq_ignore_ascii_case(choice)) => (Some(choice.to_string()), None),
+        _ => (None, Some(choice.to_string())),
+    };
+    host.style = style;
+    host.color = color;
+}
@@ -0,0 +1,334 @@
+use anyhow::{ Context, Result };
+use serde::{ Deserialize, Serialize };
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::{ sshc_file, InventorySource, SshcDir, ParseLimits, SshHost, DEFAULT_MAX_CONFIG_BYTES, DEFAULT_MAX_HOSTS, DEFAULT_MAX_LINE_LENGTH };
+
+/// ssh 运行期间如何处理 sshc 的终端界面
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionScreen {
+    /// 离开备用屏幕，ssh 结束后重新进入
+    #[default]
+    Leave,
+    /// 留在备用屏幕，只关闭 raw mode 并清屏，适合在远程 tmux 中嵌套使用
+    Keep,
+    /// 用 ssh 替换 sshc 进程（仅 Unix），连接结束后不再回到界面
+    Exec,
+}
+
+/// 进入配置管理和保存变更前的防护（多人共用账号的跳板机上防止误改）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigGuard {
+    #[default]
+    Off,
+    /// 弹窗确认，打开 2 秒后才接受 y
+    Confirm,
+    /// 必须输入 config_guard_phrase
+    Phrase,
+}
+
+/// 写回配置时选项的排列方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteStyle {
+    /// 其他选项保持解析时的顺序
+    #[default]
+    Preserve,
+    /// 按固定顺序：HostName、User、Port、IdentityFile、ProxyJump、端口转发，其余按字母排序
+    Canonical,
+}
+
+/// 新建文件夹时名称的大小写规则
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FolderCase {
+    #[default]
+    Preserve,
+    Lower,
+    /// 每个单词首字母大写，其余小写
+    Title,
+}
+
+/// 在当前模式中按下没有绑定的键时的反馈
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UnboundKeyFeedback {
+    Off,
+    /// 终端响铃
+    Bell,
+    /// 页脚短暂反色显示 "unbound key: …"
+    #[default]
+    Flash,
+}
+
+/// 编辑表单中保存的按键；Enter 只移动到下一个字段
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FormSaveKey {
+    #[default]
+    CtrlS,
+    /// 选用 F2 时保活预设改为 F3/F4
+    F2,
+}
+
+impl FormSaveKey {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::CtrlS => "Ctrl-S",
+            Self::F2 => "F2",
+        }
+    }
+}
+
+/// 用户设置，保存在设置目录（默认 ~/.config/sshc）下的 settings.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 在前九个可见主机前显示 1-9 序号，并允许在 Normal 模式按数字直接连接
+    pub quick_connect_digits: bool,
+    /// ssh 在阈值内以非零状态退出时，重新以 BatchMode 运行并显示其 stderr
+    pub capture_failed_connections: bool,
+    pub quick_failure_threshold_ms: u64,
+    /// 把每台主机最近一次连接失败写入状态文件，重启后仍显示 "!" 标记
+    pub remember_failures: bool,
+    /// 文件夹批量连接的主机数超过该值时需要确认
+    pub group_connect_confirm_threshold: usize,
+    /// 连接前弹窗确认，显示将执行的命令和展开后的 ProxyCommand
+    pub confirm_connect: bool,
+    pub connection_screen: ConnectionScreen,
+    /// 离开界面交给 ssh 前后，在备用屏幕之外打印 "Connecting to …" / "Disconnected from …" 提示行
+    pub connection_messages: bool,
+    /// 自定义连接方式（telnet、串口、kubectl exec 等），主机用 # @connector: <name> 选用
+    pub connectors: Vec<Connector>,
+    /// 健康面板自动重新探测的间隔（秒）
+    pub dashboard_refresh_secs: u64,
+    pub config_guard: ConfigGuard,
+    pub config_guard_phrase: String,
+    /// 会话持续超过该分钟数时，结束后响铃并发送桌面通知；0 表示关闭
+    pub notify_after_minutes: u64,
+    /// 预热主连接（w）等待认证完成的最长秒数，超时后结束该 ssh 进程
+    pub prewarm_timeout_secs: u64,
+    /// 文件夹名或标签命中其中之一的主机，连接前总是弹窗确认（不区分大小写）
+    pub production_labels: Vec<String>,
+    /// 确认生产主机时必须输入主机名，而不是按 y
+    pub production_type_name: bool,
+    /// 文件夹名或标签到环境徽章的映射，按顺序取第一个命中的
+    pub environments: Vec<EnvironmentBadge>,
+    /// ↑↓ 跳过文件夹标题行，只停在主机上（F 键可临时切换）
+    pub skip_folder_rows: bool,
+    /// 简洁模式：没有搜索词且不在搜索时隐藏搜索框（Ctrl-Z 切换）
+    pub zen_mode: bool,
+    /// 新建主机按文件夹选择写入的配置文件，按顺序取第一个命中的
+    pub config_file_rules: Vec<ConfigFileRule>,
+    /// 新建主机时预填的用户、密钥和跳板机
+    pub host_defaults: HostDefaults,
+    /// 按文件夹覆盖 host_defaults，按顺序取第一个命中的
+    pub folder_defaults: Vec<FolderDefaults>,
+    /// 额外读取的只读配置（如 /etc/ssh/ssh_config.d/*.conf），最后一段可用通配符；其中的主机不可编辑
+    pub system_config_paths: Vec<String>,
+    pub write_style: WriteStyle,
+    /// 保存前把主配置复制到数据目录的 backups 下
+    pub backup_on_save: bool,
+    /// 每次保存后只保留最新的这么多个备份；0 表示不限
+    pub backup_keep: usize,
+    /// 每次保存后删除超过该天数的备份；0 表示不限
+    pub backup_max_age_days: u64,
+    /// 配置文件（含被 Include 的文件）超过该字节数时拒绝解析
+    pub max_config_bytes: u64,
+    /// 超过该长度（字节）的行被跳过
+    pub max_config_line_length: usize,
+    /// 最多加载的主机数，超出时只读打开并显示横幅
+    pub max_hosts: usize,
+    /// 表单中新建文件夹时套用的大小写规则
+    pub folder_case: FolderCase,
+    pub form_save_key: FormSaveKey,
+    /// 旧行为：在表单中按 Enter 直接保存
+    pub enter_saves_form: bool,
+    pub unbound_key_feedback: UnboundKeyFeedback,
+    /// 同步远程主机清单（U）的来源；未设置时不可用
+    pub inventory: Option<InventorySource>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            quick_connect_digits: true,
+            capture_failed_connections: true,
+            quick_failure_threshold_ms: 2000,
+            remember_failures: false,
+            group_connect_confirm_threshold: 8,
+            confirm_connect: false,
+            connection_screen: ConnectionScreen::Leave,
+            connection_messages: true,
+            connectors: Vec::new(),
+            dashboard_refresh_secs: 30,
+            config_guard: ConfigGuard::Off,
+            config_guard_phrase: "edit config".to_string(),
+            notify_after_minutes: 0,
+            prewarm_timeout_secs: 60,
+            production_labels: Vec::new(),
+            production_type_name: false,
+            environments: Vec::new(),
+            skip_folder_rows: false,
+            zen_mode: false,
+            config_file_rules: Vec::new(),
+            host_defaults: HostDefaults::default(),
+            folder_defaults: Vec::new(),
+            system_config_paths: Vec::new(),
+            write_style: WriteStyle::Preserve,
+            backup_on_save: true,
+            backup_keep: 20,
+            backup_max_age_days: 0,
+            max_config_bytes: DEFAULT_MAX_CONFIG_BYTES,
+            max_config_line_length: DEFAULT_MAX_LINE_LENGTH,
+            max_hosts: DEFAULT_MAX_HOSTS,
+            folder_case: FolderCase::Preserve,
+            form_save_key: FormSaveKey::CtrlS,
+            enter_saves_form: false,
+            unbound_key_feedback: UnboundKeyFeedback::Flash,
+            inventory: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn parse_limits(&self) -> ParseLimits {
+        ParseLimits { max_bytes: self.max_config_bytes, max_line_length: self.max_config_line_length, max_hosts: self.max_hosts }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        sshc_file(SshcDir::Config, "settings.toml")
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs
+            ::read_to_string(&path)
+            .with_context(|| format!("Unable to read settings file: {:?}", path))?;
+
+        toml::from_str(&content).with_context(|| format!("Invalid settings file: {:?}", path))
+    }
+}
+
+/// 环境徽章：文件夹或标签命中 labels（不区分大小写）的主机在行首显示 badge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentBadge {
+    pub labels: Vec<String>,
+    pub badge: String,
+    /// 颜色名（red、yellow、lightgreen 等）或 #rrggbb
+    #[serde(default)]
+    pub color: String,
+}
+
+/// 主机对应的环境徽章：每个映射先看文件夹再看标签
+pub fn environment_badge<'a>(host: &SshHost, environments: &'a [EnvironmentBadge]) -> Option<&'a EnvironmentBadge> {
+    environments.iter().find(|environment| {
+        let listed = |value: &String| environment.labels.iter().any(|label| label.trim().eq_ignore_ascii_case(value.trim()));
+        host.folder.as_ref().is_some_and(listed) || host.tags.iter().any(listed)
+    })
+}
+
+/// 命令模板中的 {host}、{port}、{user}、{name} 在连接时替换为主机的值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Connector {
+    pub name: String,
+    pub command: String,
+}
+
+/// 文件夹（不区分大小写）到配置文件的映射，file 可以用 ~ 开头
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigFileRule {
+    pub folder: String,
+    pub file: String,
+}
+
+/// 文件夹对应的配置文件规则
+pub fn config_file_rule<'a>(folder: &str, rules: &'a [ConfigFileRule]) -> Option<&'a ConfigFileRule> {
+    let folder = folder.trim();
+    if folder.is_empty() {
+        return None;
+    }
+    rules.iter().find(|rule| rule.folder.trim().eq_ignore_ascii_case(folder))
+}
+
+/// 新建主机的默认值；只填表单打开时还空着的字段
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostDefaults {
+    pub user: Option<String>,
+    pub identity_file: Option<String>,
+    pub proxy_jump: Option<String>,
+}
+
+/// 某个文件夹中新建主机的默认值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderDefaults {
+    pub folder: String,
+    #[serde(flatten)]
+    pub defaults: HostDefaults,
+}
+
+/// 文件夹对应的默认值（不区分大小写）
+pub fn folder_defaults<'a>(folder: &str, entries: &'a [FolderDefaults]) -> Option<&'a HostDefaults> {
+    let folder = folder.trim();
+    if folder.is_empty() {
+        return None;
+    }
+    entries.iter().find(|entry| entry.folder.trim().eq_ignore_ascii_case(folder)).map(|entry| &entry.defaults)
+}
+
+/// 新建主机表单的预填内容。host 是明确给出的部分（如解析出的 ssh 命令），没有文件夹时用 folder（选中的文件夹）。
+/// 用户、密钥和跳板机按 明确给出 > 文件夹默认值 > 全局默认值 的顺序取第一个有值的
+pub fn prefill_new_host(mut host: SshHost, folder: Option<&str>, settings: &Settings) -> SshHost {
+    if host.folder.is_none() {
+        host.folder = folder.map(str::trim).filter(|folder| !folder.is_empty()).map(str::to_string);
+    }
+    let folder_layer = host.folder.as_deref().and_then(|folder| folder_defaults(folder, &settings.folder_defaults));
+    for layer in folder_layer.into_iter().chain([&settings.host_defaults]) {
+        if host.user.is_none() {
+            host.user = layer.user.clone();
+        }
+        if host.identity_file.is_none() {
+            host.identity_file = layer.identity_file.clone();
+        }
+        if let Some(jump) = &layer.proxy_jump && !host.other_options.contains_key("proxyjump") {
+            host.other_options.insert("proxyjump".to_string(), jump.clone());
+        }
+    }
+    host
+}
+
+/// 触发生产环境确认的规则
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtectionRule {
+    Folder(String),
+    Tag(String),
+}
+
+impl ProtectionRule {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Folder(folder) => format!("folder '{}' is listed in production_labels", folder),
+            Self::Tag(tag) => format!("tag '{}' is listed in production_labels", tag),
+        }
+    }
+}
+
+/// 主机是否命中生产标签：先看文件夹，再按顺序看标签
+pub fn protection_rule(host: &SshHost, labels: &[String]) -> Option<ProtectionRule> {
+    let listed = |value: &str| labels.iter().any(|label| label.trim().eq_ignore_ascii_case(value.trim()));
+    if let Some(folder) = &host.folder && listed(folder) {
+        return Some(ProtectionRule::Folder(folder.clone()));
+    }
+    host.tags.iter().find(|tag| listed(tag)).map(|tag| ProtectionRule::Tag(tag.clone()))
+}
@@ -0,0 +1,200 @@
+use anyhow::{ bail, Context, Result };
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::config::{ imported_port_problem, imported_value_problem, parse_config_tree, split_tags, SshHost };
+
+/// CSV 导入导出使用的列，导出时按此顺序写表头
+pub const CSV_COLUMNS: [&str; 9] = [
+    "name",
+    "display name",
+    "hostname",
+    "user",
+    "port",
+    "identity file",
+    "folder",
+    "description",
+    "tags",
+];
+
+/// 电子表格会把以这些字符开头的单元格当作公式执行
+const FORMULA_PREFIXES: [char; 4] = ['=', '+', '-', '@'];
+
+/// 导出时在像公式的单元格前加 '，让电子表格把它当作文本
+fn escape_formula(cell: &str) -> Cow<'_, str> {
+    if cell.starts_with(FORMULA_PREFIXES) {
+        Cow::Owned(format!("'{}", cell))
+    } else {
+        Cow::Borrowed(cell)
+    }
+}
+
+/// 导入时去掉导出加上的 '
+fn unescape_formula(cell: &str) -> &str {
+    match cell.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(FORMULA_PREFIXES) => rest,
+        _ => cell,
+    }
+}
+
+/// export-csv 命令：配置文件及其 Include 的文件中的所有主机导出为 CSV
+pub fn export_config_csv(config_path: &Path) -> Result<String> {
+    let (hosts, _) = parse_config_tree(config_path)?;
+    export_csv(&hosts)
+}
+
+/// 把主机导出为 CSV，标签以 ", " 连接在同一列中；以 = + - @ 开头的单元格加 ' 前缀
+pub fn export_csv(hosts: &[SshHost]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_COLUMNS)?;
+    // 全局部分和 Match 块不是主机，临时主机不导出
+    for host in hosts.iter().filter(|host| !host.is_verbatim() && !host.temporary && !host.system) {
+        let tags = host.tags.join(", ");
+        let record = [
+            host.name.as_str(),
+            host.display_name.as_deref().unwrap_or(""),
+            host.hostname.as_deref().unwrap_or(""),
+            host.user.as_deref().unwrap_or(""),
+            host.port.as_deref().unwrap_or(""),
+            host.identity_file.as_deref().unwrap_or(""),
+            host.folder.as_deref().unwrap_or(""),
+            host.description.as_deref().unwrap_or(""),
+            tags.as_str(),
+        ];
+        writer.write_record(record.map(escape_formula).iter().map(|cell| cell.as_bytes()))?;
+    }
+    let bytes = writer.into_inner().context("Unable to finish CSV output")?;
+    String::from_utf8(bytes).context("CSV output is not valid UTF-8")
+}
+
+/// 表头列名归一化：忽略大小写、空格、下划线和连字符
+fn column_key(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 看起来像 sshc 导出的 CSV：首行包含 name 和 hostname 列
+pub fn looks_like_csv(text: &str) -> bool {
+    let first_line = text.trim_start_matches('\u{feff}').lines().next().unwrap_or("");
+    let columns: Vec<String> = first_line.split(',').map(|c| column_key(c.trim().trim_matches('"'))).collect();
+    columns.iter().any(|c| c == "name") && columns.iter().any(|c| c == "hostname")
+}
+
+/// 导入使用的 CSV 读取器：列数可以不一致，值去掉首尾空白
+fn csv_reader(text: &str, has_headers: bool) -> csv::Reader<&[u8]> {
+    // Excel 保存的 UTF-8 CSV 带 BOM
+    let text = text.trim_start_matches('\u{feff}');
+    csv::ReaderBuilder::new()
+        .has_headers(has_headers)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes())
+}
+
+/// 导入时不能含空白的列：名称、地址、用户、端口和私钥路径各是 ssh 的单个参数
+const SINGLE_WORD_COLUMNS: [usize; 5] = [0, 2, 3, 4, 5];
+
+/// 按表头映射列解析 CSV；未知列忽略，缺少 name 列时报错，name 为空的行跳过。
+/// 任一列含控制字符（如引号内的换行）、单词列含空白或端口无效的行不导入，在警告中报告
+pub fn parse_csv(text: &str) -> Result<(Vec<SshHost>, Vec<String>)> {
+    let mut reader = csv_reader(text, true);
+
+    let headers: Vec<String> = reader.headers()?.iter().map(column_key).collect();
+    let find = |name: &str| headers.iter().position(|h| *h == column_key(name));
+    let Some(name_column) = find("name") else {
+        bail!("CSV has no 'name' column");
+    };
+    let columns: Vec<Option<usize>> = CSV_COLUMNS.iter().map(|c| find(c)).collect();
+
+    let mut hosts = Vec::new();
+    let mut warnings = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        // 表头占第 1 行
+        let record = record.with_context(|| format!("CSV line {}", row + 2))?;
+        let name = record.get(name_column).unwrap_or("");
+        if name.is_empty() {
+            continue;
+        }
+        let value = |i: usize| {
+            columns[i]
+                .and_then(|column| record.get(column))
+                .map(unescape_formula)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let problem = (0..CSV_COLUMNS.len()).find_map(|i| {
+            let value = value(i)?;
+            let problem = if SINGLE_WORD_COLUMNS.contains(&i) {
+                imported_value_problem(&value)
+            } else {
+                value.contains(char::is_control).then_some("contains control characters")
+            };
+            problem.or_else(|| (i == 4).then(|| imported_port_problem(&value)).flatten()).map(|problem| (CSV_COLUMNS[i], problem))
+        });
+        if let Some((column, problem)) = problem {
+            warnings.push(format!("CSV line {}: '{}' {}, row skipped", row + 2, column, problem));
+            continue;
+        }
+
+        let mut host = SshHost::new(unescape_formula(name).to_string());
+        host.display_name = value(1);
+        host.hostname = value(2);
+        host.user = value(3);
+        host.port = value(4);
+        host.identity_file = value(5);
+        host.folder = value(6);
+        host.description = value(7);
+        host.tags = value(8).map(|tags| split_tags(&tags)).unwrap_or_default();
+        hosts.push(host);
+    }
+    Ok((hosts, warnings))
+}
+
+/// 表头恰好是 name 和 description 两列
+fn is_description_header(record: &csv::StringRecord) -> bool {
+    let mut columns: Vec<String> = record.iter().map(column_key).collect();
+    columns.sort();
+    columns == ["description", "name"]
+}
+
+/// "名称,描述" 两列的 CSV：表头为 name 和 description；csv_file 为 true 时（.csv 文件）也接受
+/// 没有表头、每行恰好两列的表格
+pub fn is_description_csv(text: &str, csv_file: bool) -> bool {
+    let mut records = csv_reader(text, false).into_records();
+    let Some(Ok(first)) = records.next() else {
+        return false;
+    };
+    if is_description_header(&first) {
+        return true;
+    }
+    csv_file && !looks_like_csv(text) && first.len() == 2 && records.all(|record| record.is_ok_and(|record| record.len() == 2))
+}
+
+/// 解析 "名称,描述" CSV，按行返回 (名称, 描述)；有表头时按表头取列，描述为空表示清除，名称为空的行跳过
+pub fn parse_description_csv(text: &str) -> Result<Vec<(String, String)>> {
+    let mut records = csv_reader(text, false).into_records().peekable();
+    // 有表头时它占第 1 行
+    let (name_column, description_column, first_line) = match records.peek() {
+        Some(Ok(first)) if is_description_header(first) => {
+            let position = |name: &str| first.iter().position(|column| column_key(column) == name).unwrap_or_default();
+            let columns = (position("name"), position("description"), 2);
+            records.next();
+            columns
+        }
+        _ => (0, 1, 1),
+    };
+
+    let mut rows = Vec::new();
+    for (line, record) in records.enumerate() {
+        let record = record.with_context(|| format!("CSV line {}", line + first_line))?;
+        let name = record.get(name_column).unwrap_or("");
+        if name.is_empty() {
+            continue;
+        }
+        rows.push((name.to_string(), record.get(description_column).unwrap_or("").to_string()));
+    }
+    Ok(rows)
+}
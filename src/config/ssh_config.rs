@@ -1,20 +1,72 @@
-use anyhow::{ Context, Result };
+use anyhow::{ bail, Context, Result };
+use serde::Serialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
+use std::path::{ Path, PathBuf };
 
-#[derive(Debug, Clone)]
+use crate::config::{ expand_hostname, resolve_home, OptionMap, WriteStyle };
+
+/// 字段也是 --print-format json 的输出格式，脚本依赖它，修改时需同步 README
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SshHost {
     pub name: String,
     pub hostname: Option<String>,
     pub user: Option<String>,
     pub port: Option<String>,
     pub identity_file: Option<String>,
-    pub other_options: HashMap<String, String>,
+    pub other_options: OptionMap,
     // 元数据字段
     pub folder: Option<String>,
     pub display_name: Option<String>,
     pub description: Option<String>,
     pub visible: bool,
+    pub tags: Vec<String>,  // # @tags: a, b
+    pub aliases: Vec<String>,  // # @alias: a, b，仅用于搜索
+    // 行样式：# @style: dim、# @color: red，保存原文，无法识别的值由 lint 报告
+    pub style: Option<String>,
+    pub color: Option<String>,
+    // 连接时传给 ssh 子进程的环境变量（# @env: KEY=value，可重复）
+    pub env: Vec<(String, String)>,
+    // 缺少 '=' 的 # @env 原文，原样写回并由 lint 报告
+    pub invalid_env: Vec<String>,
+    // Match 块的原文。这类条目不是主机：不出现在列表中，写回时原样输出
+    pub match_block: Option<String>,
+    // 第一个 Host 之前的全局选项和注释原文，处理方式同 Match 块
+    pub prelude: Option<String>,
+    // 本次会话的临时主机：只在内存中，不写入配置、不进入待保存变更
+    pub temporary: bool,
+    // 所在的被 Include 文件；None 表示主配置文件
+    pub source: Option<PathBuf>,
+    // 已停用：整个块以 DISABLED_PREFIX 注释掉，ssh 不再匹配，sshc 仍列出但不连接
+    pub disabled: bool,
+    // 来自 system_config_paths 中的只读文件（source 为该文件）：可以连接和搜索，但不能编辑，也不会写回
+    pub system: bool,
+    // 所在的被包含文件当前用户无法写入（解析时检测）：可以连接，但不能编辑或删除。
+    // 取决于运行环境而不是配置内容，不输出到 --print 的 JSON
+    #[serde(skip)]
+    pub locked: bool,
+    // 连接方式：# @connector: telnet，对应设置中 connectors 的名称；None 即 ssh
+    pub connector: Option<String>,
+    // 解析时的原文，条目没有修改时原样写回
+    #[serde(skip)]
+    pub raw: Option<RawBlock>,
+}
+
+/// 条目在文件中的原文：从上方的注释到下一个条目之前，包括行尾和之后的空行
+#[derive(Debug, Clone)]
+pub struct RawBlock {
+    text: String,
+    // 解析时按字段写出的结果，写回时仍与之相同说明条目没有被修改
+    serialized: String,
+}
+
+/// 原文不参与比较：字段相同的条目即相等
+impl PartialEq for RawBlock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
 }
 
 impl SshHost {
@@ -25,14 +77,103 @@ impl SshHost {
             user: None,
             port: None,
             identity_file: None,
-            other_options: HashMap::new(),
+            other_options: OptionMap::new(),
             folder: None,
             display_name: None,
             description: None,
             visible: true,
+            tags: Vec::new(),
+            aliases: Vec::new(),
+            style: None,
+            color: None,
+            env: Vec::new(),
+            invalid_env: Vec::new(),
+            match_block: None,
+            prelude: None,
+            temporary: false,
+            source: None,
+            disabled: false,
+            system: false,
+            locked: false,
+            connector: None,
+            raw: None,
         }
     }
 
+    /// Match 块条目，name 为 Match 后面的条件
+    pub fn new_match_block(criteria: &str, raw: String) -> Self {
+        Self { match_block: Some(raw), ..Self::new(criteria.to_string()) }
+    }
+
+    pub fn is_match_block(&self) -> bool {
+        self.match_block.is_some()
+    }
+
+    /// 文件开头的全局部分，name 为空
+    pub fn new_prelude(raw: String) -> Self {
+        Self { prelude: Some(raw), ..Self::new(String::new()) }
+    }
+
+    pub fn is_prelude(&self) -> bool {
+        self.prelude.is_some()
+    }
+
+    /// 原样保留、不作为主机处理的条目（Match 块或文件开头的全局部分）
+    pub fn is_verbatim(&self) -> bool {
+        self.is_match_block() || self.is_prelude()
+    }
+
+    /// 传给 ssh 的目标：Host 行的第一个模式，整体加引号时去掉引号
+    pub fn connect_target(&self) -> &str {
+        let name = self.name.trim();
+        if let Some(inner) = name.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+            return inner;
+        }
+        name.split_whitespace().next().unwrap_or(name)
+    }
+
+    /// 展开 %h 后的 HostName，即 ssh 实际连接的地址；传给 ssh 的仍是别名，由 ssh 自己展开
+    pub fn resolved_hostname(&self) -> Option<String> {
+        self.hostname.as_deref().map(|hostname| expand_hostname(hostname, self.connect_target()))
+    }
+
+    /// 临时主机不在配置文件中，连接参数通过 -o 传给 ssh/scp/sftp；普通主机为空
+    pub fn temporary_ssh_options(&self) -> Vec<String> {
+        if !self.temporary {
+            return Vec::new();
+        }
+        let fields = [("HostName", &self.hostname), ("User", &self.user), ("Port", &self.port), ("IdentityFile", &self.identity_file)];
+        fields
+            .into_iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| (key.to_string(), value)))
+            .chain(self.other_options.iter().map(|(key, value)| (key.clone(), value)))
+            .flat_map(|(key, value)| ["-o".to_string(), format!("{}={}", key, value)])
+            .collect()
+    }
+
+    /// 调用 ssh 时的参数：临时主机的选项加上连接目标
+    pub fn ssh_args(&self) -> Vec<String> {
+        self.ssh_args_as(None)
+    }
+
+    /// 连接参数；user 不为空时以 -l 临时改用该用户，放在最前面以优先于其他来源
+    pub fn ssh_args_as(&self, user: Option<&str>) -> Vec<String> {
+        let mut args = match user {
+            Some(user) => vec!["-l".to_string(), user.to_string()],
+            None => Vec::new(),
+        };
+        args.extend(self.temporary_ssh_options());
+        args.push(self.connect_target().to_string());
+        args
+    }
+
+    /// StrictHostKeyChecking 为 no/off 时 ssh 不校验主机密钥
+    pub fn host_key_checking_disabled(&self) -> bool {
+        self.other_options
+            .get("stricthostkeychecking")
+            .is_some_and(|value| value.eq_ignore_ascii_case("no") || value.eq_ignore_ascii_case("off"))
+    }
+
     pub fn get_display_name(&self) -> String {
         self.display_name.clone().unwrap_or_else(|| self.name.clone())
     }
@@ -40,15 +181,16 @@ impl SshHost {
     pub fn get_full_display_info(&self) -> String {
         let display_name = self.get_display_name();
         let mut info_parts = Vec::new();
+        let hostname = self.resolved_hostname();
 
         if let Some(user) = &self.user {
-            if let Some(hostname) = &self.hostname {
+            if let Some(hostname) = &hostname {
                 info_parts.push(format!("{}@{}", user, hostname));
             } else {
                 info_parts.push(format!("user:{}", user));
             }
-        } else if let Some(hostname) = &self.hostname {
-            info_parts.push(hostname.clone());
+        } else if let Some(hostname) = hostname {
+            info_parts.push(hostname);
         }
 
         if let Some(port) = &self.port {
@@ -71,45 +213,295 @@ impl SshHost {
     pub fn matches_search(&self, query: &str) -> bool {
         let query = query.to_lowercase();
         self.name.to_lowercase().contains(&query) ||
-            self.hostname.as_ref().map_or(false, |h| h.to_lowercase().contains(&query)) ||
-            self.user.as_ref().map_or(false, |u| u.to_lowercase().contains(&query)) ||
-            self.display_name.as_ref().map_or(false, |d| d.to_lowercase().contains(&query)) ||
-            self.description.as_ref().map_or(false, |d| d.to_lowercase().contains(&query)) ||
-            self.folder.as_ref().map_or(false, |f| f.to_lowercase().contains(&query))
+            self.resolved_hostname().is_some_and(|h| h.to_lowercase().contains(&query)) ||
+            self.user.as_ref().is_some_and(|u| u.to_lowercase().contains(&query)) ||
+            self.display_name.as_ref().is_some_and(|d| d.to_lowercase().contains(&query)) ||
+            self.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&query)) ||
+            self.folder.as_ref().is_some_and(|f| f.to_lowercase().contains(&query)) ||
+            self.aliases.iter().any(|a| a.to_lowercase().contains(&query))
     }
 }
 
+pub fn default_config_path() -> Result<PathBuf> {
+    let home_dir = resolve_home()?;
+    Ok(home_dir.join(".ssh").join("config"))
+}
+
 pub fn parse_ssh_config() -> Result<Vec<SshHost>> {
-    let home_dir = home::home_dir().context("Unable to get user home directory")?;
-    let config_path = home_dir.join(".ssh").join("config");
+    parse_ssh_config_file(&default_config_path()?)
+}
+
+pub fn parse_ssh_config_file(config_path: &Path) -> Result<Vec<SshHost>> {
+    parse_ssh_config_file_with_limits(config_path, &ParseLimits::default()).map(|(hosts, _)| hosts)
+}
+
+/// 默认限制：正常的配置远小于这些值
+pub const DEFAULT_MAX_CONFIG_BYTES: u64 = 16 * 1024 * 1024;
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 16 * 1024;
+pub const DEFAULT_MAX_HOSTS: usize = 10_000;
+
+/// 解析配置的安全限制，防止误把二进制或超大文件当作配置时卡住界面
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseLimits {
+    pub max_bytes: u64,
+    pub max_line_length: usize,
+    pub max_hosts: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self { max_bytes: DEFAULT_MAX_CONFIG_BYTES, max_line_length: DEFAULT_MAX_LINE_LENGTH, max_hosts: DEFAULT_MAX_HOSTS }
+    }
+}
+
+/// 配置文件看起来不是文本配置，拒绝解析
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    TooLarge { size: u64, limit: u64 },
+    Binary { offset: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge { size, limit } => write!(f, "the file is {} bytes, over the limit of {} (max_config_bytes)", size, limit),
+            Self::Binary { offset } => write!(f, "the file contains a NUL byte at offset {} and does not look like a text config", offset),
+        }
+    }
+}
 
+impl std::error::Error for ParseError {}
+
+/// 读取配置文本：超过大小限制时不读取内容，含 NUL 字节时不解析，都以 ParseError 失败
+pub fn read_config_text(config_path: &Path, limits: &ParseLimits) -> Result<String> {
+    let context = || format!("Unable to read SSH config file: {:?}", config_path);
+    let size = fs::metadata(config_path).with_context(context)?.len();
+    if size > limits.max_bytes {
+        return Err(anyhow::Error::new(ParseError::TooLarge { size, limit: limits.max_bytes }).context(context()));
+    }
+    let bytes = fs::read(config_path).with_context(context)?;
+    if let Some(offset) = bytes.iter().position(|&byte| byte == 0) {
+        return Err(anyhow::Error::new(ParseError::Binary { offset }).context(context()));
+    }
+    String::from_utf8(bytes).with_context(context)
+}
+
+/// 把超过长度（字节）限制的行替换为空行，行号保持不变；同时返回被跳过的行号
+pub fn drop_long_lines(content: &str, max_line_length: usize) -> (Cow<'_, str>, Vec<usize>) {
+    let skipped: Vec<usize> = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.len() > max_line_length)
+        .map(|(index, _)| index + 1)
+        .collect();
+    if skipped.is_empty() {
+        return (Cow::Borrowed(content), skipped);
+    }
+    let kept: Vec<&str> = content
+        .lines()
+        .map(|line| if line.len() > max_line_length { "" } else { line })
+        .collect();
+    (Cow::Owned(kept.join("\n")), skipped)
+}
+
+/// 按限制解析配置文件，返回主机和因过长被跳过的行号
+pub fn parse_ssh_config_file_with_limits(config_path: &Path, limits: &ParseLimits) -> Result<(Vec<SshHost>, Vec<usize>)> {
     if !config_path.exists() {
-        return Ok(vec![]);
+        return Ok((vec![], vec![]));
+    }
+    let content = read_config_text(config_path, limits)?;
+    let (content, skipped) = drop_long_lines(&content, limits.max_line_length);
+    Ok((parse_ssh_config_from_str(&content), skipped))
+}
+
+pub fn parse_ssh_config_from_str(content: &str) -> Vec<SshHost> {
+    parse_ssh_config_with_warnings(content).0
+}
+
+/// 检查主机名中 ssh 会特殊对待的字符，返回后果说明；None 表示可以安全使用
+pub fn host_name_problem(name: &str) -> Option<String> {
+    if name.chars().any(char::is_whitespace) {
+        Some("contains whitespace: ssh reads each word as a separate Host pattern".to_string())
+    } else if name.contains(['*', '?']) {
+        Some("contains * or ?: ssh treats it as a wildcard matching other hosts".to_string())
+    } else if name.contains('!') {
+        Some("contains !: ssh treats it as a negated pattern".to_string())
+    } else if name.starts_with('-') {
+        Some("starts with -: ssh reads it as a command-line option".to_string())
+    } else {
+        None
+    }
+}
+
+/// 从清单、CSV 等外部来源导入的单个值（名称、地址、用户）的问题：
+/// 控制字符（包括换行）会改变配置的结构，空白会被 ssh 拆成多个参数
+pub fn imported_value_problem(value: &str) -> Option<&'static str> {
+    if value.chars().any(char::is_control) {
+        Some("contains control characters")
+    } else if value.chars().any(char::is_whitespace) {
+        Some("contains whitespace")
+    } else {
+        None
+    }
+}
+
+/// 导入的 Port 只能是端口号或服务名（如 ssh）
+pub fn imported_port_problem(port: &str) -> Option<&'static str> {
+    let number = port.parse::<u16>().is_ok_and(|number| number > 0);
+    let service = port.starts_with(|c: char| c.is_ascii_alphabetic())
+        && port.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    (!number && !service).then_some("is neither a port number nor a service name")
+}
+
+/// 把名称中 ssh 特殊对待的字符替换为 '-'，用于导入等自动生成名称的场景
+pub fn sanitize_host_name(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_whitespace() || matches!(c, '*' | '?' | '!') { '-' } else { c })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('-');
+    if sanitized.is_empty() { "host".to_string() } else { sanitized.to_string() }
+}
+
+/// 拆分逗号或分号分隔的标签，去掉空白和空项
+pub fn split_tags(value: &str) -> Vec<String> {
+    value
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 值按原样传给 shell 的选项，不去除行尾注释
+const RAW_VALUE_OPTIONS: [&str; 4] = ["proxycommand", "localcommand", "remotecommand", "knownhostscommand"];
+
+/// 去掉行尾注释：引号外、位于词首的 # 及其后内容（与 OpenSSH 一致）
+fn strip_inline_comment(value: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return value[..index].trim_end(),
+            None => {}
+        }
+        previous = c;
     }
+    value
+}
+
+/// ssh 只使用第一次出现的值
+fn duplicate_warning(line_number: usize, key: &str) -> String {
+    format!("line {}: duplicate '{}' is ignored (ssh uses the first value)", line_number, key)
+}
+
+/// 停用主机时加在块内每一行前的注释前缀
+pub const DISABLED_PREFIX: &str = "#sshc-disabled#";
+
+/// 一个 Host 行上方的 # @ 元数据键
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetadataKey {
+    pub key: &'static str,
+    pub syntax: &'static str,
+    pub summary: &'static str,
+}
+
+/// sshc 认识的所有元数据键：解析时只收集这些键，内置参考页也由此生成
+pub const METADATA_KEYS: [MetadataKey; 10] = [
+    MetadataKey { key: "folder", syntax: "# @folder: <name>", summary: "Folder the host is listed under" },
+    MetadataKey { key: "name", syntax: "# @name: <text>", summary: "Display name shown instead of the Host name" },
+    MetadataKey { key: "description", syntax: "# @description: <text>", summary: "Description shown in the details pane and searched" },
+    MetadataKey { key: "visible", syntax: "# @visible: false", summary: "Hide the host unless hidden hosts are shown (H)" },
+    MetadataKey { key: "tags", syntax: "# @tags: <tag>, <tag>", summary: "Tags for search, filters, environment badges and production_labels" },
+    MetadataKey { key: "alias", syntax: "# @alias: <name>, <name>", summary: "Extra names the host is found by in search; never passed to ssh" },
+    MetadataKey { key: "style", syntax: "# @style: dim", summary: "Row style in the host list" },
+    MetadataKey { key: "color", syntax: "# @color: <color name or #rrggbb>", summary: "Row color in the host list" },
+    MetadataKey { key: "env", syntax: "# @env: KEY=value", summary: "Environment variable for the ssh process; may be repeated" },
+    MetadataKey { key: "connector", syntax: "# @connector: <name>", summary: "Connect with mosh or a connector from settings instead of ssh" },
+];
+
+pub fn is_metadata_key(key: &str) -> bool {
+    METADATA_KEYS.iter().any(|metadata| metadata.key == key)
+}
+
+/// 解析时每一行的类别，用于划分各条目的原文
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineKind {
+    Blank,
+    Comment,
+    Metadata,
+    Content,
+}
 
-    let content = fs
-        ::read_to_string(&config_path)
-        .with_context(|| format!("Unable to read SSH config file: {:?}", config_path))?;
+/// 条目在文件中的位置：每行的类别和每个条目 Host/Match 行的行号（全局部分为 None）
+struct Layout {
+    kinds: Vec<LineKind>,
+    headers: Vec<Option<usize>>,
+}
+
+/// 解析配置文本，同时返回带行号的解析警告（用于导入片段时提示用户）
+pub fn parse_ssh_config_with_warnings(content: &str) -> (Vec<SshHost>, Vec<String>) {
+    let (mut hosts, warnings, layout) = parse_entries(content);
+    attach_raw_blocks(content, &mut hosts, &layout);
+    (hosts, warnings)
+}
 
+fn parse_entries(content: &str) -> (Vec<SshHost>, Vec<String>, Layout) {
     let mut hosts = Vec::new();
+    let mut layout = Layout { kinds: Vec::new(), headers: Vec::new() };
+    // 当前主机或 Match 块的 Host/Match 行
+    let mut header_line = 0;
+    let mut warnings = Vec::new();
     let mut current_host: Option<SshHost> = None;
     let mut pending_metadata: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let line = line.trim();
+    let mut pending_env: Vec<String> = Vec::new();
+    // 正在读取的 Match 块：(条件, 原文各行)
+    let mut current_match: Option<(String, Vec<String>)> = None;
+    // 第一个 Host 或 Match 之前的原文，之后为 None
+    let mut prelude: Option<Vec<String>> = Some(Vec::new());
+
+    for (line_index, raw_line) in content.lines().enumerate() {
+        let line_number = line_index + 1;
+        // 停用的主机块去掉前缀后按普通行解析
+        let (line, disabled) = match raw_line.trim().strip_prefix(DISABLED_PREFIX).map(str::trim) {
+            Some(rest) if !rest.is_empty() => (rest, true),
+            _ => (raw_line.trim(), false),
+        };
 
         if line.is_empty() {
+            layout.kinds.push(LineKind::Blank);
+            if let Some((_, lines)) = &mut current_match {
+                lines.push(String::new());
+            } else if let Some(lines) = &mut prelude {
+                lines.push(String::new());
+            }
             continue;
         }
 
         // 处理元数据注释
         if line.starts_with('#') {
-            if line.starts_with("# @") {
-                let meta_line = &line[3..].trim();
+            layout.kinds.push(if line.starts_with("# @") { LineKind::Metadata } else { LineKind::Comment });
+            if !line.starts_with("# @") {
+                if let Some((_, lines)) = &mut current_match {
+                    lines.push(raw_line.to_string());
+                } else if let Some(lines) = &mut prelude {
+                    lines.push(raw_line.to_string());
+                }
+            }
+            if let Some(meta_line) = line.strip_prefix("# @") {
+                let meta_line = meta_line.trim();
                 if let Some(colon_pos) = meta_line.find(':') {
                     let key = meta_line[..colon_pos].trim().to_string();
                     let value = meta_line[colon_pos + 1..].trim().to_string();
-                    pending_metadata.insert(key, value);
+                    if key == "env" {
+                        pending_env.push(value);
+                    } else if is_metadata_key(&key) {
+                        pending_metadata.insert(key, value);
+                    }
                 }
             }
             continue;
@@ -123,12 +515,58 @@ pub fn parse_ssh_config() -> Result<Vec<SshHost>> {
         let key = parts[0].to_lowercase();
         let value = if parts.len() > 1 { parts[1].trim() } else { "" };
 
+        // 不属于停用块的带前缀选项行只是普通注释
+        if disabled && key != "host" && !current_host.as_ref().is_some_and(|host| host.disabled) {
+            layout.kinds.push(LineKind::Comment);
+            if let Some((_, lines)) = &mut current_match {
+                lines.push(raw_line.to_string());
+            } else if let Some(lines) = &mut prelude {
+                lines.push(raw_line.to_string());
+            }
+            continue;
+        }
+
+        layout.kinds.push(LineKind::Content);
+        // Match 块到下一个 Host 或 Match 为止，内容不解析，原样保留
+        if key == "match" || key == "host" {
+            if let Some(entry) = prelude.take().and_then(prelude_entry) {
+                hosts.push(entry);
+                layout.headers.push(None);
+            }
+            if let Some(host) = current_host.take() {
+                hosts.push(host);
+                layout.headers.push(Some(header_line));
+            }
+            if let Some((criteria, lines)) = current_match.take() {
+                hosts.push(match_block_entry(&criteria, lines));
+                layout.headers.push(Some(header_line));
+            }
+            header_line = line_index;
+            if key == "match" {
+                // sshc 只在 Host 前写元数据，Match 前的不属于后面的主机
+                pending_metadata.clear();
+                pending_env.clear();
+                current_match = Some((value.to_string(), vec![raw_line.to_string()]));
+                continue;
+            }
+        } else if let Some((_, lines)) = &mut current_match {
+            lines.push(raw_line.to_string());
+            continue;
+        } else if let Some(lines) = &mut prelude {
+            warnings.push(format!("line {}: '{}' appears before any Host line", line_number, parts[0]));
+            lines.push(raw_line.to_string());
+            continue;
+        }
+        // 命令类选项整行交给 shell，其中的 # 不是注释
+        let value = if RAW_VALUE_OPTIONS.contains(&key.as_str()) { value } else { strip_inline_comment(value) };
+
+        if value.is_empty() {
+            warnings.push(format!("line {}: '{}' has no value", line_number, parts[0]));
+        }
+
         match key.as_str() {
             "host" => {
-                if let Some(host) = current_host.take() {
-                    hosts.push(host);
-                }
-                let mut new_host = SshHost::new(value.to_string());
+                let mut new_host = SshHost { disabled, ..SshHost::new(value.to_string()) };
 
                 // 应用待处理的元数据
                 if let Some(folder) = pending_metadata.remove("folder") {
@@ -143,68 +581,281 @@ pub fn parse_ssh_config() -> Result<Vec<SshHost>> {
                 if let Some(visible) = pending_metadata.remove("visible") {
                     new_host.visible = visible.to_lowercase() != "false";
                 }
+                if let Some(tags) = pending_metadata.remove("tags") {
+                    new_host.tags = split_tags(&tags);
+                }
+                if let Some(aliases) = pending_metadata.remove("alias") {
+                    new_host.aliases = split_tags(&aliases);
+                }
+                new_host.style = pending_metadata.remove("style").filter(|style| !style.is_empty());
+                new_host.color = pending_metadata.remove("color").filter(|color| !color.is_empty());
+                new_host.connector = pending_metadata.remove("connector").filter(|connector| !connector.is_empty());
+
+                for entry in pending_env.drain(..) {
+                    match entry.split_once('=') {
+                        Some((key, value)) if !key.trim().is_empty() => {
+                            new_host.env.push((key.trim().to_string(), value.trim().to_string()));
+                        }
+                        _ => new_host.invalid_env.push(entry),
+                    }
+                }
 
                 pending_metadata.clear();
                 current_host = Some(new_host);
             }
             "hostname" => {
                 if let Some(ref mut host) = current_host {
-                    if !value.is_empty() {
+                    if host.hostname.is_some() {
+                        warnings.push(duplicate_warning(line_number, parts[0]));
+                    } else if !value.is_empty() {
                         host.hostname = Some(value.to_string());
                     }
                 }
             }
             "user" => {
                 if let Some(ref mut host) = current_host {
-                    if !value.is_empty() {
+                    if host.user.is_some() {
+                        warnings.push(duplicate_warning(line_number, parts[0]));
+                    } else if !value.is_empty() {
                         host.user = Some(value.to_string());
                     }
                 }
             }
             "port" => {
                 if let Some(ref mut host) = current_host {
-                    if !value.is_empty() {
+                    if host.port.is_some() {
+                        warnings.push(duplicate_warning(line_number, parts[0]));
+                    } else if !value.is_empty() {
                         host.port = Some(value.to_string());
                     }
                 }
             }
             "identityfile" => {
                 if let Some(ref mut host) = current_host {
-                    if !value.is_empty() {
-                        host.identity_file = Some(value.to_string());
+                    // 第一个作为表单中的 IdentityFile，其余按顺序保留在其他选项中
+                    if host.identity_file.is_none() {
+                        if !value.is_empty() {
+                            host.identity_file = Some(value.to_string());
+                        }
+                    } else {
+                        host.other_options.push(key, value.to_string());
                     }
                 }
             }
             _ => {
                 if let Some(ref mut host) = current_host {
-                    host.other_options.insert(key, value.to_string());
+                    // 可重复的选项（LocalForward 等）全部保留
+                    host.other_options.push(key, value.to_string());
                 }
             }
         }
     }
 
+    if let Some(entry) = prelude.and_then(prelude_entry) {
+        hosts.push(entry);
+        layout.headers.push(None);
+    }
     if let Some(host) = current_host {
         hosts.push(host);
+        layout.headers.push(Some(header_line));
+    }
+    if let Some((criteria, lines)) = current_match {
+        hosts.push(match_block_entry(&criteria, lines));
+        layout.headers.push(Some(header_line));
     }
 
-    Ok(hosts)
+    (hosts, warnings, layout)
+}
+
+/// 把文件按条目切开，给每个条目附上原文，按顺序拼接即为原文件。
+/// 有条目的原文单独解析后与它不一致时（如元数据与 Host 行隔着其他块的选项）都不附加，整个文件按字段写回
+fn attach_raw_blocks(content: &str, hosts: &mut [SshHost], layout: &Layout) {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut starts = Vec::with_capacity(hosts.len());
+    // 上一个条目之后第一行的行号，以及它是否为原样保留的条目
+    let mut previous: Option<(usize, bool)> = None;
+    for (host, header) in hosts.iter().zip(&layout.headers) {
+        let start = match (header, previous) {
+            (Some(header), Some((floor, verbatim))) => head_start(&layout.kinds, floor, *header, verbatim),
+            _ => 0,
+        };
+        starts.push(start);
+        previous = Some((header.map_or(0, |header| header + 1), host.is_verbatim()));
+    }
+
+    let ends = starts.iter().skip(1).copied().chain([lines.len()]);
+    let raws: Vec<String> = starts.iter().zip(ends).map(|(&start, end)| lines[start..end].concat()).collect();
+    let serialized: Vec<Option<String>> = hosts.iter().map(|host| serialize_host(host, true).ok()).collect();
+    let matches = hosts
+        .iter()
+        .zip(&raws)
+        .zip(&serialized)
+        .all(|((host, raw), serialized)| serialized.is_some() && raw_serialization(raw, host.is_prelude()) == *serialized);
+    if !matches {
+        return;
+    }
+    for ((host, text), serialized) in hosts.iter_mut().zip(raws).zip(serialized) {
+        host.raw = serialized.map(|serialized| RawBlock { text, serialized });
+    }
+}
+
+/// 条目原文的第一行：上一个 Host/Match 之后的 # @ 元数据都属于它；
+/// 上一个条目是主机时，紧贴在 Host 行上方的注释也归它（原样保留的条目自己保留这些注释）
+fn head_start(kinds: &[LineKind], floor: usize, header: usize, after_verbatim: bool) -> usize {
+    let metadata = (floor..header).find(|&index| kinds[index] == LineKind::Metadata).unwrap_or(header);
+    if after_verbatim {
+        return metadata;
+    }
+    let mut start = header;
+    while start > floor && matches!(kinds[start - 1], LineKind::Comment | LineKind::Metadata) {
+        start -= 1;
+    }
+    start.min(metadata)
+}
+
+/// 单独解析一段原文并按字段写出；主机上方的普通注释单独解析时成为全局部分，不计入
+fn raw_serialization(raw: &str, prelude: bool) -> Option<String> {
+    let (mut entries, _, _) = parse_entries(raw);
+    let comments_only = |text: &str| text.lines().all(|line| line.trim().is_empty() || line.trim().starts_with('#'));
+    if !prelude && entries.len() > 1 && entries[0].prelude.as_deref().is_some_and(comments_only) {
+        entries.remove(0);
+    }
+    match entries.as_slice() {
+        [entry] => serialize_host(entry, true).ok(),
+        _ => None,
+    }
+}
+
+/// 去掉 Match 块末尾的空行后生成条目，原文以换行结尾
+fn match_block_entry(criteria: &str, mut lines: Vec<String>) -> SshHost {
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    SshHost::new_match_block(criteria, format!("{}\n", lines.join("\n")))
+}
+
+/// 全局部分只有空行时不生成条目
+fn prelude_entry(mut lines: Vec<String>) -> Option<SshHost> {
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    Some(SshHost::new_prelude(format!("{}\n", lines.join("\n"))))
 }
 
 pub fn write_ssh_config(hosts: &[SshHost]) -> Result<()> {
-    let home_dir = home::home_dir().context("Unable to get user home directory")?;
-    let config_path = home_dir.join(".ssh").join("config");
+    write_ssh_config_file(&default_config_path()?, hosts)
+}
+
+pub fn write_ssh_config_file(config_path: &Path, hosts: &[SshHost]) -> Result<()> {
+    write_ssh_config_file_with_style(config_path, hosts, WriteStyle::Preserve)
+}
 
+pub fn write_ssh_config_file_with_style(config_path: &Path, hosts: &[SshHost], style: WriteStyle) -> Result<()> {
     // Create .ssh directory if it doesn't exist
-    let ssh_dir = home_dir.join(".ssh");
-    if !ssh_dir.exists() {
+    if let Some(ssh_dir) = config_path.parent() && !ssh_dir.exists() {
         std::fs
-            ::create_dir_all(&ssh_dir)
+            ::create_dir_all(ssh_dir)
             .with_context(|| format!("Unable to create .ssh directory: {:?}", ssh_dir))?;
     }
 
+    let content = serialize_hosts_with_style(hosts, style)?;
+
+    std::fs
+        ::write(config_path, content)
+        .with_context(|| format!("Unable to write SSH config file: {:?}", config_path))?;
+
+    Ok(())
+}
+
+/// 生成写入配置文件的完整内容
+pub fn serialize_hosts(hosts: &[SshHost]) -> Result<String> {
+    serialize_hosts_with_style(hosts, WriteStyle::Preserve)
+}
+
+pub fn serialize_hosts_with_style(hosts: &[SshHost], style: WriteStyle) -> Result<String> {
+    // 原样写回时沿用原文件的换行符；canonical 写法整个文件按字段重写
+    let preserve = style == WriteStyle::Preserve;
+    let crlf = preserve && hosts.iter().filter_map(|host| host.raw.as_ref()).any(|raw| raw.text.contains("\r\n"));
+    let newline = if crlf { "\r\n" } else { "\n" };
+    let mut content = String::new();
+    for host in hosts.iter().filter(|host| !host.temporary && !host.system) {
+        let block = serialize_host_with_style(host, true, style)?;
+        separate_blocks(&mut content, newline);
+        match host.raw.as_ref().filter(|raw| preserve && raw.serialized == block) {
+            // 没有修改的条目原样写回，包括其中的注释、缩进和键名大小写
+            Some(raw) => content.push_str(&raw.text),
+            None => {
+                content.push_str(&block.replace('\n', newline));
+                content.push_str(newline);
+            }
+        }
+    }
+    Ok(content)
+}
+
+/// 下一个块之前补足换行和一个空行；原样写回的块自带原来之后的空行
+fn separate_blocks(content: &mut String, newline: &str) {
+    if content.is_empty() {
+        return;
+    }
+    if !content.ends_with('\n') {
+        content.push_str(newline);
+    }
+    if !content.ends_with("\n\n") && !content.ends_with("\n\r\n") {
+        content.push_str(newline);
+    }
+}
+
+/// 生成单个主机的配置块，include_metadata 为 false 时省略 sshc 元数据注释
+pub fn serialize_host(host: &SshHost, include_metadata: bool) -> Result<String> {
+    serialize_host_with_style(host, include_metadata, WriteStyle::Preserve)
+}
+
+/// 含换行的字段名：写出后换行之后的部分会成为独立的配置行，无论来自哪条导入途径都拒绝写入
+fn line_break_field(host: &SshHost) -> Option<String> {
+    let breaks = |value: &str| value.contains(['\n', '\r']);
+    let fields = [
+        ("Host", Some(&host.name)),
+        ("HostName", host.hostname.as_ref()),
+        ("User", host.user.as_ref()),
+        ("Port", host.port.as_ref()),
+        ("IdentityFile", host.identity_file.as_ref()),
+        ("@folder", host.folder.as_ref()),
+        ("@name", host.display_name.as_ref()),
+        ("@description", host.description.as_ref()),
+        ("@style", host.style.as_ref()),
+        ("@color", host.color.as_ref()),
+        ("@connector", host.connector.as_ref()),
+    ];
+    if let Some((field, _)) = fields.iter().find(|(_, value)| value.is_some_and(|value| breaks(value))) {
+        return Some(field.to_string());
+    }
+    if let Some((key, _)) = host.other_options.iter().find(|(key, value)| breaks(key) || breaks(value)) {
+        return Some(option_display_name(key));
+    }
+    if host.tags.iter().any(|tag| breaks(tag)) {
+        return Some("@tags".to_string());
+    }
+    if host.aliases.iter().any(|alias| breaks(alias)) {
+        return Some("@alias".to_string());
+    }
+    let env = host.env.iter().any(|(key, value)| breaks(key) || breaks(value)) || host.invalid_env.iter().any(|entry| breaks(entry));
+    env.then(|| "@env".to_string())
+}
+
+pub fn serialize_host_with_style(host: &SshHost, include_metadata: bool, style: WriteStyle) -> Result<String> {
+    if let Some(raw) = host.match_block.as_ref().or(host.prelude.as_ref()) {
+        return Ok(raw.clone());
+    }
+    if let Some(field) = line_break_field(host) {
+        bail!("{}: the {} value contains a line break and cannot be written", host.name.escape_debug(), field);
+    }
     let mut content = String::new();
 
-    for host in hosts {
+    if include_metadata {
         // 写入元数据注释
         if let Some(folder) = &host.folder {
             content.push_str(&format!("# @folder: {}\n", folder));
@@ -218,44 +869,121 @@ pub fn write_ssh_config(hosts: &[SshHost]) -> Result<()> {
         if !host.visible {
             content.push_str("# @visible: false\n");
         }
-
-        content.push_str(&format!("Host {}\n", host.name));
-
-        if let Some(hostname) = &host.hostname {
-            content.push_str(&format!("    HostName {}\n", hostname));
+        if !host.tags.is_empty() {
+            content.push_str(&format!("# @tags: {}\n", host.tags.join(", ")));
         }
-        if let Some(user) = &host.user {
-            content.push_str(&format!("    User {}\n", user));
+        if !host.aliases.is_empty() {
+            content.push_str(&format!("# @alias: {}\n", host.aliases.join(", ")));
         }
-        if let Some(port) = &host.port {
-            content.push_str(&format!("    Port {}\n", port));
+        if let Some(style) = &host.style {
+            content.push_str(&format!("# @style: {}\n", style));
         }
-        if let Some(identity_file) = &host.identity_file {
-            content.push_str(&format!("    IdentityFile {}\n", identity_file));
+        if let Some(color) = &host.color {
+            content.push_str(&format!("# @color: {}\n", color));
         }
-
-        for (key, value) in &host.other_options {
-            content.push_str(
-                &format!(
-                    "    {} {}\n",
-                    key
-                        .chars()
-                        .next()
-                        .unwrap()
-                        .to_uppercase()
-                        .chain(key.chars().skip(1))
-                        .collect::<String>(),
-                    value
-                )
-            );
+        for (key, value) in &host.env {
+            content.push_str(&format!("# @env: {}={}\n", key, value));
+        }
+        for entry in &host.invalid_env {
+            content.push_str(&format!("# @env: {}\n", entry));
+        }
+        if let Some(connector) = &host.connector {
+            content.push_str(&format!("# @connector: {}\n", connector));
         }
+    }
+
+    content.push_str(&format!("Host {}\n", host.name));
 
-        content.push('\n');
+    if let Some(hostname) = &host.hostname {
+        content.push_str(&format!("    HostName {}\n", hostname));
+    }
+    if let Some(user) = &host.user {
+        content.push_str(&format!("    User {}\n", user));
+    }
+    if let Some(port) = &host.port {
+        content.push_str(&format!("    Port {}\n", port));
+    }
+    if let Some(identity_file) = &host.identity_file {
+        content.push_str(&format!("    IdentityFile {}\n", identity_file));
     }
 
-    std::fs
-        ::write(&config_path, content)
-        .with_context(|| format!("Unable to write SSH config file: {:?}", config_path))?;
+    let mut options: Vec<(&String, &String)> = host.other_options.iter().collect();
+    if style == WriteStyle::Canonical {
+        // 稳定排序：同名选项（多个 IdentityFile、LocalForward）保持原有先后
+        options.sort_by_key(|(key, _)| canonical_option_rank(key));
+    }
+    for (key, value) in options {
+        content.push_str(&format!("    {} {}\n", option_display_name(key), value));
+    }
 
-    Ok(())
+    if host.disabled {
+        return Ok(content.lines().map(|line| format!("{} {}\n", DISABLED_PREFIX, line)).collect());
+    }
+    Ok(content)
+}
+
+/// canonical 写法中其他选项的排序键：额外的 IdentityFile、ProxyJump、转发在前，其余按键名字母顺序
+fn canonical_option_rank(key: &str) -> (usize, &str) {
+    const LEADING: [&str; 5] = ["identityfile", "proxyjump", "localforward", "remoteforward", "dynamicforward"];
+    match LEADING.iter().position(|leading| *leading == key) {
+        Some(rank) => (rank, ""),
+        None => (LEADING.len(), key),
+    }
+}
+
+/// 常见选项的规范写法，其他选项仅首字母大写（other_options 中的键名均为小写）
+pub fn option_display_name(key: &str) -> String {
+    const KNOWN: &[&str] = &[
+        "HostName",
+        "IdentityFile",
+        "ServerAliveInterval",
+        "ServerAliveCountMax",
+        "ConnectTimeout",
+        "ProxyJump",
+        "ProxyCommand",
+        "ForwardAgent",
+        "ForwardX11",
+        "ForwardX11Trusted",
+        "LocalForward",
+        "RemoteForward",
+        "DynamicForward",
+        "ExitOnForwardFailure",
+        "IdentitiesOnly",
+        "IdentityAgent",
+        "CertificateFile",
+        "AddKeysToAgent",
+        "StrictHostKeyChecking",
+        "UserKnownHostsFile",
+        "GlobalKnownHostsFile",
+        "HostKeyAlias",
+        "HostKeyAlgorithms",
+        "PubkeyAuthentication",
+        "PasswordAuthentication",
+        "PreferredAuthentications",
+        "ControlMaster",
+        "ControlPath",
+        "ControlPersist",
+        "SetEnv",
+        "SendEnv",
+        "RequestTTY",
+        "SessionType",
+        "RemoteCommand",
+        "LocalCommand",
+        "PermitLocalCommand",
+        "LogLevel",
+        "TCPKeepAlive",
+        "BatchMode",
+        "CheckHostIP",
+        "UpdateHostKeys",
+        "KexAlgorithms",
+        "MACs",
+    ];
+    if let Some(name) = KNOWN.iter().find(|name| name.eq_ignore_ascii_case(key)) {
+        return name.to_string();
+    }
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
@@ -0,0 +1,119 @@
+use ratatui::style::{ Color, Modifier, Style };
+
+/// 界面各处使用的样式。彩色为默认；单色（NO_COLOR 或 --plain）时不输出任何颜色，
+/// 选中项改用反色，差异只靠 +/-/~ 前缀，文件夹靠 [+]/[-] 和缩进区分
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub plain: bool,
+    /// 标签、标题和提示信息
+    pub accent: Style,
+    /// 帮助行和次要文字
+    pub muted: Style,
+    /// 继承值、隐藏或禁用的主机等更不显眼的文字
+    pub faint: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub success: Style,
+    /// 临时主机、修改过的字段等需要区分的标记
+    pub special: Style,
+    /// 进度、主连接等提醒
+    pub notice: Style,
+    /// 差异中的分段标题
+    pub info: Style,
+    /// 需要突出的普通文字
+    pub bright: Style,
+    /// 列表中的选中行
+    pub selected: Style,
+    /// 正在编辑的输入框和当前搜索匹配
+    pub input: Style,
+    /// 醒目的警报（如只读横幅）
+    pub alert: Style,
+    /// 其他搜索匹配
+    pub matched: Style,
+    pub folder: Style,
+    pub added: Style,
+    pub removed: Style,
+    pub changed: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::colored()
+    }
+}
+
+impl Theme {
+    pub fn colored() -> Self {
+        let fg = |color| Style::default().fg(color);
+        Self {
+            plain: false,
+            accent: fg(Color::Cyan),
+            muted: fg(Color::Gray),
+            faint: fg(Color::DarkGray),
+            warning: fg(Color::Yellow),
+            error: fg(Color::Red),
+            success: fg(Color::Green),
+            special: fg(Color::Magenta),
+            notice: fg(Color::LightYellow),
+            info: fg(Color::Blue),
+            bright: fg(Color::White),
+            selected: Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD),
+            input: Style::default().fg(Color::Black).bg(Color::Yellow),
+            alert: Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            matched: Style::default().bg(Color::DarkGray),
+            folder: fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            added: fg(Color::Green),
+            removed: fg(Color::Red),
+            changed: fg(Color::Yellow),
+        }
+    }
+
+    /// 单色预设：只用粗体、暗淡、下划线和反色
+    pub fn monochrome() -> Self {
+        let plain = Style::default();
+        Self {
+            plain: true,
+            accent: plain,
+            muted: plain,
+            faint: plain.add_modifier(Modifier::DIM),
+            warning: plain,
+            error: plain.add_modifier(Modifier::BOLD),
+            success: plain,
+            special: plain,
+            notice: plain,
+            info: plain.add_modifier(Modifier::BOLD),
+            bright: plain,
+            selected: plain.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            input: plain.add_modifier(Modifier::REVERSED),
+            alert: plain.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            matched: plain.add_modifier(Modifier::UNDERLINED),
+            folder: plain.add_modifier(Modifier::BOLD),
+            added: plain,
+            removed: plain,
+            changed: plain,
+        }
+    }
+
+    /// --plain 或 NO_COLOR（非空时生效，见 no-color.org）时使用单色
+    pub fn select(plain: bool, no_color: Option<&str>) -> Self {
+        if plain || no_color.is_some_and(|value| !value.is_empty()) {
+            Self::monochrome()
+        } else {
+            Self::colored()
+        }
+    }
+
+    /// 用户在配置中指定的文字颜色（如 # @color），单色时忽略
+    pub fn custom(&self, color: Color) -> Style {
+        if self.plain { Style::default() } else { Style::default().fg(color) }
+    }
+
+    /// 徽章：彩色时为黑字彩底，单色时只加粗
+    pub fn badge(&self, color: Color) -> Style {
+        if self.plain {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().bg(color).fg(Color::Black).add_modifier(Modifier::BOLD)
+        }
+    }
+}
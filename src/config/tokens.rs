@@ -0,0 +1,109 @@
+use crate::config::SshHost;
+
+/// 展开结果的片段，未知 token 原样保留并单独标记以便用警告色显示
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenSegment {
+    Text(String),
+    Unknown(String),
+}
+
+/// ssh_config 中 %h/%p/%r/%n 的取值
+#[derive(Debug, Clone)]
+pub struct TokenContext {
+    pub hostname: String,
+    pub port: String,
+    pub remote_user: String,
+    pub alias: String,
+}
+
+impl TokenContext {
+    /// 按 ssh 的默认规则取值：没有 HostName 时用别名，端口默认 22，用户默认本地用户
+    pub fn for_host(host: &SshHost) -> Self {
+        let local_user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_default();
+        Self {
+            hostname: host.resolved_hostname().unwrap_or_else(|| host.name.clone()),
+            port: host.port.clone().unwrap_or_else(|| "22".to_string()),
+            remote_user: host.user.clone().unwrap_or(local_user),
+            alias: host.name.clone(),
+        }
+    }
+}
+
+/// 展开 %h、%p、%r、%n 和 %%，其他 token 作为 Unknown 片段原样返回
+pub fn expand_tokens(template: &str, context: &TokenContext) -> Vec<TokenSegment> {
+    expand_with(template, |token| match token {
+        'h' => Some(context.hostname.as_str()),
+        'p' => Some(context.port.as_str()),
+        'r' => Some(context.remote_user.as_str()),
+        'n' => Some(context.alias.as_str()),
+        '%' => Some("%"),
+        _ => None,
+    })
+}
+
+/// HostName 中 ssh 只展开 %h（命令行给出的别名）和 %%，其他 token 原样保留
+pub fn expand_hostname(hostname: &str, alias: &str) -> String {
+    expand_with(hostname, |token| match token {
+        'h' => Some(alias),
+        '%' => Some("%"),
+        _ => None,
+    })
+        .into_iter()
+        .map(|segment| match segment {
+            TokenSegment::Text(text) | TokenSegment::Unknown(text) => text,
+        })
+        .collect()
+}
+
+/// value 返回 token 字母对应的值，返回 None 的 token 作为 Unknown 片段
+fn expand_with<'a>(template: &str, value: impl Fn(char) -> Option<&'a str>) -> Vec<TokenSegment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+
+        let value = match chars.next() {
+            Some(token) if let Some(value) = value(token) => Some(value),
+            Some(other) => {
+                if !text.is_empty() {
+                    segments.push(TokenSegment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(TokenSegment::Unknown(format!("%{}", other)));
+                None
+            }
+            None => {
+                if !text.is_empty() {
+                    segments.push(TokenSegment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(TokenSegment::Unknown("%".to_string()));
+                None
+            }
+        };
+
+        if let Some(value) = value {
+            text.push_str(value);
+        }
+    }
+
+    if !text.is_empty() {
+        segments.push(TokenSegment::Text(text));
+    }
+    segments
+}
+
+/// 展开后拼成一个字符串
+pub fn expand_tokens_to_string(template: &str, context: &TokenContext) -> String {
+    expand_tokens(template, context)
+        .into_iter()
+        .map(|segment| match segment {
+            TokenSegment::Text(text) | TokenSegment::Unknown(text) => text,
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+use anyhow::{ Context, Result };
+use serde::{ Deserialize, Serialize };
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::{ create_parent_dir, sshc_file, SshcDir };
+
+/// 每个列表保留的最近路径数
+pub const PATH_HISTORY_LEN: usize = 5;
+
+/// scp/sftp 弹窗用过的路径：远程路径按主机名保存，本地路径全局共用，最近的在前
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferPaths {
+    pub remote: BTreeMap<String, Vec<String>>,
+    pub local: Vec<String>,
+}
+
+impl TransferPaths {
+    pub fn path() -> Option<PathBuf> {
+        sshc_file(SshcDir::State, "transfer_paths.toml")
+    }
+
+    /// 文件不存在或损坏时返回空记录，只是少了预填
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        toml::from_str(content).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("Unable to locate the sshc state directory")?;
+        create_parent_dir(&path)?;
+        let content = toml::to_string(self).context("Unable to serialize transfer paths")?;
+        fs::write(&path, content).with_context(|| format!("Unable to write transfer paths: {:?}", path))
+    }
+
+    pub fn remote_paths(&self, host: &str) -> &[String] {
+        self.remote.get(host).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn remember_remote(&mut self, host: &str, path: &str) {
+        remember(self.remote.entry(host.to_string()).or_default(), path);
+    }
+
+    pub fn remember_local(&mut self, path: &str) {
+        remember(&mut self.local, path);
+    }
+
+    /// 合并较新的记录（例如文件加载完成前本次会话记下的路径），较新的排在前面
+    pub fn merge(&mut self, newer: &TransferPaths) {
+        for (host, paths) in &newer.remote {
+            for path in paths.iter().rev() {
+                self.remember_remote(host, path);
+            }
+        }
+        for path in newer.local.iter().rev() {
+            self.remember_local(path);
+        }
+    }
+}
+
+/// 放到列表最前面，去掉重复项并截断到 PATH_HISTORY_LEN
+fn remember(paths: &mut Vec<String>, path: &str) {
+    let path = path.trim();
+    if path.is_empty() {
+        return;
+    }
+    paths.retain(|existing| existing != path);
+    paths.insert(0, path.to_string());
+    paths.truncate(PATH_HISTORY_LEN);
+}
@@ -0,0 +1,292 @@
+use crossterm::event::KeyCode;
+
+use crate::config::SshHost;
+use crate::core::Selector;
+
+/// 操作所属的界面：主列表或配置管理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionScope {
+    Normal,
+    Config,
+}
+
+/// 针对选中主机的单键操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostAction {
+    Connect,
+    ConnectAs,
+    Transfer,
+    EffectiveConfig,
+    Compare,
+    Prewarm,
+    CloseMaster,
+    Queue,
+    Delete,
+    Edit,
+    Derive,
+    EditTags,
+    ToggleDisabled,
+    KeepTemporary,
+    RotateKey,
+    Mark,
+    ShowChanges,
+}
+
+/// 操作与按键的对应关系
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionBinding {
+    pub action: HostAction,
+    pub scope: ActionScope,
+    pub key: KeyCode,
+    pub label: &'static str,
+}
+
+const fn bind(action: HostAction, scope: ActionScope, key: KeyCode, label: &'static str) -> ActionBinding {
+    ActionBinding { action, scope, key, label }
+}
+
+/// 主机操作的唯一登记处：按键分派和操作菜单都从这里取，按键与菜单不会不一致。
+/// 按界面分组，组内顺序即菜单中的顺序
+pub const HOST_ACTIONS: [ActionBinding; 20] = [
+    bind(HostAction::Connect, ActionScope::Normal, KeyCode::Enter, "Connect"),
+    bind(HostAction::ConnectAs, ActionScope::Normal, KeyCode::Char('u'), "Connect as another user"),
+    bind(HostAction::Transfer, ActionScope::Normal, KeyCode::Char('t'), "scp/sftp transfer"),
+    bind(HostAction::EffectiveConfig, ActionScope::Normal, KeyCode::Char('g'), "Show effective config (ssh -G)"),
+    bind(HostAction::Compare, ActionScope::Normal, KeyCode::Char('='), "Mark for comparison / compare"),
+    bind(HostAction::Prewarm, ActionScope::Normal, KeyCode::Char('w'), "Pre-warm master connection"),
+    bind(HostAction::CloseMaster, ActionScope::Normal, KeyCode::Char('W'), "Close master connection"),
+    bind(HostAction::Queue, ActionScope::Normal, KeyCode::Char('+'), "Add to / remove from queue"),
+    bind(HostAction::Delete, ActionScope::Normal, KeyCode::Delete, "Delete host"),
+    bind(HostAction::Edit, ActionScope::Config, KeyCode::Char('e'), "Edit host"),
+    bind(HostAction::Derive, ActionScope::Config, KeyCode::Char('T'), "Derive tunnel/SOCKS/jump host"),
+    bind(HostAction::EditTags, ActionScope::Config, KeyCode::Char('t'), "Edit tags of marked hosts"),
+    bind(HostAction::ToggleDisabled, ActionScope::Config, KeyCode::Char('x'), "Disable/enable host"),
+    bind(HostAction::KeepTemporary, ActionScope::Config, KeyCode::Char('P'), "Keep temporary host"),
+    bind(HostAction::RotateKey, ActionScope::Config, KeyCode::Char('K'), "Rotate identity file"),
+    bind(HostAction::Mark, ActionScope::Config, KeyCode::Char(' '), "Mark/unmark"),
+    bind(HostAction::Delete, ActionScope::Config, KeyCode::Char('d'), "Delete host"),
+    bind(HostAction::ShowChanges, ActionScope::Config, KeyCode::Char('D'), "Show unsaved changes"),
+    bind(HostAction::EffectiveConfig, ActionScope::Config, KeyCode::Char('g'), "Show effective config (ssh -G)"),
+    bind(HostAction::Compare, ActionScope::Config, KeyCode::Char('='), "Mark for comparison / compare"),
+];
+
+/// 不针对某台主机的命令，与 HOST_ACTIONS 一起构成按键分派和命令面板的登记处
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppCommand {
+    Quit,
+    LeaveConfig,
+    Search,
+    ConfigManagement,
+    About,
+    ToggleHidden,
+    GroupConnect,
+    Export,
+    Import,
+    Lint,
+    NextChange,
+    PreviousChange,
+    NextFinding,
+    PreviousFinding,
+    Dashboard,
+    ConnectRemembered,
+    SkipFolders,
+    VerbatimBlocks,
+    KeyReport,
+    ClearQueue,
+    StartQueue,
+    FilterMenu,
+    Views,
+    AddHost,
+    AddFromClipboard,
+    ToggleFolderHidden,
+    InventorySync,
+    Backups,
+    BulkIdentity,
+    Onboarding,
+    Reload,
+    Zen,
+    TemporaryHost,
+    WhatMatches,
+}
+
+/// 命令与按键的对应关系；ctrl 表示需要同时按住 Ctrl
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommandBinding {
+    pub command: AppCommand,
+    pub scope: ActionScope,
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub label: &'static str,
+}
+
+const fn command(command: AppCommand, scope: ActionScope, key: KeyCode, label: &'static str) -> CommandBinding {
+    CommandBinding { command, scope, key, ctrl: false, label }
+}
+
+const fn ctrl_command(command: AppCommand, scope: ActionScope, c: char, label: &'static str) -> CommandBinding {
+    CommandBinding { command, scope, key: KeyCode::Char(c), ctrl: true, label }
+}
+
+/// 主列表和配置管理中的单键命令。F5 和 Ctrl 组合键在 handle_key 中提前处理，这里登记它们是为了在面板中显示
+pub const COMMANDS: [CommandBinding; 47] = [
+    command(AppCommand::Search, ActionScope::Normal, KeyCode::Char('/'), "Search hosts"),
+    command(AppCommand::ConfigManagement, ActionScope::Normal, KeyCode::Char('e'), "Enter config management"),
+    command(AppCommand::GroupConnect, ActionScope::Normal, KeyCode::Char('C'), "Connect to every host in the folder"),
+    command(AppCommand::ConnectRemembered, ActionScope::Normal, KeyCode::Char('U'), "Connect as the remembered user"),
+    command(AppCommand::StartQueue, ActionScope::Normal, KeyCode::Char('>'), "Start connection queue"),
+    command(AppCommand::ClearQueue, ActionScope::Normal, KeyCode::Char('-'), "Clear connection queue"),
+    command(AppCommand::Dashboard, ActionScope::Normal, KeyCode::Char('!'), "Host health dashboard"),
+    command(AppCommand::ToggleHidden, ActionScope::Normal, KeyCode::Char('H'), "Toggle hidden hosts"),
+    command(AppCommand::SkipFolders, ActionScope::Normal, KeyCode::Char('F'), "Toggle skipping folder rows"),
+    command(AppCommand::FilterMenu, ActionScope::Normal, KeyCode::Char('\\'), "Quick filters"),
+    command(AppCommand::Views, ActionScope::Normal, KeyCode::Char('\''), "Saved views"),
+    command(AppCommand::Export, ActionScope::Normal, KeyCode::Char('Y'), "Export config snippet"),
+    command(AppCommand::Lint, ActionScope::Normal, KeyCode::Char('L'), "Check config for problems"),
+    command(AppCommand::NextChange, ActionScope::Normal, KeyCode::Char(']'), "Jump to next pending change"),
+    command(AppCommand::PreviousChange, ActionScope::Normal, KeyCode::Char('['), "Jump to previous pending change"),
+    command(AppCommand::NextFinding, ActionScope::Normal, KeyCode::Char('}'), "Jump to next lint finding"),
+    command(AppCommand::PreviousFinding, ActionScope::Normal, KeyCode::Char('{'), "Jump to previous lint finding"),
+    command(AppCommand::VerbatimBlocks, ActionScope::Normal, KeyCode::Char('M'), "View global options and Match blocks"),
+    command(AppCommand::KeyReport, ActionScope::Normal, KeyCode::Char('k'), "Public key report"),
+    ctrl_command(AppCommand::WhatMatches, ActionScope::Normal, 'w', "Show which blocks match a destination"),
+    ctrl_command(AppCommand::TemporaryHost, ActionScope::Normal, 'n', "Add temporary host"),
+    ctrl_command(AppCommand::Zen, ActionScope::Normal, 'z', "Toggle zen mode"),
+    command(AppCommand::Reload, ActionScope::Normal, KeyCode::F(5), "Reload config from disk"),
+    command(AppCommand::About, ActionScope::Normal, KeyCode::Char('v'), "About / version"),
+    command(AppCommand::Quit, ActionScope::Normal, KeyCode::Char('q'), "Quit"),
+    command(AppCommand::AddHost, ActionScope::Config, KeyCode::Char('a'), "Add host"),
+    command(AppCommand::AddFromClipboard, ActionScope::Config, KeyCode::Char('V'), "New host from clipboard"),
+    command(AppCommand::Import, ActionScope::Config, KeyCode::Char('i'), "Import config snippet"),
+    command(AppCommand::Onboarding, ActionScope::Config, KeyCode::Char('W'), "New server setup wizard"),
+    command(AppCommand::ToggleFolderHidden, ActionScope::Config, KeyCode::Char('h'), "Hide/show folder"),
+    command(AppCommand::BulkIdentity, ActionScope::Config, KeyCode::Char('I'), "Set folder identity file"),
+    command(AppCommand::InventorySync, ActionScope::Config, KeyCode::Char('U'), "Sync hosts from the inventory"),
+    command(AppCommand::Backups, ActionScope::Config, KeyCode::Char('B'), "Browse backups"),
+    command(AppCommand::ToggleHidden, ActionScope::Config, KeyCode::Char('H'), "Toggle hidden hosts"),
+    command(AppCommand::FilterMenu, ActionScope::Config, KeyCode::Char('\\'), "Quick filters"),
+    command(AppCommand::Views, ActionScope::Config, KeyCode::Char('\''), "Saved views"),
+    command(AppCommand::Export, ActionScope::Config, KeyCode::Char('Y'), "Export config snippet"),
+    command(AppCommand::Lint, ActionScope::Config, KeyCode::Char('L'), "Check config for problems"),
+    command(AppCommand::NextChange, ActionScope::Config, KeyCode::Char(']'), "Jump to next pending change"),
+    command(AppCommand::PreviousChange, ActionScope::Config, KeyCode::Char('['), "Jump to previous pending change"),
+    command(AppCommand::NextFinding, ActionScope::Config, KeyCode::Char('}'), "Jump to next lint finding"),
+    command(AppCommand::PreviousFinding, ActionScope::Config, KeyCode::Char('{'), "Jump to previous lint finding"),
+    ctrl_command(AppCommand::WhatMatches, ActionScope::Config, 'w', "Show which blocks match a destination"),
+    ctrl_command(AppCommand::TemporaryHost, ActionScope::Config, 'n', "Add temporary host"),
+    ctrl_command(AppCommand::Zen, ActionScope::Config, 'z', "Toggle zen mode"),
+    command(AppCommand::Reload, ActionScope::Config, KeyCode::F(5), "Reload config from disk"),
+    command(AppCommand::LeaveConfig, ActionScope::Config, KeyCode::Char('q'), "Review changes and leave config management"),
+];
+
+impl CommandBinding {
+    pub fn key_label(&self) -> String {
+        if self.ctrl {
+            format!("Ctrl-{}", key_name(self.key).to_uppercase())
+        } else {
+            key_name(self.key)
+        }
+    }
+}
+
+/// 该界面中不带修饰键的按键对应的命令
+pub fn command_for_key(scope: ActionScope, key: KeyCode) -> Option<AppCommand> {
+    COMMANDS.iter().find(|binding| binding.scope == scope && !binding.ctrl && binding.key == key).map(|binding| binding.command)
+}
+
+/// 该界面中的所有命令，按登记顺序
+pub fn commands_in(scope: ActionScope) -> impl Iterator<Item = &'static CommandBinding> {
+    COMMANDS.iter().filter(move |binding| binding.scope == scope)
+}
+
+impl ActionBinding {
+    /// 菜单和帮助中显示的按键名称
+    pub fn key_label(&self) -> String {
+        key_name(self.key)
+    }
+}
+
+/// 按键的显示名称，如 "Enter"、"Del"、"F7"、"x"
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "Shift-Tab".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::Insert => "Ins".to_string(),
+        key => format!("{:?}", key),
+    }
+}
+
+/// 不算作按错的按键：单独的修饰键、锁定键和媒体键等，按下它们不给反馈
+pub fn is_ignored_key(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::Modifier(_)
+            | KeyCode::Media(_)
+            | KeyCode::Null
+            | KeyCode::CapsLock
+            | KeyCode::NumLock
+            | KeyCode::ScrollLock
+            | KeyCode::PrintScreen
+            | KeyCode::Pause
+            | KeyCode::Menu
+            | KeyCode::KeypadBegin
+    )
+}
+
+/// 该界面中按键对应的操作
+pub fn action_for_key(scope: ActionScope, key: KeyCode) -> Option<HostAction> {
+    HOST_ACTIONS.iter().find(|binding| binding.scope == scope && binding.key == key).map(|binding| binding.action)
+}
+
+/// 该界面中的所有操作，按登记顺序
+pub fn actions_in(scope: ActionScope) -> impl Iterator<Item = &'static ActionBinding> {
+    HOST_ACTIONS.iter().filter(move |binding| binding.scope == scope)
+}
+
+impl HostAction {
+    /// 操作对这台主机是否有意义，菜单只列出适用的操作；master_open 表示有正在预热或已建立的主连接，
+    /// marking 表示配置模式中有标记的主机
+    pub fn applies_to(self, host: &SshHost, master_open: bool, read_only: bool, marking: bool) -> bool {
+        match self {
+            HostAction::Connect | HostAction::ConnectAs | HostAction::Transfer | HostAction::Prewarm | HostAction::Queue => !host.disabled,
+            HostAction::CloseMaster => master_open,
+            HostAction::Delete => !read_only,
+            HostAction::ToggleDisabled => !host.temporary,
+            HostAction::KeepTemporary => host.temporary,
+            HostAction::RotateKey => host.identity_file.is_some(),
+            HostAction::EditTags => marking,
+            HostAction::EffectiveConfig | HostAction::Compare | HostAction::Edit | HostAction::Derive | HostAction::Mark | HostAction::ShowChanges => true,
+        }
+    }
+}
+
+/// 操作菜单：列出选中主机适用的操作，Enter 或操作本身的按键执行
+#[derive(Debug, Clone)]
+pub struct ActionMenu {
+    pub scope: ActionScope,
+    pub host: String,
+    pub actions: Vec<&'static ActionBinding>,
+    pub choice: Selector,
+}
+
+impl ActionMenu {
+    pub fn new(scope: ActionScope, host: &SshHost, master_open: bool, read_only: bool, marking: bool) -> Self {
+        let actions: Vec<_> = actions_in(scope).filter(|binding| binding.action.applies_to(host, master_open, read_only, marking)).collect();
+        let choice = Selector::new(actions.len());
+        Self { scope, host: host.name.clone(), actions, choice }
+    }
+
+    /// 菜单中按键对应的操作（区分大小写，如 w 和 W）
+    pub fn action_for_key(&self, key: KeyCode) -> Option<HostAction> {
+        self.actions.iter().find(|binding| binding.key == key).map(|binding| binding.action)
+    }
+}
@@ -1,10 +1,21 @@
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Color;
 use ratatui::widgets::ListState;
-use std::process::Command;
+use std::collections::{ HashMap, HashSet };
+use std::path::{ Path, PathBuf };
+use std::process::{ Command, Stdio };
+use std::time::{ Duration, Instant, SystemTime };
 
-use crate::utils::{Result, SshcError};
-use crate::config::{parse_ssh_config, write_ssh_config, SshHost};
-use crate::core::TerminalManager;
+use crate::utils::{copy_to_clipboard, read_clipboard, resolve_port, Result, SshcError};
+use crate::config::{
+    apply_row_style_choice, compare_effective, compare_hosts, match_destination, row_style_choice, export_csv, export_snippet, find_dependents, home_dir, host_name_problem, lint_hosts, lint_key_files, include_covers, list_private_keys, looks_like_csv, parse_csv, is_description_csv, parse_description_csv, option_display_name, sync_inventory, inherited_options,
+    build_key_report, create_backup, format_utc, list_backups, prune_backups, sshc_file, SshcDir, key_report_table, lock_path, merge_system_hosts, parse_config_tree_with_limits, parse_system_configs, parse_ssh_config_with_warnings, parse_ssh_g_output, protection_rule, resolve_home, retarget_references, rotate_identity, serialize_host_with_style, split_tags, prefill_new_host, tighten_key_permissions, tilde_relative, write_config_tree, config_file_rule, Theme,
+    CompareRow, ConfigGuard, UnboundKeyFeedback, InventoryDelta, InventorySnapshot, ConnectionScreen, DestinationMatch, ROW_STYLE_CHOICES, EffectiveRow, DISABLED_PREFIX, FormSaveKey, History, HostFailure, LimitReport, ConfigLock, LockStatus, LintFinding, LintSeverity, ProtectionRule, TransferPaths, Settings, SnippetOptions, SshHost,
+};
+use crate::core::{
+    action_for_key, command_for_key, commands_in, actions_in, is_ignored_key, key_name, ActionMenu, ActionScope, AppCommand, CommandPalette, HostAction, PaletteAction, PaletteEntry, build_import_candidates, capture_ssh_stderr, classify_failure, connect_command, connecting_message, disconnected_message, parse_ssh_command, format_duration, host_env, inside_tmux, is_connection_failure, is_quick_failure, notify_session_end, ring_bell, open_tmux_panes, query_effective_config, should_notify, uses_ssh,
+    derive_host, fill_blanks, dns_name, folder_suggestions, merge_pending, probe_target, replay_changes, control_path_configured, summarize_changes, host_diff, preview_backup, BackupBrowser, plan_descriptions, unique_host_name, SUMMARY_ROW_LIMIT, ConflictResolution, DeriveState, DescriptionOutcome, HostDiff, ReferenceView, DeriveTemplate, QuickFilter, ConnectionError, DnsStatus, FailureReason, FolderSuggestion, ImportStage, ImportState, MergeChoice, MergeState, Multiplexer, PrewarmJob, PrewarmOutcome, ProbeRun, ProbeStatus, Selector, SelectorEvent, SshBinary, CONFIRM_YES, DeferredLoad, OnboardingState, OnboardingStep, StartupProfile, StepOutcome, TagEditor, TaskKind, TaskOutput, TaskRegistry, TerminalManager, TransferState, UserSwitchState, load_views, save_views, ViewPicker, ViewPrompt, ViewState,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppMode {
@@ -16,6 +27,41 @@ pub enum AppMode {
     ConfirmDiscardEdit,
     ReviewChanges,
     ShowVersion,
+    ShowConnectionError,
+    ConfirmGroupConnect,
+    ConfirmConnect,
+    ShowMessage,
+    ExportHosts,
+    ImportSnippet,
+    ConfirmDeleteDependents,
+    LintView,
+    ShowEffectiveConfig,
+    CompareHosts,
+    WhatMatches,
+    InventorySync,
+    BulkIdentity,
+    RotateKey,
+    ConfirmFixPermissions,
+    ConfirmReviewPending,
+    Dashboard,
+    ConfirmGuard,
+    ConfirmReload,
+    QueueNext,
+    FilterMenu,
+    DeriveHost,
+    EditTags,
+    Transfer,
+    SwitchUser,
+    ResolveConflicts,
+    ConfigLocked,
+    Onboarding,
+    ActionMenu,
+    CommandPalette,
+    ConfirmCopySystem,
+    HostDiff,
+    Reference,
+    Backups,
+    Views,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -26,31 +72,505 @@ pub enum ConfigAction {
     Delete,
 }
 
-#[derive(Debug, Clone)]
-pub struct EditingHostData {
+/// 编辑表单的字段值，按表单顺序：0-8 为文本，9 为可见性，10-12 为保活设置，13-15 为主机密钥信任设置，16 为配置文件，17 为行样式
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostFormFields {
     pub name: String,
     pub hostname: String,
     pub user: String,
     pub port: String,
     pub identity_file: String,
-    // 新增的元数据字段
     pub folder: String,
     pub display_name: String,
     pub description: String,
+    // 逗号分隔的搜索别名
+    pub aliases: String,
     pub visible: bool,
+    // 由 other_options 提升出来的保活设置，空表示不写入
+    pub server_alive_interval: String,
+    pub server_alive_count_max: String,
+    pub connect_timeout: String,
+    // 主机密钥信任设置，同样来自 other_options
+    pub strict_host_key_checking: String,
+    pub user_known_hosts_file: String,
+    pub host_key_alias: String,
+    // 写入的配置文件（显示用的路径）；空表示按文件夹规则自动选择，仅新建主机可用
+    pub config_file: String,
+    // 行样式：空、dim 或颜色名
+    pub row_style: String,
+}
+
+/// 名称字段的序号
+pub const NAME_FIELD: usize = 0;
+
+/// 端口字段的序号
+pub const PORT_FIELD: usize = 3;
+
+/// 文件夹字段的序号，输入时显示补全列表
+pub const FOLDER_FIELD: usize = 5;
+
+/// 可见性字段的序号
+pub const VISIBLE_FIELD: usize = 9;
+
+/// 第一个保活字段（ServerAliveInterval）的序号，其后依次是 ServerAliveCountMax 和 ConnectTimeout
+pub const KEEPALIVE_FIELD: usize = 10;
+
+/// StrictHostKeyChecking 字段的序号，只能在固定取值间切换
+pub const STRICT_HOST_KEY_FIELD: usize = 13;
+
+/// 配置文件字段的序号，在主配置和被包含的文件间切换
+pub const CONFIG_FILE_FIELD: usize = 16;
+
+/// 行样式字段的序号，在 ROW_STYLE_CHOICES 间切换
+pub const ROW_STYLE_FIELD: usize = 17;
+
+impl Default for HostFormFields {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            hostname: String::new(),
+            user: String::new(),
+            port: String::new(),
+            identity_file: String::new(),
+            folder: String::new(),
+            display_name: String::new(),
+            description: String::new(),
+            aliases: String::new(),
+            visible: true,
+            server_alive_interval: String::new(),
+            server_alive_count_max: String::new(),
+            connect_timeout: String::new(),
+            strict_host_key_checking: String::new(),
+            user_known_hosts_file: String::new(),
+            host_key_alias: String::new(),
+            config_file: String::new(),
+            row_style: String::new(),
+        }
+    }
+}
+
+impl HostFormFields {
+    pub const COUNT: usize = 18;
+
+    pub fn from_host(host: &SshHost) -> Self {
+        let option = |key: &str| host.other_options.get(key).cloned().unwrap_or_default();
+        Self {
+            name: host.name.clone(),
+            hostname: host.hostname.clone().unwrap_or_default(),
+            user: host.user.clone().unwrap_or_default(),
+            port: host.port.clone().unwrap_or_default(),
+            identity_file: host.identity_file.clone().unwrap_or_default(),
+            folder: host.folder.clone().unwrap_or_default(),
+            display_name: host.display_name.clone().unwrap_or_default(),
+            description: host.description.clone().unwrap_or_default(),
+            aliases: host.aliases.join(", "),
+            visible: host.visible,
+            server_alive_interval: option(KEEPALIVE_OPTIONS[0]),
+            server_alive_count_max: option(KEEPALIVE_OPTIONS[1]),
+            connect_timeout: option(KEEPALIVE_OPTIONS[2]),
+            strict_host_key_checking: option(TRUST_OPTIONS[0]),
+            user_known_hosts_file: option(TRUST_OPTIONS[1]),
+            host_key_alias: option(TRUST_OPTIONS[2]),
+            config_file: String::new(),
+            row_style: row_style_choice(host),
+        }
+    }
+
+    /// 文本字段；可见性字段返回 None
+    pub fn text(&self, field: usize) -> Option<&String> {
+        match field {
+            0 => Some(&self.name),
+            1 => Some(&self.hostname),
+            2 => Some(&self.user),
+            3 => Some(&self.port),
+            4 => Some(&self.identity_file),
+            5 => Some(&self.folder),
+            6 => Some(&self.display_name),
+            7 => Some(&self.description),
+            8 => Some(&self.aliases),
+            10 => Some(&self.server_alive_interval),
+            11 => Some(&self.server_alive_count_max),
+            12 => Some(&self.connect_timeout),
+            13 => Some(&self.strict_host_key_checking),
+            14 => Some(&self.user_known_hosts_file),
+            15 => Some(&self.host_key_alias),
+            16 => Some(&self.config_file),
+            17 => Some(&self.row_style),
+            _ => None,
+        }
+    }
+
+    pub fn text_mut(&mut self, field: usize) -> Option<&mut String> {
+        match field {
+            0 => Some(&mut self.name),
+            1 => Some(&mut self.hostname),
+            2 => Some(&mut self.user),
+            3 => Some(&mut self.port),
+            4 => Some(&mut self.identity_file),
+            5 => Some(&mut self.folder),
+            6 => Some(&mut self.display_name),
+            7 => Some(&mut self.description),
+            8 => Some(&mut self.aliases),
+            10 => Some(&mut self.server_alive_interval),
+            11 => Some(&mut self.server_alive_count_max),
+            12 => Some(&mut self.connect_timeout),
+            13 => Some(&mut self.strict_host_key_checking),
+            14 => Some(&mut self.user_known_hosts_file),
+            15 => Some(&mut self.host_key_alias),
+            16 => Some(&mut self.config_file),
+            17 => Some(&mut self.row_style),
+            _ => None,
+        }
+    }
+
+    pub fn field_differs(&self, other: &Self, field: usize) -> bool {
+        match field {
+            VISIBLE_FIELD => self.visible != other.visible,
+            _ => self.text(field) != other.text(field),
+        }
+    }
+
+    /// 与 original 不同的字段序号
+    pub fn changed_fields(&self, original: &Self) -> Vec<usize> {
+        (0..Self::COUNT).filter(|&field| self.field_differs(original, field)).collect()
+    }
+
+    /// 把一个字段恢复为 original 中的值
+    pub fn revert_field(&mut self, original: &Self, field: usize) {
+        match field {
+            VISIBLE_FIELD => self.visible = original.visible,
+            _ => {
+                if let (Some(value), Some(original)) = (self.text_mut(field), original.text(field)) {
+                    *value = original.clone();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EditingHostData {
+    pub current: HostFormFields,
+    // 打开表单时的值，用于判断哪些字段被修改
+    pub original: HostFormFields,
     pub current_field: usize,
-    // 原始值用于比较变更
-    pub original_name: String,
-    pub original_hostname: String,
-    pub original_user: String,
-    pub original_port: String,
-    pub original_identity_file: String,
-    pub original_folder: String,
-    pub original_display_name: String,
-    pub original_description: String,
-    pub original_visible: bool,
+    pub error: Option<String>,
+    // 用户已确认仍要使用的有问题的名称
+    pub confirmed_name: Option<String>,
+    // 文件夹补全列表的选择器，None 表示焦点仍在输入框
+    pub folder_choice: Option<Selector>,
+}
+
+impl EditingHostData {
+    pub fn new(original: HostFormFields) -> Self {
+        Self { current: original.clone(), original, current_field: 0, error: None, confirmed_name: None, folder_choice: None }
+    }
+
+    pub fn is_modified(&self, field: usize) -> bool {
+        self.current.field_differs(&self.original, field)
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.current != self.original
+    }
+}
+
+/// 编辑表单中提升为独立字段的 ssh 选项（other_options 中的小写键名）
+pub const KEEPALIVE_OPTIONS: [&str; 3] = ["serveraliveinterval", "serveralivecountmax", "connecttimeout"];
+
+/// 编辑表单中的主机密钥信任设置：StrictHostKeyChecking、UserKnownHostsFile、HostKeyAlias
+pub const TRUST_OPTIONS: [&str; 3] = ["stricthostkeychecking", "userknownhostsfile", "hostkeyalias"];
+
+/// StrictHostKeyChecking 可选的值，空表示不写入
+pub const STRICT_HOST_KEY_VALUES: [&str; 5] = ["", "yes", "accept-new", "no", "ask"];
+
+/// 切换到下一个（或上一个）StrictHostKeyChecking 取值；不认识的值从头开始
+pub fn cycle_strict_host_key(current: &str, forward: bool) -> String {
+    let count = STRICT_HOST_KEY_VALUES.len();
+    let next = match STRICT_HOST_KEY_VALUES.iter().position(|value| value.eq_ignore_ascii_case(current)) {
+        Some(index) if forward => (index + 1) % count,
+        Some(index) => (index + count - 1) % count,
+        None => 0,
+    };
+    STRICT_HOST_KEY_VALUES[next].to_string()
+}
+
+/// 文件的修改时间；文件不存在或无法读取时为 None
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// 主机块在 diff 中的行（元数据注释、Host 行和选项），每行以 sign 开头
+fn host_block_lines(host: &SshHost, sign: char) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(folder) = &host.folder {
+        lines.push(format!("{} # @folder: {}", sign, folder));
+    }
+    if let Some(display_name) = &host.display_name {
+        lines.push(format!("{} # @name: {}", sign, display_name));
+    }
+    if let Some(description) = &host.description {
+        lines.push(format!("{} # @description: {}", sign, description));
+    }
+    if !host.visible {
+        lines.push(format!("{} # @visible: false", sign));
+    }
+    if !host.tags.is_empty() {
+        lines.push(format!("{} # @tags: {}", sign, host.tags.join(", ")));
+    }
+    if !host.aliases.is_empty() {
+        lines.push(format!("{} # @alias: {}", sign, host.aliases.join(", ")));
+    }
+    if let Some(style) = &host.style {
+        lines.push(format!("{} # @style: {}", sign, style));
+    }
+    if let Some(color) = &host.color {
+        lines.push(format!("{} # @color: {}", sign, color));
+    }
+    for (key, value) in &host.env {
+        lines.push(format!("{} # @env: {}={}", sign, key, value));
+    }
+    if let Some(connector) = &host.connector {
+        lines.push(format!("{} # @connector: {}", sign, connector));
+    }
+
+    lines.push(format!("{} Host {}", sign, host.name));
+    if let Some(hostname) = &host.hostname {
+        lines.push(format!("{}   HostName {}", sign, hostname));
+    }
+    if let Some(user) = &host.user {
+        lines.push(format!("{}   User {}", sign, user));
+    }
+    if let Some(port) = &host.port {
+        lines.push(format!("{}   Port {}", sign, port));
+    }
+    if let Some(identity_file) = &host.identity_file {
+        lines.push(format!("{}   IdentityFile {}", sign, identity_file));
+    }
+    for (key, value) in &host.other_options {
+        lines.push(format!("{}   {} {}", sign, option_display_name(key), value));
+    }
+    lines
+}
+
+/// 在 choices 中切换到下一个（或上一个）值；不在列表中的值从头开始
+pub fn cycle_choice(choices: &[String], current: &str, forward: bool) -> String {
+    let count = choices.len();
+    if count == 0 {
+        return current.to_string();
+    }
+    let next = match choices.iter().position(|choice| choice == current) {
+        Some(index) if forward => (index + 1) % count,
+        Some(index) => (index + count - 1) % count,
+        None => 0,
+    };
+    choices[next].clone()
+}
+
+/// 保活预设：(名称, ServerAliveInterval, ServerAliveCountMax)
+pub const KEEPALIVE_PRESETS: [(&str, &str, &str); 2] = [("aggressive", "15", "3"), ("relaxed", "60", "5")];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// 底部状态栏的一次性提示，下一次按键时清除
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub text: String,
+    pub level: StatusLevel,
+}
+
+/// 通用的滚动文本弹窗，用于显示操作报告
+#[derive(Debug, Clone)]
+pub struct MessagePopup {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+    pub return_mode: AppMode,
+}
+
+/// 文件夹批量连接的目标
+#[derive(Debug, Clone)]
+pub struct GroupConnect {
+    pub folder: String,
+    pub host_indices: Vec<usize>,
+    pub use_tmux: bool,
+}
+
+/// 导出配置片段弹窗的状态
+#[derive(Debug, Clone)]
+pub struct ExportState {
+    pub host_indices: Vec<usize>,
+    pub options: SnippetOptions,
+    pub csv: bool,  // 导出为表格用的 CSV 而不是配置片段
+    pub file_path: String,
+    pub editing_path: bool,
+    pub status: Option<String>,
+    pub return_mode: AppMode,
+}
+
+/// 删除被其他主机作为跳板引用的主机时的警告状态
+#[derive(Debug, Clone)]
+pub struct DeleteDependents {
+    pub host_index: usize,
+    pub dependents: Vec<usize>,
+    pub retarget: Option<String>,  // 正在输入的新跳板名
+}
+
+/// 检查结果视图的状态
+#[derive(Debug, Clone)]
+pub struct LintState {
+    pub findings: Vec<LintFinding>,
+    pub selected: usize,
+    pub return_mode: AppMode,
+}
+
+/// 两台主机的对照弹窗
+#[derive(Debug, Clone)]
+pub struct HostComparison {
+    pub left: String,
+    pub right: String,
+    pub rows: Vec<CompareRow>,
+    pub scroll: usize,
+    pub return_mode: AppMode,
+}
+
+/// 审阅弹窗用 +/- 调整大小的最多次数
+pub const REVIEW_ZOOM_LIMIT: i16 = 8;
+
+/// 审阅 diff 的最大滚动位置：content_len 行内容在 visible_height 行高的区域中
+pub fn max_review_scroll(content_len: usize, visible_height: usize) -> usize {
+    content_len.saturating_sub(visible_height.max(1))
+}
+
+/// "哪些块会匹配" 弹窗：输入目标，结果随输入更新
+#[derive(Debug, Clone)]
+pub struct DestinationQuery {
+    pub input: String,
+    pub result: DestinationMatch,
+    pub scroll: usize,
+    pub return_mode: AppMode,
+}
+
+impl DestinationQuery {
+    /// 结果的行数：每个块一行标题加上它的选项
+    pub fn line_count(&self) -> usize {
+        self.result.blocks.iter().map(|block| 1 + block.options.len()).sum()
+    }
+}
+
+/// 主机清单同步弹窗：先列出变化，有要删除的机器时再单独确认
+#[derive(Debug, Clone)]
+pub struct InventorySyncView {
+    pub delta: InventoryDelta,
+    /// 保存变更后写入的快照
+    pub snapshot: InventorySnapshot,
+    pub confirm_removal: bool,
+    pub scroll: usize,
+}
+
+impl InventorySyncView {
+    /// 列表的行数：警告、添加、修改、删除各一行
+    pub fn line_count(&self) -> usize {
+        self.delta.warnings.len() + self.delta.added.len() + self.delta.modified.len() + self.delta.removed.len()
+    }
+}
+
+/// ssh -G 有效配置弹窗
+#[derive(Debug, Clone)]
+pub struct EffectiveConfigView {
+    pub host: String,
+    pub rows: Vec<EffectiveRow>,
+    pub scroll: usize,
+    pub return_mode: AppMode,
+}
+
+/// 文件夹批量修改 IdentityFile 的弹窗状态
+#[derive(Debug, Clone)]
+pub struct BulkIdentityState {
+    pub folder: String,
+    pub host_indices: Vec<usize>,
+    pub new_path: String,
+    pub only_when: String,  // 非空时只修改当前值等于它的主机
+    pub field: usize,  // 0: 新路径，1: 过滤条件
+    pub picker: Option<(Vec<String>, usize)>,  // ~/.ssh 中的私钥及选中项
+}
+
+/// 密钥轮换弹窗：把所有引用旧私钥的主机改为新私钥
+#[derive(Debug, Clone)]
+pub struct KeyRotationState {
+    pub old_path: String,
+    pub new_path: String,
+    pub append: bool,  // 保留旧私钥，把新私钥追加为额外的 IdentityFile
+    pub field: usize,  // 0: 旧路径，1: 新路径，2: 替换/追加
+    pub picker: Option<(Vec<String>, usize)>,  // ~/.ssh 中的私钥及选中项
+}
+
+/// 主机健康面板的状态
+#[derive(Debug, Clone)]
+pub struct DashboardState {
+    pub rows: Vec<(usize, ProbeStatus)>,  // 主机下标及探测结果
+    pub selected: usize,
+    pub sort_by_status: bool,
+    pub last_refreshed: Option<Instant>,  // 最近一轮探测开始的时间
+}
+
+/// 审阅 diff 中的搜索状态
+#[derive(Debug, Clone, Default)]
+pub struct ReviewSearch {
+    pub query: String,
+    pub editing: bool,  // 正在输入搜索词
+    pub current: Option<usize>,  // 当前匹配所在的 diff 行
+}
+
+/// 防护确认弹窗保护的操作
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GuardAction {
+    EnterConfig,
+    /// 启动时的 --add：进入配置管理后打开新建主机表单
+    AddHost,
+    DeleteHost,  // Normal 模式下按 Delete 删除选中主机
+    ApplyChanges,
+    SaveAndReload,
+}
+
+/// 防护确认弹窗的状态
+#[derive(Debug, Clone)]
+pub struct GuardPrompt {
+    pub action: GuardAction,
+    pub typed: String,
+    pub opened: Instant,
+    pub return_mode: AppMode,
+}
+
+/// 配置锁被其他实例持有时的选择：只读继续，或抢走锁后继续 action
+#[derive(Debug, Clone)]
+pub struct LockPrompt {
+    pub holder: u32,
+    pub action: GuardAction,
+    pub return_mode: AppMode,
+    pub choice: Selector,
 }
 
+/// 配置锁弹窗中的两个选项
+pub const LOCK_READ_ONLY: usize = 0;
+pub const LOCK_STEAL: usize = 1;
+
+/// Confirm 模式下弹窗打开后需要等待的时间
+pub const GUARD_DELAY: Duration = Duration::from_secs(2);
+
+/// 未绑定按键时页脚反色显示的时长
+pub const FOOTER_FLASH: Duration = Duration::from_millis(400);
+
+/// 单轮 TCP 探测的超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// 待保存变更只有少量，按值保存两份主机比装箱后到处解引用更简单
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum ChangeType {
     Added(SshHost),
@@ -64,6 +584,34 @@ pub enum TreeItem {
     Host { host_index: usize },
 }
 
+/// 列表标题中的位置提示，如 "host 47 of 312 (folder: work)"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListPosition {
+    /// 选中主机的序号（从 1 开始）；选中文件夹或没有选中时为 None
+    pub host: Option<usize>,
+    pub total: usize,
+    /// 选中主机所在的文件夹，或选中的文件夹
+    pub folder: Option<String>,
+}
+
+impl ListPosition {
+    pub fn label(&self) -> String {
+        let folder = self.folder.as_ref().map(|folder| format!(" (folder: {})", folder)).unwrap_or_default();
+        match self.host {
+            Some(host) => format!("host {} of {}{}", host, self.total, folder),
+            None if self.total == 1 => format!("1 host{}", folder),
+            None => format!("{} hosts{}", self.total, folder),
+        }
+    }
+}
+
+/// 树重建前记录的选中项身份，用于重建后恢复选中位置
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionAnchor {
+    Folder(String),
+    Host { name: String, folder: Option<String> },
+}
+
 pub struct App {
     pub hosts: Vec<SshHost>,
     pub original_hosts: Vec<SshHost>,
@@ -77,16 +625,176 @@ pub struct App {
     pub editing_host_index: Option<usize>,
     pub pending_changes: Vec<ChangeType>,
     pub delete_target: Option<usize>,
+    pub delete_typed: String,  // 从 Normal 模式删除生产主机时输入的主机名
+    pub delete_return_mode: AppMode,  // 取消删除时回到的模式
+    pub confirm: Selector,  // 删除确认和放弃编辑确认的是/否选项
     pub review_scroll: usize,
+    pub review_lines: Vec<String>,  // 进入审阅时生成的摘要和 diff，搜索匹配位置基于它
+    pub review_jumps: Vec<usize>,  // 摘要中第 N 行主机对应的 diff 块所在行，数字键跳转
+    pub review_blocks: Vec<usize>,  // 每条待保存变更的 diff 块所在行
+    pub review_selected: usize,  // 审阅中选中的变更，Shift-↑↓ 调整它的顺序
+    pub review_search: ReviewSearch,
+    pub review_height: usize,  // diff 可见行数，渲染时更新
+    pub review_zoom: i16,  // 审阅弹窗用 +/- 调整大小的次数
     pub current_edit_change_index: Option<usize>,
+    pub show_hidden: bool,
+    pub settings: Settings,
+    pub list_height: usize,  // 主机列表可见行数，渲染时更新
+    pub collapsed_folders: HashSet<String>,
+    pub search_collapsed_folders: HashSet<String>,  // 搜索结果中收起的文件夹，搜索词变化时清空
+    pub config_path: Option<PathBuf>,
+    pub included_files: Vec<PathBuf>,  // 主配置通过 Include 读入的文件
+    pub startup_notice: Vec<String>,  // 空列表时显示的说明（例如尝试过的路径）
+    pub pending_connect: Option<usize>,  // 等待主循环执行连接的主机下标
+    pub print_mode: bool,  // --print：选中主机后输出并退出，不连接
+    pub picked: Option<usize>,  // --print 模式下选中的主机下标
+    pub connect_user: Option<String>,  // 这次连接用 -l 临时改用的用户
+    pub session_users: HashMap<String, String>,  // 本次会话中各主机选过的用户，不写入配置
+    pub failures: HashMap<String, HostFailure>,  // 本次会话中各主机最近一次连接失败，按主机名记录
+    pub user_switch: Option<UserSwitchState>,
+    pub connect_confirm: Option<usize>,  // 连接确认弹窗对应的主机下标
+    pub connect_confirm_typed: String,  // 生产主机严格模式下输入的主机名
+    pub needs_redraw: bool,
+    pub connection_error: Option<ConnectionError>,
+    pub connection_error_scroll: usize,
+    pub group_connect: Option<GroupConnect>,
+    pub pending_group_connect: bool,
+    pub message: Option<MessagePopup>,
+    pub export: Option<ExportState>,
+    pub import: Option<ImportState>,
+    pub delete_dependents: Option<DeleteDependents>,
+    pub lint: Option<LintState>,
+    pub linted: bool,  // 本次会话运行过检查，}/{ 才能在有问题的主机间跳转
+    pub effective: Option<EffectiveConfigView>,
+    pub compare_mark: Option<String>,  // 按 = 标记、等待与另一台主机对照的主机名
+    pub comparison: Option<HostComparison>,
+    pub destination_query: Option<DestinationQuery>,
+    pub inventory_sync: Option<InventorySyncView>,
+    /// 已同步但尚未保存的清单快照，保存变更后写入 inventory_snapshot_path
+    pub pending_inventory: Option<InventorySnapshot>,
+    pub inventory_snapshot_path: Option<PathBuf>,
+    pub bulk_identity: Option<BulkIdentityState>,
+    pub key_rotation: Option<KeyRotationState>,
+    pub permission_fix: Vec<PathBuf>,  // 等待确认收紧权限的私钥文件
+    pub new_host_template: Option<SshHost>,  // 新建主机时表单之外的预填选项
+    add_prefill: Option<String>,  // --add 给出的名称或 ssh 命令，进入表单时使用
+    pub adding_temporary: Option<AppMode>,  // 正在新建临时主机时为关闭表单后返回的模式
+    pub dashboard: Option<DashboardState>,
+    pub guard: Option<GuardPrompt>,
+    pub derive: Option<DeriveState>,
+    pub marked_hosts: Vec<String>,  // 配置模式下多选的主机名
+    pub skip_folders: bool,  // ↑↓ 是否跳过文件夹行，初始值来自设置
+    pub zen: bool,  // 简洁模式，初始值来自设置
+    pub tag_editor: Option<TagEditor>,
+    pub transfer: Option<TransferState>,
+    pub pending_transfer: bool,  // 等待主循环执行 scp/sftp
+    pub onboarding: Option<OnboardingState>,
+    pub action_menu: Option<ActionMenu>,
+    pub command_palette: Option<CommandPalette>,
+    pub system_hosts: Vec<SshHost>,  // system_config_paths 中的只读主机，与用户配置同名的不显示
+    pub copy_system: Option<(usize, AppMode)>,  // 询问是否复制到用户配置的系统主机，以及取消后返回的模式
+    pub host_diff: Option<HostDiff>,  // 选中主机未保存修改的逐字段对比
+    pub reference: Option<ReferenceView>,  // 元数据、选项和设置的参考页
+    pub backup_dir: Option<PathBuf>,  // 保存前自动备份主配置的目录；None 时不备份
+    pub backups: Option<BackupBrowser>,
+    pub views_path: Option<PathBuf>,  // 保存的视图（views.toml）；None 时不能保存视图
+    pub view_picker: Option<ViewPicker>,
+    pub pending_onboarding: bool,  // 等待主循环执行向导当前步骤的命令
+    pub transfer_paths: TransferPaths,
+    pub quick_filters: Vec<QuickFilter>,  // 按 QuickFilter::ALL 的顺序保存
+    pub filter_menu_selected: usize,
+    pub filter_menu_return: AppMode,
+    pub connect_queue: Vec<String>,  // 排队依次连接的主机名，按名称保存以免受搜索和重建影响
+    pub queue_running: bool,
+    pub reload_return_mode: AppMode,  // 重新加载确认弹窗关闭后回到的模式
+    pub session_folders: Vec<String>,  // 本次会话在表单中新建、还没有主机保存进去的文件夹
+    pub merge: Option<MergeState>,  // 重新加载时与磁盘内容合并产生的冲突
+    pub loaded_mtimes: Vec<(PathBuf, Option<SystemTime>)>,  // 加载（或保存）时各配置文件的修改时间
+    pub changed_on_disk: Option<PathBuf>,  // 加载后被其他程序修改的配置文件；解决前不允许保存
+    pub disk_changed_hosts: Vec<String>,  // 磁盘上的版本与加载时不同的主机
+    pub read_only: bool,  // --read-only：完全禁止配置管理，比 config_guard 更严格
+    pub theme: Theme,  // 界面样式；NO_COLOR 或 --plain 时为单色
+    pub config_lock: Option<ConfigLock>,  // 配置管理期间持有的锁，回到 Normal 且没有待保存变更时释放
+    pub lock_prompt: Option<LockPrompt>,
+    pub limit_report: LimitReport,  // 解析时超出限制被跳过的内容，不为空时只读
+    pub ssh_binary: SshBinary,  // 启动时查找的 ssh；找不到时禁用连接，F5 重新查找
+    dashboard_run: Option<ProbeRun>,  // 进行中的探测，离开面板时取消
+    pub dns_cache: HashMap<String, DnsStatus>,  // 只有别名的主机的 DNS 结果，按名字缓存
+    pub tasks: TaskRegistry,  // 按主机进行的后台任务（ssh -G、DNS），离开主机或按 Esc 时取消
+    task_focus: Option<String>,  // 上次 tick 时选中的主机，用于发现离开
+    pub multiplexer: Multiplexer,  // 预热中的主连接和本次会话建立的主连接，退出时清理
+    pub status: Option<StatusMessage>,
+    pub footer_flash: Option<Instant>,  // 未绑定按键的提示开始反色显示的时间
+    pub pending_bell: bool,  // 未绑定按键的响铃，在 handle_event 中输出
+    pub history: History,
+    pub deferred: Vec<DeferredLoad>,  // 首帧之后才加载的状态文件
+    pub startup: StartupProfile,
     pub should_quit: bool,
 }
 
 impl App {
     pub fn new() -> Result<Self> {
-        let hosts = parse_ssh_config()?;
+        let mut startup = StartupProfile::default();
+        // 设置决定解析限制和生产主机确认等行为，必须在首帧前就绪
+        let settings = startup.time("settings", Settings::load)?;
+        let mut app = match resolve_home() {
+            Ok(home) => {
+                let config_path = home.join(".ssh").join("config");
+                let tree = startup.time("config parse", || parse_config_tree_with_limits(&config_path, &settings.parse_limits()))?;
+                let mut app = Self::with_config_tree(config_path, tree, settings);
+                app.backup_dir = sshc_file(SshcDir::Data, "backups");
+                app.views_path = sshc_file(SshcDir::Data, "views.toml");
+                app
+            }
+            Err(SshcError::ConfigNotFound { attempted }) => {
+                // 找不到 home 目录时仍然启动，在空列表中说明尝试过的路径
+                let mut app = Self::with_hosts(Vec::new(), None, settings);
+                app.startup_notice = SshcError::ConfigNotFound { attempted: attempted.clone() }
+                    .to_string()
+                    .lines()
+                    .map(str::to_string)
+                    .collect();
+                app.startup_notice.push(String::new());
+                app.startup_notice.push("Tried home directories:".to_string());
+                app.startup_notice.extend(attempted.iter().map(|p| format!("  {}", p.display())));
+                app.startup_notice.push("Set SSHC_HOME or pass --home <DIR> to choose one.".to_string());
+                app
+            }
+            // 明确指定的 home 不存在时不启动，避免误用真实 home 中的配置
+            Err(err) => return Err(err),
+        };
+        app.ssh_binary = startup.time("ssh lookup", SshBinary::locate);
+        app.startup = startup;
+        Ok(app)
+    }
+
+    pub fn with_config_path(config_path: PathBuf, settings: Settings) -> Result<Self> {
+        let tree = parse_config_tree_with_limits(&config_path, &settings.parse_limits())?;
+        Ok(Self::with_config_tree(config_path, tree, settings))
+    }
+
+    /// 用已解析的配置创建界面；历史等状态文件留到首帧之后由 tick 加载
+    fn with_config_tree(config_path: PathBuf, (hosts, included_files, report): (Vec<SshHost>, Vec<PathBuf>, LimitReport), settings: Settings) -> Self {
+        let mut app = Self::with_hosts(hosts, Some(config_path.clone()), settings);
+        app.included_files = included_files;
+        app.load_system_hosts();
+        app.replace_hosts(app.hosts.clone());
+        app.record_load_times();
+        app.apply_limit_report(report);
+        if app.hosts.iter().all(SshHost::is_verbatim) {
+            app.startup_notice = vec![
+                format!("No hosts found in {}", config_path.display()),
+                "Press e then a to add one; the file is created on save.".to_string()
+            ];
+        }
+        app
+    }
+
+    fn with_hosts(hosts: Vec<SshHost>, config_path: Option<PathBuf>, settings: Settings) -> Self {
         let filtered_hosts: Vec<usize> = (0..hosts.len()).collect();
         let list_state = ListState::default();
+        let skip_folders = settings.skip_folder_rows;
+        let zen = settings.zen_mode;
         
         let mut app = App {
             original_hosts: hosts.clone(),
@@ -101,8 +809,109 @@ impl App {
             editing_host_index: None,
             pending_changes: Vec::new(),
             delete_target: None,
+            delete_typed: String::new(),
+            delete_return_mode: AppMode::ConfigManagement,
+            confirm: Selector::confirm(),
             review_scroll: 0,
+            review_lines: Vec::new(),
+            review_jumps: Vec::new(),
+            review_blocks: Vec::new(),
+            review_selected: 0,
+            review_search: ReviewSearch::default(),
+            review_height: 0,
+            review_zoom: 0,
             current_edit_change_index: None,
+            show_hidden: false,
+            settings,
+            list_height: 0,
+            collapsed_folders: HashSet::new(),
+            search_collapsed_folders: HashSet::new(),
+            config_path,
+            included_files: Vec::new(),
+            startup_notice: Vec::new(),
+            ssh_binary: SshBinary::Unchecked,
+            pending_connect: None,
+            print_mode: false,
+            picked: None,
+            connect_user: None,
+            session_users: HashMap::new(),
+            failures: HashMap::new(),
+            user_switch: None,
+            needs_redraw: false,
+            connection_error: None,
+            connection_error_scroll: 0,
+            group_connect: None,
+            pending_group_connect: false,
+            connect_confirm: None,
+            connect_confirm_typed: String::new(),
+            message: None,
+            export: None,
+            delete_dependents: None,
+            lint: None,
+            linted: false,
+            effective: None,
+            compare_mark: None,
+            comparison: None,
+            destination_query: None,
+            inventory_sync: None,
+            pending_inventory: None,
+            inventory_snapshot_path: InventorySnapshot::path(),
+            bulk_identity: None,
+            key_rotation: None,
+            permission_fix: Vec::new(),
+            new_host_template: None,
+            add_prefill: None,
+            adding_temporary: None,
+            dashboard: None,
+            guard: None,
+            derive: None,
+            marked_hosts: Vec::new(),
+            skip_folders,
+            zen,
+            tag_editor: None,
+            transfer: None,
+            pending_transfer: false,
+            onboarding: None,
+            action_menu: None,
+            command_palette: None,
+            system_hosts: Vec::new(),
+            copy_system: None,
+            host_diff: None,
+            reference: None,
+            backup_dir: None,
+            backups: None,
+            views_path: None,
+            view_picker: None,
+            pending_onboarding: false,
+            transfer_paths: TransferPaths::default(),
+            quick_filters: Vec::new(),
+            filter_menu_selected: 0,
+            filter_menu_return: AppMode::Normal,
+            connect_queue: Vec::new(),
+            queue_running: false,
+            reload_return_mode: AppMode::Normal,
+            session_folders: Vec::new(),
+            merge: None,
+            loaded_mtimes: Vec::new(),
+            changed_on_disk: None,
+            disk_changed_hosts: Vec::new(),
+            read_only: false,
+            theme: Theme::default(),
+            config_lock: None,
+            lock_prompt: None,
+            limit_report: LimitReport::default(),
+            dashboard_run: None,
+            dns_cache: HashMap::new(),
+            tasks: TaskRegistry::default(),
+            task_focus: None,
+            multiplexer: Multiplexer::default(),
+            import: None,
+            status: None,
+            footer_flash: None,
+            pending_bell: false,
+            history: History::default(),
+            deferred: DeferredLoad::ALL.to_vec(),
+            startup: StartupProfile::default(),
             should_quit: false,
         };
         
@@ -112,765 +921,5115 @@ impl App {
             app.list_state.select(Some(0));
         }
 
-        Ok(app)
+        app
     }
 
     pub fn handle_event(&mut self, event: Event, terminal: &mut TerminalManager) -> Result<()> {
-        if let Event::Key(key) = event {
-            if key.kind == KeyEventKind::Press {
-                match self.mode {
-                    AppMode::Search => self.handle_search_input(key.code, terminal)?,
-                    AppMode::Normal => self.handle_normal_input(key.code, terminal)?,
-                    AppMode::ConfigManagement => self.handle_config_input(key.code, terminal)?,
-                    AppMode::EditingHost => self.handle_editing_input(key.code, terminal)?,
-                    AppMode::ConfirmDelete => self.handle_delete_confirm_input(key.code)?,
-                    AppMode::ConfirmDiscardEdit => self.handle_discard_edit_confirm_input(key.code)?,
-                    AppMode::ReviewChanges => self.handle_review_input(key.code)?,
-                    AppMode::ShowVersion => self.handle_version_input(key.code)?,
-                }
-            }
+        match event {
+            Event::Key(key) => self.handle_key(key)?,
+            Event::Paste(text) => self.handle_paste(&text),
+            _ => {}
+        }
+        if self.pending_bell {
+            self.pending_bell = false;
+            ring_bell();
         }
-        Ok(())
-    }
 
-    fn handle_search_input(&mut self, key_code: KeyCode, terminal: &mut TerminalManager) -> Result<()> {
-        match key_code {
-            KeyCode::Char(c) => {
-                self.search_query.push(c);
-                self.filter_hosts();
-            }
-            KeyCode::Backspace => {
-                self.search_query.pop();
-                self.filter_hosts();
-            }
-            KeyCode::Enter => {
-                self.mode = AppMode::Normal;
-                // 处理文件夹展开/收起或连接到主机
-                if let Some(selected) = self.list_state.selected() {
-                    if let Some(tree_item) = self.tree_items.get(selected) {
-                        match tree_item {
-                            TreeItem::Folder { .. } => {
-                                self.toggle_folder_expanded(selected);
-                            },
-                            TreeItem::Host { .. } => {
-                                self.connect_to_selected(terminal)?;
-                            }
-                        }
-                    }
-                }
-            }
-            KeyCode::Esc => {
-                self.mode = AppMode::Normal;
+        if let Some(host_index) = self.pending_connect.take() {
+            self.connect_to_host(host_index, terminal)?;
+            if self.queue_running {
+                self.queue_session_finished();
             }
-            _ => {}
+        }
+        if self.pending_group_connect {
+            self.pending_group_connect = false;
+            self.run_group_connect(terminal)?;
+        }
+        if self.pending_transfer {
+            self.pending_transfer = false;
+            self.run_transfer(terminal)?;
+        }
+        if self.pending_onboarding {
+            self.pending_onboarding = false;
+            self.run_onboarding_step(terminal)?;
+        }
+        if self.needs_redraw {
+            terminal.terminal().clear().map_err(|e| SshcError::Terminal(e.to_string()))?;
+            self.needs_redraw = false;
         }
         Ok(())
     }
 
-    fn handle_normal_input(&mut self, key_code: KeyCode, terminal: &mut TerminalManager) -> Result<()> {
-        match key_code {
-            KeyCode::Char('q') => self.should_quit = true,
-            KeyCode::Char('/') => self.mode = AppMode::Search,
-            KeyCode::Char('e') => self.mode = AppMode::ConfigManagement,
-            KeyCode::Char('v') => self.mode = AppMode::ShowVersion,
-            KeyCode::Down => self.next(),
-            KeyCode::Up => self.previous(),
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                // 处理文件夹展开/收起或连接到主机
-                if let Some(selected) = self.list_state.selected() {
-                    if let Some(tree_item) = self.tree_items.get(selected) {
-                        match tree_item {
-                            TreeItem::Folder { .. } => {
-                                self.toggle_folder_expanded(selected);
-                            },
-                            TreeItem::Host { .. } => {
-                                self.connect_to_selected(terminal)?;
-                            }
-                        }
-                    }
-                }
-            },
-            _ => {}
-        }
-        Ok(())
+    /// 主循环空闲时调用，收取后台任务的结果
+    pub fn tick(&mut self) {
+        self.tick_deferred();
+        self.tick_dashboard();
+        self.tick_tasks();
+        self.tick_prewarm();
+        self.check_disk_changes();
     }
 
-    fn handle_config_input(&mut self, key_code: KeyCode, _terminal: &mut TerminalManager) -> Result<()> {
-        match key_code {
-            KeyCode::Esc => {
-                if !self.pending_changes.is_empty() {
-                    self.mode = AppMode::ReviewChanges;
-                } else {
-                    self.mode = AppMode::Normal;
-                    self.config_action = ConfigAction::None;
-                }
+    /// 取消离开的主机的任务，收取已完成的结果，再为选中的主机发起 DNS 查询
+    pub fn tick_tasks(&mut self) {
+        let selected = self.get_selected_host().map(|host| host.name.clone());
+        if self.task_focus != selected {
+            if let Some(previous) = self.task_focus.take() {
+                self.cancel_host_tasks(&previous, false);
             }
-            KeyCode::Char('q') => {
-                if !self.pending_changes.is_empty() {
-                    self.mode = AppMode::ReviewChanges;
-                } else {
-                    self.mode = AppMode::Normal;
+            self.task_focus = selected;
+        }
+
+        for (task, output) in self.tasks.poll() {
+            match output {
+                TaskOutput::Dns(name, status) => {
+                    self.dns_cache.insert(name, status);
                 }
+                TaskOutput::EffectiveConfig(result) => self.finish_effective_config(&task.host, result),
             }
-            KeyCode::Char('a') => {
-                self.start_adding_host();
-            }
-            KeyCode::Char('e') => {
-                self.start_editing_selected_host();
-            }
-            KeyCode::Char('d') => {
-                self.start_deleting_selected_host();
-            }
-            KeyCode::Down => self.next(),
-            KeyCode::Up => self.previous(),
-            _ => {}
         }
-        Ok(())
+        self.tick_dns();
     }
 
-    pub fn filter_hosts(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_hosts = (0..self.hosts.len()).collect();
-            self.rebuild_tree();
-        } else {
-            self.filtered_hosts = self
-                .hosts
-                .iter()
-                .enumerate()
-                .filter(|(_, host)| host.matches_search(&self.search_query))
-                .map(|(i, _)| i)
-                .collect();
-            
-            // 在搜索模式下，显示简单列表而不是树形结构
-            self.tree_items.clear();
-            for &host_index in &self.filtered_hosts {
-                self.tree_items.push(TreeItem::Host { host_index });
+    /// 取消主机的全部后台任务。Esc 取消的 DNS 查询记为已取消，离开主机时则清掉，回来后重新查询
+    fn cancel_host_tasks(&mut self, name: &str, explicit: bool) -> Vec<TaskKind> {
+        let cancelled = self.tasks.cancel_host(name);
+        for target in cancelled.iter().filter_map(|task| task.dns_target()) {
+            if explicit {
+                self.dns_cache.insert(target.to_string(), DnsStatus::Cancelled);
+            } else {
+                self.dns_cache.remove(target);
             }
         }
-        
-        if !self.tree_items.is_empty() {
-            self.list_state.select(Some(0));
-        } else {
-            self.list_state.select(None);
+        if !explicit
+            && let Some(target) = self.hosts.iter().find(|host| host.name == name).and_then(dns_name)
+            && self.dns_cache.get(target) == Some(&DnsStatus::Cancelled)
+        {
+            self.dns_cache.remove(target);
         }
+        cancelled.iter().map(|task| task.kind).collect()
     }
 
-    pub fn next(&mut self) {
-        if self.tree_items.is_empty() {
-            return;
+    /// Esc：取消选中主机进行中的任务；没有任务时返回 false
+    fn cancel_selected_tasks(&mut self) -> bool {
+        let Some(name) = self.get_selected_host().map(|host| host.name.clone()) else {
+            return false;
+        };
+        let kinds = self.cancel_host_tasks(&name, true);
+        if kinds.is_empty() {
+            return false;
+        }
+        let labels: Vec<&str> = kinds.iter().map(TaskKind::label).collect();
+        self.set_status(StatusLevel::Info, format!("Cancelled {} for {}", labels.join(", "), name));
+        true
+    }
+
+    fn finish_effective_config(&mut self, name: &str, result: std::result::Result<String, String>) {
+        // 结果回来时用户已进入其他弹窗或主机已不存在则丢弃
+        let Some(host) = self.hosts.iter().find(|host| host.name == name).cloned() else {
+            return;
+        };
+        if !matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement) {
+            return;
+        }
+        match result {
+            Ok(output) => {
+                let rows = compare_effective(&host, &parse_ssh_g_output(&output), home_dir().as_deref());
+                self.effective = Some(EffectiveConfigView {
+                    host: host.name.clone(),
+                    rows,
+                    scroll: 0,
+                    return_mode: self.mode,
+                });
+                self.mode = AppMode::ShowEffectiveConfig;
+            }
+            Err(error) => self.set_status(StatusLevel::Error, error),
+        }
+    }
+
+    /// 在后台运行 ssh -G，结果由 tick 收取
+    pub fn start_effective_config(&mut self) {
+        let Some(host) = self.get_selected_host().cloned() else {
+            return;
+        };
+        if self.tasks.pending_kind(&host.name, TaskKind::EffectiveConfig) {
+            self.set_status(StatusLevel::Info, format!("Already running ssh -G {} (Esc cancels)", host.name));
+            return;
+        }
+        self.tasks.start_effective_config(&host, self.config_path.as_deref());
+        self.set_status(StatusLevel::Info, format!("Running ssh -G {}... (Esc cancels)", host.name));
+    }
+
+    /// 在后台为选中主机建立 ControlMaster，之后的连接复用它而无需再次认证
+    pub fn start_prewarm(&mut self) {
+        let Some(host) = self.get_selected_host().cloned() else {
+            return;
+        };
+        if self.multiplexer.is_pending(&host.name) {
+            self.set_status(StatusLevel::Info, format!("Already pre-warming {} (W cancels)", host.name));
+            return;
+        }
+        if self.multiplexer.is_live(&host.name) {
+            self.set_status(StatusLevel::Info, format!("{} already has a live master connection (W closes it)", host.name));
+            return;
+        }
+        if self.refuse_without_ssh(true) {
+            return;
+        }
+        match query_effective_config(self.config_path.as_deref(), &host.ssh_args(), &host_env(&host)) {
+            Ok(output) if control_path_configured(&parse_ssh_g_output(&output)) => {}
+            Ok(_) => {
+                self.set_status(StatusLevel::Warning, format!("{} has no ControlPath; set ControlMaster auto and ControlPath to pre-warm it", host.name));
+                return;
+            }
+            Err(error) => {
+                self.set_status(StatusLevel::Error, error);
+                return;
+            }
+        }
+        let timeout = Duration::from_secs(self.settings.prewarm_timeout_secs);
+        match PrewarmJob::start(host.clone(), timeout) {
+            Ok(job) => {
+                self.multiplexer.jobs.push(job);
+                self.set_status(StatusLevel::Info, format!("Pre-warming a master connection to {}... (W cancels)", host.name));
+            }
+            Err(e) => self.set_status(StatusLevel::Error, format!("Unable to run ssh: {}", e)),
+        }
+    }
+
+    /// 取消选中主机的预热，或关闭本次会话为它建立的主连接
+    pub fn teardown_master(&mut self) {
+        let Some(name) = self.get_selected_host().map(|host| host.name.clone()) else {
+            return;
+        };
+        let pending = self.multiplexer.is_pending(&name);
+        match self.multiplexer.teardown(&name) {
+            Some(Ok(())) if pending => self.set_status(StatusLevel::Info, format!("Cancelled pre-warming {}", name)),
+            Some(Ok(())) => self.set_status(StatusLevel::Info, format!("Closed the master connection to {}", name)),
+            Some(Err(error)) => self.set_status(StatusLevel::Error, format!("Closing the master connection to {} failed: {}", name, error)),
+            None => self.set_status(StatusLevel::Info, format!("No master connection to {} was started here", name)),
+        }
+    }
+
+    /// 收取预热结果；失败时在弹窗中显示 ssh 的错误输出
+    fn tick_prewarm(&mut self) {
+        for (name, outcome) in self.multiplexer.poll() {
+            let (title, lines) = match outcome {
+                PrewarmOutcome::Ready => {
+                    self.set_status(StatusLevel::Info, format!("⚡ Master connection to {} is up; connects reuse it", name));
+                    continue;
+                }
+                PrewarmOutcome::Failed(lines) => (format!("Pre-warming {} failed", name), lines),
+                PrewarmOutcome::TimedOut(mut lines) => {
+                    lines.insert(0, format!("ssh did not finish authenticating within {}s and was stopped", self.settings.prewarm_timeout_secs));
+                    (format!("Pre-warming {} timed out", name), lines)
+                }
+            };
+            // 正在其他弹窗中时只在状态栏提示
+            if matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement) {
+                self.show_message(title, lines);
+            } else {
+                self.set_status(StatusLevel::Error, title);
+            }
+        }
+    }
+
+    fn handle_effective_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(view) = &mut self.effective else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+        match key_code {
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = (view.scroll + 1).min(view.rows.len().saturating_sub(1)),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = (view.scroll + 10).min(view.rows.len().saturating_sub(1)),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.mode = view.return_mode;
+                self.effective = None;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 第一次按 = 标记选中的主机，在另一台主机上再按一次打开对照；在标记的主机上再按取消标记
+    pub fn mark_for_comparison(&mut self) {
+        let Some(selected) = self.selected_host_for("compare").map(|index| self.hosts[index].clone()) else {
+            return;
+        };
+        let marked = self.compare_mark.take().and_then(|name| self.hosts.iter().find(|host| host.name == name).cloned());
+        match marked {
+            Some(marked) if marked.name == selected.name => {
+                self.set_status(StatusLevel::Info, "Comparison mark cleared".to_string());
+            }
+            Some(marked) => {
+                self.comparison = Some(HostComparison {
+                    rows: compare_hosts(&marked, &selected),
+                    left: marked.name,
+                    right: selected.name,
+                    scroll: 0,
+                    return_mode: self.mode,
+                });
+                self.mode = AppMode::CompareHosts;
+            }
+            None => {
+                self.set_status(StatusLevel::Info, format!("Marked {} for comparison; select another host and press = again", selected.name));
+                self.compare_mark = Some(selected.name);
+            }
+        }
+    }
+
+    /// Ctrl-W：输入一个 ssh 目标，列出会对它生效的块；预填选中主机的连接目标
+    pub fn open_destination_query(&mut self) {
+        let input = self.get_selected_host()
+            .filter(|host| host_name_problem(&host.name).is_none())
+            .map(|host| host.connect_target().to_string())
+            .unwrap_or_default();
+        self.destination_query = Some(DestinationQuery {
+            result: match_destination(&self.hosts, &input),
+            input,
+            scroll: 0,
+            return_mode: self.mode,
+        });
+        self.mode = AppMode::WhatMatches;
+    }
+
+    fn handle_destination_query_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(query) = &mut self.destination_query else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+        let last = query.line_count().saturating_sub(1);
+        match key_code {
+            KeyCode::Esc => {
+                self.mode = query.return_mode;
+                self.destination_query = None;
+                return Ok(());
+            }
+            KeyCode::Up => query.scroll = query.scroll.saturating_sub(1),
+            KeyCode::Down => query.scroll = (query.scroll + 1).min(last),
+            KeyCode::PageUp => query.scroll = query.scroll.saturating_sub(10),
+            KeyCode::PageDown => query.scroll = (query.scroll + 10).min(last),
+            KeyCode::Backspace => {
+                query.input.pop();
+            }
+            // 目标不含空白
+            KeyCode::Char(c) if !c.is_whitespace() => query.input.push(c),
+            key_code => {
+                self.unbound_key(key_code);
+                return Ok(());
+            }
+        }
+        if matches!(key_code, KeyCode::Backspace | KeyCode::Char(_)) {
+            query.result = match_destination(&self.hosts, &query.input);
+            query.scroll = 0;
+        }
+        Ok(())
+    }
+
+    /// U：获取配置的主机清单并与上次同步的快照比较。网络请求只在这里发生，会阻塞到完成
+    pub fn start_inventory_sync(&mut self) {
+        let Some(source) = self.settings.inventory.clone() else {
+            self.set_status(StatusLevel::Warning, "No inventory source: add an [inventory] table with a url to settings.toml".to_string());
+            return;
+        };
+        // 上次同步后尚未保存时，以那次的结果为基准
+        let snapshot = match (&self.pending_inventory, &self.inventory_snapshot_path) {
+            (Some(pending), _) => pending.clone(),
+            (None, Some(path)) => InventorySnapshot::load_from(path),
+            (None, None) => InventorySnapshot::default(),
+        };
+        let (delta, snapshot) = match sync_inventory(&source, &snapshot, &self.hosts) {
+            Ok(result) => result,
+            Err(e) => {
+                self.set_status(StatusLevel::Error, format!("Inventory sync failed: {:#}", e));
+                return;
+            }
+        };
+
+        if delta.is_empty() && delta.warnings.is_empty() {
+            self.set_status(StatusLevel::Info, format!("Inventory is up to date ({} machines)", snapshot.hosts.len()));
+            self.record_inventory_snapshot(snapshot);
+            return;
+        }
+        self.inventory_sync = Some(InventorySyncView { delta, snapshot, confirm_removal: false, scroll: 0 });
+        self.mode = AppMode::InventorySync;
+    }
+
+    fn handle_inventory_sync_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(view) = &mut self.inventory_sync else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+        let last = view.line_count().saturating_sub(1);
+        if view.confirm_removal {
+            match key_code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.stage_inventory_sync(true),
+                KeyCode::Char('n') | KeyCode::Char('N') => self.stage_inventory_sync(false),
+                KeyCode::Esc => view.confirm_removal = false,
+                key_code => self.unbound_key(key_code),
+            }
+            return Ok(());
+        }
+        match key_code {
+            KeyCode::Esc => {
+                self.inventory_sync = None;
+                self.mode = AppMode::ConfigManagement;
+            }
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = (view.scroll + 1).min(last),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = (view.scroll + 10).min(last),
+            KeyCode::Enter if !view.delta.removed.is_empty() => view.confirm_removal = true,
+            KeyCode::Enter => self.stage_inventory_sync(false),
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 把同步的变化加入待保存变更并打开审阅；remove 为 false 时保留清单中已消失的机器
+    fn stage_inventory_sync(&mut self, remove: bool) {
+        let Some(view) = self.inventory_sync.take() else {
+            return;
+        };
+        let InventoryDelta { added, modified, removed, .. } = view.delta;
+        for host in added {
+            self.pending_changes.push(ChangeType::Added(host.clone()));
+            self.hosts.push(host);
+        }
+        for (old, new) in modified {
+            if let Some(index) = self.hosts.iter().position(|host| host.name == old.name) {
+                self.pending_changes.push(ChangeType::Modified { old, new: new.clone() });
+                self.hosts[index] = new;
+            }
+        }
+        if remove {
+            for host in removed {
+                if let Some(index) = self.hosts.iter().position(|h| h.name == host.name && !h.is_verbatim()) {
+                    self.stage_delete(index);
+                }
+            }
+        }
+        self.filter_hosts();
+        self.record_inventory_snapshot(view.snapshot);
+        if self.pending_changes.is_empty() {
+            self.mode = AppMode::ConfigManagement;
+        } else {
+            self.open_review();
+        }
+    }
+
+    /// 有待保存变更时快照随变更一起保存，否则立即写入
+    fn record_inventory_snapshot(&mut self, snapshot: InventorySnapshot) {
+        if !self.pending_changes.is_empty() {
+            self.pending_inventory = Some(snapshot);
+            return;
+        }
+        self.pending_inventory = None;
+        if let Some(path) = &self.inventory_snapshot_path
+            && let Err(e) = snapshot.save_to(path)
+        {
+            self.set_status(StatusLevel::Warning, format!("{:#}", e));
+        }
+    }
+
+    fn handle_comparison_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(view) = &mut self.comparison else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+        let last = view.rows.len().saturating_sub(1);
+        match key_code {
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = (view.scroll + 1).min(last),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = (view.scroll + 10).min(last),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.mode = view.return_mode;
+                self.comparison = None;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 处理按键，需要终端的操作（连接、强制重绘）记录下来由 handle_event 执行
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        // 只处理按下，松开和重复事件不产生任何效果
+        if key.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        self.status = None;
+        self.footer_flash = None;
+
+        // F5（Normal 模式下也可用 Ctrl-R）从磁盘重新加载
+        let ctrl_r = key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL);
+        let reload = match self.mode {
+            AppMode::Normal => key.code == KeyCode::F(5) || ctrl_r,
+            AppMode::ConfigManagement => key.code == KeyCode::F(5),
+            _ => false,
+        };
+        if reload {
+            self.request_reload();
+            return Ok(());
+        }
+        // 配置在磁盘上被修改后，横幅中的 r 重新加载，O 保留内存中的版本并允许覆盖
+        if self.changed_on_disk.is_some()
+            && matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement)
+            && !key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            match key.code {
+                KeyCode::Char('r') => {
+                    self.request_reload();
+                    return Ok(());
+                }
+                KeyCode::Char('O') => {
+                    self.record_load_times();
+                    self.filter_hosts();
+                    self.set_status(StatusLevel::Warning, "Saving will overwrite the changes made on disk".to_string());
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        if self.mode == AppMode::EditingHost && self.is_form_save_key(&key) {
+            self.save_edited_host();
+            self.needs_redraw = true;
+            return Ok(());
+        }
+        if self.mode == AppMode::EditingHost && key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.revert_current_field();
+            return Ok(());
+        }
+        if matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement)
+            && key.code == KeyCode::Char('z')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.run_command(AppCommand::Zen);
+            return Ok(());
+        }
+        if matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement)
+            && key.code == KeyCode::Char('p')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.open_command_palette();
+            return Ok(());
+        }
+        if matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement)
+            && key.code == KeyCode::Char('n')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.run_command(AppCommand::TemporaryHost);
+            return Ok(());
+        }
+        if matches!(self.mode, AppMode::Normal | AppMode::ConfigManagement)
+            && key.code == KeyCode::Char('w')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.run_command(AppCommand::WhatMatches);
+            return Ok(());
+        }
+        if self.mode == AppMode::Normal && key.code == KeyCode::Enter && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.start_queue();
+            return Ok(());
+        }
+        if self.mode == AppMode::ReviewChanges
+            && !self.review_search.editing
+            && matches!(key.code, KeyCode::Up | KeyCode::Down)
+            && key.modifiers.contains(KeyModifiers::SHIFT)
+        {
+            self.move_review_change(key.code == KeyCode::Down);
+            return Ok(());
+        }
+
+        match self.mode {
+            AppMode::Search => self.handle_search_input(key.code)?,
+            AppMode::Normal => self.handle_normal_input(key.code)?,
+            AppMode::ConfigManagement => self.handle_config_input(key.code)?,
+            AppMode::EditingHost => self.handle_editing_input(key.code)?,
+            AppMode::ConfirmDelete => self.handle_delete_confirm_input(key.code)?,
+            AppMode::ConfirmDiscardEdit => self.handle_discard_edit_confirm_input(key.code)?,
+            AppMode::ReviewChanges => self.handle_review_input(key.code)?,
+            AppMode::ShowVersion => self.handle_version_input(key.code)?,
+            AppMode::ShowConnectionError => self.handle_connection_error_input(key.code)?,
+            AppMode::ConfirmGroupConnect => self.handle_group_connect_confirm_input(key.code)?,
+            AppMode::ConfirmConnect => self.handle_connect_confirm_input(key.code)?,
+            AppMode::ShowMessage => self.handle_message_input(key.code)?,
+            AppMode::ExportHosts => self.handle_export_input(key.code)?,
+            AppMode::ImportSnippet => self.handle_import_input(key)?,
+            AppMode::ConfirmDeleteDependents => self.handle_delete_dependents_input(key.code)?,
+            AppMode::LintView => self.handle_lint_input(key.code)?,
+            AppMode::ShowEffectiveConfig => self.handle_effective_input(key.code)?,
+            AppMode::CompareHosts => self.handle_comparison_input(key.code)?,
+            AppMode::WhatMatches => self.handle_destination_query_input(key.code)?,
+            AppMode::InventorySync => self.handle_inventory_sync_input(key.code)?,
+            AppMode::BulkIdentity => self.handle_bulk_identity_input(key.code)?,
+            AppMode::RotateKey => self.handle_key_rotation_input(key.code)?,
+            AppMode::SwitchUser => self.handle_user_switch_input(key.code)?,
+            AppMode::ResolveConflicts => self.handle_merge_input(key.code)?,
+            AppMode::ConfirmFixPermissions => self.handle_fix_permissions_input(key.code)?,
+            AppMode::ConfirmReviewPending => self.handle_review_pending_input(key.code)?,
+            AppMode::Dashboard => self.handle_dashboard_input(key.code)?,
+            AppMode::ConfirmGuard => self.handle_guard_input(key.code)?,
+            AppMode::ConfigLocked => self.handle_lock_input(key.code),
+            AppMode::ConfirmReload => self.handle_reload_confirm_input(key.code)?,
+            AppMode::QueueNext => self.handle_queue_next_input(key.code)?,
+            AppMode::FilterMenu => self.handle_filter_menu_input(key.code)?,
+            AppMode::DeriveHost => self.handle_derive_input(key.code)?,
+            AppMode::Onboarding => self.handle_onboarding_input(key.code),
+            AppMode::ActionMenu => self.handle_action_menu_input(key.code),
+            AppMode::CommandPalette => self.handle_command_palette_input(key.code),
+            AppMode::ConfirmCopySystem => self.handle_copy_system_input(key.code),
+            AppMode::HostDiff => self.handle_host_diff_input(key.code),
+            AppMode::Reference => self.handle_reference_input(key.code),
+            AppMode::Backups => self.handle_backups_input(key.code)?,
+            AppMode::Views => self.handle_views_input(key.code),
+            AppMode::EditTags => self.handle_tag_editor_input(key.code)?,
+            AppMode::Transfer => self.handle_transfer_input(key.code)?,
+        }
+        // 回到 Normal 且没有待保存变更时释放配置锁，其他实例即可编辑
+        if self.mode == AppMode::Normal && self.pending_changes.is_empty() {
+            self.config_lock = None;
+        }
+        Ok(())
+    }
+
+    /// 终端括号粘贴的内容，只在导入弹窗的输入阶段接收
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.mode == AppMode::ImportSnippet
+            && let Some(import) = &mut self.import
+            && import.stage == ImportStage::Input
+        {
+            import.text.push_str(&text.replace("\r\n", "\n").replace('\r', "\n"));
+        }
+    }
+
+    fn handle_search_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match key_code {
+            KeyCode::Char(c) => {
+                self.search_query.push(c);
+                self.update_search();
+            }
+            KeyCode::Backspace => {
+                self.search_query.pop();
+                self.update_search();
+            }
+            KeyCode::Enter => {
+                self.mode = AppMode::Normal;
+                // 处理文件夹展开/收起或连接到主机
+                if let Some(selected) = self.list_state.selected()
+                    && let Some(tree_item) = self.tree_items.get(selected)
+                {
+                    match tree_item {
+                        TreeItem::Folder { .. } => {
+                            self.toggle_folder_expanded(selected);
+                        },
+                        TreeItem::Host { .. } => {
+                            self.request_connect();
+                        }
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.mode = AppMode::Normal;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn handle_normal_input(&mut self, key_code: KeyCode) -> Result<()> {
+        if let Some(action) = action_for_key(ActionScope::Normal, key_code) {
+            self.run_host_action(action);
+            return Ok(());
+        }
+        if let Some(command) = command_for_key(ActionScope::Normal, key_code) {
+            self.run_command(command);
+            return Ok(());
+        }
+        match key_code {
+            KeyCode::Char('.') | KeyCode::Char('o') => self.open_action_menu(ActionScope::Normal),
+            KeyCode::Char(':') => self.open_command_palette(),
+            KeyCode::Esc if self.cancel_selected_tasks() => {}
+            KeyCode::Esc => self.clear_quick_filters(),
+            KeyCode::Char(c @ '1'..='9') if self.settings.quick_connect_digits => {
+                let slot = (c as usize) - ('1' as usize);
+                if let Some(&tree_index) = self.quick_connect_targets().get(slot) {
+                    self.list_state.select(Some(tree_index));
+                    self.request_connect();
+                }
+            }
+            KeyCode::Down => self.next(),
+            KeyCode::Up => self.previous(),
+            KeyCode::Left => self.collapse_or_parent(),
+            KeyCode::Right => self.expand_or_child(),
+            KeyCode::Char(' ') => self.activate_selected(),
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// Enter/Space：展开或收起文件夹，或连接到主机
+    fn activate_selected(&mut self) {
+        if let Some(selected) = self.list_state.selected()
+            && let Some(tree_item) = self.tree_items.get(selected)
+        {
+            match tree_item {
+                TreeItem::Folder { .. } => {
+                    self.toggle_folder_expanded(selected);
+                },
+                TreeItem::Host { .. } => {
+                    self.request_connect();
+                }
+            }
+        }
+    }
+
+    fn handle_config_input(&mut self, key_code: KeyCode) -> Result<()> {
+        // 有标记的主机时 T 也打开标签编辑器，没有标记时仍是派生主机
+        if key_code == KeyCode::Char('T') && !self.marked_hosts.is_empty() {
+            self.run_host_action(HostAction::EditTags);
+            return Ok(());
+        }
+        if let Some(action) = action_for_key(ActionScope::Config, key_code) {
+            self.run_host_action(action);
+            return Ok(());
+        }
+        if let Some(command) = command_for_key(ActionScope::Config, key_code) {
+            self.run_command(command);
+            return Ok(());
+        }
+        match key_code {
+            KeyCode::Esc if self.cancel_selected_tasks() => {}
+            KeyCode::Esc if !self.marked_hosts.is_empty() => self.marked_hosts.clear(),
+            KeyCode::Esc => {
+                if !self.pending_changes.is_empty() {
+                    self.open_review();
+                } else {
+                    self.mode = AppMode::Normal;
+                    self.config_action = ConfigAction::None;
+                }
+            }
+            KeyCode::Char('.') | KeyCode::Char('o') => self.open_action_menu(ActionScope::Config),
+            KeyCode::Char(':') => self.open_command_palette(),
+            KeyCode::Down => self.next(),
+            KeyCode::Up => self.previous(),
+            KeyCode::Left => self.collapse_or_parent(),
+            KeyCode::Right => self.expand_or_child(),
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 当前模式中没有绑定的按键：按设置响铃或在页脚反色提示，修饰键等不提示
+    fn unbound_key(&mut self, key_code: KeyCode) {
+        if is_ignored_key(key_code) {
+            return;
+        }
+        match self.settings.unbound_key_feedback {
+            UnboundKeyFeedback::Off => {}
+            UnboundKeyFeedback::Bell => self.pending_bell = true,
+            UnboundKeyFeedback::Flash => {
+                // 只有主列表和配置管理能用 . 打开操作菜单，弹窗的按键写在各自的帮助行中
+                let text = match self.mode {
+                    AppMode::Normal | AppMode::ConfigManagement => format!("unbound key: {} — press . for actions", key_name(key_code)),
+                    _ => format!("unbound key: {}", key_name(key_code)),
+                };
+                self.set_status(StatusLevel::Warning, text);
+                self.footer_flash = Some(Instant::now());
+            }
+        }
+    }
+
+    /// 页脚是否还在反色提示未绑定的按键
+    pub fn footer_flashing(&self) -> bool {
+        self.footer_flash.is_some_and(|started| started.elapsed() < FOOTER_FLASH)
+    }
+
+    /// 执行登记在 HOST_ACTIONS 中的操作，按键和操作菜单都经过这里
+    pub fn run_host_action(&mut self, action: HostAction) {
+        match action {
+            HostAction::Connect => self.activate_selected(),
+            HostAction::ConnectAs => self.start_user_switch(),
+            HostAction::Transfer => self.start_transfer(),
+            HostAction::EffectiveConfig => self.start_effective_config(),
+            HostAction::Compare => self.mark_for_comparison(),
+            HostAction::Prewarm => self.start_prewarm(),
+            HostAction::CloseMaster => self.teardown_master(),
+            HostAction::Queue => self.toggle_queued(),
+            HostAction::Delete if self.mode == AppMode::Normal => self.start_quick_delete(),
+            HostAction::Delete => self.start_deleting_selected_host(),
+            HostAction::Edit => self.start_editing_selected_host(),
+            HostAction::Derive => self.start_derive(),
+            HostAction::EditTags => self.start_tag_editor(),
+            HostAction::ToggleDisabled => self.toggle_selected_disabled(),
+            HostAction::KeepTemporary => self.promote_selected_temporary(),
+            HostAction::RotateKey => {
+                // 以选中主机的私钥作为旧路径
+                let old_path = self.get_selected_host().and_then(|h| h.identity_file.clone());
+                self.start_key_rotation(old_path.unwrap_or_default());
+            }
+            HostAction::Mark => self.toggle_marked(),
+            HostAction::ShowChanges => self.show_host_diff(),
+        }
+    }
+
+    /// 打开选中主机的操作菜单，只列出适用于它的操作
+    pub fn open_action_menu(&mut self, scope: ActionScope) {
+        let Some(host) = self.get_selected_host() else {
+            self.set_status(StatusLevel::Warning, "Select a host to see its actions".to_string());
+            return;
+        };
+        let master_open = self.multiplexer.is_live(&host.name) || self.multiplexer.is_pending(&host.name);
+        self.action_menu = Some(ActionMenu::new(scope, host, master_open, self.read_only, !self.marked_hosts.is_empty()));
+        self.mode = AppMode::ActionMenu;
+    }
+
+    fn handle_action_menu_input(&mut self, key_code: KeyCode) {
+        let Some(menu) = &mut self.action_menu else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        let action = match menu.choice.handle_key(key_code) {
+            SelectorEvent::Accepted(index) => menu.actions.get(index).map(|binding| binding.action),
+            SelectorEvent::Moved => return,
+            SelectorEvent::Cancelled => None,
+            // 菜单中直接按操作的按键同样执行；再按一次 . 或 o 关闭
+            SelectorEvent::Unhandled => match menu.action_for_key(key_code) {
+                Some(action) => Some(action),
+                None if matches!(key_code, KeyCode::Char('.') | KeyCode::Char('o')) => None,
+                None => {
+                    self.unbound_key(key_code);
+                    return;
+                }
+            },
+        };
+        self.mode = match menu.scope {
+            ActionScope::Normal => AppMode::Normal,
+            ActionScope::Config => AppMode::ConfigManagement,
+        };
+        self.action_menu = None;
+        if let Some(action) = action {
+            self.run_host_action(action);
+        }
+    }
+
+    /// 执行登记在 COMMANDS 中的命令，按键和命令面板都经过这里
+    pub fn run_command(&mut self, command: AppCommand) {
+        match command {
+            AppCommand::Quit => self.should_quit = true,
+            AppCommand::LeaveConfig => {
+                if !self.pending_changes.is_empty() {
+                    self.open_review();
+                } else {
+                    self.mode = AppMode::Normal;
+                }
+            }
+            AppCommand::Search => self.mode = AppMode::Search,
+            AppCommand::ConfigManagement => self.start_config_management(),
+            AppCommand::About => self.mode = AppMode::ShowVersion,
+            AppCommand::ToggleHidden => self.toggle_show_hidden(),
+            AppCommand::GroupConnect => self.start_group_connect(),
+            AppCommand::Export => self.start_export(),
+            AppCommand::Import => {
+                self.import = Some(ImportState::new());
+                self.mode = AppMode::ImportSnippet;
+            }
+            AppCommand::Lint => self.start_lint(),
+            AppCommand::NextChange => self.jump_to_change(true),
+            AppCommand::PreviousChange => self.jump_to_change(false),
+            AppCommand::NextFinding => self.jump_to_finding(true),
+            AppCommand::PreviousFinding => self.jump_to_finding(false),
+            AppCommand::Dashboard => self.start_dashboard(),
+            AppCommand::ConnectRemembered => self.connect_as_remembered_user(),
+            AppCommand::SkipFolders => self.toggle_skip_folders(),
+            AppCommand::VerbatimBlocks => self.show_verbatim_blocks(),
+            AppCommand::KeyReport => self.show_key_report(),
+            AppCommand::ClearQueue => self.clear_queue(),
+            AppCommand::StartQueue => self.start_queue(),
+            AppCommand::FilterMenu => self.open_filter_menu(),
+            AppCommand::Views => self.open_views(),
+            AppCommand::AddHost => self.start_adding_host(),
+            AppCommand::AddFromClipboard => self.add_host_from_clipboard(),
+            AppCommand::ToggleFolderHidden => {
+                if let Some(selected) = self.list_state.selected() {
+                    self.toggle_folder_visibility(selected);
+                }
+            }
+            AppCommand::InventorySync => self.start_inventory_sync(),
+            AppCommand::Backups => self.open_backups(),
+            AppCommand::BulkIdentity => self.start_bulk_identity(),
+            AppCommand::Onboarding => self.start_onboarding(),
+            AppCommand::Reload => self.request_reload(),
+            AppCommand::Zen => self.zen = !self.zen,
+            AppCommand::TemporaryHost => self.start_adding_temporary_host(),
+            AppCommand::WhatMatches => self.open_destination_query(),
+        }
+    }
+
+    /// 命令在当前状态下是否有意义，命令面板据此灰显；按键仍然执行并给出原来的提示
+    pub fn command_available(&self, command: AppCommand) -> bool {
+        let selected = self.list_state.selected().and_then(|i| self.tree_items.get(i));
+        match command {
+            AppCommand::ConfigManagement => !self.read_only,
+            AppCommand::GroupConnect | AppCommand::ToggleFolderHidden => matches!(selected, Some(TreeItem::Folder { .. })),
+            AppCommand::BulkIdentity => match selected {
+                Some(TreeItem::Folder { .. }) => true,
+                Some(TreeItem::Host { host_index }) => self.hosts.get(*host_index).is_some_and(|host| host.folder.is_some()),
+                None => false,
+            },
+            AppCommand::ConnectRemembered => self.get_selected_host().is_some(),
+            AppCommand::NextChange | AppCommand::PreviousChange => !self.pending_changes.is_empty(),
+            AppCommand::NextFinding | AppCommand::PreviousFinding => self.linted,
+            AppCommand::VerbatimBlocks => self.hosts.iter().any(|host| host.is_verbatim()),
+            AppCommand::ClearQueue | AppCommand::StartQueue => !self.connect_queue.is_empty(),
+            AppCommand::InventorySync => self.settings.inventory.is_some(),
+            _ => true,
+        }
+    }
+
+    /// 打开命令面板：当前界面的主机操作和命令，不适用的灰显
+    pub fn open_command_palette(&mut self) {
+        let scope = match self.mode {
+            AppMode::ConfigManagement => ActionScope::Config,
+            _ => ActionScope::Normal,
+        };
+        let host = self.get_selected_host();
+        let master_open = host.is_some_and(|host| self.multiplexer.is_live(&host.name) || self.multiplexer.is_pending(&host.name));
+        let mut entries: Vec<PaletteEntry> = actions_in(scope)
+            .map(|binding| PaletteEntry {
+                action: PaletteAction::Host(binding.action),
+                label: binding.label,
+                key: binding.key_label(),
+                enabled: host.is_some_and(|host| binding.action.applies_to(host, master_open, self.read_only, !self.marked_hosts.is_empty())),
+            })
+            .collect();
+        entries.extend(commands_in(scope).map(|binding| PaletteEntry {
+            action: PaletteAction::Command(binding.command),
+            label: binding.label,
+            key: binding.key_label(),
+            enabled: self.command_available(binding.command),
+        }));
+        self.command_palette = Some(CommandPalette::new(scope, entries));
+        self.mode = AppMode::CommandPalette;
+    }
+
+    fn handle_command_palette_input(&mut self, key_code: KeyCode) {
+        let Some(palette) = &mut self.command_palette else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        let entry = match palette.choice.handle_key(key_code) {
+            SelectorEvent::Accepted(_) => palette.selected().cloned(),
+            SelectorEvent::Moved => return,
+            SelectorEvent::Cancelled => None,
+            // 其余按键都是过滤输入
+            SelectorEvent::Unhandled => {
+                match key_code {
+                    KeyCode::Char(c) => palette.push(c),
+                    KeyCode::Backspace => palette.pop(),
+                    key_code => self.unbound_key(key_code),
+                }
+                return;
+            }
+        };
+        let scope = palette.scope;
+        if let Some(entry) = &entry && !entry.enabled {
+            self.set_status(StatusLevel::Warning, format!("Not available here: {}", entry.label));
+            return;
+        }
+        self.mode = match scope {
+            ActionScope::Normal => AppMode::Normal,
+            ActionScope::Config => AppMode::ConfigManagement,
+        };
+        self.command_palette = None;
+        match entry.map(|entry| entry.action) {
+            Some(PaletteAction::Host(action)) => self.run_host_action(action),
+            Some(PaletteAction::Command(command)) => self.run_command(command),
+            None => {}
+        }
+    }
+
+    /// 重建展示列表，并尽量让选中项停留在原来的主机（或文件夹）上
+    pub fn filter_hosts(&mut self) {
+        let anchor = self.selection_anchor();
+        let previous = self.list_state.selected();
+        self.rebuild_filtered_tree();
+        if !self.restore_selection(anchor.as_ref()) {
+            self.select_nearest(previous);
+        }
+    }
+
+    /// 搜索词变化时，原选中项不再匹配则回到第一条结果
+    fn update_search(&mut self) {
+        let anchor = self.selection_anchor();
+        self.search_collapsed_folders.clear();
+        self.rebuild_filtered_tree();
+        if !self.restore_selection(anchor.as_ref()) {
+            self.select_nearest(Some(0));
+        }
+    }
+
+    pub fn selection_anchor(&self) -> Option<SelectionAnchor> {
+        let selected = self.list_state.selected()?;
+        match self.tree_items.get(selected)? {
+            TreeItem::Folder { name, .. } => Some(SelectionAnchor::Folder(name.clone())),
+            TreeItem::Host { host_index } => {
+                self.hosts.get(*host_index).map(|host| SelectionAnchor::Host {
+                    name: host.name.clone(),
+                    folder: host.folder.clone(),
+                })
+            }
+        }
+    }
+
+    /// 选中与 anchor 对应的行；主机在收起的文件夹中时选中该文件夹。找不到返回 false
+    pub fn restore_selection(&mut self, anchor: Option<&SelectionAnchor>) -> bool {
+        let Some(anchor) = anchor else {
+            return false;
+        };
+
+        let folder_row = |items: &[TreeItem], folder: &str| {
+            items.iter().position(|item| matches!(item, TreeItem::Folder { name, .. } if name == folder))
+        };
+
+        let position = match anchor {
+            SelectionAnchor::Folder(folder) => folder_row(&self.tree_items, folder),
+            SelectionAnchor::Host { name, folder } => {
+                self.tree_items
+                    .iter()
+                    .position(|item| match item {
+                        TreeItem::Host { host_index } =>
+                            self.hosts.get(*host_index).is_some_and(|h| h.name == *name),
+                        TreeItem::Folder { .. } => false,
+                    })
+                    .or_else(|| folder.as_deref().and_then(|f| folder_row(&self.tree_items, f)))
+            }
+        };
+
+        if position.is_some() {
+            self.list_state.select(position);
+        }
+        position.is_some()
+    }
+
+    fn select_nearest(&mut self, previous: Option<usize>) {
+        if self.tree_items.is_empty() {
+            self.list_state.select(None);
+        } else {
+            let index = previous.unwrap_or(0).min(self.tree_items.len() - 1);
+            self.list_state.select(Some(index));
+        }
+    }
+
+    fn rebuild_filtered_tree(&mut self) {
+        if self.search_query.is_empty() && self.quick_filters.is_empty() {
+            self.filtered_hosts = (0..self.hosts.len()).collect();
+            self.rebuild_tree();
+        } else {
+            // 只有快速过滤时按平常的规则隐藏主机，除非正在过滤 hidden
+            let respect_hidden = self.search_query.is_empty()
+                && !self.show_hidden
+                && !self.quick_filters.contains(&QuickFilter::Hidden);
+            self.filtered_hosts = self
+                .hosts
+                .iter()
+                .enumerate()
+                .filter(|(_, host)| self.search_query.is_empty() || host.matches_search(&self.search_query))
+                .filter(|(_, host)| !respect_hidden || host.visible)
+                .filter(|(_, host)| !host.is_verbatim())
+                .filter(|(_, host)| {
+                    let pending = self.has_pending_change(&host.name);
+                    let on_disk = self.disk_changed_hosts.contains(&host.name);
+                    let inherited = if self.quick_filters.iter().any(|filter| filter.uses_inherited()) {
+                        inherited_options(&self.hosts, host)
+                    } else {
+                        Vec::new()
+                    };
+                    self.quick_filters.iter().all(|filter| filter.matches(host, &inherited, pending, on_disk))
+                })
+                .map(|(i, _)| i)
+                .collect();
+            
+            // 搜索结果保留文件夹分组：只显示含匹配项的文件夹，默认展开
+            let matches = self.filtered_hosts.clone();
+            self.build_tree(&matches, true);
+        }
+    }
+
+    /// 该主机是否有未保存的新增或修改
+    fn has_pending_change(&self, name: &str) -> bool {
+        self.pending_changes.iter().any(|change| match change {
+            ChangeType::Added(host) => host.name == name,
+            ChangeType::Modified { new, .. } => new.name == name,
+            ChangeType::Deleted(_) => false,
+        })
+    }
+
+    /// 从选中的主机派生隧道等新条目
+    pub fn start_derive(&mut self) {
+        let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)) else {
+            self.set_status(StatusLevel::Warning, "Select a host to derive a new entry from".to_string());
+            return;
+        };
+        self.derive = Some(DeriveState::new(*host_index));
+        self.mode = AppMode::DeriveHost;
+    }
+
+    fn handle_derive_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.derive else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+
+        let Some(template) = state.template else {
+            match state.picker.handle_key(key_code) {
+                SelectorEvent::Accepted(index) => {
+                    let template = DeriveTemplate::ALL[index];
+                    state.template = Some(template);
+                    state.inputs = template.defaults();
+                    state.field = 0;
+                }
+                SelectorEvent::Cancelled => {
+                    self.derive = None;
+                    self.mode = AppMode::ConfigManagement;
+                }
+                SelectorEvent::Moved => {}
+                SelectorEvent::Unhandled => self.unbound_key(key_code),
+            }
+            return Ok(());
+        };
+
+        let fields = template.fields().len();
+        match key_code {
+            KeyCode::Esc => {
+                state.template = None;
+                state.error = None;
+            }
+            KeyCode::Tab | KeyCode::Down => state.field = (state.field + 1) % fields,
+            KeyCode::BackTab | KeyCode::Up => state.field = (state.field + fields - 1) % fields,
+            KeyCode::Backspace => {
+                state.inputs[state.field].pop();
+            }
+            KeyCode::Char(c) if !c.is_whitespace() => state.inputs[state.field].push(c),
+            KeyCode::Enter => self.finish_derive(template),
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 生成派生主机并作为新增变更暂存
+    fn finish_derive(&mut self, template: DeriveTemplate) {
+        let Some(state) = &self.derive else {
+            return;
+        };
+        let Some(base) = self.hosts.get(state.base) else {
+            return;
+        };
+        let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+        match derive_host(base, template, &state.inputs, &taken) {
+            Ok(host) => {
+                let anchor = SelectionAnchor::Host { name: host.name.clone(), folder: host.folder.clone() };
+                self.set_status(StatusLevel::Info, format!("Added {} (unsaved)", host.name));
+                self.pending_changes.push(ChangeType::Added(host.clone()));
+                self.hosts.push(host);
+                self.derive = None;
+                self.mode = AppMode::ConfigManagement;
+                self.filter_hosts();
+                self.restore_selection(Some(&anchor));
+            }
+            Err(error) => {
+                if let Some(state) = &mut self.derive {
+                    state.error = Some(error);
+                }
+            }
+        }
+    }
+
+    /// 新服务器向导：主机信息、密钥、ssh-copy-id、连接测试，最后暂存新增的主机
+    pub fn start_onboarding(&mut self) {
+        let keys = home_dir().map(|home| list_private_keys(&home.join(".ssh"))).unwrap_or_default();
+        self.onboarding = Some(OnboardingState::new(keys));
+        self.mode = AppMode::Onboarding;
+    }
+
+    fn handle_onboarding_input(&mut self, key_code: KeyCode) {
+        let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+        let Some(state) = &mut self.onboarding else {
+            self.mode = AppMode::ConfigManagement;
+            return;
+        };
+
+        if key_code == KeyCode::Esc {
+            if !state.back() {
+                self.onboarding = None;
+                self.mode = AppMode::ConfigManagement;
+            }
+            return;
+        }
+        match state.step {
+            OnboardingStep::Details => {
+                let fields = state.inputs.len();
+                match key_code {
+                    KeyCode::Tab | KeyCode::Down => state.field = (state.field + 1) % fields,
+                    KeyCode::BackTab | KeyCode::Up => state.field = (state.field + fields - 1) % fields,
+                    KeyCode::Backspace => {
+                        state.inputs[state.field].pop();
+                    }
+                    KeyCode::Char(c) if !c.is_whitespace() => state.inputs[state.field].push(c),
+                    KeyCode::Enter => {
+                        let _ = state.submit_details(&taken);
+                    }
+                    key_code => self.unbound_key(key_code),
+                }
+            }
+            OnboardingStep::Key => match state.key_picker.handle_key(key_code) {
+                SelectorEvent::Accepted(index) => {
+                    let ssh_dir = home_dir().map(|home| home.join(".ssh")).unwrap_or_default();
+                    self.pending_onboarding = state.choose_key(index, &ssh_dir);
+                }
+                SelectorEvent::Unhandled if matches!(key_code, KeyCode::Char('s' | 'S')) => {
+                    state.skip();
+                }
+                SelectorEvent::Unhandled => self.unbound_key(key_code),
+                _ => {}
+            },
+            OnboardingStep::CopyId | OnboardingStep::Test => match key_code {
+                KeyCode::Enter | KeyCode::Char('r' | 'R') => self.pending_onboarding = true,
+                KeyCode::Char('s' | 'S') => {
+                    state.skip();
+                }
+                key_code => self.unbound_key(key_code),
+            },
+            OnboardingStep::Summary => match key_code {
+                KeyCode::Enter => self.finish_onboarding(),
+                key_code => self.unbound_key(key_code),
+            },
+        }
+    }
+
+    /// 在终端外执行向导当前步骤的命令；ssh-keygen 和 ssh-copy-id 需要交互输入
+    fn run_onboarding_step(&mut self, terminal: &mut TerminalManager) -> Result<()> {
+        let Some(state) = &self.onboarding else {
+            return Ok(());
+        };
+        let (program, args) = match state.step {
+            OnboardingStep::Key => {
+                let Some(path) = &state.generating else {
+                    return Ok(());
+                };
+                ("ssh-keygen", state.keygen_args(&expand_tilde(path)))
+            }
+            OnboardingStep::CopyId => ("ssh-copy-id", state.copy_id_args(state.key.as_deref().map(expand_tilde).as_deref())),
+            OnboardingStep::Test => ("ssh", state.test_args()),
+            OnboardingStep::Details | OnboardingStep::Summary => return Ok(()),
+        };
+
+        let result = if state.step == OnboardingStep::Test {
+            // BatchMode 不会询问任何内容，不需要离开界面
+            match Command::new(program).args(&args).stdin(Stdio::null()).output() {
+                Ok(output) if output.status.success() => Ok("connected without a password".to_string()),
+                Ok(output) => {
+                    let stderr: Vec<String> = String::from_utf8_lossy(&output.stderr).lines().map(|line| line.trim_end().to_string()).collect();
+                    let exit = output.status.code().map_or("signal".to_string(), |code| format!("exit {}", code));
+                    Err(format!("{} ({})", classify_failure(output.status.code(), &stderr).label(), exit))
+                }
+                Err(e) => Err(format!("Unable to run {}: {}", program, e)),
+            }
+        } else {
+            terminal.suspend_with(self.settings.connection_screen == ConnectionScreen::Keep)?;
+            let status = Command::new(program).args(&args).status();
+            terminal.resume()?;
+            self.needs_redraw = true;
+            match status {
+                Ok(status) if status.success() => Ok(match &state.generating {
+                    Some(path) => format!("generated {}", path),
+                    None => "key installed on the server".to_string(),
+                }),
+                Ok(status) => Err(format!("{} failed ({})", program, status.code().map_or("signal".to_string(), |code| format!("exit {}", code)))),
+                Err(e) => Err(format!("Unable to run {}: {}", program, e)),
+            }
+        };
+
+        if let Some(state) = &mut self.onboarding {
+            state.finish_step(result);
+        }
+        Ok(())
+    }
+
+    /// 把向导填写的主机作为新增变更暂存，状态栏汇总各步结果
+    fn finish_onboarding(&mut self) {
+        let Some(state) = self.onboarding.take() else {
+            return;
+        };
+        let host = state.host();
+        let anchor = SelectionAnchor::Host { name: host.name.clone(), folder: host.folder.clone() };
+        let skipped = OnboardingStep::ALL
+            .iter()
+            .filter(|step| !matches!(state.outcome(**step), StepOutcome::Done(_)))
+            .map(|step| step.label())
+            .collect::<Vec<_>>();
+        let text = if skipped.is_empty() {
+            format!("Added {} (unsaved)", host.name)
+        } else {
+            format!("Added {} (unsaved); not completed: {}", host.name, skipped.join(", "))
+        };
+        self.set_status(if skipped.is_empty() { StatusLevel::Info } else { StatusLevel::Warning }, text);
+        self.pending_changes.push(ChangeType::Added(host.clone()));
+        self.hosts.push(host);
+        self.mode = AppMode::ConfigManagement;
+        self.filter_hosts();
+        self.restore_selection(Some(&anchor));
+    }
+
+    /// 标记/取消标记选中的主机；选中文件夹时对其中所有主机整体切换
+    pub fn toggle_marked(&mut self) {
+        let names: Vec<String> = match self.list_state.selected().and_then(|i| self.tree_items.get(i)) {
+            Some(TreeItem::Host { host_index }) => vec![self.hosts[*host_index].name.clone()],
+            Some(TreeItem::Folder { children_indices, .. }) => children_indices.iter().map(|&i| self.hosts[i].name.clone()).collect(),
+            None => return,
+        };
+        if names.iter().all(|name| self.marked_hosts.contains(name)) {
+            self.marked_hosts.retain(|name| !names.contains(name));
+        } else {
+            for name in names {
+                if !self.marked_hosts.contains(&name) {
+                    self.marked_hosts.push(name);
+                }
+            }
+        }
+    }
+
+    /// 为标记的主机打开批量标签编辑器
+    pub fn start_tag_editor(&mut self) {
+        if self.marked_hosts.is_empty() {
+            self.set_status(StatusLevel::Warning, "Mark hosts with Space to edit their tags".to_string());
+            return;
+        }
+        let hosts: Vec<&SshHost> = self.marked_hosts
+            .iter()
+            .filter_map(|name| self.hosts.iter().find(|host| host.name == *name))
+            .collect();
+        if hosts.is_empty() {
+            self.marked_hosts.clear();
+            return;
+        }
+        self.tag_editor = Some(TagEditor::new(&hosts));
+        self.mode = AppMode::EditTags;
+    }
+
+    fn handle_tag_editor_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(editor) = &mut self.tag_editor else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+        // 输入框有内容时 Enter 先把新标签加入列表，否则应用
+        if key_code == KeyCode::Enter && !editor.input.trim().is_empty() {
+            editor.add_input();
+            return Ok(());
+        }
+        match editor.picker.handle_key(key_code) {
+            SelectorEvent::Cancelled => {
+                self.tag_editor = None;
+                self.mode = AppMode::ConfigManagement;
+            }
+            SelectorEvent::Accepted(_) => self.apply_tag_editor(),
+            SelectorEvent::Moved => {}
+            SelectorEvent::Unhandled => match key_code {
+                KeyCode::Char(' ') => editor.toggle(editor.picker.selected),
+                KeyCode::Char(c) => editor.input.push(c),
+                KeyCode::Backspace => {
+                    editor.input.pop();
+                }
+                // 没有标签行时 Enter 同样应用
+                KeyCode::Enter => self.apply_tag_editor(),
+                key_code => self.unbound_key(key_code),
+            },
+        }
+        Ok(())
+    }
+
+    /// 只为标签集合确实变化的主机记录修改
+    fn apply_tag_editor(&mut self) {
+        let Some(editor) = self.tag_editor.take() else {
+            return;
+        };
+        let mut changed = 0;
+        for name in &editor.hosts {
+            // 系统主机只读，不改标签
+            let Some(host_index) = self.hosts.iter().position(|host| host.name == *name && !host.system) else {
+                continue;
+            };
+            let old_host = self.hosts[host_index].clone();
+            let tags = editor.apply(&old_host.tags);
+            if tags == old_host.tags {
+                continue;
+            }
+            let mut new_host = old_host.clone();
+            new_host.tags = tags;
+            if !old_host.temporary {
+                self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+            }
+            self.hosts[host_index] = new_host;
+            changed += 1;
+        }
+        self.mode = AppMode::ConfigManagement;
+        self.filter_hosts();
+        self.set_status(StatusLevel::Info, format!("Tags staged for {} of {} hosts", changed, editor.hosts.len()));
+    }
+
+    pub fn open_filter_menu(&mut self) {
+        self.filter_menu_return = self.mode;
+        self.filter_menu_selected = 0;
+        self.mode = AppMode::FilterMenu;
+    }
+
+    pub fn toggle_quick_filter(&mut self, filter: QuickFilter) {
+        if self.quick_filters.contains(&filter) {
+            self.quick_filters.retain(|f| *f != filter);
+        } else {
+            self.quick_filters.push(filter);
+            self.quick_filters.sort_by_key(|f| QuickFilter::ALL.iter().position(|a| a == f));
+        }
+        self.update_search();
+    }
+
+    pub fn clear_quick_filters(&mut self) {
+        if self.quick_filters.is_empty() {
+            return;
+        }
+        self.quick_filters.clear();
+        self.filter_hosts();
+    }
+
+    /// 当前列表状态，可保存为视图
+    pub fn current_view(&self) -> ViewState {
+        ViewState {
+            query: self.search_query.clone(),
+            quick_filters: self.quick_filters.clone(),
+            show_hidden: self.show_hidden,
+        }
+    }
+
+    /// 完整还原视图保存的状态，未保存的部分回到默认值
+    pub fn apply_view(&mut self, view: ViewState) {
+        self.search_query = view.query;
+        self.quick_filters = view.quick_filters;
+        self.show_hidden = view.show_hidden;
+        self.update_search();
+    }
+
+    pub fn open_views(&mut self) {
+        let Some(path) = &self.views_path else {
+            self.set_status(StatusLevel::Warning, "Saved views are not available without a data directory".to_string());
+            return;
+        };
+        // 文件损坏时不打开，以免保存时覆盖其中的视图
+        match load_views(path) {
+            Ok(views) => {
+                self.view_picker = Some(ViewPicker::new(views, self.mode));
+                self.mode = AppMode::Views;
+            }
+            Err(e) => self.set_status(StatusLevel::Error, format!("{:#}", e)),
+        }
+    }
+
+    /// 写回视图文件；失败时在状态栏提示，选择器中的修改保留到关闭
+    fn write_views(&mut self, done: String) {
+        let (Some(path), Some(picker)) = (&self.views_path, &self.view_picker) else {
+            return;
+        };
+        match save_views(path, &picker.views) {
+            Ok(()) => self.set_status(StatusLevel::Info, done),
+            Err(e) => self.set_status(StatusLevel::Error, format!("Unable to save views: {:#}", e)),
+        }
+    }
+
+    fn handle_views_input(&mut self, key_code: KeyCode) {
+        let current = self.current_view();
+        let Some(picker) = &mut self.view_picker else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+
+        if let Some((prompt, name)) = &mut picker.prompt {
+            match key_code {
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Esc => picker.prompt = None,
+                KeyCode::Enter => {
+                    let name = name.trim().to_string();
+                    let prompt = prompt.clone();
+                    let except = match prompt {
+                        ViewPrompt::New => None,
+                        ViewPrompt::Rename(index) => Some(index),
+                    };
+                    if name.is_empty() {
+                        self.set_status(StatusLevel::Warning, "Enter a name for the view".to_string());
+                    } else if picker.name_taken(&name, except) {
+                        self.set_status(StatusLevel::Warning, format!("A view named '{}' already exists; select it and press u to update it", name));
+                    } else {
+                        picker.prompt = None;
+                        let done = match prompt {
+                            ViewPrompt::New => {
+                                picker.views.push(current.to_view(&name));
+                                picker.choice.set_len(picker.views.len());
+                                picker.choice.selected = picker.views.len() - 1;
+                                format!("Saved view '{}': {}", name, current.summary())
+                            }
+                            ViewPrompt::Rename(index) => {
+                                let old = std::mem::replace(&mut picker.views[index].name, name.clone());
+                                format!("Renamed view '{}' to '{}'", old, name)
+                            }
+                        };
+                        self.write_views(done);
+                    }
+                }
+                key_code => self.unbound_key(key_code),
+            }
+            return;
+        }
+
+        let selected = picker.choice.selected;
+        match picker.choice.handle_key(key_code) {
+            SelectorEvent::Accepted(index) => {
+                let view = &picker.views[index];
+                match view.migrate() {
+                    Ok(state) => {
+                        let name = view.name.clone();
+                        self.mode = picker.return_mode;
+                        self.view_picker = None;
+                        self.apply_view(state);
+                        self.set_status(StatusLevel::Info, format!("View '{}' applied", name));
+                    }
+                    Err(e) => self.set_status(StatusLevel::Warning, e),
+                }
+            }
+            SelectorEvent::Cancelled => {
+                self.mode = picker.return_mode;
+                self.view_picker = None;
+            }
+            SelectorEvent::Moved => {}
+            SelectorEvent::Unhandled => match key_code {
+                KeyCode::Char('q') | KeyCode::Char('\'') => {
+                    self.mode = picker.return_mode;
+                    self.view_picker = None;
+                }
+                KeyCode::Char('s') | KeyCode::Char('a') => picker.prompt = Some((ViewPrompt::New, String::new())),
+                KeyCode::Char('r' | 'u' | 'd') | KeyCode::Delete if picker.views.is_empty() => {}
+                KeyCode::Char('r') => picker.prompt = Some((ViewPrompt::Rename(selected), picker.views[selected].name.clone())),
+                KeyCode::Char('u') => {
+                    let view = &mut picker.views[selected];
+                    *view = current.to_view(&view.name);
+                    let done = format!("Updated view '{}': {}", view.name, current.summary());
+                    self.write_views(done);
+                }
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    let view = picker.views.remove(selected);
+                    picker.choice.set_len(picker.views.len());
+                    self.write_views(format!("Deleted view '{}'", view.name));
+                }
+                key_code => self.unbound_key(key_code),
+            },
+        }
+    }
+
+    fn handle_filter_menu_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let count = QuickFilter::ALL.len();
+        match key_code {
+            KeyCode::Up => self.filter_menu_selected = (self.filter_menu_selected + count - 1) % count,
+            KeyCode::Down => self.filter_menu_selected = (self.filter_menu_selected + 1) % count,
+            KeyCode::Enter | KeyCode::Char(' ') => self.toggle_quick_filter(QuickFilter::ALL[self.filter_menu_selected]),
+            KeyCode::Char(c @ '1'..='6') => {
+                let index = c as usize - '1' as usize;
+                self.filter_menu_selected = index;
+                self.toggle_quick_filter(QuickFilter::ALL[index]);
+            }
+            KeyCode::Char('c') => self.clear_quick_filters(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('\\') => self.mode = self.filter_menu_return,
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    pub fn next(&mut self) {
+        self.step_selection(true);
+    }
+
+    pub fn previous(&mut self) {
+        self.step_selection(false);
+    }
+
+    /// 移动一行，两端循环；跳过文件夹时只停在主机行，没有主机行时仍停在文件夹上
+    fn step_selection(&mut self, forward: bool) {
+        let len = self.tree_items.len();
+        if len == 0 {
+            return;
+        }
+        let skip = self.skip_folders && self.tree_items.iter().any(|item| matches!(item, TreeItem::Host { .. }));
+        let mut selected = self.list_state.selected();
+        for _ in 0..len {
+            let i = match selected {
+                Some(i) if forward => (i + 1) % len,
+                Some(i) => (i + len - 1) % len,
+                None => 0,
+            };
+            selected = Some(i);
+            if !skip || matches!(self.tree_items[i], TreeItem::Host { .. }) {
+                break;
+            }
+        }
+        self.list_state.select(selected);
+    }
+
+    /// 从选中行开始在可见的树中找下一台（或上一台）满足条件的主机，到头后回绕，跳过文件夹行
+    fn find_host_row(&self, forward: bool, matches: impl Fn(&SshHost) -> bool) -> Option<usize> {
+        let len = self.tree_items.len();
+        let start = self.list_state.selected().unwrap_or(if forward { len.saturating_sub(1) } else { 0 });
+        (1..=len)
+            .map(|step| if forward { (start + step) % len } else { (start + len - step) % len })
+            .find(|&row| match self.tree_items[row] {
+                TreeItem::Host { host_index } => self.hosts.get(host_index).is_some_and(&matches),
+                TreeItem::Folder { .. } => false,
+            })
+    }
+
+    /// ]/[：跳到下一台/上一台有待保存变更的主机，状态栏说明是什么变更
+    pub fn jump_to_change(&mut self, forward: bool) {
+        let names: Vec<&str> = self.pending_changes
+            .iter()
+            .filter_map(|change| match change {
+                ChangeType::Added(host) | ChangeType::Modified { new: host, .. } => Some(host.name.as_str()),
+                ChangeType::Deleted(_) => None,
+            })
+            .collect();
+        let Some(row) = self.find_host_row(forward, |host| !host.temporary && names.contains(&host.name.as_str())) else {
+            self.set_status(StatusLevel::Info, "No listed host has a pending change".to_string());
+            return;
+        };
+        self.list_state.select(Some(row));
+        let Some(name) = self.get_selected_host().map(|host| host.name.clone()) else {
+            return;
+        };
+        let diff = host_diff(&self.pending_changes, &name);
+        let text = if diff.added {
+            format!("{}: new host", name)
+        } else if diff.fields.is_empty() {
+            format!("{}: modified", name)
+        } else {
+            format!("{}: modified {}", name, diff.fields.iter().map(|change| change.field.as_str()).collect::<Vec<_>>().join(", "))
+        };
+        self.set_status(StatusLevel::Info, text);
+    }
+
+    /// }/{：跳到下一台/上一台有检查问题的主机；本次会话运行过检查（L）后才可用，问题按当前配置重新检查
+    pub fn jump_to_finding(&mut self, forward: bool) {
+        if !self.linted {
+            self.set_status(StatusLevel::Warning, "Run the checks (L) first to jump between findings".to_string());
+            return;
+        }
+        let findings = self.lint_findings();
+        let Some(row) = self.find_host_row(forward, |host| findings.iter().any(|finding| finding.host == host.name)) else {
+            self.set_status(StatusLevel::Info, "No listed host has a lint finding".to_string());
+            return;
+        };
+        self.list_state.select(Some(row));
+        let Some(name) = self.get_selected_host().map(|host| host.name.clone()) else {
+            return;
+        };
+        let mut host_findings = findings.iter().filter(|finding| finding.host == name);
+        let Some(first) = host_findings.next() else {
+            return;
+        };
+        let more = match host_findings.count() {
+            0 => String::new(),
+            count => format!(" (+{} more)", count),
+        };
+        let level = if first.severity == LintSeverity::Error { StatusLevel::Error } else { StatusLevel::Warning };
+        self.set_status(level, format!("{}: {}{}", name, first.message, more));
+    }
+
+    pub fn toggle_skip_folders(&mut self) {
+        self.skip_folders = !self.skip_folders;
+        let text = if self.skip_folders { "↑↓ skip folder rows" } else { "↑↓ stop on folder rows" };
+        self.set_status(StatusLevel::Info, text.to_string());
+    }
+
+    /// ←：在展开的文件夹上收起它，在文件夹内的主机上跳到所属文件夹
+    pub fn collapse_or_parent(&mut self) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        match self.tree_items.get(selected) {
+            Some(TreeItem::Folder { expanded: true, .. }) => self.toggle_folder_expanded(selected),
+            Some(TreeItem::Host { host_index }) => {
+                let host_index = *host_index;
+                let parent = self.tree_items[..selected]
+                    .iter()
+                    .rposition(|item| matches!(item, TreeItem::Folder { children_indices, .. } if children_indices.contains(&host_index)));
+                if let Some(parent) = parent {
+                    self.list_state.select(Some(parent));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// →：展开收起的文件夹，在展开的文件夹上进入第一台主机
+    pub fn expand_or_child(&mut self) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        match self.tree_items.get(selected) {
+            Some(TreeItem::Folder { expanded: false, .. }) => self.toggle_folder_expanded(selected),
+            Some(TreeItem::Folder { expanded: true, children_indices, .. }) if !children_indices.is_empty() => {
+                if matches!(self.tree_items.get(selected + 1), Some(TreeItem::Host { .. })) {
+                    self.list_state.select(Some(selected + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 当前可见窗口内前九个主机行（跳过文件夹行）在 tree_items 中的下标，对应数字键 1-9
+    pub fn quick_connect_targets(&self) -> Vec<usize> {
+        let start = self.list_state.offset();
+        let end = (start + self.list_height).min(self.tree_items.len());
+
+        (start..end)
+            .filter(|&i| matches!(self.tree_items[i], TreeItem::Host { .. }))
+            .take(9)
+            .collect()
+    }
+
+    /// 记录待连接的选中主机，由主循环在拿到终端后执行
+    pub fn request_connect(&mut self) {
+        self.request_connect_as(None);
+    }
+
+    /// 同 request_connect；user 不为空时这次连接以该用户登录
+    fn request_connect_as(&mut self, user: Option<String>) {
+        self.connect_user = user;
+        let target = self.list_state.selected().and_then(|selected| {
+            match self.tree_items.get(selected) {
+                Some(TreeItem::Host { host_index }) => Some(*host_index),
+                _ => None,
+            }
+        });
+        if target.is_some_and(|i| self.refuse_disabled(i)) {
+            return;
+        }
+
+        // --print 只输出选中的主机，不连接，也不需要确认
+        if self.print_mode {
+            if target.is_some() {
+                self.picked = target;
+                self.should_quit = true;
+            }
+            return;
+        }
+        if self.refuse_without_ssh(target.is_some_and(|i| uses_ssh(&self.hosts[i]))) {
+            return;
+        }
+
+        // 生产主机无论全局设置如何都要确认
+        let protected = target.is_some_and(|i| self.connect_protection(i).is_some());
+        if (self.settings.confirm_connect || protected) && target.is_some() {
+            self.connect_confirm = target;
+            self.connect_confirm_typed.clear();
+            self.mode = AppMode::ConfirmConnect;
+        } else {
+            self.pending_connect = target;
+        }
+    }
+
+    /// 主机命中的生产标签规则
+    pub fn connect_protection(&self, host_index: usize) -> Option<ProtectionRule> {
+        protection_rule(self.hosts.get(host_index)?, &self.settings.production_labels)
+    }
+
+    /// 确认时是否要求输入主机名
+    pub fn connect_confirm_strict(&self) -> bool {
+        self.settings.production_type_name && self.connect_confirm.is_some_and(|i| self.connect_protection(i).is_some())
+    }
+
+    fn handle_connect_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
+        if self.connect_confirm_strict() {
+            match key_code {
+                KeyCode::Char(c) => self.connect_confirm_typed.push(c),
+                KeyCode::Backspace => {
+                    self.connect_confirm_typed.pop();
+                }
+                KeyCode::Enter => {
+                    let expected = self.connect_confirm.map(|i| self.hosts[i].connect_target().to_string());
+                    if expected.as_deref() == Some(self.connect_confirm_typed.trim()) {
+                        self.pending_connect = self.connect_confirm.take();
+                        self.mode = AppMode::Normal;
+                    }
+                }
+                KeyCode::Esc => {
+                    self.connect_confirm = None;
+                    self.connect_user = None;
+                    self.mode = AppMode::Normal;
+                }
+                key_code => self.unbound_key(key_code),
+            }
+            return Ok(());
+        }
+        match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.pending_connect = self.connect_confirm.take();
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.connect_confirm = None;
+                self.connect_user = None;
+                self.mode = AppMode::Normal;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    pub fn connect_to_host(&mut self, host_index: usize, terminal: &mut TerminalManager) -> Result<()> {
+        let user = self.connect_user.take();
+        let Some(host) = self.hosts.get(host_index).cloned() else {
+            return Ok(());
+        };
+        // 连接器模板缺少值时不离开界面
+        let command = match connect_command(&host, user.as_deref(), &self.settings.connectors) {
+            Ok(command) => command,
+            Err(e) => {
+                self.set_status(StatusLevel::Error, e);
+                return Ok(());
+            }
+        };
+        let (program, args) = command.split_first().expect("connect command is never empty");
+        let ssh = uses_ssh(&host);
+        let name = host.name.clone();
+        let env = host_env(&host);
+        let messages = self.settings.connection_messages;
+        let connecting = messages.then(|| connecting_message(&host, user.as_deref()));
+        if self.settings.connection_screen == ConnectionScreen::Exec {
+            return exec_command(program, args, &env, connecting.as_deref(), terminal);
+        }
+        terminal.suspend_with(self.settings.connection_screen == ConnectionScreen::Keep)?;
+        if let Some(text) = &connecting {
+            terminal.print_outside(text, Color::Cyan)?;
+        }
+
+        let started = Instant::now();
+        let status = Command::new(program)
+            .args(args)
+            .envs(env.iter().cloned())
+            .status();
+        let elapsed = started.elapsed();
+
+        // 很快失败时趁还在终端外，非交互地重跑一次 ssh 收集 stderr；其他连接器没有对应的方式
+        let threshold = Duration::from_millis(self.settings.quick_failure_threshold_ms);
+        let captured = match &status {
+            Ok(status) if ssh && self.settings.capture_failed_connections && is_quick_failure(status, elapsed, threshold) => {
+                Some(ConnectionError {
+                    host: name.clone(),
+                    exit_code: status.code(),
+                    stderr: capture_ssh_stderr(args, &env),
+                })
+            }
+            _ => None,
+        };
+
+        if messages && terminal.print_outside(&disconnected_message(&name), Color::Yellow)? {
+            terminal.wait_for_key()?;
+        }
+        terminal.resume()?;
+
+        match status {
+            Ok(status) => {
+                if should_notify(elapsed, self.settings.notify_after_minutes) {
+                    notify_session_end(&name, elapsed, status.code());
+                }
+                let failure = if ssh {
+                    is_connection_failure(&status, elapsed, threshold).then(|| {
+                        let stderr = captured.as_ref().map_or(&[][..], |error| error.stderr.as_slice());
+                        classify_failure(status.code(), stderr)
+                    })
+                } else {
+                    is_quick_failure(&status, elapsed, threshold).then_some(FailureReason::Exit)
+                };
+                self.record_connection_result(&name, status.code(), failure);
+                self.record_session(&name, elapsed, status.code());
+                if let Some(error) = captured {
+                    self.connection_error = Some(error);
+                    self.connection_error_scroll = 0;
+                    self.mode = AppMode::ShowConnectionError;
+                }
+            },
+            // 自定义连接器的程序可能没有安装，只提示而不退出
+            Err(e) if !ssh => self.set_status(StatusLevel::Error, format!("Unable to run {}: {}", program, e)),
+            Err(e) => return Err(SshcError::Ssh(format!("SSH connection error: {}", e))),
+        }
+        Ok(())
+    }
+
+    /// 选中主机的下标，选中文件夹时给出提示
+    fn selected_host_for(&mut self, action: &str) -> Option<usize> {
+        match self.list_state.selected().and_then(|i| self.tree_items.get(i)) {
+            Some(TreeItem::Host { host_index }) => Some(*host_index),
+            _ => {
+                self.set_status(StatusLevel::Warning, format!("Select a host to {}", action));
+                None
+            }
+        }
+    }
+
+    /// 打开以其他用户连接的选择弹窗
+    pub fn start_user_switch(&mut self) {
+        let Some(host_index) = self.selected_host_for("connect as another user") else {
+            return;
+        };
+        let remembered = self.session_users.get(&self.hosts[host_index].name).map(String::as_str);
+        self.user_switch = Some(UserSwitchState::new(host_index, &self.hosts, remembered));
+        self.mode = AppMode::SwitchUser;
+    }
+
+    /// 以本次会话中为该主机选过的用户直接连接；还没选过时打开选择弹窗
+    pub fn connect_as_remembered_user(&mut self) {
+        let Some(host_index) = self.selected_host_for("connect as another user") else {
+            return;
+        };
+        match self.session_users.get(&self.hosts[host_index].name).cloned() {
+            Some(user) => self.request_connect_as(Some(user)),
+            None => self.start_user_switch(),
+        }
+    }
+
+    fn handle_user_switch_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.user_switch else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+        match key_code {
+            KeyCode::Esc => {
+                self.user_switch = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Up => state.select_previous(),
+            KeyCode::Down => state.select_next(),
+            KeyCode::Char(c) => state.push_char(c),
+            KeyCode::Backspace => {
+                state.custom.pop();
+            }
+            KeyCode::Enter => {
+                let Some(user) = state.user() else {
+                    return Ok(());
+                };
+                let name = self.hosts[state.host_index].name.clone();
+                self.user_switch = None;
+                self.mode = AppMode::Normal;
+                self.session_users.insert(name, user.clone());
+                self.request_connect_as(Some(user));
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 加载下一项推迟的状态文件；每次 tick 只做一项，让界面尽早响应按键
+    fn tick_deferred(&mut self) {
+        if !self.deferred.is_empty() {
+            let load = self.deferred.remove(0);
+            self.load_deferred(load);
+        }
+    }
+
+    fn load_deferred(&mut self, load: DeferredLoad) {
+        let started = Instant::now();
+        match load {
+            // 加载前内存中已有的记录更新，合并在磁盘内容之上
+            DeferredLoad::History => {
+                let mut history = History::load();
+                history.merge(&self.history);
+                self.history = history;
+            }
+            DeferredLoad::TransferPaths => {
+                let mut paths = TransferPaths::load();
+                paths.merge(&self.transfer_paths);
+                self.transfer_paths = paths;
+            }
+        }
+        self.startup.record(load.label(), started.elapsed());
+    }
+
+    /// 需要用到或写回状态文件之前立即加载还没加载的部分，避免用空内容覆盖磁盘上的文件
+    fn ensure_deferred_loaded(&mut self) {
+        for load in std::mem::take(&mut self.deferred) {
+            self.load_deferred(load);
+        }
+    }
+
+    /// 为选中的主机打开 scp/sftp 弹窗
+    pub fn start_transfer(&mut self) {
+        self.ensure_deferred_loaded();
+        let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)) else {
+            self.set_status(StatusLevel::Warning, "Select a host to transfer files".to_string());
+            return;
+        };
+        let host_index = *host_index;
+        self.transfer = Some(TransferState::new(host_index, &self.hosts[host_index].name, &self.transfer_paths));
+        self.mode = AppMode::Transfer;
+    }
+
+    fn handle_transfer_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.transfer else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+        state.error = None;
+        match key_code {
+            KeyCode::Esc => {
+                self.transfer = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Tab => state.move_field(true),
+            KeyCode::BackTab => state.move_field(false),
+            KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right if state.field == 0 => state.cycle_kind(),
+            // 路径字段中 ↑↓ 浏览最近用过的路径
+            KeyCode::Up | KeyCode::Down => {
+                let history = state.history(&self.transfer_paths).to_vec();
+                state.step_history(&history, key_code == KeyCode::Down);
+            }
+            KeyCode::Char(c) => {
+                if let Some(value) = state.value_mut() {
+                    value.push(c);
+                    state.history_index = None;
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(value) = state.value_mut() {
+                    value.pop();
+                    state.history_index = None;
+                }
+            }
+            KeyCode::Enter => {
+                let target = self.hosts[state.host_index].connect_target().to_string();
+                match state.command(&target) {
+                    Ok(_) => {
+                        self.pending_transfer = true;
+                        self.mode = AppMode::Normal;
+                    }
+                    Err(error) => state.error = Some(error),
+                }
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 在终端外执行 scp/sftp，成功后记住用过的路径
+    fn run_transfer(&mut self, terminal: &mut TerminalManager) -> Result<()> {
+        let Some(state) = self.transfer.take() else {
+            return Ok(());
+        };
+        let Some(host) = self.hosts.get(state.host_index) else {
+            return Ok(());
+        };
+        let Ok((program, args)) = state.command(host.connect_target()) else {
+            return Ok(());
+        };
+        let env = host_env(host);
+
+        terminal.suspend_with(self.settings.connection_screen == ConnectionScreen::Keep)?;
+        let status = Command::new(program)
+            .args(host.temporary_ssh_options())
+            .args(&args)
+            .envs(env.iter().cloned())
+            .status();
+        terminal.resume()?;
+        self.needs_redraw = true;
+
+        match status {
+            Ok(status) if status.success() => {
+                self.transfer_paths.remember_remote(&state.host, &state.remote);
+                if state.kind.uses_local_path() {
+                    self.transfer_paths.remember_local(&state.local);
+                }
+                match self.transfer_paths.save() {
+                    Ok(()) => self.set_status(StatusLevel::Info, format!("{} with {} finished", state.kind.label(), state.host)),
+                    Err(e) => self.set_status(StatusLevel::Warning, format!("Unable to save transfer paths: {}", e)),
+                }
+            }
+            Ok(status) => {
+                let exit = status.code().map_or("signal".to_string(), |code| format!("exit {}", code));
+                self.set_status(StatusLevel::Error, format!("{} with {} failed ({})", state.kind.label(), state.host, exit));
+            }
+            Err(e) => self.set_status(StatusLevel::Error, format!("Unable to run {}: {}", program, e)),
+        }
+        Ok(())
+    }
+
+    /// 记录会话时长到历史并在状态栏显示；不到一秒的非零退出通常是连接失败
+    fn record_session(&mut self, name: &str, elapsed: Duration, exit_code: Option<i32>) {
+        self.ensure_deferred_loaded();
+        self.history.record_session(name, elapsed, exit_code);
+        let saved = self.history.save();
+
+        let exit = exit_code.map_or("signal".to_string(), |code| format!("exit {}", code));
+        if exit_code != Some(0) && elapsed < Duration::from_secs(1) {
+            self.set_status(
+                StatusLevel::Error,
+                format!("Connection to {} failed after {} ({}) — likely never connected", name, format_duration(elapsed), exit)
+            );
+        } else {
+            self.set_status(
+                StatusLevel::Info,
+                format!("Session to {} lasted {} ({})", name, format_duration(elapsed), exit)
+            );
+        }
+
+        if let Err(e) = saved {
+            self.set_status(StatusLevel::Warning, format!("Unable to save history: {}", e));
+        }
+    }
+
+    /// 记录连接结果：失败时记下原因，列表中显示 "!" 标记；成功连接后清除
+    pub fn record_connection_result(&mut self, name: &str, exit_code: Option<i32>, failure: Option<FailureReason>) {
+        self.ensure_deferred_loaded();
+        let Some(reason) = failure else {
+            self.failures.remove(name);
+            if let Some(entry) = self.history.hosts.get_mut(name) {
+                entry.last_failure = None;
+            }
+            return;
+        };
+        let at = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let failure = HostFailure { exit_code, reason: reason.label().to_string(), at };
+        if self.settings.remember_failures {
+            self.history.hosts.entry(name.to_string()).or_default().last_failure = Some(failure.clone());
+        }
+        self.failures.insert(name.to_string(), failure);
+    }
+
+    /// 主机最近一次连接失败；开启 remember_failures 时也包括以前会话中记下的
+    pub fn last_failure(&self, name: &str) -> Option<&HostFailure> {
+        self.failures.get(name).or_else(|| {
+            self.history.hosts
+                .get(name)
+                .filter(|_| self.settings.remember_failures)
+                .and_then(|entry| entry.last_failure.as_ref())
+        })
+    }
+
+    pub fn set_status(&mut self, level: StatusLevel, text: String) {
+        self.status = Some(StatusMessage { text, level });
+    }
+
+    pub fn get_selected_host(&self) -> Option<&SshHost> {
+        self.list_state.selected()
+            .and_then(|selected| self.tree_items.get(selected))
+            .and_then(|tree_item| match tree_item {
+                TreeItem::Host { host_index } => self.hosts.get(*host_index),
+                TreeItem::Folder { .. } => None,
+            })
+    }
+
+    /// 打开空的新建主机表单，预填选中的文件夹及其默认值
+    fn start_adding_host(&mut self) {
+        let folder = self.list_position().folder;
+        self.open_new_host_form(SshHost::new(String::new()), folder.as_deref());
+    }
+
+    /// 打开新建主机表单：host 是明确给出的内容，空着的字段由文件夹和全局默认值补上（见 prefill_new_host）
+    fn open_new_host_form(&mut self, host: SshHost, folder: Option<&str>) {
+        let host = prefill_new_host(host, folder, &self.settings);
+        // 表单之外的选项（如 -J、-o 和默认的跳板机）保存时从模板带入
+        self.new_host_template = Some(host.clone());
+        self.adding_temporary = None;
+        // 以只有默认值的表单为起点：明确给出的字段显示 modified、名称照常检查，默认值本身不算改动
+        let defaults = prefill_new_host(SshHost::new(String::new()), folder, &self.settings);
+        let mut editing_data = EditingHostData::new(HostFormFields::from_host(&defaults));
+        editing_data.current = HostFormFields::from_host(&host);
+        self.editing_host = Some(editing_data);
+        self.editing_host_index = None;
+        self.current_edit_change_index = None;
+        self.mode = AppMode::EditingHost;
+    }
+
+    /// 新建只在本次会话中存在的主机
+    fn start_adding_temporary_host(&mut self) {
+        let return_mode = self.mode;
+        self.start_adding_host();
+        self.adding_temporary = Some(return_mode);
+    }
+
+    /// 把临时主机转为普通的新增变更，保存后写入配置
+    fn promote_selected_temporary(&mut self) {
+        let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)).cloned() else {
+            return;
+        };
+        let Some(host) = self.hosts.get_mut(host_index).filter(|host| host.temporary) else {
+            self.set_status(StatusLevel::Warning, "Only temporary hosts can be added to the config".to_string());
+            return;
+        };
+        host.temporary = false;
+        let host = host.clone();
+        self.set_status(StatusLevel::Info, format!("{} will be added to the config when you save", host.name));
+        self.pending_changes.push(ChangeType::Added(host));
+    }
+
+    /// 停用或重新启用选中的主机，写入时整个块被注释掉或还原
+    fn toggle_selected_disabled(&mut self) {
+        let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)).cloned() else {
+            return;
+        };
+        if self.refuse_read_only_host(host_index) {
+            return;
+        }
+        let old_host = self.hosts[host_index].clone();
+        if old_host.temporary {
+            self.set_status(StatusLevel::Warning, "Temporary hosts are not in the config and cannot be disabled".to_string());
+            return;
+        }
+        let new_host = SshHost { disabled: !old_host.disabled, ..old_host.clone() };
+        let action = if new_host.disabled { "disabled" } else { "re-enabled" };
+        self.set_status(StatusLevel::Info, format!("{} will be {} when you save", new_host.name, action));
+        self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+        self.hosts[host_index] = new_host;
+        self.filter_hosts();
+    }
+
+    /// 停用的主机已从 ssh 配置中注释掉，无法连接
+    fn refuse_disabled(&mut self, host_index: usize) -> bool {
+        let Some(host) = self.hosts.get(host_index).filter(|host| host.disabled) else {
+            return false;
+        };
+        let text = format!("{} is disabled; re-enable it with x in config management to connect", host.name);
+        self.set_status(StatusLevel::Warning, text);
+        true
+    }
+
+    /// 找不到 ssh 且这次连接需要它时给出提示并返回 true；用自定义连接器的主机不受影响
+    fn refuse_without_ssh(&mut self, needs_ssh: bool) -> bool {
+        if !needs_ssh || !self.ssh_binary.is_missing() {
+            return false;
+        }
+        self.set_status(StatusLevel::Warning, "ssh binary not found — connections are disabled (F5: check again)".to_string());
+        true
+    }
+
+    /// 重新查找 ssh；F5 时与重新加载配置一起进行，找到后连接恢复可用
+    pub fn recheck_ssh_binary(&mut self) {
+        if self.ssh_binary == SshBinary::Unchecked {
+            return;
+        }
+        let was_missing = self.ssh_binary.is_missing();
+        self.ssh_binary = SshBinary::locate();
+        match &self.ssh_binary {
+            SshBinary::Found(path) if was_missing => {
+                self.set_status(StatusLevel::Info, format!("Found ssh at {}; connections are enabled", path.display()));
+            }
+            SshBinary::Missing if !was_missing => {
+                self.set_status(StatusLevel::Warning, "ssh binary not found — connections are disabled".to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// 配置文件在表单和 diff 中显示的路径（home 下的写成 ~/...）；None 为主配置
+    pub fn config_file_label(&self, source: Option<&Path>) -> String {
+        let Some(path) = source.or(self.config_path.as_deref()) else {
+            return String::new();
+        };
+        let home = home_dir().map(|home| home.to_string_lossy().into_owned()).unwrap_or_default();
+        tilde_relative(&path.to_string_lossy(), &home)
+    }
+
+    /// 按文件夹规则为新主机选择的文件；没有命中规则时为主配置（None）
+    pub fn auto_config_file(&self, folder: &str) -> Option<PathBuf> {
+        config_file_rule(folder, &self.settings.config_file_rules)
+            .map(|rule| PathBuf::from(expand_tilde(&rule.file)))
+            .filter(|file| Some(file) != self.config_path.as_ref())
+    }
+
+    /// 表单中可选的配置文件：主配置、被包含的文件和规则中的目标文件
+    pub fn config_file_choices(&self) -> Vec<String> {
+        let mut choices = vec![self.config_file_label(None)];
+        let rule_files = self.settings.config_file_rules.iter().map(|rule| PathBuf::from(expand_tilde(&rule.file)));
+        for file in self.included_files.iter().cloned().chain(rule_files) {
+            let label = self.config_file_label(Some(&file));
+            if !choices.contains(&label) {
+                choices.push(label);
+            }
+        }
+        choices
+    }
+
+    /// 表单选择的配置文件；空值按文件夹规则选择
+    fn form_config_file(&self, form: &HostFormFields) -> Option<PathBuf> {
+        if form.config_file.is_empty() {
+            return self.auto_config_file(&form.folder);
+        }
+        if form.config_file == self.config_file_label(None) {
+            return None;
+        }
+        self.included_files
+            .iter()
+            .find(|file| self.config_file_label(Some(file)) == form.config_file)
+            .cloned()
+            .or_else(|| Some(PathBuf::from(expand_tilde(&form.config_file))))
+    }
+
+    /// 关闭主机表单（保存或放弃）；新建临时主机时回到打开表单前的模式
+    fn close_host_form(&mut self) {
+        self.editing_host = None;
+        // 表单之外的预填选项只用于这一次新建
+        self.new_host_template = None;
+        self.editing_host_index = None;
+        self.current_edit_change_index = None;
+        self.mode = self.adding_temporary.take().unwrap_or(AppMode::ConfigManagement);
+    }
+
+    /// 剪贴板是 ssh 命令时打开预填的新建表单，是 Host 块时交给导入流程
+    fn add_host_from_clipboard(&mut self) {
+        let text = match read_clipboard() {
+            Ok(text) => text,
+            Err(e) => {
+                self.set_status(StatusLevel::Error, format!("Unable to read clipboard: {}", e));
+                return;
+            }
+        };
+
+        let (parsed, _) = parse_ssh_config_with_warnings(&text);
+        if parsed.iter().any(|host| !host.is_verbatim()) {
+            let mut import = ImportState::new();
+            import.text = text;
+            self.import = Some(import);
+            self.mode = AppMode::ImportSnippet;
+            self.parse_import_text();
+            return;
+        }
+
+        let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+        let Some(host) = parse_ssh_command(&text, &taken) else {
+            self.set_status(StatusLevel::Warning, "Clipboard does not contain an ssh command or Host block".to_string());
+            return;
+        };
+        let folder = self.list_position().folder;
+        self.open_new_host_form(host, folder.as_deref());
+    }
+
+    /// 进入配置管理，与 Normal 模式下按 e 相同（启动时的 --edit 也走这里）
+    pub fn start_config_management(&mut self) {
+        if self.read_only {
+            self.set_status(StatusLevel::Warning, "Read-only mode: config management is disabled".to_string());
+        } else if !self.open_guard(GuardAction::EnterConfig) {
+            self.enter_config_management();
+        }
+    }
+
+    /// 启动时的 --add：经过防护和配置锁进入配置管理，再打开新建主机表单。
+    /// text 像 ssh 命令（如 "root@10.0.0.5 -p 2222"）时按快速添加解析，否则作为名称
+    pub fn start_adding_host_from(&mut self, text: Option<&str>) {
+        if self.read_only {
+            self.set_status(StatusLevel::Warning, "Read-only mode: config management is disabled".to_string());
+            return;
+        }
+        self.add_prefill = text.map(str::trim).filter(|text| !text.is_empty()).map(str::to_string);
+        if !self.open_guard(GuardAction::AddHost) {
+            self.enter_config_and_add_host();
+        }
+    }
+
+    fn enter_config_and_add_host(&mut self) {
+        if !self.acquire_config_lock(GuardAction::AddHost) {
+            return;
+        }
+        self.enter_config_management();
+        // 有未保存变更时先完成审阅询问，不打开表单
+        if self.mode != AppMode::ConfigManagement {
+            return;
+        }
+        // 启动时没有用户选中的文件夹，只用全局默认值
+        let Some(text) = self.add_prefill.take() else {
+            self.open_new_host_form(SshHost::new(String::new()), None);
+            return;
+        };
+        let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+        // 多个词时允许省略开头的 ssh；单个词只有 user@host 才按目标解析，其余作为名称
+        let parsed = parse_ssh_command(&text, &taken)
+            .or_else(|| text.contains(char::is_whitespace).then(|| parse_ssh_command(&format!("ssh {}", text), &taken)).flatten());
+        match parsed {
+            Some(host) => self.open_new_host_form(host, None),
+            None => self.open_new_host_form(SshHost::new(text), None),
+        }
+    }
+
+    fn start_editing_selected_host(&mut self) {
+        if let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)).cloned()
+            && self.refuse_read_only_host(host_index)
+        {
+            return;
+        }
+        if let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i))
+            && let Some(host) = self.hosts.get(*host_index)
+        {
+            let mut fields = HostFormFields::from_host(host);
+            fields.config_file = self.config_file_label(host.source.as_deref());
+            let editing_data = EditingHostData::new(fields);
+            self.editing_host = Some(editing_data);
+            self.editing_host_index = Some(*host_index);
+            self.current_edit_change_index = None;
+            self.mode = AppMode::EditingHost;
+        }
+    }
+
+    fn start_deleting_selected_host(&mut self) {
+        if !self.acquire_config_lock(GuardAction::DeleteHost) {
+            return;
+        }
+        if let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)).cloned()
+            && self.refuse_read_only_host(host_index)
+        {
+            return;
+        }
+        if let Some(TreeItem::Host { host_index }) = self.list_state.selected().and_then(|i| self.tree_items.get(i)) {
+            self.delete_target = Some(*host_index);
+            self.delete_typed.clear();
+            self.delete_return_mode = self.mode;
+            self.confirm = Selector::confirm();
+            self.mode = AppMode::ConfirmDelete;
+        }
+    }
+
+    /// Normal 模式下按 Delete：直接打开删除确认，确认后暂存删除并进入配置管理
+    fn start_quick_delete(&mut self) {
+        if self.read_only {
+            self.set_status(StatusLevel::Warning, "Read-only mode: config management is disabled".to_string());
+            return;
+        }
+        self.marked_hosts.clear();
+        if !self.open_guard(GuardAction::DeleteHost) {
+            self.start_deleting_selected_host();
+        }
+    }
+
+    /// 从 Normal 模式删除生产主机时要求输入主机名
+    pub fn delete_confirm_strict(&self) -> bool {
+        self.delete_return_mode == AppMode::Normal && self.delete_target.is_some_and(|i| self.connect_protection(i).is_some())
+    }
+
+    fn handle_editing_input(&mut self, key_code: KeyCode) -> Result<()> {
+        if self.handle_folder_suggestion_key(key_code) {
+            return Ok(());
+        }
+        // 新建主机时可以留空，按文件夹规则自动选择
+        let mut file_choices = self.config_file_choices();
+        if self.editing_host_index.is_none() {
+            file_choices.insert(0, String::new());
+        }
+        let row_style_choices: Vec<String> = ROW_STYLE_CHOICES.iter().map(|choice| choice.to_string()).collect();
+        let enter_saves = self.settings.enter_saves_form;
+        // 保存键占用 F2 时，保活预设顺延到 F3/F4
+        let preset_key = if self.settings.form_save_key == FormSaveKey::F2 { 3 } else { 2 };
+        if let Some(ref mut editing_data) = self.editing_host {
+            let field = editing_data.current_field;
+            match key_code {
+                KeyCode::Esc => {
+                    if self.has_edit_changes() {
+                        self.confirm = Selector::confirm();
+                        self.mode = AppMode::ConfirmDiscardEdit;
+                    } else {
+                        self.close_host_form();
+                    }
+                }
+                KeyCode::Enter if enter_saves => {
+                    self.save_edited_host();
+                    self.needs_redraw = true;
+                }
+                KeyCode::Tab | KeyCode::Down | KeyCode::Enter => {
+                    editing_data.current_field = (field + 1) % HostFormFields::COUNT;
+                }
+                KeyCode::BackTab | KeyCode::Up => {
+                    editing_data.current_field = if field == 0 { HostFormFields::COUNT - 1 } else { field - 1 };
+                }
+                KeyCode::F(n) if (preset_key..preset_key + 2).contains(&n) => {
+                    // 套用保活预设
+                    let (_, interval, count_max) = KEEPALIVE_PRESETS[(n - preset_key) as usize];
+                    editing_data.current.server_alive_interval = interval.to_string();
+                    editing_data.current.server_alive_count_max = count_max.to_string();
+                    editing_data.error = None;
+                }
+                KeyCode::Backspace if field == STRICT_HOST_KEY_FIELD => editing_data.current.strict_host_key_checking.clear(),
+                KeyCode::Backspace if field == CONFIG_FILE_FIELD => editing_data.current.config_file = file_choices[0].clone(),
+                KeyCode::Backspace if field == ROW_STYLE_FIELD => editing_data.current.row_style.clear(),
+                KeyCode::Backspace => {
+                    // 可见性字段不支持backspace
+                    if let Some(value) = editing_data.current.text_mut(field) {
+                        value.pop();
+                    }
+                }
+                // StrictHostKeyChecking 只在固定取值间切换
+                KeyCode::Char(' ') | KeyCode::Right if field == STRICT_HOST_KEY_FIELD => {
+                    editing_data.current.strict_host_key_checking = cycle_strict_host_key(&editing_data.current.strict_host_key_checking, true);
+                }
+                KeyCode::Left if field == STRICT_HOST_KEY_FIELD => {
+                    editing_data.current.strict_host_key_checking = cycle_strict_host_key(&editing_data.current.strict_host_key_checking, false);
+                }
+                KeyCode::Char(_) if field == STRICT_HOST_KEY_FIELD => {}
+                // 配置文件同样只能在列出的文件间切换
+                KeyCode::Char(' ') | KeyCode::Right if field == CONFIG_FILE_FIELD => {
+                    editing_data.current.config_file = cycle_choice(&file_choices, &editing_data.current.config_file, true);
+                }
+                KeyCode::Left if field == CONFIG_FILE_FIELD => {
+                    editing_data.current.config_file = cycle_choice(&file_choices, &editing_data.current.config_file, false);
+                }
+                KeyCode::Char(_) if field == CONFIG_FILE_FIELD => {}
+                // 行样式同样只在固定取值间切换
+                KeyCode::Char(' ') | KeyCode::Right if field == ROW_STYLE_FIELD => {
+                    editing_data.current.row_style = cycle_choice(&row_style_choices, &editing_data.current.row_style, true);
+                }
+                KeyCode::Left if field == ROW_STYLE_FIELD => {
+                    editing_data.current.row_style = cycle_choice(&row_style_choices, &editing_data.current.row_style, false);
+                }
+                KeyCode::Char(_) if field == ROW_STYLE_FIELD => {}
+                KeyCode::Char(' ') if field == VISIBLE_FIELD => {
+                    editing_data.current.visible = !editing_data.current.visible;
+                }
+                KeyCode::Char(' ') if field < VISIBLE_FIELD => {
+                    if let Some(value) = editing_data.current.text_mut(field) {
+                        value.push(' ');
+                    }
+                }
+                // 数值字段不接受空格
+                KeyCode::Char(' ') => {}
+                KeyCode::Char(c) if field == VISIBLE_FIELD => {
+                    // 对于可见性字段，允许输入 t/f 或 y/n
+                    match c.to_lowercase().next() {
+                        Some('t') | Some('y') => editing_data.current.visible = true,
+                        Some('f') | Some('n') => editing_data.current.visible = false,
+                        _ => {},
+                    }
+                }
+                KeyCode::Char(c) => {
+                    if let Some(value) = editing_data.current.text_mut(field) {
+                        value.push(c);
+                    }
+                }
+                key_code => self.unbound_key(key_code),
+            }
+        }
+        Ok(())
+    }
+
+    /// 焦点在文件夹字段时的补全列表
+    pub fn folder_suggestions(&self) -> Vec<FolderSuggestion> {
+        match &self.editing_host {
+            Some(editing) if editing.current_field == FOLDER_FIELD => {
+                folder_suggestions(&self.get_available_folders(), &editing.current.folder, self.settings.folder_case)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// 补全列表打开时 ↓↑ 在列表中移动，Enter 选用，ESC 回到输入框；返回按键是否已处理
+    fn handle_folder_suggestion_key(&mut self, key_code: KeyCode) -> bool {
+        let suggestions = self.folder_suggestions();
+        let Some(editing) = &mut self.editing_host else {
+            return false;
+        };
+        if suggestions.is_empty() || matches!(key_code, KeyCode::Char(_) | KeyCode::Backspace) {
+            editing.folder_choice = None;
+            return false;
+        }
+        let Some(picker) = &mut editing.folder_choice else {
+            // ↓ 从输入框进入列表
+            if key_code == KeyCode::Down {
+                editing.folder_choice = Some(Selector::new(suggestions.len()));
+                return true;
+            }
+            return false;
+        };
+        // 在第一项按 ↑ 回到输入框
+        if key_code == KeyCode::Up && picker.selected == 0 {
+            editing.folder_choice = None;
+            return true;
+        }
+        picker.set_len(suggestions.len());
+        match picker.handle_key(key_code) {
+            SelectorEvent::Moved => {}
+            SelectorEvent::Cancelled => editing.folder_choice = None,
+            SelectorEvent::Accepted(i) => {
+                let suggestion = suggestions[i].clone();
+                editing.current.folder = suggestion.name().to_string();
+                editing.folder_choice = None;
+                if let FolderSuggestion::Create(name) = suggestion {
+                    if !self.session_folders.contains(&name) {
+                        self.session_folders.push(name.clone());
+                    }
+                    self.set_status(StatusLevel::Info, format!("Folder '{}' created; it is saved with the first host in it", name));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Ctrl-Z：把当前字段恢复为打开表单时的值
+    pub fn revert_current_field(&mut self) {
+        if let Some(editing_data) = &mut self.editing_host {
+            let field = editing_data.current_field;
+            editing_data.current.revert_field(&editing_data.original, field);
+            editing_data.error = None;
+        }
+    }
+
+    fn handle_delete_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
+        if self.delete_confirm_strict() {
+            match key_code {
+                KeyCode::Char(c) => self.delete_typed.push(c),
+                KeyCode::Backspace => {
+                    self.delete_typed.pop();
+                }
+                KeyCode::Enter => {
+                    let expected = self.delete_target.map(|i| self.hosts[i].name.clone());
+                    if expected.as_deref() == Some(self.delete_typed.trim()) {
+                        self.confirm_delete();
+                    }
+                }
+                KeyCode::Esc => self.cancel_delete(),
+                key_code => self.unbound_key(key_code),
+            }
+            return Ok(());
+        }
+        match self.confirm.handle_key(key_code) {
+            SelectorEvent::Accepted(CONFIRM_YES) => self.confirm_delete(),
+            SelectorEvent::Accepted(_) | SelectorEvent::Cancelled => self.cancel_delete(),
+            SelectorEvent::Moved => {}
+            SelectorEvent::Unhandled => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 确认删除后总是进入配置管理，待保存标记可见
+    fn confirm_delete(&mut self) {
+        self.mode = AppMode::ConfigManagement;
+        self.delete_typed.clear();
+        if let Some(host_idx) = self.delete_target.take() {
+            let dependents = match self.hosts.get(host_idx) {
+                // 系统主机即使经由它跳转也无法修改
+                Some(host) => find_dependents(&self.hosts, &host.name).into_iter().filter(|&i| !self.hosts[i].system).collect(),
+                None => Vec::new(),
+            };
+            if dependents.is_empty() {
+                self.stage_delete(host_idx);
+            } else {
+                // 其他主机把它当跳板时先警告
+                self.delete_dependents = Some(DeleteDependents { host_index: host_idx, dependents, retarget: None });
+                self.mode = AppMode::ConfirmDeleteDependents;
+            }
+        }
+    }
+
+    fn cancel_delete(&mut self) {
+        self.delete_target = None;
+        self.delete_typed.clear();
+        self.mode = self.delete_return_mode;
+    }
+
+    fn stage_delete(&mut self, host_idx: usize) {
+        if let Some(host) = self.hosts.get(host_idx).cloned() {
+            if !host.temporary {
+                self.pending_changes.push(ChangeType::Deleted(host));
+            }
+            self.hosts.remove(host_idx);
+            self.filter_hosts();
+        }
+    }
+
+    fn handle_delete_dependents_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.delete_dependents else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+
+        // 输入新跳板名
+        if let Some(retarget) = &mut state.retarget {
+            match key_code {
+                KeyCode::Char(c) if !c.is_whitespace() => retarget.push(c),
+                KeyCode::Backspace => {
+                    retarget.pop();
+                }
+                KeyCode::Enter if !retarget.is_empty() => {
+                    let new_target = retarget.clone();
+                    self.delete_with_dependents(Some(&new_target));
+                }
+                // 还没有输入目标时 Enter 什么也不做
+                KeyCode::Enter => {}
+                KeyCode::Esc => state.retarget = None,
+                key_code => self.unbound_key(key_code),
+            }
+            return Ok(());
+        }
+
+        match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let host_index = state.host_index;
+                self.delete_dependents = None;
+                self.stage_delete(host_index);
+                self.mode = AppMode::ConfigManagement;
+            }
+            KeyCode::Char('x') => self.delete_with_dependents(None),
+            KeyCode::Char('r') => state.retarget = Some(String::new()),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.delete_dependents = None;
+                self.mode = AppMode::ConfigManagement;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 先为依赖主机生成清除或改指跳板的修改，再删除目标主机
+    fn delete_with_dependents(&mut self, new_target: Option<&str>) {
+        let Some(state) = self.delete_dependents.take() else {
+            return;
+        };
+        let Some(name) = self.hosts.get(state.host_index).map(|h| h.name.clone()) else {
+            return;
+        };
+
+        for &dependent in &state.dependents {
+            let old_host = self.hosts[dependent].clone();
+            let new_host = retarget_references(&old_host, &name, new_target);
+            if new_host != old_host {
+                self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+                self.hosts[dependent] = new_host;
+            }
+        }
+
+        self.stage_delete(state.host_index);
+        self.mode = AppMode::ConfigManagement;
+    }
+
+    /// 当前主机的检查结果，按主机名排序
+    fn lint_findings(&self) -> Vec<LintFinding> {
+        let mut findings = lint_hosts(&self.hosts);
+        findings.extend(lint_key_files(&self.hosts, home_dir().as_deref()));
+        findings.sort_by(|a, b| a.host.cmp(&b.host));
+        findings
+    }
+
+    pub fn start_lint(&mut self) {
+        let findings = self.lint_findings();
+        self.linted = true;
+        if findings.is_empty() {
+            self.set_status(StatusLevel::Info, "No problems found".to_string());
+            return;
+        }
+        self.lint = Some(LintState { findings, selected: 0, return_mode: self.mode });
+        self.mode = AppMode::LintView;
+    }
+
+    fn handle_lint_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(lint) = &mut self.lint else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        match key_code {
+            KeyCode::Up => lint.selected = lint.selected.saturating_sub(1),
+            KeyCode::Down => lint.selected = (lint.selected + 1).min(lint.findings.len().saturating_sub(1)),
+            KeyCode::Enter => {
+                // 跳到对应主机
+                let host = lint.findings.get(lint.selected).map(|f| f.host.clone());
+                self.mode = lint.return_mode;
+                self.lint = None;
+                if let Some(name) = host {
+                    let folder = self.hosts.iter().find(|h| h.name == name).and_then(|h| h.folder.clone());
+                    self.restore_selection(Some(&SelectionAnchor::Host { name, folder }));
+                }
+            }
+            KeyCode::Char('f') => {
+                // 收集所有可修复的私钥文件，确认后统一处理
+                let mut paths: Vec<PathBuf> = lint.findings
+                    .iter()
+                    .filter(|f| f.rule == "insecure-key-permissions")
+                    .filter_map(|f| f.path.clone())
+                    .collect();
+                paths.sort();
+                paths.dedup();
+                if !paths.is_empty() {
+                    self.permission_fix = paths;
+                    self.mode = AppMode::ConfirmFixPermissions;
+                }
+            }
+            KeyCode::Char('r') => {
+                let Some(finding) = lint.findings.get(lint.selected).filter(|f| f.rule == "missing-identity-file") else {
+                    return Ok(());
+                };
+                // 暂存变更需要在配置管理中进行
+                if lint.return_mode != AppMode::ConfigManagement {
+                    self.set_status(StatusLevel::Warning, "Open the checks from config management (e, then L) to rotate keys".to_string());
+                    return Ok(());
+                }
+                let home = home_dir().map(|home| home.to_string_lossy().into_owned()).unwrap_or_default();
+                let old_path = finding.path.as_ref().map(|path| tilde_relative(&path.to_string_lossy(), &home)).unwrap_or_default();
+                self.lint = None;
+                self.mode = AppMode::ConfigManagement;
+                self.start_key_rotation(old_path);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = lint.return_mode;
+                self.lint = None;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 打开健康面板并立即探测所有可见主机
+    pub fn start_dashboard(&mut self) {
+        let rows = self.hosts
+            .iter()
+            .enumerate()
+            .filter(|(_, host)| (host.visible || self.show_hidden) && !host.is_verbatim() && !host.disabled)
+            .map(|(index, _)| (index, ProbeStatus::Pending))
+            .collect();
+        self.dashboard = Some(DashboardState { rows, selected: 0, sort_by_status: false, last_refreshed: None });
+        self.mode = AppMode::Dashboard;
+        self.refresh_dashboard();
+    }
+
+    /// 重新开始一轮探测，旧的一轮随之取消
+    pub fn refresh_dashboard(&mut self) {
+        let Some(dashboard) = &mut self.dashboard else {
+            return;
+        };
+        let mut targets = Vec::new();
+        for (host_index, status) in &mut dashboard.rows {
+            match probe_target(*host_index, &self.hosts[*host_index]) {
+                Ok(target) => {
+                    *status = ProbeStatus::Pending;
+                    targets.push(target);
+                }
+                Err(reason) => *status = ProbeStatus::Skipped(reason),
+            }
+        }
+        dashboard.last_refreshed = Some(Instant::now());
+        self.dashboard_run = Some(ProbeRun::start(targets, PROBE_TIMEOUT));
+    }
+
+    /// 面板是否仍有探测在进行
+    pub fn dashboard_probing(&self) -> bool {
+        self.dashboard_run.as_ref().is_some_and(|run| !run.is_finished())
+    }
+
+    fn tick_dashboard(&mut self) {
+        let Some(dashboard) = &mut self.dashboard else {
+            return;
+        };
+        if let Some(run) = &mut self.dashboard_run {
+            let results = run.poll();
+            if !results.is_empty() {
+                for (host_index, status) in results {
+                    if let Some(row) = dashboard.rows.iter_mut().find(|(index, _)| *index == host_index) {
+                        row.1 = status;
+                    }
+                }
+                if dashboard.sort_by_status {
+                    sort_dashboard(dashboard, &self.hosts);
+                }
+            }
+            if run.is_finished() {
+                self.dashboard_run = None;
+            }
+        }
+
+        let interval = Duration::from_secs(self.settings.dashboard_refresh_secs.max(1));
+        let due = dashboard.last_refreshed.is_none_or(|at| at.elapsed() >= interval);
+        if self.dashboard_run.is_none() && due {
+            self.refresh_dashboard();
+        }
+    }
+
+    /// 为选中的纯别名主机发起 DNS 查询（每个名字只查一次）
+    fn tick_dns(&mut self) {
+        let Some((host, name)) = self.get_selected_host().and_then(|host| Some((host.name.clone(), dns_name(host)?.to_string()))) else {
+            return;
+        };
+        if self.dns_cache.contains_key(&name) {
+            return;
+        }
+        self.dns_cache.insert(name.clone(), DnsStatus::Resolving);
+        self.tasks.start_dns(&host, &name);
+    }
+
+    fn close_dashboard(&mut self) {
+        if let Some(run) = self.dashboard_run.take() {
+            run.cancel();
+        }
+        self.dashboard = None;
+        self.mode = AppMode::Normal;
+    }
+
+    fn handle_dashboard_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(dashboard) = &mut self.dashboard else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        match key_code {
+            KeyCode::Up => dashboard.selected = dashboard.selected.saturating_sub(1),
+            KeyCode::Down => dashboard.selected = (dashboard.selected + 1).min(dashboard.rows.len().saturating_sub(1)),
+            KeyCode::Char('s') => {
+                dashboard.sort_by_status = !dashboard.sort_by_status;
+                if dashboard.sort_by_status {
+                    sort_dashboard(dashboard, &self.hosts);
+                } else {
+                    let current = dashboard.rows.get(dashboard.selected).map(|(index, _)| *index);
+                    dashboard.rows.sort_by_key(|(index, _)| *index);
+                    dashboard.selected = dashboard.rows.iter().position(|(index, _)| Some(*index) == current).unwrap_or(0);
+                }
+            }
+            KeyCode::Char('r') => self.refresh_dashboard(),
+            KeyCode::Enter => {
+                // 跳到对应主机
+                let host = dashboard.rows.get(dashboard.selected).map(|(index, _)| &self.hosts[*index]);
+                let anchor = host.map(|h| SelectionAnchor::Host { name: h.name.clone(), folder: h.folder.clone() });
+                self.close_dashboard();
+                self.restore_selection(anchor.as_ref());
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('!') => self.close_dashboard(),
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn handle_review_pending_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => self.open_review(),
+            KeyCode::Char('n') | KeyCode::Char('N') => self.mode = AppMode::ConfigManagement,
+            KeyCode::Esc => self.mode = AppMode::Normal,
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn handle_fix_permissions_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let report: Vec<String> = std::mem::take(&mut self.permission_fix)
+                    .iter()
+                    .map(|path| match tighten_key_permissions(path) {
+                        Ok(mode) => format!("✓ {}: now {:04o}", path.display(), mode),
+                        Err(e) => format!("✗ {}: {}", path.display(), e),
+                    })
+                    .collect();
+                self.mode = self.lint.take().map_or(AppMode::Normal, |lint| lint.return_mode);
+                self.show_message("Fix key permissions".to_string(), report);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.permission_fix.clear();
+                self.mode = AppMode::LintView;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn save_reviewed_changes(&mut self) {
+        self.review_scroll = 0;
+        self.mode = AppMode::Normal;
+        if let Err(e) = self.apply_changes() {
+            // 保存失败时保留待保存变更，回到配置管理模式
+            self.mode = AppMode::ConfigManagement;
+            self.show_message("Save failed".to_string(), vec![e.to_string()]);
+        }
+    }
+
+    fn enter_config_management(&mut self) {
+        if !self.acquire_config_lock(GuardAction::EnterConfig) {
+            return;
+        }
+        self.marked_hosts.clear();
+        // 有未保存变更时先询问是否直接审阅
+        self.mode = if self.pending_changes.is_empty() {
+            AppMode::ConfigManagement
+        } else {
+            AppMode::ConfirmReviewPending
+        };
+    }
+
+    /// 确保持有配置锁并返回 true；锁被其他运行中的实例持有时打开选择弹窗并返回 false，
+    /// 抢到锁后由弹窗继续 action。无法创建锁文件（例如目录只读）时提示后照常编辑
+    fn acquire_config_lock(&mut self, action: GuardAction) -> bool {
+        if self.config_lock.as_ref().is_some_and(ConfigLock::is_held) {
+            return true;
+        }
+        let Some(config_path) = &self.config_path else {
+            return true;
+        };
+        match ConfigLock::acquire(&lock_path(config_path)) {
+            Ok(LockStatus::Acquired(lock)) => {
+                self.config_lock = Some(lock);
+                true
+            }
+            Ok(LockStatus::HeldBy(holder)) => {
+                let choice = Selector::new(2).with_accelerators(&[('r', LOCK_READ_ONLY), ('s', LOCK_STEAL)]);
+                self.lock_prompt = Some(LockPrompt { holder, action, return_mode: self.mode, choice });
+                self.mode = AppMode::ConfigLocked;
+                false
+            }
+            Err(e) => {
+                self.set_status(StatusLevel::Warning, format!("{:#}; editing without a lock", e));
+                true
+            }
+        }
+    }
+
+    fn handle_lock_input(&mut self, key_code: KeyCode) {
+        let Some(prompt) = &mut self.lock_prompt else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        let steal = match prompt.choice.handle_key(key_code) {
+            SelectorEvent::Accepted(choice) => choice == LOCK_STEAL,
+            SelectorEvent::Cancelled => false,
+            SelectorEvent::Moved => return,
+            SelectorEvent::Unhandled => {
+                self.unbound_key(key_code);
+                return;
+            }
+        };
+        let Some(prompt) = self.lock_prompt.take() else {
+            return;
+        };
+        self.mode = prompt.return_mode;
+        if !steal {
+            self.set_status(
+                StatusLevel::Info,
+                format!("The config is being edited by another sshc (PID {}); continuing read-only", prompt.holder)
+            );
+            return;
+        }
+
+        let Some(config_path) = &self.config_path else {
+            return;
+        };
+        match ConfigLock::steal(&lock_path(config_path)) {
+            Ok(lock) => {
+                self.config_lock = Some(lock);
+                match prompt.action {
+                    GuardAction::DeleteHost => self.start_deleting_selected_host(),
+                    GuardAction::AddHost => self.enter_config_and_add_host(),
+                    _ => self.enter_config_management(),
+                }
+                self.set_status(
+                    StatusLevel::Warning,
+                    format!("Took the config lock from PID {}; saving there is refused from now on", prompt.holder)
+                );
+            }
+            Err(e) => self.set_status(StatusLevel::Error, format!("{:#}", e)),
+        }
+    }
+
+    /// 启用防护时打开确认弹窗并返回 true；未启用时返回 false，由调用方直接执行
+    fn open_guard(&mut self, action: GuardAction) -> bool {
+        if self.settings.config_guard == ConfigGuard::Off {
+            return false;
+        }
+        self.guard = Some(GuardPrompt { action, typed: String::new(), opened: Instant::now(), return_mode: self.mode });
+        self.mode = AppMode::ConfirmGuard;
+        true
+    }
+
+    /// 防护弹窗是否已允许确认（Confirm 模式需等待 GUARD_DELAY）
+    pub fn guard_ready(&self) -> bool {
+        self.guard.as_ref().is_some_and(|guard| match self.settings.config_guard {
+            ConfigGuard::Phrase => guard.typed == self.settings.config_guard_phrase,
+            _ => guard.opened.elapsed() >= GUARD_DELAY,
+        })
+    }
+
+    fn handle_guard_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(guard) = &mut self.guard else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        let phrase = self.settings.config_guard == ConfigGuard::Phrase;
+        let confirmed = match key_code {
+            KeyCode::Esc => {
+                self.mode = guard.return_mode;
+                self.guard = None;
+                return Ok(());
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') if !phrase => {
+                self.mode = guard.return_mode;
+                self.guard = None;
+                return Ok(());
+            }
+            KeyCode::Char(c) if phrase => {
+                guard.typed.push(c);
+                false
+            }
+            KeyCode::Backspace if phrase => {
+                guard.typed.pop();
+                false
+            }
+            KeyCode::Enter if phrase => true,
+            KeyCode::Char('y') | KeyCode::Char('Y') => true,
+            key_code => {
+                self.unbound_key(key_code);
+                false
+            }
+        };
+        if !confirmed {
+            return Ok(());
+        }
+        if !self.guard_ready() {
+            if phrase && let Some(guard) = &mut self.guard {
+                guard.typed.clear();
+            }
+            return Ok(());
+        }
+
+        let Some(guard) = self.guard.take() else {
+            return Ok(());
+        };
+        match guard.action {
+            GuardAction::EnterConfig => self.enter_config_management(),
+            GuardAction::AddHost => self.enter_config_and_add_host(),
+            GuardAction::DeleteHost => {
+                self.mode = guard.return_mode;
+                self.start_deleting_selected_host();
+            }
+            GuardAction::ApplyChanges => self.save_reviewed_changes(),
+            GuardAction::SaveAndReload => self.save_and_reload(guard.return_mode),
+        }
+        Ok(())
+    }
+
+    /// 重新加载配置；有未保存变更时先弹窗询问
+    pub fn request_reload(&mut self) {
+        if self.pending_changes.is_empty() {
+            self.reload_with_status();
+        } else {
+            self.reload_return_mode = self.mode;
+            self.mode = AppMode::ConfirmReload;
+        }
+        self.recheck_ssh_binary();
+    }
+
+    fn reload_with_status(&mut self) {
+        match self.reload_config() {
+            Ok(()) => self.set_status(StatusLevel::Info, format!("Reloaded {} hosts", self.hosts.len())),
+            Err(e) => self.set_status(StatusLevel::Error, format!("Reload failed: {}", e)),
+        }
+    }
+
+    fn save_and_reload(&mut self, return_mode: AppMode) {
+        self.mode = return_mode;
+        match self.apply_changes() {
+            Ok(()) => self.reload_with_status(),
+            Err(e) => self.show_message("Save failed".to_string(), vec![e.to_string()]),
+        }
+    }
+
+    fn handle_reload_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let return_mode = self.reload_return_mode;
+        match key_code {
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                self.mode = return_mode;
+                if !self.open_guard(GuardAction::SaveAndReload) {
+                    self.save_and_reload(return_mode);
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                self.mode = return_mode;
+                self.reload_with_status();
+            }
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                self.mode = return_mode;
+                self.merge_with_disk();
+            }
+            KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('n') => self.mode = return_mode,
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 重新读取配置并把待保存变更合并上去；不能干净合并的主机在冲突列表中逐个选择
+    pub fn merge_with_disk(&mut self) {
+        let Some(config_path) = &self.config_path else {
+            return;
+        };
+        let (disk_hosts, included_files) = match parse_config_tree_with_limits(config_path, &self.settings.parse_limits()) {
+            Ok((hosts, files, report)) if report.is_empty() => (hosts, files),
+            Ok(_) => {
+                self.set_status(StatusLevel::Error, "The config on disk exceeds the parse limits; reload it to see what was skipped".to_string());
+                return;
+            }
+            Err(e) => {
+                self.set_status(StatusLevel::Error, format!("Reload failed: {}", e));
+                return;
+            }
+        };
+        let result = merge_pending(&disk_hosts, &self.pending_changes);
+        let state = MergeState::new(result, disk_hosts, included_files);
+        if state.result.conflicts.is_empty() {
+            self.finish_merge(state);
+        } else {
+            self.merge = Some(state);
+            self.mode = AppMode::ResolveConflicts;
+        }
+    }
+
+    /// 以磁盘内容为新的基准，换上合并后的主机和待保存变更
+    fn finish_merge(&mut self, mut state: MergeState) {
+        let disk_hosts = std::mem::take(&mut state.disk_hosts);
+        self.included_files = std::mem::take(&mut state.included_files);
+        let (hosts, changes) = state.finish();
+        self.record_load_times();
+        self.original_hosts = disk_hosts;
+        self.replace_hosts(hosts);
+        self.pending_changes = changes;
+        self.mode = self.reload_return_mode;
+        self.set_status(
+            StatusLevel::Info,
+            format!("Merged with the config on disk; {} pending changes", self.pending_changes.len())
+        );
+    }
+
+    fn handle_merge_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.merge else {
+            self.mode = self.reload_return_mode;
+            return Ok(());
+        };
+        match key_code {
+            KeyCode::Down => state.select_next(),
+            KeyCode::Up => state.select_previous(),
+            KeyCode::Char('m') => state.choose(MergeChoice::Mine),
+            KeyCode::Char('t') => state.choose(MergeChoice::Theirs),
+            KeyCode::Enter if state.all_chosen() => {
+                if let Some(state) = self.merge.take() {
+                    self.finish_merge(state);
+                }
+            }
+            KeyCode::Enter => {
+                let open = state.choices.iter().filter(|choice| choice.is_none()).count();
+                self.set_status(StatusLevel::Warning, format!("Choose mine or theirs for the remaining {} conflicts", open));
+            }
+            // 取消时保留原来的待保存变更，配置仍标记为已在磁盘上修改
+            KeyCode::Esc => {
+                self.merge = None;
+                self.mode = self.reload_return_mode;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 生成并缓存摘要和 diff 后进入审阅
+    pub fn open_review(&mut self) {
+        self.build_review();
+        self.review_search = ReviewSearch::default();
+        self.mode = AppMode::ReviewChanges;
+    }
+
+    fn build_review(&mut self) {
+        let summary = summarize_changes(&self.pending_changes);
+        let (diff, blocks) = self.diff_with_blocks();
+
+        let mut lines = vec![format!("Summary: {}", summary.headline())];
+        for (number, row) in summary.rows.iter().take(SUMMARY_ROW_LIMIT).enumerate() {
+            let fields = if row.fields.is_empty() { String::new() } else { format!(": {}", row.fields.join(", ")) };
+            lines.push(format!("  {}. {} {}{}", number + 1, row.kind.marker(), row.name, fields));
+        }
+        if summary.rows.len() > SUMMARY_ROW_LIMIT {
+            lines.push(format!("  … and {} more", summary.rows.len() - SUMMARY_ROW_LIMIT));
+        }
+        lines.push(String::new());
+
+        let offset = lines.len();
+        self.review_jumps = summary.rows
+            .iter()
+            .take(SUMMARY_ROW_LIMIT)
+            .filter_map(|row| blocks.get(row.change_index).map(|line| offset + line))
+            .collect();
+        self.review_blocks = blocks.iter().map(|line| offset + line).collect();
+        lines.extend(diff);
+        // 待保存变更没有变化时（例如离开审阅再回来）保留上次的滚动位置和选中的变更
+        if lines != self.review_lines {
+            self.review_scroll = 0;
+            self.review_selected = 0;
+        }
+        self.review_lines = lines;
+        self.clamp_review_scroll();
+    }
+
+    /// 选中变更的 diff 块所占的行
+    pub fn review_selected_lines(&self) -> std::ops::Range<usize> {
+        let Some(&start) = self.review_blocks.get(self.review_selected) else {
+            return 0..0;
+        };
+        let end = self.review_blocks.iter().copied().find(|&line| line > start).unwrap_or(self.review_lines.len());
+        start..end
+    }
+
+    /// 选中一条变更并滚动到它的 diff 块
+    fn select_review_change(&mut self, index: usize) {
+        let Some(&line) = self.review_blocks.get(index) else {
+            return;
+        };
+        self.review_selected = index;
+        self.review_scroll = line;
+        self.clamp_review_scroll();
+    }
+
+    /// 把选中的变更与前一条（或后一条）交换；保存时按审阅中的顺序逐条应用
+    fn move_review_change(&mut self, down: bool) {
+        let from = self.review_selected;
+        let to = if down { from + 1 } else { from.wrapping_sub(1) };
+        if to >= self.pending_changes.len() {
+            return;
+        }
+        self.pending_changes.swap(from, to);
+        self.current_edit_change_index = None;
+        // 能按新顺序应用时让列表显示同样的结果；不能时保存会被阻止，先提示原因
+        match replay_changes(&self.original_hosts, &self.pending_changes) {
+            Ok(hosts) => self.replace_hosts(hosts),
+            Err(message) => self.set_status(StatusLevel::Warning, message),
+        }
+        self.build_review();
+        self.select_review_change(to);
+    }
+
+    /// 选中主机未保存修改的逐字段对比，不必打开整个审阅界面
+    fn show_host_diff(&mut self) {
+        let Some(host) = self.get_selected_host() else {
+            self.set_status(StatusLevel::Warning, "Select a host to see its unsaved changes".to_string());
+            return;
+        };
+        self.host_diff = Some(host_diff(&self.pending_changes, &host.name));
+        self.mode = AppMode::HostDiff;
+    }
+
+    fn handle_host_diff_input(&mut self, key_code: KeyCode) {
+        match key_code {
+            KeyCode::Char('u') => self.revert_host_diff(),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('D') => {
+                self.host_diff = None;
+                self.mode = AppMode::ConfigManagement;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+    }
+
+    /// 撤销这台主机的所有修改：移除对应的变更后从加载时的主机重新应用其余变更
+    fn revert_host_diff(&mut self) {
+        let Some(diff) = self.host_diff.take_if(|diff| !diff.change_indices.is_empty()) else {
+            return;
+        };
+        let Some(ChangeType::Modified { old, .. }) = diff.change_indices.first().and_then(|&index| self.pending_changes.get(index)) else {
+            return;
+        };
+        let anchor = SelectionAnchor::Host { name: old.name.clone(), folder: old.folder.clone() };
+        let mut changes = self.pending_changes.clone();
+        for &index in diff.change_indices.iter().rev() {
+            changes.remove(index);
+        }
+        // 其余变更依赖这些修改（例如之后又改名）时不撤销
+        match replay_changes(&self.original_hosts, &changes) {
+            Ok(hosts) => {
+                self.pending_changes = changes;
+                self.current_edit_change_index = None;
+                self.replace_hosts(hosts);
+                self.restore_selection(Some(&anchor));
+                self.set_status(StatusLevel::Info, format!("Reverted unsaved changes to {}", diff.name));
+            }
+            Err(message) => self.set_status(StatusLevel::Warning, format!("Cannot revert {}: {}", diff.name, message)),
+        }
+        self.mode = AppMode::ConfigManagement;
+    }
+
+    /// 打开备份浏览器，列出自动备份并预览最新的一个
+    pub fn open_backups(&mut self) {
+        let Some(dir) = self.backup_dir.clone() else {
+            self.set_status(StatusLevel::Warning, "Backups are not available without a data directory".to_string());
+            return;
+        };
+        let backups = list_backups(&dir);
+        if backups.is_empty() {
+            let hint = if self.settings.backup_on_save { "one is made each time you save" } else { "backup_on_save is off" };
+            self.set_status(StatusLevel::Info, format!("No backups in {} yet; {}", dir.display(), hint));
+            return;
+        }
+        self.backups = Some(BackupBrowser::new(backups));
+        self.update_backup_preview();
+        self.mode = AppMode::Backups;
+    }
+
+    /// 重新计算选中备份与当前主配置（含待保存变更）的差异
+    fn update_backup_preview(&mut self) {
+        let current = replay_changes(&self.original_hosts, &self.pending_changes);
+        let Some(browser) = &mut self.backups else {
+            return;
+        };
+        browser.preview_scroll = 0;
+        browser.preview = match (browser.selected(), current) {
+            (Some(backup), Ok(current)) => preview_backup(backup, &current),
+            (None, _) => Ok(Vec::new()),
+            (_, Err(e)) => Err(e),
+        };
+    }
+
+    fn handle_backups_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(browser) = &mut self.backups else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+        match key_code {
+            KeyCode::PageDown => browser.preview_scroll += 10,
+            KeyCode::PageUp => browser.preview_scroll = browser.preview_scroll.saturating_sub(10),
+            KeyCode::Char('q') => {
+                self.backups = None;
+                self.mode = AppMode::ConfigManagement;
+            }
+            _ => match browser.choice.handle_key(key_code) {
+                SelectorEvent::Moved => self.update_backup_preview(),
+                SelectorEvent::Accepted(_) => self.stage_backup_restore(),
+                SelectorEvent::Cancelled => {
+                    self.backups = None;
+                    self.mode = AppMode::ConfigManagement;
+                }
+                SelectorEvent::Unhandled => self.unbound_key(key_code),
+            },
+        }
+        Ok(())
+    }
+
+    /// 把恢复选中备份所需的变更加入待保存变更，保存前仍要经过审阅
+    fn stage_backup_restore(&mut self) {
+        let Some(browser) = &self.backups else {
+            return;
+        };
+        let changes = match &browser.preview {
+            Ok(changes) if changes.is_empty() => {
+                self.set_status(StatusLevel::Info, "The main config already matches this backup".to_string());
+                return;
+            }
+            Ok(changes) => changes.clone(),
+            Err(e) => {
+                self.set_status(StatusLevel::Error, e.clone());
+                return;
+            }
+        };
+        let created = browser.selected().map(|backup| format_utc(backup.created)).unwrap_or_default();
+        let mut pending = self.pending_changes.clone();
+        pending.extend(changes.iter().cloned());
+        match replay_changes(&self.original_hosts, &pending) {
+            Ok(hosts) => {
+                self.pending_changes = pending;
+                self.current_edit_change_index = None;
+                self.replace_hosts(hosts);
+                self.backups = None;
+                self.mode = AppMode::ConfigManagement;
+                self.set_status(StatusLevel::Info, format!("Staged {} change(s) restoring the backup from {} UTC; review before saving", changes.len(), created));
+            }
+            Err(e) => self.set_status(StatusLevel::Error, format!("Cannot restore this backup: {}", e)),
+        }
+    }
+
+    /// 包含搜索词的 diff 行（不区分大小写）
+    pub fn review_matches(&self) -> Vec<usize> {
+        let query = self.review_search.query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        self.review_lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.to_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// 跳到下一个（或上一个）匹配并滚动使其可见，到头后回绕
+    fn jump_to_match(&mut self, forward: bool) {
+        let matches = self.review_matches();
+        let from = self.review_search.current;
+        let target = if forward {
+            matches.iter().find(|&&line| from.is_none_or(|from| line > from)).or(matches.first())
+        } else {
+            matches.iter().rev().find(|&&line| from.is_some_and(|from| line < from)).or(matches.last())
+        };
+        let Some(&line) = target else {
+            self.review_search.current = None;
+            return;
+        };
+
+        self.review_search.current = Some(line);
+        let height = self.review_height.max(1);
+        if line < self.review_scroll || line >= self.review_scroll + height {
+            self.review_scroll = line.saturating_sub(height / 3);
+        }
+        self.clamp_review_scroll();
+    }
+
+    /// 滚动到底时最后一行位于可见区域底部；尚未渲染（高度为 0）时按一行计算
+    fn clamp_review_scroll(&mut self) {
+        self.review_scroll = self.review_scroll.min(max_review_scroll(self.review_lines.len(), self.review_height));
+    }
+
+    fn handle_review_search_input(&mut self, key_code: KeyCode) {
+        match key_code {
+            KeyCode::Char(c) => self.review_search.query.push(c),
+            KeyCode::Backspace => {
+                self.review_search.query.pop();
+            }
+            KeyCode::Enter => {
+                self.review_search.editing = false;
+                // 从当前可见位置开始找第一个匹配
+                self.review_search.current = self.review_scroll.checked_sub(1);
+                self.jump_to_match(true);
+            }
+            KeyCode::Esc => {
+                // 退出搜索，保留滚动位置
+                self.review_search = ReviewSearch::default();
+            }
+            key_code => self.unbound_key(key_code),
+        }
+    }
+
+    fn handle_review_input(&mut self, key_code: KeyCode) -> Result<()> {
+        if self.review_search.editing {
+            self.handle_review_search_input(key_code);
+            return Ok(());
+        }
+        let searching = !self.review_search.query.is_empty();
+
+        match key_code {
+            KeyCode::Char('/') => {
+                self.review_search = ReviewSearch { editing: true, ..ReviewSearch::default() };
+            }
+            // 有搜索词时 n/N 在匹配间跳转，否则 n 为放弃变更
+            KeyCode::Char('n') if searching => self.jump_to_match(true),
+            KeyCode::Char('N') if searching => self.jump_to_match(false),
+            KeyCode::Esc if searching => self.review_search = ReviewSearch::default(),
+            KeyCode::Char(c @ '1'..='9') => {
+                // 选中摘要中对应主机的变更并滚动到它的 diff 块
+                if let Some(&line) = self.review_jumps.get((c as usize) - ('1' as usize))
+                    && let Some(index) = self.review_blocks.iter().position(|&block| block == line)
+                {
+                    self.select_review_change(index);
+                }
+            }
+            KeyCode::Tab if !self.pending_changes.is_empty() => {
+                self.select_review_change((self.review_selected + 1) % self.pending_changes.len());
+            }
+            KeyCode::BackTab if !self.pending_changes.is_empty() => {
+                let len = self.pending_changes.len();
+                self.select_review_change((self.review_selected + len - 1) % len);
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                // 启用防护时先确认，确认后再保存
+                if self.open_guard(GuardAction::ApplyChanges) {
+                    return Ok(());
+                }
+                self.save_reviewed_changes();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                self.discard_changes();
+                self.mode = AppMode::Normal;
+                self.review_scroll = 0;
+            }
+            KeyCode::Esc => {
+                self.mode = AppMode::ConfigManagement;
+            }
+            KeyCode::Up => {
+                self.review_scroll = self.review_scroll.saturating_sub(1);
+            }
+            KeyCode::Home => {
+                self.review_scroll = 0;
+            }
+            KeyCode::End => {
+                self.review_scroll = usize::MAX;
+                self.clamp_review_scroll();
+            }
+            KeyCode::Down => {
+                self.review_scroll += 1;
+                self.clamp_review_scroll();
+            }
+            KeyCode::PageUp => {
+                self.review_scroll = self.review_scroll.saturating_sub(10);
+            }
+            KeyCode::PageDown => {
+                self.review_scroll += 10;
+                self.clamp_review_scroll();
+            }
+            // 放大或缩小审阅弹窗，本次会话内保持
+            KeyCode::Char('+') | KeyCode::Char('=') => self.review_zoom = (self.review_zoom + 1).min(REVIEW_ZOOM_LIMIT),
+            KeyCode::Char('-') => self.review_zoom = (self.review_zoom - 1).max(-REVIEW_ZOOM_LIMIT),
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 表单校验失败：显示错误并把焦点移到出错的字段
+    fn reject_form(&mut self, field: usize, message: String) {
+        if let Some(editing_data) = &mut self.editing_host {
+            editing_data.error = Some(message);
+            editing_data.current_field = field;
+        }
+    }
+
+    /// 表单中保存所用按键的名称，用于帮助行和提示
+    pub fn form_save_label(&self) -> &'static str {
+        if self.settings.enter_saves_form { "Enter" } else { self.settings.form_save_key.label() }
+    }
+
+    fn is_form_save_key(&self, key: &KeyEvent) -> bool {
+        match self.settings.form_save_key {
+            FormSaveKey::CtrlS => key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL),
+            FormSaveKey::F2 => key.code == KeyCode::F(2),
+        }
+    }
+
+    /// 按表单当前内容生成保存时写入的主机（不做校验）；保存和表单旁的预览共用
+    pub fn host_from_form(&self) -> Option<SshHost> {
+        let editing_data = self.editing_host.as_ref()?;
+        // 编辑已有主机时保留表单未涉及的其他选项
+        let mut new_host = self.editing_host_index
+            .and_then(|i| self.hosts.get(i).cloned())
+            .or_else(|| self.new_host_template.clone())
+            .unwrap_or_else(|| SshHost::new(String::new()));
+        let form = &editing_data.current;
+        let name = form.name.clone();
+        let name_changed = editing_data.is_modified(NAME_FIELD);
+        let non_empty = |value: &String| (!value.is_empty()).then(|| value.clone());
+        // 坚持使用含空白的名称时加引号写入，使其成为单个模式
+        new_host.name = if name_changed && name.contains(char::is_whitespace) && !name.starts_with('"') {
+            format!("\"{}\"", name)
+        } else {
+            name
+        };
+        new_host.hostname = non_empty(&form.hostname);
+        new_host.user = non_empty(&form.user);
+        new_host.port = non_empty(&form.port);
+        new_host.identity_file = non_empty(&form.identity_file);
+        
+        // 设置元数据字段
+        new_host.folder = non_empty(&form.folder);
+        new_host.display_name = non_empty(&form.display_name);
+        new_host.description = non_empty(&form.description);
+        new_host.aliases = split_tags(&form.aliases);
+        new_host.visible = form.visible;
+        new_host.source = self.form_config_file(form);
+        // 只在改动过时写回，手写的 # @style 和 # @color 组合保持原样
+        if editing_data.is_modified(ROW_STYLE_FIELD) {
+            apply_row_style_choice(&mut new_host, &form.row_style);
+        }
+
+        let keepalive = [&form.server_alive_interval, &form.server_alive_count_max, &form.connect_timeout];
+        let trust = [&form.strict_host_key_checking, &form.user_known_hosts_file, &form.host_key_alias];
+        let promoted = KEEPALIVE_OPTIONS.iter().zip(keepalive).chain(TRUST_OPTIONS.iter().zip(trust));
+        for (key, value) in promoted {
+            let value = value.trim();
+            if value.is_empty() {
+                new_host.other_options.remove(key);
+            } else if new_host.other_options.get(key).map(String::as_str) != Some(value) {
+                new_host.other_options.insert(key.to_string(), value.to_string());
+            }
+        }
+        Some(new_host)
+    }
+
+    /// 预览中显示的配置块，与保存后写入文件的内容一致
+    pub fn form_preview(&self) -> Option<String> {
+        self.host_from_form().and_then(|host| serialize_host_with_style(&host, true, self.settings.write_style).ok())
+    }
+
+    pub fn save_edited_host(&mut self) {
+        if let Some(editing_data) = &self.editing_host {
+            let form = &editing_data.current;
+            if form.name.trim().is_empty() {
+                self.reject_form(NAME_FIELD, "Name is required".to_string());
+                return;
+            }
+
+            if !form.port.is_empty() && resolve_port(&form.port).is_none() {
+                self.reject_form(PORT_FIELD, "Port must be a number from 1 to 65535 or a known service name".to_string());
+                return;
+            }
+
+            let keepalive = [
+                ("ServerAliveInterval", &form.server_alive_interval),
+                ("ServerAliveCountMax", &form.server_alive_count_max),
+                ("ConnectTimeout", &form.connect_timeout),
+            ];
+            // 按表单顺序检查，光标停在第一个出错的字段
+            if let Some((offset, (label, _))) = keepalive
+                .iter()
+                .enumerate()
+                .find(|(_, (_, value))| !value.is_empty() && !value.parse::<u32>().is_ok_and(|n| n > 0))
+            {
+                let message = format!("{} must be a positive integer", label);
+                self.reject_form(KEEPALIVE_FIELD + offset, message);
+                return;
+            }
+
+            // 编辑已有主机但没有改动任何字段时不产生变更
+            if self.editing_host_index.is_some() && !editing_data.has_changes() {
+                self.close_host_form();
+                return;
+            }
+
+            // 名称含 ssh 特殊字符时先警告，再保存一次才写入
+            let name = form.name.clone();
+            let name_changed = editing_data.is_modified(NAME_FIELD);
+            if name_changed
+                && editing_data.confirmed_name.as_ref() != Some(&name)
+                && let Some(problem) = host_name_problem(&name)
+            {
+                let message = format!("Name {} ({} again to keep it)", problem, self.form_save_label());
+                self.reject_form(NAME_FIELD, message);
+                if let Some(editing_data) = &mut self.editing_host {
+                    editing_data.confirmed_name = Some(name);
+                }
+                return;
+            }
+
+            let Some(mut new_host) = self.host_from_form() else {
+                return;
+            };
+            let saved_anchor = SelectionAnchor::Host {
+                name: new_host.name.clone(),
+                folder: new_host.folder.clone(),
+            };
+            // 写入主配置没有 Include 的文件时，ssh 不会读取到这台主机
+            let uncovered = new_host.source.clone().filter(|file| {
+                let base_dir = self.config_path.as_deref().and_then(Path::parent).unwrap_or(Path::new(""));
+                !self.included_files.contains(file) && !include_covers(&self.hosts, base_dir, file)
+            });
+
+            if let Some(host_idx) = self.editing_host_index {
+                // Editing existing host
+                if self.hosts.get(host_idx).is_some_and(|host| host.temporary) {
+                    // 临时主机直接修改，不产生变更
+                    self.hosts[host_idx] = new_host;
+                } else if let Some(old_host) = self.hosts.get(host_idx).cloned() {
+                    self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+                    self.current_edit_change_index = Some(self.pending_changes.len() - 1);
+                    self.hosts[host_idx] = new_host;
+                }
+            } else if self.adding_temporary.is_some() {
+                new_host.temporary = true;
+                self.hosts.push(new_host);
+            } else {
+                // Adding new host
+                self.pending_changes.push(ChangeType::Added(new_host.clone()));
+                self.current_edit_change_index = Some(self.pending_changes.len() - 1);
+                self.hosts.push(new_host);
+            }
+
+            // 保存后选中刚保存的主机（可能已改名或移动到其他文件夹）
+            self.filter_hosts();
+            self.restore_selection(Some(&saved_anchor));
+            if let Some(file) = uncovered {
+                let label = self.config_file_label(Some(&file));
+                self.set_status(StatusLevel::Warning, format!("{} is not included by the main config; ssh will not read it", label));
+            }
+        }
+
+        self.close_host_form();
+    }
+
+    pub fn apply_changes(&mut self) -> Result<()> {
+        // 不覆盖其他程序在加载后写入的内容
+        if self.check_disk_changes() && let Some(path) = &self.changed_on_disk {
+            return Err(SshcError::ChangedOnDisk(path.clone()));
+        }
+        let config_path = self.config_path
+            .clone()
+            .ok_or_else(|| SshcError::ConfigNotFound { attempted: crate::config::home_candidates() })?;
+        // 锁被其他实例抢走（或持有）时拒绝保存，避免互相覆盖
+        if !self.config_lock.as_ref().is_some_and(ConfigLock::is_held) {
+            match ConfigLock::acquire(&lock_path(&config_path)) {
+                Ok(LockStatus::Acquired(lock)) => self.config_lock = Some(lock),
+                Ok(LockStatus::HeldBy(holder)) => {
+                    return Err(SshcError::Config(format!(
+                        "another sshc (PID {}) holds the config lock; leave config management and enter it again to take the lock over",
+                        holder
+                    )));
+                }
+                // 与进入配置管理时一样：锁文件无法读写时照常保存，但要提示
+                Err(e) => self.set_status(StatusLevel::Warning, format!("{:#}; saving without a lock", e)),
+            }
+        }
+        // 按审阅中的顺序把变更应用到加载时的内容上，写入的就是 diff 所示的结果
+        let hosts = replay_changes(&self.original_hosts, &self.pending_changes).map_err(SshcError::Config)?;
+        // 备份失败时不写入，磁盘上的内容仍是最后一个可以恢复的版本
+        let backup_dir = self.backup_dir.clone().filter(|_| self.settings.backup_on_save);
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        if let Some(dir) = &backup_dir {
+            create_backup(dir, &config_path, now).map_err(|e| SshcError::Config(format!("Unable to back up the config before saving: {:#}", e)))?;
+        }
+        write_config_tree(&config_path, &self.included_files, &hosts, self.settings.write_style).map_err(|e| SshcError::Config(format!("{:#}", e)))?;
+        if let Some(dir) = &backup_dir && let Err(e) = prune_backups(dir, self.settings.backup_keep, self.settings.backup_max_age_days, now) {
+            self.set_status(StatusLevel::Warning, format!("Saved, but old backups could not be pruned: {:#}", e));
+        }
+        self.replace_hosts(hosts);
+        self.original_hosts = self.hosts.iter().filter(|host| !host.system).cloned().collect();
+        self.pending_changes.clear();
+        self.record_load_times();
+        if let Some(snapshot) = self.pending_inventory.take() {
+            self.record_inventory_snapshot(snapshot);
+        }
+        Ok(())
+    }
+
+    /// 记录各配置文件当前的修改时间作为基准，并清除磁盘修改标记
+    fn record_load_times(&mut self) {
+        self.loaded_mtimes = self.config_path
+            .iter()
+            .chain(&self.included_files)
+            .map(|path| (path.clone(), modified_time(path)))
+            .collect();
+        self.changed_on_disk = None;
+        self.disk_changed_hosts.clear();
+    }
+
+    /// 检查配置文件是否比加载时更新；发现后记下文件和磁盘上有变化的主机，返回是否已被修改
+    pub fn check_disk_changes(&mut self) -> bool {
+        if self.changed_on_disk.is_some() {
+            return true;
+        }
+        let Some(path) = self.loaded_mtimes
+            .iter()
+            .find(|(path, loaded)| modified_time(path) > *loaded)
+            .map(|(path, _)| path.clone())
+        else {
+            return false;
+        };
+
+        if let Some(config_path) = &self.config_path && let Ok((disk_hosts, _, _)) = parse_config_tree_with_limits(config_path, &self.settings.parse_limits()) {
+            let differs = |host: &SshHost, others: &[SshHost]| !others.iter().any(|other| other == host);
+            let mut names: Vec<String> = self.original_hosts
+                .iter()
+                .filter(|host| differs(host, &disk_hosts))
+                .chain(disk_hosts.iter().filter(|host| differs(host, &self.original_hosts)))
+                .filter(|host| !host.is_verbatim())
+                .map(|host| host.name.clone())
+                .collect();
+            names.sort();
+            names.dedup();
+            self.disk_changed_hosts = names;
+        }
+        self.changed_on_disk = Some(path);
+        if self.quick_filters.contains(&QuickFilter::ChangedOnDisk) {
+            self.filter_hosts();
+        }
+        true
+    }
+
+    pub fn discard_changes(&mut self) {
+        self.replace_hosts(self.original_hosts.clone());
+        self.pending_changes.clear();
+        self.pending_inventory = None;
+    }
+
+    /// 解析失败时保留内存中的主机和待保存变更
+    pub fn reload_config(&mut self) -> Result<()> {
+        let Some(config_path) = &self.config_path else {
+            return Ok(());
+        };
+        let (hosts, included_files, report) = parse_config_tree_with_limits(config_path, &self.settings.parse_limits())?;
+        self.included_files = included_files;
+        self.load_system_hosts();
+        self.record_load_times();
+        self.apply_limit_report(report);
+        self.original_hosts = hosts.clone();
+        self.replace_hosts(hosts);
+        self.pending_changes.clear();
+        self.pending_inventory = None;
+        Ok(())
+    }
+
+    /// 重新读取 system_config_paths；下次 replace_hosts 时放入列表
+    fn load_system_hosts(&mut self) {
+        self.system_hosts = parse_system_configs(&self.settings.system_config_paths, &self.settings.parse_limits()).0;
+    }
+
+    /// 系统主机和只读被包含文件中的主机不能修改：询问是否复制到用户配置，返回 true 表示已拦下
+    fn refuse_read_only_host(&mut self, host_index: usize) -> bool {
+        if !self.hosts.get(host_index).is_some_and(|host| host.system || host.locked) {
+            return false;
+        }
+        self.copy_system = Some((host_index, self.mode));
+        self.confirm = Selector::confirm();
+        self.mode = AppMode::ConfirmCopySystem;
+        true
+    }
+
+    fn handle_copy_system_input(&mut self, key_code: KeyCode) {
+        let Some((host_index, return_mode)) = self.copy_system else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        match self.confirm.handle_key(key_code) {
+            SelectorEvent::Accepted(CONFIRM_YES) => {
+                self.copy_system = None;
+                self.copy_system_host(host_index);
+            }
+            SelectorEvent::Accepted(_) | SelectorEvent::Cancelled => {
+                self.copy_system = None;
+                self.mode = return_mode;
+            }
+            SelectorEvent::Moved => {}
+            SelectorEvent::Unhandled => self.unbound_key(key_code),
+        }
+    }
+
+    /// 只读被包含文件中的主机复制到主配置时使用的名称。ssh 取第一个匹配的条目，
+    /// 被包含的条目可能排在前面，所以副本用一个不同的名称，避免被原条目遮住
+    pub fn copy_name_for(&self, host_index: usize) -> Option<String> {
+        let host = self.hosts.get(host_index).filter(|host| host.locked)?;
+        let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+        Some(unique_host_name(&host.name, &taken))
+    }
+
+    /// 把只读主机作为新增变更复制到主配置。系统主机的副本与它同名，列表中取代系统条目；
+    /// 只读被包含文件中的主机保留原条目，副本使用 copy_name_for 的名称
+    fn copy_system_host(&mut self, host_index: usize) {
+        let Some(original) = self.hosts.get(host_index).filter(|host| host.system || host.locked) else {
+            return;
+        };
+        let from = self.config_file_label(original.source.as_deref());
+        let name = self.copy_name_for(host_index).unwrap_or_else(|| original.name.clone());
+        let host = SshHost { name, system: false, locked: false, source: None, ..original.clone() };
+        let anchor = SelectionAnchor::Host { name: host.name.clone(), folder: host.folder.clone() };
+        if original.locked {
+            self.set_status(
+                StatusLevel::Info,
+                format!("Copied {} from {} into your config as {} (unsaved) so the read-only entry does not shadow it", original.name, from, host.name)
+            );
+            self.hosts.push(host.clone());
+        } else {
+            self.set_status(StatusLevel::Info, format!("Copied {} from {} into your config (unsaved); edit the copy", host.name, from));
+            self.hosts[host_index] = host.clone();
+        }
+        self.pending_changes.push(ChangeType::Added(host));
+        self.mode = AppMode::ConfigManagement;
+        self.filter_hosts();
+        self.restore_selection(Some(&anchor));
+    }
+
+    /// 记录超出解析限制的内容；有内容被跳过时转为只读，避免保存时把它们从文件中删掉
+    fn apply_limit_report(&mut self, report: LimitReport) {
+        if !report.is_empty() {
+            self.read_only = true;
+        }
+        self.limit_report = report;
+    }
+
+    /// 替换主机列表并重建树；选中项按替换前的主机身份恢复（下标可能已变化）
+    fn replace_hosts(&mut self, mut hosts: Vec<SshHost>) {
+        let anchor = self.selection_anchor();
+        let previous = self.list_state.selected();
+        // 临时主机不在文件中，重新加载或放弃变更后保留
+        hosts.retain(|host| !host.temporary);
+        hosts.extend(self.hosts.iter().filter(|host| host.temporary).cloned());
+        merge_system_hosts(&mut hosts, &self.system_hosts);
+        self.hosts = hosts;
+        self.rebuild_filtered_tree();
+        if !self.restore_selection(anchor.as_ref()) {
+            self.select_nearest(previous);
+        }
+    }
+
+    pub fn generate_diff_lines(&self) -> Vec<String> {
+        self.diff_with_blocks().0
+    }
+
+    /// diff 各行，以及每条变更所在块的起始行
+    fn diff_with_blocks(&self) -> (Vec<String>, Vec<usize>) {
+        let mut lines = Vec::new();
+        let mut blocks = Vec::new();
+        let mut i = 0;
+        // 配置分布在多个文件时，每个块前标注写入的文件
+        let multi_file = !self.included_files.is_empty() || self.hosts.iter().chain(&self.original_hosts).any(|host| host.source.is_some());
+        let file_label = |host: &SshHost| multi_file.then(|| format!("@ {}", self.config_file_label(host.source.as_deref())));
+
+        while i < self.pending_changes.len() {
+            // 同一文件夹内连续的可见性批量变更合并为一个摘要块
+            let run = self.visibility_run_len(i);
+            blocks.extend(std::iter::repeat_n(lines.len(), run.max(1)));
+            if run > 1 {
+                if let ChangeType::Modified { new, .. } = &self.pending_changes[i] {
+                    let folder = new.folder.clone().unwrap_or_default();
+                    let action = if new.visible { "shown" } else { "hidden" };
+                    lines.push(format!("~ Folder {}: {} hosts {}", folder, run, action));
+                }
+                for change in &self.pending_changes[i..i + run] {
+                    if let ChangeType::Modified { new, .. } = change {
+                        lines.push(format!("~   Host {} (# @visible: {})", new.name, new.visible));
+                    }
+                }
+                lines.push(String::new());
+                i += run;
+                continue;
+            }
+
+            let change = &self.pending_changes[i];
+            i += 1;
+            match change {
+                ChangeType::Added(host) => {
+                    if let Some(label) = &file_label(host) {
+                        lines.push(label.clone());
+                    }
+                    lines.extend(host_block_lines(host, '+'));
+                    lines.push(String::new());
+                }
+                // 移动到其他文件的主机显示为旧文件中的删除和新文件中的新增
+                ChangeType::Modified { old, new } if old.source != new.source => {
+                    for (host, sign) in [(old, '-'), (new, '+')] {
+                        if let Some(label) = &file_label(host) {
+                            lines.push(label.clone());
+                        }
+                        lines.extend(host_block_lines(host, sign));
+                        lines.push(String::new());
+                    }
+                }
+                ChangeType::Modified { old, new } => {
+                    if let Some(label) = &file_label(new) {
+                        lines.push(label.clone());
+                    }
+                    let host_line = |host: &SshHost| if host.disabled { format!("{} Host {}", DISABLED_PREFIX, host.name) } else { format!("Host {}", host.name) };
+                    // 改名时同时显示旧名和新名，审阅中才能看清依赖改名的其他变更
+                    let renamed = old.name != new.name;
+                    if renamed {
+                        lines.push(format!("- {}", host_line(old)));
+                        lines.push(format!("+ {}", host_line(new)));
+                    } else {
+                        lines.push(format!("~ Host {}", old.name));
+                    }
+                    
+                    // 比较元数据字段
+                    if old.folder != new.folder {
+                        if let Some(old_folder) = &old.folder {
+                            lines.push(format!("- # @folder: {}", old_folder));
+                        }
+                        if let Some(new_folder) = &new.folder {
+                            lines.push(format!("+ # @folder: {}", new_folder));
+                        }
+                    }
+                    
+                    if old.display_name != new.display_name {
+                        if let Some(old_name) = &old.display_name {
+                            lines.push(format!("- # @name: {}", old_name));
+                        }
+                        if let Some(new_name) = &new.display_name {
+                            lines.push(format!("+ # @name: {}", new_name));
+                        }
+                    }
+                    
+                    if old.description != new.description {
+                        if let Some(old_desc) = &old.description {
+                            lines.push(format!("- # @description: {}", old_desc));
+                        }
+                        if let Some(new_desc) = &new.description {
+                            lines.push(format!("+ # @description: {}", new_desc));
+                        }
+                    }
+                    
+                    if old.visible != new.visible {
+                        lines.push(format!("- # @visible: {}", old.visible));
+                        lines.push(format!("+ # @visible: {}", new.visible));
+                    }
+
+                    if old.disabled != new.disabled && !renamed {
+                        lines.push(format!("- {}", host_line(old)));
+                        lines.push(format!("+ {}", host_line(new)));
+                    }
+
+                    if old.tags != new.tags {
+                        if !old.tags.is_empty() {
+                            lines.push(format!("- # @tags: {}", old.tags.join(", ")));
+                        }
+                        if !new.tags.is_empty() {
+                            lines.push(format!("+ # @tags: {}", new.tags.join(", ")));
+                        }
+                    }
+
+                    if old.aliases != new.aliases {
+                        if !old.aliases.is_empty() {
+                            lines.push(format!("- # @alias: {}", old.aliases.join(", ")));
+                        }
+                        if !new.aliases.is_empty() {
+                            lines.push(format!("+ # @alias: {}", new.aliases.join(", ")));
+                        }
+                    }
+
+                    let metadata = [("style", &old.style, &new.style), ("color", &old.color, &new.color), ("connector", &old.connector, &new.connector)];
+                    for (key, old_value, new_value) in metadata {
+                        if old_value != new_value {
+                            if let Some(value) = old_value {
+                                lines.push(format!("- # @{}: {}", key, value));
+                            }
+                            if let Some(value) = new_value {
+                                lines.push(format!("+ # @{}: {}", key, value));
+                            }
+                        }
+                    }
+
+                    if old.env != new.env {
+                        for (key, value) in &old.env {
+                            lines.push(format!("- # @env: {}={}", key, value));
+                        }
+                        for (key, value) in &new.env {
+                            lines.push(format!("+ # @env: {}={}", key, value));
+                        }
+                    }
+                    
+                    // 比较基本SSH配置字段
+                    if old.hostname != new.hostname {
+                        if let Some(old_hostname) = &old.hostname {
+                            lines.push(format!("-   HostName {}", old_hostname));
+                        }
+                        if let Some(new_hostname) = &new.hostname {
+                            lines.push(format!("+   HostName {}", new_hostname));
+                        }
+                    }
+                    
+                    if old.user != new.user {
+                        if let Some(old_user) = &old.user {
+                            lines.push(format!("-   User {}", old_user));
+                        }
+                        if let Some(new_user) = &new.user {
+                            lines.push(format!("+   User {}", new_user));
+                        }
+                    }
+                    
+                    if old.port != new.port {
+                        if let Some(old_port) = &old.port {
+                            lines.push(format!("-   Port {}", old_port));
+                        }
+                        if let Some(new_port) = &new.port {
+                            lines.push(format!("+   Port {}", new_port));
+                        }
+                    }
+                    
+                    if old.identity_file != new.identity_file {
+                        if let Some(old_file) = &old.identity_file {
+                            lines.push(format!("-   IdentityFile {}", old_file));
+                        }
+                        if let Some(new_file) = &new.identity_file {
+                            lines.push(format!("+   IdentityFile {}", new_file));
+                        }
+                    }
+
+                    // 其他选项按键名排序比较
+                    let mut keys: Vec<&String> = old.other_options.keys().chain(new.other_options.keys()).collect();
+                    keys.sort();
+                    keys.dedup();
+                    for key in keys {
+                        let old_values: Vec<&String> = old.other_options.get_all(key).collect();
+                        let new_values: Vec<&String> = new.other_options.get_all(key).collect();
+                        if old_values != new_values {
+                            let option_name = option_display_name(key);
+                            for value in old_values {
+                                lines.push(format!("-   {} {}", option_name, value));
+                            }
+                            for value in new_values {
+                                lines.push(format!("+   {} {}", option_name, value));
+                            }
+                        }
+                    }
+                    
+                    lines.push(String::new());
+                }
+                ChangeType::Deleted(host) => {
+                    if let Some(label) = &file_label(host) {
+                        lines.push(label.clone());
+                    }
+                    lines.extend(host_block_lines(host, '-'));
+                    lines.push(String::new());
+                }
+            }
+        }
+
+        (lines, blocks)
+    }
+
+    /// 从 start 开始，只切换了可见性、且属于同一文件夹和同一方向的连续变更数量
+    fn visibility_run_len(&self, start: usize) -> usize {
+        let is_visibility_only = |change: &ChangeType| -> Option<(Option<String>, bool)> {
+            if let ChangeType::Modified { old, new } = change
+                && old.visible != new.visible
+            {
+                let mut flipped = old.clone();
+                flipped.visible = new.visible;
+                if flipped == *new {
+                    return Some((new.folder.clone(), new.visible));
+                }
+            }
+            None
+        };
+
+        let Some(first) = self.pending_changes.get(start).and_then(is_visibility_only) else {
+            return 0;
+        };
+        if first.0.is_none() {
+            return 0;
+        }
+
+        self.pending_changes[start..]
+            .iter()
+            .take_while(|change| is_visibility_only(change).as_ref() == Some(&first))
+            .count()
+    }
+
+    fn has_edit_changes(&self) -> bool {
+        self.editing_host.as_ref().is_some_and(EditingHostData::has_changes)
+    }
+
+    fn handle_discard_edit_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match self.confirm.handle_key(key_code) {
+            SelectorEvent::Accepted(CONFIRM_YES) => {
+                // Discard changes and exit
+                self.discard_current_edit();
+                self.close_host_form();
+            }
+            SelectorEvent::Accepted(_) | SelectorEvent::Cancelled => {
+                // Return to editing
+                self.mode = AppMode::EditingHost;
+            }
+            SelectorEvent::Moved => {}
+            SelectorEvent::Unhandled => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn discard_current_edit(&mut self) {
+        // If there's a current edit change that was already saved, remove it and revert the hosts
+        if let Some(change_index) = self.current_edit_change_index
+            && change_index < self.pending_changes.len()
+        {
+            match &self.pending_changes[change_index] {
+                ChangeType::Added(_) => {
+                    // Remove the added host
+                    if self.editing_host_index.is_some() {
+                        // This is an edit, don't remove
+                    } else {
+                        // This was a new addition, remove the last host
+                        self.hosts.pop();
+                    }
+                }
+                ChangeType::Modified { old, .. } => {
+                    // Revert to old host
+                    if let Some(host_idx) = self.editing_host_index {
+                        self.hosts[host_idx] = old.clone();
+                    }
+                }
+                ChangeType::Deleted(_) => {
+                    // This shouldn't happen in edit context
+                }
+            }
+                
+            // Remove the change from pending_changes
+            self.pending_changes.remove(change_index);
+            self.filter_hosts();
+        }
+        // Note: If current_edit_change_index is None, it means the user was editing
+        // but never saved (never pressed Enter), so there's nothing to revert in
+        // hosts or pending_changes - just clearing the editing state is sufficient
+    }
+
+    fn handle_version_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match key_code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Char('m') => {
+                self.reference = Some(ReferenceView::new(AppMode::ShowVersion));
+                self.mode = AppMode::Reference;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn handle_reference_input(&mut self, key_code: KeyCode) {
+        let Some(view) = &mut self.reference else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        if view.search.editing {
+            match key_code {
+                KeyCode::Char(c) => view.search.query.push(c),
+                KeyCode::Backspace => {
+                    view.search.query.pop();
+                }
+                KeyCode::Enter => {
+                    // 从当前可见位置开始找第一个匹配
+                    view.search.editing = false;
+                    view.search.current = view.scroll.checked_sub(1);
+                    view.jump_to_match(true);
+                }
+                KeyCode::Esc => view.search = ReviewSearch::default(),
+                key_code => self.unbound_key(key_code),
+            }
+            return;
+        }
+
+        let searching = !view.search.query.is_empty();
+        match key_code {
+            KeyCode::Char('/') => view.search = ReviewSearch { editing: true, ..ReviewSearch::default() },
+            KeyCode::Char('n') if searching => {
+                view.jump_to_match(true);
+            }
+            KeyCode::Char('N') if searching => {
+                view.jump_to_match(false);
+            }
+            KeyCode::Esc if searching => view.search = ReviewSearch::default(),
+            KeyCode::Up => view.scroll_by(-1),
+            KeyCode::Down => view.scroll_by(1),
+            KeyCode::PageUp => view.scroll_by(-10),
+            KeyCode::PageDown => view.scroll_by(10),
+            KeyCode::Home => view.scroll = 0,
+            KeyCode::End => view.scroll_by(isize::MAX),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.mode = view.return_mode;
+                self.reference = None;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+    }
+
+    /// 选中文件夹时准备批量连接其中所有可见主机
+    pub fn start_group_connect(&mut self) {
+        let Some(TreeItem::Folder { name, children_indices, .. }) =
+            self.list_state.selected().and_then(|i| self.tree_items.get(i)) else {
+            return;
+        };
+
+        let host_indices: Vec<usize> = children_indices
+            .iter()
+            .copied()
+            .filter(|&i| self.hosts.get(i).is_some_and(|h| h.visible && !h.disabled))
+            .collect();
+        if host_indices.is_empty() {
+            return;
+        }
+        let folder = name.clone();
+        let needs_ssh = host_indices.iter().any(|&i| uses_ssh(&self.hosts[i]));
+        if self.refuse_without_ssh(needs_ssh) {
+            return;
+        }
+
+        let use_tmux = inside_tmux();
+        let needs_confirm = !use_tmux || host_indices.len() > self.settings.group_connect_confirm_threshold;
+        self.group_connect = Some(GroupConnect { folder, host_indices, use_tmux });
+
+        if needs_confirm {
+            self.mode = AppMode::ConfirmGroupConnect;
+        } else {
+            self.pending_group_connect = true;
         }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i >= self.tree_items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+    }
+
+    fn handle_group_connect_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.pending_group_connect = true;
+                self.mode = AppMode::Normal;
             }
-            None => 0,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.group_connect = None;
+                self.mode = AppMode::Normal;
+            }
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    /// 把选中的主机加入连接队列，已在队列中则移除
+    pub fn toggle_queued(&mut self) {
+        let Some(name) = self.get_selected_host().map(|h| h.name.clone()) else {
+            return;
         };
-        self.list_state.select(Some(i));
+        if let Some(position) = self.connect_queue.iter().position(|n| *n == name) {
+            self.connect_queue.remove(position);
+            self.set_status(StatusLevel::Info, format!("Removed {} from the queue ({} queued)", name, self.connect_queue.len()));
+        } else if self.get_selected_host().is_some_and(|h| h.disabled) {
+            self.set_status(StatusLevel::Warning, format!("{} is disabled and cannot be queued", name));
+        } else {
+            self.connect_queue.push(name.clone());
+            self.set_status(StatusLevel::Info, format!("Queued {} ({} queued)", name, self.connect_queue.len()));
+        }
     }
 
-    pub fn previous(&mut self) {
-        if self.tree_items.is_empty() {
+    pub fn clear_queue(&mut self) {
+        if self.connect_queue.is_empty() {
             return;
         }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.tree_items.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.list_state.select(Some(i));
-    }
-
-    pub fn connect_to_selected(&self, terminal: &mut TerminalManager) -> Result<()> {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(tree_item) = self.tree_items.get(selected) {
-                match tree_item {
-                    TreeItem::Host { host_index } => {
-                        if let Some(host) = self.hosts.get(*host_index) {
-                            terminal.suspend()?;
-                            
-                            let status = Command::new("ssh")
-                                .arg(&host.name)
-                                .status();
-                            
-                            terminal.resume()?;
-                            
-                            match status {
-                                Ok(_) => {
-                                    // Force a complete redraw by clearing the terminal
-                                    terminal.terminal().clear().map_err(|e| SshcError::Terminal(e.to_string()))?;
-                                },
-                                Err(e) => return Err(SshcError::Ssh(format!("SSH connection error: {}", e))),
-                            }
-                        }
-                    },
-                    TreeItem::Folder { .. } => {
-                        // 文件夹项目不能连接，但可以展开/收起
-                        // 这个逻辑会在按键处理中实现
-                    }
-                }
+        self.connect_queue.clear();
+        self.queue_running = false;
+        self.set_status(StatusLevel::Info, "Connection queue cleared".to_string());
+    }
+
+    /// 开始按队列连接：tmux 中一次性打开所有 pane，否则依次连接，每个会话结束后询问下一个
+    pub fn start_queue(&mut self) {
+        if self.connect_queue.is_empty() {
+            self.set_status(StatusLevel::Warning, "The connection queue is empty (press + to queue a host)".to_string());
+            return;
+        }
+        let needs_ssh = self.connect_queue.iter().any(|name| self.hosts.iter().any(|h| h.name == *name && uses_ssh(h)));
+        if self.refuse_without_ssh(needs_ssh) {
+            return;
+        }
+
+        if inside_tmux() {
+            let host_indices = std::mem::take(&mut self.connect_queue)
+                .iter()
+                .filter_map(|name| self.hosts.iter().position(|h| h.name == *name && !h.disabled))
+                .collect();
+            self.group_connect = Some(GroupConnect { folder: "queue".to_string(), host_indices, use_tmux: true });
+            self.pending_group_connect = true;
+            return;
+        }
+
+        // exec 模式下 ssh 取代了本进程，无法在会话结束后继续
+        if self.settings.connection_screen == ConnectionScreen::Exec {
+            self.set_status(StatusLevel::Warning, "The connection queue needs tmux when connection_screen is \"exec\"".to_string());
+            return;
+        }
+
+        self.queue_running = true;
+        self.connect_next_queued();
+    }
+
+    /// 连接队列中下一个仍存在的主机；队列耗尽时结束
+    fn connect_next_queued(&mut self) {
+        while !self.connect_queue.is_empty() {
+            let name = self.connect_queue.remove(0);
+            if let Some(index) = self.hosts.iter().position(|h| h.name == name && !h.disabled) {
+                self.mode = AppMode::Normal;
+                self.pending_connect = Some(index);
+                return;
             }
         }
-        Ok(())
+        self.finish_queue();
     }
 
-    pub fn get_selected_host(&self) -> Option<&SshHost> {
-        self.list_state.selected()
-            .and_then(|selected| self.tree_items.get(selected))
-            .and_then(|tree_item| match tree_item {
-                TreeItem::Host { host_index } => self.hosts.get(*host_index),
-                TreeItem::Folder { .. } => None,
-            })
+    fn finish_queue(&mut self) {
+        self.queue_running = false;
+        self.mode = AppMode::Normal;
+        self.set_status(StatusLevel::Info, "Connection queue finished".to_string());
     }
 
-    fn start_adding_host(&mut self) {
-        let editing_data = EditingHostData {
-            name: String::new(),
-            hostname: String::new(),
-            user: String::new(),
-            port: String::new(),
-            identity_file: String::new(),
-            folder: String::new(),
-            display_name: String::new(),
-            description: String::new(),
-            visible: true,
-            current_field: 0,
-            original_name: String::new(),
-            original_hostname: String::new(),
-            original_user: String::new(),
-            original_port: String::new(),
-            original_identity_file: String::new(),
-            original_folder: String::new(),
-            original_display_name: String::new(),
-            original_description: String::new(),
-            original_visible: true,
-        };
-        self.editing_host = Some(editing_data);
-        self.editing_host_index = None;
-        self.current_edit_change_index = None;
-        self.mode = AppMode::EditingHost;
+    /// 队列中的一个会话结束后调用：还有主机时询问是否继续
+    pub fn queue_session_finished(&mut self) {
+        if self.connect_queue.is_empty() {
+            self.queue_running = false;
+            return;
+        }
+        // 显示连接错误时，关闭错误弹窗后再询问
+        if self.mode == AppMode::Normal {
+            self.mode = AppMode::QueueNext;
+        }
     }
 
-    fn start_editing_selected_host(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(tree_item) = self.tree_items.get(selected) {
-                if let TreeItem::Host { host_index } = tree_item {
-                    if let Some(host) = self.hosts.get(*host_index) {
-                    let name = host.name.clone();
-                    let hostname = host.hostname.clone().unwrap_or_default();
-                    let user = host.user.clone().unwrap_or_default();
-                    let port = host.port.clone().unwrap_or_default();
-                    let identity_file = host.identity_file.clone().unwrap_or_default();
-                    let folder = host.folder.clone().unwrap_or_default();
-                    let display_name = host.display_name.clone().unwrap_or_default();
-                    let description = host.description.clone().unwrap_or_default();
-                    let visible = host.visible;
-                    
-                    let editing_data = EditingHostData {
-                        name: name.clone(),
-                        hostname: hostname.clone(),
-                        user: user.clone(),
-                        port: port.clone(),
-                        identity_file: identity_file.clone(),
-                        folder: folder.clone(),
-                        display_name: display_name.clone(),
-                        description: description.clone(),
-                        visible,
-                        current_field: 0,
-                        original_name: name,
-                        original_hostname: hostname,
-                        original_user: user,
-                        original_port: port,
-                        original_identity_file: identity_file,
-                        original_folder: folder,
-                        original_display_name: display_name,
-                        original_description: description,
-                        original_visible: visible,
-                    };
-                    self.editing_host = Some(editing_data);
-                    self.editing_host_index = Some(*host_index);
-                    self.current_edit_change_index = None;
-                    self.mode = AppMode::EditingHost;
-                    }
+    fn handle_queue_next_input(&mut self, key_code: KeyCode) -> Result<()> {
+        match key_code {
+            KeyCode::Enter => self.connect_next_queued(),
+            KeyCode::Char('s') => {
+                if !self.connect_queue.is_empty() {
+                    self.connect_queue.remove(0);
+                }
+                if self.connect_queue.is_empty() {
+                    self.finish_queue();
                 }
             }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                // 停止后剩余主机仍留在队列中，可以稍后继续
+                self.queue_running = false;
+                self.mode = AppMode::Normal;
+                self.set_status(StatusLevel::Info, format!("Queue stopped, {} host(s) left", self.connect_queue.len()));
+            }
+            key_code => self.unbound_key(key_code),
         }
+        Ok(())
     }
 
-    fn start_deleting_selected_host(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(tree_item) = self.tree_items.get(selected) {
-                if let TreeItem::Host { host_index } = tree_item {
-                    self.delete_target = Some(*host_index);
-                    self.mode = AppMode::ConfirmDelete;
-                }
+    /// 执行批量连接：tmux 中每个主机一个 pane，否则依次连接；失败汇总后统一报告
+    fn run_group_connect(&mut self, terminal: &mut TerminalManager) -> Result<()> {
+        let Some(group) = self.group_connect.take() else {
+            return Ok(());
+        };
+        let hosts: Vec<SshHost> = group.host_indices
+            .iter()
+            .filter_map(|&i| self.hosts.get(i).cloned())
+            .collect();
+
+        // 连接器命令无法渲染的主机直接记为失败
+        let mut failures = Vec::new();
+        let mut sessions = Vec::new();
+        for host in &hosts {
+            match connect_command(host, None, &self.settings.connectors) {
+                Ok(command) => sessions.push((host.clone(), command)),
+                Err(e) => failures.push(format!("{}: {}", host.name, e)),
             }
         }
-    }
 
-    fn handle_editing_input(&mut self, key_code: KeyCode, terminal: &mut TerminalManager) -> Result<()> {
-        if let Some(ref mut editing_data) = self.editing_host {
-            match key_code {
-                KeyCode::Esc => {
-                    if self.has_edit_changes() {
-                        self.mode = AppMode::ConfirmDiscardEdit;
-                    } else {
-                        self.editing_host = None;
-                        self.editing_host_index = None;
-                        self.mode = AppMode::ConfigManagement;
-                    }
-                }
-                KeyCode::Tab | KeyCode::Down => {
-                    editing_data.current_field = (editing_data.current_field + 1) % 9;
-                }
-                KeyCode::BackTab | KeyCode::Up => {
-                    editing_data.current_field = if editing_data.current_field == 0 { 8 } else { editing_data.current_field - 1 };
-                }
-                KeyCode::Enter => {
-                    self.save_edited_host();
-                    terminal.terminal().clear().map_err(|e| SshcError::Terminal(e.to_string()))?;
-                }
-                KeyCode::Backspace => {
-                    match editing_data.current_field {
-                        0 => { editing_data.name.pop(); },
-                        1 => { editing_data.hostname.pop(); },
-                        2 => { editing_data.user.pop(); },
-                        3 => { editing_data.port.pop(); },
-                        4 => { editing_data.identity_file.pop(); },
-                        5 => { editing_data.folder.pop(); },
-                        6 => { editing_data.display_name.pop(); },
-                        7 => { editing_data.description.pop(); },
-                        8 => { }, // 可见性字段不支持backspace
-                        _ => {},
-                    };
+        if group.use_tmux {
+            failures.extend(open_tmux_panes(&group.folder, &sessions));
+        } else {
+            terminal.suspend_with(self.settings.connection_screen == ConnectionScreen::Keep)?;
+            let messages = self.settings.connection_messages;
+            for (host, command) in &sessions {
+                if messages {
+                    terminal.print_outside(&connecting_message(host, None), Color::Cyan)?;
                 }
-                KeyCode::Char(' ') => {
-                    if editing_data.current_field == 8 {
-                        editing_data.visible = !editing_data.visible;
-                    } else {
-                        // 对其他字段添加空格
-                        match editing_data.current_field {
-                            0 => { editing_data.name.push(' '); },
-                            1 => { editing_data.hostname.push(' '); },
-                            2 => { editing_data.user.push(' '); },
-                            3 => { editing_data.port.push(' '); },
-                            4 => { editing_data.identity_file.push(' '); },
-                            5 => { editing_data.folder.push(' '); },
-                            6 => { editing_data.display_name.push(' '); },
-                            7 => { editing_data.description.push(' '); },
-                            _ => {},
-                        };
+                let (program, args) = command.split_first().expect("connect command is never empty");
+                match Command::new(program).args(args).envs(host_env(host)).status() {
+                    Ok(status) if uses_ssh(host) && status.code() == Some(255) => {
+                        failures.push(format!("{}: ssh exited with status 255 (connection failed)", host.name));
                     }
+                    Ok(_) => {}
+                    Err(e) => failures.push(format!("{}: unable to start {}: {}", host.name, program, e)),
                 }
-                KeyCode::Char(c) => {
-                    match editing_data.current_field {
-                        0 => { editing_data.name.push(c); },
-                        1 => { editing_data.hostname.push(c); },
-                        2 => { editing_data.user.push(c); },
-                        3 => { editing_data.port.push(c); },
-                        4 => { editing_data.identity_file.push(c); },
-                        5 => { editing_data.folder.push(c); },
-                        6 => { editing_data.display_name.push(c); },
-                        7 => { editing_data.description.push(c); },
-                        8 => { 
-                            // 对于可见性字段，允许输入 t/f 或 y/n
-                            match c.to_lowercase().next() {
-                                Some('t') | Some('y') => editing_data.visible = true,
-                                Some('f') | Some('n') => editing_data.visible = false,
-                                _ => {},
-                            }
-                        },
-                        _ => {},
-                    };
-                }
-                _ => {}
             }
+            let last = hosts.last().map(|host| host.name.as_str()).unwrap_or_default();
+            if messages && terminal.print_outside(&disconnected_message(last), Color::Yellow)? {
+                terminal.wait_for_key()?;
+            }
+            terminal.resume()?;
+        }
+
+        if !failures.is_empty() {
+            self.show_message(
+                format!("Group connect: {} of {} hosts failed", failures.len(), hosts.len()),
+                failures
+            );
         }
         Ok(())
     }
 
-    fn handle_delete_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
-        match key_code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                if let Some(host_idx) = self.delete_target {
-                    if let Some(host) = self.hosts.get(host_idx).cloned() {
-                        self.pending_changes.push(ChangeType::Deleted(host));
-                        self.hosts.remove(host_idx);
-                        self.filter_hosts();
-                        
-                        // Update selection
-                        if let Some(selected) = self.list_state.selected() {
-                            if self.tree_items.is_empty() {
-                                self.list_state.select(None);
-                            } else if selected >= self.tree_items.len() {
-                                self.list_state.select(Some(self.tree_items.len() - 1));
-                            }
-                        }
+    /// 对选中的文件夹（或选中主机所在的文件夹）批量设置 IdentityFile
+    pub fn start_bulk_identity(&mut self) {
+        let folder = match self.list_state.selected().and_then(|i| self.tree_items.get(i)) {
+            Some(TreeItem::Folder { name, .. }) => name.clone(),
+            Some(TreeItem::Host { host_index }) => match self.hosts.get(*host_index).and_then(|h| h.folder.clone()) {
+                Some(folder) => folder,
+                None => {
+                    self.set_status(StatusLevel::Warning, "Select a folder to change its identity file".to_string());
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        let host_indices = self.hosts
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.folder.as_deref() == Some(folder.as_str()))
+            .map(|(i, _)| i)
+            .collect();
+        self.bulk_identity = Some(BulkIdentityState {
+            folder,
+            host_indices,
+            new_path: String::new(),
+            only_when: String::new(),
+            field: 0,
+            picker: None,
+        });
+        self.mode = AppMode::BulkIdentity;
+    }
+
+    /// 会被修改的主机：满足过滤条件且当前值与新路径不同
+    pub fn bulk_identity_targets(&self) -> Vec<usize> {
+        let Some(state) = &self.bulk_identity else {
+            return Vec::new();
+        };
+        let new_path = expand_tilde(state.new_path.trim());
+        let only_when = expand_tilde(state.only_when.trim());
+
+        state.host_indices
+            .iter()
+            .copied()
+            .filter(|&i| !self.hosts[i].system)
+            .filter(|&i| {
+                let current = self.hosts[i].identity_file.as_deref().map(expand_tilde).unwrap_or_default();
+                (only_when.is_empty() || current == only_when) && current != new_path
+            })
+            .collect()
+    }
+
+    fn handle_bulk_identity_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.bulk_identity else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+
+        // 私钥选择列表
+        if let Some((keys, selected)) = &mut state.picker {
+            match key_code {
+                KeyCode::Up => *selected = selected.saturating_sub(1),
+                KeyCode::Down => *selected = (*selected + 1).min(keys.len().saturating_sub(1)),
+                KeyCode::Enter => {
+                    if let Some(key) = keys.get(*selected).cloned() {
+                        if state.field == 0 { state.new_path = key } else { state.only_when = key }
                     }
+                    state.picker = None;
                 }
-                self.delete_target = None;
+                KeyCode::Esc | KeyCode::F(2) => state.picker = None,
+                key_code => self.unbound_key(key_code),
+            }
+            return Ok(());
+        }
+
+        let field = if state.field == 0 { &mut state.new_path } else { &mut state.only_when };
+        match key_code {
+            KeyCode::Esc => {
+                self.bulk_identity = None;
                 self.mode = AppMode::ConfigManagement;
             }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                self.delete_target = None;
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => state.field = 1 - state.field,
+            KeyCode::F(2) => {
+                let keys = home_dir().map(|home| list_private_keys(&home.join(".ssh"))).unwrap_or_default();
+                if keys.is_empty() {
+                    self.set_status(StatusLevel::Warning, "No private keys found in ~/.ssh".to_string());
+                } else {
+                    state.picker = Some((keys, 0));
+                }
+            }
+            KeyCode::Char(c) => field.push(c),
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Enter => {
+                if state.new_path.trim().is_empty() {
+                    return Ok(());
+                }
+                let new_path = state.new_path.trim().to_string();
+                let targets = self.bulk_identity_targets();
+                for &host_index in &targets {
+                    let old_host = self.hosts[host_index].clone();
+                    let mut new_host = old_host.clone();
+                    new_host.identity_file = Some(new_path.clone());
+                    self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+                    self.hosts[host_index] = new_host;
+                }
+                self.bulk_identity = None;
                 self.mode = AppMode::ConfigManagement;
+                self.filter_hosts();
+                self.set_status(StatusLevel::Info, format!("IdentityFile staged for {} hosts", targets.len()));
             }
-            _ => {}
+            key_code => self.unbound_key(key_code),
         }
         Ok(())
     }
 
-    fn handle_review_input(&mut self, key_code: KeyCode) -> Result<()> {
-        match key_code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                self.apply_changes()?;
-                self.mode = AppMode::Normal;
-                self.review_scroll = 0;
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') => {
-                self.discard_changes();
-                self.mode = AppMode::Normal;
-                self.review_scroll = 0;
+    pub fn start_key_rotation(&mut self, old_path: String) {
+        let field = if old_path.is_empty() { 0 } else { 1 };
+        self.key_rotation = Some(KeyRotationState { old_path, new_path: String::new(), append: false, field, picker: None });
+        self.mode = AppMode::RotateKey;
+    }
+
+    /// 轮换后会变化的主机及其新版本；临时主机和原样保留的块不参与
+    pub fn key_rotation_changes(&self) -> Vec<(usize, SshHost)> {
+        let Some(state) = &self.key_rotation else {
+            return Vec::new();
+        };
+        let home = home_dir();
+        self.hosts
+            .iter()
+            .enumerate()
+            .filter(|(_, host)| !host.temporary && !host.system && !host.is_verbatim())
+            .filter_map(|(i, host)| rotate_identity(host, &state.old_path, &state.new_path, state.append, home.as_deref()).map(|new| (i, new)))
+            .collect()
+    }
+
+    fn handle_key_rotation_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let Some(state) = &mut self.key_rotation else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+
+        if let Some((keys, selected)) = &mut state.picker {
+            match key_code {
+                KeyCode::Up => *selected = selected.saturating_sub(1),
+                KeyCode::Down => *selected = (*selected + 1).min(keys.len().saturating_sub(1)),
+                KeyCode::Enter => {
+                    if let Some(key) = keys.get(*selected).cloned() {
+                        if state.field == 0 { state.old_path = key } else { state.new_path = key }
+                    }
+                    state.picker = None;
+                }
+                KeyCode::Esc | KeyCode::F(2) => state.picker = None,
+                key_code => self.unbound_key(key_code),
             }
+            return Ok(());
+        }
+
+        match key_code {
             KeyCode::Esc => {
+                self.key_rotation = None;
                 self.mode = AppMode::ConfigManagement;
-                self.review_scroll = 0;
             }
-            KeyCode::Up => {
-                if self.review_scroll > 0 {
-                    self.review_scroll -= 1;
+            KeyCode::Tab | KeyCode::Down => state.field = (state.field + 1) % 3,
+            KeyCode::BackTab | KeyCode::Up => state.field = (state.field + 2) % 3,
+            KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right if state.field == 2 => state.append = !state.append,
+            KeyCode::F(2) if state.field < 2 => {
+                let keys = home_dir().map(|home| list_private_keys(&home.join(".ssh"))).unwrap_or_default();
+                if keys.is_empty() {
+                    self.set_status(StatusLevel::Warning, "No private keys found in ~/.ssh".to_string());
+                } else {
+                    state.picker = Some((keys, 0));
                 }
             }
-            KeyCode::Down => {
-                self.review_scroll += 1;
+            KeyCode::Char(c) if state.field < 2 => {
+                if state.field == 0 { state.old_path.push(c) } else { state.new_path.push(c) }
             }
-            KeyCode::PageUp => {
-                self.review_scroll = self.review_scroll.saturating_sub(10);
+            KeyCode::Backspace if state.field < 2 => {
+                if state.field == 0 { state.old_path.pop() } else { state.new_path.pop() };
             }
-            KeyCode::PageDown => {
-                self.review_scroll += 10;
+            KeyCode::Enter => {
+                let changes = self.key_rotation_changes();
+                if changes.is_empty() {
+                    return Ok(());
+                }
+                for (host_index, new_host) in &changes {
+                    let old_host = self.hosts[*host_index].clone();
+                    self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+                    self.hosts[*host_index] = new_host.clone();
+                }
+                self.key_rotation = None;
+                self.mode = AppMode::ConfigManagement;
+                self.filter_hosts();
+                self.set_status(StatusLevel::Info, format!("IdentityFile rotated on {} hosts (unsaved)", changes.len()));
             }
-            _ => {}
+            key_code => self.unbound_key(key_code),
         }
         Ok(())
     }
 
-    fn save_edited_host(&mut self) {
-        if let Some(editing_data) = &self.editing_host {
-            if editing_data.name.trim().is_empty() {
-                return;
+    /// 导出选中的主机；选中文件夹时导出其中所有主机
+    pub fn start_export(&mut self) {
+        let host_indices = match self.list_state.selected().and_then(|i| self.tree_items.get(i)) {
+            Some(TreeItem::Host { host_index }) => vec![*host_index],
+            Some(TreeItem::Folder { children_indices, .. }) => children_indices.clone(),
+            None => return,
+        };
+        if host_indices.is_empty() {
+            return;
+        }
+
+        self.export = Some(ExportState {
+            host_indices,
+            options: SnippetOptions { include_metadata: false, tilde_paths: true },
+            csv: false,
+            file_path: String::new(),
+            editing_path: false,
+            status: None,
+            return_mode: self.mode,
+        });
+        self.mode = AppMode::ExportHosts;
+    }
+
+    pub fn export_preview(&self) -> String {
+        match &self.export {
+            Some(export) => {
+                let hosts: Vec<SshHost> = export.host_indices
+                    .iter()
+                    .filter_map(|&i| self.hosts.get(i).cloned())
+                    .collect();
+                if !export.csv {
+                    return export_snippet(&hosts, export.options).unwrap_or_else(|e| format!("Unable to build snippet: {}", e));
+                }
+                let home = home_dir().map(|h| h.to_string_lossy().to_string());
+                let hosts: Vec<SshHost> = hosts
+                    .into_iter()
+                    .map(|mut host| {
+                        if export.options.tilde_paths && let (Some(home), Some(path)) = (&home, &host.identity_file) {
+                            host.identity_file = Some(tilde_relative(path, home));
+                        }
+                        host
+                    })
+                    .collect();
+                export_csv(&hosts).unwrap_or_else(|e| format!("Unable to build CSV: {}", e))
             }
+            None => String::new(),
+        }
+    }
+
+    fn handle_export_input(&mut self, key_code: KeyCode) -> Result<()> {
+        let snippet = self.export_preview();
+        let Some(export) = &mut self.export else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        if export.editing_path {
+            match key_code {
+                KeyCode::Char(c) => export.file_path.push(c),
+                KeyCode::Backspace => {
+                    export.file_path.pop();
+                }
+                KeyCode::Esc => export.editing_path = false,
+                KeyCode::Enter => {
+                    export.editing_path = false;
+                    let path = expand_tilde(export.file_path.trim());
+                    export.status = Some(match std::fs::write(&path, &snippet) {
+                        Ok(()) => format!("Written to {}", path),
+                        Err(e) => format!("Unable to write {}: {}", path, e),
+                    });
+                }
+                key_code => self.unbound_key(key_code),
+            }
+            return Ok(());
+        }
 
-            let mut new_host = SshHost::new(editing_data.name.clone());
-            if !editing_data.hostname.is_empty() {
-                new_host.hostname = Some(editing_data.hostname.clone());
+        match key_code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = export.return_mode;
+                self.export = None;
             }
-            if !editing_data.user.is_empty() {
-                new_host.user = Some(editing_data.user.clone());
+            KeyCode::Char('m') => {
+                export.options.include_metadata = !export.options.include_metadata;
             }
-            if !editing_data.port.is_empty() {
-                new_host.port = Some(editing_data.port.clone());
+            KeyCode::Char('t') => {
+                export.options.tilde_paths = !export.options.tilde_paths;
             }
-            if !editing_data.identity_file.is_empty() {
-                new_host.identity_file = Some(editing_data.identity_file.clone());
+            KeyCode::Char('x') => {
+                export.csv = !export.csv;
+                export.status = None;
             }
-            
-            // 设置元数据字段
-            if !editing_data.folder.is_empty() {
-                new_host.folder = Some(editing_data.folder.clone());
+            KeyCode::Char('c') => {
+                export.status = Some(match copy_to_clipboard(&snippet) {
+                    Ok(()) => "Copied to clipboard".to_string(),
+                    Err(e) => e.to_string(),
+                });
+            }
+            KeyCode::Char('f') => {
+                export.editing_path = true;
+                export.status = None;
             }
-            if !editing_data.display_name.is_empty() {
-                new_host.display_name = Some(editing_data.display_name.clone());
+            key_code => self.unbound_key(key_code),
+        }
+        Ok(())
+    }
+
+    fn handle_import_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(import) = &mut self.import else {
+            self.mode = AppMode::ConfigManagement;
+            return Ok(());
+        };
+
+        match import.stage {
+            ImportStage::Input => {
+                let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                match key.code {
+                    KeyCode::Esc => {
+                        self.import = None;
+                        self.mode = AppMode::ConfigManagement;
+                    }
+                    KeyCode::Char('d') if ctrl => self.parse_import_text(),
+                    KeyCode::F(2) => self.parse_import_text(),
+                    KeyCode::Char(c) => import.text.push(c),
+                    KeyCode::Enter => import.text.push('\n'),
+                    KeyCode::Tab => import.text.push('\t'),
+                    KeyCode::Backspace => {
+                        import.text.pop();
+                    }
+                    _ => self.unbound_key(key.code),
+                }
+            }
+            ImportStage::Descriptions if key.code == KeyCode::Char('o') => {
+                import.overwrite = !import.overwrite;
+                import.descriptions = plan_descriptions(&import.description_csv, &self.hosts, import.overwrite);
+            }
+            ImportStage::Descriptions => {
+                match import.picker.handle_key(key.code) {
+                    SelectorEvent::Cancelled => import.stage = ImportStage::Input,
+                    SelectorEvent::Accepted(_) => self.stage_descriptions(),
+                    SelectorEvent::Moved => {}
+                    SelectorEvent::Unhandled => self.unbound_key(key.code),
+                }
             }
-            if !editing_data.description.is_empty() {
-                new_host.description = Some(editing_data.description.clone());
+            ImportStage::Review => {
+                match import.picker.handle_key(key.code) {
+                    // 回到输入阶段，原文保留
+                    SelectorEvent::Cancelled => import.stage = ImportStage::Input,
+                    SelectorEvent::Accepted(_) => self.stage_import(),
+                    SelectorEvent::Moved => {}
+                    SelectorEvent::Unhandled => self.set_import_resolution(key.code),
+                }
             }
-            new_host.visible = editing_data.visible;
+        }
+        Ok(())
+    }
 
-            if let Some(host_idx) = self.editing_host_index {
-                // Editing existing host
-                if let Some(old_host) = self.hosts.get(host_idx).cloned() {
-                    self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
-                    self.current_edit_change_index = Some(self.pending_changes.len() - 1);
-                    self.hosts[host_idx] = new_host;
+    /// 审阅导入时 a/s/o/r/m 修改选中主机的处理方式；只读的已有主机不能合并，其他按键没有绑定
+    fn set_import_resolution(&mut self, key_code: KeyCode) {
+        let KeyCode::Char(c @ ('a' | 's' | 'o' | 'r' | 'm')) = key_code else {
+            self.unbound_key(key_code);
+            return;
+        };
+        if let Some(import) = &mut self.import
+            && let Some(candidate) = import.candidates.get_mut(import.picker.selected)
+        {
+            let mergeable = candidate.duplicate
+                .and_then(|duplicate| self.hosts.get(duplicate.existing))
+                .is_some_and(|host| !host.system && !host.locked && !host.temporary);
+            candidate.resolution = match (c, candidate.conflict) {
+                ('s', _) => ConflictResolution::Skip,
+                ('a', false) => ConflictResolution::Add,
+                ('o', true) => ConflictResolution::Overwrite,
+                ('r', true) => ConflictResolution::Rename,
+                ('m', false) if mergeable => ConflictResolution::Merge,
+                _ => candidate.resolution,
+            };
+        }
+    }
+
+    /// 以文件路径打开导入弹窗并直接解析（命令行 import-csv 使用）
+    pub fn start_import_file(&mut self, path: &std::path::Path) {
+        let mut import = ImportState::new();
+        import.text = path.to_string_lossy().to_string();
+        self.import = Some(import);
+        self.mode = AppMode::ImportSnippet;
+        self.parse_import_text();
+    }
+
+    /// 解析导入文本；整段文本是一个已存在的文件路径时读取该文件
+    fn parse_import_text(&mut self) {
+        let Some(import) = &mut self.import else {
+            return;
+        };
+
+        let trimmed = import.text.trim();
+        let path = expand_tilde(trimmed);
+        let content = if !trimmed.contains('\n') && !trimmed.is_empty() && std::path::Path::new(&path).is_file() {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    import.warnings = vec![format!("Unable to read {}: {}", path, e)];
+                    return;
                 }
-            } else {
-                // Adding new host
-                self.pending_changes.push(ChangeType::Added(new_host.clone()));
-                self.current_edit_change_index = Some(self.pending_changes.len() - 1);
-                self.hosts.push(new_host);
             }
+        } else {
+            import.text.clone()
+        };
 
-            self.filter_hosts();
+        // 两列的 "名称,描述" CSV：批量设置描述，先显示匹配报告
+        if is_description_csv(&content, path.to_lowercase().ends_with(".csv")) {
+            match parse_description_csv(&content) {
+                Ok(rows) if rows.is_empty() => import.warnings = vec!["No rows found in CSV".to_string()],
+                Ok(rows) => {
+                    import.warnings.clear();
+                    import.descriptions = plan_descriptions(&rows, &self.hosts, import.overwrite);
+                    import.description_csv = rows;
+                    import.picker = Selector::new(import.descriptions.len());
+                    import.stage = ImportStage::Descriptions;
+                }
+                Err(e) => import.warnings = vec![format!("{:#}", e)],
+            }
+            return;
         }
 
-        self.editing_host = None;
-        self.editing_host_index = None;
-        self.current_edit_change_index = None;
-        self.mode = AppMode::ConfigManagement;
-    }
+        // 表格导出的 CSV（文件扩展名或表头判断）
+        if path.to_lowercase().ends_with(".csv") || looks_like_csv(&content) {
+            match parse_csv(&content) {
+                Ok((parsed, warnings)) => {
+                    import.warnings = warnings;
+                    import.candidates = build_import_candidates(parsed, &self.hosts);
+                    import.picker = Selector::new(import.candidates.len());
+                    if import.candidates.is_empty() {
+                        import.warnings.push("No hosts found in CSV".to_string());
+                    } else {
+                        import.stage = ImportStage::Review;
+                    }
+                }
+                Err(e) => import.warnings = vec![format!("{:#}", e)],
+            }
+            return;
+        }
 
-    fn apply_changes(&mut self) -> Result<()> {
-        write_ssh_config(&self.hosts).map_err(|e| SshcError::Config(e.to_string()))?;
-        self.original_hosts = self.hosts.clone();
-        self.pending_changes.clear();
-        Ok(())
-    }
+        let (mut parsed, mut warnings) = parse_ssh_config_with_warnings(&content);
+        // 片段中的全局选项和 Match 块不导入
+        parsed.retain(|host| !host.is_verbatim());
+        // 没有 Host 块时尝试按 ssh 命令解析
+        if parsed.is_empty() {
+            let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+            if let Some(host) = parse_ssh_command(&content, &taken) {
+                parsed.push(host);
+                warnings.clear();
+            }
+        }
+        import.warnings = warnings;
+        import.candidates = build_import_candidates(parsed, &self.hosts);
+        import.picker = Selector::new(import.candidates.len());
 
-    fn discard_changes(&mut self) {
-        self.hosts = self.original_hosts.clone();
-        self.pending_changes.clear();
-        self.filter_hosts();
+        if import.candidates.is_empty() {
+            import.warnings.push("No Host blocks found".to_string());
+        } else {
+            import.stage = ImportStage::Review;
+        }
     }
 
-    pub fn reload_config(&mut self) -> Result<()> {
-        self.hosts = parse_ssh_config()?;
-        self.original_hosts = self.hosts.clone();
-        self.pending_changes.clear();
-        self.filter_hosts();
-        Ok(())
-    }
+    /// 把确认导入的主机加入待保存变更
+    fn stage_import(&mut self) {
+        let Some(import) = self.import.take() else {
+            return;
+        };
 
-    pub fn generate_diff_lines(&self) -> Vec<String> {
-        let mut lines = Vec::new();
-        
-        for change in &self.pending_changes {
-            match change {
-                ChangeType::Added(host) => {
-                    // 显示元数据注释
-                    if let Some(folder) = &host.folder {
-                        lines.push(format!("+ # @folder: {}", folder));
-                    }
-                    if let Some(display_name) = &host.display_name {
-                        lines.push(format!("+ # @name: {}", display_name));
-                    }
-                    if let Some(description) = &host.description {
-                        lines.push(format!("+ # @description: {}", description));
-                    }
-                    if !host.visible {
-                        lines.push(format!("+ # @visible: false"));
-                    }
-                    
-                    lines.push(format!("+ Host {}", host.name));
-                    if let Some(hostname) = &host.hostname {
-                        lines.push(format!("+   HostName {}", hostname));
-                    }
-                    if let Some(user) = &host.user {
-                        lines.push(format!("+   User {}", user));
-                    }
-                    if let Some(port) = &host.port {
-                        lines.push(format!("+   Port {}", port));
-                    }
-                    if let Some(identity_file) = &host.identity_file {
-                        lines.push(format!("+   IdentityFile {}", identity_file));
-                    }
-                    for (key, value) in &host.other_options {
-                        lines.push(format!("+   {} {}", 
-                            key.chars().next().unwrap().to_uppercase().chain(key.chars().skip(1)).collect::<String>(),
-                            value));
-                    }
-                    lines.push(String::new());
+        for candidate in import.candidates {
+            let mut host = candidate.host;
+            match candidate.resolution {
+                ConflictResolution::Skip => {}
+                ConflictResolution::Add => {
+                    self.pending_changes.push(ChangeType::Added(host.clone()));
+                    self.hosts.push(host);
                 }
-                ChangeType::Modified { old, new } => {
-                    lines.push(format!("~ Host {}", old.name));
-                    
-                    // 比较元数据字段
-                    if old.folder != new.folder {
-                        if let Some(old_folder) = &old.folder {
-                            lines.push(format!("- # @folder: {}", old_folder));
-                        }
-                        if let Some(new_folder) = &new.folder {
-                            lines.push(format!("+ # @folder: {}", new_folder));
-                        }
-                    }
-                    
-                    if old.display_name != new.display_name {
-                        if let Some(old_name) = &old.display_name {
-                            lines.push(format!("- # @name: {}", old_name));
-                        }
-                        if let Some(new_name) = &new.display_name {
-                            lines.push(format!("+ # @name: {}", new_name));
-                        }
-                    }
-                    
-                    if old.description != new.description {
-                        if let Some(old_desc) = &old.description {
-                            lines.push(format!("- # @description: {}", old_desc));
-                        }
-                        if let Some(new_desc) = &new.description {
-                            lines.push(format!("+ # @description: {}", new_desc));
-                        }
-                    }
-                    
-                    if old.visible != new.visible {
-                        lines.push(format!("- # @visible: {}", old.visible));
-                        lines.push(format!("+ # @visible: {}", new.visible));
-                    }
-                    
-                    // 比较基本SSH配置字段
-                    if old.hostname != new.hostname {
-                        if let Some(old_hostname) = &old.hostname {
-                            lines.push(format!("-   HostName {}", old_hostname));
-                        }
-                        if let Some(new_hostname) = &new.hostname {
-                            lines.push(format!("+   HostName {}", new_hostname));
-                        }
-                    }
-                    
-                    if old.user != new.user {
-                        if let Some(old_user) = &old.user {
-                            lines.push(format!("-   User {}", old_user));
-                        }
-                        if let Some(new_user) = &new.user {
-                            lines.push(format!("+   User {}", new_user));
-                        }
-                    }
-                    
-                    if old.port != new.port {
-                        if let Some(old_port) = &old.port {
-                            lines.push(format!("-   Port {}", old_port));
-                        }
-                        if let Some(new_port) = &new.port {
-                            lines.push(format!("+   Port {}", new_port));
-                        }
-                    }
-                    
-                    if old.identity_file != new.identity_file {
-                        if let Some(old_file) = &old.identity_file {
-                            lines.push(format!("-   IdentityFile {}", old_file));
-                        }
-                        if let Some(new_file) = &new.identity_file {
-                            lines.push(format!("+   IdentityFile {}", new_file));
-                        }
-                    }
-                    
-                    lines.push(String::new());
+                ConflictResolution::Rename => {
+                    let taken: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+                    host.name = unique_host_name(&host.name, &taken);
+                    self.pending_changes.push(ChangeType::Added(host.clone()));
+                    self.hosts.push(host);
                 }
-                ChangeType::Deleted(host) => {
-                    // 显示被删除的元数据注释
-                    if let Some(folder) = &host.folder {
-                        lines.push(format!("- # @folder: {}", folder));
+                ConflictResolution::Overwrite => {
+                    if let Some(index) = self.hosts.iter().position(|h| h.name == host.name) {
+                        let old = self.hosts[index].clone();
+                        self.pending_changes.push(ChangeType::Modified { old, new: host.clone() });
+                        self.hosts[index] = host;
                     }
-                    if let Some(display_name) = &host.display_name {
-                        lines.push(format!("- # @name: {}", display_name));
-                    }
-                    if let Some(description) = &host.description {
-                        lines.push(format!("- # @description: {}", description));
-                    }
-                    if !host.visible {
-                        lines.push(format!("- # @visible: false"));
-                    }
-                    
-                    lines.push(format!("- Host {}", host.name));
-                    if let Some(hostname) = &host.hostname {
-                        lines.push(format!("-   HostName {}", hostname));
-                    }
-                    if let Some(user) = &host.user {
-                        lines.push(format!("-   User {}", user));
-                    }
-                    if let Some(port) = &host.port {
-                        lines.push(format!("-   Port {}", port));
-                    }
-                    if let Some(identity_file) = &host.identity_file {
-                        lines.push(format!("-   IdentityFile {}", identity_file));
-                    }
-                    for (key, value) in &host.other_options {
-                        lines.push(format!("-   {} {}", 
-                            key.chars().next().unwrap().to_uppercase().chain(key.chars().skip(1)).collect::<String>(),
-                            value));
+                }
+                ConflictResolution::Merge => {
+                    // 导入期间只会在末尾追加主机，解析时记录的下标仍然有效
+                    let Some(index) = candidate.duplicate.map(|duplicate| duplicate.existing) else {
+                        continue;
+                    };
+                    let old = self.hosts[index].clone();
+                    let new = fill_blanks(&old, &host);
+                    if new != old {
+                        self.pending_changes.push(ChangeType::Modified { old, new: new.clone() });
+                        self.hosts[index] = new;
                     }
-                    lines.push(String::new());
                 }
             }
         }
-        
-        lines
+
+        self.filter_hosts();
+        self.mode = AppMode::ConfigManagement;
     }
 
-    fn has_edit_changes(&self) -> bool {
-        if let Some(editing_data) = &self.editing_host {
-            editing_data.name != editing_data.original_name ||
-            editing_data.hostname != editing_data.original_hostname ||
-            editing_data.user != editing_data.original_user ||
-            editing_data.port != editing_data.original_port ||
-            editing_data.identity_file != editing_data.original_identity_file ||
-            editing_data.folder != editing_data.original_folder ||
-            editing_data.display_name != editing_data.original_display_name ||
-            editing_data.description != editing_data.original_description ||
-            editing_data.visible != editing_data.original_visible
+    /// 暂存描述 CSV 中需要更新的描述，每台主机一条修改
+    fn stage_descriptions(&mut self) {
+        let Some(import) = self.import.take() else {
+            return;
+        };
+
+        let mut updated = 0;
+        for row in import.descriptions.iter().filter(|row| row.outcome == DescriptionOutcome::Update) {
+            if let Some(index) = self.hosts.iter().position(|host| host.name == row.name && !host.is_verbatim()) {
+                let old = self.hosts[index].clone();
+                let new = SshHost { description: row.description.clone(), ..old.clone() };
+                self.pending_changes.push(ChangeType::Modified { old, new: new.clone() });
+                self.hosts[index] = new;
+                updated += 1;
+            }
+        }
+
+        self.filter_hosts();
+        self.mode = AppMode::ConfigManagement;
+        if updated == 0 {
+            self.set_status(StatusLevel::Info, "No descriptions to update".to_string());
         } else {
-            false
+            self.set_status(StatusLevel::Info, format!("Staged new descriptions for {} host(s)", updated));
         }
     }
 
-    fn handle_discard_edit_confirm_input(&mut self, key_code: KeyCode) -> Result<()> {
-        match key_code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                // Discard changes and exit
-                self.discard_current_edit();
-                self.editing_host = None;
-                self.editing_host_index = None;
-                self.current_edit_change_index = None;
-                self.mode = AppMode::ConfigManagement;
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                // Return to editing
-                self.mode = AppMode::EditingHost;
+    /// 只读查看原样保留的部分：第一个 Host 之前的全局选项和 Match 块（sshc 不解析其中的条件）
+    pub fn show_verbatim_blocks(&mut self) {
+        let blocks: Vec<&SshHost> = self.hosts.iter().filter(|host| host.is_verbatim()).collect();
+        if blocks.is_empty() {
+            self.set_status(StatusLevel::Info, "No global options or Match blocks in the config".to_string());
+            return;
+        }
+        let mut lines = vec![
+            "These parts are kept exactly as written and are not evaluated,".to_string(),
+            "so hosts may connect with settings not shown in their details.".to_string(),
+        ];
+        for host in &blocks {
+            lines.push(String::new());
+            if let Some(raw) = &host.prelude {
+                lines.push("Before the first Host:".to_string());
+                lines.extend(raw.lines().map(|line| format!("  {}", line)));
+            } else if let Some(raw) = &host.match_block {
+                lines.extend(raw.lines().map(str::to_string));
             }
-            _ => {}
         }
-        Ok(())
+        self.show_message("Global options & Match rules".to_string(), lines);
     }
 
-    fn discard_current_edit(&mut self) {
-        // If there's a current edit change that was already saved, remove it and revert the hosts
-        if let Some(change_index) = self.current_edit_change_index {
-            if change_index < self.pending_changes.len() {
-                match &self.pending_changes[change_index] {
-                    ChangeType::Added(_) => {
-                        // Remove the added host
-                        if self.editing_host_index.is_some() {
-                            // This is an edit, don't remove
-                        } else {
-                            // This was a new addition, remove the last host
-                            self.hosts.pop();
-                        }
-                    }
-                    ChangeType::Modified { old, .. } => {
-                        // Revert to old host
-                        if let Some(host_idx) = self.editing_host_index {
-                            self.hosts[host_idx] = old.clone();
-                        }
-                    }
-                    ChangeType::Deleted(_) => {
-                        // This shouldn't happen in edit context
-                    }
+    /// 按密钥列出主机引用的公钥及其指纹，标出找不到 .pub 的私钥
+    pub fn show_key_report(&mut self) {
+        let entries = build_key_report(&self.hosts, home_dir().as_deref());
+        self.show_message("Public keys".to_string(), key_report_table(&entries));
+    }
+
+    pub fn show_message(&mut self, title: String, lines: Vec<String>) {
+        let return_mode = match self.mode {
+            AppMode::ConfigManagement => AppMode::ConfigManagement,
+            _ => AppMode::Normal,
+        };
+        self.message = Some(MessagePopup { title, lines, scroll: 0, return_mode });
+        self.mode = AppMode::ShowMessage;
+    }
+
+    fn handle_message_input(&mut self, key_code: KeyCode) -> Result<()> {
+        if let Some(message) = &mut self.message {
+            match key_code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                    self.mode = message.return_mode;
+                    self.message = None;
                 }
-                
-                // Remove the change from pending_changes
-                self.pending_changes.remove(change_index);
-                self.filter_hosts();
+                KeyCode::Up => {
+                    message.scroll = message.scroll.saturating_sub(1);
+                }
+                KeyCode::Down => {
+                    message.scroll = (message.scroll + 1).min(message.lines.len().saturating_sub(1));
+                }
+                key_code => self.unbound_key(key_code),
             }
+        } else {
+            self.mode = AppMode::Normal;
         }
-        // Note: If current_edit_change_index is None, it means the user was editing
-        // but never saved (never pressed Enter), so there's nothing to revert in
-        // hosts or pending_changes - just clearing the editing state is sufficient
+        Ok(())
     }
 
-    fn handle_version_input(&mut self, key_code: KeyCode) -> Result<()> {
+    fn handle_connection_error_input(&mut self, key_code: KeyCode) -> Result<()> {
         match key_code {
             KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
-                self.mode = AppMode::Normal;
+                self.connection_error = None;
+                self.connection_error_scroll = 0;
+                // 队列运行中，看完错误后继续询问下一个
+                self.mode = if self.queue_running { AppMode::QueueNext } else { AppMode::Normal };
             }
-            _ => {}
+            KeyCode::Up => {
+                self.connection_error_scroll = self.connection_error_scroll.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                let lines = self.connection_error.as_ref().map_or(0, |e| e.stderr.len());
+                if self.connection_error_scroll + 1 < lines {
+                    self.connection_error_scroll += 1;
+                }
+            }
+            key_code => self.unbound_key(key_code),
         }
         Ok(())
     }
@@ -886,10 +6045,12 @@ impl App {
         }
     }
 
+    /// 已有主机的文件夹，加上本次会话在表单中新建的文件夹
     pub fn get_available_folders(&self) -> Vec<String> {
         let mut folders: Vec<String> = self.hosts
             .iter()
             .filter_map(|host| host.folder.clone())
+            .chain(self.session_folders.iter().cloned())
             .collect::<std::collections::HashSet<_>>()
             .into_iter()
             .collect();
@@ -898,18 +6059,27 @@ impl App {
     }
 
     pub fn rebuild_tree(&mut self) {
+        let visible: Vec<usize> = self.hosts
+            .iter()
+            .enumerate()
+            .filter(|(_, host)| host.visible || self.show_hidden)
+            .map(|(i, _)| i)
+            .collect();
+        self.build_tree(&visible, false);
+    }
+
+    /// 用给定的主机构建树；searching 时文件夹的收起状态只在本次搜索内有效
+    fn build_tree(&mut self, host_indices: &[usize], searching: bool) {
         self.tree_items.clear();
         
         // 按文件夹分组主机
         let mut folder_groups: std::collections::HashMap<Option<String>, Vec<usize>> = std::collections::HashMap::new();
         
-        for (index, host) in self.hosts.iter().enumerate() {
-            if !host.visible {
-                continue; // 跳过不可见的主机
+        for &index in host_indices {
+            // Match 块和全局部分不是主机，不出现在列表中
+            if let Some(host) = self.hosts.get(index) && !host.is_verbatim() {
+                folder_groups.entry(host.folder.clone()).or_default().push(index);
             }
-            
-            let folder_key = host.folder.clone();
-            folder_groups.entry(folder_key).or_insert_with(Vec::new).push(index);
         }
         
         // 处理有文件夹的主机，按字母顺序排序
@@ -925,16 +6095,20 @@ impl App {
                     name_a.cmp(&name_b)
                 });
                 
+                let collapsed = if searching { &self.search_collapsed_folders } else { &self.collapsed_folders };
+                let expanded = !collapsed.contains(&folder_name);
                 let folder_item = TreeItem::Folder {
                     name: folder_name,
-                    expanded: true,  // 默认展开
+                    expanded,  // 默认展开，保留用户收起的状态
                     children_indices: host_indices.clone(),
                 };
                 self.tree_items.push(folder_item);
                 
                 // 添加文件夹中的主机（只在展开状态下）
-                for &host_index in &host_indices {
-                    self.tree_items.push(TreeItem::Host { host_index });
+                if expanded {
+                    for &host_index in &host_indices {
+                        self.tree_items.push(TreeItem::Host { host_index });
+                    }
                 }
             }
         }
@@ -953,9 +6127,109 @@ impl App {
         }
     }
 
+    /// 列表中的位置：选中主机是第几台，以及列出的主机总数。收起的文件夹中的主机也计入，文件夹行不计
+    pub fn list_position(&self) -> ListPosition {
+        let selected = self.list_state.selected();
+        let mut position = ListPosition::default();
+        for (row, item) in self.tree_items.iter().enumerate() {
+            match item {
+                TreeItem::Folder { name, expanded, children_indices } => {
+                    if !expanded {
+                        position.total += children_indices.len();
+                    }
+                    if selected == Some(row) {
+                        position.folder = Some(name.clone());
+                    }
+                }
+                TreeItem::Host { host_index } => {
+                    position.total += 1;
+                    if selected == Some(row) {
+                        position.host = Some(position.total);
+                        position.folder = self.hosts.get(*host_index).and_then(|host| host.folder.clone());
+                    }
+                }
+            }
+        }
+        position
+    }
+
+    /// 按 ratatui List 的滚动规则计算可见窗口 (start, end)，使选中行始终可见
+    pub fn list_window(len: usize, selected: Option<usize>, offset: usize, height: usize) -> (usize, usize) {
+        if len == 0 || height == 0 {
+            return (0, 0);
+        }
+
+        let mut start = offset.min(len - 1);
+        if let Some(selected) = selected {
+            let selected = selected.min(len - 1);
+            if selected >= start + height {
+                start = selected + 1 - height;
+            } else if selected < start {
+                start = selected;
+            }
+        }
+
+        (start, (start + height).min(len))
+    }
+
+    pub fn toggle_show_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.filter_hosts();
+    }
+
+    /// 文件夹中的所有主机均不可见时返回 true
+    pub fn folder_all_hidden(&self, children_indices: &[usize]) -> bool {
+        !children_indices.is_empty() &&
+            children_indices
+                .iter()
+                .all(|&i| self.hosts.get(i).is_some_and(|h| !h.visible))
+    }
+
+    /// 批量切换文件夹下所有主机的可见性，每个主机生成一条 Modified 变更
+    pub fn toggle_folder_visibility(&mut self, folder_index: usize) {
+        let folder_name = match self.tree_items.get(folder_index) {
+            Some(TreeItem::Folder { name, .. }) => name.clone(),
+            _ => return,
+        };
+
+        let children: Vec<usize> = self.hosts
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.folder.as_deref() == Some(folder_name.as_str()) && !h.system)
+            .map(|(i, _)| i)
+            .collect();
+
+        // 只要有一个主机可见就全部隐藏，否则全部显示
+        let make_visible = self.folder_all_hidden(&children);
+
+        for host_index in children {
+            let old_host = self.hosts[host_index].clone();
+            if old_host.visible == make_visible {
+                continue;
+            }
+            let mut new_host = old_host.clone();
+            new_host.visible = make_visible;
+            self.pending_changes.push(ChangeType::Modified { old: old_host, new: new_host.clone() });
+            self.hosts[host_index] = new_host;
+        }
+
+        self.filter_hosts();
+    }
+
     pub fn toggle_folder_expanded(&mut self, folder_index: usize) {
-        if let Some(&mut TreeItem::Folder { ref mut expanded, ref children_indices, .. }) = self.tree_items.get_mut(folder_index) {
+        if let Some(&mut TreeItem::Folder { ref name, ref mut expanded, ref children_indices }) = self.tree_items.get_mut(folder_index) {
             *expanded = !*expanded;
+            // 搜索中的收起只影响本次搜索结果
+            let collapsed = if self.search_query.is_empty() {
+                &mut self.collapsed_folders
+            } else {
+                &mut self.search_collapsed_folders
+            };
+            if *expanded {
+                collapsed.remove(name);
+            } else {
+                collapsed.insert(name.clone());
+            }
             
             if *expanded {
                 // 展开：在文件夹后按排序顺序插入子项
@@ -990,4 +6264,56 @@ pub struct VersionInfo {
     pub license: String,
     pub description: String,
     pub repository: String,
-}
\ No newline at end of file
+}
+/// 用连接命令（通常是 ssh）替换当前进程；只有 exec 失败时才会返回
+#[cfg(unix)]
+fn exec_command(program: &str, args: &[String], env: &[(String, String)], connecting: Option<&str>, terminal: &mut TerminalManager) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    terminal.restore()?;
+    if let Some(text) = connecting {
+        terminal.print_outside(text, Color::Cyan)?;
+    }
+    let error = Command::new(program).args(args).envs(env.iter().cloned()).exec();
+    Err(SshcError::Ssh(format!("Unable to exec {}: {}", program, error)))
+}
+
+/// 非 Unix 平台没有 exec，退回为普通的挂起/恢复方式
+#[cfg(not(unix))]
+fn exec_command(program: &str, args: &[String], env: &[(String, String)], connecting: Option<&str>, terminal: &mut TerminalManager) -> Result<()> {
+    terminal.suspend()?;
+    if let Some(text) = connecting {
+        terminal.print_outside(text, Color::Cyan)?;
+    }
+    let status = Command::new(program).args(args).envs(env.iter().cloned()).status();
+    terminal.resume()?;
+    status.map(|_| ()).map_err(|e| SshcError::Ssh(format!("Connection error: {}", e)))
+}
+
+/// 展开路径开头的 ~
+pub fn expand_tilde(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix('~')
+        && (rest.is_empty() || rest.starts_with('/'))
+        && let Some(home) = crate::config::home_dir()
+    {
+        return format!("{}{}", home.to_string_lossy(), rest);
+    }
+    path.to_string()
+}
+
+/// 不可达的排在最前，其次是探测中、跳过的，最后是可达的；同组按主机名排序
+fn sort_dashboard(dashboard: &mut DashboardState, hosts: &[SshHost]) {
+    let current = dashboard.rows.get(dashboard.selected).map(|(index, _)| *index);
+    dashboard.rows.sort_by(|(a, a_status), (b, b_status)| {
+        let rank = |status: &ProbeStatus| match status {
+            ProbeStatus::Unreachable(_) => 0,
+            ProbeStatus::Pending => 1,
+            ProbeStatus::Skipped(_) => 2,
+            ProbeStatus::Reachable(_) => 3,
+        };
+        rank(a_status).cmp(&rank(b_status)).then_with(|| hosts[*a].name.cmp(&hosts[*b].name))
+    });
+    if let Some(current) = current {
+        dashboard.selected = dashboard.rows.iter().position(|(index, _)| *index == current).unwrap_or(0);
+    }
+}
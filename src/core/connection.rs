@@ -0,0 +1,298 @@
+use std::path::Path;
+use std::process::{ Command, ExitStatus, Stdio };
+use std::time::Duration;
+
+use crate::config::SshHost;
+
+/// ssh 很快以非零状态退出时收集到的错误信息，显示在弹窗中
+#[derive(Debug, Clone)]
+pub struct ConnectionError {
+    pub host: String,
+    pub exit_code: Option<i32>,
+    pub stderr: Vec<String>,
+}
+
+/// 交给 ssh 前在终端外打印的提示，例如 "Connecting to web-prod (admin@10.0.4.2) …"
+pub fn connecting_message(host: &SshHost, user: Option<&str>) -> String {
+    let user = user.or(host.user.as_deref());
+    let mut target = match (user, host.resolved_hostname()) {
+        (Some(user), Some(hostname)) => format!("{}@{}", user, hostname),
+        (Some(user), None) => format!("{}@{}", user, host.name),
+        (None, Some(hostname)) => hostname,
+        (None, None) => String::new(),
+    };
+    if let Some(port) = &host.port {
+        target = format!("{} port {}", target, port).trim_start().to_string();
+    }
+    if target.is_empty() {
+        format!("Connecting to {} …", host.name)
+    } else {
+        format!("Connecting to {} ({}) …", host.name, target)
+    }
+}
+
+/// ssh 结束、回到界面之前打印的提示
+pub fn disconnected_message(host: &str) -> String {
+    format!("Disconnected from {} — press any key", host)
+}
+
+/// 把会话时长格式化为 "1h 23m"、"5m 12s"、"42s"
+pub fn format_duration(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let (hours, minutes, seconds) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// 非零退出且耗时低于阈值，通常说明连接根本没有建立（ProxyCommand 出错、DNS 失败等）
+pub fn is_quick_failure(status: &ExitStatus, elapsed: Duration, threshold: Duration) -> bool {
+    !status.success() && elapsed < threshold
+}
+
+/// 连接是否算作失败：ssh 自身出错时退出码为 255，很快以非零状态退出也说明没有连上
+pub fn is_connection_failure(status: &ExitStatus, elapsed: Duration, threshold: Duration) -> bool {
+    status.code() == Some(255) || is_quick_failure(status, elapsed, threshold)
+}
+
+/// 连接失败的原因，按 ssh 的 stderr 分类；没有收集到 stderr 时只能看退出码
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailureReason {
+    Authentication,
+    HostKey,
+    Dns,
+    Refused,
+    Timeout,
+    Unreachable,
+    Closed,
+    Ssh,
+    Exit,
+}
+
+impl FailureReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureReason::Authentication => "authentication failed",
+            FailureReason::HostKey => "host key verification failed",
+            FailureReason::Dns => "hostname could not be resolved",
+            FailureReason::Refused => "connection refused",
+            FailureReason::Timeout => "connection timed out",
+            FailureReason::Unreachable => "host unreachable",
+            FailureReason::Closed => "connection closed by the remote side",
+            FailureReason::Ssh => "ssh error",
+            FailureReason::Exit => "non-zero exit",
+        }
+    }
+}
+
+/// 按 stderr 中的典型信息分类，前面的规则优先
+pub fn classify_failure(exit_code: Option<i32>, stderr: &[String]) -> FailureReason {
+    const RULES: &[(&[&str], FailureReason)] = &[
+        (&["host key verification failed", "remote host identification has changed"], FailureReason::HostKey),
+        (&["permission denied", "too many authentication failures", "no supported authentication methods"], FailureReason::Authentication),
+        (&["could not resolve hostname", "name or service not known", "nodename nor servname"], FailureReason::Dns),
+        (&["connection refused"], FailureReason::Refused),
+        (&["timed out"], FailureReason::Timeout),
+        (&["no route to host", "network is unreachable", "host is down"], FailureReason::Unreachable),
+        (&["connection closed", "connection reset", "kex_exchange_identification", "broken pipe"], FailureReason::Closed),
+    ];
+    let text = stderr.join("\n").to_lowercase();
+    RULES
+        .iter()
+        .find(|(patterns, _)| patterns.iter().any(|pattern| text.contains(pattern)))
+        .map(|(_, reason)| *reason)
+        .unwrap_or(if exit_code == Some(255) { FailureReason::Ssh } else { FailureReason::Exit })
+}
+
+/// 会话时长超过设置的分钟数时才提醒；0 表示关闭
+pub fn should_notify(elapsed: Duration, notify_after_minutes: u64) -> bool {
+    notify_after_minutes > 0 && elapsed >= Duration::from_secs(notify_after_minutes * 60)
+}
+
+/// 终端响铃
+pub fn ring_bell() {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(b"\x07");
+    let _ = stdout.flush();
+}
+
+/// 会话结束提醒：终端响铃，并尽量发送桌面通知。通知程序不存在或失败时静默忽略
+pub fn notify_session_end(host: &str, elapsed: Duration, exit_code: Option<i32>) {
+    ring_bell();
+
+    let exit = exit_code.map_or("signal".to_string(), |code| format!("exit {}", code));
+    let body = format!("Session to {} ended after {} ({})", host, format_duration(elapsed), exit);
+    let command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title \"sshc\"", body);
+        Some(("osascript", vec!["-e".to_string(), script]))
+    } else if cfg!(unix) {
+        Some(("notify-send", vec!["sshc".to_string(), body]))
+    } else {
+        None
+    };
+
+    // 在后台等待通知程序退出，不阻塞界面
+    if let Some((program, args)) = command {
+        std::thread::spawn(move || {
+            let _ = Command::new(program)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        });
+    }
+}
+
+/// 以非交互方式重新运行一次 ssh 并收集 stderr，交互式会话本身不受影响
+pub fn capture_ssh_stderr(args: &[String], env: &[(String, String)]) -> Vec<String> {
+    let output = Command::new("ssh")
+        .envs(env.iter().cloned())
+        .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"])
+        .args(args)
+        .arg("exit")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output();
+
+    match output {
+        Ok(output) =>
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .map(|line| line.trim_end().to_string())
+                .filter(|line| !line.is_empty())
+                .collect(),
+        Err(e) => vec![format!("Unable to run ssh: {}", e)],
+    }
+}
+
+/// 展开值中的 ${VAR} 和 $VAR，未设置的变量按 shell 习惯替换为空
+pub fn expand_env_value(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            // 变量名需以字母或下划线开头，"$5" 之类保持原样
+            let end = after
+                .char_indices()
+                .find(|&(i, c)| !(c.is_ascii_alphabetic() || c == '_' || (i > 0 && c.is_ascii_digit())))
+                .map(|(i, _)| i)
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        if name.is_empty() {
+            result.push('$');
+            rest = after;
+        } else {
+            result.push_str(&std::env::var(name).unwrap_or_default());
+            rest = &after[consumed..];
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// 主机配置的环境变量，值已展开
+pub fn host_env(host: &SshHost) -> Vec<(String, String)> {
+    host.env
+        .iter()
+        .map(|(key, value)| (key.clone(), expand_env_value(value)))
+        .collect()
+}
+
+/// ssh -G 的参数；给出 config 时以 -F 读取该文件，与界面中编辑的配置一致
+pub fn effective_config_args(config: Option<&Path>, args: &[String]) -> Vec<String> {
+    let mut result = vec!["-G".to_string()];
+    if let Some(config) = config {
+        result.push("-F".to_string());
+        result.push(config.display().to_string());
+    }
+    result.extend(args.iter().cloned());
+    result
+}
+
+/// 运行 ssh -G 取得主机的有效配置；旧版 ssh 不支持 -G 时返回说明
+pub fn query_effective_config(config: Option<&Path>, args: &[String], env: &[(String, String)]) -> Result<String, String> {
+    let output = Command::new("ssh")
+        .envs(env.iter().cloned())
+        .args(effective_config_args(config, args))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Unable to run ssh: {}", e))?;
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.contains("illegal option") || stderr.contains("unknown option") {
+        Err("This ssh client does not support -G (OpenSSH 6.8 or newer is required)".to_string())
+    } else {
+        Err(format!("ssh -G failed: {}", stderr))
+    }
+}
+
+pub fn inside_tmux() -> bool {
+    std::env::var_os("TMUX").is_some()
+}
+
+/// 在新的 tmux 窗口中为每个主机打开一个 pane 并平铺，pane 中运行主机的连接命令；返回每个失败主机的错误描述
+pub fn open_tmux_panes(window_name: &str, sessions: &[(SshHost, Vec<String>)]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut window_id: Option<String> = None;
+
+    for (host, command) in sessions {
+        // 环境变量通过 -e 传给 pane（tmux 3.0+）
+        let env_args: Vec<String> = host_env(host)
+            .into_iter()
+            .flat_map(|(key, value)| ["-e".to_string(), format!("{}={}", key, value)])
+            .collect();
+        let result = match &window_id {
+            None => Command::new("tmux")
+                .args(["new-window", "-P", "-F", "#{window_id}", "-n", window_name])
+                .args(&env_args)
+                .args(command)
+                .output(),
+            Some(id) => Command::new("tmux")
+                .args(["split-window", "-t", id])
+                .args(&env_args)
+                .args(command)
+                .output(),
+        };
+
+        match result {
+            Ok(output) if output.status.success() => {
+                if window_id.is_none() {
+                    window_id = Some(String::from_utf8_lossy(&output.stdout).trim().to_string());
+                }
+                // 每次分屏后重新平铺，避免 pane 太小导致后续分屏失败
+                if let Some(id) = &window_id {
+                    let _ = Command::new("tmux").args(["select-layout", "-t", id, "tiled"]).output();
+                }
+            }
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                errors.push(format!("{}: tmux failed: {}", host.name, stderr));
+            }
+            Err(e) => errors.push(format!("{}: unable to run tmux: {}", host.name, e)),
+        }
+    }
+
+    errors
+}
@@ -0,0 +1,156 @@
+use crate::config::{ Connector, SshHost };
+
+/// 命令模板中可用的占位符
+pub const CONNECTOR_PLACEHOLDERS: [&str; 4] = ["host", "port", "user", "name"];
+
+/// 主机是否用 ssh 连接（没有 # @connector 或写的是 ssh）
+pub fn uses_ssh(host: &SshHost) -> bool {
+    host.connector.as_deref().is_none_or(|name| name.trim().eq_ignore_ascii_case("ssh"))
+}
+
+/// 按 shell 的规则把命令拆成参数：支持单引号、双引号和反斜杠转义，不展开变量
+pub fn split_command_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        // 双引号内反斜杠只转义 " 和 \
+                        Some('\\') => match chars.next() {
+                            Some(next @ ('"' | '\\')) => word.push(next),
+                            Some(next) => word.extend(['\\', next]),
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(next) => word.get_or_insert_with(String::new).push(next),
+                None => return Err("trailing backslash".to_string()),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// 替换一个参数中的 {placeholder}，value 返回占位符的值或错误；只有 {字母} 算占位符，其他花括号原样保留
+fn substitute(word: &str, value: &impl Fn(&str) -> Result<String, String>) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = word;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').filter(|&end| end > 0 && after[..end].chars().all(|c| c.is_ascii_alphabetic())) {
+            Some(end) => {
+                result.push_str(&value(&after[..end])?);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 渲染连接器的命令：先拆分参数再替换占位符，值中的空格不会拆出新参数。user 为临时改用的用户
+pub fn render_connector(connector: &Connector, host: &SshHost, user: Option<&str>) -> Result<Vec<String>, String> {
+    let name = &connector.name;
+    let words = split_command_words(&connector.command).map_err(|e| format!("Connector '{}': {}", name, e))?;
+    if words.is_empty() {
+        return Err(format!("Connector '{}' has an empty command", name));
+    }
+
+    let value = |placeholder: &str| {
+        let value = match placeholder {
+            "host" => Some(host.resolved_hostname().unwrap_or_else(|| host.connect_target().to_string())),
+            "port" => host.port.clone(),
+            "user" => user.map(str::to_string).or_else(|| host.user.clone()),
+            "name" => Some(host.connect_target().to_string()),
+            _ => return Err(format!("Connector '{}' uses unknown placeholder {{{}}} (use {})", name, placeholder, placeholder_list())),
+        };
+        value.ok_or_else(|| format!("Connector '{}' needs {{{}}}, but {} has no {} set", name, placeholder, host.name, placeholder_field(placeholder)))
+    };
+    words.iter().map(|word| substitute(word, &value)).collect()
+}
+
+/// 缺少值时提示的字段名
+fn placeholder_field(placeholder: &str) -> &'static str {
+    match placeholder {
+        "port" => "Port",
+        "user" => "User",
+        _ => "value",
+    }
+}
+
+fn placeholder_list() -> String {
+    CONNECTOR_PLACEHOLDERS.iter().map(|name| format!("{{{}}}", name)).collect::<Vec<_>>().join(", ")
+}
+
+/// 内置的 mosh：mosh 通过 ssh 连接，会读取 ssh 配置，只需传入别名
+fn mosh_command(host: &SshHost, user: Option<&str>) -> Vec<String> {
+    let mut command = vec!["mosh".to_string()];
+    let options = host.temporary_ssh_options();
+    if !options.is_empty() {
+        command.push(format!("--ssh=ssh {}", options.join(" ")));
+    }
+    command.push(match user {
+        Some(user) => format!("{}@{}", user, host.connect_target()),
+        None => host.connect_target().to_string(),
+    });
+    command
+}
+
+/// 连接主机时执行的完整命令（程序名在第一个）。内置 ssh 和 mosh，其他名称在设置的 connectors 中查找，
+/// 设置中同名的 mosh 优先于内置的
+pub fn connect_command(host: &SshHost, user: Option<&str>, connectors: &[Connector]) -> Result<Vec<String>, String> {
+    if uses_ssh(host) {
+        return Ok(std::iter::once("ssh".to_string()).chain(host.ssh_args_as(user)).collect());
+    }
+    let name = host.connector.as_deref().unwrap_or_default().trim();
+    if let Some(connector) = connectors.iter().find(|connector| connector.name.trim().eq_ignore_ascii_case(name)) {
+        return render_connector(connector, host, user);
+    }
+    if name.eq_ignore_ascii_case("mosh") {
+        return Ok(mosh_command(host, user));
+    }
+    Err(format!("Unknown connector '{}': define it under [[connectors]] in settings.toml", name))
+}
+
+/// 显示用的命令行：含空白或引号的参数加单引号
+pub fn display_command(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|word| {
+            if !word.is_empty() && !word.chars().any(|c| c.is_whitespace() || "'\"\\$`".contains(c)) {
+                word.clone()
+            } else {
+                format!("'{}'", word.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
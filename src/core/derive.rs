@@ -0,0 +1,105 @@
+use crate::config::{ sanitize_host_name, Forward, SshHost };
+use crate::core::{ unique_host_name, Selector };
+
+/// 从已有主机派生新条目的模板
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeriveTemplate {
+    /// 复制主机并加上 LocalForward，不开远程会话
+    PortForward,
+    /// 复制主机并加上 DynamicForward（SOCKS 代理），不开远程会话
+    Socks,
+    /// 经由该主机跳转到另一台机器的新条目
+    JumpOnly,
+}
+
+impl DeriveTemplate {
+    pub const ALL: [DeriveTemplate; 3] = [DeriveTemplate::PortForward, DeriveTemplate::Socks, DeriveTemplate::JumpOnly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DeriveTemplate::PortForward => "Port forward…",
+            DeriveTemplate::Socks => "SOCKS proxy…",
+            DeriveTemplate::JumpOnly => "Jump only…",
+        }
+    }
+
+    /// 需要用户填写的参数
+    pub fn fields(self) -> &'static [&'static str] {
+        match self {
+            DeriveTemplate::PortForward => &["Local port", "Remote host:port (empty: localhost, same port)"],
+            DeriveTemplate::Socks => &["Local port"],
+            DeriveTemplate::JumpOnly => &["Target HostName", "Target user (optional)"],
+        }
+    }
+
+    pub fn defaults(self) -> Vec<String> {
+        match self {
+            DeriveTemplate::Socks => vec!["1080".to_string()],
+            template => vec![String::new(); template.fields().len()],
+        }
+    }
+
+    fn name_suffix(self) -> &'static str {
+        match self {
+            DeriveTemplate::PortForward | DeriveTemplate::Socks => "tunnel",
+            DeriveTemplate::JumpOnly => "jump",
+        }
+    }
+}
+
+/// 派生主机弹窗的状态：先选模板，再填参数
+#[derive(Debug, Clone)]
+pub struct DeriveState {
+    pub base: usize,
+    pub template: Option<DeriveTemplate>,
+    /// 模板列表，1-3 直接选用
+    pub picker: Selector,
+    pub inputs: Vec<String>,
+    pub field: usize,
+    pub error: Option<String>,
+}
+
+impl DeriveState {
+    pub fn new(base: usize) -> Self {
+        let picker = Selector::new(DeriveTemplate::ALL.len()).with_accelerators(&[('1', 0), ('2', 1), ('3', 2)]);
+        Self { base, template: None, picker, inputs: Vec::new(), field: 0, error: None }
+    }
+}
+
+/// 按模板生成派生主机，名称为 "<主机>-tunnel" 等并避开 taken 中已有的名称
+pub fn derive_host(base: &SshHost, template: DeriveTemplate, inputs: &[String], taken: &[&str]) -> Result<SshHost, String> {
+    let input = |index: usize| inputs.get(index).map(|value| value.trim()).unwrap_or_default();
+    let name = unique_host_name(&format!("{}-{}", sanitize_host_name(base.connect_target()), template.name_suffix()), taken);
+
+    let forward = match template {
+        DeriveTemplate::PortForward => Forward::local(input(0), input(1))?,
+        DeriveTemplate::Socks => Forward::dynamic(input(0))?,
+        DeriveTemplate::JumpOnly => {
+            if input(0).is_empty() {
+                return Err("Target HostName is required".to_string());
+            }
+            let mut host = SshHost::new(name);
+            host.hostname = Some(input(0).to_string());
+            host.user = (!input(1).is_empty()).then(|| input(1).to_string());
+            host.folder = base.folder.clone();
+            host.description = Some(format!("via {}", base.name));
+            host.other_options.insert("proxyjump".to_string(), base.connect_target().to_string());
+            return Ok(host);
+        }
+    };
+
+    // 与原主机的 HostName、ProxyJump 等完全相同，只多出转发
+    let mut host = base.clone();
+    host.name = name;
+    // 派生自系统主机时写入主配置
+    if host.system {
+        host.system = false;
+        host.source = None;
+    }
+    host.display_name = None;
+    host.aliases.clear();
+    host.description = Some(format!("{} via {}", forward.command_line(), base.name));
+    forward.add_to(&mut host.other_options);
+    host.other_options.insert("sessiontype".to_string(), "none".to_string());
+    Ok(host)
+}
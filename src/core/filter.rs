@@ -0,0 +1,75 @@
+use crate::config::{ inherited_value, InheritedOption, SshHost };
+
+/// 主列表的快速过滤条件，可叠加，全部满足的主机才显示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickFilter {
+    MissingHostName,
+    MissingIdentityFile,
+    NoFolder,
+    Hidden,
+    PendingChange,
+    ChangedOnDisk,
+}
+
+impl QuickFilter {
+    /// 过滤菜单中的顺序
+    pub const ALL: [QuickFilter; 6] = [
+        QuickFilter::MissingHostName,
+        QuickFilter::MissingIdentityFile,
+        QuickFilter::NoFolder,
+        QuickFilter::Hidden,
+        QuickFilter::PendingChange,
+        QuickFilter::ChangedOnDisk,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuickFilter::MissingHostName => "missing HostName",
+            QuickFilter::MissingIdentityFile => "missing IdentityFile",
+            QuickFilter::NoFolder => "no folder",
+            QuickFilter::Hidden => "hidden",
+            QuickFilter::PendingChange => "has pending change",
+            QuickFilter::ChangedOnDisk => "changed on disk",
+        }
+    }
+
+    /// 保存视图时使用的名称，不随界面文字变化
+    pub fn key(self) -> &'static str {
+        match self {
+            QuickFilter::MissingHostName => "missing-hostname",
+            QuickFilter::MissingIdentityFile => "missing-identityfile",
+            QuickFilter::NoFolder => "no-folder",
+            QuickFilter::Hidden => "hidden",
+            QuickFilter::PendingChange => "pending-change",
+            QuickFilter::ChangedOnDisk => "changed-on-disk",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        QuickFilter::ALL.into_iter().find(|filter| filter.key() == key)
+    }
+
+    /// 是否需要主机从通配块继承的值（由调用方计算后传给 matches）
+    pub fn uses_inherited(self) -> bool {
+        matches!(self, QuickFilter::MissingHostName | QuickFilter::MissingIdentityFile)
+    }
+
+    /// pending 表示该主机有未保存的变更，on_disk 表示磁盘上的版本与加载时不同；
+    /// 从通配块继承到的 HostName、IdentityFile 不算缺少
+    pub fn matches(self, host: &SshHost, inherited: &[InheritedOption], pending: bool, on_disk: bool) -> bool {
+        match self {
+            QuickFilter::MissingHostName => {
+                host.hostname.as_deref().is_none_or(|h| h.trim().is_empty()) && inherited_value(inherited, "hostname").is_none()
+            }
+            QuickFilter::MissingIdentityFile => {
+                host.identity_file.is_none()
+                    && !host.other_options.contains_key("identityfile")
+                    && inherited_value(inherited, "identityfile").is_none()
+            }
+            QuickFilter::NoFolder => host.folder.is_none(),
+            QuickFilter::Hidden => !host.visible,
+            QuickFilter::PendingChange => pending,
+            QuickFilter::ChangedOnDisk => on_disk,
+        }
+    }
+}
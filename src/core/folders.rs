@@ -0,0 +1,63 @@
+use crate::config::FolderCase;
+
+/// 文件夹字段补全列表中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub enum FolderSuggestion {
+    Existing(String),
+    /// 输入的名称规范化后还没有这个文件夹，确认后新建
+    Create(String),
+}
+
+impl FolderSuggestion {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Existing(name) | Self::Create(name) => name,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Existing(name) => name.clone(),
+            Self::Create(name) => format!("+ create new folder: '{}'", name),
+        }
+    }
+}
+
+/// 规范化文件夹名：去掉首尾空白，内部连续空白合并为一个空格，再按设置调整大小写
+pub fn normalize_folder_name(name: &str, case: FolderCase) -> String {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    match case {
+        FolderCase::Preserve => words.join(" "),
+        FolderCase::Lower => words.join(" ").to_lowercase(),
+        FolderCase::Title => words
+            .iter()
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                    None => String::new(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" "),
+    }
+}
+
+/// 输入不为空时列出包含输入（不区分大小写）的已有文件夹；输入规范化后不是已有文件夹时，
+/// 最后加一项新建。输入已与某个文件夹完全相同时返回空列表
+pub fn folder_suggestions(available: &[String], typed: &str, case: FolderCase) -> Vec<FolderSuggestion> {
+    let normalized = normalize_folder_name(typed, case);
+    if normalized.is_empty() || available.iter().any(|folder| folder == typed) {
+        return Vec::new();
+    }
+    let query = normalized.to_lowercase();
+    let mut suggestions: Vec<FolderSuggestion> = available
+        .iter()
+        .filter(|folder| folder.to_lowercase().contains(&query))
+        .map(|folder| FolderSuggestion::Existing(folder.clone()))
+        .collect();
+    if !available.iter().any(|folder| folder.eq_ignore_ascii_case(&normalized)) {
+        suggestions.push(FolderSuggestion::Create(normalized));
+    }
+    suggestions
+}
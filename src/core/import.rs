@@ -0,0 +1,386 @@
+use crate::config::{ sanitize_host_name, SshHost };
+use crate::core::Selector;
+use crate::utils::{ is_ipv6_literal, split_host_port };
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportStage {
+    Input,
+    Review,
+    /// "名称,描述" CSV 的匹配报告，确认后才暂存
+    Descriptions,
+}
+
+/// 与已有主机重名或可能重复时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictResolution {
+    Add,
+    Skip,
+    Overwrite,
+    Rename,
+    /// 只填写已有主机上空着的字段
+    Merge,
+}
+
+/// 名称不同但可能是同一台机器的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateReason {
+    /// 相同的 HostName（不区分大小写）和端口
+    SameTarget,
+    /// 名称或搜索别名与已有主机的名称或别名重合
+    SharedAlias,
+}
+
+impl DuplicateReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            DuplicateReason::SameTarget => "same HostName and port",
+            DuplicateReason::SharedAlias => "shares a name or alias",
+        }
+    }
+}
+
+/// 导入的主机可能与之重复的已有主机
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PossibleDuplicate {
+    /// 已有主机列表中的下标
+    pub existing: usize,
+    pub reason: DuplicateReason,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportCandidate {
+    pub host: SshHost,
+    pub conflict: bool,
+    /// 不重名时可能重复的已有主机
+    pub duplicate: Option<PossibleDuplicate>,
+    pub resolution: ConflictResolution,
+}
+
+/// 导入配置片段弹窗的状态，粘贴的原文始终保留以便修改后重试
+#[derive(Debug, Clone)]
+pub struct ImportState {
+    pub text: String,
+    pub stage: ImportStage,
+    pub warnings: Vec<String>,
+    pub candidates: Vec<ImportCandidate>,
+    pub picker: Selector,
+    /// 描述 CSV 中的 (名称, 描述)，切换覆盖方式时据此重新匹配
+    pub description_csv: Vec<(String, String)>,
+    pub descriptions: Vec<DescriptionRow>,
+    /// true 时覆盖已有描述，false 时只填写空白的描述
+    pub overwrite: bool,
+}
+
+impl ImportState {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            stage: ImportStage::Input,
+            warnings: Vec::new(),
+            candidates: Vec::new(),
+            picker: Selector::new(0),
+            description_csv: Vec::new(),
+            descriptions: Vec::new(),
+            overwrite: false,
+        }
+    }
+}
+
+impl Default for ImportState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 描述 CSV 中一行的处理结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DescriptionOutcome {
+    Update,
+    Unchanged,
+    /// 只填空白时，主机已有描述
+    NotBlank,
+    /// 同名的行只采用第一行
+    Duplicate,
+    /// 系统主机或临时主机，不能暂存修改
+    NotEditable,
+    Unmatched,
+}
+
+impl DescriptionOutcome {
+    pub fn label(self) -> &'static str {
+        match self {
+            DescriptionOutcome::Update => "update",
+            DescriptionOutcome::Unchanged => "same",
+            DescriptionOutcome::NotBlank => "has one",
+            DescriptionOutcome::Duplicate => "duplicate",
+            DescriptionOutcome::NotEditable => "read-only",
+            DescriptionOutcome::Unmatched => "no host",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptionRow {
+    pub name: String,
+    /// 新的描述，None 表示清除
+    pub description: Option<String>,
+    /// 主机当前的描述
+    pub current: Option<String>,
+    pub outcome: DescriptionOutcome,
+}
+
+/// 按名称（区分大小写）把描述 CSV 的行匹配到主机；overwrite 为 false 时只填写没有描述的主机
+pub fn plan_descriptions(rows: &[(String, String)], hosts: &[SshHost], overwrite: bool) -> Vec<DescriptionRow> {
+    let mut seen: Vec<&str> = Vec::new();
+    rows.iter()
+        .map(|(name, description)| {
+            let description = (!description.is_empty()).then(|| description.clone());
+            let host = hosts.iter().find(|host| host.name == *name && !host.is_verbatim());
+            let current = host.and_then(|host| host.description.clone());
+            let outcome = match host {
+                _ if seen.contains(&name.as_str()) => DescriptionOutcome::Duplicate,
+                None => DescriptionOutcome::Unmatched,
+                Some(host) if host.system || host.temporary => DescriptionOutcome::NotEditable,
+                Some(_) if current == description => DescriptionOutcome::Unchanged,
+                // 只填空白时不会改动或清除已有的描述
+                Some(_) if !overwrite && current.is_some() => DescriptionOutcome::NotBlank,
+                Some(_) => DescriptionOutcome::Update,
+            };
+            seen.push(name);
+            DescriptionRow { name: name.clone(), description, current, outcome }
+        })
+        .collect()
+}
+
+/// 为解析出的主机标记重名冲突和可能的重复；两者都默认跳过，其余默认添加
+pub fn build_import_candidates(parsed: Vec<SshHost>, existing: &[SshHost]) -> Vec<ImportCandidate> {
+    let parsed: Vec<SshHost> = parsed.into_iter().filter(|host| !host.name.trim().is_empty()).collect();
+    let duplicates = find_possible_duplicates(&parsed, existing);
+    parsed
+        .into_iter()
+        .zip(duplicates)
+        .map(|(host, duplicate)| {
+            let conflict = existing.iter().any(|h| h.name == host.name);
+            let duplicate = duplicate.filter(|_| !conflict);
+            let resolution = if conflict || duplicate.is_some() { ConflictResolution::Skip } else { ConflictResolution::Add };
+            ImportCandidate { host, conflict, duplicate, resolution }
+        })
+        .collect()
+}
+
+/// 连接目标 (HostName, 端口)：没有 HostName 时 ssh 直接连接名称；小写、去掉末尾的点和 IPv6 的方括号，
+/// 端口缺省为 22。名称是通配模式且没有 HostName 时返回 None
+fn target(host: &SshHost) -> Option<(String, String)> {
+    let hostname = match &host.hostname {
+        Some(hostname) => hostname.as_str(),
+        None if host.name.contains(['*', '?', '!']) || host.name.contains(char::is_whitespace) => return None,
+        None => host.name.as_str(),
+    };
+    let hostname = hostname.trim().trim_start_matches('[').trim_end_matches(']').trim_end_matches('.').to_lowercase();
+    let port = host.port.as_deref().map(str::trim).filter(|port| !port.is_empty()).unwrap_or("22");
+    // 数字端口去掉前导零；服务名按小写比较
+    let port = port.parse::<u16>().map_or_else(|_| port.to_lowercase(), |port| port.to_string());
+    (!hostname.is_empty()).then_some((hostname, port))
+}
+
+/// Host 行上的各个名称和搜索别名，小写
+fn alias_set(host: &SshHost) -> Vec<String> {
+    host.name
+        .split_whitespace()
+        .map(|name| name.trim_matches('"'))
+        .chain(host.aliases.iter().map(String::as_str))
+        .filter(|name| !name.is_empty() && !name.contains(['*', '?', '!']))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 对每台导入的主机找出可能重复的已有主机：先按连接目标，再按名称和别名；重名的不算（由冲突处理）。
+/// 原样保留的块不参与比较
+pub fn find_possible_duplicates(parsed: &[SshHost], existing: &[SshHost]) -> Vec<Option<PossibleDuplicate>> {
+    let existing: Vec<(usize, &SshHost)> = existing.iter().enumerate().filter(|(_, host)| !host.is_verbatim()).collect();
+    parsed
+        .iter()
+        .map(|host| {
+            if existing.iter().any(|(_, other)| other.name == host.name) {
+                return None;
+            }
+            let by_target = target(host).and_then(|wanted| {
+                existing.iter().find(|(_, other)| target(other).as_ref() == Some(&wanted))
+            });
+            if let Some((index, _)) = by_target {
+                return Some(PossibleDuplicate { existing: *index, reason: DuplicateReason::SameTarget });
+            }
+            let names = alias_set(host);
+            existing
+                .iter()
+                .find(|(_, other)| alias_set(other).iter().any(|name| names.contains(name)))
+                .map(|(index, _)| PossibleDuplicate { existing: *index, reason: DuplicateReason::SharedAlias })
+        })
+        .collect()
+}
+
+/// 合并导入的主机：只填写已有主机上空着的字段和没有的选项，已有的值保持不变
+pub fn fill_blanks(existing: &SshHost, imported: &SshHost) -> SshHost {
+    let mut merged = existing.clone();
+    let fill = |field: &mut Option<String>, value: &Option<String>| {
+        if field.is_none() {
+            *field = value.clone();
+        }
+    };
+    fill(&mut merged.hostname, &imported.hostname);
+    fill(&mut merged.user, &imported.user);
+    fill(&mut merged.port, &imported.port);
+    fill(&mut merged.identity_file, &imported.identity_file);
+    fill(&mut merged.folder, &imported.folder);
+    fill(&mut merged.display_name, &imported.display_name);
+    fill(&mut merged.description, &imported.description);
+    if merged.tags.is_empty() {
+        merged.tags = imported.tags.clone();
+    }
+    if merged.aliases.is_empty() {
+        merged.aliases = imported.aliases.clone();
+    }
+    // 可重复的选项（LocalForward 等）整组带入
+    for (key, value) in imported.other_options.iter() {
+        if !existing.other_options.contains_key(key) {
+            merged.other_options.push(key.clone(), value.clone());
+        }
+    }
+    merged
+}
+
+/// 生成不与 taken 中任何名称冲突的主机名：base、base-2、base-3 ...
+pub fn unique_host_name(base: &str, taken: &[&str]) -> String {
+    if !taken.contains(&base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(&candidate.as_str()))
+        .unwrap()
+}
+
+/// ssh 中需要参数的单字母选项
+const SSH_OPTIONS_WITH_ARG: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+
+/// 把 "ssh admin@203.0.113.7 -p 2200" 之类的命令解析为预填的主机；不像 ssh 命令时返回 None。
+/// 主机名取 HostName 的第一段（IP 保持原样），并避开 taken 中已有的名称
+pub fn parse_ssh_command(text: &str, taken: &[&str]) -> Option<SshHost> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let words: Vec<String> = line
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\'').to_string())
+        .collect();
+
+    // 必须以 ssh 开头，或者整行只有一个 user@host 形式的目标
+    let args = match words.first().map(String::as_str) {
+        Some("ssh") => &words[1..],
+        Some(word) if words.len() == 1 && word.contains('@') => &words[..],
+        _ => return None,
+    };
+
+    let mut user = None;
+    let mut port = None;
+    let mut identity_file = None;
+    let mut options: Vec<(String, String)> = Vec::new();
+    let mut destination = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        i += 1;
+
+        let Some(flags) = arg.strip_prefix('-').filter(|f| !f.is_empty()) else {
+            // 和 ssh 一样，目标之后仍可出现选项；再遇到非选项参数就是远程命令，忽略
+            if destination.is_some() {
+                break;
+            }
+            destination = Some(arg.clone());
+            continue;
+        };
+
+        // 按字符处理组合参数，例如 -4A 或 -p2200
+        for (pos, flag) in flags.char_indices() {
+            if !SSH_OPTIONS_WITH_ARG.contains(flag) {
+                continue;
+            }
+            let attached = &flags[pos + flag.len_utf8()..];
+            let value = if !attached.is_empty() {
+                attached.to_string()
+            } else if i < args.len() {
+                i += 1;
+                args[i - 1].clone()
+            } else {
+                return None;
+            };
+            match flag {
+                'p' => port = Some(value),
+                'l' => user = Some(value),
+                // 与 ssh 一样可以给出多个 -i，第一个填入表单，其余作为 IdentityFile 保留
+                'i' if identity_file.is_none() => identity_file = Some(value),
+                'i' => options.push(("identityfile".to_string(), value)),
+                'J' => options.push(("proxyjump".to_string(), value)),
+                'L' | 'R' => {
+                    if let Some(forward) = forward_option(&value) {
+                        let key = if flag == 'L' { "localforward" } else { "remoteforward" };
+                        options.push((key.to_string(), forward));
+                    }
+                }
+                'D' => options.push(("dynamicforward".to_string(), value)),
+                'o' => {
+                    let (key, value) = value.split_once('=').unwrap_or((&value, ""));
+                    if !value.is_empty() {
+                        options.push((key.trim().to_lowercase(), value.trim().to_string()));
+                    }
+                }
+                _ => {}
+            }
+            break;
+        }
+    }
+
+    let destination = destination?;
+    let destination = destination.strip_prefix("ssh://").unwrap_or(&destination);
+    let (dest_user, host_port) = match destination.rsplit_once('@') {
+        Some((user, rest)) => (Some(user.to_string()), rest),
+        None => (None, destination),
+    };
+    // ssh://host:port 或 [v6]:port 形式的端口；不带方括号的 IPv6 地址不拆分
+    let (hostname, dest_port) = match split_host_port(host_port) {
+        (host, Some(port)) if port.parse::<u16>().is_ok() => (host, Some(port.to_string())),
+        (host, Some(_)) if is_ipv6_literal(host) => (host, None),
+        (_, Some(_)) => (host_port, None),
+        (host, None) => (host, None),
+    };
+    if hostname.is_empty() || hostname.contains('/') {
+        return None;
+    }
+
+    let is_ip = hostname.parse::<std::net::IpAddr>().is_ok();
+    let base = if is_ip { hostname } else { hostname.split('.').next().unwrap_or(hostname) };
+    let mut host = SshHost::new(unique_host_name(&sanitize_host_name(base), taken));
+    host.hostname = Some(hostname.to_string());
+    host.user = user.or(dest_user);
+    host.port = port.or(dest_port);
+    host.identity_file = identity_file;
+    // 逐项追加：重复的 -o、-L、-i 都要保留，不能按键名去重
+    for (key, value) in options {
+        host.other_options.push(key, value);
+    }
+    Some(host)
+}
+
+/// 把 -L/-R 的 [bind:]port:host:hostport 转为配置文件中的 "[bind:]port host:hostport"；
+/// 方括号中的 IPv6 目标不拆开，其他形式（如 Unix 套接字）返回 None
+fn forward_option(spec: &str) -> Option<String> {
+    let (rest, target_port) = spec.rsplit_once(':')?;
+    let split = match rest.strip_suffix(']') {
+        Some(_) => rest.rfind('[')?.checked_sub(1)?,
+        None => rest.rfind(':')?,
+    };
+    let (listen, target) = (&rest[..split], rest[split..].strip_prefix(':')?);
+    if listen.is_empty() || target.is_empty() {
+        return None;
+    }
+    Some(format!("{} {}:{}", listen, target, target_port))
+}
@@ -0,0 +1,323 @@
+use std::path::PathBuf;
+
+use crate::config::{ OptionMap, SshHost };
+use crate::core::ChangeType;
+
+/// 冲突时选用哪一方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeChoice {
+    Mine,
+    Theirs,
+}
+
+/// 无法自动合并的主机。None 表示该方删除了主机（或从未有过）
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub name: String,
+    pub mine: Option<SshHost>,
+    pub theirs: Option<SshHost>,
+}
+
+/// 合并结果：hosts 为磁盘内容加上能干净应用的变更，changes 为基于磁盘内容重新生成的待保存变更
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    pub hosts: Vec<SshHost>,
+    pub changes: Vec<ChangeType>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// 冲突列表界面的状态；确认前不改动 App 中的主机和变更
+#[derive(Debug, Clone)]
+pub struct MergeState {
+    pub result: MergeResult,
+    pub disk_hosts: Vec<SshHost>,
+    pub included_files: Vec<PathBuf>,
+    pub choices: Vec<Option<MergeChoice>>,
+    pub selected: usize,
+}
+
+impl MergeState {
+    pub fn new(result: MergeResult, disk_hosts: Vec<SshHost>, included_files: Vec<PathBuf>) -> Self {
+        let choices = vec![None; result.conflicts.len()];
+        Self { result, disk_hosts, included_files, choices, selected: 0 }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.choices.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// 为选中的冲突做出选择并移到下一个
+    pub fn choose(&mut self, choice: MergeChoice) {
+        if let Some(slot) = self.choices.get_mut(self.selected) {
+            *slot = Some(choice);
+            self.select_next();
+        }
+    }
+
+    pub fn all_chosen(&self) -> bool {
+        self.choices.iter().all(Option::is_some)
+    }
+
+    /// 按选择解决所有冲突，返回最终的主机和待保存变更
+    pub fn finish(self) -> (Vec<SshHost>, Vec<ChangeType>) {
+        let MergeResult { mut hosts, mut changes, conflicts } = self.result;
+        for (conflict, choice) in conflicts.iter().zip(&self.choices) {
+            resolve_conflict(&mut hosts, &mut changes, conflict, choice.unwrap_or(MergeChoice::Theirs));
+        }
+        (hosts, changes)
+    }
+}
+
+/// 字段名、两台主机该字段是否相同、把 from 的该字段复制到 to
+type Field = (&'static str, fn(&SshHost, &SshHost) -> bool, fn(&mut SshHost, &SshHost));
+
+macro_rules! fields {
+    ($($name:literal => $field:ident),* $(,)?) => {
+        &[$((
+            $name,
+            |a: &SshHost, b: &SshHost| a.$field == b.$field,
+            |to: &mut SshHost, from: &SshHost| to.$field = from.$field.clone(),
+        )),*]
+    };
+}
+
+/// 参与逐字段合并的字段；其他选项另按键合并
+const FIELDS: &[Field] = fields![
+    "name" => name,
+    "hostname" => hostname,
+    "user" => user,
+    "port" => port,
+    "identity_file" => identity_file,
+    "folder" => folder,
+    "display_name" => display_name,
+    "description" => description,
+    "visible" => visible,
+    "tags" => tags,
+    "aliases" => aliases,
+    "style" => style,
+    "color" => color,
+    "env" => env,
+    "invalid_env" => invalid_env,
+    "source" => source,
+];
+
+/// 三方合并单台主机：base 为加载时的版本，mine 为本地修改后，theirs 为磁盘上的新版本。
+/// 两边修改了同一字段（或同一选项键）且结果不同时返回 None
+pub fn merge_host(base: &SshHost, mine: &SshHost, theirs: &SshHost) -> Option<SshHost> {
+    let mut merged = theirs.clone();
+    for (_, same, copy) in FIELDS {
+        let mine_changed = !same(base, mine);
+        if !mine_changed {
+            continue;
+        }
+        if !same(base, theirs) && !same(mine, theirs) {
+            return None;
+        }
+        copy(&mut merged, mine);
+    }
+    merged.other_options = merge_options(&base.other_options, &mine.other_options, &theirs.other_options)?;
+    Some(merged)
+}
+
+/// 按键合并其他选项：以 theirs 的顺序为准，本地修改过的键原位替换为本地的值
+fn merge_options(base: &OptionMap, mine: &OptionMap, theirs: &OptionMap) -> Option<OptionMap> {
+    let values = |map: &OptionMap, key: &str| map.get_all(key).cloned().collect::<Vec<String>>();
+    let mut changed: Vec<&String> = Vec::new();
+    for key in base.keys().chain(mine.keys()) {
+        if changed.contains(&key) || values(base, key) == values(mine, key) {
+            continue;
+        }
+        let theirs_values = values(theirs, key);
+        if theirs_values != values(base, key) && theirs_values != values(mine, key) {
+            return None;
+        }
+        changed.push(key);
+    }
+
+    let mut merged = OptionMap::new();
+    let mut written: Vec<&String> = Vec::new();
+    for (key, value) in theirs {
+        if !changed.contains(&key) {
+            merged.push(key.clone(), value.clone());
+        } else if !written.contains(&key) {
+            for value in mine.get_all(key) {
+                merged.push(key.clone(), value.clone());
+            }
+            written.push(key);
+        }
+    }
+    for key in changed.into_iter().filter(|key| !written.contains(key)) {
+        for value in mine.get_all(key) {
+            merged.push(key.clone(), value.clone());
+        }
+    }
+    Some(merged)
+}
+
+fn position(hosts: &[SshHost], name: &str) -> Option<usize> {
+    hosts.iter().position(|host| !host.is_verbatim() && host.name == name)
+}
+
+/// 把待保存变更按主机名重新应用到磁盘上的新内容（变更中记录的旧版本即加载时的版本）。
+/// 新增仍新增、删除仍删除；修改在外部没改动相同字段时套用到新解析的主机上，否则列为冲突
+pub fn merge_pending(disk: &[SshHost], changes: &[ChangeType]) -> MergeResult {
+    let mut hosts = disk.to_vec();
+    let mut merged: Vec<ChangeType> = Vec::new();
+    let mut conflicts: Vec<MergeConflict> = Vec::new();
+
+    for change in changes {
+        let name = match change {
+            ChangeType::Added(host) | ChangeType::Deleted(host) => &host.name,
+            ChangeType::Modified { old, .. } => &old.name,
+        };
+        // 已冲突的主机：后续变更只更新本地版本
+        if let Some(conflict) = conflicts.iter_mut().find(|conflict| conflict.name == *name) {
+            match change {
+                ChangeType::Added(host) => conflict.mine = Some(host.clone()),
+                ChangeType::Modified { new, .. } => {
+                    conflict.name = new.name.clone();
+                    conflict.mine = Some(new.clone());
+                }
+                ChangeType::Deleted(_) => conflict.mine = None,
+            }
+            continue;
+        }
+
+        let current = position(&hosts, name);
+        match change {
+            ChangeType::Added(host) => match current {
+                None => {
+                    hosts.push(host.clone());
+                    merged.push(change.clone());
+                }
+                // 外部也新增了同名主机，内容相同时无需再改
+                Some(index) if hosts[index] == *host => {}
+                Some(index) => conflicts.push(MergeConflict { name: name.clone(), mine: Some(host.clone()), theirs: Some(hosts[index].clone()) }),
+            },
+            ChangeType::Modified { old, new } => {
+                let Some(index) = current else {
+                    conflicts.push(MergeConflict { name: new.name.clone(), mine: Some(new.clone()), theirs: None });
+                    continue;
+                };
+                let theirs = hosts[index].clone();
+                if theirs == *new {
+                    continue;
+                }
+                // 改名后的名称被外部新增的主机占用时同样冲突
+                let taken = new.name != old.name && position(&hosts, &new.name).is_some();
+                match merge_host(old, new, &theirs).filter(|_| !taken) {
+                    Some(result) => {
+                        hosts[index] = result.clone();
+                        merged.push(ChangeType::Modified { old: theirs, new: result });
+                    }
+                    None => conflicts.push(MergeConflict { name: new.name.clone(), mine: Some(new.clone()), theirs: Some(theirs) }),
+                }
+            }
+            ChangeType::Deleted(host) => match current {
+                // 外部已删除
+                None => {}
+                Some(index) if hosts[index] == *host => {
+                    hosts.remove(index);
+                    merged.push(change.clone());
+                }
+                Some(index) => conflicts.push(MergeConflict { name: name.clone(), mine: None, theirs: Some(hosts[index].clone()) }),
+            },
+        }
+    }
+
+    MergeResult { hosts, changes: merged, conflicts }
+}
+
+/// 按选择解决一个冲突：选 theirs 时保持磁盘内容，选 mine 时把本地版本作为新的待保存变更
+pub fn resolve_conflict(hosts: &mut Vec<SshHost>, changes: &mut Vec<ChangeType>, conflict: &MergeConflict, choice: MergeChoice) {
+    if choice == MergeChoice::Theirs {
+        return;
+    }
+    let index = conflict.theirs.as_ref().and_then(|theirs| position(hosts, &theirs.name));
+    match (&conflict.mine, index) {
+        (Some(mine), Some(index)) => {
+            let theirs = std::mem::replace(&mut hosts[index], mine.clone());
+            changes.push(ChangeType::Modified { old: theirs, new: mine.clone() });
+        }
+        (Some(mine), None) => {
+            hosts.push(mine.clone());
+            changes.push(ChangeType::Added(mine.clone()));
+        }
+        (None, Some(index)) => {
+            let theirs = hosts.remove(index);
+            changes.push(ChangeType::Deleted(theirs));
+        }
+        (None, None) => {}
+    }
+}
+
+/// 变更的简短描述，用于错误信息
+fn change_label(change: &ChangeType) -> String {
+    match change {
+        ChangeType::Added(host) => format!("+ {}", host.name),
+        ChangeType::Deleted(host) => format!("- {}", host.name),
+        ChangeType::Modified { old, new } if old.name != new.name => format!("~ {} → {}", old.name, new.name),
+        ChangeType::Modified { new, .. } => format!("~ {}", new.name),
+    }
+}
+
+/// 按显示顺序把待保存变更逐条应用到加载时的主机上，得到保存时写入的内容。
+/// 修改和删除作用于与变更中旧版本完全相同的主机，找不到时（依赖的变更排在后面）返回错误；
+/// 两条变更最终得到同名主机时也返回错误
+pub fn replay_changes(base: &[SshHost], changes: &[ChangeType]) -> Result<Vec<SshHost>, String> {
+    let mut hosts: Vec<SshHost> = base.iter().filter(|host| !host.temporary && !host.system).cloned().collect();
+    // 每台主机最后由哪条变更写入，None 为加载时的内容
+    let mut origins: Vec<Option<usize>> = vec![None; hosts.len()];
+
+    for (index, change) in changes.iter().enumerate() {
+        let find = |hosts: &[SshHost], host: &SshHost| {
+            hosts.iter().position(|h| h == host).ok_or_else(|| format!(
+                "Change {} ({}) needs a version of {} that does not exist at that point; move it after the change it builds on",
+                index + 1,
+                change_label(change),
+                host.name
+            ))
+        };
+        match change {
+            ChangeType::Added(host) => {
+                hosts.push(host.clone());
+                origins.push(Some(index));
+            }
+            ChangeType::Modified { old, new } => {
+                let position = find(&hosts, old)?;
+                hosts[position] = new.clone();
+                origins[position] = Some(index);
+            }
+            ChangeType::Deleted(host) => {
+                let position = find(&hosts, host)?;
+                hosts.remove(position);
+                origins.remove(position);
+            }
+        }
+    }
+
+    for (i, host) in hosts.iter().enumerate() {
+        let Some(first) = origins[i].filter(|_| !host.is_verbatim()) else {
+            continue;
+        };
+        let clash = (i + 1..hosts.len()).find_map(|j| origins[j].filter(|_| !hosts[j].is_verbatim() && hosts[j].name == host.name));
+        if let Some(second) = clash {
+            let (first, second) = (first.min(second), first.max(second));
+            return Err(format!(
+                "Changes {} ({}) and {} ({}) both end with a host named {}; rename one of them before saving",
+                first + 1,
+                change_label(&changes[first]),
+                second + 1,
+                change_label(&changes[second]),
+                host.name
+            ));
+        }
+    }
+    Ok(hosts)
+}
@@ -1,5 +1,51 @@
+pub mod actions;
 pub mod app;
+pub mod connection;
+pub mod connector;
+pub mod derive;
+pub mod filter;
+pub mod folders;
+pub mod import;
+pub mod merge;
+pub mod multiplex;
+pub mod onboarding;
+pub mod palette;
+pub mod probe;
+pub mod reference;
+pub mod restore;
+pub mod selector;
+pub mod ssh_binary;
+pub mod startup;
+pub mod summary;
+pub mod tags;
+pub mod tasks;
 pub mod terminal;
+pub mod transfer;
+pub mod user_switch;
+pub mod views;
 
+pub use actions::*;
 pub use app::*;
+pub use connection::*;
+pub use connector::*;
+pub use derive::*;
+pub use filter::*;
+pub use folders::*;
+pub use import::*;
+pub use merge::*;
+pub use multiplex::*;
+pub use onboarding::*;
+pub use palette::*;
+pub use probe::*;
+pub use selector::*;
+pub use ssh_binary::*;
+pub use startup::*;
+pub use reference::*;
+pub use restore::*;
+pub use summary::*;
+pub use tags::*;
+pub use tasks::*;
 pub use terminal::*;
+pub use transfer::*;
+pub use user_switch::*;
+pub use views::*;
\ No newline at end of file
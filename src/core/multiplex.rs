@@ -0,0 +1,195 @@
+use std::io::Read;
+use std::process::{ Child, Command, Stdio };
+use std::sync::mpsc::{ self, Receiver };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use crate::config::SshHost;
+use crate::core::{ format_duration, host_env };
+
+/// 预热结束时的结果，失败和超时附带 ssh 的 stderr
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrewarmOutcome {
+    Ready,
+    Failed(Vec<String>),
+    TimedOut(Vec<String>),
+}
+
+/// 在后台建立主连接的参数：认证完成后 -f 转入后台，-N 不执行远程命令
+pub fn prewarm_args(host: &SshHost) -> Vec<String> {
+    let mut args = vec!["-fN".to_string(), "-o".to_string(), "ControlMaster=yes".to_string()];
+    args.extend(host.ssh_args());
+    args
+}
+
+/// 关闭主连接的参数
+pub fn teardown_args(host: &SshHost) -> Vec<String> {
+    let mut args = vec!["-O".to_string(), "exit".to_string()];
+    args.extend(host.ssh_args());
+    args
+}
+
+/// ssh -G 的有效配置中是否设置了 ControlPath；没有时主连接不会监听套接字，预热没有意义
+pub fn control_path_configured(effective: &[(String, String)]) -> bool {
+    effective.iter().any(|(key, value)| key == "controlpath" && !value.eq_ignore_ascii_case("none"))
+}
+
+/// 一个正在后台进行的预热
+pub struct PrewarmJob {
+    pub host: SshHost,
+    pub started: Instant,
+    timeout: Duration,
+    child: Child,
+    stderr: Receiver<Vec<String>>,
+}
+
+impl PrewarmJob {
+    /// 启动 ssh 预热主连接
+    pub fn start(host: SshHost, timeout: Duration) -> std::io::Result<Self> {
+        let mut command = Command::new("ssh");
+        command.envs(host_env(&host)).args(prewarm_args(&host));
+        Self::spawn(host, command, timeout)
+    }
+
+    /// 运行任意命令作为预热进程，stderr 在单独的线程中收集
+    pub fn spawn(host: SshHost, mut command: Command, timeout: Duration) -> std::io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let (sender, receiver) = mpsc::channel();
+        if let Some(mut stderr) = child.stderr.take() {
+            thread::spawn(move || {
+                let mut text = String::new();
+                let _ = stderr.read_to_string(&mut text);
+                let lines = text
+                    .lines()
+                    .map(|line| line.trim_end().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                let _ = sender.send(lines);
+            });
+        }
+        Ok(Self { host, started: Instant::now(), timeout, child, stderr: receiver })
+    }
+
+    /// 不阻塞地检查进程；前台进程退出（认证完成转入后台或失败）或超时后返回结果
+    pub fn poll(&mut self) -> Option<PrewarmOutcome> {
+        match self.child.try_wait() {
+            Ok(Some(status)) if status.success() => Some(PrewarmOutcome::Ready),
+            Ok(Some(status)) => {
+                let mut lines = self.stderr_lines();
+                if lines.is_empty() {
+                    lines.push(format!("ssh exited with {}", status));
+                }
+                Some(PrewarmOutcome::Failed(lines))
+            }
+            Ok(None) if self.started.elapsed() >= self.timeout => {
+                self.kill();
+                Some(PrewarmOutcome::TimedOut(self.stderr_lines()))
+            }
+            Ok(None) => None,
+            Err(e) => Some(PrewarmOutcome::Failed(vec![e.to_string()])),
+        }
+    }
+
+    pub fn kill(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    fn stderr_lines(&self) -> Vec<String> {
+        self.stderr.recv_timeout(Duration::from_millis(500)).unwrap_or_default()
+    }
+}
+
+impl Drop for PrewarmJob {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            self.kill();
+        }
+    }
+}
+
+/// 本次会话的预热任务和已建立的主连接。丢弃时结束未完成的预热，并关闭本次会话建立的主连接
+#[derive(Default)]
+pub struct Multiplexer {
+    pub jobs: Vec<PrewarmJob>,
+    pub live: Vec<SshHost>,
+}
+
+impl Multiplexer {
+    pub fn is_pending(&self, name: &str) -> bool {
+        self.jobs.iter().any(|job| job.host.name == name)
+    }
+
+    pub fn is_live(&self, name: &str) -> bool {
+        self.live.iter().any(|host| host.name == name)
+    }
+
+    /// 收取已结束的预热；成功的主机记为已有主连接
+    pub fn poll(&mut self) -> Vec<(String, PrewarmOutcome)> {
+        let mut finished = Vec::new();
+        let mut index = 0;
+        while index < self.jobs.len() {
+            match self.jobs[index].poll() {
+                Some(outcome) => {
+                    let job = self.jobs.remove(index);
+                    if outcome == PrewarmOutcome::Ready {
+                        self.live.push(job.host.clone());
+                    }
+                    finished.push((job.host.name.clone(), outcome));
+                }
+                None => index += 1,
+            }
+        }
+        finished
+    }
+
+    /// 顶栏显示的进度，例如 "⚡ Pre-warming bastion 12s"
+    pub fn progress(&self) -> Option<String> {
+        if self.jobs.is_empty() {
+            return None;
+        }
+        let jobs: Vec<String> = self.jobs
+            .iter()
+            .map(|job| format!("{} {}", job.host.name, format_duration(job.started.elapsed())))
+            .collect();
+        Some(format!("⚡ Pre-warming {}", jobs.join(", ")))
+    }
+
+    /// 取消预热中的任务；没有时关闭已建立的主连接。返回 ssh -O exit 的错误输出
+    pub fn teardown(&mut self, name: &str) -> Option<Result<(), String>> {
+        if let Some(index) = self.jobs.iter().position(|job| job.host.name == name) {
+            self.jobs.remove(index).kill();
+            return Some(Ok(()));
+        }
+        let index = self.live.iter().position(|host| host.name == name)?;
+        Some(close_master(&self.live.remove(index)))
+    }
+}
+
+impl Drop for Multiplexer {
+    fn drop(&mut self) {
+        self.jobs.clear();
+        for host in &self.live {
+            let _ = close_master(host);
+        }
+    }
+}
+
+/// 运行 ssh -O exit 关闭主机的主连接
+pub fn close_master(host: &SshHost) -> Result<(), String> {
+    let output = Command::new("ssh")
+        .envs(host_env(host))
+        .args(teardown_args(host))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Unable to run ssh: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
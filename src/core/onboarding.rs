@@ -0,0 +1,255 @@
+use std::path::Path;
+
+use crate::config::{ host_name_problem, sanitize_host_name, SshHost };
+use crate::core::Selector;
+use crate::utils::resolve_port;
+
+/// 新服务器向导的步骤，按顺序进行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnboardingStep {
+    Details,
+    Key,
+    CopyId,
+    Test,
+    /// 显示各步结果，确认后暂存新增变更
+    Summary,
+}
+
+impl OnboardingStep {
+    /// 会产生结果的步骤，Summary 除外
+    pub const ALL: [OnboardingStep; 4] = [OnboardingStep::Details, OnboardingStep::Key, OnboardingStep::CopyId, OnboardingStep::Test];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OnboardingStep::Details => "Host details",
+            OnboardingStep::Key => "Key",
+            OnboardingStep::CopyId => "Copy key (ssh-copy-id)",
+            OnboardingStep::Test => "Connection test",
+            OnboardingStep::Summary => "Summary",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            OnboardingStep::Details => 0,
+            OnboardingStep::Key => 1,
+            OnboardingStep::CopyId => 2,
+            OnboardingStep::Test => 3,
+            OnboardingStep::Summary => 4,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL.get(self.index() + 1).copied().unwrap_or(OnboardingStep::Summary)
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[self.index().saturating_sub(1)]
+    }
+}
+
+/// 一个步骤的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepOutcome {
+    Pending,
+    Done(String),
+    Skipped,
+    /// 失败后停在该步骤，可以重试或跳过
+    Failed(String),
+}
+
+/// 第一步的输入框
+pub const ONBOARDING_FIELDS: [&str; 4] = ["Name", "HostName", "User", "Port"];
+
+/// 新服务器向导：填写主机、选择或生成密钥、ssh-copy-id、BatchMode 连接测试，最后暂存新增的主机。
+/// 只记录状态和结果，外部命令由 App 在终端外执行后调用 finish_step
+#[derive(Debug, Clone)]
+pub struct OnboardingState {
+    pub step: OnboardingStep,
+    pub inputs: Vec<String>,
+    pub field: usize,
+    /// ~/.ssh 中已有的私钥；选择列表的最后一项是 "生成新密钥"
+    pub keys: Vec<String>,
+    pub key_picker: Selector,
+    /// 新主机使用的 IdentityFile
+    pub key: Option<String>,
+    /// 正在生成的密钥路径，生成成功后成为 key
+    pub generating: Option<String>,
+    pub outcomes: [StepOutcome; 4],
+    /// 第一步的输入错误
+    pub error: Option<String>,
+}
+
+impl OnboardingState {
+    pub fn new(keys: Vec<String>) -> Self {
+        let key_picker = Selector::new(keys.len() + 1);
+        Self {
+            step: OnboardingStep::Details,
+            inputs: vec![String::new(); ONBOARDING_FIELDS.len()],
+            field: 0,
+            keys,
+            key_picker,
+            key: None,
+            generating: None,
+            outcomes: [StepOutcome::Pending, StepOutcome::Pending, StepOutcome::Pending, StepOutcome::Pending],
+            error: None,
+        }
+    }
+
+    pub fn outcome(&self, step: OnboardingStep) -> &StepOutcome {
+        self.outcomes.get(step.index()).unwrap_or(&StepOutcome::Pending)
+    }
+
+    fn input(&self, index: usize) -> &str {
+        self.inputs.get(index).map(|value| value.trim()).unwrap_or_default()
+    }
+
+    /// 检查第一步的输入，通过后进入选择密钥；taken 为已有的主机名
+    pub fn submit_details(&mut self, taken: &[&str]) -> Result<(), String> {
+        let result = self.check_details(taken);
+        match &result {
+            Ok(()) => {
+                self.error = None;
+                self.outcomes[0] = StepOutcome::Done(self.target());
+                self.step = OnboardingStep::Key;
+            }
+            Err(error) => self.error = Some(error.clone()),
+        }
+        result
+    }
+
+    fn check_details(&self, taken: &[&str]) -> Result<(), String> {
+        let name = self.input(0);
+        if name.is_empty() {
+            return Err("Name is required".to_string());
+        }
+        if let Some(problem) = host_name_problem(name) {
+            return Err(format!("Name {}", problem));
+        }
+        if taken.contains(&name) {
+            return Err(format!("A host named {} already exists", name));
+        }
+        if !self.input(3).is_empty() && resolve_port(self.input(3)).is_none() {
+            return Err("Port must be a number from 1 to 65535 or a known service name".to_string());
+        }
+        Ok(())
+    }
+
+    /// 选择列表中的一项：已有私钥直接采用并进入下一步；返回 true 表示要生成新密钥（路径在 generating 中）
+    pub fn choose_key(&mut self, index: usize, ssh_dir: &Path) -> bool {
+        match self.keys.get(index) {
+            Some(key) => {
+                self.key = Some(key.clone());
+                self.outcomes[1] = StepOutcome::Done(format!("using {}", key));
+                self.step = self.step.next();
+                false
+            }
+            None => {
+                self.generating = Some(new_key_path(self.input(0), ssh_dir));
+                true
+            }
+        }
+    }
+
+    /// 外部命令结束后记录结果：成功进入下一步，失败停留在当前步骤
+    pub fn finish_step(&mut self, result: Result<String, String>) {
+        let Some(outcome) = self.outcomes.get_mut(self.step.index()) else {
+            return;
+        };
+        let generated = self.generating.take();
+        match result {
+            Ok(detail) => {
+                *outcome = StepOutcome::Done(detail);
+                if self.step == OnboardingStep::Key {
+                    self.key = generated;
+                }
+                self.step = self.step.next();
+            }
+            Err(error) => *outcome = StepOutcome::Failed(error),
+        }
+    }
+
+    /// 跳过当前步骤；第一步和 Summary 不能跳过。跳过密钥步骤时新主机不写 IdentityFile
+    pub fn skip(&mut self) -> bool {
+        if matches!(self.step, OnboardingStep::Details | OnboardingStep::Summary) {
+            return false;
+        }
+        if self.step == OnboardingStep::Key {
+            self.key = None;
+            self.generating = None;
+        }
+        self.outcomes[self.step.index()] = StepOutcome::Skipped;
+        self.step = self.step.next();
+        true
+    }
+
+    /// 回到上一步，已填写的内容和结果保留；在第一步时返回 false，表示关闭向导
+    pub fn back(&mut self) -> bool {
+        if self.step == OnboardingStep::Details {
+            return false;
+        }
+        self.step = self.step.previous();
+        true
+    }
+
+    /// ssh-copy-id 和连接测试使用的目标：[user@]hostname，没有 HostName 时用名称
+    pub fn target(&self) -> String {
+        let hostname = if self.input(1).is_empty() { self.input(0) } else { self.input(1) };
+        match self.input(2) {
+            "" => hostname.to_string(),
+            user => format!("{}@{}", user, hostname),
+        }
+    }
+
+    /// 向导最终暂存的主机
+    pub fn host(&self) -> SshHost {
+        let optional = |index: usize| (!self.input(index).is_empty()).then(|| self.input(index).to_string());
+        let mut host = SshHost::new(self.input(0).to_string());
+        host.hostname = optional(1);
+        host.user = optional(2);
+        host.port = optional(3);
+        host.identity_file = self.key.clone();
+        host
+    }
+
+    /// ssh-keygen 的参数，生成 ed25519 密钥；密码短语由 ssh-keygen 交互询问
+    pub fn keygen_args(&self, path: &str) -> Vec<String> {
+        let comment = format!("sshc:{}", self.input(0));
+        ["-t", "ed25519", "-f", path, "-C", &comment].iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// ssh-copy-id 的参数；没有选择密钥时由 ssh-copy-id 使用默认密钥
+    pub fn copy_id_args(&self, key_path: Option<&str>) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(key) = key_path {
+            args.extend(["-i".to_string(), key.to_string()]);
+        }
+        if let Some(port) = resolve_port(self.input(3)) {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        args.push(self.target());
+        args
+    }
+
+    /// BatchMode 连接测试的参数：以临时主机的方式传入所有选项，不依赖尚未写入的配置
+    pub fn test_args(&self) -> Vec<String> {
+        let mut host = self.host();
+        host.temporary = true;
+        let mut args: Vec<String> = ["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"].iter().map(|arg| arg.to_string()).collect();
+        args.extend(host.ssh_args());
+        args.push("exit".to_string());
+        args
+    }
+}
+
+/// 新密钥的路径 ~/.ssh/id_ed25519_<名称>，文件已存在时加上 -2、-3 等后缀
+pub fn new_key_path(name: &str, ssh_dir: &Path) -> String {
+    let base = format!("id_ed25519_{}", sanitize_host_name(name).replace('/', "-"));
+    let mut file = base.clone();
+    let mut n = 2;
+    while ssh_dir.join(&file).exists() || ssh_dir.join(format!("{}.pub", file)).exists() {
+        file = format!("{}-{}", base, n);
+        n += 1;
+    }
+    format!("~/.ssh/{}", file)
+}
@@ -0,0 +1,98 @@
+use crate::core::{ ActionScope, AppCommand, HostAction, Selector };
+
+/// 命令面板中的一项所执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteAction {
+    Host(HostAction),
+    Command(AppCommand),
+}
+
+/// 命令面板中的一项；enabled 为 false 时灰显且不能执行
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    pub action: PaletteAction,
+    pub label: &'static str,
+    pub key: String,
+    pub enabled: bool,
+}
+
+/// 命令面板：列出当前界面的所有操作，输入即模糊过滤
+#[derive(Debug, Clone)]
+pub struct CommandPalette {
+    pub scope: ActionScope,
+    pub query: String,
+    pub entries: Vec<PaletteEntry>,
+    /// 与输入匹配的项在 entries 中的位置：可用的在前，其次按匹配程度
+    pub matches: Vec<usize>,
+    pub choice: Selector,
+}
+
+impl CommandPalette {
+    pub fn new(scope: ActionScope, entries: Vec<PaletteEntry>) -> Self {
+        let mut palette = Self { scope, query: String::new(), entries, matches: Vec::new(), choice: Selector::new(0) };
+        palette.refilter();
+        palette
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refilter();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.refilter();
+    }
+
+    /// 选中的项（没有匹配时为 None）
+    pub fn selected(&self) -> Option<&PaletteEntry> {
+        self.matches.get(self.choice.selected).map(|&index| &self.entries[index])
+    }
+
+    /// 输入变化后重新过滤，选中第一项
+    fn refilter(&mut self) {
+        let mut scored: Vec<(usize, i32)> = self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| fuzzy_score(&self.query, entry.label).map(|score| (index, score)))
+            .collect();
+        // 稳定排序，得分相同时保持登记顺序
+        scored.sort_by_key(|&(index, score)| (!self.entries[index].enabled, -score));
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.choice = Selector::new(self.matches.len());
+    }
+}
+
+/// 模糊匹配：query 中的字符（忽略空白和大小写）按顺序出现在 text 中即匹配。
+/// 连续匹配和单词开头的匹配得分更高，越靠前开始匹配得分越高；不匹配返回 None
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let Some(&first) = query.first() else {
+        return Some(0);
+    };
+    // 从首字符的每个出现位置开始贪心匹配，取最高分
+    (0..text.len())
+        .filter(|&start| text[start] == first)
+        .filter_map(|start| score_from(&query, &text, start))
+        .max()
+}
+
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<i32> {
+    let mut score = -(start.min(12) as i32) / 3;
+    let mut previous: Option<usize> = None;
+    let mut position = start;
+    for &wanted in query {
+        let found = position + text[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 6;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use std::net::{ IpAddr, TcpStream, ToSocketAddrs };
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::sync::mpsc::{ self, Receiver };
+use std::sync::{ Arc, Mutex };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+use crate::config::{ host_name_problem, SshHost };
+use crate::utils::{ resolve_port, unbracket };
+
+/// 同时进行的 TCP 探测数上限
+pub const MAX_CONCURRENT_PROBES: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeStatus {
+    Pending,
+    Reachable(Duration),
+    Unreachable(String),
+    /// 未探测，附原因（例如经由跳板机连接）
+    Skipped(String),
+}
+
+/// 一个探测目标，key 由调用方定义（通常是主机下标）
+#[derive(Debug, Clone)]
+pub struct ProbeTarget {
+    pub key: usize,
+    pub address: String,
+    pub port: u16,
+}
+
+/// 主机对应的探测目标；经由跳板或端口无法解析时返回跳过原因
+pub fn probe_target(key: usize, host: &SshHost) -> Result<ProbeTarget, String> {
+    if host.other_options.contains_key("proxyjump") || host.other_options.contains_key("proxycommand") {
+        return Err("via proxy".to_string());
+    }
+    let port = match &host.port {
+        // 服务名按系统 services 数据库解析，解析不了就跳过探测
+        Some(port) => resolve_port(port).ok_or_else(|| format!("unknown port '{}'", port))?,
+        None => 22,
+    };
+    Ok(ProbeTarget {
+        key,
+        // HostName 可能写成 [v6]，解析地址时去掉方括号
+        address: unbracket(&host.resolved_hostname().unwrap_or_else(|| host.connect_target().to_string())).to_string(),
+        port,
+    })
+}
+
+/// 尝试 TCP 连接并计时，依次尝试解析到的每个地址
+pub fn probe_tcp(address: &str, port: u16, timeout: Duration) -> ProbeStatus {
+    let addrs = match (address, port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect::<Vec<_>>(),
+        Err(e) => return ProbeStatus::Unreachable(format!("resolve: {}", e)),
+    };
+
+    let mut last_error = "no addresses".to_string();
+    for addr in addrs {
+        let started = Instant::now();
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => return ProbeStatus::Reachable(started.elapsed()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    ProbeStatus::Unreachable(last_error)
+}
+
+/// 一轮后台任务：固定数量的工作线程从队列取目标，结果通过通道返回。
+/// 取消或丢弃后，尚未开始的目标不再处理。默认用于 TCP 探测，DNS 查询也复用它
+pub struct ProbeRun<T = ProbeStatus> {
+    receiver: Receiver<(usize, T)>,
+    cancel: Arc<AtomicBool>,
+    remaining: usize,
+}
+
+impl ProbeRun {
+    pub fn start(targets: Vec<ProbeTarget>, timeout: Duration) -> Self {
+        Self::spawn(targets, move |target| probe_tcp(&target.address, target.port, timeout))
+    }
+}
+
+impl<T: Send + 'static> ProbeRun<T> {
+    /// 对每个目标在工作线程中执行 work
+    pub fn spawn<F>(targets: Vec<ProbeTarget>, work: F) -> Self where F: Fn(&ProbeTarget) -> T + Send + Sync + 'static {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let remaining = targets.len();
+        let queue = Arc::new(Mutex::new(VecDeque::from(targets)));
+        let work = Arc::new(work);
+
+        for _ in 0..MAX_CONCURRENT_PROBES.min(remaining) {
+            let sender = sender.clone();
+            let cancel = Arc::clone(&cancel);
+            let queue = Arc::clone(&queue);
+            let work = Arc::clone(&work);
+            thread::spawn(move || {
+                while !cancel.load(Ordering::Relaxed) {
+                    let Some(target) = queue.lock().ok().and_then(|mut q| q.pop_front()) else {
+                        break;
+                    };
+                    if sender.send((target.key, work(&target))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        Self { receiver, cancel, remaining }
+    }
+}
+
+impl<T> ProbeRun<T> {
+    /// 取出目前已完成的结果，不阻塞
+    pub fn poll(&mut self) -> Vec<(usize, T)> {
+        let results: Vec<_> = self.receiver.try_iter().collect();
+        self.remaining = self.remaining.saturating_sub(results.len());
+        results
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Drop for ProbeRun<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DnsStatus {
+    Resolving,
+    Resolved(Vec<IpAddr>),
+    Unresolved,
+    /// 查询被 Esc 取消；离开该主机后再回来会重新查询
+    Cancelled,
+}
+
+/// 没有 HostName 且不经由代理的主机，ssh 会直接拿别名去做 DNS 解析；返回这个名字
+pub fn dns_name(host: &SshHost) -> Option<&str> {
+    if host.hostname.is_some()
+        || host.other_options.contains_key("proxyjump")
+        || host.other_options.contains_key("proxycommand")
+        || host_name_problem(&host.name).is_some()
+    {
+        return None;
+    }
+    Some(host.connect_target())
+}
+
+/// 解析名字的地址，去重并保持系统返回的顺序
+pub fn resolve_name(name: &str) -> DnsStatus {
+    let Ok(addrs) = (name, 0).to_socket_addrs() else {
+        return DnsStatus::Unresolved;
+    };
+    let mut ips: Vec<IpAddr> = Vec::new();
+    for addr in addrs {
+        if !ips.contains(&addr.ip()) {
+            ips.push(addr.ip());
+        }
+    }
+    if ips.is_empty() { DnsStatus::Unresolved } else { DnsStatus::Resolved(ips) }
+}
@@ -0,0 +1,118 @@
+use crate::config::{ option_display_name, Settings, METADATA_KEYS };
+use crate::core::{ AppMode, ReviewSearch, KEEPALIVE_OPTIONS, TRUST_OPTIONS };
+
+/// 不在表单中、但 sshc 会专门处理的 ssh 选项
+const HANDLED_OPTIONS: [(&str, &str); 4] = [
+    ("proxyjump", "Jump hosts: deleting one offers to fix the hosts that use it; derived hosts can jump through it"),
+    ("proxycommand", "Expanded in the connect confirmation; jump hosts named in it count as dependents"),
+    ("controlpath", "Needed by master pre-warming (w); hosts with a live master get a mark"),
+    ("localforward", "Derived tunnel hosts add one; repeated lines are kept in order (also RemoteForward, DynamicForward)"),
+];
+
+/// 参考页的标题行，渲染时加粗
+pub fn is_reference_heading(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with(' ')
+}
+
+/// 参考页的全部内容：元数据键、有专门处理的 ssh 选项和设置项及默认值，都从代码中的表生成
+pub fn reference_lines() -> Vec<String> {
+    let mut lines = vec![
+        "METADATA COMMENTS".to_string(),
+        "  Written on the lines directly above a Host line; unknown keys are ignored.".to_string(),
+        String::new(),
+    ];
+    for metadata in METADATA_KEYS {
+        lines.push(format!("  {}", metadata.syntax));
+        lines.push(format!("      {}", metadata.summary));
+    }
+
+    lines.extend([String::new(), "SSH OPTIONS WITH FIRST-CLASS SUPPORT".to_string(), String::new()]);
+    let form = [
+        ("hostname", "Host form field; shown in the list and the details pane"),
+        ("user", "Host form field"),
+        ("port", "Host form field; service names are resolved and reported by lint"),
+        ("identityfile", "Host form field (F2 picks a key); further IdentityFile lines are kept"),
+    ];
+    let options = form
+        .into_iter()
+        .chain(KEEPALIVE_OPTIONS.iter().map(|key| (*key, "Keep-alive field in the host form, with presets")))
+        .chain(TRUST_OPTIONS.iter().map(|key| (*key, "Host key field in the host form")))
+        .chain(HANDLED_OPTIONS);
+    for (key, summary) in options {
+        lines.push(format!("  {}", option_display_name(key)));
+        lines.push(format!("      {}", summary));
+    }
+
+    lines.extend([
+        String::new(),
+        "NEW HOST DEFAULTS".to_string(),
+        "  User, IdentityFile and ProxyJump of a new host take the first value set in:".to_string(),
+        "    1. explicit input: an ssh command from --add or V, or what you type into the form".to_string(),
+        "    2. the folder_defaults entry for the host's folder (the selected folder when the form opens)".to_string(),
+        "    3. host_defaults".to_string(),
+        "  Defaults only prefill the add-host form; editing an existing host never re-applies them.".to_string(),
+    ]);
+
+    lines.extend([String::new(), "SETTINGS (settings.toml, with defaults)".to_string(), String::new()]);
+    // 由默认设置序列化得到，新增的设置项自动出现；未设置的可选项为 null
+    if let Ok(serde_json::Value::Object(settings)) = serde_json::to_value(Settings::default()) {
+        for (key, value) in settings {
+            let value = match value {
+                serde_json::Value::Null => "(not set)".to_string(),
+                value => value.to_string(),
+            };
+            lines.push(format!("  {} = {}", key, value));
+        }
+    }
+    lines
+}
+
+/// 参考页弹窗：可滚动，/ 搜索，n/N 在匹配间跳转
+#[derive(Debug, Clone)]
+pub struct ReferenceView {
+    pub lines: Vec<String>,
+    pub scroll: usize,
+    pub search: ReviewSearch,
+    pub return_mode: AppMode,
+}
+
+impl ReferenceView {
+    pub fn new(return_mode: AppMode) -> Self {
+        Self { lines: reference_lines(), scroll: 0, search: ReviewSearch::default(), return_mode }
+    }
+
+    /// 包含搜索词的行（不区分大小写）
+    pub fn matches(&self) -> Vec<usize> {
+        let query = self.search.query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.to_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// 跳到下一个（或上一个）匹配并滚动到它，到头后回绕；没有匹配时返回 false
+    pub fn jump_to_match(&mut self, forward: bool) -> bool {
+        let matches = self.matches();
+        let from = self.search.current;
+        let target = if forward {
+            matches.iter().find(|&&line| from.is_none_or(|from| line > from)).or(matches.first())
+        } else {
+            matches.iter().rev().find(|&&line| from.is_some_and(|from| line < from)).or(matches.last())
+        };
+        self.search.current = target.copied();
+        if let Some(&line) = target {
+            self.scroll = line;
+        }
+        target.is_some()
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let last = self.lines.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(last);
+    }
+}
@@ -0,0 +1,68 @@
+use std::fs;
+
+use crate::config::{ parse_ssh_config_with_warnings, BackupEntry, SshHost };
+use crate::core::{ ChangeType, Selector };
+
+/// 条目的身份：全局部分、Match 块和主机分别按各自的名称对应
+fn entry_key(host: &SshHost) -> (u8, &str) {
+    let kind = if host.is_prelude() {
+        0
+    } else if host.is_match_block() {
+        1
+    } else {
+        2
+    };
+    (kind, host.name.as_str())
+}
+
+/// 把主配置恢复为备份内容所需的变更：先删除备份中没有的，再修改不同的，最后新增当前没有的。
+/// current 是应用待保存变更后的主机，只比较主配置中的条目（被 Include 的文件不在备份中）
+pub fn backup_restore_changes(backup: &[SshHost], current: &[SshHost]) -> Vec<ChangeType> {
+    let current: Vec<&SshHost> = current
+        .iter()
+        .filter(|host| host.source.is_none() && !host.temporary && !host.system)
+        .collect();
+    let in_backup = |host: &SshHost| backup.iter().any(|other| entry_key(other) == entry_key(host));
+
+    let mut changes: Vec<ChangeType> = current
+        .iter()
+        .filter(|host| !in_backup(host))
+        .map(|host| ChangeType::Deleted((*host).clone()))
+        .collect();
+    for host in backup {
+        match current.iter().find(|other| entry_key(other) == entry_key(host)) {
+            Some(old) if *old != host => changes.push(ChangeType::Modified { old: (*old).clone(), new: host.clone() }),
+            Some(_) => {}
+            None => changes.push(ChangeType::Added(host.clone())),
+        }
+    }
+    changes
+}
+
+/// 读取并解析备份文件，计算恢复它所需的变更
+pub fn preview_backup(backup: &BackupEntry, current: &[SshHost]) -> Result<Vec<ChangeType>, String> {
+    let content = fs::read_to_string(&backup.path).map_err(|e| format!("Unable to read {}: {}", backup.path.display(), e))?;
+    let (hosts, _) = parse_ssh_config_with_warnings(&content);
+    Ok(backup_restore_changes(&hosts, current))
+}
+
+/// 备份浏览器：列出备份，预览选中备份与当前主配置的差异，Enter 把恢复暂存为待保存变更
+#[derive(Debug, Clone)]
+pub struct BackupBrowser {
+    pub backups: Vec<BackupEntry>,
+    pub choice: Selector,
+    /// 选中备份的恢复变更；读取失败时为错误信息
+    pub preview: Result<Vec<ChangeType>, String>,
+    pub preview_scroll: usize,
+}
+
+impl BackupBrowser {
+    pub fn new(backups: Vec<BackupEntry>) -> Self {
+        let choice = Selector::new(backups.len());
+        Self { backups, choice, preview: Ok(Vec::new()), preview_scroll: 0 }
+    }
+
+    pub fn selected(&self) -> Option<&BackupEntry> {
+        self.backups.get(self.choice.selected)
+    }
+}
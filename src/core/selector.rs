@@ -0,0 +1,76 @@
+use crossterm::event::KeyCode;
+
+/// 确认框中 "是" 和 "否" 两项的位置
+pub const CONFIRM_YES: usize = 0;
+pub const CONFIRM_NO: usize = 1;
+
+/// 选择器处理按键的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectorEvent {
+    Moved,
+    /// Enter 或选项的快捷键确认了这一项
+    Accepted(usize),
+    Cancelled,
+    /// 选择器不处理的按键，交给弹窗自己（如输入框、切换勾选）
+    Unhandled,
+}
+
+/// 弹窗中的选择列表，统一按键：↑↓/Tab/Shift-Tab 移动（首尾循环），Enter 确认，ESC 取消，
+/// 快捷键（如确认框的 y/n）直接确认对应项。只保存位置，选项文本由弹窗渲染
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub selected: usize,
+    pub len: usize,
+    /// (按键, 选项)，不区分大小写
+    pub accelerators: Vec<(char, usize)>,
+}
+
+impl Selector {
+    pub fn new(len: usize) -> Self {
+        Self { selected: 0, len, accelerators: Vec::new() }
+    }
+
+    pub fn with_accelerators(mut self, accelerators: &[(char, usize)]) -> Self {
+        self.accelerators = accelerators.to_vec();
+        self
+    }
+
+    /// 是/否确认框：y/n 直接确认，默认选中 "否"，误按 Enter 不会执行操作
+    pub fn confirm() -> Self {
+        Self { selected: CONFIRM_NO, ..Self::new(2).with_accelerators(&[('y', CONFIRM_YES), ('n', CONFIRM_NO)]) }
+    }
+
+    /// 列表长度变化后（如加入新项）保持选中项在范围内
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len;
+        self.selected = self.selected.min(len.saturating_sub(1));
+    }
+
+    pub fn handle_key(&mut self, key_code: KeyCode) -> SelectorEvent {
+        match key_code {
+            KeyCode::Esc => SelectorEvent::Cancelled,
+            KeyCode::Down | KeyCode::Tab if self.len > 0 => {
+                self.selected = (self.selected + 1) % self.len;
+                SelectorEvent::Moved
+            }
+            KeyCode::Up | KeyCode::BackTab if self.len > 0 => {
+                self.selected = (self.selected + self.len - 1) % self.len;
+                SelectorEvent::Moved
+            }
+            KeyCode::Enter if self.len > 0 => SelectorEvent::Accepted(self.selected.min(self.len - 1)),
+            KeyCode::Char(c) => {
+                let found = self.accelerators
+                    .iter()
+                    .find(|(key, index)| key.eq_ignore_ascii_case(&c) && *index < self.len);
+                match found {
+                    Some(&(_, index)) => {
+                        self.selected = index;
+                        SelectorEvent::Accepted(index)
+                    }
+                    None => SelectorEvent::Unhandled,
+                }
+            }
+            _ => SelectorEvent::Unhandled,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::ffi::OsStr;
+use std::path::{ Path, PathBuf };
+
+/// 连接用的 ssh 程序的查找结果，缓存在 App 上，F5 时重新查找
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum SshBinary {
+    /// 还没有查找（例如测试中直接创建的 App），连接照常进行
+    #[default]
+    Unchecked,
+    Found(PathBuf),
+    Missing,
+}
+
+impl SshBinary {
+    /// 按 PATH 的顺序查找 ssh，与连接时 Command::new("ssh") 的查找一致
+    pub fn locate() -> Self {
+        Self::locate_in(std::env::var_os("PATH").as_deref())
+    }
+
+    pub fn locate_in(path: Option<&OsStr>) -> Self {
+        find_program("ssh", path).map_or(Self::Missing, Self::Found)
+    }
+
+    pub fn is_missing(&self) -> bool {
+        *self == Self::Missing
+    }
+}
+
+/// 在 path（PATH 格式的目录列表）中查找可执行的 name；Windows 上同时查找 name.exe
+pub fn find_program(name: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    let names: Vec<String> = if cfg!(windows) { vec![format!("{}.exe", name), name.to_string()] } else { vec![name.to_string()] };
+    std::env::split_paths(path?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)).collect::<Vec<_>>())
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,56 @@
+use std::time::{ Duration, Instant };
+
+/// 首帧之后才读取的状态文件，主循环空闲时每次 tick 加载一项
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeferredLoad {
+    History,
+    TransferPaths,
+}
+
+impl DeferredLoad {
+    pub const ALL: [DeferredLoad; 2] = [DeferredLoad::History, DeferredLoad::TransferPaths];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::History => "history",
+            Self::TransferPaths => "transfer paths",
+        }
+    }
+}
+
+/// 启动各阶段的耗时；--profile-startup 时在退出后打印到 stderr
+#[derive(Debug, Clone)]
+pub struct StartupProfile {
+    started: Instant,
+    /// 阶段名、耗时、完成时距启动的时间
+    pub phases: Vec<(String, Duration, Duration)>,
+}
+
+impl Default for StartupProfile {
+    fn default() -> Self {
+        Self { started: Instant::now(), phases: Vec::new() }
+    }
+}
+
+impl StartupProfile {
+    pub fn record(&mut self, phase: &str, duration: Duration) {
+        self.phases.push((phase.to_string(), duration, self.started.elapsed()));
+    }
+
+    /// 执行 work 并记录耗时
+    pub fn time<T>(&mut self, phase: &str, work: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = work();
+        self.record(phase, started.elapsed());
+        result
+    }
+
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = vec!["startup profile:".to_string()];
+        let millis = |duration: &Duration| duration.as_secs_f64() * 1000.0;
+        for (phase, duration, at) in &self.phases {
+            lines.push(format!("  {:<16} {:>8.1} ms  (done at {:.1} ms)", phase, millis(duration), millis(at)));
+        }
+        lines
+    }
+}
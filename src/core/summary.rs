@@ -0,0 +1,207 @@
+use crate::config::{ option_display_name, SshHost };
+use crate::core::ChangeType;
+
+/// 审阅摘要中最多列出的主机数，对应数字键 1-9
+pub const SUMMARY_ROW_LIMIT: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SummaryKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl SummaryKind {
+    pub fn marker(self) -> char {
+        match self {
+            SummaryKind::Added => '+',
+            SummaryKind::Modified => '~',
+            SummaryKind::Deleted => '-',
+        }
+    }
+}
+
+/// 摘要中的一台主机；同一主机的多次修改合并为一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryRow {
+    pub name: String,
+    pub kind: SummaryKind,
+    pub fields: Vec<String>,  // 修改过的字段，仅 Modified 使用
+    pub change_index: usize,  // 第一条相关变更在 pending_changes 中的位置
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeSummary {
+    pub rows: Vec<SummaryRow>,
+    pub folders: Vec<String>,  // 涉及的文件夹（修改前后都算），按名称排序
+}
+
+impl ChangeSummary {
+    pub fn count(&self, kind: SummaryKind) -> usize {
+        self.rows.iter().filter(|row| row.kind == kind).count()
+    }
+
+    /// 例如 "2 added, 3 modified, 1 deleted — folders affected: work, lab"
+    pub fn headline(&self) -> String {
+        let counts: Vec<String> = [(SummaryKind::Added, "added"), (SummaryKind::Modified, "modified"), (SummaryKind::Deleted, "deleted")]
+            .into_iter()
+            .map(|(kind, label)| (self.count(kind), label))
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, label)| format!("{} {}", count, label))
+            .collect();
+        let counts = if counts.is_empty() { "No changes".to_string() } else { counts.join(", ") };
+        if self.folders.is_empty() {
+            counts
+        } else {
+            format!("{} — folders affected: {}", counts, self.folders.join(", "))
+        }
+    }
+}
+
+/// 一个字段修改前后的值，未设置显示为 (none)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+fn value(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "(none)".to_string())
+}
+
+fn list(values: &[String]) -> String {
+    if values.is_empty() { "(none)".to_string() } else { values.join(", ") }
+}
+
+fn row_style_value(host: &SshHost) -> String {
+    list(&host.style.iter().chain(&host.color).cloned().collect::<Vec<_>>())
+}
+
+fn env_value(host: &SshHost) -> String {
+    list(&host.env.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>())
+}
+
+fn source_value(host: &SshHost) -> String {
+    host.source.as_ref().map(|path| path.display().to_string()).unwrap_or_else(|| "(main config)".to_string())
+}
+
+/// 两个版本之间不同的字段及其前后的值，顺序与 diff 一致；其他选项用规范写法并按键名排序
+pub fn field_changes(old: &SshHost, new: &SshHost) -> Vec<FieldChange> {
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" }.to_string();
+    let shown = |visible: bool| if visible { "shown" } else { "hidden" }.to_string();
+    let mut changes: Vec<FieldChange> = [
+        ("Name", old.name != new.name, old.name.clone(), new.name.clone()),
+        ("Folder", old.folder != new.folder, value(&old.folder), value(&new.folder)),
+        ("Display name", old.display_name != new.display_name, value(&old.display_name), value(&new.display_name)),
+        ("Description", old.description != new.description, value(&old.description), value(&new.description)),
+        ("Visibility", old.visible != new.visible, shown(old.visible), shown(new.visible)),
+        ("Disabled", old.disabled != new.disabled, yes_no(old.disabled), yes_no(new.disabled)),
+        ("Tags", old.tags != new.tags, list(&old.tags), list(&new.tags)),
+        ("Aliases", old.aliases != new.aliases, list(&old.aliases), list(&new.aliases)),
+        ("Row style", old.style != new.style || old.color != new.color, row_style_value(old), row_style_value(new)),
+        ("Env", old.env != new.env, env_value(old), env_value(new)),
+        ("Connector", old.connector != new.connector, value(&old.connector), value(&new.connector)),
+        ("HostName", old.hostname != new.hostname, value(&old.hostname), value(&new.hostname)),
+        ("User", old.user != new.user, value(&old.user), value(&new.user)),
+        ("Port", old.port != new.port, value(&old.port), value(&new.port)),
+        ("IdentityFile", old.identity_file != new.identity_file, value(&old.identity_file), value(&new.identity_file)),
+        ("Config file", old.source != new.source, source_value(old), source_value(new)),
+    ]
+        .into_iter()
+        .filter(|(_, changed, _, _)| *changed)
+        .map(|(field, _, before, after)| FieldChange { field: field.to_string(), before, after })
+        .collect();
+
+    let mut keys: Vec<&String> = old.other_options.keys().chain(new.other_options.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        if !old.other_options.get_all(key).eq(new.other_options.get_all(key)) {
+            let field = option_display_name(key);
+            // 额外的 IdentityFile 保存在其他选项中，不重复列出
+            if !changes.iter().any(|change| change.field == field) {
+                let values = |host: &SshHost| list(&host.other_options.get_all(key).cloned().collect::<Vec<_>>());
+                changes.push(FieldChange { field, before: values(old), after: values(new) });
+            }
+        }
+    }
+    changes
+}
+
+/// 两个版本之间不同的字段名
+pub fn changed_fields(old: &SshHost, new: &SshHost) -> Vec<String> {
+    field_changes(old, new).into_iter().map(|change| change.field).collect()
+}
+
+/// 一台主机的未保存修改：从第一条修改前到最后一条修改后的逐字段对比
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostDiff {
+    pub name: String,
+    pub fields: Vec<FieldChange>,
+    /// 这台主机的修改在 pending_changes 中的位置，撤销时一并移除
+    pub change_indices: Vec<usize>,
+    /// 主机本身是未保存的新增，没有可对比的旧版本
+    pub added: bool,
+}
+
+/// 从最后一条变更往前沿着修改链（包括改名）找到这台主机的所有修改，遇到新增或删除为止
+pub fn host_diff(changes: &[ChangeType], name: &str) -> HostDiff {
+    let mut diff = HostDiff { name: name.to_string(), ..HostDiff::default() };
+    let mut target = name.to_string();
+    let mut range: Option<(&SshHost, &SshHost)> = None;
+    for (index, change) in changes.iter().enumerate().rev() {
+        match change {
+            ChangeType::Modified { old, new } if new.name == target => {
+                diff.change_indices.insert(0, index);
+                range = Some((old, range.map_or(new, |(_, last)| last)));
+                target = old.name.clone();
+            }
+            ChangeType::Added(host) if host.name == target => {
+                return HostDiff { name: name.to_string(), added: true, ..HostDiff::default() };
+            }
+            ChangeType::Deleted(host) if host.name == target => break,
+            _ => {}
+        }
+    }
+    if let Some((old, new)) = range {
+        diff.fields = field_changes(old, new);
+    }
+    diff
+}
+
+/// 汇总待保存变更：每台主机一行，并收集涉及的文件夹
+pub fn summarize_changes(changes: &[ChangeType]) -> ChangeSummary {
+    let mut summary = ChangeSummary::default();
+
+    for (index, change) in changes.iter().enumerate() {
+        let (hosts, row) = match change {
+            ChangeType::Added(host) => (vec![host], SummaryRow { name: host.name.clone(), kind: SummaryKind::Added, fields: Vec::new(), change_index: index }),
+            ChangeType::Deleted(host) => (vec![host], SummaryRow { name: host.name.clone(), kind: SummaryKind::Deleted, fields: Vec::new(), change_index: index }),
+            ChangeType::Modified { old, new } => {
+                let fields = changed_fields(old, new);
+                // 同一主机之前已有新增或修改时合并到那一行（可能已改名）
+                let existing = summary.rows
+                    .iter_mut()
+                    .find(|row| row.name == old.name && row.kind != SummaryKind::Deleted);
+                if let Some(row) = existing {
+                    row.name = new.name.clone();
+                    for field in fields {
+                        if row.kind == SummaryKind::Modified && !row.fields.contains(&field) {
+                            row.fields.push(field);
+                        }
+                    }
+                    summary.folders.extend(old.folder.iter().chain(&new.folder).cloned());
+                    continue;
+                }
+                (vec![old, new], SummaryRow { name: new.name.clone(), kind: SummaryKind::Modified, fields, change_index: index })
+            }
+        };
+        summary.folders.extend(hosts.iter().filter_map(|host| host.folder.clone()));
+        summary.rows.push(row);
+    }
+
+    summary.folders.sort();
+    summary.folders.dedup();
+    summary
+}
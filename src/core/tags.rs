@@ -0,0 +1,92 @@
+use crate::config::{ split_tags, SshHost };
+use crate::core::Selector;
+
+/// 标签在批量编辑器中的勾选状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagCheck {
+    /// 所有选中主机都有（应用时补给缺少的主机）
+    Checked,
+    /// 只有部分主机有（应用时保持原样）
+    Partial,
+    /// 所有主机都没有（应用时移除）
+    Unchecked,
+}
+
+#[derive(Debug, Clone)]
+pub struct TagRow {
+    pub tag: String,
+    pub original: TagCheck,
+    pub state: TagCheck,
+}
+
+/// 对多选主机批量编辑标签
+#[derive(Debug, Clone)]
+pub struct TagEditor {
+    pub hosts: Vec<String>,  // 被编辑的主机名
+    pub rows: Vec<TagRow>,
+    pub picker: Selector,
+    pub input: String,  // 新标签输入框，逗号分隔
+}
+
+impl TagEditor {
+    /// 汇总各主机的标签，按首次出现顺序排列
+    pub fn new(hosts: &[&SshHost]) -> Self {
+        let mut rows: Vec<TagRow> = Vec::new();
+        for host in hosts {
+            for tag in &host.tags {
+                if rows.iter().any(|row| row.tag == *tag) {
+                    continue;
+                }
+                let count = hosts.iter().filter(|h| h.tags.contains(tag)).count();
+                let check = if count == hosts.len() { TagCheck::Checked } else { TagCheck::Partial };
+                rows.push(TagRow { tag: tag.clone(), original: check, state: check });
+            }
+        }
+        Self {
+            hosts: hosts.iter().map(|host| host.name.clone()).collect(),
+            picker: Selector::new(rows.len()),
+            rows,
+            input: String::new(),
+        }
+    }
+
+    /// 切换勾选：部分主机拥有的标签在 部分 → 全部 → 无 之间循环，其余在 全部/无 之间切换
+    pub fn toggle(&mut self, index: usize) {
+        let Some(row) = self.rows.get_mut(index) else {
+            return;
+        };
+        row.state = match (row.state, row.original) {
+            (TagCheck::Partial, _) => TagCheck::Checked,
+            (TagCheck::Checked, _) => TagCheck::Unchecked,
+            (TagCheck::Unchecked, TagCheck::Partial) => TagCheck::Partial,
+            (TagCheck::Unchecked, _) => TagCheck::Checked,
+        };
+    }
+
+    /// 把输入框中的标签加入列表并勾选，已存在的标签直接改为勾选
+    pub fn add_input(&mut self) {
+        for tag in split_tags(&self.input) {
+            match self.rows.iter_mut().find(|row| row.tag == tag) {
+                Some(row) => row.state = TagCheck::Checked,
+                None => self.rows.push(TagRow { tag, original: TagCheck::Unchecked, state: TagCheck::Checked }),
+            }
+        }
+        self.picker.set_len(self.rows.len());
+        self.input.clear();
+    }
+
+    /// 应用到一台主机后的标签：保留原有顺序，移除取消勾选的，末尾追加新勾选的
+    pub fn apply(&self, tags: &[String]) -> Vec<String> {
+        let mut result: Vec<String> = tags
+            .iter()
+            .filter(|tag| !self.rows.iter().any(|row| row.tag == **tag && row.state == TagCheck::Unchecked))
+            .cloned()
+            .collect();
+        for row in &self.rows {
+            if row.state == TagCheck::Checked && !result.contains(&row.tag) {
+                result.push(row.tag.clone());
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+use std::sync::mpsc::{ self, Receiver, TryRecvError };
+use std::thread;
+use std::time::Instant;
+
+use crate::config::SshHost;
+use crate::core::{ host_env, query_effective_config, resolve_name, DnsStatus, ProbeRun, ProbeTarget };
+
+/// 主机行和详情中显示的进行中标记，每次 tick 换一帧
+pub const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+pub type TaskId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskKind {
+    EffectiveConfig,
+    Dns,
+}
+
+impl TaskKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::EffectiveConfig => "ssh -G",
+            Self::Dns => "DNS lookup",
+        }
+    }
+}
+
+/// 工作线程的句柄；丢弃即取消：通道断开后迟到的结果无处可送，ProbeRun 同时置位取消标志
+pub enum TaskHandle {
+    EffectiveConfig(Receiver<Result<String, String>>),
+    /// 查询的名字和查询本身
+    Dns(String, ProbeRun<DnsStatus>),
+}
+
+/// 已完成任务的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskOutput {
+    EffectiveConfig(Result<String, String>),
+    Dns(String, DnsStatus),
+}
+
+/// 一个进行中的后台任务
+pub struct BackgroundTask {
+    pub id: TaskId,
+    pub host: String,
+    pub kind: TaskKind,
+    pub started: Instant,
+    handle: TaskHandle,
+}
+
+impl BackgroundTask {
+    /// 不阻塞地检查是否完成
+    fn poll(&mut self) -> Option<TaskOutput> {
+        match &mut self.handle {
+            TaskHandle::EffectiveConfig(receiver) => match receiver.try_recv() {
+                Ok(result) => Some(TaskOutput::EffectiveConfig(result)),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(TaskOutput::EffectiveConfig(Err("ssh -G did not return a result".to_string()))),
+            },
+            TaskHandle::Dns(name, run) => run.poll().pop().map(|(_, status)| TaskOutput::Dns(name.clone(), status)),
+        }
+    }
+
+    /// DNS 任务查询的名字
+    pub fn dns_target(&self) -> Option<&str> {
+        match &self.handle {
+            TaskHandle::Dns(name, _) => Some(name),
+            TaskHandle::EffectiveConfig(_) => None,
+        }
+    }
+}
+
+/// 所有按主机进行的后台任务：任务 id → 主机 → 类型，以及各自的句柄
+#[derive(Default)]
+pub struct TaskRegistry {
+    next_id: TaskId,
+    pub tasks: Vec<BackgroundTask>,
+    frame: usize,
+}
+
+impl TaskRegistry {
+    /// 登记一个已启动的任务，id 在本次会话内不重复
+    pub fn track(&mut self, host: &str, kind: TaskKind, handle: TaskHandle) -> TaskId {
+        self.next_id += 1;
+        self.tasks.push(BackgroundTask { id: self.next_id, host: host.to_string(), kind, started: Instant::now(), handle });
+        self.next_id
+    }
+
+    /// 在后台运行 ssh -G，config 为正在编辑的配置文件
+    pub fn start_effective_config(&mut self, host: &SshHost, config: Option<&Path>) -> TaskId {
+        let (sender, receiver) = mpsc::channel();
+        let config = config.map(Path::to_path_buf);
+        let args = host.ssh_args();
+        let env = host_env(host);
+        thread::spawn(move || {
+            let _ = sender.send(query_effective_config(config.as_deref(), &args, &env));
+        });
+        self.track(&host.name, TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver))
+    }
+
+    /// 在后台解析 name
+    pub fn start_dns(&mut self, host: &str, name: &str) -> TaskId {
+        let target = ProbeTarget { key: 0, address: name.to_string(), port: 0 };
+        let run = ProbeRun::spawn(vec![target], |target| resolve_name(&target.address));
+        self.track(host, TaskKind::Dns, TaskHandle::Dns(name.to_string(), run))
+    }
+
+    /// 收取已完成的任务，并推进进行中标记的动画
+    pub fn poll(&mut self) -> Vec<(BackgroundTask, TaskOutput)> {
+        self.frame = self.frame.wrapping_add(1);
+        let mut finished = Vec::new();
+        let mut index = 0;
+        while index < self.tasks.len() {
+            match self.tasks[index].poll() {
+                Some(output) => finished.push((self.tasks.remove(index), output)),
+                None => index += 1,
+            }
+        }
+        finished
+    }
+
+    /// 从登记中移除主机的全部任务并返回它们；丢弃返回值即释放句柄，之后完成的结果无处可送
+    pub fn cancel_host(&mut self, host: &str) -> Vec<BackgroundTask> {
+        let (cancelled, kept) = std::mem::take(&mut self.tasks).into_iter().partition(|task| task.host == host);
+        self.tasks = kept;
+        cancelled
+    }
+
+    pub fn is_pending(&self, host: &str) -> bool {
+        self.tasks.iter().any(|task| task.host == host)
+    }
+
+    pub fn pending_kind(&self, host: &str, kind: TaskKind) -> bool {
+        self.tasks.iter().any(|task| task.host == host && task.kind == kind)
+    }
+
+    pub fn for_host<'a>(&'a self, host: &'a str) -> impl Iterator<Item = &'a BackgroundTask> {
+        self.tasks.iter().filter(move |task| task.host == host)
+    }
+
+    pub fn spinner(&self) -> char {
+        SPINNER_FRAMES[self.frame % SPINNER_FRAMES.len()]
+    }
+}
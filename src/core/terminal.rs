@@ -1,59 +1,114 @@
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    cursor::MoveTo,
+    event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyEventKind},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
 
 use crate::utils::{Result, SshcError};
 
+/// 界面输出的目标，通常是 stdout；--print 时为 stderr，让 stdout 只包含结果
+pub type TerminalOutput = Box<dyn io::Write + Send>;
+
 pub struct TerminalManager {
-    terminal: Terminal<CrosstermBackend<io::Stdout>>,
+    terminal: Terminal<CrosstermBackend<TerminalOutput>>,
+    left_alternate_screen: bool,  // suspend 时是否离开了备用屏幕，resume 据此决定是否重新进入
+    pub plain: bool,  // NO_COLOR 或 --plain：界面外的提示也不带颜色
 }
 
 impl TerminalManager {
     pub fn new() -> Result<Self> {
+        Self::with_output(Box::new(io::stdout()))
+    }
+
+    pub fn with_output(mut output: TerminalOutput) -> Result<Self> {
         enable_raw_mode().map_err(|e| SshcError::Terminal(e.to_string()))?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
+        execute!(output, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)
             .map_err(|e| SshcError::Terminal(e.to_string()))?;
         
-        let backend = CrosstermBackend::new(stdout);
+        let backend = CrosstermBackend::new(output);
         let mut terminal = Terminal::new(backend)
             .map_err(|e| SshcError::Terminal(e.to_string()))?;
         
         // Hide cursor initially
         terminal.hide_cursor().map_err(|e| SshcError::Terminal(e.to_string()))?;
 
-        Ok(TerminalManager { terminal })
+        Ok(TerminalManager { terminal, left_alternate_screen: false, plain: false })
     }
 
-    pub fn terminal(&mut self) -> &mut Terminal<CrosstermBackend<io::Stdout>> {
+    pub fn terminal(&mut self) -> &mut Terminal<CrosstermBackend<TerminalOutput>> {
         &mut self.terminal
     }
 
     pub fn suspend(&mut self) -> Result<()> {
+        self.suspend_with(false)
+    }
+
+    /// keep_alternate_screen 为 true 时不离开备用屏幕，只清屏，避免嵌套终端闪烁
+    pub fn suspend_with(&mut self, keep_alternate_screen: bool) -> Result<()> {
         // Show cursor before suspending
         self.terminal.show_cursor().map_err(|e| SshcError::Terminal(e.to_string()))?;
         disable_raw_mode().map_err(|e| SshcError::Terminal(e.to_string()))?;
         execute!(
             self.terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableBracketedPaste
         ).map_err(|e| SshcError::Terminal(e.to_string()))?;
+
+        if keep_alternate_screen {
+            execute!(self.terminal.backend_mut(), Clear(ClearType::All), MoveTo(0, 0))
+                .map_err(|e| SshcError::Terminal(e.to_string()))?;
+        } else {
+            execute!(self.terminal.backend_mut(), LeaveAlternateScreen)
+                .map_err(|e| SshcError::Terminal(e.to_string()))?;
+        }
+        self.left_alternate_screen = !keep_alternate_screen;
         Ok(())
     }
 
+    /// 在备用屏幕之外打印一行带颜色（单色模式下不带）的提示，返回是否已打印。
+    /// 仍停留在备用屏幕时（keep 模式）不打印，提示不会混入界面
+    pub fn print_outside(&mut self, text: &str, color: Color) -> Result<bool> {
+        if !self.left_alternate_screen {
+            return Ok(false);
+        }
+        let backend = self.terminal.backend_mut();
+        let printed = if self.plain {
+            execute!(backend, Print(text), Print("\r\n"))
+        } else {
+            execute!(backend, SetForegroundColor(color), Print(text), ResetColor, Print("\r\n"))
+        };
+        printed.map_err(|e| SshcError::Terminal(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// 等待任意按键；会打开 raw mode，随后的 resume 照常恢复界面
+    pub fn wait_for_key(&mut self) -> Result<()> {
+        enable_raw_mode().map_err(|e| SshcError::Terminal(e.to_string()))?;
+        loop {
+            if let Event::Key(key) = event::read()? && key.kind == KeyEventKind::Press {
+                return Ok(());
+            }
+        }
+    }
+
     pub fn resume(&mut self) -> Result<()> {
         enable_raw_mode().map_err(|e| SshcError::Terminal(e.to_string()))?;
+        if self.left_alternate_screen {
+            execute!(self.terminal.backend_mut(), EnterAlternateScreen)
+                .map_err(|e| SshcError::Terminal(e.to_string()))?;
+            self.left_alternate_screen = false;
+        }
         execute!(
             self.terminal.backend_mut(),
-            EnterAlternateScreen,
-            EnableMouseCapture
+            EnableMouseCapture,
+            EnableBracketedPaste
         ).map_err(|e| SshcError::Terminal(e.to_string()))?;
         
-        // Clear and redraw the terminal
+        // 无论哪种方式都清屏，保证会话结束后不残留旧界面
         self.terminal.clear().map_err(|e| SshcError::Terminal(e.to_string()))?;
         self.terminal.hide_cursor().map_err(|e| SshcError::Terminal(e.to_string()))?;
         
@@ -65,8 +120,10 @@ impl TerminalManager {
         execute!(
             self.terminal.backend_mut(),
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableBracketedPaste
         ).map_err(|e| SshcError::Terminal(e.to_string()))?;
+        self.left_alternate_screen = true;
         self.terminal.show_cursor()
             .map_err(|e| SshcError::Terminal(e.to_string()))?;
         Ok(())
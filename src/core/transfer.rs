@@ -0,0 +1,116 @@
+use crate::config::TransferPaths;
+
+/// 文件传输方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferKind {
+    /// scp 本地 → 远程
+    Upload,
+    /// scp 远程 → 本地
+    Download,
+    /// 交互式 sftp，从远程路径开始
+    Sftp,
+}
+
+impl TransferKind {
+    pub const ALL: [TransferKind; 3] = [TransferKind::Upload, TransferKind::Download, TransferKind::Sftp];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TransferKind::Upload => "scp upload",
+            TransferKind::Download => "scp download",
+            TransferKind::Sftp => "sftp",
+        }
+    }
+
+    pub fn uses_local_path(self) -> bool {
+        self != TransferKind::Sftp
+    }
+}
+
+/// 传输弹窗的字段：0 方式，1 本地路径，2 远程路径
+pub const TRANSFER_FIELDS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct TransferState {
+    pub host_index: usize,
+    pub host: String,  // 主机名，远程路径历史按它保存
+    pub kind: TransferKind,
+    pub local: String,
+    pub remote: String,
+    pub field: usize,
+    // 在路径历史中选中的位置，None 表示正在手动输入
+    pub history_index: Option<usize>,
+    pub error: Option<String>,
+}
+
+impl TransferState {
+    /// 用最近一次的本地路径和该主机最近一次的远程路径预填
+    pub fn new(host_index: usize, host: &str, paths: &TransferPaths) -> Self {
+        Self {
+            host_index,
+            host: host.to_string(),
+            kind: TransferKind::Upload,
+            local: paths.local.first().cloned().unwrap_or_default(),
+            remote: paths.remote_paths(host).first().cloned().unwrap_or_default(),
+            field: 1,
+            history_index: None,
+            error: None,
+        }
+    }
+
+    pub fn cycle_kind(&mut self) {
+        let index = TransferKind::ALL.iter().position(|kind| *kind == self.kind).unwrap_or(0);
+        self.kind = TransferKind::ALL[(index + 1) % TransferKind::ALL.len()];
+    }
+
+    /// 当前路径字段对应的历史列表
+    pub fn history<'a>(&self, paths: &'a TransferPaths) -> &'a [String] {
+        match self.field {
+            1 => &paths.local,
+            2 => paths.remote_paths(&self.host),
+            _ => &[],
+        }
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            1 => Some(&mut self.local),
+            2 => Some(&mut self.remote),
+            _ => None,
+        }
+    }
+
+    /// 在历史中上下移动并把选中的路径填入当前字段
+    pub fn step_history(&mut self, history: &[String], down: bool) {
+        if history.is_empty() {
+            return;
+        }
+        let index = match (self.history_index, down) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1).min(history.len() - 1),
+            (Some(i), false) => i.saturating_sub(1),
+        };
+        self.history_index = Some(index);
+        if let Some(value) = self.value_mut() {
+            *value = history[index].clone();
+        }
+    }
+
+    pub fn move_field(&mut self, forward: bool) {
+        self.field = if forward { (self.field + 1) % TRANSFER_FIELDS } else { (self.field + TRANSFER_FIELDS - 1) % TRANSFER_FIELDS };
+        self.history_index = None;
+    }
+
+    /// 要执行的程序和参数；缺少必需的路径时返回错误说明
+    pub fn command(&self, target: &str) -> Result<(&'static str, Vec<String>), String> {
+        let (local, remote) = (self.local.trim(), self.remote.trim());
+        let remote_spec = format!("{}:{}", target, remote);
+        match self.kind {
+            TransferKind::Sftp if remote.is_empty() => Ok(("sftp", vec![target.to_string()])),
+            TransferKind::Sftp => Ok(("sftp", vec![remote_spec])),
+            _ if local.is_empty() || remote.is_empty() => Err("Both the local and the remote path are required".to_string()),
+            TransferKind::Upload => Ok(("scp", vec!["-r".to_string(), local.to_string(), remote_spec])),
+            TransferKind::Download => Ok(("scp", vec!["-r".to_string(), remote_spec, local.to_string()])),
+        }
+    }
+}
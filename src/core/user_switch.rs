@@ -0,0 +1,62 @@
+use crate::config::SshHost;
+
+/// 以其他用户连接的选择弹窗：列表中的用户，最后一行为自由输入
+#[derive(Debug, Clone)]
+pub struct UserSwitchState {
+    pub host_index: usize,
+    pub choices: Vec<String>,
+    pub selected: usize,  // 等于 choices.len() 时为自由输入行
+    pub custom: String,
+}
+
+impl UserSwitchState {
+    /// 默认选中本次会话中为该主机选过的用户
+    pub fn new(host_index: usize, hosts: &[SshHost], remembered: Option<&str>) -> Self {
+        let mut choices = user_choices(&hosts[host_index], hosts);
+        if let Some(user) = remembered && !choices.iter().any(|choice| choice == user) {
+            choices.push(user.to_string());
+        }
+        let selected = remembered.and_then(|user| choices.iter().position(|choice| choice == user)).unwrap_or(0);
+        Self { host_index, choices, selected, custom: String::new() }
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.choices.len());
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// 输入字符时切换到自由输入行
+    pub fn push_char(&mut self, c: char) {
+        self.custom.push(c);
+        self.selected = self.choices.len();
+    }
+
+    pub fn user(&self) -> Option<String> {
+        match self.choices.get(self.selected) {
+            Some(user) => Some(user.clone()),
+            None => Some(self.custom.trim().to_string()).filter(|user| !user.is_empty()),
+        }
+    }
+}
+
+/// 可选用户：root、主机配置的用户，以及同一文件夹中其他主机的用户（按名称排序）
+pub fn user_choices(host: &SshHost, hosts: &[SshHost]) -> Vec<String> {
+    let mut choices = vec!["root".to_string()];
+    choices.extend(host.user.clone());
+
+    let mut folder_users: Vec<String> = hosts
+        .iter()
+        .filter(|other| host.folder.is_some() && other.folder == host.folder && !other.is_verbatim())
+        .filter_map(|other| other.user.clone())
+        .collect();
+    folder_users.sort();
+    for user in folder_users {
+        if !choices.contains(&user) {
+            choices.push(user);
+        }
+    }
+    choices
+}
@@ -0,0 +1,139 @@
+use anyhow::{ Context, Result };
+use serde::{ Deserialize, Serialize };
+use std::fs;
+use std::path::Path;
+
+use crate::config::create_parent_dir;
+use crate::core::{ AppMode, QuickFilter, Selector };
+
+/// 视图的格式版本。视图能还原的状态增加时加一，并在 SavedView::migrate 中为旧版本补上默认值
+pub const VIEW_FORMAT_VERSION: u32 = 1;
+
+/// 保存的视图，存放在数据目录下的 views.toml。version 为 0 表示没有写版本号（例如手写）的条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub query: String,
+    /// QuickFilter::key 的名称
+    #[serde(default)]
+    pub quick_filters: Vec<String>,
+    #[serde(default)]
+    pub show_hidden: bool,
+}
+
+/// 视图还原的列表状态：搜索词、快速过滤和是否显示隐藏主机
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ViewState {
+    pub query: String,
+    pub quick_filters: Vec<QuickFilter>,
+    pub show_hidden: bool,
+}
+
+impl ViewState {
+    /// 以当前格式版本保存为视图
+    pub fn to_view(&self, name: &str) -> SavedView {
+        SavedView {
+            name: name.to_string(),
+            version: VIEW_FORMAT_VERSION,
+            query: self.query.clone(),
+            quick_filters: self.quick_filters.iter().map(|filter| filter.key().to_string()).collect(),
+            show_hidden: self.show_hidden,
+        }
+    }
+
+    /// 一行描述，如 "search "db" + missing IdentityFile + hidden shown"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.query.is_empty() {
+            parts.push(format!("search \"{}\"", self.query));
+        }
+        parts.extend(self.quick_filters.iter().map(|filter| filter.label().to_string()));
+        if self.show_hidden {
+            parts.push("hidden shown".to_string());
+        }
+        if parts.is_empty() { "all hosts".to_string() } else { parts.join(" + ") }
+    }
+}
+
+impl SavedView {
+    /// 升级到当前格式。较新版本保存的视图或无法识别的过滤条件无法完整还原，返回错误而不是部分应用
+    pub fn migrate(&self) -> Result<ViewState, String> {
+        if self.version > VIEW_FORMAT_VERSION {
+            return Err(format!(
+                "View '{}' was saved by a newer sshc (format {}); update sshc to apply it",
+                self.name, self.version
+            ));
+        }
+        // 版本 0 与版本 1 的字段相同，只是没有写版本号
+        let mut quick_filters = Vec::new();
+        for key in &self.quick_filters {
+            let filter = QuickFilter::from_key(key)
+                .ok_or_else(|| format!("View '{}' uses an unknown filter '{}'", self.name, key))?;
+            if !quick_filters.contains(&filter) {
+                quick_filters.push(filter);
+            }
+        }
+        quick_filters.sort_by_key(|filter| QuickFilter::ALL.iter().position(|f| f == filter));
+        Ok(ViewState { query: self.query.clone(), quick_filters, show_hidden: self.show_hidden })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ViewFile {
+    views: Vec<SavedView>,
+}
+
+/// 读取保存的视图；文件不存在时为空
+pub fn load_views(path: &Path) -> Result<Vec<SavedView>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Unable to read {:?}", path)),
+    };
+    let file: ViewFile = toml::from_str(&content).with_context(|| format!("Unable to parse {:?}", path))?;
+    Ok(file.views)
+}
+
+/// 写回全部视图；无法应用的视图（如较新版本保存的）原样保留
+pub fn save_views(path: &Path, views: &[SavedView]) -> Result<()> {
+    create_parent_dir(path)?;
+    let content = toml::to_string(&ViewFile { views: views.to_vec() }).context("Unable to serialize views")?;
+    fs::write(path, content).with_context(|| format!("Unable to write {:?}", path))
+}
+
+/// 视图选择器中正在输入的名称
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewPrompt {
+    /// 把当前状态保存为新视图
+    New,
+    Rename(usize),
+}
+
+/// ' 打开的视图选择器，打开时从文件读取视图，每次修改后立即写回
+#[derive(Debug, Clone)]
+pub struct ViewPicker {
+    pub views: Vec<SavedView>,
+    pub choice: Selector,
+    pub prompt: Option<(ViewPrompt, String)>,
+    /// 关闭后回到的模式
+    pub return_mode: AppMode,
+}
+
+impl ViewPicker {
+    pub fn new(views: Vec<SavedView>, return_mode: AppMode) -> Self {
+        let choice = Selector::new(views.len());
+        Self { views, choice, prompt: None, return_mode }
+    }
+
+    /// 名称是否已被其他视图使用（不区分大小写）；except 为正在重命名的视图
+    pub fn name_taken(&self, name: &str, except: Option<usize>) -> bool {
+        self.views
+            .iter()
+            .enumerate()
+            .any(|(i, view)| Some(i) != except && view.name.eq_ignore_ascii_case(name))
+    }
+}
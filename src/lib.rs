@@ -1,34 +1,146 @@
+pub mod cli;
 pub mod core;
 pub mod config;
 pub mod ui;
 pub mod utils;
 
 use crossterm::event;
+use std::time::{ Duration, Instant };
 
+use crate::cli::{ parse_args, CliCommand, KeyReportFormat, PrintFormat, StartIn, USAGE };
+use crate::config::{
+    build_key_report, export_config_csv, host_fields_line, host_json, key_report_csv, key_report_json, key_report_table, parse_config_tree,
+    resolve_home, set_home_override, Theme,
+};
 use crate::core::{ App, TerminalManager };
-use crate::utils::Result;
+use crate::utils::{ Result, SshcError };
 use crate::ui::render;
 
 pub fn run() -> Result<()> {
-    let mut terminal = TerminalManager::new()?;
+    let options = parse_args(std::env::args().skip(1))?;
+    if options.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if let Some(home) = options.home {
+        set_home_override(home);
+    }
+
+    if options.read_only && matches!(options.command, Some(CliCommand::ImportCsv(_))) {
+        return Err(SshcError::Usage(format!("import-csv cannot be used with --read-only\n\n{}", USAGE)));
+    }
+    if options.print.is_some() && options.command.is_some() {
+        return Err(SshcError::Usage(format!("--print cannot be combined with a command\n\n{}", USAGE)));
+    }
+    if let Some(start) = &options.start {
+        let flag = match start {
+            StartIn::Config => "--edit",
+            StartIn::AddHost(_) => "--add",
+        };
+        if options.read_only {
+            return Err(SshcError::Usage(format!("{} cannot be used with --read-only\n\n{}", flag, USAGE)));
+        }
+        if options.print.is_some() {
+            return Err(SshcError::Usage(format!("{} cannot be combined with --print\n\n{}", flag, USAGE)));
+        }
+        if options.command.is_some() {
+            return Err(SshcError::Usage(format!("{} cannot be combined with a command\n\n{}", flag, USAGE)));
+        }
+    }
+    if let Some(CliCommand::ExportCsv(file)) = &options.command {
+        return export_csv_command(file.as_deref());
+    }
+    if let Some(CliCommand::KeyReport(format)) = options.command {
+        return key_report_command(format);
+    }
+
     let mut app = App::new()?;
+    let started = Instant::now();
+    let mut terminal = match options.print {
+        Some(_) => TerminalManager::with_output(Box::new(std::io::stderr()))?,
+        None => TerminalManager::new()?,
+    };
+    app.startup.record("terminal setup", started.elapsed());
+    // 超出解析限制时 App 已设为只读
+    if options.read_only {
+        app.read_only = true;
+    }
+    app.print_mode = options.print.is_some();
+    app.theme = Theme::select(options.plain, std::env::var("NO_COLOR").ok().as_deref());
+    terminal.plain = app.theme.plain;
+    if let Some(CliCommand::ImportCsv(file)) = &options.command {
+        app.start_import_file(file);
+    }
+    match &options.start {
+        Some(StartIn::Config) => app.start_config_management(),
+        Some(StartIn::AddHost(text)) => app.start_adding_host_from(text.as_deref()),
+        None => {}
+    }
 
     let result = run_app(&mut terminal, &mut app);
     terminal.restore()?;
+    if options.profile_startup {
+        eprintln!("{}", app.startup.report().join("\n"));
+    }
+    result?;
+
+    // 界面已退出，stdout 只包含选中的主机
+    if let Some(format) = &options.print {
+        let host = app.picked
+            .and_then(|index| app.hosts.get(index))
+            .ok_or_else(|| SshcError::Usage("No host selected".to_string()))?;
+        match format {
+            PrintFormat::Name => println!("{}", host.connect_target()),
+            PrintFormat::Json => println!("{}", host_json(host)?),
+            PrintFormat::Fields(fields) => println!("{}", host_fields_line(host, fields)),
+        }
+    }
+    Ok(())
+}
+
+/// 不启动界面，直接把所有主机导出为 CSV
+fn export_csv_command(file: Option<&std::path::Path>) -> Result<()> {
+    let home = resolve_home()?;
+    let content = export_config_csv(&home.join(".ssh").join("config"))?;
+    match file {
+        Some(path) => std::fs::write(path, content)?,
+        None => print!("{}", content),
+    }
+    Ok(())
+}
 
-    result
+/// 不启动界面，输出主机引用的公钥报告（包括 Include 的文件中的主机）
+fn key_report_command(format: KeyReportFormat) -> Result<()> {
+    let home = resolve_home()?;
+    let (hosts, _) = parse_config_tree(&home.join(".ssh").join("config"))?;
+    let entries = build_key_report(&hosts, Some(&home));
+    match format {
+        KeyReportFormat::Table => println!("{}", key_report_table(&entries).join("\n")),
+        KeyReportFormat::Json => println!("{}", key_report_json(&entries)?),
+        KeyReportFormat::Csv => print!("{}", key_report_csv(&entries)?),
+    }
+    Ok(())
 }
 
 fn run_app(terminal: &mut TerminalManager, app: &mut App) -> Result<()> {
+    let mut first_draw = Some(Instant::now());
     loop {
         terminal.terminal().draw(|f| render(f, app))?;
+        if let Some(started) = first_draw.take() {
+            app.startup.record("first draw", started.elapsed());
+        }
 
         if app.should_quit {
             break;
         }
 
-        let event = event::read()?;
-        app.handle_event(event, terminal)?;
+        // 定时唤醒以收取后台任务的结果
+        if event::poll(Duration::from_millis(200))? {
+            let event = event::read()?;
+            app.handle_event(event, terminal)?;
+        } else {
+            app.tick();
+        }
     }
 
     Ok(())
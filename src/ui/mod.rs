@@ -1,74 +1,538 @@
 use ratatui::{
-    layout::{ Constraint, Direction, Layout, Margin },
+    layout::{ Alignment, Constraint, Direction, Layout, Margin },
     style::{ Color, Modifier, Style },
     text::{ Line, Span },
-    widgets::{ Block, Borders, List, ListItem, Paragraph },
+    widgets::{ block::{ Position, Title }, Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState },
     Frame,
 };
 
-use crate::core::{ App, AppMode };
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
 
-pub fn render(f: &mut Frame, app: &App) {
+use crate::config::{ Theme, compare_hosts, format_size, format_utc, check_key_permissions, identity_file_warning, environment_badge, identity_files, inherited_options, inherited_value, InheritedOption, ConfigGuard, expand_tokens, FormSaveKey, host_name_problem, row_style, serialize_host, sshc_dir, LintSeverity, SshHost, SshcDir, TokenContext, TokenSegment };
+use crate::core::{ summarize_changes, SummaryKind, classify_failure, connect_command, display_command, uses_ssh, dns_name, max_review_scroll, ROW_STYLE_FIELD, expand_tilde, format_duration, App, DnsStatus, AppMode, is_reference_heading, ConflictResolution, PossibleDuplicate, DescriptionOutcome, FolderSuggestion, GuardAction, GUARD_DELAY, ImportStage, DeriveTemplate, OnboardingStep, StepOutcome, ONBOARDING_FIELDS, MergeChoice, ProbeStatus, QuickFilter, ViewPrompt, Selector, CONFIRM_NO, CONFIRM_YES, LOCK_READ_ONLY, LOCK_STEAL, StatusLevel, TagCheck, TransferKind, TreeItem, CONFIG_FILE_FIELD, FOLDER_FIELD, STRICT_HOST_KEY_FIELD, VISIBLE_FIELD };
+
+pub fn render(f: &mut Frame, app: &mut App) {
     match app.mode {
         AppMode::EditingHost => render_edit_form(f, app),
         AppMode::ConfirmDelete => render_delete_confirm(f, app),
         AppMode::ConfirmDiscardEdit => render_discard_edit_confirm(f, app),
         AppMode::ReviewChanges => render_changes_review(f, app),
         AppMode::ShowVersion => render_version_info(f, app),
+        AppMode::Reference => render_reference(f, app),
+        AppMode::ShowConnectionError => render_connection_error(f, app),
+        AppMode::ConfirmGroupConnect => render_group_connect_confirm(f, app),
+        AppMode::ConfirmConnect => render_connect_confirm(f, app),
+        AppMode::ShowMessage => render_message(f, app),
+        AppMode::ExportHosts => render_export(f, app),
+        AppMode::ImportSnippet => render_import(f, app),
+        AppMode::ConfirmDeleteDependents => render_delete_dependents(f, app),
+        AppMode::LintView => render_lint(f, app),
+        AppMode::ShowEffectiveConfig => render_effective_config(f, app),
+        AppMode::CompareHosts => render_host_comparison(f, app),
+        AppMode::WhatMatches => render_destination_query(f, app),
+        AppMode::InventorySync => render_inventory_sync(f, app),
+        AppMode::BulkIdentity => render_bulk_identity(f, app),
+        AppMode::RotateKey => render_key_rotation(f, app),
+        AppMode::ConfirmFixPermissions => render_fix_permissions_confirm(f, app),
+        AppMode::ConfirmReviewPending => render_review_pending_confirm(f, app),
+        AppMode::Dashboard => render_dashboard(f, app),
+        AppMode::ConfirmGuard => render_guard_confirm(f, app),
+        AppMode::ConfigLocked => render_config_locked(f, app),
+        AppMode::ConfirmReload => render_reload_confirm(f, app),
+        AppMode::QueueNext => render_queue_next(f, app),
+        AppMode::DeriveHost => render_derive(f, app),
+        AppMode::Onboarding => render_onboarding(f, app),
+        AppMode::ConfirmCopySystem => render_copy_system_confirm(f, app),
+        AppMode::HostDiff => {
+            // 弹窗下方保留列表，能看到是哪台主机
+            render_main_view(f, app);
+            render_host_diff(f, app);
+        }
+        AppMode::Backups => render_backups(f, app),
+        AppMode::ActionMenu => {
+            // 菜单下方保留列表，能看到操作的是哪台主机
+            render_main_view(f, app);
+            render_action_menu(f, app);
+        }
+        AppMode::CommandPalette => {
+            render_main_view(f, app);
+            render_command_palette(f, app);
+        }
+        AppMode::EditTags => render_tag_editor(f, app),
+        AppMode::Transfer => render_transfer(f, app),
+        AppMode::SwitchUser => render_user_switch(f, app),
+        AppMode::ResolveConflicts => render_merge_conflicts(f, app),
+        AppMode::FilterMenu => {
+            // 菜单下方保留列表，切换过滤条件时能直接看到结果
+            render_main_view(f, app);
+            render_filter_menu(f, app);
+        }
+        AppMode::Views => {
+            render_main_view(f, app);
+            render_views(f, app);
+        }
         _ => render_main_view(f, app),
     }
 }
 
-fn render_main_view(f: &mut Frame, app: &App) {
+/// 搜索框占用的行数：简洁模式下没有搜索词时不显示，搜索中或有搜索词时只占一行。
+/// 配置在磁盘上被修改或超出解析限制时总是显示完整的顶栏，以便看到提示
+pub fn search_bar_height(app: &App) -> u16 {
+    if !app.zen || app.changed_on_disk.is_some() || !app.limit_report.is_empty() || app.ssh_binary.is_missing() {
+        3
+    } else if app.mode == AppMode::Search || !app.search_query.is_empty() {
+        1
+    } else {
+        0
+    }
+}
+
+fn render_main_view(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .constraints([Constraint::Length(search_bar_height(app)), Constraint::Min(0), Constraint::Length(1)].as_ref())
         .split(f.size());
 
-    render_search_box(f, app, chunks[0]);
-    render_host_list(f, app, chunks[1]);
-    render_help_text(f, app, chunks[1]);
+    match search_bar_height(app) {
+        3 => render_search_box(f, app, chunks[0]),
+        1 => render_inline_search(f, app, chunks[0]),
+        _ => {}
+    }
+
+    // 终端足够宽时在右侧显示选中主机的详情
+    if chunks[1].width >= 100 {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(chunks[1]);
+        render_host_list(f, app, columns[0]);
+        render_detail_pane(f, app, columns[1]);
+    } else {
+        render_host_list(f, app, chunks[1]);
+    }
+    render_help_text(f, app, chunks[2]);
+}
+
+fn render_detail_pane(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = &app.theme;
+    let host = app.list_state
+        .selected()
+        .and_then(|selected| app.tree_items.get(selected))
+        .and_then(|item| match item {
+            TreeItem::Host { host_index } => app.hosts.get(*host_index),
+            TreeItem::Folder { .. } => None,
+        });
+
+    let lines = match host {
+        Some(host) => {
+            let inherited = inherited_options(&app.hosts, host);
+            let mut lines = host_detail_lines(theme, host, dns_name(host).and_then(|name| app.dns_cache.get(name)), &inherited);
+            // 进行中的后台任务及已用时间，避免把旧结果当作最新
+            let running: Vec<String> = app.tasks
+                .for_host(&host.name)
+                .map(|task| format!("{} {}", task.kind.label(), format_duration(task.started.elapsed())))
+                .collect();
+            if !running.is_empty() {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<14}", "Running"), theme.accent),
+                    Span::styled(format!("{} {} (Esc cancels)", app.tasks.spinner(), running.join(", ")), theme.muted),
+                ]));
+            }
+            if (host.system || host.locked) && let Some(source) = &host.source {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<14}", "Read-only"), theme.accent),
+                    Span::styled(format!("from {}", app.config_file_label(Some(source))), theme.muted),
+                ]));
+            }
+            // 非 ssh 的连接器显示渲染后的完整命令，模板缺少值时显示原因
+            if let Some(connector) = host.connector.as_ref().filter(|_| !uses_ssh(host)) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<14}", "Connector"), theme.accent),
+                    Span::raw(connector.clone()),
+                ]));
+                let command = match connect_command(host, None, &app.settings.connectors) {
+                    Ok(command) => Span::raw(display_command(&command)),
+                    Err(e) => Span::styled(e, theme.error),
+                };
+                lines.push(Line::from(vec![Span::styled(format!("{:<14}", "Command"), theme.accent), command]));
+            }
+            if let Some(failure) = app.last_failure(&host.name) {
+                let exit = failure.exit_code.map_or("signal".to_string(), |code| format!("exit {}", code));
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+                let age = format_duration(Duration::from_secs(now.saturating_sub(failure.at)));
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<14}", "Last error"), theme.accent),
+                    Span::styled(format!("{} ({}), {} ago", failure.reason, exit, age), theme.error),
+                ]));
+            }
+            lines
+        }
+        None => vec![Line::from(Span::styled("No host selected", theme.muted))],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Details"))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+/// 主机自己的字段和选项；没有写而由通配块或全局选项提供的值灰显并注明来源
+fn host_detail_lines(theme: &Theme, host: &SshHost, dns: Option<&DnsStatus>, inherited: &[InheritedOption]) -> Vec<Line<'static>> {
+    let label = theme.accent;
+    let field = |name: &str, value: Option<&String>| {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", name), label),
+            Span::raw(value.cloned().unwrap_or_else(|| "-".to_string())),
+        ])
+    };
+    let inherited_field = |name: &str, option: &InheritedOption| {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", name), label),
+            Span::styled(format!("{}  (inherited from {})", option.value, option.from), theme.faint),
+        ])
+    };
+    let own_or_inherited = |name: &str, value: Option<&String>| match (value, inherited_value(inherited, name)) {
+        (None, Some(option)) => inherited_field(name, option),
+        _ => field(name, value),
+    };
+    const SHOWN_ABOVE: [&str; 4] = ["hostname", "user", "port", "identityfile"];
+
+    let mut lines = vec![field("Host", Some(&host.name))];
+    match dns_name(host).filter(|_| inherited_value(inherited, "hostname").is_none()) {
+        // 没有 HostName 并不是配置错误，ssh 会直接解析别名
+        Some(name) => {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<14}", "HostName"), label),
+                Span::styled(format!("resolves via DNS as {}", name), theme.muted),
+            ]));
+            let address = match dns {
+                Some(DnsStatus::Resolved(ips)) => Span::raw(ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ")),
+                Some(DnsStatus::Unresolved) => Span::styled("unresolved", theme.warning),
+                Some(DnsStatus::Cancelled) => Span::styled("lookup cancelled", theme.muted),
+                Some(DnsStatus::Resolving) | None => Span::styled("resolving...", theme.muted),
+            };
+            lines.push(Line::from(vec![Span::styled(format!("{:<14}", "Address"), label), address]));
+        }
+        None => {
+            lines.push(own_or_inherited("HostName", host.hostname.as_ref()));
+            // 含 %h 等 token 时同时显示展开后的地址
+            if let Some(resolved) = host.resolved_hostname().filter(|resolved| Some(resolved) != host.hostname.as_ref()) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<14}", "Address"), label),
+                    Span::styled(resolved, theme.muted),
+                ]));
+            }
+        }
+    }
+    lines.extend([
+        own_or_inherited("User", host.user.as_ref()),
+        own_or_inherited("Port", host.port.as_ref()),
+        own_or_inherited("IdentityFile", host.identity_file.as_ref()),
+    ]);
+    if let Some(identity_file) = &host.identity_file {
+        let path = expand_tilde(identity_file);
+        if let Some(issue) = check_key_permissions(std::path::Path::new(&path)) {
+            lines.push(Line::from(Span::styled(
+                format!("{:<14}key is accessible by other users ({:04o})", "", issue.mode),
+                theme.error
+            )));
+        }
+        // 只看第一行判断类型，不显示任何密钥内容
+        if let Some(warning) = identity_file_warning(identity_file, std::path::Path::new(&path)) {
+            lines.push(Line::from(Span::styled(format!("{:<14}{}", "", warning), theme.warning)));
+        }
+    }
+    if let Some(folder) = &host.folder {
+        lines.push(field("Folder", Some(folder)));
+    }
+    if let Some(description) = &host.description {
+        lines.push(field("Description", Some(description)));
+    }
+    if !host.tags.is_empty() {
+        lines.push(field("Tags", Some(&host.tags.join(", "))));
+    }
+    if !host.aliases.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<14}", "Aliases"), label),
+            Span::styled(host.aliases.join(", "), theme.faint),
+        ]));
+    }
+
+    if !host.env.is_empty() || !host.invalid_env.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Environment", label)));
+        for (key, value) in &host.env {
+            lines.push(Line::from(format!("  {}={}", key, value)));
+        }
+        for entry in &host.invalid_env {
+            lines.push(Line::from(Span::styled(format!("  {} (ignored)", entry), theme.warning)));
+        }
+    }
+
+    let mut options: Vec<(&String, &String)> = host.other_options
+        .iter()
+        .filter(|(key, _)| key.as_str() != "proxycommand")
+        .collect();
+    options.sort();
+    if !options.is_empty() {
+        lines.push(Line::from(""));
+        for (key, value) in options {
+            lines.push(field(key, Some(value)));
+        }
+    }
+
+    let other_inherited: Vec<&InheritedOption> = inherited
+        .iter()
+        .filter(|option| !SHOWN_ABOVE.contains(&option.key.to_lowercase().as_str()))
+        .collect();
+    if !other_inherited.is_empty() {
+        lines.push(Line::from(""));
+        for option in other_inherited {
+            lines.push(inherited_field(&option.key, option));
+        }
+    }
+
+    if let Some(proxy) = proxy_command_line(theme, host) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("ProxyCommand", label)));
+        lines.push(Line::from(host.other_options["proxycommand"].clone()));
+        lines.push(proxy);
+    }
+
+    lines
+}
+
+/// 展开 ProxyCommand 中的 token，未知 token 用警告色原样显示
+fn proxy_command_line(theme: &Theme, host: &SshHost) -> Option<Line<'static>> {
+    let template = host.other_options.get("proxycommand")?;
+    let context = TokenContext::for_host(host);
+    let mut spans = vec![Span::styled("→ ", theme.muted)];
+    spans.extend(
+        expand_tokens(template, &context)
+            .into_iter()
+            .map(|segment| match segment {
+                TokenSegment::Text(text) => Span::raw(text),
+                TokenSegment::Unknown(token) => Span::styled(token, theme.warning),
+            })
+    );
+    Some(Line::from(spans))
+}
+
+/// 简洁模式下的单行搜索提示
+fn render_inline_search(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = &app.theme;
+    let cursor = if app.mode == AppMode::Search { "|" } else { "" };
+    let line = Line::from(vec![
+        Span::styled("/", theme.accent),
+        Span::raw(format!("{}{}", app.search_query, cursor)),
+    ]);
+    f.render_widget(Paragraph::new(line), area);
 }
 
 fn render_search_box(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = &app.theme;
     let search_text = match app.mode {
         AppMode::Search => format!("Search: {}|", app.search_query),
         AppMode::Normal => format!("Search: {} (Press / to search)", app.search_query),
-        AppMode::ConfigManagement => {
-            if !app.pending_changes.is_empty() {
-                format!("Config Management Mode - {} pending changes", app.pending_changes.len())
-            } else {
-                "Config Management Mode".to_string()
-            }
-        }
+        AppMode::ConfigManagement => "Config Management Mode".to_string(),
         _ => "SSH Host Selector".to_string(),
     };
 
-    let search_paragraph = Paragraph::new(search_text).block(
-        Block::default().borders(Borders::ALL).title("SSH Host Selector")
-    );
+    let mut block = Block::default().borders(Borders::ALL).title("SSH Host Selector");
+    if !app.connect_queue.is_empty() {
+        block = block.title(Span::styled(format!(" Queue: {} ", app.connect_queue.len()), theme.accent));
+    }
+    if let Some(banner) = app.limit_report.banner() {
+        block = block.title(Span::styled(
+            format!(" {} ", banner),
+            theme.input.add_modifier(Modifier::BOLD)
+        ));
+    }
+    if let Some(progress) = app.multiplexer.progress() {
+        block = block.title(Span::styled(format!(" {} ", progress), theme.notice));
+    }
+    if app.ssh_binary.is_missing() {
+        block = block.title(Span::styled(
+            " ⚠ ssh binary not found — connections disabled, config editing still works (F5: check again) ",
+            theme.alert
+        ));
+    }
+    if app.changed_on_disk.is_some() {
+        block = block.title(Span::styled(
+            " ⚠ config changed on disk (r: reload, O: overwrite on save) ",
+            theme.alert
+        ));
+    }
+    if let Some(indicator) = pending_changes_indicator(app) {
+        block = block.title(
+            Title::from(Span::styled(format!(" {} ", indicator), theme.warning.add_modifier(Modifier::BOLD)))
+                .alignment(Alignment::Right)
+        );
+    }
 
+    let search_paragraph = Paragraph::new(search_text).block(block);
     f.render_widget(search_paragraph, area);
 }
 
-fn render_host_list(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+/// 有未保存变更时在顶栏显示的提示，附带当前模式下进入审阅的按键
+pub fn pending_changes_indicator(app: &App) -> Option<String> {
+    let count = app.pending_changes.len();
+    if count == 0 {
+        return None;
+    }
+    let hint = match app.mode {
+        AppMode::Normal => " (press e to review)",
+        AppMode::ConfigManagement => " (press q to review)",
+        _ => "",
+    };
+    Some(format!("● {}{}", unsaved_changes(count), hint))
+}
+
+/// "1 unsaved change" / "3 unsaved changes"
+fn unsaved_changes(count: usize) -> String {
+    let noun = if count == 1 { "change" } else { "changes" };
+    format!("{} unsaved {}", count, noun)
+}
+
+fn render_host_list(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme;
+    // 空状态：说明配置文件位置或尝试过的路径
+    if app.tree_items.is_empty() && app.search_query.is_empty() && !app.startup_notice.is_empty() {
+        let lines: Vec<Line> = app.startup_notice
+            .iter()
+            .map(|line| Line::from(Span::styled(line.as_str(), theme.muted)))
+            .collect();
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("SSH Hosts"))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    // 先确定可见窗口，数字序号要跟随滚动
+    app.list_height = area.height.saturating_sub(2) as usize;
+    let (offset, _) = App::list_window(
+        app.tree_items.len(),
+        app.list_state.selected(),
+        app.list_state.offset(),
+        app.list_height
+    );
+    *app.list_state.offset_mut() = offset;
+
+    // 行内容可用宽度：去掉边框和高亮符号
+    let row_width = area.width.saturating_sub(2 + 3) as usize;
+    let quick_targets = if app.settings.quick_connect_digits {
+        app.quick_connect_targets()
+    } else {
+        Vec::new()
+    };
+    let app = &*app;
+
     let items: Vec<ListItem> = app.tree_items
         .iter()
-        .map(|tree_item| {
+        .enumerate()
+        .map(|(tree_index, tree_item)| {
             match tree_item {
-                crate::core::TreeItem::Folder { name, expanded, .. } => {
+                crate::core::TreeItem::Folder { name, expanded, children_indices } => {
                     let icon = if *expanded { "[-]" } else { "[+]" };
-                    let folder_text = format!("{} {}", icon, name);
+                    let folder_text = if app.folder_all_hidden(children_indices) {
+                        format!("{} {} (hidden)", icon, name)
+                    } else {
+                        format!("{} {}", icon, name)
+                    };
                     ListItem::new(Line::from(vec![
-                        Span::styled(folder_text, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                        Span::styled(folder_text, theme.folder)
                     ]))
                 },
                 crate::core::TreeItem::Host { host_index } => {
                     if let Some(host) = app.hosts.get(*host_index) {
                         let indent = if host.folder.is_some() { "  " } else { "" };
-                        let display_text = format!("{}{}", indent, host.get_full_display_info());
-                        ListItem::new(Line::from(vec![Span::raw(display_text)]))
+                        let quick_index = match quick_targets.iter().position(|&i| i == tree_index) {
+                            Some(slot) => format!("{} ", slot + 1),
+                            None if app.settings.quick_connect_digits => "  ".to_string(),
+                            None => String::new(),
+                        };
+                        let queued = app.connect_queue
+                            .iter()
+                            .position(|name| *name == host.name)
+                            .map(|position| format!(" [queued #{}]", position + 1))
+                            .unwrap_or_default();
+                        let hidden = if host.visible { "" } else { " (hidden)" };
+                        let disabled = if host.disabled { " (disabled)" } else { "" };
+                        let temporary = if host.temporary { " (temp)" } else { "" };
+                        let system = if host.system { " (system)" } else if host.locked { " 🔒" } else { "" };
+                        // 关闭了主机密钥校验的主机显示一个盾牌作为提醒
+                        let shield = if host.host_key_checking_disabled() { " 🛡" } else { "" };
+                        // 最近一次连接失败的主机，成功连接后消失
+                        let failed = if app.last_failure(&host.name).is_some() { " !" } else { "" };
+                        let on_disk = if app.disk_changed_hosts.contains(&host.name) { " (changed on disk)" } else { "" };
+                        // 已有主连接的主机显示闪电，预热中显示省略号
+                        let socket = if app.multiplexer.is_live(&host.name) {
+                            " ⚡"
+                        } else if app.multiplexer.is_pending(&host.name) {
+                            " ⚡…"
+                        } else {
+                            ""
+                        };
+                        // 有后台任务（ssh -G、DNS）进行中时显示转动的标记
+                        let pending = if app.tasks.is_pending(&host.name) { format!(" {}", app.tasks.spinner()) } else { String::new() };
+                        let marked = if app.mode != AppMode::Normal && app.marked_hosts.contains(&host.name) { "[x] " } else { "" };
+                        let compare = if app.compare_mark.as_ref() == Some(&host.name) { " [=]" } else { "" };
+                        // 缩进、序号和标记不参与截断
+                        let fixed = indent.len() + quick_index.chars().count() + marked.len() + hidden.len() + disabled.len() + temporary.len() + system.len() + shield.chars().count() + failed.len() + on_disk.len() + queued.chars().count() + socket.chars().count() + pending.chars().count() + compare.len();
+                        let environment = environment_badge(host, &app.settings.environments);
+                        let (badge, row) = row_with_badge(host, environment.map(|e| e.badge.as_str()), row_width.saturating_sub(fixed));
+                        let mut spans = vec![Span::raw(format!("{}{}{}", indent, quick_index, marked))];
+                        if let Some(badge) = badge && let Some(environment) = environment {
+                            spans.push(badge_span(&theme, &badge, &environment.color));
+                            spans.push(Span::raw(" "));
+                        }
+                        let style = row_style(host);
+                        if host.visible && !host.disabled && !style.is_plain() {
+                            // 选中行的底色是亮绿色，彩色文字改为黑色以保证可读，暗色行保留斜体
+                            let selected = app.list_state.selected() == Some(tree_index);
+                            let mut row_style = Style::default();
+                            if let Some(color) = style.color.filter(|_| !selected) {
+                                row_style = row_style.patch(theme.custom(color));
+                            }
+                            if style.dim {
+                                row_style = row_style.add_modifier(Modifier::DIM | Modifier::ITALIC);
+                            }
+                            spans.push(Span::styled(row, row_style));
+                        } else if host.visible && !host.disabled {
+                            spans.push(Span::raw(row));
+                        } else {
+                            spans.push(Span::styled(format!("{}{}{}", row, hidden, disabled), theme.faint));
+                        }
+                        if !temporary.is_empty() {
+                            spans.push(Span::styled(temporary, theme.special));
+                        }
+                        if !system.is_empty() {
+                            spans.push(Span::styled(system, theme.faint));
+                        }
+                        if !shield.is_empty() {
+                            spans.push(Span::styled(shield, theme.warning));
+                        }
+                        if !failed.is_empty() {
+                            spans.push(Span::styled(failed, theme.error.add_modifier(Modifier::BOLD)));
+                        }
+                        if !on_disk.is_empty() {
+                            spans.push(Span::styled(on_disk, theme.error));
+                        }
+                        if !socket.is_empty() {
+                            spans.push(Span::styled(socket, theme.notice));
+                        }
+                        if !compare.is_empty() {
+                            spans.push(Span::styled(compare, theme.accent));
+                        }
+                        if !pending.is_empty() {
+                            spans.push(Span::styled(pending, theme.muted));
+                        }
+                        if !queued.is_empty() {
+                            spans.push(Span::styled(queued, theme.accent));
+                        }
+                        ListItem::new(Line::from(spans))
                     } else {
                         ListItem::new(Line::from(vec![Span::raw("Invalid host")]))
                     }
@@ -77,51 +541,190 @@ fn render_host_list(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         })
         .collect();
 
-    let title = if app.search_query.is_empty() {
-        "SSH Hosts (Enter/Space: Connect/Toggle folder, e: Edit)"
+    let mut title = if app.search_query.is_empty() {
+        "SSH Hosts (Enter/Space: Connect/Toggle folder, e: Edit)".to_string()
     } else {
-        "Search Results"
+        "Search Results".to_string()
     };
+    if !app.quick_filters.is_empty() {
+        let labels: Vec<&str> = app.quick_filters.iter().map(|filter| filter.label()).collect();
+        title = format!("Filtered: {} ({} hosts, \\: Change)", labels.join(" + "), app.filtered_hosts.len());
+    }
 
+    // 右下角的位置提示只数主机，随选中项和搜索结果更新
+    let position = Title::from(Span::styled(format!(" {} ", app.list_position().label()), theme.muted))
+        .position(Position::Bottom)
+        .alignment(Alignment::Right);
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(title))
-        .highlight_style(Style::default().bg(Color::LightGreen).add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL).title(title).title(position))
+        .highlight_style(theme.selected)
         .highlight_symbol(">> ");
 
     f.render_stateful_widget(list, area, &mut app.list_state.clone());
+
+    // 行数超过可见高度时，在右边框上画出滚动条，位置取自 ListState 的实际偏移
+    let rows = app.tree_items.len();
+    if rows > app.list_height {
+        let mut state = list_scrollbar_state(rows, app.list_state.offset(), app.list_height);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some("│"))
+            .thumb_symbol("┃")
+            .thumb_style(theme.muted);
+        f.render_stateful_widget(scrollbar, area.inner(&Margin { vertical: 1, horizontal: 0 }), &mut state);
+    }
+}
+
+/// ratatui 0.24 的滑块位置按 position / content_length 计算：以最大偏移作为内容长度，
+/// 滚到底时滑块正好贴底，再按比例换算可见长度使滑块大小为 height / rows
+fn list_scrollbar_state(rows: usize, offset: usize, height: usize) -> ScrollbarState {
+    let max_offset = rows.saturating_sub(height).max(1);
+    let viewport = (height * max_offset).div_ceil(rows.max(1)).max(1);
+    ScrollbarState::new(max_offset).position(offset.min(max_offset)).viewport_content_length(viewport)
+}
+
+/// 带环境徽章的主机行：宽度不够完整显示名称时先去掉徽章
+pub fn row_with_badge(host: &SshHost, badge: Option<&str>, width: usize) -> (Option<String>, String) {
+    if let Some(badge) = badge {
+        let badge_len = badge.chars().count() + 1;
+        if width >= badge_len + host.get_display_name().chars().count() {
+            return (Some(badge.to_string()), formatted_row(host, width - badge_len));
+        }
+    }
+    (None, formatted_row(host, width))
+}
+
+/// 环境徽章：彩色底的短标签，颜色无法识别时用灰色
+fn badge_span(theme: &Theme, badge: &str, color: &str) -> Span<'static> {
+    let color = color.parse::<Color>().unwrap_or(Color::Gray);
+    Span::styled(badge.to_string(), theme.badge(color))
+}
+
+/// 按可用宽度格式化主机行：优先保留名称和 user@host，其次端口，描述最先被截断
+pub fn formatted_row(host: &SshHost, width: usize) -> String {
+    let full = host.get_full_display_info();
+    if full.chars().count() <= width {
+        return full;
+    }
+
+    let name = host.get_display_name();
+    let target = match (&host.user, host.resolved_hostname()) {
+        (Some(user), Some(hostname)) => Some(format!("{}@{}", user, hostname)),
+        (Some(user), None) => Some(format!("user:{}", user)),
+        (None, Some(hostname)) => Some(hostname),
+        (None, None) => None,
+    };
+    let port = host.port.as_ref().map(|port| format!("port:{}", port));
+    let info: Vec<&str> = target.iter().chain(port.iter()).map(String::as_str).collect();
+    let base = if info.is_empty() { name.clone() } else { format!("{} ({})", name, info.join(" ")) };
+
+    // 放得下基本信息时，描述用剩余宽度截断
+    let base_len = base.chars().count();
+    if base_len <= width {
+        return match &host.description {
+            Some(description) if width >= base_len + " # ".len() + 2 => {
+                format!("{} # {}", base, truncate(description, width - base_len - " # ".len()))
+            }
+            _ => base,
+        };
+    }
+
+    // 放不下时先去掉端口，再截断目标地址，最后截断名称
+    let name_len = name.chars().count();
+    if let Some(target) = &target {
+        let room = width.saturating_sub(name_len + " ()".len());
+        if room >= 2 {
+            return format!("{} ({})", name, truncate(target, room));
+        }
+    }
+    truncate(&name, width)
+}
+
+/// 页脚右侧的上下文提示：生效中的过滤条件等
+pub fn footer_context(app: &App) -> Vec<String> {
+    let mut context = Vec::new();
+    if app.mode != AppMode::Search && !app.search_query.is_empty() {
+        context.push(format!("filter: /{}", app.search_query));
+    }
+    if app.show_hidden {
+        context.push("hidden hosts shown".to_string());
+    }
+    if !app.quick_filters.is_empty() {
+        context.push(format!("quick filters: {}", app.quick_filters.len()));
+    }
+    if !app.marked_hosts.is_empty() && app.mode != AppMode::Normal {
+        context.push(format!("marked: {}", app.marked_hosts.len()));
+    }
+    if app.zen && let Some(indicator) = pending_changes_indicator(app) {
+        // 搜索框隐藏时未保存变更的提示移到页脚
+        context.push(indicator);
+    }
+    if app.read_only {
+        context.push("read-only".to_string());
+    }
+    context
 }
 
+/// 页脚独占主界面最下方一行。左侧为帮助文字，有状态提示时由状态提示替代；
+/// 右侧为上下文提示，空间不足时让位于左侧内容
 fn render_help_text(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = &app.theme;
     let help_text = match app.mode {
         AppMode::Search => "ESC: Exit search | Enter/Space: Select and connect",
-        AppMode::Normal => "↑↓: Select | Enter/Space: Connect/Toggle folder | /: Search | e: Edit config | v: Version | q: Quit",
+        AppMode::Normal => "↑↓: Select | Enter/Space: Connect/Toggle folder | C: Connect folder | Y: Export | L: Lint | }/{: Next/prev finding | g: ssh -G | =: Compare | Ctrl-W: What matches | !: Health | t: scp/sftp | u/U: Connect as user / as last one | k: Keys | w/W: Pre-warm/close master | Del: Delete | ./o: Actions | :/Ctrl-P: Commands | ←→: Folder | F: Skip folders | +: Queue | \\: Filter | ': Views | /: Search | Ctrl-N: Temp host | e: Edit config | F5: Reload | H: Show hidden | Ctrl-Z: Zen | v: Version | q: Quit",
         AppMode::ConfigManagement =>
-            "a: Add host | e: Edit host | d: Delete host | q: Save & exit | ESC: Back",
+            "a: Add host | e: Edit host | d: Delete host | D: Unsaved changes | h: Hide/show folder | I: Folder identity file | K: Rotate key | H: Show hidden | \\: Filter | ': Views | Y: Export | i: Import | V: From clipboard | Ctrl-N: Temp host | P: Keep temp host | x: Disable/enable host | Space: Mark | t: Tag marked | T: Derive tunnel | W: New server setup | ./o: Actions | :/Ctrl-P: Commands | L: Lint | ]/[: Next/prev change | }/{: Next/prev finding | U: Sync inventory | B: Backups | =: Compare | Ctrl-W: What matches | F5: Reload | q: Save & exit | ESC: Back",
         _ => "",
     };
 
-    let help_paragraph = Paragraph::new(help_text).style(Style::default().fg(Color::Gray));
-
-    let help_area = area.inner(
-        &(Margin {
-            vertical: 0,
-            horizontal: 1,
-        })
-    );
-
-    let help_y = help_area.bottom().saturating_sub(1);
-    let help_rect = ratatui::layout::Rect {
-        x: help_area.x,
-        y: help_y,
-        width: help_area.width,
-        height: 1,
+    let area = area.inner(&Margin { vertical: 0, horizontal: 1 });
+    let (left, left_style) = match &app.status {
+        Some(status) => {
+            let style = match status.level {
+                StatusLevel::Info => theme.accent,
+                StatusLevel::Warning => theme.warning,
+                StatusLevel::Error => theme.error,
+            };
+            // 未绑定按键的提示先短暂反色显示
+            let style = if app.footer_flashing() { style.add_modifier(Modifier::REVERSED) } else { style };
+            (status.text.as_str(), style)
+        }
+        None => (help_text, theme.muted),
     };
 
-    f.render_widget(help_paragraph, help_rect);
+    let context = footer_context(app).join(" | ");
+    let context_width = context.chars().count() as u16;
+    // 状态提示优先：右侧提示放不下时省略
+    let too_narrow = area.width < context_width + 20;
+    let status_needs_room = app.status.is_some() && (left.chars().count() as u16) + context_width + 3 > area.width;
+    let reserved = if context.is_empty() || too_narrow || status_needs_room { 0 } else { context_width + 3 };
+
+    let left_rect = ratatui::layout::Rect { width: area.width - reserved, ..area };
+    f.render_widget(Paragraph::new(left).style(left_style), left_rect);
+    if reserved > 0 {
+        let right_rect = ratatui::layout::Rect { x: area.right() - context_width, width: context_width, ..area };
+        f.render_widget(Paragraph::new(context).style(theme.special), right_rect);
+    }
 }
 
+/// 编辑表单旁显示配置预览所需的最小终端宽度，以及预览的宽度
+const FORM_PREVIEW_MIN_WIDTH: u16 = 130;
+const FORM_PREVIEW_WIDTH: u16 = 46;
+
 fn render_edit_form(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
     if let Some(editing_data) = &app.editing_host {
+        // 终端足够宽时在右侧预览保存后写入的配置块，窄时隐藏
+        let (form_area, preview_area) = if f.size().width >= FORM_PREVIEW_MIN_WIDTH {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(FORM_PREVIEW_WIDTH)].as_ref())
+                .split(f.size());
+            (columns[0], Some(columns[1]))
+        } else {
+            (f.size(), None)
+        };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
@@ -135,90 +738,357 @@ fn render_edit_form(f: &mut Frame, app: &App) {
                 Constraint::Length(3), // Folder
                 Constraint::Length(3), // Display Name
                 Constraint::Length(3), // Description
-                Constraint::Length(3), // Visible
+                Constraint::Length(3), // Aliases / Visible
+                Constraint::Length(3), // Keepalive
+                Constraint::Length(3), // Trust settings
                 Constraint::Min(1), // Help
             ])
-            .split(f.size());
+            .split(form_area);
+
+        // 已修改的字段标题用颜色标出
+        let field_title = |label: &str, field: usize| {
+            if editing_data.is_modified(field) {
+                Span::styled(format!("{} (modified)", label), theme.special.add_modifier(Modifier::BOLD))
+            } else {
+                Span::raw(label.to_string())
+            }
+        };
 
-        let title = if app.editing_host_index.is_some() { "Edit Host" } else { "Add New Host" };
+        let title = match (app.editing_host_index, app.adding_temporary) {
+            (Some(_), _) => "Edit Host",
+            (None, Some(_)) => "New Temporary Host (this session only)",
+            (None, None) => "Add New Host",
+        };
         let title_paragraph = Paragraph::new(title).block(Block::default().borders(Borders::ALL));
-        f.render_widget(title_paragraph, chunks[0]);
+
+        // 标题行右侧选择写入的配置文件；留空时按文件夹规则自动选择
+        let title_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+            .split(chunks[0]);
+        let config_file = match editing_data.current.config_file.as_str() {
+            "" => format!("◂ auto: {} ▸", app.config_file_label(app.auto_config_file(&editing_data.current.folder).as_deref())),
+            value => format!("◂ {} ▸", value),
+        };
+        let config_file_style = if editing_data.current_field == CONFIG_FILE_FIELD {
+            theme.input
+        } else {
+            Style::default()
+        };
+        let config_file_paragraph = Paragraph::new(config_file)
+            .style(config_file_style)
+            .block(Block::default().borders(Borders::ALL).title(field_title("Config file", CONFIG_FILE_FIELD)));
+        f.render_widget(title_paragraph, title_chunks[0]);
+        f.render_widget(config_file_paragraph, title_chunks[1]);
 
         let fields = [
-            ("Name", editing_data.name.as_str(), 0),
-            ("Hostname", editing_data.hostname.as_str(), 1),
-            ("User", editing_data.user.as_str(), 2),
-            ("Port", editing_data.port.as_str(), 3),
-            ("Identity File", editing_data.identity_file.as_str(), 4),
-            ("Folder", editing_data.folder.as_str(), 5),
-            ("Display Name *", editing_data.display_name.as_str(), 6),
-            ("Description *", editing_data.description.as_str(), 7),
+            ("Name", editing_data.current.name.as_str(), 0),
+            ("Hostname", editing_data.current.hostname.as_str(), 1),
+            ("User", editing_data.current.user.as_str(), 2),
+            ("Port", editing_data.current.port.as_str(), 3),
+            ("Identity File", editing_data.current.identity_file.as_str(), 4),
+            ("Folder", editing_data.current.folder.as_str(), 5),
+            ("Display Name *", editing_data.current.display_name.as_str(), 6),
+            ("Description *", editing_data.current.description.as_str(), 7),
         ];
 
         for (i, (label, value, field_index)) in fields.iter().enumerate() {
             let style = if *field_index == editing_data.current_field {
-                Style::default().bg(Color::Yellow).fg(Color::Black)
+                theme.input
             } else {
                 Style::default()
             };
 
             let paragraph = Paragraph::new(*value)
                 .style(style)
-                .block(Block::default().borders(Borders::ALL).title(*label));
+                .block(Block::default().borders(Borders::ALL).title(field_title(label, *field_index)));
             f.render_widget(paragraph, chunks[i + 1]);
         }
 
         // 可见性字段特殊处理
-        let visible_style = if 8 == editing_data.current_field {
-            Style::default().bg(Color::Yellow).fg(Color::Black)
+        let visible_style = if VISIBLE_FIELD == editing_data.current_field {
+            theme.input
         } else {
             Style::default()
         };
-        let visible_text = if editing_data.visible { "Yes" } else { "No" };
+        let visible_text = if editing_data.current.visible { "Yes" } else { "No" };
         let visible_paragraph = Paragraph::new(visible_text)
             .style(visible_style)
-            .block(Block::default().borders(Borders::ALL).title("Visible on main page"));
-        f.render_widget(visible_paragraph, chunks[8]);
+            .block(Block::default().borders(Borders::ALL).title(field_title("Visible on main page", VISIBLE_FIELD)));
+
+        // 别名、可见性和行样式共用一行
+        let alias_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 4), Constraint::Ratio(1, 4)].as_ref())
+            .split(chunks[9]);
+        let aliases_style = if editing_data.current_field == 8 {
+            theme.input
+        } else {
+            Style::default()
+        };
+        let aliases_paragraph = Paragraph::new(editing_data.current.aliases.as_str())
+            .style(aliases_style)
+            .block(Block::default().borders(Borders::ALL).title(field_title("Search aliases * (comma separated)", 8)));
+        let row_style = match editing_data.current.row_style.as_str() {
+            "" => "◂ (default) ▸".to_string(),
+            value => format!("◂ {} ▸", value),
+        };
+        let row_style_style = if editing_data.current_field == ROW_STYLE_FIELD {
+            theme.input
+        } else {
+            Style::default()
+        };
+        let row_style_paragraph = Paragraph::new(row_style)
+            .style(row_style_style)
+            .block(Block::default().borders(Borders::ALL).title(field_title("Row style *", ROW_STYLE_FIELD)));
+        f.render_widget(aliases_paragraph, alias_chunks[0]);
+        f.render_widget(visible_paragraph, alias_chunks[1]);
+        f.render_widget(row_style_paragraph, alias_chunks[2]);
+
+        // 保活设置三个数值字段并排显示
+        let keepalive_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(1, 3), Constraint::Ratio(1, 3)].as_ref())
+            .split(chunks[10]);
+        let keepalive_fields = [
+            ("ServerAliveInterval *", editing_data.current.server_alive_interval.as_str(), 10),
+            ("ServerAliveCountMax *", editing_data.current.server_alive_count_max.as_str(), 11),
+            ("ConnectTimeout *", editing_data.current.connect_timeout.as_str(), 12),
+        ];
+        for (i, (label, value, field_index)) in keepalive_fields.iter().enumerate() {
+            let style = if *field_index == editing_data.current_field {
+                theme.input
+            } else {
+                Style::default()
+            };
+            let paragraph = Paragraph::new(*value)
+                .style(style)
+                .block(Block::default().borders(Borders::ALL).title(field_title(label, *field_index)));
+            f.render_widget(paragraph, keepalive_chunks[i]);
+        }
+
+        // 主机密钥信任设置：StrictHostKeyChecking 为下拉式取值
+        let trust_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(1, 3), Constraint::Ratio(1, 3)].as_ref())
+            .split(chunks[11]);
+        let strict = match editing_data.current.strict_host_key_checking.as_str() {
+            "" => "◂ (default) ▸".to_string(),
+            value => format!("◂ {} ▸", value),
+        };
+        let trust_fields = [
+            ("Trust: StrictHostKeyChecking *", strict.as_str(), STRICT_HOST_KEY_FIELD),
+            ("UserKnownHostsFile *", editing_data.current.user_known_hosts_file.as_str(), 14),
+            ("HostKeyAlias *", editing_data.current.host_key_alias.as_str(), 15),
+        ];
+        for (i, (label, value, field_index)) in trust_fields.iter().enumerate() {
+            let style = if *field_index == editing_data.current_field {
+                theme.input
+            } else {
+                Style::default()
+            };
+            let paragraph = Paragraph::new(*value)
+                .style(style)
+                .block(Block::default().borders(Borders::ALL).title(field_title(label, *field_index)));
+            f.render_widget(paragraph, trust_chunks[i]);
+        }
+
+        let help_paragraph = match &editing_data.error {
+            Some(error) => Paragraph::new(error.as_str()).style(theme.error),
+            None => {
+                let navigate = if app.settings.enter_saves_form { "Tab/↑↓: Navigate" } else { "Tab/Enter/↑↓: Navigate" };
+                // 保存键占用 F2 时保活预设为 F3/F4
+                let preset = if app.settings.form_save_key == FormSaveKey::F2 { 3 } else { 2 };
+                Paragraph::new(format!(
+                    "{} | {}: Save | ESC: Cancel | Ctrl-Z: Revert field | Space: Toggle visible / next value | F{}: Aggressive 15/3 | F{}: Relaxed 60/5 | *=Optional",
+                    navigate,
+                    app.form_save_label(),
+                    preset,
+                    preset + 1
+                )).style(theme.muted)
+            }
+        };
+        f.render_widget(help_paragraph, chunks[12]);
+
+        if let Some(area) = preview_area {
+            render_form_preview(f, app, area.inner(&Margin { vertical: 1, horizontal: 0 }));
+        }
+
+        // 文件夹补全列表覆盖在文件夹字段下方
+        let suggestions = app.folder_suggestions();
+        if !suggestions.is_empty() {
+            let folder_area = chunks[FOLDER_FIELD + 1];
+            let area = ratatui::layout::Rect {
+                x: folder_area.x + 2,
+                y: folder_area.bottom(),
+                width: folder_area.width.saturating_sub(4),
+                height: (suggestions.len() as u16 + 2).min(8).min(f.size().bottom().saturating_sub(folder_area.bottom())),
+            };
+            let items: Vec<ListItem> = suggestions
+                .iter()
+                .enumerate()
+                .map(|(i, suggestion)| {
+                    let mut style = match suggestion {
+                        FolderSuggestion::Existing(_) => Style::default(),
+                        FolderSuggestion::Create(_) => theme.success,
+                    };
+                    if let Some(picker) = &editing_data.folder_choice {
+                        style = selector_style(picker, i, style);
+                    }
+                    ListItem::new(suggestion.label()).style(style)
+                })
+                .collect();
+            let list = List::new(items).block(
+                Block::default().borders(Borders::ALL).title("Folders (↑↓/Tab: Select | Enter: Use | ESC: Back to typing)")
+            );
+            f.render_widget(ratatui::widgets::Clear, area);
+            f.render_widget(list, area);
+        }
+    }
+}
+
+/// 编辑表单旁的配置块预览，每次按键后按表单内容重新生成
+fn render_form_preview(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = &app.theme;
+    let Some(host) = app.host_from_form() else {
+        return;
+    };
+    let title = if host.temporary || app.adding_temporary.is_some() {
+        "Preview (temporary, not written)".to_string()
+    } else {
+        format!("Preview: {}", app.config_file_label(host.source.as_deref()))
+    };
+    let preview = app.form_preview().unwrap_or_default();
+    let lines: Vec<Line> = preview.lines().map(|line| config_line(theme, line)).collect();
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+}
 
-        let help_text = "Tab/↑↓: Navigate | Enter: Save | ESC: Cancel | Space: Toggle visible | *=Optional";
-        let help_paragraph = Paragraph::new(help_text).style(Style::default().fg(Color::Gray));
-        f.render_widget(help_paragraph, chunks[9]);
+/// 按 ssh 配置的语法给一行上色：注释、sshc 元数据注释、Host/Match 行和选项名分别区分
+fn config_line<'a>(theme: &Theme, line: &'a str) -> Line<'a> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if trimmed.starts_with("# @") {
+        return Line::from(Span::styled(line, theme.special));
+    }
+    if trimmed.starts_with('#') {
+        return Line::from(Span::styled(line, theme.faint));
     }
+    let key_end = trimmed.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(trimmed.len());
+    let (key, rest) = trimmed.split_at(key_end);
+    let key_style = if key.eq_ignore_ascii_case("Host") || key.eq_ignore_ascii_case("Match") {
+        theme.accent.add_modifier(Modifier::BOLD)
+    } else {
+        theme.info
+    };
+    Line::from(vec![Span::raw(indent), Span::styled(key, key_style), Span::raw(rest)])
+}
+
+/// 选择器弹窗共用的帮助行
+const CONFIRM_HELP: &str = "↑↓/Tab: Choose | Enter: Accept | y/n | ESC: Cancel";
+
+/// 选择器中选中项的样式：在弹窗自己的样式上反色
+fn selector_style(selector: &Selector, index: usize, style: Style) -> Style {
+    if index == selector.selected { style.add_modifier(Modifier::REVERSED) } else { style }
+}
+
+/// 确认框底部的是/否按钮
+fn confirm_buttons(theme: &Theme, selector: &Selector, yes: &str, no: &str) -> Line<'static> {
+    let button = |index: usize, label: &str| {
+        let style = if index == CONFIRM_YES { theme.error } else { theme.success };
+        Span::styled(format!("[ {} ]", label), selector_style(selector, index, style))
+    };
+    Line::from(vec![button(CONFIRM_YES, yes), Span::raw("  "), button(CONFIRM_NO, no)])
 }
 
 fn render_delete_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
     let area = centered_rect(50, 30, f.size());
     f.render_widget(ratatui::widgets::Clear, area);
 
-    if let Some(host_idx) = app.delete_target {
-        if let Some(host) = app.hosts.get(host_idx) {
-            let text = format!("Delete host '{}'?\n\nThis action cannot be undone.", host.name);
-            let paragraph = Paragraph::new(text)
-                .block(Block::default().borders(Borders::ALL).title("Confirm Delete"))
-                .wrap(ratatui::widgets::Wrap { trim: true });
-            f.render_widget(paragraph, area);
-
-            let help_area = ratatui::layout::Rect {
-                x: area.x + 1,
-                y: area.bottom() - 2,
-                width: area.width - 2,
-                height: 1,
-            };
-            let help_text = "y: Yes, delete | n: No, cancel";
-            let help_paragraph = Paragraph::new(help_text).style(Style::default().fg(Color::Gray));
-            f.render_widget(help_paragraph, help_area);
+    if let Some(host_idx) = app.delete_target
+        && let Some(host) = app.hosts.get(host_idx)
+    {
+        let mut lines = vec![
+            Line::from(format!("Delete host '{}'?", host.name)),
+            Line::from(""),
+            Line::from("This action cannot be undone."),
+        ];
+        let strict = app.delete_confirm_strict();
+        if strict && let Some(rule) = app.connect_protection(host_idx) {
+            lines.push(Line::from(Span::styled(
+                format!("Production host: {}", rule.describe()),
+                theme.error
+            )));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled(format!("Type {} to delete: ", host.name), theme.warning),
+                Span::raw(format!("{}_", app.delete_typed)),
+            ]));
+        } else {
+            lines.push(Line::from(""));
+            lines.push(confirm_buttons(theme, &app.confirm, "Yes, delete", "No, cancel"));
         }
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Confirm Delete"))
+            .wrap(ratatui::widgets::Wrap { trim: true });
+        f.render_widget(paragraph, area);
+
+        let help_area = ratatui::layout::Rect {
+            x: area.x + 1,
+            y: area.bottom() - 2,
+            width: area.width - 2,
+            height: 1,
+        };
+        let help_text = if strict { "Enter: Delete | ESC: Cancel" } else { CONFIRM_HELP };
+        let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+        f.render_widget(help_paragraph, help_area);
     }
 }
 
-fn render_discard_edit_confirm(f: &mut Frame, _app: &App) {
-    let area = centered_rect(50, 30, f.size());
+fn render_delete_dependents(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 60, f.size());
     f.render_widget(ratatui::widgets::Clear, area);
 
-    let text = "You have unsaved changes.\n\nDiscard all changes and exit?";
-    let paragraph = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("Discard Changes"))
-        .wrap(ratatui::widgets::Wrap { trim: true });
+    let Some(state) = &app.delete_dependents else {
+        return;
+    };
+    let Some(target) = app.hosts.get(state.host_index) else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("'{}' is used as a jump host by {} other hosts:", target.name, state.dependents.len()),
+            theme.warning
+        )),
+        Line::from("")
+    ];
+    for &dependent in &state.dependents {
+        if let Some(host) = app.hosts.get(dependent) {
+            let via = host.other_options
+                .get("proxyjump")
+                .map(|value| format!("ProxyJump {}", value))
+                .or_else(|| host.other_options.get("proxycommand").map(|value| format!("ProxyCommand {}", value)))
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<20} ", host.name)),
+                Span::styled(via, theme.muted),
+            ]));
+        }
+    }
+    if let Some(retarget) = &state.retarget {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("New jump host: ", theme.accent),
+            Span::raw(format!("{}|", retarget)),
+        ]));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Delete Jump Host"))
+        .wrap(ratatui::widgets::Wrap { trim: false });
     f.render_widget(paragraph, area);
 
     let help_area = ratatui::layout::Rect {
@@ -227,60 +1097,1490 @@ fn render_discard_edit_confirm(f: &mut Frame, _app: &App) {
         width: area.width - 2,
         height: 1,
     };
-    let help_text = "y: Yes, discard changes | n: No, continue editing";
-    let help_paragraph = Paragraph::new(help_text).style(Style::default().fg(Color::Gray));
+    let help_text = if state.retarget.is_some() {
+        "Enter: Retarget and delete | ESC: Back"
+    } else {
+        "y: Delete anyway | x: Delete and clear references | r: Delete and retarget | n/ESC: Cancel"
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
     f.render_widget(help_paragraph, help_area);
 }
 
-fn render_changes_review(f: &mut Frame, app: &App) {
-    let area = centered_rect(90, 80, f.size());
+fn render_lint(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 70, f.size());
     f.render_widget(ratatui::widgets::Clear, area);
 
-    let diff_lines = app.generate_diff_lines();
-
-    // Calculate visible lines based on scroll position
-    let content_height = (area.height as usize) - 4; // Account for borders and help text
-    let start_line = app.review_scroll;
-    let end_line = (start_line + content_height).min(diff_lines.len());
+    let Some(lint) = &app.lint else {
+        return;
+    };
 
-    let visible_lines: Vec<Line> = diff_lines[start_line..end_line]
+    let items: Vec<ListItem> = lint.findings
         .iter()
-        .map(|line| {
-            if line.starts_with('+') {
-                Line::from(Span::styled(line, Style::default().fg(Color::Green)))
-            } else if line.starts_with('-') {
-                Line::from(Span::styled(line, Style::default().fg(Color::Red)))
-            } else if line.starts_with('~') {
-                Line::from(Span::styled(line, Style::default().fg(Color::Yellow)))
-            } else {
-                Line::from(line.as_str())
-            }
+        .map(|finding| {
+            let (tag, style) = match finding.severity {
+                LintSeverity::Error => ("E", theme.error),
+                LintSeverity::Warning => ("W", theme.warning),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("[{}] ", tag), style),
+                Span::styled(format!("{:<20} ", finding.host), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(finding.message.clone()),
+                Span::styled(format!("  ({})", finding.rule), theme.faint),
+            ]))
         })
         .collect();
 
-    // Add header and footer information
-    let mut all_lines = vec![
-        Line::from(Span::styled("Pending Changes :", Style::default().fg(Color::Cyan))),
-        Line::from("")
-    ];
+    let list_area = ratatui::layout::Rect { height: area.height - 1, ..area };
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(lint.selected));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!("Lint ({} findings)", lint.findings.len())))
+        .highlight_style(theme.selected);
+    f.render_stateful_widget(list, list_area, &mut state);
 
-    all_lines.extend(visible_lines);
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 1,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "↑↓: Select | Enter: Go to host | f: Fix key permissions | r: Rotate missing key | ESC: Close";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
 
-    // Add scrolling indicator
-    if diff_lines.len() > content_height {
-        let scroll_info = format!(
-            "Lines {}-{} of {} (↑↓ to scroll, PgUp/PgDown for faster)",
+fn render_effective_config(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(90, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(view) = &app.effective else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("{:<26}{:<44}{}", "Option", "ssh -G", "Host block"),
+            Style::default().add_modifier(Modifier::BOLD)
+        ))
+    ];
+    for row in view.rows.iter().skip(view.scroll) {
+        let style = if row.differs {
+            theme.warning
+        } else if row.configured.is_some() {
+            Style::default()
+        } else {
+            theme.faint
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{:<26}{:<44}{}", row.key, row.effective, row.configured.as_deref().unwrap_or("")),
+            style
+        )));
+    }
+
+    let differing = view.rows.iter().filter(|row| row.differs).count();
+    let title = format!("Effective config: {} ({} differ from the host block)", view.host, differing);
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "↑↓/PgUp/PgDn: Scroll | Enter/ESC: Close";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_host_comparison(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(90, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(view) = &app.comparison else {
+        return;
+    };
+
+    // 两列值平分字段名之后的宽度，过长的值截断
+    let value_width = (area.width.saturating_sub(2 + 22) / 2) as usize;
+    let row_text = |field: &str, left: &str, right: &str| {
+        format!("{:<22}{:<value_width$}{}", truncate(field, 21), truncate(left, value_width.saturating_sub(1)), truncate(right, value_width))
+    };
+    let unset = |value: &str| if value.is_empty() { "-".to_string() } else { value.to_string() };
+    let mut lines = vec![
+        Line::from(Span::styled(row_text("Field", &view.left, &view.right), Style::default().add_modifier(Modifier::BOLD)))
+    ];
+    for row in view.rows.iter().skip(view.scroll) {
+        let style = if row.same { theme.faint } else { theme.warning };
+        lines.push(Line::from(Span::styled(row_text(&row.field, &unset(&row.left), &unset(&row.right)), style)));
+    }
+
+    let differing = view.rows.iter().filter(|row| !row.same).count();
+    let title = format!("Compare: {} ↔ {} ({} differ)", view.left, view.right, differing);
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "↑↓/PgUp/PgDn: Scroll | Enter/ESC: Close";
+    f.render_widget(Paragraph::new(help_text).style(theme.muted), help_area);
+}
+
+fn render_destination_query(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(query) = &app.destination_query else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("ssh ", theme.accent),
+            Span::styled(format!("{}|", query.input), theme.input),
+        ]),
+        Line::from(""),
+    ];
+    if query.result.blocks.is_empty() {
+        lines.push(Line::from(Span::styled("No Host block matches this destination", theme.muted)));
+    }
+    // 每个块一行标题，选项缩进；被前面的块抢先设置的值灰显并标注
+    let mut body = Vec::new();
+    for block in &query.result.blocks {
+        let title = match &block.patterns {
+            Some(patterns) => format!("Host {}", patterns),
+            None => "(global options)".to_string(),
+        };
+        body.push(Line::from(Span::styled(title, theme.accent.add_modifier(Modifier::BOLD))));
+        for option in &block.options {
+            let line = if option.applies {
+                Line::from(vec![
+                    Span::styled("  ✓ ", theme.success),
+                    Span::raw(format!("{:<24}{}", option.key, option.value)),
+                ])
+            } else {
+                Line::from(Span::styled(
+                    format!("    {:<24}{}  (overridden above)", option.key, option.value),
+                    theme.faint,
+                ))
+            };
+            body.push(line);
+        }
+    }
+    lines.extend(body.into_iter().skip(query.scroll));
+
+    let mut title = format!("What matches? {} block(s)", query.result.blocks.iter().filter(|block| block.patterns.is_some()).count());
+    if query.result.skipped_match_blocks > 0 {
+        title.push_str(&format!(" | {} Match block(s) not evaluated", query.result.skipped_match_blocks));
+    }
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "Type a destination | ✓ = value ssh uses (first match wins) | ↑↓/PgUp/PgDn: Scroll | ESC: Close";
+    f.render_widget(Paragraph::new(help_text).style(theme.muted), help_area);
+}
+
+/// 清单同步修改了哪些字段："HostName a → b, Port 22 → 2222"
+fn inventory_change_text(old: &SshHost, new: &SshHost) -> String {
+    let fields = [("HostName", &old.hostname, &new.hostname), ("User", &old.user, &new.user), ("Port", &old.port, &new.port)];
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(key, old, new)| format!("{} {} → {}", key, old.as_deref().unwrap_or("(none)"), new.as_deref().unwrap_or("(none)")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_inventory_sync(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(view) = &app.inventory_sync else {
+        return;
+    };
+    let delta = &view.delta;
+    let target = |host: &SshHost| host.hostname.clone().unwrap_or_default();
+
+    let mut lines: Vec<Line> = Vec::new();
+    for warning in &delta.warnings {
+        lines.push(Line::from(Span::styled(format!("! {}", warning), theme.warning)));
+    }
+    for host in &delta.added {
+        lines.push(Line::from(Span::styled(format!("+ {:<24}{}", host.name, target(host)), theme.added)));
+    }
+    for (old, new) in &delta.modified {
+        lines.push(Line::from(Span::styled(format!("~ {:<24}{}", new.name, inventory_change_text(old, new)), theme.accent)));
+    }
+    for host in &delta.removed {
+        lines.push(Line::from(Span::styled(format!("- {:<24}{}  (no longer in the inventory)", host.name, target(host)), theme.removed)));
+    }
+    if delta.is_empty() {
+        lines.push(Line::from(Span::styled("No changes to stage", theme.muted)));
+    }
+    let lines: Vec<Line> = lines.into_iter().skip(view.scroll).collect();
+
+    let title = format!(
+        "Inventory sync: {} new, {} changed, {} removed",
+        delta.added.len(),
+        delta.modified.len(),
+        delta.removed.len()
+    );
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let (help_text, style) = if view.confirm_removal {
+        (
+            format!("Delete the {} host(s) no longer in the inventory? y: Delete | n: Keep them | ESC: Back", delta.removed.len()),
+            theme.error.add_modifier(Modifier::BOLD),
+        )
+    } else {
+        ("Enter: Stage and review | ↑↓/PgUp/PgDn: Scroll | ESC: Cancel".to_string(), theme.muted)
+    };
+    f.render_widget(Paragraph::new(help_text).style(style), help_area);
+}
+
+fn render_bulk_identity(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 70, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(state) = &app.bulk_identity else {
+        return;
+    };
+
+    let input_style = |field: usize| {
+        if state.field == field && state.picker.is_none() {
+            theme.input
+        } else {
+            Style::default()
+        }
+    };
+    let targets = app.bulk_identity_targets();
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("New IdentityFile:      ", theme.accent),
+            Span::styled(format!("{}|", state.new_path), input_style(0)),
+        ]),
+        Line::from(vec![
+            Span::styled("Only when currently:   ", theme.accent),
+            Span::styled(format!("{}|", state.only_when), input_style(1)),
+            Span::styled(if state.only_when.is_empty() { "  (any value)" } else { "" }, theme.muted),
+        ]),
+        Line::from(""),
+        Line::from(format!("{} of {} hosts will change:", targets.len(), state.host_indices.len())),
+    ];
+    for &host_index in &targets {
+        if let Some(host) = app.hosts.get(host_index) {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<20} ", host.name)),
+                Span::styled(host.identity_file.clone().unwrap_or_else(|| "(none)".to_string()), theme.muted),
+                Span::raw(format!(" → {}", state.new_path.trim())),
+            ]));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Set IdentityFile: {}", state.folder)))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+
+    if let Some((keys, selected)) = &state.picker {
+        let picker_area = centered_rect(50, 50, area);
+        f.render_widget(ratatui::widgets::Clear, picker_area);
+        let items: Vec<ListItem> = keys.iter().map(|key| ListItem::new(key.as_str())).collect();
+        let mut list_state = ratatui::widgets::ListState::default();
+        list_state.select(Some(*selected));
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("~/.ssh keys"))
+            .highlight_style(theme.selected);
+        f.render_stateful_widget(list, picker_area, &mut list_state);
+    }
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = if state.picker.is_some() {
+        "↑↓: Select | Enter: Use key | ESC: Close picker"
+    } else {
+        "Tab: Switch field | F2: Pick from ~/.ssh | Enter: Stage changes | ESC: Cancel"
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_key_rotation(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 70, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(state) = &app.key_rotation else {
+        return;
+    };
+
+    let input_style = |field: usize| {
+        if state.field == field && state.picker.is_none() {
+            theme.input
+        } else {
+            Style::default()
+        }
+    };
+    let mode = if state.append { "◂ append as extra IdentityFile ▸" } else { "◂ replace ▸" };
+    let changes = app.key_rotation_changes();
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Old IdentityFile:  ", theme.accent),
+            Span::styled(format!("{}|", state.old_path), input_style(0)),
+        ]),
+        Line::from(vec![
+            Span::styled("New IdentityFile:  ", theme.accent),
+            Span::styled(format!("{}|", state.new_path), input_style(1)),
+        ]),
+        Line::from(vec![
+            Span::styled("Mode:              ", theme.accent),
+            Span::styled(mode, input_style(2)),
+        ]),
+        Line::from(""),
+        Line::from(format!("{} hosts will change:", changes.len())),
+    ];
+    for (host_index, new_host) in &changes {
+        if let Some(host) = app.hosts.get(*host_index) {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<20} ", host.name)),
+                Span::styled(identity_files(host).join(", "), theme.muted),
+                Span::raw(format!(" → {}", identity_files(new_host).join(", "))),
+            ]));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Rotate Key"))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+
+    if let Some((keys, selected)) = &state.picker {
+        let picker_area = centered_rect(50, 50, area);
+        f.render_widget(ratatui::widgets::Clear, picker_area);
+        let items: Vec<ListItem> = keys.iter().map(|key| ListItem::new(key.as_str())).collect();
+        let mut list_state = ratatui::widgets::ListState::default();
+        list_state.select(Some(*selected));
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("~/.ssh keys"))
+            .highlight_style(theme.selected);
+        f.render_stateful_widget(list, picker_area, &mut list_state);
+    }
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = if state.picker.is_some() {
+        "↑↓: Select | Enter: Use key | ESC: Close picker"
+    } else {
+        "Tab: Switch field | F2: Pick from ~/.ssh | Space: Replace/append | Enter: Stage changes | ESC: Cancel"
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_fix_permissions_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let mut lines = vec![Line::from("Remove group/other access (at most 0600) from:"), Line::from("")];
+    for path in &app.permission_fix {
+        lines.push(Line::from(format!("  {}", path.display())));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Fix Key Permissions"))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new("y: Fix | n/ESC: Back").style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_dashboard(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = f.size();
+    let Some(dashboard) = &app.dashboard else {
+        return;
+    };
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let items: Vec<ListItem> = dashboard.rows
+        .iter()
+        .map(|(host_index, status)| {
+            let host = &app.hosts[*host_index];
+            let (tcp, latency, style) = match status {
+                ProbeStatus::Pending => ("…".to_string(), String::new(), theme.faint),
+                ProbeStatus::Reachable(elapsed) => ("up".to_string(), format!("{} ms", elapsed.as_millis()), theme.success),
+                ProbeStatus::Unreachable(error) => ("down".to_string(), error.clone(), theme.error),
+                ProbeStatus::Skipped(reason) => ("-".to_string(), reason.clone(), theme.faint),
+            };
+            let history = app.history.hosts.get(&host.name);
+            let exit = history
+                .and_then(|h| h.last_exit_code)
+                .map_or("-".to_string(), |code| code.to_string());
+            let exit_style = match history.and_then(|h| h.last_exit_code) {
+                Some(0) | None => Style::default(),
+                Some(_) => theme.warning,
+            };
+            let last = history
+                .and_then(|h| h.last_connected)
+                .map_or("never".to_string(), |at| format!("{} ago", format_duration(Duration::from_secs(now.saturating_sub(at)))));
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<24}", host.name), Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(format!("{:<6}", tcp), style),
+                Span::styled(format!("{:<28}", truncate(&latency, 27)), style),
+                Span::styled(format!("{:<10}", exit), exit_style),
+                Span::raw(last),
+            ]))
+        })
+        .collect();
+
+    let down = dashboard.rows.iter().filter(|(_, status)| matches!(status, ProbeStatus::Unreachable(_))).count();
+    let refreshed = if app.dashboard_probing() {
+        "probing…".to_string()
+    } else {
+        dashboard.last_refreshed.map_or(String::new(), |at| format!("refreshed {} ago", format_duration(at.elapsed())))
+    };
+    let title = format!(
+        "Host health ({} hosts, {} down) - {}{}",
+        dashboard.rows.len(),
+        down,
+        refreshed,
+        if dashboard.sort_by_status { " - sorted by status" } else { "" }
+    );
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    let header = format!("   {:<24}{:<6}{:<28}{:<10}{}", "Host", "TCP", "Latency", "Last exit", "Last connected");
+    f.render_widget(Paragraph::new(header).style(Style::default().add_modifier(Modifier::BOLD)), chunks[0]);
+
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(dashboard.selected));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(theme.selected)
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    let help_text = "↑↓: Select | Enter: Go to host | r: Refresh | s: Sort by status | ESC: Close";
+    f.render_widget(Paragraph::new(help_text).style(theme.muted), chunks[2]);
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else if max == 0 {
+        String::new()
+    } else {
+        format!("{}…", text.chars().take(max.saturating_sub(1)).collect::<String>())
+    }
+}
+
+fn render_guard_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(50, 30, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(guard) = &app.guard else {
+        return;
+    };
+
+    let action = match guard.action {
+        GuardAction::EnterConfig => "enter config management".to_string(),
+        GuardAction::AddHost => "enter config management and add a host".to_string(),
+        GuardAction::DeleteHost => "delete the selected host".to_string(),
+        GuardAction::ApplyChanges => format!("save {} change(s) to the SSH config", app.pending_changes.len()),
+        GuardAction::SaveAndReload => format!("save {} change(s) and reload the SSH config", app.pending_changes.len()),
+    };
+    let phrase = app.settings.config_guard == ConfigGuard::Phrase;
+    let mut lines = vec![
+        Line::from(format!("This account is guarded. You are about to {}.", action)),
+        Line::from(""),
+    ];
+    if phrase {
+        lines.push(Line::from(format!("Type \"{}\" to continue:", app.settings.config_guard_phrase)));
+        lines.push(Line::from(Span::styled(format!("> {}|", guard.typed), theme.warning)));
+    } else {
+        let remaining = GUARD_DELAY.saturating_sub(guard.opened.elapsed());
+        lines.push(Line::from(if remaining.is_zero() {
+            Span::styled("Continue? (y/n)", theme.warning)
+        } else {
+            Span::styled(format!("Continue? (y/n) - wait {:.0}s", remaining.as_secs_f32().ceil()), theme.faint)
+        }));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Confirm"))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = if phrase { "Enter: Confirm | ESC: Cancel" } else { "y: Continue | n/ESC: Cancel" };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_config_locked(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 30, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(prompt) = &app.lock_prompt else {
+        return;
+    };
+    let option = |index: usize, label: &str, style: Style| {
+        Span::styled(format!("[ {} ]", label), selector_style(&prompt.choice, index, style))
+    };
+    let lines = vec![
+        Line::from(format!("Another sshc (PID {}) is editing this SSH config.", prompt.holder)),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Taking the lock over makes its next save fail, so its unsaved changes stay unsaved there.",
+            theme.warning
+        )),
+        Line::from(""),
+        Line::from(vec![
+            option(LOCK_READ_ONLY, "Continue read-only", theme.success),
+            Span::raw("  "),
+            option(LOCK_STEAL, "Steal the lock", theme.error),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Config Locked"))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new("↑↓/Tab: Choose | Enter: Accept | r: Read-only | s: Steal | ESC: Read-only")
+        .style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_reload_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(50, 30, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let text = format!(
+        "You have {}.\n\nReloading the config from disk discards them unless you save first, or merge them into the file's new content.",
+        unsaved_changes(app.pending_changes.len())
+    );
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Reload Config"))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "s: Save, then reload | m: Merge | d: Discard and reload | ESC: Cancel";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_merge_conflicts(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(state) = &app.merge else {
+        return;
+    };
+    let area = centered_rect(80, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Merge conflicts ({} applied cleanly)", state.result.changes.len()));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((state.result.conflicts.len() as u16 + 1).min(inner.height / 3)),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    // 冲突列表：每台主机一行，标出已做的选择
+    let mut lines = vec![Line::from(Span::styled(
+        "These hosts were changed both here and on disk:",
+        theme.warning
+    ))];
+    for (i, (conflict, choice)) in state.result.conflicts.iter().zip(&state.choices).enumerate() {
+        let (label, label_style) = match choice {
+            Some(MergeChoice::Mine) => ("[mine]  ", theme.success),
+            Some(MergeChoice::Theirs) => ("[theirs]", theme.accent),
+            None => ("[?]     ", theme.error),
+        };
+        let mut style = Style::default();
+        if i == state.selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", label), label_style),
+            Span::styled(conflict.name.clone(), style),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), chunks[0]);
+
+    // 选中冲突的两个版本并排预览
+    if let Some(conflict) = state.result.conflicts.get(state.selected) {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[1]);
+        let preview = |host: Option<&SshHost>, missing: &str| -> Vec<Line> {
+            match host {
+                Some(host) => match serialize_host(host, true) {
+                    Ok(block) => block.lines().map(|line| Line::from(line.to_string())).collect(),
+                    Err(e) => vec![Line::from(Span::styled(e.to_string(), theme.error))],
+                },
+                None => vec![Line::from(Span::styled(missing.to_string(), theme.error))],
+            }
+        };
+        let mine = Paragraph::new(preview(conflict.mine.as_ref(), "(deleted here)"))
+            .block(Block::default().borders(Borders::ALL).title("Mine").border_style(theme.success));
+        let theirs = Paragraph::new(preview(conflict.theirs.as_ref(), "(not in the file)"))
+            .block(Block::default().borders(Borders::ALL).title("Theirs (on disk)").border_style(theme.accent));
+        f.render_widget(mine, panes[0]);
+        f.render_widget(theirs, panes[1]);
+    }
+
+    let help_text = "↑↓: Select | m: Keep mine | t: Keep theirs | Enter: Apply | ESC: Cancel";
+    f.render_widget(Paragraph::new(help_text).style(theme.muted), chunks[2]);
+}
+
+fn render_derive(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 40, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(state) = &app.derive else {
+        return;
+    };
+    let base = app.hosts.get(state.base).map(|h| h.name.as_str()).unwrap_or_default();
+
+    let mut lines = Vec::new();
+    let help_text = match state.template {
+        None => {
+            for (i, template) in DeriveTemplate::ALL.iter().enumerate() {
+                let style = selector_style(&state.picker, i, Style::default());
+                lines.push(Line::from(Span::styled(format!("{} {}", i + 1, template.label()), style)));
+            }
+            "↑↓/Tab: Select | Enter/1-3: Choose | ESC: Cancel"
+        }
+        Some(template) => {
+            lines.push(Line::from(Span::styled(template.label(), Style::default().add_modifier(Modifier::BOLD))));
+            lines.push(Line::from(""));
+            for (i, label) in template.fields().iter().enumerate() {
+                let value = state.inputs.get(i).map(String::as_str).unwrap_or_default();
+                let (text, style) = if i == state.field {
+                    (format!("{}|", value), theme.warning)
+                } else {
+                    (value.to_string(), Style::default())
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{}: ", label), theme.accent),
+                    Span::styled(text, style),
+                ]));
+            }
+            if let Some(error) = &state.error {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(error.clone(), theme.error)));
+            }
+            "Tab: Next field | Enter: Create | ESC: Back"
+        }
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Derive from {}", base)))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_action_menu(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(menu) = &app.action_menu else {
+        return;
+    };
+    let area = centered_rect(50, 60, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let width = menu.actions.iter().map(|binding| binding.key_label().len()).max().unwrap_or(0);
+    let lines: Vec<Line> = menu.actions
+        .iter()
+        .enumerate()
+        .map(|(i, binding)| {
+            let style = selector_style(&menu.choice, i, Style::default());
+            Line::from(vec![
+                Span::styled(format!(" {:>width$}  ", binding.key_label(), width = width), style.patch(theme.accent)),
+                Span::styled(binding.label, style),
+            ])
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Actions: {}", menu.host)));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new("↑↓/Tab: Select | Enter or key: Run | ESC: Close").style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_command_palette(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(palette) = &app.command_palette else {
+        return;
+    };
+    let area = centered_rect(60, 70, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("> ", theme.accent),
+        Span::styled(format!("{}_", palette.query), theme.input),
+    ]))
+    .block(Block::default().borders(Borders::ALL).title(format!("Commands ({}/{})", palette.matches.len(), palette.entries.len())));
+    f.render_widget(input, chunks[0]);
+
+    // 按键靠右对齐；不可用的操作灰显
+    let width = chunks[1].width.saturating_sub(4) as usize;
+    let items: Vec<ListItem> = palette.matches
+        .iter()
+        .map(|&index| {
+            let entry = &palette.entries[index];
+            let style = if entry.enabled { Style::default() } else { theme.faint };
+            let gap = width.saturating_sub(entry.label.chars().count() + entry.key.chars().count()).max(1);
+            ListItem::new(Line::from(vec![
+                Span::styled(entry.label, style),
+                Span::raw(" ".repeat(gap)),
+                Span::styled(entry.key.clone(), if entry.enabled { theme.accent } else { theme.faint }),
+            ]))
+        })
+        .collect();
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(palette.selected().map(|_| palette.choice.selected));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(theme.selected)
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    let help_text = "Type to filter | ↑↓/Tab: Select | Enter: Run | ESC: Close";
+    f.render_widget(Paragraph::new(help_text).style(theme.muted), chunks[2]);
+}
+
+fn render_onboarding(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 60, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(state) = &app.onboarding else {
+        return;
+    };
+
+    // 顶部是各步骤的进度和结果
+    let mut lines = Vec::new();
+    for step in OnboardingStep::ALL {
+        let (mark, detail, outcome_style) = match state.outcome(step) {
+            StepOutcome::Pending => ("·", String::new(), theme.muted),
+            StepOutcome::Done(detail) => ("✓", detail.clone(), theme.success),
+            StepOutcome::Skipped => ("-", "skipped".to_string(), theme.warning),
+            StepOutcome::Failed(error) => ("✗", error.clone(), theme.error),
+        };
+        let style = if step == state.step { Style::default().add_modifier(Modifier::BOLD) } else { Style::default() };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", mark), outcome_style),
+            Span::styled(format!("{:<24}", step.label()), style),
+            Span::styled(detail, outcome_style),
+        ]));
+    }
+    lines.push(Line::from(""));
+
+    let failed = matches!(state.outcome(state.step), StepOutcome::Failed(_));
+    let help_text = match state.step {
+        OnboardingStep::Details => {
+            for (i, label) in ONBOARDING_FIELDS.iter().enumerate() {
+                let value = state.inputs.get(i).map(String::as_str).unwrap_or_default();
+                let (text, style) = if i == state.field {
+                    (format!("{}|", value), theme.warning)
+                } else {
+                    (value.to_string(), Style::default())
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{}: ", label), theme.accent),
+                    Span::styled(text, style),
+                ]));
+            }
+            if let Some(error) = &state.error {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(error.clone(), theme.error)));
+            }
+            "Tab: Next field | Enter: Continue | ESC: Cancel"
+        }
+        OnboardingStep::Key => {
+            let options = state.keys.iter().cloned().chain(std::iter::once("Generate a new ed25519 key…".to_string()));
+            for (i, option) in options.enumerate() {
+                lines.push(Line::from(Span::styled(option, selector_style(&state.key_picker, i, Style::default()))));
+            }
+            "↑↓/Tab: Select | Enter: Use | s: Skip | ESC: Back"
+        }
+        OnboardingStep::CopyId => {
+            lines.push(Line::from(format!("Run ssh-copy-id for {}; it asks for the server password.", state.target())));
+            if failed { "Enter/r: Retry | s: Skip | ESC: Back" } else { "Enter: Run | s: Skip | ESC: Back" }
+        }
+        OnboardingStep::Test => {
+            lines.push(Line::from(format!("Connect to {} with BatchMode (no password prompts).", state.target())));
+            if failed { "Enter/r: Retry | s: Skip | ESC: Back" } else { "Enter: Run | s: Skip | ESC: Back" }
+        }
+        OnboardingStep::Summary => {
+            let host = state.host();
+            lines.push(Line::from(Span::styled(format!("Host {}", host.name), Style::default().add_modifier(Modifier::BOLD))));
+            let fields = [("HostName", &host.hostname), ("User", &host.user), ("Port", &host.port), ("IdentityFile", &host.identity_file)];
+            for (label, value) in fields {
+                if let Some(value) = value {
+                    lines.push(Line::from(format!("    {} {}", label, value)));
+                }
+            }
+            "Enter: Add host | ESC: Back"
+        }
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("New Server Setup"))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_transfer(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(state) = &app.transfer else {
+        return;
+    };
+    let area = centered_rect(70, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let input_style = |field: usize| {
+        if state.field == field {
+            theme.input
+        } else {
+            Style::default()
+        }
+    };
+    let kinds: Vec<Span> = TransferKind::ALL
+        .iter()
+        .flat_map(|kind| {
+            let style = if *kind == state.kind { input_style(0).add_modifier(Modifier::BOLD) } else { theme.muted };
+            [Span::styled(format!(" {} ", kind.label()), style), Span::raw(" ")]
+        })
+        .collect();
+    let local = if state.kind.uses_local_path() {
+        Span::styled(format!("{}|", state.local), input_style(1))
+    } else {
+        Span::styled("(not used by sftp)", theme.muted)
+    };
+    let mut lines = vec![
+        Line::from([vec![Span::styled("Mode:         ", theme.accent)], kinds].concat()),
+        Line::from(vec![Span::styled("Local path:   ", theme.accent), local]),
+        Line::from(vec![
+            Span::styled("Remote path:  ", theme.accent),
+            Span::styled(format!("{}|", state.remote), input_style(2)),
+        ]),
+    ];
+
+    // 当前路径字段的最近记录
+    let history = state.history(&app.transfer_paths);
+    if !history.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Recent (↑↓):", theme.muted)));
+        for (i, path) in history.iter().enumerate() {
+            let style = if state.history_index == Some(i) { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+            lines.push(Line::from(Span::styled(format!("  {}", path), style)));
+        }
+    }
+    if let Some(error) = &state.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(error.clone(), theme.error)));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Transfer: {}", state.host)));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "Tab: Next field | Space: Change mode | ↑↓: Recent paths | Enter: Run | ESC: Cancel";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_user_switch(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(state) = &app.user_switch else {
+        return;
+    };
+    let area = centered_rect(40, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let configured = app.hosts.get(state.host_index).and_then(|host| host.user.as_deref());
+    let mut lines: Vec<Line> = state.choices
+        .iter()
+        .enumerate()
+        .map(|(i, user)| {
+            let note = if Some(user.as_str()) == configured { " (configured)" } else { "" };
+            let style = if i == state.selected { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+            Line::from(Span::styled(format!("  {}{}", user, note), style))
+        })
+        .collect();
+    let custom_style = if state.selected == state.choices.len() {
+        theme.input
+    } else {
+        theme.muted
+    };
+    lines.push(Line::from(vec![Span::raw("  Other: "), Span::styled(format!("{}|", state.custom), custom_style)]));
+
+    let name = app.hosts.get(state.host_index).map(|host| host.name.as_str()).unwrap_or_default();
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Connect to {} as", name)));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "↑↓: Select | type: Other user | Enter: Connect | ESC: Cancel";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_tag_editor(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(editor) = &app.tag_editor else {
+        return;
+    };
+    let area = centered_rect(50, 60, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let mut lines: Vec<Line> = editor.rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let (checked, style) = match row.state {
+                TagCheck::Checked => ("[x]", theme.success),
+                TagCheck::Partial => ("[~]", theme.warning),
+                TagCheck::Unchecked => ("[ ]", theme.muted),
+            };
+            let style = selector_style(&editor.picker, i, style);
+            let changed = if row.state != row.original { " *" } else { "" };
+            Line::from(Span::styled(format!("{} {}{}", checked, row.tag, changed), style))
+        })
+        .collect();
+    if editor.rows.is_empty() {
+        lines.push(Line::from(Span::styled("No tags yet", theme.muted)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("New tag: ", theme.accent),
+        Span::raw(format!("{}_", editor.input)),
+    ]));
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Tags of {} hosts", editor.hosts.len())));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "↑↓/Tab: Select | Space: Toggle | type + Enter: Add tag | Enter: Apply | ESC: Cancel";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_filter_menu(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(40, 40, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let lines: Vec<Line> = QuickFilter::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, filter)| {
+            let checked = if app.quick_filters.contains(filter) { "[x]" } else { "[ ]" };
+            let mut style = Style::default();
+            if app.quick_filters.contains(filter) {
+                style = style.patch(theme.special);
+            }
+            if i == app.filter_menu_selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Line::from(Span::styled(format!("{} {} {}", i + 1, checked, filter.label()), style))
+        })
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Filters ({} hosts)", app.filtered_hosts.len())));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "Space/1-6: Toggle | c: Clear | ESC: Close";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_queue_next(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(50, 30, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let next = app.connect_queue.first().map(String::as_str).unwrap_or("-");
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw("Next: "),
+            Span::styled(next, theme.accent.add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(""),
+    ];
+    if app.connect_queue.len() > 1 {
+        lines.push(Line::from(Span::styled(
+            format!("Then: {}", app.connect_queue[1..].join(", ")),
+            theme.muted
+        )));
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("Connection Queue ({} left)", app.connect_queue.len())))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = "Enter: Connect | s: Skip | q: Stop";
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_review_pending_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let text = format!(
+        "You have {}.\n\nReview them now?",
+        unsaved_changes(app.pending_changes.len())
+    );
+    let help_text = "y/Enter: Review | n: Keep editing | ESC: Cancel";
+    let lines: Vec<Line> = text.lines().map(Line::from).collect();
+    let (width, height) = content_size(&lines);
+    let area = popup_rect(PopupLimits::new(50, 30).fit(width.max(help_text.chars().count() as u16), height + 1), 0, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Unsaved Changes"))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_discard_edit_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let text = vec![
+        Line::from("You have unsaved changes."),
+        Line::from(""),
+        Line::from("Discard all changes and exit?"),
+        Line::from(""),
+        confirm_buttons(theme, &app.confirm, "Yes, discard changes", "No, continue editing"),
+    ];
+    // 按钮行不折行；帮助行另占一行
+    let (width, height) = content_size(&text);
+    let area = popup_rect(PopupLimits::new(50, 30).fit(width.max(CONFIRM_HELP.chars().count() as u16), height + 1), 0, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Discard Changes"))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = CONFIRM_HELP;
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_copy_system_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(host) = app.copy_system.and_then(|(host_index, _)| app.hosts.get(host_index)) else {
+        return;
+    };
+    let area = centered_rect(60, 30, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let offer = match app.copy_system.and_then(|(host_index, _)| app.copy_name_for(host_index)) {
+        // 被包含的条目可能先被 ssh 匹配到，副本换一个名称
+        Some(name) => format!("Copy it into your config as {}? The read-only entry may match \"{}\" first, so the copy gets its own name.", name, host.name),
+        None => "Copy it into your config? The copy takes precedence and can be edited.".to_string(),
+    };
+    let text = vec![
+        Line::from(format!("{} comes from {}, which is read-only.", host.name, app.config_file_label(host.source.as_deref()))),
+        Line::from(""),
+        Line::from(offer),
+        Line::from(""),
+        confirm_buttons(theme, &app.confirm, "Yes, copy into my config", "No"),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Read-only Host"))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new(CONFIRM_HELP).style(theme.muted);
+    f.render_widget(help_paragraph, help_area);
+}
+
+fn render_host_diff(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(diff) = &app.host_diff else {
+        return;
+    };
+    let area = centered_rect(70, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    // 修改前后的值平分字段名之后的宽度，过长的值截断
+    let value_width = (area.width.saturating_sub(2 + 16) / 2) as usize;
+    let row_text = |field: &str, before: &str, after: &str| {
+        format!("{:<16}{:<value_width$}{}", truncate(field, 15), truncate(before, value_width.saturating_sub(3)) + " →", truncate(after, value_width))
+    };
+    let message = if diff.added {
+        Some(format!("{} is a new host that has not been saved yet", diff.name))
+    } else if diff.change_indices.is_empty() {
+        Some("No unsaved changes for this host".to_string())
+    } else if diff.fields.is_empty() {
+        Some("The unsaved edits leave every field as it was".to_string())
+    } else {
+        None
+    };
+    let lines = match message {
+        Some(message) => vec![Line::from(Span::styled(message, theme.faint))],
+        None => std::iter::once(Line::from(Span::styled(format!("{:<16}{:<value_width$}{}", "Field", "Before", "After"), Style::default().add_modifier(Modifier::BOLD))))
+            .chain(diff.fields.iter().map(|change| {
+                Line::from(Span::styled(row_text(&change.field, &change.before, &change.after), theme.warning))
+            }))
+            .collect(),
+    };
+    let title = format!("Unsaved changes: {}", diff.name);
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_text = if diff.change_indices.is_empty() { "Enter/ESC: Close" } else { "u: Revert these changes | Enter/ESC: Close" };
+    f.render_widget(Paragraph::new(help_text).style(theme.muted), help_area);
+}
+
+fn render_backups(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(browser) = &app.backups else {
+        return;
+    };
+    let area = centered_rect(80, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+    let block = Block::default().borders(Borders::ALL).title(format!("Backups ({})", browser.backups.len()));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // 上方是备份列表，下方是与当前主配置的差异，最后一行留给帮助
+    let list_height = (browser.backups.len() as u16).clamp(1, (inner.height.saturating_sub(3) / 2).max(1));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(list_height), Constraint::Min(2), Constraint::Length(1)])
+        .split(inner);
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (start, end) = App::list_window(browser.backups.len(), Some(browser.choice.selected), 0, list_height as usize);
+    let rows: Vec<Line> = browser.backups[start..end]
+        .iter()
+        .enumerate()
+        .map(|(offset, backup)| {
+            let age = format_duration(Duration::from_secs(now.saturating_sub(backup.created)));
+            let text = format!("{} UTC   {:>12}   {:>10}", format_utc(backup.created), format!("{} ago", age), format_size(backup.size));
+            Line::from(Span::styled(text, selector_style(&browser.choice, start + offset, Style::default())))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(rows), chunks[0]);
+
+    let mut preview = Vec::new();
+    match &browser.preview {
+        Ok(changes) => {
+            let summary = summarize_changes(changes);
+            let headline = if changes.is_empty() { "Identical to the current main config".to_string() } else { format!("Restoring: {}", summary.headline()) };
+            preview.push(Line::from(Span::styled(headline, Style::default().add_modifier(Modifier::BOLD))));
+            for row in &summary.rows {
+                let name = if row.name.is_empty() { "(global options)" } else { row.name.as_str() };
+                let (style, detail) = match row.kind {
+                    SummaryKind::Added => (theme.added, " (only in the backup)".to_string()),
+                    SummaryKind::Deleted => (theme.removed, " (not in the backup)".to_string()),
+                    SummaryKind::Modified => (theme.changed, format!(": {}", row.fields.join(", "))),
+                };
+                preview.push(Line::from(Span::styled(format!("{} {}{}", row.kind.marker(), name, detail), style)));
+            }
+        }
+        Err(e) => preview.push(Line::from(Span::styled(e.clone(), theme.error))),
+    }
+    let scroll = browser.preview_scroll.min(preview.len().saturating_sub(1));
+    f.render_widget(
+        Paragraph::new(preview.into_iter().skip(scroll).collect::<Vec<_>>()).block(Block::default().borders(Borders::TOP).title("Compared with the current main config")),
+        chunks[1]
+    );
+
+    let help = "↑↓: Select backup | PgUp/PgDn: Scroll | Enter: Stage restore (review before saving) | ESC: Close";
+    f.render_widget(Paragraph::new(help).style(theme.muted), chunks[2]);
+}
+
+fn render_views(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(picker) = &app.view_picker else {
+        return;
+    };
+    let area = centered_rect(60, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+    let block = Block::default().borders(Borders::ALL).title(format!("Saved Views ({})", picker.views.len()));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1), Constraint::Length(1), Constraint::Length(1)])
+        .split(inner);
+    f.render_widget(
+        Paragraph::new(Line::from(vec![Span::styled("Current: ", theme.accent), Span::raw(app.current_view().summary())])),
+        chunks[0]
+    );
+
+    // 无法还原的视图（较新版本保存等）显示原因
+    let width = picker.views.iter().map(|view| view.name.chars().count()).max().unwrap_or(0).min(24);
+    let (start, end) = App::list_window(picker.views.len(), Some(picker.choice.selected), 0, chunks[1].height as usize);
+    let mut rows: Vec<Line> = picker.views[start..end]
+        .iter()
+        .enumerate()
+        .map(|(offset, view)| {
+            let (detail, style) = match view.migrate() {
+                Ok(state) => (state.summary(), Style::default()),
+                Err(e) => (e, theme.faint),
+            };
+            let text = format!("{:<width$}  {}", truncate(&view.name, 24), detail, width = width);
+            Line::from(Span::styled(text, selector_style(&picker.choice, start + offset, style)))
+        })
+        .collect();
+    if picker.views.is_empty() {
+        rows.push(Line::from(Span::styled("No saved views yet; press s to save the current filters as one", theme.muted)));
+    }
+    f.render_widget(Paragraph::new(rows), chunks[1]);
+
+    let (prompt, help) = match &picker.prompt {
+        Some((kind, name)) => {
+            let label = match kind {
+                ViewPrompt::New => "Save as: ",
+                ViewPrompt::Rename(_) => "Rename to: ",
+            };
+            (Line::from(vec![Span::styled(label, theme.accent), Span::styled(format!("{}|", name), theme.input)]), "Enter: Confirm | ESC: Cancel")
+        }
+        None => (Line::from(""), "Enter: Apply | s: Save current | u: Update with current | r: Rename | d: Delete | ESC: Close"),
+    };
+    f.render_widget(Paragraph::new(prompt), chunks[2]);
+    f.render_widget(Paragraph::new(help).style(theme.muted), chunks[3]);
+}
+
+fn render_changes_review(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    // +/- 调整大小，需要看到更多上下文时放大
+    let area = popup_rect(PopupLimits { min_width: 60, min_height: 12, ..PopupLimits::new(90, 80) }, app.review_zoom, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    // Calculate visible lines based on scroll position
+    let content_height = (area.height as usize).saturating_sub(4); // Account for borders and help text
+    app.review_height = content_height;
+    // 区域变高（例如调整终端大小）后不留空白
+    app.review_scroll = app.review_scroll.min(max_review_scroll(app.review_lines.len(), content_height));
+    let diff_lines = &app.review_lines;
+    let start_line = app.review_scroll;
+    let end_line = (start_line + content_height).min(diff_lines.len());
+
+    let matches = app.review_matches();
+    // 选中的变更：摘要行反色，diff 块加粗
+    let selected_block = app.review_selected_lines();
+    let selected_row = app.review_jumps.iter().position(|&line| line == selected_block.start).map(|number| number + 1);
+    let visible_lines: Vec<Line> = diff_lines[start_line..end_line]
+        .iter()
+        .enumerate()
+        .map(|(offset, line)| {
+            let index = start_line + offset;
+            let mut style = if line.starts_with('+') {
+                theme.added
+            } else if line.starts_with('-') {
+                theme.removed
+            } else if line.starts_with('~') {
+                theme.changed
+            } else if line.starts_with("@ ") {
+                theme.info.add_modifier(Modifier::BOLD)
+            } else if line.starts_with("Summary:") {
+                theme.accent.add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            if selected_row == Some(index) {
+                style = style.add_modifier(Modifier::REVERSED);
+            } else if selected_block.contains(&index) && !line.is_empty() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            // 高亮搜索匹配，当前匹配反色显示
+            if app.review_search.current == Some(index) {
+                style = theme.input;
+            } else if matches.binary_search(&index).is_ok() {
+                style = style.patch(theme.matched);
+            }
+            Line::from(Span::styled(line.as_str(), style))
+        })
+        .collect();
+
+    // Add header and footer information
+    let mut all_lines = vec![
+        Line::from(Span::styled("Pending Changes :", theme.accent)),
+        Line::from("")
+    ];
+
+    all_lines.extend(visible_lines);
+
+    // Add scrolling indicator
+    if diff_lines.len() > content_height {
+        let scroll_info = format!(
+            "Lines {}-{} of {} (↑↓ to scroll, PgUp/PgDown for faster)",
             start_line + 1,
             end_line.min(diff_lines.len()),
             diff_lines.len()
         );
         all_lines.push(Line::from(""));
-        all_lines.push(Line::from(Span::styled(scroll_info, Style::default().fg(Color::Gray))));
+        all_lines.push(Line::from(Span::styled(scroll_info, theme.muted)));
     }
 
     all_lines.push(Line::from(""));
     all_lines.push(
-        Line::from(Span::styled("Save these changes?", Style::default().fg(Color::White)))
+        Line::from(Span::styled("Save these changes?", theme.bright))
     );
 
     let paragraph = Paragraph::new(all_lines)
@@ -294,77 +2594,591 @@ fn render_changes_review(f: &mut Frame, app: &App) {
         width: area.width - 2,
         height: 1,
     };
-    let help_text = "↑↓: Scroll | PgUp/PgDn: Fast scroll | y: Save | n: Discard | ESC: Back";
-    let help_paragraph = Paragraph::new(help_text).style(Style::default().fg(Color::Gray));
+    let search = &app.review_search;
+    let help_line = if search.editing {
+        Line::from(vec![
+            Span::styled(format!("/{}|", search.query), theme.warning),
+            Span::styled(format!("  {} match(es) | Enter: Jump | ESC: Cancel", matches.len()), theme.muted),
+        ])
+    } else if !search.query.is_empty() {
+        let position = search.current
+            .and_then(|line| matches.iter().position(|&m| m == line))
+            .map_or("-".to_string(), |i| (i + 1).to_string());
+        Line::from(vec![
+            Span::styled(format!("/{}  [{}/{}]", search.query, position, matches.len()), theme.warning),
+            Span::styled(" | n/N: Next/previous match | ESC: Clear search | y: Save", theme.muted),
+        ])
+    } else {
+        Line::from(Span::styled(
+            "↑↓: Scroll | PgUp/PgDn: Fast scroll | Home/End: Top/bottom | 1-9/Tab: Select change | Shift-↑↓: Move change | +/-: Resize | /: Search | y: Save | n: Discard | ESC: Back",
+            theme.muted
+        ))
+    };
+    f.render_widget(Paragraph::new(help_line), help_area);
+}
+
+fn render_connection_error(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    if let Some(error) = &app.connection_error {
+        let status = match error.exit_code {
+            Some(code) => format!(
+                "ssh to '{}' exited immediately with status {} ({})",
+                error.host,
+                code,
+                classify_failure(error.exit_code, &error.stderr).label()
+            ),
+            None => format!("ssh to '{}' was terminated by a signal", error.host),
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(status, theme.error.add_modifier(Modifier::BOLD))),
+            Line::from("")
+        ];
+
+        if error.stderr.is_empty() {
+            lines.push(Line::from(Span::styled("(ssh produced no error output)", theme.muted)));
+        } else {
+            lines.extend(
+                error.stderr
+                    .iter()
+                    .skip(app.connection_error_scroll)
+                    .map(|line| Line::from(line.as_str()))
+            );
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Connection Failed"))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(paragraph, area);
+
+        let help_area = ratatui::layout::Rect {
+            x: area.x + 1,
+            y: area.bottom() - 2,
+            width: area.width - 2,
+            height: 1,
+        };
+        let help_text = "↑↓: Scroll | Enter/ESC: Close";
+        let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+        f.render_widget(help_paragraph, help_area);
+    }
+}
+
+fn render_group_connect_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 60, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    if let Some(group) = &app.group_connect {
+        let how = if group.use_tmux { "in tiled tmux panes" } else { "one after another" };
+        let mut lines = vec![
+            Line::from(format!(
+                "Open {} hosts from '{}' {}?",
+                group.host_indices.len(),
+                group.folder,
+                how
+            )),
+            Line::from("")
+        ];
+
+        if group.host_indices.len() > app.settings.group_connect_confirm_threshold {
+            lines.push(Line::from(Span::styled(
+                format!("Warning: more than {} hosts", app.settings.group_connect_confirm_threshold),
+                theme.warning
+            )));
+            lines.push(Line::from(""));
+        }
+
+        for &host_index in &group.host_indices {
+            if let Some(host) = app.hosts.get(host_index) {
+                lines.push(Line::from(format!("  {}", host.get_full_display_info())));
+            }
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Group Connect"))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(paragraph, area);
+
+        let help_area = ratatui::layout::Rect {
+            x: area.x + 1,
+            y: area.bottom() - 2,
+            width: area.width - 2,
+            height: 1,
+        };
+        let help_text = "y/Enter: Connect all | n/ESC: Cancel";
+        let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+        f.render_widget(help_paragraph, help_area);
+    }
+}
+
+fn render_connect_confirm(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 40, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    if let Some(host_index) = app.connect_confirm && let Some(host) = app.hosts.get(host_index) {
+        let rule = app.connect_protection(host_index);
+        // 生产主机的名称用红色突出，并说明是哪条规则要求确认
+        let title_style = if rule.is_some() {
+            theme.error.add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let mut title = vec![Span::raw("Connect to ")];
+        if let Some(environment) = environment_badge(host, &app.settings.environments) {
+            title.push(badge_span(theme, &environment.badge, &environment.color));
+            title.push(Span::raw(" "));
+        }
+        title.extend([Span::styled(host.get_full_display_info(), title_style), Span::raw("?")]);
+        let mut lines = vec![Line::from(title)];
+        // 临时改用的用户放在标题下方醒目显示
+        if let Some(user) = &app.connect_user {
+            lines.push(Line::from(vec![
+                Span::raw("As user: "),
+                Span::styled(user.clone(), theme.badge(Color::Magenta)),
+                Span::styled(" (instead of the configured user, this session only)", theme.muted),
+            ]));
+        }
+        if let Some(rule) = &rule {
+            lines.push(Line::from(Span::styled(
+                format!("Production host: {}", rule.describe()),
+                theme.error
+            )));
+        }
+        let command = match connect_command(host, app.connect_user.as_deref(), &app.settings.connectors) {
+            Ok(command) => Span::raw(display_command(&command)),
+            Err(e) => Span::styled(e, theme.error),
+        };
+        lines.extend([
+            Line::from(""),
+            Line::from(vec![Span::styled("Command: ", theme.accent), command]),
+        ]);
+        if let Some(name) = dns_name(host) {
+            lines.push(Line::from(Span::styled(
+                format!("No HostName set; ssh resolves {} via DNS", name),
+                theme.muted
+            )));
+        }
+        if let Some(proxy) = proxy_command_line(theme, host) {
+            lines.push(Line::from(Span::styled("ProxyCommand:", theme.accent)));
+            lines.push(proxy);
+        }
+        let strict = app.connect_confirm_strict();
+        if strict {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled(format!("Type {} to connect: ", host.connect_target()), theme.warning),
+                Span::raw(format!("{}_", app.connect_confirm_typed)),
+            ]));
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Connect"))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(paragraph, area);
+
+        let help_area = ratatui::layout::Rect {
+            x: area.x + 1,
+            y: area.bottom() - 2,
+            width: area.width - 2,
+            height: 1,
+        };
+        let help_text = if strict { "Enter: Connect | ESC: Cancel" } else { "y/Enter: Connect | n/ESC: Cancel" };
+        let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+        f.render_widget(help_paragraph, help_area);
+    }
+}
+
+fn render_message(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 50, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    if let Some(message) = &app.message {
+        let lines: Vec<Line> = message.lines
+            .iter()
+            .skip(message.scroll)
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(message.title.as_str()))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(paragraph, area);
+
+        let help_area = ratatui::layout::Rect {
+            x: area.x + 1,
+            y: area.bottom() - 2,
+            width: area.width - 2,
+            height: 1,
+        };
+        let help_text = "↑↓: Scroll | Enter/ESC: Close";
+        let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+        f.render_widget(help_paragraph, help_area);
+    }
+}
+
+fn render_export(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 70, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    if let Some(export) = &app.export {
+        let on_off = |flag: bool| if flag { "on" } else { "off" };
+        let mut lines = vec![
+            Line::from(format!(
+                "Format: {} | Metadata comments: {} | ~-relative identity files: {}",
+                if export.csv { "CSV" } else { "config snippet" },
+                if export.csv { "n/a" } else { on_off(export.options.include_metadata) },
+                on_off(export.options.tilde_paths)
+            )),
+            Line::from(if export.editing_path {
+                format!("File: {}|", export.file_path)
+            } else {
+                format!("File: {}", export.file_path)
+            }),
+        ];
+        if let Some(status) = &export.status {
+            lines.push(Line::from(Span::styled(status.as_str(), theme.accent)));
+        }
+        lines.push(Line::from(""));
+
+        let preview = app.export_preview();
+        lines.extend(
+            preview.lines().map(|line| Line::from(Span::styled(line.to_string(), theme.success)))
+        );
+
+        let title = format!("Export {} host(s)", export.host_indices.len());
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(paragraph, area);
+
+        let help_area = ratatui::layout::Rect {
+            x: area.x + 1,
+            y: area.bottom() - 2,
+            width: area.width - 2,
+            height: 1,
+        };
+        let help_text = if export.editing_path {
+            "Type a path | Enter: Write file | ESC: Cancel"
+        } else {
+            "c: Copy | f: Write to file | x: Toggle CSV | m: Toggle metadata | t: Toggle ~ paths | ESC: Close"
+        };
+        let help_paragraph = Paragraph::new(help_text).style(theme.muted);
+        f.render_widget(help_paragraph, help_area);
+    }
+}
+
+fn render_import(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 80, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let Some(import) = &app.import else {
+        return;
+    };
+
+    let mut lines: Vec<Line> = import.warnings
+        .iter()
+        .map(|warning| Line::from(Span::styled(format!("! {}", warning), theme.warning)))
+        .collect();
+    if !lines.is_empty() {
+        lines.push(Line::from(""));
+    }
+
+    let (title, help_text) = match import.stage {
+        ImportStage::Input => {
+            if import.text.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    "Paste Host blocks or CSV here, or type the path of a config or .csv file",
+                    theme.muted
+                )));
+            }
+            let text_lines: Vec<&str> = import.text.split('\n').collect();
+            let last = text_lines.len() - 1;
+            for (i, line) in text_lines.iter().enumerate() {
+                if i == last {
+                    lines.push(Line::from(format!("{}|", line)));
+                } else {
+                    lines.push(Line::from(line.to_string()));
+                }
+            }
+            ("Import Config Snippet", "Ctrl-D/F2: Parse | ESC: Cancel")
+        }
+        ImportStage::Review => {
+            let duplicate_of = |duplicate: &PossibleDuplicate| app.hosts.get(duplicate.existing);
+            for (i, candidate) in import.candidates.iter().enumerate() {
+                let (marker, marker_style) = match (candidate.conflict, candidate.resolution) {
+                    (_, ConflictResolution::Skip) => ("skip     ", theme.faint),
+                    (_, ConflictResolution::Merge) => ("merge    ", theme.changed),
+                    (false, _) => ("add      ", theme.added),
+                    (true, ConflictResolution::Overwrite) => ("overwrite", theme.removed),
+                    (true, _) => ("rename   ", theme.changed),
+                };
+                let conflict = match candidate.duplicate.as_ref().and_then(|duplicate| duplicate_of(duplicate).map(|host| (host, duplicate.reason))) {
+                    _ if candidate.conflict => " (name exists)".to_string(),
+                    Some((host, reason)) => format!(" (possible duplicate of {}: {})", host.name, reason.label()),
+                    None => String::new(),
+                };
+                // 名称含 ssh 特殊字符时附上简短提示
+                let problem = host_name_problem(&candidate.host.name)
+                    .map(|problem| format!(" (name {})", problem.split(':').next().unwrap_or_default()))
+                    .unwrap_or_default();
+                let text = format!("[{}] {}{}{}", marker, candidate.host.get_full_display_info(), conflict, problem);
+                let style = selector_style(&import.picker, i, marker_style);
+                lines.push(Line::from(Span::styled(text, style)));
+            }
+
+            // 选中的主机可能重复时，逐项对照已有主机和导入的主机（两边都没有的字段省略）
+            let selected = import.candidates.get(import.picker.selected);
+            if let Some((candidate, existing)) = selected.and_then(|candidate| Some((candidate, duplicate_of(candidate.duplicate.as_ref()?)?))) {
+                let value_width = (area.width.saturating_sub(2 + 16) / 2) as usize;
+                let row_text = |field: &str, left: &str, right: &str| {
+                    format!("{:<16}{:<value_width$}{}", truncate(field, 15), truncate(left, value_width.saturating_sub(1)), truncate(right, value_width))
+                };
+                let unset = |value: &str| if value.is_empty() { "-".to_string() } else { value.to_string() };
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(row_text("Field", "Existing", "Imported"), Style::default().add_modifier(Modifier::BOLD))));
+                for row in compare_hosts(existing, &candidate.host) {
+                    if row.left.is_empty() && row.right.is_empty() || row.field == "Visible" || row.field == "Row style" {
+                        continue;
+                    }
+                    let style = if row.same { theme.faint } else { theme.warning };
+                    lines.push(Line::from(Span::styled(row_text(&row.field, &unset(&row.left), &unset(&row.right)), style)));
+                }
+            }
+            ("Review Import", "↑↓/Tab: Select | a: Add (anyway) | s: Skip | o: Overwrite | r: Rename | m: Merge into existing | Enter: Stage | ESC: Back")
+        }
+        ImportStage::Descriptions => {
+            let count = |outcomes: &[DescriptionOutcome]| import.descriptions.iter().filter(|row| outcomes.contains(&row.outcome)).count();
+            let skipped = count(&[DescriptionOutcome::Unchanged, DescriptionOutcome::NotBlank, DescriptionOutcome::Duplicate, DescriptionOutcome::NotEditable]);
+            let mode = if import.overwrite { "overwrite existing descriptions" } else { "only fill blank descriptions" };
+            lines.push(Line::from(format!("Mode: {}", mode)));
+            lines.push(Line::from(format!(
+                "{} updated, {} skipped, {} unmatched",
+                count(&[DescriptionOutcome::Update]),
+                skipped,
+                count(&[DescriptionOutcome::Unmatched])
+            )));
+            lines.push(Line::from(""));
+
+            // 只渲染可见的行，选中行始终可见；过长的行截断而不换行
+            let width = area.width.saturating_sub(2) as usize;
+            let visible = (area.height as usize).saturating_sub(lines.len() + 4).max(1);
+            let offset = import.picker.selected.saturating_sub(visible - 1);
+            let value = |description: &Option<String>| description.clone().unwrap_or_else(|| "(none)".to_string());
+            for (i, row) in import.descriptions.iter().enumerate().skip(offset).take(visible) {
+                let outcome_style = match row.outcome {
+                    DescriptionOutcome::Update => theme.changed,
+                    DescriptionOutcome::Unmatched => theme.error,
+                    _ => theme.faint,
+                };
+                let change = match row.outcome {
+                    DescriptionOutcome::Update => format!("{} → {}", value(&row.current), value(&row.description)),
+                    DescriptionOutcome::Unmatched | DescriptionOutcome::Duplicate => value(&row.description),
+                    _ => value(&row.current),
+                };
+                let text = format!("[{:<9}] {}: {}", row.outcome.label(), row.name, change);
+                let style = selector_style(&import.picker, i, outcome_style);
+                lines.push(Line::from(Span::styled(truncate(&text, width), style)));
+            }
+            ("Import Descriptions", "↑↓: Scroll | o: Toggle fill blanks / overwrite | Enter: Stage | ESC: Back")
+        }
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let help_paragraph = Paragraph::new(help_text).style(theme.muted);
     f.render_widget(help_paragraph, help_area);
 }
 
+/// 弹窗的尺寸：先取终端宽高的百分比，再限制在最小和最大尺寸之间（最小尺寸可由内容决定），最后不超过终端
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PopupLimits {
+    pub percent_x: u16,
+    pub percent_y: u16,
+    pub min_width: u16,
+    pub min_height: u16,
+    pub max_width: u16,
+    pub max_height: u16,
+}
+
+/// 每次 +/- 调整弹窗大小时宽和高的变化量
+pub const POPUP_ZOOM_STEP: (u16, u16) = (8, 3);
+
+impl PopupLimits {
+    /// 默认限制：窄终端上不小于 44×8，超宽终端上不超过 140×50
+    pub const fn new(percent_x: u16, percent_y: u16) -> Self {
+        Self { percent_x, percent_y, min_width: 44, min_height: 8, max_width: 140, max_height: 50 }
+    }
+
+    /// 按内容放宽最小尺寸：最长一行加上边框和留白，行数加上边框和帮助行
+    pub fn fit(self, content_width: u16, content_height: u16) -> Self {
+        Self {
+            min_width: self.min_width.max(content_width.saturating_add(4)),
+            min_height: self.min_height.max(content_height.saturating_add(4)),
+            max_width: self.max_width.max(content_width.saturating_add(4)),
+            max_height: self.max_height.max(content_height.saturating_add(4)),
+            ..self
+        }
+    }
+}
+
+/// 在 area 中居中的弹窗区域；zoom 为 +/- 调整的次数，放大时可以超过最大尺寸，缩小时不小于最小尺寸
+pub fn popup_rect(limits: PopupLimits, zoom: i16, area: ratatui::layout::Rect) -> ratatui::layout::Rect {
+    let size = |total: u16, percent: u16, min: u16, max: u16, step: u16| {
+        let base = (total as u32 * percent as u32 / 100) as u16;
+        let base = base.clamp(min, max.max(min)) as i32;
+        let zoomed = (base + zoom as i32 * step as i32).max(min as i32);
+        (zoomed.min(total as i32)).max(0) as u16
+    };
+    let width = size(area.width, limits.percent_x, limits.min_width, limits.max_width, POPUP_ZOOM_STEP.0);
+    let height = size(area.height, limits.percent_y, limits.min_height, limits.max_height, POPUP_ZOOM_STEP.1);
+    ratatui::layout::Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// 按终端百分比取弹窗区域，并套用默认的最小和最大尺寸
 fn centered_rect(
     percent_x: u16,
     percent_y: u16,
     r: ratatui::layout::Rect
 ) -> ratatui::layout::Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
+    popup_rect(PopupLimits::new(percent_x, percent_y), 0, r)
+}
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
+/// 弹窗内容的宽度（最长的一行）和行数
+fn content_size(lines: &[Line]) -> (u16, u16) {
+    let width = lines.iter().map(Line::width).max().unwrap_or(0);
+    (width.min(u16::MAX as usize) as u16, lines.len().min(u16::MAX as usize) as u16)
+}
+
+fn render_reference(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(view) = &app.reference else {
+        return;
+    };
+    let area = centered_rect(90, 85, f.size());
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let matches = view.matches();
+    let height = (area.height as usize).saturating_sub(4);
+    let lines: Vec<Line> = view.lines
+        .iter()
+        .enumerate()
+        .skip(view.scroll)
+        .take(height)
+        .map(|(index, line)| {
+            let mut style = if is_reference_heading(line) {
+                theme.accent.add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            // 高亮搜索匹配，当前匹配反色显示
+            if view.search.current == Some(index) {
+                style = theme.input;
+            } else if matches.binary_search(&index).is_ok() {
+                style = style.patch(theme.matched);
+            }
+            Line::from(Span::styled(line.as_str(), style))
+        })
+        .collect();
+    let title = format!("Reference ({}/{})", (view.scroll + 1).min(view.lines.len()), view.lines.len());
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+
+    let help_area = ratatui::layout::Rect {
+        x: area.x + 1,
+        y: area.bottom() - 2,
+        width: area.width - 2,
+        height: 1,
+    };
+    let search = &view.search;
+    let help_line = if search.editing {
+        Line::from(vec![
+            Span::styled(format!("/{}|", search.query), theme.warning),
+            Span::styled(format!("  {} match(es) | Enter: Jump | ESC: Cancel", matches.len()), theme.muted),
+        ])
+    } else if !search.query.is_empty() {
+        let position = search.current
+            .and_then(|line| matches.iter().position(|&m| m == line))
+            .map_or("-".to_string(), |i| (i + 1).to_string());
+        Line::from(vec![
+            Span::styled(format!("/{}  [{}/{}]", search.query, position, matches.len()), theme.warning),
+            Span::styled(" | n/N: Next/previous match | ESC: Clear search", theme.muted),
         ])
-        .split(popup_layout[1])[1]
+    } else {
+        Line::from(Span::styled("↑↓: Scroll | PgUp/PgDn: Fast scroll | Home/End: Top/bottom | /: Search | Enter/ESC: Close", theme.muted))
+    };
+    f.render_widget(Paragraph::new(help_line), help_area);
 }
 
-fn render_version_info(f: &mut Frame, _app: &App) {
-    let area = centered_rect(60, 50, f.size());
+fn render_version_info(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 70, f.size());
     f.render_widget(ratatui::widgets::Clear, area);
 
     let version_info = App::get_version_info();
     
-    let lines = vec![
+    let mut lines = vec![
         Line::from(Span::styled(
-            format!("{}", version_info.name.to_uppercase()),
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+            version_info.name.to_uppercase(),
+            theme.accent.add_modifier(Modifier::BOLD)
         )),
         Line::from(""),
         Line::from(Span::styled(
             format!("Version: {}", version_info.version),
-            Style::default().fg(Color::Green)
+            theme.success
         )),
         Line::from(""),
         Line::from(Span::styled(
             format!("Description: {}", version_info.description),
-            Style::default().fg(Color::White)
+            theme.bright
         )),
         Line::from(""),
         Line::from(Span::styled(
             format!("Authors: {}", version_info.authors),
-            Style::default().fg(Color::Yellow)
+            theme.warning
         )),
         Line::from(""),
         Line::from(Span::styled(
             format!("License: {}", version_info.license),
-            Style::default().fg(Color::Magenta)
+            theme.special
         )),
         Line::from(""),
         Line::from(Span::styled(
             format!("Repository: {}", version_info.repository),
-            Style::default().fg(Color::Blue)
+            theme.info
         )),
         Line::from(""),
         Line::from(Span::styled(
             "A Terminal User Interface for SSH connection management",
-            Style::default().fg(Color::Gray).add_modifier(Modifier::ITALIC)
+            theme.muted.add_modifier(Modifier::ITALIC)
         )),
+        Line::from(""),
     ];
+    // sshc 自己的文件所在目录，便于排查 XDG 变量是否生效
+    for kind in SshcDir::ALL {
+        let dir = sshc_dir(kind).map(|dir| dir.display().to_string()).unwrap_or_else(|| "unavailable".to_string());
+        lines.push(Line::from(Span::styled(format!("{} dir: {}", kind.label(), dir), theme.faint)));
+    }
 
     let paragraph = Paragraph::new(lines)
         .block(Block::default().borders(Borders::ALL).title("About"))
@@ -378,9 +3192,9 @@ fn render_version_info(f: &mut Frame, _app: &App) {
         width: area.width - 2,
         height: 1,
     };
-    let help_text = "Press any key to continue";
+    let help_text = "m: Metadata, options & settings reference | Enter/ESC: Close";
     let help_paragraph = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::Gray))
+        .style(theme.muted)
         .alignment(ratatui::layout::Alignment::Center);
     f.render_widget(help_paragraph, help_area);
 }
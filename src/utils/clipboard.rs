@@ -0,0 +1,66 @@
+use std::io::Write;
+use std::process::{ Command, Stdio };
+
+use crate::utils::{ Result, SshcError };
+
+/// 各平台常见的剪贴板命令，按顺序尝试
+const COPY_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// 读取剪贴板的命令，按顺序尝试
+const PASTE_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbpaste", &[]),
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+    ("powershell.exe", &["-NoProfile", "-Command", "Get-Clipboard"]),
+];
+
+/// 通过系统剪贴板命令复制文本，所有命令都不可用时返回错误
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    for (program, args) in COPY_COMMANDS {
+        let child = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        let Ok(mut child) = child else {
+            continue;
+        };
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        drop(child.stdin.take());
+
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    Err(SshcError::Config("No clipboard command found (pbcopy, wl-copy, xclip, xsel)".to_string()))
+}
+
+/// 通过系统剪贴板命令读取文本，所有命令都不可用时返回错误
+pub fn read_clipboard() -> Result<String> {
+    for (program, args) in PASTE_COMMANDS {
+        let output = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+
+        if let Ok(output) = output && output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n"));
+        }
+    }
+
+    Err(SshcError::Config("No clipboard command found (pbpaste, wl-paste, xclip, xsel)".to_string()))
+}
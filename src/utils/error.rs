@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub enum SshcError {
@@ -6,6 +7,13 @@ pub enum SshcError {
     Config(String),
     Terminal(String),
     Ssh(String),
+    /// 找不到 home 目录，附带依次尝试过的路径
+    ConfigNotFound { attempted: Vec<PathBuf> },
+    /// --home 或 $SSHC_HOME 指定的路径不是目录
+    HomeNotDirectory { source: &'static str, path: PathBuf },
+    Usage(String),
+    /// 配置文件在加载后被其他程序修改，需要重新加载或强制覆盖后才能保存
+    ChangedOnDisk(PathBuf),
 }
 
 impl fmt::Display for SshcError {
@@ -15,6 +23,23 @@ impl fmt::Display for SshcError {
             SshcError::Config(msg) => write!(f, "Config error: {}", msg),
             SshcError::Terminal(msg) => write!(f, "Terminal error: {}", msg),
             SshcError::Ssh(msg) => write!(f, "SSH error: {}", msg),
+            SshcError::ConfigNotFound { attempted } => {
+                write!(f, "Config error: no home directory found (tried ")?;
+                if attempted.is_empty() {
+                    write!(f, "nothing: set SSHC_HOME or pass --home")?;
+                } else {
+                    let paths: Vec<String> = attempted.iter().map(|p| p.display().to_string()).collect();
+                    write!(f, "{}", paths.join(", "))?;
+                }
+                write!(f, ")")
+            }
+            SshcError::HomeNotDirectory { source, path } => {
+                write!(f, "Config error: {} is set to {}, which is not a directory", source, path.display())
+            }
+            SshcError::Usage(msg) => write!(f, "{}", msg),
+            SshcError::ChangedOnDisk(path) => {
+                write!(f, "Config error: {} changed on disk since it was loaded; press r to reload it or O to overwrite it", path.display())
+            }
         }
     }
 }
@@ -29,7 +54,8 @@ impl From<std::io::Error> for SshcError {
 
 impl From<anyhow::Error> for SshcError {
     fn from(err: anyhow::Error) -> Self {
-        SshcError::Config(err.to_string())
+        // 经 anyhow 传递的 SshcError 保留原来的类型
+        err.downcast::<SshcError>().unwrap_or_else(|err| SshcError::Config(format!("{:#}", err)))
     }
 }
 
@@ -1,3 +1,7 @@
+pub mod clipboard;
 pub mod error;
+pub mod net;
 
-pub use error::*;
\ No newline at end of file
+pub use clipboard::*;
+pub use error::*;
+pub use net::*;
@@ -0,0 +1,67 @@
+use std::net::Ipv6Addr;
+
+/// 去掉 IPv6 地址外层的方括号："[2001:db8::1]" -> "2001:db8::1"
+pub fn unbracket(host: &str) -> &str {
+    host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host)
+}
+
+/// 是否为 IPv6 字面量（可带方括号和 %zone 后缀）
+pub fn is_ipv6_literal(host: &str) -> bool {
+    let host = unbracket(host);
+    let address = host.split_once('%').map_or(host, |(address, _)| address);
+    address.parse::<Ipv6Addr>().is_ok()
+}
+
+/// 组合成 host:port，IPv6 地址加方括号
+pub fn format_host_port(host: &str, port: &str) -> String {
+    if is_ipv6_literal(host) {
+        format!("[{}]:{}", unbracket(host), port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// 拆分 host[:port]。支持 "[v6]:port"、"[v6]"；不带方括号的 IPv6 地址整体作为主机，不拆端口
+pub fn split_host_port(value: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = value.strip_prefix('[') && let Some((host, after)) = rest.split_once(']') {
+        return match after.strip_prefix(':') {
+            Some(port) if !port.is_empty() => (host, Some(port)),
+            _ => (host, None),
+        };
+    }
+    match value.split_once(':') {
+        Some((host, port)) if !port.contains(':') && !port.is_empty() => (host, Some(port)),
+        _ => (value, None),
+    }
+}
+
+/// 把 Port 的值解析为端口号：数字直接解析，否则与 ssh 一样按服务名查系统的 services 数据库
+pub fn resolve_port(value: &str) -> Option<u16> {
+    let value = value.trim();
+    if let Ok(port) = value.parse::<u16>() {
+        return (port != 0).then_some(port);
+    }
+    let services = std::fs::read_to_string(services_path()).ok()?;
+    lookup_service(&services, value)
+}
+
+fn services_path() -> std::path::PathBuf {
+    if cfg!(windows) {
+        let root = std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into());
+        std::path::Path::new(&root).join("System32").join("drivers").join("etc").join("services")
+    } else {
+        std::path::PathBuf::from("/etc/services")
+    }
+}
+
+/// 在 services 文件内容中查找 tcp 服务（名称或别名）的端口
+pub fn lookup_service(services: &str, name: &str) -> Option<u16> {
+    services.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let service = fields.next()?;
+        let (port, protocol) = fields.next()?.split_once('/')?;
+        let matches = service.eq_ignore_ascii_case(name) || fields.any(|alias| alias.eq_ignore_ascii_case(name));
+        (matches && protocol == "tcp").then(|| port.parse().ok()).flatten()
+    })
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ Settings, SshHost };
+use ssh_tui::core::{ action_for_key, actions_in, ActionMenu, ActionScope, App, AppMode, ChangeType, HostAction };
+use std::collections::HashSet;
+use std::fs;
+use tempfile::TempDir;
+
+fn app_with_hosts(dir: &TempDir) -> App {
+    let path = dir.path().join("config");
+    fs::write(&path, "# @folder: Work\nHost web\n    HostName 10.0.0.1\n    IdentityFile ~/.ssh/id_web\n").unwrap();
+    App::with_config_path(path, Settings::default()).unwrap()
+}
+
+fn labels(menu: &ActionMenu) -> Vec<String> {
+    menu.actions.iter().map(|binding| binding.key_label()).collect()
+}
+
+#[test]
+fn each_key_has_one_action_per_scope() {
+    for scope in [ActionScope::Normal, ActionScope::Config] {
+        let mut keys = HashSet::new();
+        for binding in actions_in(scope) {
+            assert!(keys.insert(binding.key), "{:?} bound twice", binding.key);
+            assert_eq!(action_for_key(scope, binding.key), Some(binding.action));
+        }
+    }
+    assert_eq!(action_for_key(ActionScope::Normal, KeyCode::Char('W')), Some(HostAction::CloseMaster));
+    assert_eq!(action_for_key(ActionScope::Config, KeyCode::Char('W')), None);
+}
+
+#[test]
+fn the_menu_lists_only_actions_that_apply() {
+    let mut host = SshHost::new("web".to_string());
+    let menu = ActionMenu::new(ActionScope::Normal, &host, false, false, false);
+    assert_eq!(labels(&menu), ["Enter", "u", "t", "g", "=", "w", "+", "Del"]);
+    assert_eq!(labels(&ActionMenu::new(ActionScope::Normal, &host, true, true, false)), ["Enter", "u", "t", "g", "=", "w", "W", "+"]);
+
+    host.disabled = true;
+    assert!(!labels(&ActionMenu::new(ActionScope::Normal, &host, false, false, false)).contains(&"Enter".to_string()));
+
+    // 临时主机可以保留但不能停用；没有 IdentityFile 时不能轮换密钥
+    host.disabled = false;
+    host.temporary = true;
+    let config = labels(&ActionMenu::new(ActionScope::Config, &host, false, false, false));
+    assert!(config.contains(&"P".to_string()) && !config.contains(&"x".to_string()) && !config.contains(&"K".to_string()));
+
+    // 派生主机总是可用；标签编辑器有自己的菜单项，只在有标记的主机时出现
+    assert!(config.contains(&"T".to_string()) && !config.contains(&"t".to_string()));
+    let marking = ActionMenu::new(ActionScope::Config, &host, false, false, true);
+    let entry = |key: char| marking.actions.iter().find(|binding| binding.key == KeyCode::Char(key)).map(|binding| binding.label);
+    assert_eq!(entry('T'), Some("Derive tunnel/SOCKS/jump host"));
+    assert_eq!(entry('t'), Some("Edit tags of marked hosts"));
+}
+
+#[test]
+fn menu_entries_run_with_enter_or_their_own_key() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_with_hosts(&dir);
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "web");
+
+    press(&mut app, KeyCode::Char('.'));
+    assert_eq!(app.mode, AppMode::ActionMenu);
+    let menu = app.action_menu.as_ref().unwrap();
+    assert_eq!(menu.host, "web");
+    assert!(labels(menu).contains(&"K".to_string()));
+    press(&mut app, KeyCode::Char('x'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.action_menu.is_none());
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Modified { new, .. }] if new.disabled));
+
+    // 第一项是编辑，Enter 执行
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::EditingHost);
+}
+
+#[test]
+fn the_menu_needs_a_host_and_closes_without_running_anything() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_with_hosts(&dir);
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('.'));
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.status.as_ref().unwrap().text.contains("Select a host"));
+
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('z'));
+    assert_eq!(app.mode, AppMode::ActionMenu);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+    press(&mut app, KeyCode::Char('.'));
+    press(&mut app, KeyCode::Char('.'));
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.pending_connect.is_none());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{ ctrl, press, select_host, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ parse_ssh_config_file, write_ssh_config_file };
+use ssh_tui::core::{ AppMode };
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @alias: billing-db, old-name-before-migration
+Host pg-eu-1
+    HostName 10.0.5.1
+
+Host web
+    HostName 10.0.0.1
+";
+
+#[test]
+fn aliases_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+
+    let hosts = parse_ssh_config_file(&path).unwrap();
+    assert_eq!(hosts[0].aliases, ["billing-db", "old-name-before-migration"]);
+
+    write_ssh_config_file(&path, &hosts).unwrap();
+    assert!(fs::read_to_string(&path).unwrap().contains("# @alias: billing-db, old-name-before-migration\nHost pg-eu-1"));
+}
+
+#[test]
+fn search_matches_aliases() {
+    let (_dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char('/'));
+    for c in "billing".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+
+    assert_eq!(app.tree_items.len(), 1);
+    assert_eq!(app.get_selected_host().unwrap().name, "pg-eu-1");
+}
+
+#[test]
+fn aliases_are_edited_in_the_form_and_shown_in_the_diff() {
+    let (_dir, mut app) = setup(CONFIG);
+    select_host(&mut app, "web");
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    for _ in 0..8 {
+        press(&mut app, KeyCode::Down);
+    }
+    for c in "www, frontend".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    ctrl(&mut app, 's');
+    let host = app.hosts.iter().find(|h| h.name == "web").unwrap();
+    assert_eq!(host.aliases, ["www", "frontend"]);
+
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    assert!(app.review_lines.iter().any(|line| line == "+ # @alias: www, frontend"));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ create_backup, format_utc, list_backups, parse_ssh_config_from_str, prune_backups, Settings };
+use ssh_tui::core::{ backup_restore_changes, App, AppMode, ChangeType };
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const DAY: u64 = 24 * 60 * 60;
+const NOW: u64 = 1_700_000_000;
+
+fn names(dir: &Path) -> Vec<String> {
+    list_backups(dir).iter().map(|backup| backup.path.file_name().unwrap().to_string_lossy().into_owned()).collect()
+}
+
+/// 在目录中放入时间戳依次为 NOW - days 天的假备份
+fn fake_backups(dir: &Path, days: &[u64]) {
+    fs::create_dir_all(dir).unwrap();
+    for day in days {
+        fs::write(dir.join(format!("config-{}.bak", NOW - day * DAY)), format!("Host day-{}\n", day)).unwrap();
+    }
+}
+
+#[test]
+fn retention_keeps_the_newest_and_drops_old_ones() {
+    let dir = TempDir::new().unwrap();
+    let backups = dir.path().join("backups");
+    fake_backups(&backups, &[0, 1, 2, 10, 40]);
+    fs::write(backups.join("notes.txt"), "not a backup").unwrap();
+
+    assert!(prune_backups(&backups, 0, 0, NOW).unwrap().is_empty());
+    assert_eq!(prune_backups(&backups, 0, 30, NOW).unwrap().len(), 1);
+    let removed = prune_backups(&backups, 2, 0, NOW).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert_eq!(names(&backups), [format!("config-{}.bak", NOW), format!("config-{}.bak", NOW - DAY)]);
+    assert!(backups.join("notes.txt").exists());
+    assert_eq!(format_utc(NOW), "2023-11-14 22:13");
+}
+
+#[test]
+fn backups_are_skipped_when_nothing_changed_and_ordered_within_a_second() {
+    let dir = TempDir::new().unwrap();
+    let backups = dir.path().join("backups");
+    let config = dir.path().join("config");
+    assert_eq!(create_backup(&backups, &config, NOW).unwrap(), None);
+
+    fs::write(&config, "Host a\n").unwrap();
+    assert!(create_backup(&backups, &config, NOW).unwrap().is_some());
+    assert_eq!(create_backup(&backups, &config, NOW).unwrap(), None);
+    for host in ["b", "c"] {
+        fs::write(&config, format!("Host {}\n", host)).unwrap();
+        create_backup(&backups, &config, NOW).unwrap();
+    }
+    assert_eq!(names(&backups), [format!("config-{}-3.bak", NOW), format!("config-{}-2.bak", NOW), format!("config-{}.bak", NOW)]);
+    assert_eq!(list_backups(&backups)[0].size, "Host c\n".len() as u64);
+}
+
+#[test]
+fn restore_changes_cover_added_removed_and_modified_hosts() {
+    let backup = parse_ssh_config_from_str("Host a\n    Port 22\n\nHost b\n\nHost c\n");
+    let mut current = parse_ssh_config_from_str("Host a\n    Port 2222\n\nHost c\n\nHost d\n");
+    let mut included = parse_ssh_config_from_str("Host e\n")[0].clone();
+    included.source = Some("/home/me/.ssh/config.d/work".into());
+    current.push(included);
+
+    let changes = backup_restore_changes(&backup, &current);
+    assert!(matches!(&changes[..], [
+        ChangeType::Deleted(d),
+        ChangeType::Modified { old, new },
+        ChangeType::Added(b),
+    ] if d.name == "d" && old.port.as_deref() == Some("2222") && new.port.as_deref() == Some("22") && b.name == "b"));
+
+    assert!(backup_restore_changes(&backup, &backup).is_empty());
+}
+
+#[test]
+fn saving_backs_up_and_a_restore_goes_through_review() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    let original = "Host web\n    HostName 10.0.0.1\n\nHost db\n    HostName 10.0.0.2\n";
+    fs::write(&config, original).unwrap();
+    let settings = Settings { backup_keep: 2, ..Settings::default() };
+    let mut app = App::with_config_path(config.clone(), settings).unwrap();
+    let backups = dir.path().join("backups");
+    app.backup_dir = Some(backups.clone());
+
+    press(&mut app, KeyCode::Char('e'));
+    for _ in 0..3 {
+        press(&mut app, KeyCode::Char('x'));
+        app.apply_changes().unwrap();
+    }
+    // 每次保存前备份一次，只保留最新的两个；最早的原始内容已被删除
+    let listed = list_backups(&backups);
+    assert_eq!(listed.len(), 2);
+    assert_ne!(fs::read_to_string(&listed[1].path).unwrap(), original);
+    assert!(fs::read_to_string(&config).unwrap().contains("#sshc-disabled#"));
+
+    press(&mut app, KeyCode::Char('B'));
+    assert_eq!(app.mode, AppMode::Backups);
+    let preview = app.backups.as_ref().unwrap().preview.clone().unwrap();
+    assert!(matches!(&preview[..], [ChangeType::Modified { old, new }] if old.disabled && !new.disabled));
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 1);
+    assert!(app.hosts.iter().all(|host| !host.disabled));
+    // 只是暂存，文件在审阅保存前保持不变
+    assert!(fs::read_to_string(&config).unwrap().contains("#sshc-disabled#"));
+}
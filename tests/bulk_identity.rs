@@ -0,0 +1,70 @@
+mod common;
+
+use common::{ press, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::config::Settings;
+use ssh_tui::core::{ App, AppMode, ChangeType };
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: deploy
+Host a
+    IdentityFile ~/.ssh/deploy_old
+
+# @folder: deploy
+Host b
+    IdentityFile ~/.ssh/alice_personal
+
+# @folder: deploy
+Host c
+
+Host outside
+    IdentityFile ~/.ssh/deploy_old
+";
+
+fn open_bulk_identity(dir: &TempDir) -> App {
+    let path = dir.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+    press(&mut app, KeyCode::Char('e'));
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('I'));
+    assert_eq!(app.mode, AppMode::BulkIdentity);
+    app
+}
+
+fn identity(app: &App, name: &str) -> Option<String> {
+    app.hosts.iter().find(|h| h.name == name).unwrap().identity_file.clone()
+}
+
+#[test]
+fn replaces_identity_for_every_host_in_folder() {
+    let dir = TempDir::new().unwrap();
+    let mut app = open_bulk_identity(&dir);
+    type_text(&mut app, "~/.ssh/deploy_new");
+    assert_eq!(app.bulk_identity_targets().len(), 3);
+
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.pending_changes.len(), 3);
+    assert!(app.pending_changes.iter().all(|c| matches!(c, ChangeType::Modified { .. })));
+    assert_eq!(identity(&app, "c").as_deref(), Some("~/.ssh/deploy_new"));
+    assert_eq!(identity(&app, "outside").as_deref(), Some("~/.ssh/deploy_old"));
+}
+
+#[test]
+fn only_when_filter_skips_personal_keys() {
+    let dir = TempDir::new().unwrap();
+    let mut app = open_bulk_identity(&dir);
+    type_text(&mut app, "~/.ssh/deploy_new");
+    press(&mut app, KeyCode::Tab);
+    type_text(&mut app, "~/.ssh/deploy_old");
+
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.pending_changes.len(), 1);
+    assert_eq!(identity(&app, "a").as_deref(), Some("~/.ssh/deploy_new"));
+    assert_eq!(identity(&app, "b").as_deref(), Some("~/.ssh/alice_personal"));
+    assert_eq!(identity(&app, "c"), None);
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{ ctrl, press, setup, type_text };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::core::{ fuzzy_score, ActionScope, App, AppCommand, AppMode, HostAction, PaletteAction, TreeItem, COMMANDS, HOST_ACTIONS };
+use ssh_tui::ui::render;
+
+const CONFIG: &str = "\
+# @folder: work
+Host api
+    HostName 10.0.0.7
+
+Host home
+    HostName 192.168.1.2
+";
+
+fn select_row(app: &mut App, matches: impl Fn(&App, &TreeItem) -> bool) {
+    let row = app.tree_items.iter().position(|item| matches(app, item)).unwrap();
+    app.list_state.select(Some(row));
+}
+
+/// 面板中某项是否可用；不在面板中时为 None
+fn enabled(app: &App, action: PaletteAction) -> Option<bool> {
+    let palette = app.command_palette.as_ref().unwrap();
+    palette.entries.iter().find(|entry| entry.action == action).map(|entry| entry.enabled)
+}
+
+fn labels(app: &App) -> Vec<&'static str> {
+    let palette = app.command_palette.as_ref().unwrap();
+    palette.matches.iter().map(|&index| palette.entries[index].label).collect()
+}
+
+#[test]
+fn fuzzy_matching_prefers_word_starts_and_runs() {
+    assert_eq!(fuzzy_score("", "Quit"), Some(0));
+    assert!(fuzzy_score("rld cfg", "Reload config from disk").is_some());
+    assert!(fuzzy_score("RELOAD", "Reload config from disk").is_some());
+    assert_eq!(fuzzy_score("xyz", "Reload config from disk"), None);
+    // 字符必须按顺序出现
+    assert_eq!(fuzzy_score("gifnoc", "Reload config from disk"), None);
+    // 单词开头的匹配优先于单词中间的匹配
+    assert!(fuzzy_score("host", "Toggle hidden hosts") > fuzzy_score("host", "Ghosts"));
+    assert!(fuzzy_score("exp", "Export config snippet") > fuzzy_score("exp", "Jump to next pending change"));
+    assert!(fuzzy_score("th", "Toggle hidden hosts") > fuzzy_score("th", "Search hosts"));
+}
+
+#[test]
+fn availability_follows_the_selection_and_state() {
+    let (_dir, mut app) = setup(CONFIG);
+    select_row(&mut app, |_, item| matches!(item, TreeItem::Folder { .. }));
+    press(&mut app, KeyCode::Char(':'));
+    assert_eq!(app.mode, AppMode::CommandPalette);
+    // 文件夹行：主机操作不可用，连接整个文件夹可用
+    assert_eq!(enabled(&app, PaletteAction::Host(HostAction::Connect)), Some(false));
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::GroupConnect)), Some(true));
+    // 没有待保存变更、队列为空、没有运行过检查
+    for command in [AppCommand::NextChange, AppCommand::ClearQueue, AppCommand::StartQueue, AppCommand::NextFinding] {
+        assert_eq!(enabled(&app, PaletteAction::Command(command)), Some(false), "{:?}", command);
+    }
+    // 配置管理中的命令不出现在主列表的面板中
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::AddHost)), None);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+
+    select_row(&mut app, |app, item| matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == "api"));
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char(':'));
+    assert_eq!(enabled(&app, PaletteAction::Host(HostAction::Connect)), Some(true));
+    assert_eq!(enabled(&app, PaletteAction::Host(HostAction::CloseMaster)), Some(false));
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::GroupConnect)), Some(false));
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::ClearQueue)), Some(true));
+    press(&mut app, KeyCode::Esc);
+
+    // 配置管理：有待保存变更后才能在变更间跳转
+    press(&mut app, KeyCode::Char('e'));
+    ctrl(&mut app, 'p');
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::Quit)), None);
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::NextChange)), Some(false));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Char(':'));
+    assert_eq!(enabled(&app, PaletteAction::Command(AppCommand::NextChange)), Some(true));
+}
+
+#[test]
+fn the_selection_runs_and_unavailable_entries_stay_put() {
+    let (_dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "hidden");
+    assert_eq!(labels(&app)[0], "Toggle hidden hosts");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!((app.mode, app.show_hidden), (AppMode::Normal, true));
+
+    // 不可用的项排在后面；选中它按 Enter 只提示，面板保持打开
+    select_row(&mut app, |app, item| matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == "api"));
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "queue");
+    assert_eq!(labels(&app), ["Add to / remove from queue", "Start connection queue", "Clear connection queue"]);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::CommandPalette);
+    assert_eq!(app.status.as_ref().map(|status| status.text.as_str()), Some("Not available here: Start connection queue"));
+    type_text(&mut app, "zzz");
+    assert!(labels(&app).is_empty());
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::CommandPalette);
+    for _ in 0.."queuezzz".len() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    assert_eq!(labels(&app).len(), app.command_palette.as_ref().unwrap().entries.len());
+    press(&mut app, KeyCode::Esc);
+
+    // 配置管理中从面板新建主机
+    press(&mut app, KeyCode::Char('e'));
+    app.list_state.select(None);
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "add host");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::EditingHost);
+}
+
+#[test]
+fn bindings_come_from_the_registry_and_are_shown() {
+    // 每个界面中一个按键只对应一个操作
+    for scope in [ActionScope::Normal, ActionScope::Config] {
+        let mut keys: Vec<(String, bool)> = HOST_ACTIONS
+            .iter()
+            .filter(|binding| binding.scope == scope)
+            .map(|binding| (binding.key_label(), false))
+            .chain(COMMANDS.iter().filter(|binding| binding.scope == scope).map(|binding| (binding.key_label(), binding.ctrl)))
+            .collect();
+        let total = keys.len();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), total, "{:?}", scope);
+    }
+
+    let (_dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char(':'));
+    type_text(&mut app, "zen");
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect())
+        .collect();
+    let row = rows.iter().find(|row| row.contains("Toggle zen mode")).unwrap();
+    // 按键靠右贴着边框
+    assert!(row.contains("Ctrl-Z│"), "{}", rows.join("\n"));
+    assert!(rows.iter().any(|row| row.contains("> zen_")));
+}
@@ -0,0 +1,73 @@
+//! 集成测试共用的辅助函数，测试文件以 `mod common;` 引入。
+//! 每个测试文件单独编译，用不到的函数不算死代码
+#![allow(dead_code)]
+
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ set_home_override, Settings };
+use ssh_tui::core::{ App, TreeItem };
+use ssh_tui::ui::render;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::OnceLock;
+use tempfile::TempDir;
+
+pub fn press(app: &mut App, code: KeyCode) {
+    app.handle_key(KeyEvent::from(code)).unwrap();
+}
+
+/// Ctrl 加字符键
+pub fn ctrl(app: &mut App, c: char) {
+    app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)).unwrap();
+}
+
+pub fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        press(app, KeyCode::Char(c));
+    }
+}
+
+/// 临时目录中的配置文件路径
+pub fn config_path(dir: &TempDir) -> PathBuf {
+    dir.path().join("config")
+}
+
+/// 把 content 写入临时目录中的配置文件，用默认设置创建界面；TempDir 需保留到测试结束
+pub fn setup(content: &str) -> (TempDir, App) {
+    setup_with(content, Settings::default())
+}
+
+pub fn setup_with(content: &str, settings: Settings) -> (TempDir, App) {
+    let dir = TempDir::new().unwrap();
+    let path = config_path(&dir);
+    fs::write(&path, content).unwrap();
+    let app = App::with_config_path(path, settings).unwrap();
+    (dir, app)
+}
+
+/// 把 home 指向临时目录，历史记录等文件不会读写真实 home
+pub fn isolated_home() -> &'static Path {
+    static HOME: OnceLock<TempDir> = OnceLock::new();
+    let home = HOME.get_or_init(|| TempDir::new().unwrap()).path();
+    set_home_override(home.to_path_buf());
+    home
+}
+
+/// 按给定终端大小渲染一帧，返回每一行的文字
+pub fn screen_rows(app: &mut App, width: u16, height: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| (0..width).map(|x| buffer.get(x, y).symbol.clone()).collect())
+        .collect()
+}
+
+/// 在列表中选中名为 name 的主机（主机必须在当前列表中）
+pub fn select_host(app: &mut App, name: &str) {
+    let row = app.tree_items
+        .iter()
+        .position(|item| matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == name))
+        .unwrap();
+    app.list_state.select(Some(row));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ compare_hosts, parse_ssh_config_from_str };
+use ssh_tui::core::{ App, AppMode };
+use ssh_tui::ui::render;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: envs
+# @tags: web
+Host staging
+    HostName staging.example.com
+    User deploy
+    ProxyJump bastion
+    LocalForward 8080 localhost:80
+    ServerAliveInterval 30
+
+# @folder: envs
+# @tags: web, prod
+Host prod
+    HostName prod.example.com
+    User deploy
+    LocalForward 8080 localhost:80
+    LocalForward 8443 localhost:443
+    IdentitiesOnly yes
+    ServerAliveInterval 30
+";
+
+fn setup() -> (TempDir, App) {
+    let (dir, mut app) = common::setup(CONFIG);
+    // 第一行是文件夹 envs，其下按名称排序：prod、staging
+    app.list_state.select(Some(1));
+    (dir, app)
+}
+
+fn screen(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..30).map(|y| (0..120).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn rows_cover_fields_metadata_and_the_union_of_options() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let rows = compare_hosts(&hosts[0], &hosts[1]);
+    let fields: Vec<&str> = rows.iter().map(|row| row.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        [
+            "Host", "Display name", "Folder", "Description", "Visible", "Tags", "Aliases", "Row style", "Env", "Config file",
+            "HostName", "User", "Port", "IdentityFile", "IdentitiesOnly", "LocalForward", "ProxyJump", "ServerAliveInterval",
+        ]
+    );
+    let differing: Vec<&str> = rows.iter().filter(|row| !row.same).map(|row| row.field.as_str()).collect();
+    assert_eq!(differing, ["Host", "Tags", "HostName", "IdentitiesOnly", "LocalForward", "ProxyJump"]);
+
+    let row = |field: &str| rows.iter().find(|row| row.field == field).unwrap();
+    assert_eq!((row("ProxyJump").left.as_str(), row("ProxyJump").right.as_str()), ("bastion", ""));
+    assert_eq!(row("LocalForward").right, "8080 localhost:80, 8443 localhost:443");
+    assert_eq!((row("User").left.as_str(), row("User").same), ("deploy", true));
+    assert_eq!((row("Port").left.as_str(), row("Port").same), ("", true));
+}
+
+#[test]
+fn a_host_compared_with_itself_has_no_differences() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert!(compare_hosts(&hosts[1], &hosts[1]).iter().all(|row| row.same));
+}
+
+#[test]
+fn equals_marks_then_opens_the_comparison() {
+    let (_dir, mut app) = setup();
+    press(&mut app, KeyCode::Char('='));
+    assert_eq!(app.compare_mark.as_deref(), Some("prod"));
+    assert!(screen(&mut app).contains("prod.example.com) [=]"));
+
+    // 在同一台主机上再按一次取消标记
+    press(&mut app, KeyCode::Char('='));
+    assert_eq!(app.compare_mark, None);
+    assert_eq!(app.status.as_ref().unwrap().text, "Comparison mark cleared");
+
+    press(&mut app, KeyCode::Char('='));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('='));
+    assert_eq!(app.mode, AppMode::CompareHosts);
+    assert_eq!(app.compare_mark, None);
+    let view = app.comparison.as_ref().unwrap();
+    assert_eq!((view.left.as_str(), view.right.as_str()), ("prod", "staging"));
+
+    let shown = screen(&mut app);
+    assert!(shown.contains("Compare: prod ↔ staging (6 differ)"));
+    assert!(shown.contains("HostName"));
+
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.comparison.is_none());
+}
+
+#[test]
+fn long_comparisons_scroll() {
+    let (_dir, mut app) = setup();
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('='));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('='));
+    assert_eq!(app.mode, AppMode::CompareHosts);
+
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(app.comparison.as_ref().unwrap().scroll, 10);
+    let shown = screen(&mut app);
+    assert!(!shown.contains("Display name"));
+    assert!(shown.contains("ServerAliveInterval"));
+    for _ in 0..10 {
+        press(&mut app, KeyCode::Down);
+    }
+    assert_eq!(app.comparison.as_ref().unwrap().scroll, 17);
+
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+}
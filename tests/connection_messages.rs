@@ -0,0 +1,34 @@
+use ssh_tui::config::{ Settings, SshHost };
+use ssh_tui::core::{ connecting_message, disconnected_message };
+
+fn host() -> SshHost {
+    let mut host = SshHost::new("web-prod".to_string());
+    host.hostname = Some("10.0.4.2".to_string());
+    host
+}
+
+#[test]
+fn messages_are_on_by_default_and_can_be_disabled() {
+    assert!(Settings::default().connection_messages);
+    let settings: Settings = toml::from_str("connection_messages = false").unwrap();
+    assert!(!settings.connection_messages);
+}
+
+#[test]
+fn connecting_message_names_the_host_and_target() {
+    let mut host = host();
+    assert_eq!(connecting_message(&host, None), "Connecting to web-prod (10.0.4.2) …");
+
+    host.user = Some("deploy".to_string());
+    host.port = Some("2222".to_string());
+    assert_eq!(connecting_message(&host, None), "Connecting to web-prod (deploy@10.0.4.2 port 2222) …");
+    // ssh -l 临时改用的用户优先
+    assert_eq!(connecting_message(&host, Some("root")), "Connecting to web-prod (root@10.0.4.2 port 2222) …");
+
+    assert_eq!(connecting_message(&SshHost::new("bare".to_string()), None), "Connecting to bare …");
+}
+
+#[test]
+fn disconnected_message_asks_for_a_key() {
+    assert_eq!(disconnected_message("web-prod"), "Disconnected from web-prod — press any key");
+}
@@ -0,0 +1,88 @@
+use ssh_tui::config::{ parse_ssh_config_from_str, serialize_host, Connector };
+use ssh_tui::core::{ connect_command, display_command, render_connector, split_command_words, uses_ssh };
+
+fn words(list: &[&str]) -> Vec<String> {
+    list.iter().map(|word| word.to_string()).collect()
+}
+
+fn connector(name: &str, command: &str) -> Connector {
+    Connector { name: name.to_string(), command: command.to_string() }
+}
+
+const CONFIG: &str = "\
+# @connector: telnet
+Host switch-01
+    HostName 10.0.8.2
+    Port 23
+
+# @connector: mosh
+Host dev
+    HostName dev.example.com
+
+Host web
+";
+
+#[test]
+fn commands_are_split_like_a_shell_would() {
+    assert_eq!(split_command_words("  telnet  {host}\t{port} ").unwrap(), words(&["telnet", "{host}", "{port}"]));
+    assert_eq!(
+        split_command_words(r#"sh -c 'exec "$0"' "a \"b\" \c" x\ y ''"#).unwrap(),
+        words(&["sh", "-c", r#"exec "$0""#, r#"a "b" \c"#, "x y", ""])
+    );
+    assert_eq!(split_command_words("telnet 'host").unwrap_err(), "unterminated single quote");
+    assert_eq!(split_command_words("telnet \"host").unwrap_err(), "unterminated double quote");
+    assert_eq!(split_command_words("telnet host\\").unwrap_err(), "trailing backslash");
+}
+
+#[test]
+fn placeholders_are_filled_in_after_splitting() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let switch = &hosts[0];
+    let telnet = connector("telnet", "telnet {host} {port}");
+    assert_eq!(render_connector(&telnet, switch, None).unwrap(), words(&["telnet", "10.0.8.2", "23"]));
+
+    // 值中的空格不会拆出新参数，不是占位符的花括号原样保留
+    let mut spaced = switch.clone();
+    spaced.user = Some("ops team".to_string());
+    let echo = connector("echo", "echo {user}@{name} '{}' {not-a-placeholder}");
+    assert_eq!(render_connector(&echo, &spaced, None).unwrap(), words(&["echo", "ops team@switch-01", "{}", "{not-a-placeholder}"]));
+    assert_eq!(render_connector(&echo, &spaced, Some("root")).unwrap()[1], "root@switch-01");
+
+    assert_eq!(
+        render_connector(&echo, switch, None).unwrap_err(),
+        "Connector 'echo' needs {user}, but switch-01 has no User set"
+    );
+    assert_eq!(
+        render_connector(&connector("bad", "telnet {hostname}"), switch, None).unwrap_err(),
+        "Connector 'bad' uses unknown placeholder {hostname} (use {host}, {port}, {user}, {name})"
+    );
+    assert_eq!(render_connector(&connector("none", "  "), switch, None).unwrap_err(), "Connector 'none' has an empty command");
+}
+
+#[test]
+fn hosts_choose_ssh_mosh_or_a_configured_connector() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let (switch, dev, web) = (&hosts[0], &hosts[1], &hosts[2]);
+    let connectors = [connector("telnet", "telnet {host} {port}")];
+
+    assert!(uses_ssh(web) && !uses_ssh(switch));
+    assert_eq!(connect_command(web, Some("root"), &connectors).unwrap(), words(&["ssh", "-l", "root", "web"]));
+    assert_eq!(connect_command(dev, Some("root"), &connectors).unwrap(), words(&["mosh", "root@dev"]));
+    assert_eq!(connect_command(switch, None, &connectors).unwrap(), words(&["telnet", "10.0.8.2", "23"]));
+    assert!(connect_command(switch, None, &[]).unwrap_err().starts_with("Unknown connector 'telnet'"));
+
+    // 设置中的 mosh 替换内置的
+    let custom = [connector("mosh", "mosh --predict=always {name}")];
+    assert_eq!(display_command(&connect_command(dev, None, &custom).unwrap()), "mosh --predict=always dev");
+    assert_eq!(display_command(&words(&["sh", "-c", "echo it's", ""])), r"sh -c 'echo it'\''s' ''");
+}
+
+#[test]
+fn the_connector_comment_survives_a_round_trip() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert_eq!(hosts[0].connector.as_deref(), Some("telnet"));
+    assert_eq!(hosts[2].connector, None);
+    let written = serialize_host(&hosts[0], true).unwrap();
+    assert!(written.starts_with("# @connector: telnet\nHost switch-01\n"), "{}", written);
+    assert_eq!(parse_ssh_config_from_str(&written)[0], hosts[0]);
+}
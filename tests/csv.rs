@@ -0,0 +1,143 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::cli::{ parse_args, CliCommand };
+use ssh_tui::config::{ export_config_csv, export_csv, parse_csv, parse_ssh_config_with_warnings, serialize_host, Settings, SshHost };
+use ssh_tui::core::{ App, AppMode, ChangeType, ConflictResolution };
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn sample_host() -> SshHost {
+    let mut host = SshHost::new("web".to_string());
+    host.display_name = Some("Web, primary".to_string());
+    host.hostname = Some("10.0.0.1".to_string());
+    host.user = Some("deploy".to_string());
+    host.port = Some("2222".to_string());
+    host.identity_file = Some("~/.ssh/id_web".to_string());
+    host.folder = Some("prod".to_string());
+    host.description = Some("Says \"hi\"".to_string());
+    host.tags = vec!["nginx".to_string(), "eu".to_string()];
+    host
+}
+
+#[test]
+fn export_round_trips_through_import() {
+    let csv = export_csv(&[sample_host()]).unwrap();
+    assert!(csv.starts_with("name,display name,hostname,user,port,identity file,folder,description,tags\n"));
+
+    assert_eq!(parse_csv(&csv).unwrap(), (vec![sample_host()], Vec::new()));
+}
+
+#[test]
+fn formula_like_cells_are_exported_as_text() {
+    let mut host = sample_host();
+    host.name = "-web".to_string();
+    host.display_name = Some("=HYPERLINK(\"http://x\")".to_string());
+    host.description = Some("@risk, +1".to_string());
+    host.tags = vec!["-legacy".to_string()];
+    let csv = export_csv(&[host.clone()]).unwrap();
+
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.starts_with("'-web,\"'=HYPERLINK(\"\"http://x\"\")\",10.0.0.1,"), "{}", row);
+    assert!(row.ends_with(",\"'@risk, +1\",'-legacy"), "{}", row);
+    // 导入时去掉前缀
+    assert_eq!(parse_csv(&csv).unwrap(), (vec![host], Vec::new()));
+}
+
+#[test]
+fn excel_bom_quoted_commas_and_column_order_are_handled() {
+    let csv = "\u{feff}Hostname,Name,Description,Tags,Extra\r\n\
+               10.0.0.5,db,\"primary, do not reboot\",\"pg; eu\",ignored\r\n\
+               ,,,,\r\n";
+    let (hosts, warnings) = parse_csv(csv).unwrap();
+    assert!(warnings.is_empty());
+
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].name, "db");
+    assert_eq!(hosts[0].hostname.as_deref(), Some("10.0.0.5"));
+    assert_eq!(hosts[0].description.as_deref(), Some("primary, do not reboot"));
+    assert_eq!(hosts[0].tags, ["pg", "eu"]);
+    assert_eq!(hosts[0].port, None);
+}
+
+#[test]
+fn missing_name_column_is_an_error() {
+    assert!(parse_csv("hostname,user\n10.0.0.1,root\n").is_err());
+}
+
+#[test]
+fn rows_that_would_add_config_lines_are_skipped() {
+    let csv = "name,hostname,user,port,folder\n\
+               ok,10.0.0.1,root,ssh,Team A\n\
+               evil,\"10.0.0.2\n    ProxyCommand touch /tmp/pwned\",root,,\n\
+               crlf,10.0.0.3,\"root\r\nProxyJump x\",,\n\
+               two words,10.0.0.4,,,\n\
+               port,10.0.0.5,,\"22 -oProxyCommand=x\",\n\
+               folder,10.0.0.6,,,\"a\nb\"\n";
+    let (hosts, warnings) = parse_csv(csv).unwrap();
+
+    // 文件夹可以含空格，但不能含控制字符
+    assert_eq!(hosts.iter().map(|host| host.name.as_str()).collect::<Vec<_>>(), ["ok"]);
+    assert_eq!(hosts[0].folder.as_deref(), Some("Team A"));
+    assert_eq!(warnings, [
+        "CSV line 3: 'hostname' contains control characters, row skipped",
+        "CSV line 4: 'user' contains control characters, row skipped",
+        "CSV line 5: 'name' contains whitespace, row skipped",
+        "CSV line 6: 'port' contains whitespace, row skipped",
+        "CSV line 7: 'folder' contains control characters, row skipped",
+    ]);
+}
+
+#[test]
+fn tags_survive_the_config_file() {
+    let (hosts, _) = parse_ssh_config_with_warnings(&serialize_host(&sample_host(), true).unwrap());
+    assert_eq!(hosts[0].tags, ["nginx", "eu"]);
+}
+
+#[test]
+fn csv_import_uses_conflict_chooser_and_stages_changes() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    fs::write(&config, "Host web\n    HostName 10.0.0.9\n").unwrap();
+    let file = dir.path().join("inventory.csv");
+    fs::write(&file, "name,hostname\nweb,10.0.0.1\nnew,10.0.0.2\n").unwrap();
+    let mut app = App::with_config_path(config.clone(), Settings::default()).unwrap();
+
+    app.start_import_file(&file);
+    assert_eq!(app.mode, AppMode::ImportSnippet);
+    let import = app.import.as_ref().unwrap();
+    assert_eq!(import.candidates[0].resolution, ConflictResolution::Skip);
+    assert_eq!(import.candidates[1].resolution, ConflictResolution::Add);
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('r'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 2);
+    assert!(app.pending_changes.iter().all(|c| matches!(c, ChangeType::Added(_))));
+    assert!(app.hosts.iter().any(|h| h.name == "web-2"));
+    // 确认保存前不写文件
+    assert!(!fs::read_to_string(&config).unwrap().contains("10.0.0.2"));
+}
+
+#[test]
+fn csv_subcommands_parse() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|s| s.to_string())).unwrap().command;
+
+    assert_eq!(args(&["export-csv"]), Some(CliCommand::ExportCsv(None)));
+    assert_eq!(args(&["export-csv", "out.csv"]), Some(CliCommand::ExportCsv(Some(PathBuf::from("out.csv")))));
+    assert_eq!(args(&["import-csv", "in.csv", "--home", "/tmp"]), Some(CliCommand::ImportCsv(PathBuf::from("in.csv"))));
+    assert!(parse_args(["import-csv".to_string()]).is_err());
+}
+
+#[test]
+fn cli_export_includes_hosts_from_included_files() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    fs::create_dir(dir.path().join("config.d")).unwrap();
+    fs::write(dir.path().join("config.d/work"), "Host build\n    HostName 10.0.0.9\n").unwrap();
+    fs::write(&config, "Include config.d/*\n\nHost web\n    HostName 10.0.0.1\n").unwrap();
+
+    let (hosts, _) = parse_csv(&export_config_csv(&config).unwrap()).unwrap();
+    let names: Vec<&str> = hosts.iter().map(|host| host.name.as_str()).collect();
+    assert_eq!(names, ["web", "build"]);
+}
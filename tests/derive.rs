@@ -0,0 +1,67 @@
+mod common;
+
+use common::setup;
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ Forward, SshHost };
+use ssh_tui::core::{ derive_host, AppMode, ChangeType, DeriveTemplate, TreeItem };
+
+fn db_prod() -> SshHost {
+    let mut host = SshHost::new("db-prod".to_string());
+    host.hostname = Some("10.0.3.4".to_string());
+    host.user = Some("postgres".to_string());
+    host.other_options.insert("proxyjump".to_string(), "bastion".to_string());
+    host
+}
+
+fn inputs(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn port_forward_copies_the_base_host() {
+    let host = derive_host(&db_prod(), DeriveTemplate::PortForward, &inputs(&["5432", ""]), &["db-prod"]).unwrap();
+    assert_eq!(host.name, "db-prod-tunnel");
+    assert_eq!(host.hostname.as_deref(), Some("10.0.3.4"));
+    assert_eq!(host.other_options["proxyjump"], "bastion");
+    assert_eq!(host.other_options["localforward"], "5432 localhost:5432");
+    assert_eq!(host.other_options["sessiontype"], "none");
+    assert_eq!(host.forwards(), [Forward::Local { listen: "5432".to_string(), target: "localhost:5432".to_string() }]);
+}
+
+#[test]
+fn derived_names_avoid_collisions() {
+    let taken = ["db-prod", "db-prod-tunnel"];
+    let host = derive_host(&db_prod(), DeriveTemplate::Socks, &inputs(&["1080"]), &taken).unwrap();
+    assert_eq!(host.name, "db-prod-tunnel-2");
+    assert_eq!(host.other_options["dynamicforward"], "1080");
+}
+
+#[test]
+fn jump_only_goes_through_the_base_host() {
+    let host = derive_host(&db_prod(), DeriveTemplate::JumpOnly, &inputs(&["10.0.3.9", "app"]), &[]).unwrap();
+    assert_eq!(host.name, "db-prod-jump");
+    assert_eq!(host.hostname.as_deref(), Some("10.0.3.9"));
+    assert_eq!(host.other_options["proxyjump"], "db-prod");
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    assert!(derive_host(&db_prod(), DeriveTemplate::PortForward, &inputs(&["abc", ""]), &[]).is_err());
+    assert!(derive_host(&db_prod(), DeriveTemplate::PortForward, &inputs(&["5432", "db"]), &[]).is_err());
+    assert!(derive_host(&db_prod(), DeriveTemplate::JumpOnly, &inputs(&["", ""]), &[]).is_err());
+}
+
+#[test]
+fn derived_host_is_staged_as_added() {
+    let (_dir, mut app) = setup("Host db-prod\n    HostName 10.0.3.4\n");
+    app.list_state.select(Some(0));
+    assert!(matches!(app.tree_items[0], TreeItem::Host { .. }));
+
+    for code in [KeyCode::Char('e'), KeyCode::Char('T'), KeyCode::Char('1'), KeyCode::Char('5'), KeyCode::Char('4'), KeyCode::Enter] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Added(host)] if host.name == "db-prod-tunnel"));
+    assert_eq!(app.get_selected_host().unwrap().name, "db-prod-tunnel");
+}
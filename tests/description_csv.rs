@@ -0,0 +1,76 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ is_description_csv, parse_description_csv, Settings, SshHost };
+use ssh_tui::core::{ plan_descriptions, App, AppMode, ChangeType, DescriptionOutcome, ImportStage };
+use std::fs;
+use tempfile::TempDir;
+
+fn rows(list: &[(&str, &str)]) -> Vec<(String, String)> {
+    list.iter().map(|(name, description)| (name.to_string(), description.to_string())).collect()
+}
+
+fn outcomes(rows: &[(String, String)], hosts: &[SshHost], overwrite: bool) -> Vec<DescriptionOutcome> {
+    plan_descriptions(rows, hosts, overwrite).iter().map(|row| row.outcome).collect()
+}
+
+#[test]
+fn description_csvs_are_recognised_with_or_without_a_header() {
+    let headed = "\u{feff}Description,Name\r\nFrontend,\"web\"\nPostgres primary,db\n";
+    assert!(is_description_csv(headed, false));
+    assert_eq!(parse_description_csv(headed).unwrap(), rows(&[("web", "Frontend"), ("db", "Postgres primary")]));
+
+    // 没有表头时只接受 .csv 文件，且每行恰好两列
+    let bare = "web,\"Frontend, EU\"\ndb,\n";
+    assert!(!is_description_csv(bare, false));
+    assert!(is_description_csv(bare, true));
+    assert_eq!(parse_description_csv(bare).unwrap(), rows(&[("web", "Frontend, EU"), ("db", "")]));
+    assert!(!is_description_csv("web,a,b\n", true));
+    assert!(!is_description_csv("name,hostname\nweb,10.0.0.1\n", true));
+}
+
+#[test]
+fn rows_match_by_exact_name_and_respect_the_mode() {
+    let mut described = SshHost::new("db".to_string());
+    described.description = Some("old".to_string());
+    let hosts = vec![SshHost::new("web".to_string()), described, SshHost { temporary: true, ..SshHost::new("tmp".to_string()) }];
+    let csv = rows(&[("web", "Frontend"), ("Web", "case differs"), ("db", "Postgres"), ("web", "again"), ("tmp", "x"), ("db", "")]);
+
+    use DescriptionOutcome::*;
+    assert_eq!(outcomes(&csv, &hosts, false), [Update, Unmatched, NotBlank, Duplicate, NotEditable, Duplicate]);
+    assert_eq!(outcomes(&csv, &hosts, true), [Update, Unmatched, Update, Duplicate, NotEditable, Duplicate]);
+
+    // 覆盖时空描述清除已有描述，只填空白时不动
+    let clear = rows(&[("db", ""), ("web", "")]);
+    assert_eq!(outcomes(&clear, &hosts, true), [Update, Unchanged]);
+    assert_eq!(outcomes(&clear, &hosts, false), [NotBlank, Unchanged]);
+    assert_eq!(plan_descriptions(&clear, &hosts, true)[0].description, None);
+}
+
+#[test]
+fn the_report_is_shown_before_anything_is_staged() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    fs::write(&config, "Host web\n    HostName 10.0.0.1\n\n# @description: Old text\nHost db\n    HostName 10.0.0.2\n").unwrap();
+    let file = dir.path().join("purposes.csv");
+    fs::write(&file, "web,Frontend\ndb,Postgres primary\ncache,Redis\n").unwrap();
+    let mut app = App::with_config_path(config, Settings::default()).unwrap();
+    press(&mut app, KeyCode::Char('e'));
+    app.start_import_file(&file);
+
+    let import = app.import.as_ref().unwrap();
+    assert_eq!(import.stage, ImportStage::Descriptions);
+    assert_eq!(import.descriptions.iter().filter(|row| row.outcome == DescriptionOutcome::Update).count(), 1);
+    assert_eq!(import.descriptions[2].outcome, DescriptionOutcome::Unmatched);
+    assert!(app.pending_changes.is_empty());
+
+    press(&mut app, KeyCode::Char('o'));
+    assert!(app.import.as_ref().unwrap().overwrite);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 2);
+    assert!(app.pending_changes.iter().all(|change| matches!(change, ChangeType::Modified { old, new } if old.description != new.description)));
+    let db = app.hosts.iter().find(|host| host.name == "db").unwrap();
+    assert_eq!(db.description.as_deref(), Some("Postgres primary"));
+}
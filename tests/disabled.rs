@@ -0,0 +1,94 @@
+mod common;
+
+use common::{ select_host, setup };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ lint_hosts, parse_ssh_config_from_str };
+use ssh_tui::core::{ changed_fields, AppMode, StatusLevel };
+use ssh_tui::ui::render;
+
+const CONFIG: &str = "\
+#sshc-disabled# ForwardAgent yes
+Host bastion
+    HostName bastion.example.com
+#sshc-disabled# User root
+
+#sshc-disabled# # @tags: db
+#sshc-disabled# Host db-old
+#sshc-disabled#     HostName 10.0.0.9
+#sshc-disabled#     ProxyJump bastion
+
+Host db
+    HostName 10.0.0.10
+    ProxyJump db-old
+";
+
+#[test]
+fn disabled_blocks_are_parsed_as_hosts() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let names: Vec<(&str, bool)> = hosts.iter().map(|host| (host.name.as_str(), host.disabled)).collect();
+    assert_eq!(names, [("", false), ("bastion", false), ("db-old", true), ("db", false)]);
+    let db_old = &hosts[2];
+    assert_eq!(db_old.tags, ["db"]);
+    assert_eq!(db_old.hostname.as_deref(), Some("10.0.0.9"));
+    assert_eq!(db_old.other_options.get("proxyjump").map(String::as_str), Some("bastion"));
+
+    // 不属于停用块的带前缀行只是注释：留在全局部分，不成为 bastion 的选项
+    assert_eq!(hosts[0].prelude.as_deref(), Some("#sshc-disabled# ForwardAgent yes\n"));
+    assert_eq!(hosts[1].user, None);
+}
+
+#[test]
+fn disabled_hosts_are_listed_dimmed_and_cannot_be_connected() {
+    let (_dir, mut app) = setup(CONFIG);
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..20).flat_map(|y| (0..100).map(move |x| (x, y))).map(|(x, y)| buffer.get(x, y).symbol.clone()).collect();
+    assert!(screen.contains("db-old (10.0.0.9) (disabled)"));
+
+    select_host(&mut app, "db-old");
+    app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+    assert_eq!(app.pending_connect, None);
+    assert_eq!(app.mode, AppMode::Normal);
+    let status = app.status.as_ref().unwrap();
+    assert_eq!(status.level, StatusLevel::Warning);
+    assert!(status.text.starts_with("db-old is disabled"));
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('+'))).unwrap();
+    assert!(app.connect_queue.is_empty());
+
+    select_host(&mut app, "db");
+    app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+    assert!(app.pending_connect.is_some());
+}
+
+#[test]
+fn toggling_stages_a_change_with_a_diff() {
+    let (_dir, mut app) = setup(CONFIG);
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    select_host(&mut app, "bastion");
+    app.handle_key(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+    select_host(&mut app, "db-old");
+    app.handle_key(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+
+    assert_eq!(app.pending_changes.len(), 2);
+    let diff = app.generate_diff_lines();
+    assert!(diff.contains(&"- Host bastion".to_string()));
+    assert!(diff.contains(&"+ #sshc-disabled# Host bastion".to_string()));
+    assert!(diff.contains(&"- #sshc-disabled# Host db-old".to_string()));
+    assert!(diff.contains(&"+ Host db-old".to_string()));
+
+    let db_old = app.hosts.iter().find(|host| host.name == "db-old").unwrap();
+    let mut before = db_old.clone();
+    before.disabled = true;
+    assert_eq!(changed_fields(&before, db_old), ["Disabled"]);
+}
+
+#[test]
+fn lint_ignores_disabled_hosts_but_flags_jumps_through_them() {
+    let findings = lint_hosts(&parse_ssh_config_from_str(CONFIG));
+    assert_eq!(findings.len(), 1);
+    assert_eq!((findings[0].host.as_str(), findings[0].rule), ("db", "missing-jump-host"));
+    assert_eq!(findings[0].message, "ProxyJump references 'db-old', which is disabled");
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, AppMode, QuickFilter };
+use ssh_tui::ui::search_bar_height;
+use ssh_tui::utils::SshcError;
+use std::fs::{ self, File };
+use std::path::{ Path, PathBuf };
+use std::time::{ Duration, SystemTime };
+use tempfile::TempDir;
+
+const CONFIG: &str = "Host alpha\n    HostName 10.0.0.1\n\nHost beta\n    HostName 10.0.0.2\n";
+
+fn setup() -> (TempDir, PathBuf, App) {
+    let (dir, app) = common::setup(CONFIG);
+    let path = common::config_path(&dir);
+    (dir, path, app)
+}
+
+/// 模拟其他程序写入：改写内容并把修改时间推到加载之后
+fn edit_on_disk(path: &Path, content: &str) {
+    fs::write(path, content).unwrap();
+    let later = SystemTime::now() + Duration::from_secs(60);
+    File::options().write(true).open(path).unwrap().set_modified(later).unwrap();
+}
+
+/// 在配置管理中删除选中的主机，留下一项未保存变更
+fn stage_delete(app: &mut App) {
+    press(app, KeyCode::Char('e'));
+    press(app, KeyCode::Char('d'));
+    press(app, KeyCode::Char('y'));
+    assert_eq!(app.pending_changes.len(), 1);
+}
+
+#[test]
+fn tick_detects_a_newer_mtime() {
+    let (_dir, path, mut app) = setup();
+    app.zen = true;
+    app.tick();
+    assert_eq!(app.changed_on_disk, None);
+    assert_eq!(search_bar_height(&app), 0);
+
+    edit_on_disk(&path, "Host alpha\n    HostName 10.9.9.9\n\nHost beta\n    HostName 10.0.0.2\n");
+    app.tick();
+
+    assert_eq!(app.changed_on_disk, Some(path));
+    assert_eq!(app.disk_changed_hosts, ["alpha"]);
+    // 简洁模式下也显示顶栏提示
+    assert_eq!(search_bar_height(&app), 3);
+
+    app.toggle_quick_filter(QuickFilter::ChangedOnDisk);
+    assert_eq!(app.filtered_hosts.len(), 1);
+    assert_eq!(app.hosts[app.filtered_hosts[0]].name, "alpha");
+}
+
+#[test]
+fn apply_refuses_until_resolved() {
+    let (_dir, path, mut app) = setup();
+    stage_delete(&mut app);
+    let external = format!("{}\nHost gamma\n    HostName 10.0.0.3\n", CONFIG);
+    edit_on_disk(&path, &external);
+
+    let error = app.apply_changes().unwrap_err();
+
+    assert!(matches!(&error, SshcError::ChangedOnDisk(changed) if *changed == path));
+    assert!(error.to_string().contains("changed on disk"));
+    assert_eq!(app.pending_changes.len(), 1);
+    assert_eq!(fs::read_to_string(&path).unwrap(), external);
+}
+
+#[test]
+fn r_from_the_banner_reloads_through_the_confirmation() {
+    let (_dir, path, mut app) = setup();
+    stage_delete(&mut app);
+    edit_on_disk(&path, &format!("{}\nHost gamma\n    HostName 10.0.0.3\n", CONFIG));
+    app.tick();
+
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(app.mode, AppMode::ConfirmReload);
+    press(&mut app, KeyCode::Char('d'));
+
+    assert_eq!(app.changed_on_disk, None);
+    assert!(app.pending_changes.is_empty());
+    assert_eq!(app.hosts.len(), 3);
+}
+
+#[test]
+fn overwrite_allows_the_save() {
+    let (_dir, path, mut app) = setup();
+    stage_delete(&mut app);
+    edit_on_disk(&path, &format!("{}\nHost gamma\n    HostName 10.0.0.3\n", CONFIG));
+    app.tick();
+
+    press(&mut app, KeyCode::Char('O'));
+    assert_eq!(app.changed_on_disk, None);
+    app.apply_changes().unwrap();
+
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(!written.contains("gamma"));
+    assert_eq!(app.changed_on_disk, None);
+    // 自己保存的内容不算外部修改
+    app.tick();
+    assert_eq!(app.changed_on_disk, None);
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::setup;
+use ssh_tui::config::SshHost;
+use ssh_tui::core::{ dns_name, resolve_name, DnsStatus };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+#[test]
+fn only_plain_aliases_resolve_via_dns() {
+    let alias = SshHost::new("nas".to_string());
+    assert_eq!(dns_name(&alias), Some("nas"));
+
+    let mut with_hostname = SshHost::new("nas".to_string());
+    with_hostname.hostname = Some("10.0.0.5".to_string());
+    assert_eq!(dns_name(&with_hostname), None);
+
+    let mut jumped = SshHost::new("nas".to_string());
+    jumped.other_options.insert("proxyjump".to_string(), "bastion".to_string());
+    assert_eq!(dns_name(&jumped), None);
+
+    assert_eq!(dns_name(&SshHost::new("*.lan".to_string())), None);
+}
+
+#[test]
+fn localhost_resolves_to_a_loopback_address() {
+    match resolve_name("localhost") {
+        DnsStatus::Resolved(ips) => assert!(ips.iter().all(|ip| ip.is_loopback())),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn selected_alias_is_looked_up_in_the_background() {
+    let (_dir, mut app) = setup("Host localhost\n    Port 2222\n\nHost web\n    HostName 10.0.0.1\n");
+    app.list_state.select(Some(0));
+
+    app.tick_tasks();
+    assert!(app.dns_cache.contains_key("localhost"));
+
+    let started = Instant::now();
+    while app.dns_cache["localhost"] == DnsStatus::Resolving && started.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(10));
+        app.tick_tasks();
+    }
+    assert!(matches!(app.dns_cache["localhost"], DnsStatus::Resolved(_)));
+
+    // 有 HostName 的主机不查询
+    app.list_state.select(Some(1));
+    app.tick_tasks();
+    assert_eq!(app.dns_cache.len(), 1);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{ ctrl, press, select_host, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::SshHost;
+use ssh_tui::core::{ App, AppMode, HostFormFields, VISIBLE_FIELD };
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+Host web
+    HostName 10.0.0.1
+    User deploy
+
+Host db
+    HostName 10.0.1.1
+";
+
+fn editing(name: &str) -> (TempDir, App) {
+    let (dir, mut app) = setup(CONFIG);
+    select_host(&mut app, name);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    (dir, app)
+}
+
+fn undo(app: &mut App) {
+    ctrl(app, 'z');
+}
+
+#[test]
+fn changed_fields_follow_the_snapshot() {
+    let mut host = SshHost::new("web".to_string());
+    host.user = Some("deploy".to_string());
+    let original = HostFormFields::from_host(&host);
+    let mut current = original.clone();
+
+    current.user.push('x');
+    current.visible = false;
+    assert_eq!(current.changed_fields(&original), [2, VISIBLE_FIELD]);
+
+    // 切换两次后不算修改
+    current.visible = true;
+    assert_eq!(current.changed_fields(&original), [2]);
+
+    current.revert_field(&original, 2);
+    assert!(current.changed_fields(&original).is_empty());
+}
+
+#[test]
+fn ctrl_z_reverts_only_the_focused_field() {
+    let (_dir, mut app) = editing("web");
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('9'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Backspace);
+
+    undo(&mut app);
+    let form = app.editing_host.as_ref().unwrap();
+    assert_eq!(form.current.user, "deploy");
+    assert_eq!(form.current.hostname, "10.0.0.19");
+    assert!(form.is_modified(1) && !form.is_modified(2));
+}
+
+#[test]
+fn reverted_form_has_no_changes() {
+    let (_dir, mut app) = editing("db");
+    for _ in 0..VISIBLE_FIELD {
+        press(&mut app, KeyCode::Down);
+    }
+    press(&mut app, KeyCode::Char(' '));
+    press(&mut app, KeyCode::Char(' '));
+    press(&mut app, KeyCode::Esc);
+
+    // 没有实际修改时 Esc 直接关闭，保存也不会产生变更
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.pending_changes.is_empty());
+}
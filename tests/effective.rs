@@ -0,0 +1,71 @@
+use ssh_tui::config::{ compare_effective, parse_ssh_config_from_str, parse_ssh_g_output };
+use ssh_tui::core::effective_config_args;
+use std::path::Path;
+
+// ssh -G web 的部分输出（OpenSSH 9.6）
+const SAMPLE: &str = "\
+host web
+user deploy
+hostname 10.0.0.5
+port 2222
+addressfamily any
+batchmode no
+identityfile /home/alice/.ssh/id_ed25519
+identityfile /home/alice/.ssh/id_rsa
+serveraliveinterval 30
+proxyjump none
+sendenv LANG
+sendenv LC_*
+";
+
+const CONFIG: &str = "\
+Host web
+    HostName 10.0.0.5
+    User root
+    Port 2222
+    IdentityFile ~/.ssh/id_ed25519
+    ServerAliveInterval 15
+";
+
+#[test]
+fn repeated_keys_are_merged_in_order() {
+    let entries = parse_ssh_g_output(SAMPLE);
+    assert_eq!(entries[0], ("host".to_string(), "web".to_string()));
+    let identity = entries.iter().find(|(k, _)| k == "identityfile").unwrap();
+    assert_eq!(identity.1, "/home/alice/.ssh/id_ed25519, /home/alice/.ssh/id_rsa");
+    let sendenv = entries.iter().find(|(k, _)| k == "sendenv").unwrap();
+    assert_eq!(sendenv.1, "LANG, LC_*");
+}
+
+#[test]
+fn configured_options_come_first_and_differences_are_marked() {
+    let host = &parse_ssh_config_from_str(CONFIG)[0];
+    let rows = compare_effective(host, &parse_ssh_g_output(SAMPLE), Some(Path::new("/home/alice")));
+
+    let configured: Vec<(&str, bool)> = rows
+        .iter()
+        .take_while(|row| row.configured.is_some())
+        .map(|row| (row.key.as_str(), row.differs))
+        .collect();
+    assert_eq!(configured, [
+        ("hostname", false),
+        ("user", true),
+        ("port", false),
+        ("identityfile", false),
+        ("serveraliveinterval", true),
+    ]);
+    assert!(rows.iter().any(|row| row.key == "batchmode" && row.configured.is_none() && !row.differs));
+}
+
+#[test]
+fn blank_lines_and_missing_values_are_tolerated() {
+    let entries = parse_ssh_g_output("\nuser bob\nforwardx11\n");
+    assert_eq!(entries, [("user".to_string(), "bob".to_string()), ("forwardx11".to_string(), String::new())]);
+}
+
+#[test]
+fn ssh_g_reads_the_config_being_edited() {
+    let args = vec!["-o".to_string(), "Port=2222".to_string(), "web".to_string()];
+    assert_eq!(effective_config_args(Some(Path::new("/tmp/work/config")), &args), ["-G", "-F", "/tmp/work/config", "-o", "Port=2222", "web"]);
+    assert_eq!(effective_config_args(None, &args), ["-G", "-o", "Port=2222", "web"]);
+}
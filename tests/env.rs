@@ -0,0 +1,39 @@
+use ssh_tui::config::{ lint_hosts, parse_ssh_config_from_str, serialize_hosts };
+use ssh_tui::core::expand_env_value;
+
+const CONFIG: &str = "\
+# @env: SSH_AUTH_SOCK=${HOME}/.yubikey-agent.sock
+# @env: KRB5CCNAME=FILE:/tmp/krb5cc
+# @env: BROKEN
+Host secure
+    HostName 10.0.0.9
+";
+
+#[test]
+fn env_metadata_is_parsed_and_round_trips() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert_eq!(hosts[0].env, [
+        ("SSH_AUTH_SOCK".to_string(), "${HOME}/.yubikey-agent.sock".to_string()),
+        ("KRB5CCNAME".to_string(), "FILE:/tmp/krb5cc".to_string()),
+    ]);
+    assert_eq!(hosts[0].invalid_env, ["BROKEN"]);
+
+    let reparsed = parse_ssh_config_from_str(&serialize_hosts(&hosts).unwrap());
+    assert_eq!(reparsed, hosts);
+}
+
+#[test]
+fn malformed_env_is_reported_by_lint() {
+    let findings = lint_hosts(&parse_ssh_config_from_str(CONFIG));
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, "malformed-env");
+}
+
+#[test]
+fn env_values_expand_variables() {
+    let path = std::env::var("PATH").unwrap();
+    assert_eq!(expand_env_value("${PATH}:x"), format!("{}:x", path));
+    assert_eq!(expand_env_value("$PATH/y"), format!("{}/y", path));
+    assert_eq!(expand_env_value("cost $5 and ${SSHC_SURELY_UNSET_VAR}!"), "cost $5 and !");
+    assert_eq!(expand_env_value("trailing $"), "trailing $");
+}
@@ -0,0 +1,56 @@
+use ssh_tui::config::{ environment_badge, Settings, SshHost };
+use ssh_tui::ui::row_with_badge;
+
+const SETTINGS: &str = r#"
+[[environments]]
+labels = ["prod", "production"]
+badge = "PRD"
+color = "red"
+
+[[environments]]
+labels = ["staging"]
+badge = "STG"
+color = "yellow"
+"#;
+
+fn settings() -> Settings {
+    toml::from_str(SETTINGS).unwrap()
+}
+
+#[test]
+fn badge_comes_from_folder_or_tag() {
+    let settings = settings();
+    let mut host = SshHost::new("api".to_string());
+    assert!(environment_badge(&host, &settings.environments).is_none());
+
+    host.tags = vec!["Staging".to_string()];
+    assert_eq!(environment_badge(&host, &settings.environments).unwrap().badge, "STG");
+
+    // 映射按顺序匹配
+    host.folder = Some("production".to_string());
+    let badge = environment_badge(&host, &settings.environments).unwrap();
+    assert_eq!((badge.badge.as_str(), badge.color.as_str()), ("PRD", "red"));
+}
+
+#[test]
+fn settings_without_environments_use_the_default() {
+    let settings: Settings = toml::from_str("confirm_connect = true").unwrap();
+    assert!(settings.environments.is_empty());
+}
+
+#[test]
+fn badge_is_dropped_before_the_name() {
+    let mut host = SshHost::new("database".to_string());
+    host.hostname = Some("10.0.0.1".to_string());
+
+    let (badge, row) = row_with_badge(&host, Some("PRD"), 40);
+    assert_eq!(badge.as_deref(), Some("PRD"));
+    assert_eq!(row, "database (10.0.0.1)");
+
+    // "PRD " + "database" 刚好放下
+    assert_eq!(row_with_badge(&host, Some("PRD"), 12), (Some("PRD".to_string()), "database".to_string()));
+
+    let (badge, row) = row_with_badge(&host, Some("PRD"), 10);
+    assert_eq!(badge, None);
+    assert_eq!(row, "database");
+}
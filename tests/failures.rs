@@ -0,0 +1,99 @@
+mod common;
+
+use common::{ isolated_home, screen_rows, select_host, setup_with };
+use ssh_tui::config::{ HostFailure, HostHistory, Settings };
+use ssh_tui::core::{ classify_failure, App, FailureReason };
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: Work
+Host web
+    HostName 10.0.0.1
+
+# @folder: Work
+Host db
+    HostName 10.0.0.2
+";
+
+fn app_with(settings: Settings) -> (TempDir, App) {
+    isolated_home();
+    setup_with(CONFIG, settings)
+}
+
+fn screen(app: &mut App) -> Vec<String> {
+    screen_rows(app, 120, 20)
+}
+
+fn row_of<'a>(screen: &'a [String], name: &str) -> &'a str {
+    screen.iter().find(|line| line.contains(&format!(" {} (", name))).unwrap()
+}
+
+#[test]
+fn stderr_is_classified_with_exit_code_fallback() {
+    let lines = |text: &str| text.lines().map(String::from).collect::<Vec<_>>();
+    assert_eq!(classify_failure(Some(255), &lines("admin@10.0.0.1: Permission denied (publickey).")), FailureReason::Authentication);
+    assert_eq!(
+        classify_failure(Some(255), &lines("@@@ WARNING: REMOTE HOST IDENTIFICATION HAS CHANGED! @@@\nPermission denied")),
+        FailureReason::HostKey
+    );
+    assert_eq!(classify_failure(Some(255), &lines("ssh: Could not resolve hostname nope: Name or service not known")), FailureReason::Dns);
+    assert_eq!(classify_failure(Some(255), &lines("ssh: connect to host 10.0.0.1 port 22: Connection timed out")), FailureReason::Timeout);
+    assert_eq!(classify_failure(Some(255), &[]), FailureReason::Ssh);
+    assert_eq!(classify_failure(Some(1), &[]), FailureReason::Exit);
+    assert_eq!(FailureReason::Refused.label(), "connection refused");
+}
+
+#[test]
+fn failed_hosts_show_a_marker_that_survives_filtering_until_a_success() {
+    let (_dir, mut app) = app_with(Settings::default());
+    app.record_connection_result("web", Some(255), Some(FailureReason::Refused));
+    select_host(&mut app, "web");
+
+    let lines = screen(&mut app);
+    assert!(row_of(&lines, "web").contains(" !"));
+    assert!(!row_of(&lines, "db").contains(" !"));
+    assert!(lines.iter().any(|line| line.contains("Last error") && line.contains("connection refused (exit 255)")));
+
+    // 按主机名记录，搜索和重建树后标记仍在
+    app.search_query = "we".to_string();
+    app.filter_hosts();
+    assert!(row_of(&screen(&mut app), "web").contains(" !"));
+    app.search_query.clear();
+    app.filter_hosts();
+    assert!(row_of(&screen(&mut app), "web").contains(" !"));
+
+    app.record_connection_result("web", Some(0), None);
+    assert!(!row_of(&screen(&mut app), "web").contains(" !"));
+    assert!(app.last_failure("web").is_none());
+}
+
+#[test]
+fn failures_reach_the_state_file_only_when_remembered() {
+    let (_dir, mut app) = app_with(Settings::default());
+    app.record_connection_result("db", Some(255), Some(FailureReason::Timeout));
+    assert!(app.history.hosts.get("db").is_none_or(|entry| entry.last_failure.is_none()));
+
+    let settings = Settings { remember_failures: true, ..Settings::default() };
+    let (_dir, mut app) = app_with(settings);
+    app.record_connection_result("db", Some(255), Some(FailureReason::Timeout));
+    assert_eq!(app.history.hosts["db"].last_failure.as_ref().unwrap().reason, "connection timed out");
+
+    // 以前会话记下的失败（来自状态文件）同样显示，成功后一起清除
+    app.failures.clear();
+    assert!(app.last_failure("db").is_some());
+    app.record_connection_result("db", Some(0), None);
+    assert!(app.history.hosts["db"].last_failure.is_none());
+}
+
+#[test]
+fn history_entries_without_failures_keep_their_format() {
+    let entry = HostHistory { connections: 2, ..HostHistory::default() };
+    assert!(!toml::to_string(&entry).unwrap().contains("last_failure"));
+
+    let failed = HostHistory {
+        last_failure: Some(HostFailure { exit_code: Some(255), reason: "host unreachable".to_string(), at: 1_700_000_000 }),
+        ..entry
+    };
+    let round_trip: HostHistory = toml::from_str(&toml::to_string(&failed).unwrap()).unwrap();
+    assert_eq!(round_trip.last_failure, failed.last_failure);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{ press, setup, setup_with, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ FolderCase, Settings };
+use ssh_tui::core::{ folder_suggestions, normalize_folder_name, AppMode, FolderSuggestion, FOLDER_FIELD };
+
+const CONFIG: &str = "\
+# @folder: Work
+Host web
+    HostName 10.0.0.1
+
+# @folder: Homelab
+Host nas
+    HostName 10.0.0.2
+";
+
+#[test]
+fn normalization_trims_and_collapses_whitespace() {
+    assert_eq!(normalize_folder_name("  Data   Center \t east ", FolderCase::Preserve), "Data Center east");
+    assert_eq!(normalize_folder_name("   ", FolderCase::Preserve), "");
+    assert_eq!(normalize_folder_name(" Data  CENTER ", FolderCase::Lower), "data center");
+    assert_eq!(normalize_folder_name("data  cENTER ékő", FolderCase::Title), "Data Center Ékő");
+    assert_eq!(Settings::default().folder_case, FolderCase::Preserve);
+    let settings: Settings = toml::from_str("folder_case = \"title\"").unwrap();
+    assert_eq!(settings.folder_case, FolderCase::Title);
+}
+
+#[test]
+fn suggestions_list_matches_and_offer_creation_last() {
+    let available = vec!["Homelab".to_string(), "Work".to_string(), "work-vpn".to_string()];
+    assert_eq!(folder_suggestions(&available, "wor", FolderCase::Preserve), [
+        FolderSuggestion::Existing("Work".to_string()),
+        FolderSuggestion::Existing("work-vpn".to_string()),
+        FolderSuggestion::Create("wor".to_string()),
+    ]);
+    // 只是大小写不同时不再提供新建
+    assert_eq!(folder_suggestions(&available, " work ", FolderCase::Lower), [
+        FolderSuggestion::Existing("Work".to_string()),
+        FolderSuggestion::Existing("work-vpn".to_string()),
+    ]);
+    assert_eq!(folder_suggestions(&available, "new  lab", FolderCase::Title), [FolderSuggestion::Create("New Lab".to_string())]);
+    assert!(folder_suggestions(&available, "Work", FolderCase::Preserve).is_empty());
+    assert!(folder_suggestions(&available, "  ", FolderCase::Preserve).is_empty());
+    assert_eq!(FolderSuggestion::Create("x".to_string()).label(), "+ create new folder: 'x'");
+}
+
+#[test]
+fn created_folder_is_available_for_later_adds_before_saving() {
+    let settings = Settings { folder_case: FolderCase::Lower, ..Settings::default() };
+    let (_dir, mut app) = setup_with(CONFIG, settings);
+
+    app.mode = AppMode::ConfigManagement;
+    // 不选中文件夹，表单的文件夹字段为空
+    app.list_state.select(None);
+    press(&mut app, KeyCode::Char('a'));
+    app.editing_host.as_mut().unwrap().current_field = FOLDER_FIELD;
+    type_text(&mut app, "  Data   Center ");
+    assert_eq!(app.folder_suggestions(), [FolderSuggestion::Create("data center".to_string())]);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.editing_host.as_ref().unwrap().folder_choice.as_ref().map(|picker| picker.selected), Some(0));
+    press(&mut app, KeyCode::Enter);
+
+    let editing = app.editing_host.as_ref().unwrap();
+    assert_eq!(editing.current.folder, "data center");
+    assert!(editing.folder_choice.is_none());
+    // 表单仍在编辑，列表已关闭
+    assert!(app.folder_suggestions().is_empty());
+    assert_eq!(app.get_available_folders(), ["Homelab", "Work", "data center"]);
+    assert!(app.pending_changes.is_empty());
+}
+
+#[test]
+fn picking_an_existing_folder_and_leaving_the_list() {
+    let (_dir, mut app) = setup(CONFIG);
+
+    app.mode = AppMode::ConfigManagement;
+    // 不选中文件夹，表单的文件夹字段为空
+    app.list_state.select(None);
+    press(&mut app, KeyCode::Char('a'));
+    app.editing_host.as_mut().unwrap().current_field = FOLDER_FIELD;
+    type_text(&mut app, "lab");
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.editing_host.as_ref().unwrap().folder_choice.as_ref().map(|picker| picker.selected), Some(1));
+    // ESC 只回到输入框，不关闭表单
+    press(&mut app, KeyCode::Esc);
+    assert!(app.editing_host.is_some());
+    assert!(app.editing_host.as_ref().unwrap().folder_choice.is_none());
+
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.editing_host.as_ref().unwrap().current.folder, "Homelab");
+    assert!(app.session_folders.is_empty());
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::screen_rows;
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::core::App;
+use ssh_tui::ui::footer_context;
+use tempfile::TempDir;
+
+fn setup(hosts: usize) -> (TempDir, App) {
+    let config: String = (1..=hosts).map(|i| format!("Host host-{:02}\n    HostName 10.0.0.{}\n\n", i, i)).collect();
+    common::setup(&config)
+}
+
+#[test]
+fn last_visible_host_is_not_covered_by_help() {
+    let (_dir, mut app) = setup(30);
+    let rows = screen_rows(&mut app, 80, 16);
+
+    // 外边距 1 行：页脚在倒数第二行，列表下边框在它上面
+    let footer = &rows[14];
+    assert!(footer.contains("Select"), "footer: {}", footer);
+    assert!(rows[13].contains('─'), "list border: {}", rows[13]);
+    let last_host_row = &rows[12];
+    assert!(last_host_row.contains("host-"), "last list row: {}", last_host_row);
+    assert!(!last_host_row.contains("Select"));
+}
+
+#[test]
+fn footer_shows_active_filter_and_hidden_toggle() {
+    let (_dir, mut app) = setup(3);
+    for code in [KeyCode::Char('/'), KeyCode::Char('0'), KeyCode::Esc, KeyCode::Char('H')] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+
+    assert_eq!(footer_context(&app), ["filter: /0", "hidden hosts shown"]);
+    let rows = screen_rows(&mut app, 120, 12);
+    assert!(rows[10].contains("filter: /0 | hidden hosts shown"), "footer: {}", rows[10]);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{ ctrl, press, type_text };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, style::Modifier, Terminal };
+use ssh_tui::config::{ Settings, WriteStyle };
+use ssh_tui::core::{ App, AppMode };
+use ssh_tui::ui::render;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn setup(content: &str, settings: Settings) -> (TempDir, PathBuf, App) {
+    let (dir, app) = common::setup_with(content, settings);
+    let path = common::config_path(&dir);
+    (dir, path, app)
+}
+
+/// 新建主机并填入表单；名称通过按键输入，其余字段直接设置
+fn fill_new_host(app: &mut App) {
+    press(app, KeyCode::Char('e'));
+    press(app, KeyCode::Char('a'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    type_text(app, "api");
+    let form = &mut app.editing_host.as_mut().unwrap().current;
+    form.hostname = "10.0.0.7".to_string();
+    form.user = "deploy".to_string();
+    form.port = "2222".to_string();
+    form.folder = "work".to_string();
+    form.description = "API server".to_string();
+    form.server_alive_interval = "30".to_string();
+}
+
+fn save(app: &mut App) {
+    ctrl(app, 's');
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    app.apply_changes().unwrap();
+}
+
+#[test]
+fn preview_matches_the_written_block() {
+    for style in [WriteStyle::Preserve, WriteStyle::Canonical] {
+        let (_dir, path, mut app) = setup("", Settings { write_style: style, ..Settings::default() });
+        fill_new_host(&mut app);
+        let preview = app.form_preview().unwrap();
+        assert_eq!(
+            preview,
+            "# @folder: work\n# @description: API server\nHost api\n    HostName 10.0.0.7\n    User deploy\n    Port 2222\n    ServerAliveInterval 30\n"
+        );
+        save(&mut app);
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", preview));
+    }
+}
+
+#[test]
+fn preview_keeps_options_outside_the_form_and_follows_keystrokes() {
+    let (_dir, path, mut app) = setup("Host web\n    HostName 10.0.0.1\n    ProxyJump bastion\n", Settings::default());
+    press(&mut app, KeyCode::Char('e'));
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('e'));
+    assert!(app.form_preview().unwrap().contains("    ProxyJump bastion\n"));
+
+    type_text(&mut app, "-2");
+    assert!(app.form_preview().unwrap().starts_with("Host web-2\n"));
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    type_text(&mut app, " prod");
+    // 坚持使用含空白的名称时加引号写入，预览同样如此
+    let preview = app.form_preview().unwrap();
+    assert!(preview.starts_with("Host \"web prod\"\n"), "{}", preview);
+
+    app.save_edited_host();
+    app.save_edited_host();
+    app.apply_changes().unwrap();
+    assert!(fs::read_to_string(&path).unwrap().contains(&preview));
+}
+
+#[test]
+fn preview_is_shown_beside_the_form_on_wide_terminals_only() {
+    let (_dir, _path, mut app) = setup("", Settings::default());
+    fill_new_host(&mut app);
+
+    let mut wide = Terminal::new(TestBackend::new(150, 50)).unwrap();
+    wide.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = wide.backend().buffer();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect())
+        .collect();
+    assert!(rows.iter().any(|row| row.contains("Preview: ")), "{}", rows.join("\n"));
+    let host_row = rows.iter().position(|row| row.contains("│Host api")).unwrap();
+    let border = rows[host_row].find("│Host api").unwrap();
+    let host_x = rows[host_row][..border].chars().count() as u16 + 1;
+    // Host 关键字加粗，与其他选项名区分
+    assert!(buffer.get(host_x, host_row as u16).modifier.contains(Modifier::BOLD));
+    assert!(rows.iter().any(|row| row.contains("HostName 10.0.0.7")));
+
+    let mut narrow = Terminal::new(TestBackend::new(100, 50)).unwrap();
+    narrow.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = narrow.backend().buffer();
+    let text: String = buffer.content().iter().map(|cell| cell.symbol.as_str()).collect();
+    assert!(!text.contains("Preview"));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{ ctrl, press, setup_with };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ FormSaveKey, Settings };
+use ssh_tui::core::{ App, AppMode, KEEPALIVE_FIELD, NAME_FIELD, PORT_FIELD };
+use tempfile::TempDir;
+
+fn editing(settings: Settings) -> (TempDir, App) {
+    let (dir, mut app) = setup_with("Host web\n    HostName 10.0.0.1\n", settings);
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    (dir, app)
+}
+
+fn field(app: &App) -> usize {
+    app.editing_host.as_ref().unwrap().current_field
+}
+
+#[test]
+fn enter_moves_to_the_next_field_without_saving() {
+    let (_dir, mut app) = editing(Settings::default());
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('9'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::EditingHost);
+    assert_eq!(field(&app), 2);
+    assert!(app.pending_changes.is_empty());
+
+    ctrl(&mut app, 's');
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 1);
+}
+
+#[test]
+fn failed_validation_focuses_the_offending_field() {
+    let (_dir, mut app) = editing(Settings::default());
+    for _ in 0..3 {
+        press(&mut app, KeyCode::Enter);
+    }
+    press(&mut app, KeyCode::Char('x'));
+    // 回到第一个字段后再保存，焦点应跳到端口
+    app.editing_host.as_mut().unwrap().current_field = 0;
+    ctrl(&mut app, 's');
+    assert_eq!(app.mode, AppMode::EditingHost);
+    assert_eq!(field(&app), PORT_FIELD);
+    assert!(app.editing_host.as_ref().unwrap().error.as_ref().unwrap().starts_with("Port"));
+
+    // 名称为空时同样给出提示，而不是静默忽略
+    app.editing_host.as_mut().unwrap().current.name.clear();
+    app.editing_host.as_mut().unwrap().current_field = 5;
+    app.save_edited_host();
+    assert_eq!(field(&app), NAME_FIELD);
+    assert_eq!(app.editing_host.as_ref().unwrap().error.as_deref(), Some("Name is required"));
+}
+
+#[test]
+fn several_invalid_fields_focus_the_first_in_form_order() {
+    let (_dir, mut app) = editing(Settings::default());
+    let form = &mut app.editing_host.as_mut().unwrap().current;
+    form.port = "x".to_string();
+    form.server_alive_count_max = "0".to_string();
+    ctrl(&mut app, 's');
+    assert_eq!(field(&app), PORT_FIELD);
+
+    // 端口改好后焦点跳到出错的保活字段
+    app.editing_host.as_mut().unwrap().current.port = "22".to_string();
+    ctrl(&mut app, 's');
+    assert_eq!(field(&app), KEEPALIVE_FIELD + 1);
+    assert!(app.editing_host.as_ref().unwrap().error.as_ref().unwrap().starts_with("ServerAliveCountMax"));
+}
+
+#[test]
+fn f2_save_key_moves_keepalive_presets_to_f3_and_f4() {
+    let settings = Settings { form_save_key: FormSaveKey::F2, ..Settings::default() };
+    let (_dir, mut app) = editing(settings);
+    press(&mut app, KeyCode::F(3));
+    let form = &app.editing_host.as_ref().unwrap().current;
+    assert_eq!((form.server_alive_interval.as_str(), form.server_alive_count_max.as_str()), ("15", "3"));
+    press(&mut app, KeyCode::F(4));
+    assert_eq!(app.editing_host.as_ref().unwrap().current.server_alive_interval, "60");
+
+    // Ctrl-S 此时不保存
+    ctrl(&mut app, 's');
+    assert_eq!(app.mode, AppMode::EditingHost);
+    press(&mut app, KeyCode::F(2));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 1);
+}
+
+#[test]
+fn legacy_setting_keeps_enter_as_save() {
+    let settings = Settings { enter_saves_form: true, ..Settings::default() };
+    let (_dir, mut app) = editing(settings);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('9'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 1);
+}
+
+#[test]
+fn save_key_is_read_from_settings() {
+    let settings: Settings = toml::from_str("form_save_key = \"f2\"").unwrap();
+    assert_eq!(settings.form_save_key, FormSaveKey::F2);
+    assert_eq!(Settings::default().form_save_key.label(), "Ctrl-S");
+}
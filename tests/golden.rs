@@ -0,0 +1,300 @@
+//! 解析/写回的金样测试：tests/fixtures/<name>.config 原样写回后应与原文件完全一致，
+//! 去掉原文、每个块都按字段重写后应与 <name>.golden 一致，以 write_style = "canonical" 写回后应与 <name>.canonical 一致。
+//! 修改写入逻辑后用 `UPDATE_GOLDEN=1 cargo test --test golden` 重新生成金样，并检查差异。
+
+mod common;
+
+use common::{ ctrl, isolated_home, select_host };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{
+    parse_ssh_config_file, write_ssh_config_file, write_ssh_config_file_with_style, Settings, SshHost, WriteStyle,
+};
+use ssh_tui::core::{ App, AppMode, TreeItem };
+use std::fs;
+use std::path::{ Path, PathBuf };
+use tempfile::TempDir;
+
+const FIXTURES: [&str; 12] = [
+    "canonical",
+    "metadata",
+    "quoted",
+    "wildcards",
+    "multiple_identities",
+    "inline_comments",
+    "crlf",
+    "odd_ports",
+    "match_blocks",
+    "prelude",
+    "write_style",
+    "disabled",
+];
+
+/// 同时检查 canonical 写法的样例
+const CANONICAL_FIXTURES: [&str; 3] = ["write_style", "multiple_identities", "metadata"];
+
+fn fixture_path(name: &str, extension: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(format!("{}.{}", name, extension))
+}
+
+/// 在临时目录中读入配置再原样写回，返回写出的内容
+fn round_trip(content: &[u8]) -> String {
+    round_trip_with_style(content, WriteStyle::Preserve)
+}
+
+fn round_trip_with_style(content: &[u8], style: WriteStyle) -> String {
+    write_back(content, style, false)
+}
+
+/// 去掉解析时的原文再写回，即每个块都被编辑过时的写法
+fn rewrite(content: &[u8]) -> String {
+    write_back(content, WriteStyle::Preserve, true)
+}
+
+fn write_back(content: &[u8], style: WriteStyle, drop_raw: bool) -> String {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(".ssh").join("config");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+
+    let mut hosts = parse_ssh_config_file(&path).unwrap();
+    if drop_raw {
+        hosts.iter_mut().for_each(|host| host.raw = None);
+    }
+    write_ssh_config_file_with_style(&path, &hosts, style).unwrap();
+    fs::read_to_string(&path).unwrap()
+}
+
+/// 按空行分隔的主机块（包括 CRLF 文件中的空行），块尾的换行不计入
+fn blocks(content: &str) -> Vec<String> {
+    let mut blocks = vec![String::new()];
+    for line in content.split_inclusive('\n') {
+        match line.trim().is_empty() {
+            true => blocks.push(String::new()),
+            false => blocks.last_mut().unwrap().push_str(line),
+        }
+    }
+    blocks.into_iter().map(|block| block.trim_end().to_string()).filter(|block| !block.is_empty()).collect()
+}
+
+#[test]
+fn fixtures_match_golden_output() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    for name in FIXTURES {
+        let output = rewrite(&fs::read(fixture_path(name, "config")).unwrap());
+        let golden_path = fixture_path(name, "golden");
+        if update {
+            fs::write(&golden_path, &output).unwrap();
+            continue;
+        }
+        let golden = fs::read_to_string(&golden_path).unwrap_or_default();
+        if output != golden {
+            mismatches.push(format!("--- {}.golden\n{}\n+++ actual\n{}", name, golden, output));
+        }
+    }
+
+    assert!(mismatches.is_empty(), "golden mismatch:\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn canonical_style_matches_golden_output() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    for name in CANONICAL_FIXTURES {
+        let output = round_trip_with_style(&fs::read(fixture_path(name, "config")).unwrap(), WriteStyle::Canonical);
+        let golden_path = fixture_path(name, "canonical");
+        if update {
+            fs::write(&golden_path, &output).unwrap();
+            continue;
+        }
+        let golden = fs::read_to_string(&golden_path).unwrap_or_default();
+        if output != golden {
+            mismatches.push(format!("--- {}.canonical\n{}\n+++ actual\n{}", name, golden, output));
+        }
+        // 同一组主机：两种写法只有选项顺序不同
+        let preserved = fs::read_to_string(fixture_path(name, "golden")).unwrap();
+        let sorted = |content: &str| {
+            let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(sorted(&output), sorted(&preserved), "{} styles differ in more than order", name);
+    }
+
+    assert!(mismatches.is_empty(), "golden mismatch:\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn canonical_output_is_a_fixed_point() {
+    for name in CANONICAL_FIXTURES {
+        let golden = fs::read_to_string(fixture_path(name, "canonical")).unwrap();
+        assert_eq!(round_trip_with_style(golden.as_bytes(), WriteStyle::Canonical), golden, "{}.canonical changes when written again", name);
+    }
+}
+
+#[test]
+fn golden_output_is_a_fixed_point() {
+    for name in FIXTURES {
+        let golden = fs::read_to_string(fixture_path(name, "golden")).unwrap();
+        assert_eq!(rewrite(golden.as_bytes()), golden, "{}.golden changes when written again", name);
+    }
+}
+
+#[test]
+fn untouched_configs_are_written_back_byte_identical() {
+    for name in FIXTURES {
+        let original = fs::read_to_string(fixture_path(name, "config")).unwrap();
+        assert_eq!(round_trip(original.as_bytes()), original, "{}", name);
+    }
+}
+
+#[test]
+fn metadata_and_repeated_options_survive_round_trip() {
+    for name in FIXTURES {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config");
+        fs::write(&path, fs::read(fixture_path(name, "config")).unwrap()).unwrap();
+        let before = parse_ssh_config_file(&path).unwrap();
+
+        write_ssh_config_file(&path, &before).unwrap();
+        let after = parse_ssh_config_file(&path).unwrap();
+
+        assert_eq!(before, after, "{} lost information on write", name);
+    }
+}
+
+#[test]
+fn new_blocks_are_separated_from_untouched_ones() {
+    for name in ["odd_ports", "crlf"] {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config");
+        let original = fs::read_to_string(fixture_path(name, "config")).unwrap();
+        fs::write(&path, &original).unwrap();
+
+        let mut hosts = parse_ssh_config_file(&path).unwrap();
+        hosts.push(SshHost::new("x".to_string()));
+        write_ssh_config_file(&path, &hosts).unwrap();
+        let newline = if name == "crlf" { "\r\n" } else { "\n" };
+        let expected = format!("{}{}Host x{}{}", original, newline, newline, newline);
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected, "{}", name);
+    }
+}
+
+#[test]
+fn option_order_follows_the_file() {
+    let output = rewrite(&fs::read(fixture_path("multiple_identities", "config")).unwrap());
+    let keys: Vec<&str> = output
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+    assert_eq!(
+        keys,
+        ["HostName", "IdentityFile", "IdentityFile", "IdentitiesOnly", "LocalForward", "LocalForward", "DynamicForward"]
+    );
+}
+
+#[test]
+fn editing_one_host_leaves_other_blocks_untouched() {
+    let home = isolated_home();
+    for name in FIXTURES {
+        let original = fs::read_to_string(fixture_path(name, "config")).unwrap();
+        let hosts: Vec<String> = parse_ssh_config_file(&fixture_path(name, "config"))
+            .unwrap()
+            .into_iter()
+            .filter(|host| !host.is_verbatim() && !host.disabled)
+            .map(|host| host.name)
+            .collect();
+        // 依次编辑每个主机
+        for host in hosts {
+            let dir = TempDir::new_in(home).unwrap();
+            let path = dir.path().join("config");
+            fs::write(&path, &original).unwrap();
+
+            let mut app = App::with_config_path(path.clone(), Settings::default()).unwrap();
+            // 配置管理中显示隐藏的主机
+            for code in [KeyCode::Char('e'), KeyCode::Char('H')] {
+                app.handle_key(KeyEvent::from(code)).unwrap();
+            }
+            let index = app.tree_items
+                .iter()
+                .position(|item| matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == host))
+                .unwrap_or_else(|| panic!("{}: {} not listed", name, host));
+            app.list_state.select(Some(index));
+            for code in [KeyCode::Char('e'), KeyCode::Down, KeyCode::Char('1')] {
+                app.handle_key(KeyEvent::from(code)).unwrap();
+            }
+            ctrl(&mut app, 's');
+            app.handle_key(KeyEvent::from(KeyCode::Char('q'))).unwrap();
+            assert_eq!(app.mode, AppMode::ReviewChanges, "{}: {}", name, host);
+            app.handle_key(KeyEvent::from(KeyCode::Char('y'))).unwrap();
+
+            let written = fs::read_to_string(&path).unwrap();
+            // 编辑过的块沿用文件原来的换行符
+            if original.contains("\r\n") {
+                assert_eq!(written.matches("\r\n").count(), written.matches('\n').count(), "{}: {}", name, host);
+            }
+            let (before, after) = (blocks(&original), blocks(&written));
+            assert_eq!(before.len(), after.len(), "{}: {}", name, host);
+            let header = format!("Host {}", host);
+            for (old, new) in before.iter().zip(&after) {
+                if old.lines().any(|line| line.split('#').next().unwrap().trim_end() == header) {
+                    assert_ne!(old, new, "{}: {} was not edited", name, host);
+                } else {
+                    assert_eq!(old, new, "{}: editing {} changed another block", name, host);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn saving_uses_the_configured_write_style() {
+    let home = isolated_home();
+    let dir = TempDir::new_in(home).unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, fs::read(fixture_path("write_style", "config")).unwrap()).unwrap();
+
+    let settings = Settings { write_style: WriteStyle::Canonical, ..Settings::default() };
+    let mut app = App::with_config_path(path.clone(), settings).unwrap();
+    for code in [KeyCode::Char('e'), KeyCode::Char('a'), KeyCode::Char('x')] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+    ctrl(&mut app, 's');
+    app.apply_changes().unwrap();
+
+    let written = fs::read_to_string(&path).unwrap();
+    let canonical = fs::read_to_string(fixture_path("write_style", "canonical")).unwrap();
+    assert!(written.starts_with(&canonical));
+    assert!(written.ends_with("Host x\n\n"));
+}
+
+#[test]
+fn disabling_and_re_enabling_restores_the_block() {
+    let home = isolated_home();
+    let dir = TempDir::new_in(home).unwrap();
+    let path = dir.path().join("config");
+    let original = fs::read_to_string(fixture_path("metadata", "golden")).unwrap();
+    fs::write(&path, &original).unwrap();
+
+    let toggle_lab = |path: &Path| {
+        let mut app = App::with_config_path(path.to_path_buf(), Settings::default()).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('H'))).unwrap();
+        select_host(&mut app, "lab");
+        app.handle_key(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+        app.apply_changes().unwrap();
+        fs::read_to_string(path).unwrap()
+    };
+
+    let disabled = toggle_lab(&path);
+    let lab = &blocks(&disabled)[0];
+    assert!(lab.lines().all(|line| line.starts_with("#sshc-disabled# ")), "{}", lab);
+    assert!(lab.contains("#sshc-disabled# Host lab\n"));
+    assert_eq!(blocks(&disabled)[1], blocks(&original)[1]);
+    assert!(parse_ssh_config_file(&path).unwrap()[0].disabled);
+
+    assert_eq!(toggle_lab(&path), original);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ ConfigGuard, Settings };
+use ssh_tui::core::{ App, AppMode, GUARD_DELAY };
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tempfile::TempDir;
+
+const CONFIG: &str = "Host web\n    HostName 10.0.0.1\n";
+
+fn setup(guard: ConfigGuard) -> (TempDir, PathBuf, App) {
+    let settings = Settings { config_guard: guard, config_guard_phrase: "ok".to_string(), ..Settings::default() };
+    let (dir, app) = common::setup_with(CONFIG, settings);
+    let path = common::config_path(&dir);
+    (dir, path, app)
+}
+
+/// 跳过 Confirm 模式的等待时间
+fn expire_delay(app: &mut App) {
+    app.guard.as_mut().unwrap().opened = Instant::now() - GUARD_DELAY;
+}
+
+#[test]
+fn confirm_guard_ignores_y_until_delay_passes() {
+    let (_dir, _path, mut app) = setup(ConfigGuard::Confirm);
+
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::ConfirmGuard);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfirmGuard);
+
+    expire_delay(&mut app);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+}
+
+#[test]
+fn phrase_guard_protects_saving() {
+    let (_dir, path, mut app) = setup(ConfigGuard::Phrase);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Char('k'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfirmGuard);
+
+    // 输错后清空，需要重新输入
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfirmGuard);
+    assert!(fs::read_to_string(&path).unwrap().contains("Host web"));
+
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Char('k'));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.pending_changes.is_empty());
+    assert!(!fs::read_to_string(&path).unwrap().contains("Host web"));
+}
+
+#[test]
+fn guard_leaves_connecting_and_search_alone() {
+    let (_dir, _path, mut app) = setup(ConfigGuard::Confirm);
+
+    press(&mut app, KeyCode::Enter);
+    assert!(app.pending_connect.is_some());
+    press(&mut app, KeyCode::Char('/'));
+    press(&mut app, KeyCode::Char('w'));
+    assert_eq!(app.mode, AppMode::Search);
+}
+
+#[test]
+fn read_only_blocks_config_management_even_without_guard() {
+    let (_dir, _path, mut app) = setup(ConfigGuard::Off);
+    app.read_only = true;
+
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.status.is_some());
+}
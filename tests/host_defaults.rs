@@ -0,0 +1,134 @@
+mod common;
+
+use common::{ ctrl, press };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ prefill_new_host, FolderDefaults, HostDefaults, Settings, SshHost };
+use ssh_tui::core::{ reference_lines, App, AppMode, ChangeType, TreeItem };
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: Work
+Host api
+    HostName 10.0.0.7
+
+Host home
+    HostName 192.168.1.2
+";
+
+fn settings() -> Settings {
+    Settings {
+        host_defaults: HostDefaults {
+            user: Some("deploy".to_string()),
+            identity_file: Some("~/.ssh/id_ed25519".to_string()),
+            proxy_jump: None,
+        },
+        folder_defaults: vec![FolderDefaults {
+            folder: "work".to_string(),
+            defaults: HostDefaults { user: Some("ops".to_string()), identity_file: None, proxy_jump: Some("bastion".to_string()) },
+        }],
+        ..Settings::default()
+    }
+}
+
+fn setup() -> (TempDir, App) {
+    let (dir, mut app) = common::setup_with(CONFIG, settings());
+    press(&mut app, KeyCode::Char('e'));
+    (dir, app)
+}
+
+fn select_row(app: &mut App, matches: impl Fn(&App, &TreeItem) -> bool) {
+    let row = app.tree_items.iter().position(|item| matches(app, item)).unwrap();
+    app.list_state.select(Some(row));
+}
+
+#[test]
+fn explicit_input_beats_folder_defaults_which_beat_global_defaults() {
+    let settings = settings();
+    // 文件夹默认值覆盖全局默认值，文件夹没有设置的字段回落到全局
+    let host = prefill_new_host(SshHost::new(String::new()), Some("WORK"), &settings);
+    assert_eq!(host.folder.as_deref(), Some("WORK"));
+    assert_eq!((host.user.as_deref(), host.identity_file.as_deref()), (Some("ops"), Some("~/.ssh/id_ed25519")));
+    assert_eq!(host.other_options.get("proxyjump").map(String::as_str), Some("bastion"));
+
+    // 明确给出的值不被覆盖
+    let mut explicit = SshHost::new("db".to_string());
+    explicit.user = Some("root".to_string());
+    explicit.other_options.insert("proxyjump".to_string(), "gw".to_string());
+    let host = prefill_new_host(explicit, Some("work"), &settings);
+    assert_eq!(host.user.as_deref(), Some("root"));
+    assert_eq!(host.other_options.get_all("proxyjump").collect::<Vec<_>>(), ["gw"]);
+
+    // 明确给出的文件夹优先于选中的文件夹
+    let mut explicit = SshHost::new(String::new());
+    explicit.folder = Some("home".to_string());
+    let host = prefill_new_host(explicit, Some("work"), &settings);
+    assert_eq!((host.folder.as_deref(), host.user.as_deref()), (Some("home"), Some("deploy")));
+    assert!(!host.other_options.contains_key("proxyjump"));
+}
+
+#[test]
+fn without_a_folder_only_global_defaults_apply() {
+    let host = prefill_new_host(SshHost::new(String::new()), None, &settings());
+    assert_eq!((host.folder, host.user.as_deref()), (None, Some("deploy")));
+    assert!(!host.other_options.contains_key("proxyjump"));
+
+    let host = prefill_new_host(SshHost::new(String::new()), Some("  "), &Settings::default());
+    assert_eq!(host, SshHost::new(String::new()));
+
+    let parsed: Settings = toml::from_str(
+        "[host_defaults]\nuser = \"deploy\"\n\n[[folder_defaults]]\nfolder = \"work\"\nproxy_jump = \"bastion\"\n",
+    ).unwrap();
+    assert_eq!(parsed.host_defaults.user.as_deref(), Some("deploy"));
+    assert_eq!(parsed.folder_defaults[0].defaults.proxy_jump.as_deref(), Some("bastion"));
+}
+
+#[test]
+fn adding_inside_a_folder_prefills_the_form_and_written_block() {
+    let (dir, mut app) = setup();
+    for on_folder in [true, false] {
+        // 选中文件夹行或其中的主机效果相同
+        select_row(&mut app, |app, item| match item {
+            TreeItem::Folder { name, .. } => on_folder && name == "Work",
+            TreeItem::Host { host_index } => !on_folder && app.hosts[*host_index].name == "api",
+        });
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.mode, AppMode::EditingHost);
+        let form = &app.editing_host.as_ref().unwrap().current;
+        assert_eq!((form.folder.as_str(), form.user.as_str(), form.identity_file.as_str()), ("Work", "ops", "~/.ssh/id_ed25519"));
+        assert!(app.form_preview().unwrap().contains("    ProxyJump bastion\n"));
+        press(&mut app, KeyCode::Esc);
+    }
+
+    select_row(&mut app, |app, item| matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == "home"));
+    press(&mut app, KeyCode::Char('a'));
+    for c in "db".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    let form = &app.editing_host.as_ref().unwrap().current;
+    assert_eq!((form.folder.as_str(), form.user.as_str()), ("", "deploy"));
+    ctrl(&mut app, 's');
+    app.apply_changes().unwrap();
+    let written = fs::read_to_string(dir.path().join("config")).unwrap();
+    assert!(written.contains("Host db\n    User deploy\n    IdentityFile ~/.ssh/id_ed25519\n"), "{}", written);
+}
+
+#[test]
+fn editing_never_reapplies_defaults_and_the_reference_lists_the_order() {
+    let (_dir, mut app) = setup();
+    select_row(&mut app, |app, item| matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == "api"));
+    press(&mut app, KeyCode::Char('e'));
+    let form = &app.editing_host.as_ref().unwrap().current;
+    assert_eq!((form.user.as_str(), form.identity_file.as_str()), ("", ""));
+    assert!(!app.form_preview().unwrap().contains("ProxyJump"));
+    ctrl(&mut app, 's');
+    assert!(!app.pending_changes.iter().any(|change| matches!(change, ChangeType::Modified { .. })));
+
+    let lines = reference_lines();
+    let order: Vec<usize> = ["1. explicit input", "2. the folder_defaults", "3. host_defaults"]
+        .iter()
+        .map(|step| lines.iter().position(|line| line.trim_start().starts_with(step)).unwrap())
+        .collect();
+    assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(lines.iter().any(|line| line.contains("never re-applies")));
+}
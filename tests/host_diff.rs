@@ -0,0 +1,87 @@
+mod common;
+
+use common::{ press, select_host, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::SshHost;
+use ssh_tui::core::{ field_changes, host_diff, AppMode, ChangeType, FieldChange };
+
+fn host(name: &str) -> SshHost {
+    let mut host = SshHost::new(name.to_string());
+    host.hostname = Some(format!("{}.example.com", name));
+    host
+}
+
+fn change(field: &str, before: &str, after: &str) -> FieldChange {
+    FieldChange { field: field.to_string(), before: before.to_string(), after: after.to_string() }
+}
+
+#[test]
+fn field_changes_show_values_before_and_after() {
+    let old = host("web");
+    let mut new = old.clone();
+    new.port = Some("2222".to_string());
+    new.visible = false;
+    new.tags = vec!["nginx".to_string(), "eu".to_string()];
+    new.other_options.insert("proxyjump".to_string(), "bastion".to_string());
+
+    assert_eq!(field_changes(&old, &new), [
+        change("Visibility", "shown", "hidden"),
+        change("Tags", "(none)", "nginx, eu"),
+        change("Port", "(none)", "2222"),
+        change("ProxyJump", "(none)", "bastion"),
+    ]);
+}
+
+#[test]
+fn the_diff_follows_renames_back_to_the_first_edit() {
+    let web = host("web");
+    let mut renamed = web.clone();
+    renamed.name = "web-01".to_string();
+    let mut ported = renamed.clone();
+    ported.port = Some("2222".to_string());
+    let changes = vec![
+        ChangeType::Modified { old: web, new: renamed.clone() },
+        ChangeType::Added(host("db")),
+        ChangeType::Modified { old: renamed, new: ported },
+    ];
+
+    let diff = host_diff(&changes, "web-01");
+    assert_eq!(diff.change_indices, [0, 2]);
+    assert_eq!(diff.fields, [change("Name", "web", "web-01"), change("Port", "(none)", "2222")]);
+
+    // 新增的主机没有旧版本可比，没有变更的主机为空
+    assert!(host_diff(&changes, "db").added);
+    assert!(host_diff(&changes, "other").change_indices.is_empty());
+}
+
+#[test]
+fn the_popup_reverts_only_that_hosts_edits() {
+    let (_dir, mut app) = setup("Host web\n    HostName 10.0.0.1\n\nHost db\n    HostName 10.0.0.2\n");
+    press(&mut app, KeyCode::Char('e'));
+    for name in ["web", "db"] {
+        select_host(&mut app, name);
+        press(&mut app, KeyCode::Char('x'));
+    }
+    assert_eq!(app.pending_changes.len(), 2);
+
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('D'));
+    assert_eq!(app.mode, AppMode::HostDiff);
+    assert_eq!(app.host_diff.as_ref().unwrap().fields, [change("Disabled", "no", "yes")]);
+    press(&mut app, KeyCode::Char('u'));
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Modified { new, .. }] if new.name == "db"));
+    assert!(!app.hosts.iter().find(|host| host.name == "web").unwrap().disabled);
+    assert!(app.hosts.iter().find(|host| host.name == "db").unwrap().disabled);
+    assert_eq!(app.get_selected_host().unwrap().name, "web");
+
+    // 没有变更的主机只显示提示，u 不做任何事
+    press(&mut app, KeyCode::Char('D'));
+    assert!(app.host_diff.as_ref().unwrap().change_indices.is_empty());
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(app.mode, AppMode::HostDiff);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 1);
+}
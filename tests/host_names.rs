@@ -0,0 +1,54 @@
+mod common;
+
+use common::{ ctrl, setup };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ host_name_problem, sanitize_host_name, SshHost };
+use ssh_tui::core::{ parse_ssh_command, AppMode };
+
+#[test]
+fn special_characters_are_reported() {
+    for name in ["web prod", "web*", "db?", "!old", "-x"] {
+        assert!(host_name_problem(name).is_some(), "{}", name);
+    }
+    for name in ["web-prod", "db_01", "10.0.0.1", "[::1]"] {
+        assert_eq!(host_name_problem(name), None, "{}", name);
+    }
+}
+
+#[test]
+fn sanitized_names_are_safe() {
+    assert_eq!(sanitize_host_name("web prod"), "web-prod");
+    assert_eq!(sanitize_host_name("-db*"), "db-");
+    assert_eq!(sanitize_host_name("!"), "host");
+    assert_eq!(parse_ssh_command("ssh web*.example.com", &[]).unwrap().name, "web-");
+}
+
+#[test]
+fn connect_target_is_first_pattern() {
+    let target = |name: &str| SshHost::new(name.to_string()).connect_target().to_string();
+    assert_eq!(target("web prod"), "web");
+    assert_eq!(target("\"web prod\""), "web prod");
+    assert_eq!(target("db"), "db");
+}
+
+#[test]
+fn form_warns_before_saving_a_name_with_spaces() {
+    let (_dir, mut app) = setup("Host db\n    HostName 10.0.0.1\n");
+
+    for code in [KeyCode::Char('e'), KeyCode::Char('a')] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+    for c in "web prod".chars() {
+        app.handle_key(KeyEvent::from(KeyCode::Char(c))).unwrap();
+    }
+    ctrl(&mut app, 's');
+    assert_eq!(app.mode, AppMode::EditingHost);
+    assert!(app.editing_host.as_ref().unwrap().error.as_ref().unwrap().contains("whitespace"));
+
+    // 再保存一次确认，名称加引号写入
+    ctrl(&mut app, 's');
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    let host = app.hosts.iter().find(|h| h.name.contains("web")).unwrap();
+    assert_eq!(host.name, "\"web prod\"");
+    assert_eq!(host.connect_target(), "web prod");
+}
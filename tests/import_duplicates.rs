@@ -0,0 +1,108 @@
+mod common;
+
+use common::{ press, setup };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ parse_ssh_config_with_warnings, SshHost };
+use ssh_tui::core::{ fill_blanks, find_possible_duplicates, App, AppMode, ChangeType, ConflictResolution, DuplicateReason, PossibleDuplicate };
+use ssh_tui::ui::render;
+
+const EXISTING: &str = "\
+Host web
+    HostName Web.Example.com
+    User deploy
+
+# @alias: database, pg
+Host db
+    HostName 10.0.0.5
+    Port 2222
+
+Host bastion
+";
+
+fn hosts(text: &str) -> Vec<SshHost> {
+    parse_ssh_config_with_warnings(text).0
+}
+
+fn duplicates(imported: &str) -> Vec<Option<(usize, DuplicateReason)>> {
+    find_possible_duplicates(&hosts(imported), &hosts(EXISTING))
+        .into_iter()
+        .map(|duplicate| duplicate.map(|PossibleDuplicate { existing, reason }| (existing, reason)))
+        .collect()
+}
+
+#[test]
+fn same_hostname_and_port_is_a_possible_duplicate() {
+    // HostName 不区分大小写、忽略末尾的点；端口缺省为 22
+    assert_eq!(duplicates("Host www\n    HostName web.example.COM.\n    Port 22\n"), [Some((0, DuplicateReason::SameTarget))]);
+    assert_eq!(duplicates("Host pg1\n    HostName 10.0.0.5\n    Port 2222\n"), [Some((1, DuplicateReason::SameTarget))]);
+    assert_eq!(duplicates("Host pg1\n    HostName 10.0.0.5\n"), [None]);
+    assert_eq!(duplicates("Host www\n    HostName web.example.com\n    Port 8022\n"), [None]);
+    // 没有 HostName 时 ssh 连接的就是名称
+    assert_eq!(duplicates("Host jump\n    HostName bastion\n"), [Some((2, DuplicateReason::SameTarget))]);
+    assert_eq!(duplicates("Host 10.0.0.5\n    Port 2222\n"), [Some((1, DuplicateReason::SameTarget))]);
+    // 通配模式不比较目标
+    assert_eq!(duplicates("Host *.example.com\n    User me\n"), [None]);
+}
+
+#[test]
+fn names_and_aliases_are_compared_as_sets() {
+    assert_eq!(duplicates("Host database\n    HostName 10.9.9.9\n"), [Some((1, DuplicateReason::SharedAlias))]);
+    assert_eq!(duplicates("# @alias: Web\nHost frontend\n    HostName 10.9.9.8\n"), [Some((0, DuplicateReason::SharedAlias))]);
+    assert_eq!(duplicates("Host pg other\n    HostName 10.9.9.7\n"), [Some((1, DuplicateReason::SharedAlias))]);
+    // 同名的由冲突处理，不算可能重复；每台导入的主机单独判断
+    assert_eq!(duplicates("Host web\n    HostName 10.0.0.5\n    Port 2222\n\nHost fresh\n    HostName 10.1.1.1\n"), [None, None]);
+
+    let mut existing = hosts(EXISTING);
+    existing.insert(0, SshHost::new_prelude("HostName web.example.com\n".to_string()));
+    let found = find_possible_duplicates(&hosts("Host www\n    HostName web.example.com\n"), &existing);
+    assert_eq!(found[0].map(|duplicate| duplicate.existing), Some(1));
+}
+
+#[test]
+fn merging_only_fills_blanks() {
+    let existing = &hosts(EXISTING)[0];
+    let imported = &hosts("# @folder: Web\nHost www\n    HostName other.example.com\n    User root\n    Port 2200\n    LocalForward 8080 localhost:80\n    LocalForward 8443 localhost:443\n")[0];
+    let merged = fill_blanks(existing, imported);
+    assert_eq!((merged.name.as_str(), merged.hostname.as_deref(), merged.user.as_deref()), ("web", Some("Web.Example.com"), Some("deploy")));
+    assert_eq!((merged.port.as_deref(), merged.folder.as_deref()), (Some("2200"), Some("Web")));
+    assert_eq!(merged.other_options.get_all("localforward").collect::<Vec<_>>(), ["8080 localhost:80", "8443 localhost:443"]);
+    assert_eq!(fill_blanks(&merged, imported), merged);
+}
+
+#[test]
+fn review_offers_merge_skip_and_add_anyway() {
+    let (_dir, mut app) = setup(EXISTING);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('i'));
+    app.import.as_mut().unwrap().text =
+        "Host www\n    HostName web.example.com\n    IdentityFile ~/.ssh/web\n\nHost database\n    HostName 10.9.9.9\n\nHost jump\n    HostName bastion\n".to_string();
+    press(&mut app, KeyCode::F(2));
+    let resolutions = |app: &App| app.import.as_ref().unwrap().candidates.iter().map(|c| c.resolution).collect::<Vec<_>>();
+    assert_eq!(resolutions(&app), [ConflictResolution::Skip; 3]);
+
+    let mut terminal = Terminal::new(TestBackend::new(140, 40)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol.as_str()).collect();
+    assert!(screen.contains("(possible duplicate of web: same HostName and port)"), "{}", screen);
+    assert!(screen.contains("Existing") && screen.contains("Imported") && screen.contains("~/.ssh/web"));
+
+    press(&mut app, KeyCode::Char('m'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('a'));
+    // 系统主机等只读的已有主机不能合并
+    app.hosts.iter_mut().find(|host| host.name == "bastion").unwrap().system = true;
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('m'));
+    assert_eq!(resolutions(&app), [ConflictResolution::Merge, ConflictResolution::Add, ConflictResolution::Skip]);
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    let [ChangeType::Modified { old, new }, ChangeType::Added(added)] = &app.pending_changes[..] else {
+        panic!("unexpected changes: {:?}", app.pending_changes);
+    };
+    assert_eq!((old.identity_file.as_deref(), new.identity_file.as_deref()), (None, Some("~/.ssh/web")));
+    assert_eq!((new.name.as_str(), new.hostname.as_deref()), ("web", Some("Web.Example.com")));
+    assert_eq!(added.name, "database");
+    assert!(!app.hosts.iter().any(|host| host.name == "www" || host.name == "jump"));
+}
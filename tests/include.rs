@@ -0,0 +1,122 @@
+mod common;
+
+use common::{ ctrl, press, select_host, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ parse_config_tree, resolve_include, ConfigFileRule, Settings };
+use ssh_tui::core::{ App, AppMode, ChangeType, CONFIG_FILE_FIELD };
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const MAIN: &str = "\
+Include config.d/*
+
+Host home
+    HostName 10.0.0.1
+";
+
+/// 主配置 Include config.d/*，其中 work 文件包含一台主机，other 使用非标准缩进
+fn setup() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let main = dir.path().join("config");
+    fs::write(&main, MAIN).unwrap();
+    fs::create_dir(dir.path().join("config.d")).unwrap();
+    fs::write(dir.path().join("config.d/work"), "# @folder: work\nHost build\n    HostName 10.1.0.1\n\n").unwrap();
+    fs::write(dir.path().join("config.d/other"), "Host lab\n  HostName 10.2.0.1\n").unwrap();
+    (dir, main)
+}
+
+#[test]
+fn included_files_are_parsed_with_their_source() {
+    let (dir, main) = setup();
+    let work = dir.path().join("config.d/work");
+    let other = dir.path().join("config.d/other");
+    assert_eq!(resolve_include("config.d/w?rk", dir.path()), std::slice::from_ref(&work));
+    assert_eq!(resolve_include("config.d/*", dir.path()), [other.clone(), work.clone()]);
+    assert!(resolve_include("config.d/missing", dir.path()).is_empty());
+
+    let (hosts, files) = parse_config_tree(&main).unwrap();
+    assert_eq!(files, [other.clone(), work.clone()]);
+    let source = |name: &str| hosts.iter().find(|host| host.name == name).unwrap().source.clone();
+    assert_eq!(source("home"), None);
+    assert_eq!(source("lab"), Some(other));
+    assert_eq!(source("build"), Some(work));
+}
+
+#[test]
+fn new_hosts_follow_the_folder_rule() {
+    let (dir, main) = setup();
+    let work = dir.path().join("config.d/work");
+    let settings = Settings {
+        config_file_rules: vec![ConfigFileRule { folder: "work".to_string(), file: work.to_string_lossy().into_owned() }],
+        ..Settings::default()
+    };
+    let mut app = App::with_config_path(main.clone(), settings).unwrap();
+
+    press(&mut app, KeyCode::Char('e'));
+    // 不选中文件夹，文件夹字段为空
+    app.list_state.select(None);
+    press(&mut app, KeyCode::Char('a'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    type_text(&mut app, "ci");
+    for _ in 0..5 {
+        press(&mut app, KeyCode::Tab);
+    }
+    type_text(&mut app, "work");
+    ctrl(&mut app, 's');
+
+    let diff = app.generate_diff_lines();
+    assert_eq!(diff[0], format!("@ {}", work.display()));
+    assert_eq!(diff[1], "+ # @folder: work");
+    app.apply_changes().unwrap();
+
+    // 主配置中没有变化的块原样写回
+    assert_eq!(fs::read_to_string(&main).unwrap(), MAIN);
+    let written = fs::read_to_string(&work).unwrap();
+    assert!(written.contains("Host build") && written.contains("Host ci"));
+}
+
+#[test]
+fn moving_a_host_shows_a_delete_and_an_add() {
+    let (dir, main) = setup();
+    let work = dir.path().join("config.d/work");
+    let mut app = App::with_config_path(main.clone(), Settings::default()).unwrap();
+
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "build");
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::BackTab);
+    assert_eq!(app.editing_host.as_ref().unwrap().current_field, CONFIG_FILE_FIELD);
+    // 可选顺序：主配置、other、work；从 work 向后切换回到主配置
+    press(&mut app, KeyCode::Right);
+    ctrl(&mut app, 's');
+
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Modified { old, new }] if old.source.is_some() && new.source.is_none()));
+    let diff = app.generate_diff_lines();
+    let header = |sign: char| diff.iter().position(|line| *line == format!("{} Host build", sign)).unwrap();
+    assert_eq!(diff[0], format!("@ {}", work.display()));
+    assert!(diff.contains(&format!("@ {}", main.display())));
+    assert!(header('-') < header('+'));
+
+    app.apply_changes().unwrap();
+    assert!(fs::read_to_string(&main).unwrap().contains("Host build"));
+    assert!(!fs::read_to_string(&work).unwrap().contains("Host build"));
+}
+
+#[test]
+fn unchanged_included_files_are_not_rewritten() {
+    let (dir, main) = setup();
+    let mut app = App::with_config_path(main.clone(), Settings::default()).unwrap();
+
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "home");
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Tab);
+    type_text(&mut app, "0");
+    ctrl(&mut app, 's');
+    app.apply_changes().unwrap();
+
+    assert!(fs::read_to_string(&main).unwrap().contains("HostName 10.0.0.10"));
+    assert_eq!(fs::read_to_string(dir.path().join("config.d/other")).unwrap(), "Host lab\n  HostName 10.2.0.1\n");
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::setup;
+use crossterm::event::{ KeyCode, KeyEvent };
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ inherited_options, inherited_value, parse_ssh_config_from_str, SshHost };
+use ssh_tui::core::{ App, TreeItem };
+use ssh_tui::ui::render;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn fixture() -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/inheritance.config")).unwrap()
+}
+
+fn host<'a>(hosts: &'a [SshHost], name: &str) -> &'a SshHost {
+    hosts.iter().find(|host| host.name == name).unwrap()
+}
+
+/// (键, 值, 来源)
+fn inherited(hosts: &[SshHost], name: &str) -> Vec<(String, String, String)> {
+    inherited_options(hosts, host(hosts, name))
+        .into_iter()
+        .map(|option| (option.key, option.value, option.from))
+        .collect()
+}
+
+#[test]
+fn values_come_from_matching_blocks_in_file_order() {
+    let hosts = parse_ssh_config_from_str(&fixture());
+    let web = inherited(&hosts, "web-01.acme.internal");
+    let expected = |key: &str, value: &str, from: &str| (key.to_string(), value.to_string(), from.to_string());
+    assert_eq!(web, [
+        expected("Compression", "yes", "global options"),
+        expected("User", "deploy", "*.acme.internal !legacy.acme.internal"),
+        expected("Port", "22", "*.acme.internal !legacy.acme.internal"),
+        expected("IdentityFile", "~/.ssh/acme", "*.acme.internal !legacy.acme.internal"),
+        expected("ServerAliveInterval", "30", "*"),
+    ]);
+    // 先到先得：web-* 的 User 和 Host * 的 Compression 都不生效
+    assert!(!web.iter().any(|(_, value, _)| value == "web" || value == "no"));
+}
+
+#[test]
+fn own_values_and_negated_patterns_are_respected() {
+    let hosts = parse_ssh_config_from_str(&fixture());
+    let db = inherited_options(&hosts, host(&hosts, "db-01.acme.internal"));
+    assert!(inherited_value(&db, "user").is_none());
+    assert!(inherited_value(&db, "port").is_none());
+    assert_eq!(inherited_value(&db, "IdentityFile").unwrap().value, "~/.ssh/acme");
+
+    let legacy = inherited_options(&hosts, host(&hosts, "legacy.acme.internal"));
+    assert!(inherited_value(&legacy, "user").is_none());
+    assert_eq!(inherited_value(&legacy, "serveraliveinterval").unwrap().from, "*");
+
+    // 通配块本身不计算继承
+    assert!(inherited_options(&hosts, host(&hosts, "*")).is_empty());
+}
+
+fn app() -> (TempDir, App) {
+    let (dir, app) = setup(&fixture());
+    (dir, app)
+}
+
+fn listed(app: &App) -> Vec<String> {
+    app.tree_items
+        .iter()
+        .filter_map(|item| match item {
+            TreeItem::Host { host_index } => Some(app.hosts[*host_index].name.clone()),
+            TreeItem::Folder { .. } => None,
+        })
+        .collect()
+}
+
+#[test]
+fn detail_pane_shows_inherited_values_with_their_source() {
+    let (_dir, mut app) = app();
+    let row = listed(&app).iter().position(|name| name == "web-01.acme.internal").unwrap();
+    app.list_state.select(Some(row));
+
+    let mut terminal = Terminal::new(TestBackend::new(140, 30)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let text: Vec<String> = (0..30).map(|y| (0..140).map(|x| buffer.get(x, y).symbol.clone()).collect()).collect();
+    assert!(text.iter().any(|line| line.contains("deploy  (inherited from *.acme.internal")));
+    assert!(text.iter().any(|line| line.contains("ServerAliveInterval") && line.contains("(inherited from *)")));
+}
+
+#[test]
+fn missing_identity_filter_skips_hosts_that_inherit_one() {
+    let (_dir, mut app) = app();
+    app.handle_key(KeyEvent::from(KeyCode::Char('\\'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('2'))).unwrap();
+    let listed = listed(&app);
+    assert!(listed.contains(&"legacy.acme.internal".to_string()));
+    assert!(!listed.contains(&"web-01.acme.internal".to_string()));
+    assert!(!listed.contains(&"db-01.acme.internal".to_string()));
+}
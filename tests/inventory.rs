@@ -0,0 +1,189 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ inventory_delta, parse_inventory, parse_ssh_config_from_str, InventoryEntry, InventorySnapshot, InventorySource, Settings };
+use ssh_tui::core::{ App, AppMode, ChangeType };
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const SETTINGS: &str = r#"
+[inventory]
+url = "inventory.json"
+folder = "Fleet"
+list = "data.machines"
+
+[inventory.fields]
+name = "id"
+hostname = "net.ip"
+user = "login"
+port = "ssh_port"
+"#;
+
+fn source(url: &Path) -> InventorySource {
+    let settings: Settings = toml::from_str(SETTINGS).unwrap();
+    let mut source = settings.inventory.unwrap();
+    source.url = url.to_string_lossy().to_string();
+    source
+}
+
+fn entry(hostname: &str) -> InventoryEntry {
+    InventoryEntry { hostname: hostname.to_string(), user: None, port: None }
+}
+
+#[test]
+fn entries_are_read_through_the_field_mapping() {
+    let source = source(Path::new("unused"));
+    assert_eq!(source.folder, "Fleet");
+    let json = r#"{"data": {"machines": [
+        {"id": "web-1", "net": {"ip": "10.0.0.1"}, "login": "deploy", "ssh_port": 2222},
+        {"id": "db-1", "net": {"ip": "10.0.0.2"}},
+        {"id": "web-1", "net": {"ip": "10.0.0.9"}},
+        {"id": "no-ip"},
+        {"net": {"ip": "10.0.0.3"}}
+    ]}}"#;
+    let (entries, warnings) = parse_inventory(json, &source).unwrap();
+
+    assert_eq!(entries.keys().collect::<Vec<_>>(), ["db-1", "web-1"]);
+    let web = &entries["web-1"];
+    assert_eq!((web.user.as_deref(), web.port.as_deref()), (Some("deploy"), Some("2222")));
+    assert_eq!(web.hostname, "10.0.0.1");
+    assert_eq!(warnings.len(), 3);
+
+    assert!(parse_inventory("{\"data\": {}}", &source).is_err());
+    assert!(parse_inventory("not json", &source).is_err());
+}
+
+#[test]
+fn delta_is_computed_against_the_previous_snapshot() {
+    let hosts = parse_ssh_config_from_str("\
+# @folder: Fleet
+Host web-1
+    HostName 10.0.0.1
+    User admin
+
+# @folder: Fleet
+Host old-1
+    HostName 10.0.0.5
+
+Host db-1
+    HostName 192.168.1.1
+");
+    let snapshot = InventorySnapshot {
+        hosts: BTreeMap::from([
+            ("web-1".to_string(), entry("10.0.0.1")),
+            ("old-1".to_string(), entry("10.0.0.5")),
+            ("gone-locally".to_string(), entry("10.0.0.6")),
+        ]),
+    };
+    let entries = BTreeMap::from([
+        ("web-1".to_string(), entry("10.0.0.11")),
+        ("new-1".to_string(), entry("10.0.0.7")),
+        ("gone-locally".to_string(), entry("10.0.0.6")),
+        ("db-1".to_string(), entry("10.0.0.8")),
+    ]);
+    let delta = inventory_delta(&entries, &snapshot, &hosts, "Fleet");
+
+    assert_eq!(delta.added.len(), 1);
+    assert_eq!(delta.added[0].name, "new-1");
+    assert_eq!(delta.added[0].folder.as_deref(), Some("Fleet"));
+    // 只改变清单中变化的字段，本地改过的 User 保留
+    let (old, new) = &delta.modified[0];
+    assert_eq!((old.hostname.as_deref(), new.hostname.as_deref()), (Some("10.0.0.1"), Some("10.0.0.11")));
+    assert_eq!(new.user.as_deref(), Some("admin"));
+    assert_eq!(delta.modified.len(), 1);
+    assert_eq!(delta.removed.iter().map(|host| host.name.as_str()).collect::<Vec<_>>(), ["old-1"]);
+    // 文件夹外的同名主机不接管
+    assert!(delta.warnings[0].contains("db-1"));
+}
+
+fn app_with_inventory(dir: &TempDir, json: &str) -> App {
+    let path = dir.path().join("config");
+    fs::write(&path, "# @folder: Fleet\nHost old-1\n    HostName 10.0.0.5\n").unwrap();
+    let fixture = dir.path().join("inventory.json");
+    fs::write(&fixture, json).unwrap();
+    let snapshot = dir.path().join("snapshot.toml");
+    InventorySnapshot { hosts: BTreeMap::from([("old-1".to_string(), entry("10.0.0.5"))]) }.save_to(&snapshot).unwrap();
+
+    let settings = Settings { inventory: Some(source(&fixture)), ..Settings::default() };
+    let mut app = App::with_config_path(path, settings).unwrap();
+    app.inventory_snapshot_path = Some(snapshot);
+    press(&mut app, KeyCode::Char('e'));
+    app
+}
+
+#[test]
+fn sync_stages_changes_and_asks_before_deleting() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_with_inventory(&dir, r#"{"data": {"machines": [{"id": "new-1", "net": {"ip": "10.0.0.7"}}]}}"#);
+    press(&mut app, KeyCode::Char('U'));
+    assert_eq!(app.mode, AppMode::InventorySync);
+
+    press(&mut app, KeyCode::Enter);
+    assert!(app.inventory_sync.as_ref().unwrap().confirm_removal);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Added(added), ChangeType::Deleted(deleted)] if added.name == "new-1" && deleted.name == "old-1"));
+
+    // 快照在保存后才更新，之后再同步没有变化
+    let snapshot_path = app.inventory_snapshot_path.clone().unwrap();
+    assert!(InventorySnapshot::load_from(&snapshot_path).hosts.contains_key("old-1"));
+    press(&mut app, KeyCode::Char('y'));
+    let saved = InventorySnapshot::load_from(&snapshot_path);
+    assert_eq!(saved.hosts.keys().collect::<Vec<_>>(), ["new-1"]);
+    assert!(fs::read_to_string(dir.path().join("config")).unwrap().contains("Host new-1"));
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('U'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.status.as_ref().unwrap().text.contains("up to date"));
+}
+
+#[test]
+fn declined_removals_keep_the_hosts_and_failures_are_reported() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_with_inventory(&dir, r#"{"data": {"machines": []}}"#);
+    press(&mut app, KeyCode::Char('U'));
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('n'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.pending_changes.is_empty());
+    assert!(app.hosts.iter().any(|host| host.name == "old-1"));
+
+    fs::remove_file(dir.path().join("inventory.json")).unwrap();
+    press(&mut app, KeyCode::Char('U'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.status.as_ref().unwrap().text.starts_with("Inventory sync failed"));
+}
+
+#[test]
+fn entries_that_would_add_config_lines_are_skipped() {
+    let source = source(Path::new("unused"));
+    let json = r#"{"data": {"machines": [
+        {"id": "web", "net": {"ip": "10.0.0.1\n    ProxyCommand touch /tmp/pwned"}},
+        {"id": "db", "net": {"ip": "10.0.0.2"}, "login": "root\r\n    LocalCommand id"},
+        {"id": "api\nHost *", "net": {"ip": "10.0.0.3"}},
+        {"id": "two words", "net": {"ip": "10.0.0.4"}},
+        {"id": "cache", "net": {"ip": "10.0.0.5"}, "ssh_port": "22 -oProxyCommand=x"},
+        {"id": "mail", "net": {"ip": "10.0.0.6"}, "ssh_port": "ssh"},
+        {"id": "ok", "net": {"ip": "10.0.0.7"}, "login": "deploy", "ssh_port": 2200}
+    ]}}"#;
+    let (entries, warnings) = parse_inventory(json, &source).unwrap();
+    // 服务名作为端口仍然接受
+    assert_eq!(entries.keys().collect::<Vec<_>>(), ["mail", "ok"]);
+    assert_eq!(warnings.len(), 5, "{:?}", warnings);
+    assert!(warnings[0].starts_with("web: 'net.ip' contains control characters"), "{:?}", warnings);
+    assert!(warnings[3].contains("'id' contains whitespace"), "{:?}", warnings);
+
+    // 其他途径构造的含换行的值同样拒绝写出
+    let mut host = parse_ssh_config_from_str("Host web\n    HostName 10.0.0.1\n").remove(0);
+    host.hostname = Some("10.0.0.1\n    ProxyCommand touch /tmp/pwned".to_string());
+    let error = ssh_tui::config::serialize_host(&host, true).unwrap_err();
+    assert!(error.to_string().contains("HostName value contains a line break"), "{}", error);
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    assert!(ssh_tui::config::write_ssh_config_file(&path, &[host]).is_err());
+    assert!(!path.exists());
+}
@@ -0,0 +1,57 @@
+use ssh_tui::config::{ jump_targets, retarget_references, SshHost };
+use ssh_tui::core::{ parse_ssh_command, probe_target, probe_tcp, ProbeStatus };
+use ssh_tui::utils::{ format_host_port, is_ipv6_literal, split_host_port };
+use std::net::TcpListener;
+use std::time::Duration;
+
+#[test]
+fn host_port_helpers_bracket_v6() {
+    assert!(is_ipv6_literal("2001:db8::10"));
+    assert!(is_ipv6_literal("[fe80::1%eth0]"));
+    assert!(!is_ipv6_literal("db.example.com"));
+
+    assert_eq!(format_host_port("2001:db8::10", "22"), "[2001:db8::10]:22");
+    assert_eq!(format_host_port("[2001:db8::10]", "22"), "[2001:db8::10]:22");
+    assert_eq!(format_host_port("10.0.0.1", "22"), "10.0.0.1:22");
+
+    assert_eq!(split_host_port("[2001:db8::10]:2200"), ("2001:db8::10", Some("2200")));
+    assert_eq!(split_host_port("[2001:db8::10]"), ("2001:db8::10", None));
+    assert_eq!(split_host_port("2001:db8::10"), ("2001:db8::10", None));
+    assert_eq!(split_host_port("bastion:2222"), ("bastion", Some("2222")));
+}
+
+#[test]
+fn ssh_command_with_v6_destinations() {
+    let host = parse_ssh_command("ssh admin@2001:db8::10", &[]).unwrap();
+    assert_eq!(host.hostname.as_deref(), Some("2001:db8::10"));
+    assert_eq!(host.user.as_deref(), Some("admin"));
+    assert_eq!(host.port, None);
+
+    let host = parse_ssh_command("ssh ssh://root@[2001:db8::10]:2200", &[]).unwrap();
+    assert_eq!(host.hostname.as_deref(), Some("2001:db8::10"));
+    assert_eq!(host.port.as_deref(), Some("2200"));
+}
+
+#[test]
+fn jump_hosts_with_v6_addresses() {
+    assert_eq!(jump_targets("ops@[2001:db8::1]:2222,2001:db8::2,bastion:22"), ["2001:db8::1", "2001:db8::2", "bastion"]);
+
+    let mut host = SshHost::new("inner".to_string());
+    host.other_options.insert("proxyjump".to_string(), "[2001:db8::1]:2222,bastion".to_string());
+    let retargeted = retarget_references(&host, "bastion", Some("edge"));
+    assert_eq!(retargeted.other_options["proxyjump"], "[2001:db8::1]:2222,edge");
+}
+
+#[test]
+fn probe_accepts_bare_and_bracketed_v6() {
+    let mut host = SshHost::new("v6".to_string());
+    host.hostname = Some("[2001:db8::10]".to_string());
+    assert_eq!(probe_target(0, &host).unwrap().address, "2001:db8::10");
+
+    // 沙箱可能没有 IPv6 回环，无法绑定时跳过
+    let Ok(listener) = TcpListener::bind("[::1]:0") else {
+        return;
+    };
+    let port = listener.local_addr().unwrap().port();
+    assert!(matches!(probe_tcp("::1", port, Duration::from_secs(2)), ProbeStatus::Reachable(_)));
+}
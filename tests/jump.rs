@@ -0,0 +1,101 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::config::Settings;
+use ssh_tui::core::{ App, StatusLevel, TreeItem };
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: Work
+Host alpha
+    HostName 10.0.0.1
+    Port nonsense
+
+# @folder: Work
+Host beta
+    HostName 10.0.0.2
+
+Host gamma
+    HostName 10.0.0.3
+
+Host delta
+    HostName 10.0.0.4
+    Port bogus
+";
+
+fn app(dir: &TempDir) -> App {
+    let path = dir.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+    App::with_config_path(path, Settings::default()).unwrap()
+}
+
+fn selected(app: &App) -> String {
+    app.get_selected_host().map(|host| host.name.clone()).unwrap_or_default()
+}
+
+fn status(app: &App) -> String {
+    app.status.as_ref().map(|status| status.text.clone()).unwrap_or_default()
+}
+
+#[test]
+fn findings_are_reachable_only_after_linting_and_wrap_around() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app(&dir);
+    press(&mut app, KeyCode::Char('}'));
+    assert!(status(&app).starts_with("Run the checks (L) first"));
+    assert!(app.get_selected_host().is_none());
+
+    press(&mut app, KeyCode::Char('L'));
+    press(&mut app, KeyCode::Esc);
+    select_host(&mut app, "alpha");
+    press(&mut app, KeyCode::Char('}'));
+    assert_eq!(selected(&app), "delta");
+    assert!(status(&app).starts_with("delta: Port 'bogus'"), "{}", status(&app));
+    assert_eq!(app.status.as_ref().unwrap().level, StatusLevel::Error);
+
+    // 回绕到开头，跳过文件夹行
+    press(&mut app, KeyCode::Char('}'));
+    assert_eq!(selected(&app), "alpha");
+    press(&mut app, KeyCode::Char('{'));
+    assert_eq!(selected(&app), "delta");
+}
+
+#[test]
+fn changes_are_described_in_the_status_bar() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app(&dir);
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "gamma");
+    press(&mut app, KeyCode::Char('x'));
+    select_host(&mut app, "alpha");
+    press(&mut app, KeyCode::Char('x'));
+
+    press(&mut app, KeyCode::Char(']'));
+    assert_eq!(selected(&app), "gamma");
+    assert_eq!(status(&app), "gamma: modified Disabled");
+    press(&mut app, KeyCode::Char(']'));
+    assert_eq!(selected(&app), "alpha");
+    press(&mut app, KeyCode::Char('['));
+    assert_eq!(selected(&app), "gamma");
+}
+
+#[test]
+fn jumps_stay_inside_the_search_results() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app(&dir);
+    press(&mut app, KeyCode::Char('L'));
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('/'));
+    for c in "alpha".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Esc);
+    assert!(app.tree_items.iter().all(|item| !matches!(item, TreeItem::Host { host_index } if app.hosts[*host_index].name == "delta")));
+
+    press(&mut app, KeyCode::Char('}'));
+    assert_eq!(selected(&app), "alpha");
+    press(&mut app, KeyCode::Char(']'));
+    assert_eq!(status(&app), "No listed host has a pending change");
+}
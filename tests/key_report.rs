@@ -0,0 +1,88 @@
+mod common;
+
+use common::setup;
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::cli::{ parse_args, CliCommand, KeyReportFormat };
+use ssh_tui::config::{
+    build_key_report, key_report_csv, key_report_json, parse_public_key, parse_ssh_config_from_str, public_key_path,
+    read_public_key,
+};
+use ssh_tui::core::AppMode;
+use std::path::{ Path, PathBuf };
+
+/// 用 ssh-keygen -t ed25519 生成的公钥，ssh-keygen -lf 给出的指纹如下
+const FINGERPRINT: &str = "SHA256:vibaURj+nmlsTArsVVtBU5usy/iXUe085Kla7yP/LuU";
+
+fn fixture_keys() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("keys")
+}
+
+/// work 和 build 用同一把私钥（其中一个带引号），lab 的 .pub 不存在
+fn config() -> String {
+    let keys = fixture_keys();
+    format!(
+        "Host work\n    IdentityFile {key}\n\nHost build\n    IdentityFile \"{key}\"\n\nHost lab\n    IdentityFile {missing}\n",
+        key = keys.join("work_ed25519").display(),
+        missing = keys.join("lab_rsa").display(),
+    )
+}
+
+#[test]
+fn fingerprints_match_ssh_keygen() {
+    let info = read_public_key(&public_key_path(&fixture_keys().join("work_ed25519"))).unwrap();
+    assert_eq!(info.key_type, "ssh-ed25519");
+    assert_eq!(info.fingerprint, FINGERPRINT);
+    assert_eq!(info.comment, "alice@laptop");
+
+    assert_eq!(parse_public_key("ssh-rsa not-base64!"), None);
+    assert_eq!(public_key_path(Path::new("/keys/id.old")), PathBuf::from("/keys/id.old.pub"));
+}
+
+#[test]
+fn hosts_are_grouped_by_key_and_missing_pub_is_flagged() {
+    let hosts = parse_ssh_config_from_str(&config());
+    let entries = build_key_report(&hosts, None);
+
+    assert_eq!(entries.len(), 2);
+    let found = entries.iter().find(|entry| !entry.missing_pub).unwrap();
+    assert_eq!(found.fingerprint.as_deref(), Some(FINGERPRINT));
+    assert_eq!(found.hosts, ["work", "build"]);
+    let missing = entries.iter().find(|entry| entry.missing_pub).unwrap();
+    assert_eq!(missing.hosts, ["lab"]);
+    assert_eq!(missing.public_key, fixture_keys().join("lab_rsa.pub").display().to_string());
+    assert_eq!(missing.fingerprint, None);
+}
+
+#[test]
+fn report_is_written_as_json_and_csv() {
+    let hosts = parse_ssh_config_from_str(&config());
+    let entries = build_key_report(&hosts, None);
+
+    let json: serde_json::Value = serde_json::from_str(&key_report_json(&entries).unwrap()).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 2);
+    assert!(json.as_array().unwrap().iter().any(|entry| entry["fingerprint"] == FINGERPRINT && entry["hosts"][1] == "build"));
+
+    let csv = key_report_csv(&entries).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("identity files,public key,type,fingerprint"));
+    assert!(lines.iter().any(|line| line.contains(FINGERPRINT) && line.contains("\"work, build\"")));
+    assert!(lines.iter().any(|line| line.contains(",yes,lab")));
+}
+
+#[test]
+fn key_report_popup_and_subcommand() {
+    let (_dir, mut app) = setup(&config());
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('k'))).unwrap();
+    assert_eq!(app.mode, AppMode::ShowMessage);
+    let lines = &app.message.as_ref().unwrap().lines;
+    assert!(lines.iter().any(|line| line.contains(FINGERPRINT)));
+    assert!(lines.iter().any(|line| line.contains("MISSING")));
+
+    let args = |list: &[&str]| parse_args(list.iter().map(|s| s.to_string())).map(|options| options.command);
+    assert_eq!(args(&["key-report"]).unwrap(), Some(CliCommand::KeyReport(KeyReportFormat::Table)));
+    assert_eq!(args(&["key-report", "json"]).unwrap(), Some(CliCommand::KeyReport(KeyReportFormat::Json)));
+    assert!(args(&["key-report", "yaml"]).is_err());
+    assert!(args(&["export-csv", "key-report"]).is_err());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{ press, setup, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ identity_files, normalize_key_path, parse_ssh_config_from_str, rotate_identity, Settings };
+use ssh_tui::core::{ App, AppMode, ChangeType };
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+Host a
+    IdentityFile /keys/id_rsa
+
+Host b
+    IdentityFile /keys/work
+    IdentityFile /keys/id_rsa/
+
+Host c
+    IdentityFile /keys/other
+";
+
+fn open_rotation(dir: &TempDir) -> App {
+    let path = dir.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+    press(&mut app, KeyCode::Char('e'));
+    app.list_state.select(None);
+    press(&mut app, KeyCode::Char('K'));
+    assert_eq!(app.mode, AppMode::RotateKey);
+    type_text(&mut app, "/keys/id_rsa");
+    press(&mut app, KeyCode::Tab);
+    type_text(&mut app, "/keys/id_ed25519");
+    app
+}
+
+fn files(app: &App, name: &str) -> Vec<String> {
+    identity_files(app.hosts.iter().find(|h| h.name == name).unwrap())
+}
+
+#[test]
+fn paths_are_compared_after_expanding_tilde() {
+    let home = Path::new("/home/me");
+    assert_eq!(normalize_key_path("~/.ssh/id_rsa", Some(home)), "/home/me/.ssh/id_rsa");
+    assert_eq!(normalize_key_path("\"/home/me/.ssh/id_rsa\" ", Some(home)), "/home/me/.ssh/id_rsa");
+    assert_eq!(normalize_key_path("/home/me/.ssh/id_rsa/", Some(home)), "/home/me/.ssh/id_rsa");
+    assert_eq!(normalize_key_path("~other/key", Some(home)), "~other/key");
+
+    let hosts = parse_ssh_config_from_str("Host a\n    IdentityFile /home/me/.ssh/id_rsa\n");
+    let rotated = rotate_identity(&hosts[0], "~/.ssh/id_rsa", "~/.ssh/id_ed25519", false, Some(home)).unwrap();
+    assert_eq!(rotated.identity_file.as_deref(), Some("~/.ssh/id_ed25519"));
+    assert!(rotate_identity(&hosts[0], "~/.ssh/unused", "~/.ssh/id_ed25519", false, Some(home)).is_none());
+}
+
+#[test]
+fn replace_mode_swaps_every_reference() {
+    let dir = TempDir::new().unwrap();
+    let mut app = open_rotation(&dir);
+    let targets: Vec<usize> = app.key_rotation_changes().iter().map(|(i, _)| *i).collect();
+    assert_eq!(targets.len(), 2);
+
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 2);
+    assert!(app.pending_changes.iter().all(|c| matches!(c, ChangeType::Modified { .. })));
+    assert_eq!(files(&app, "a"), ["/keys/id_ed25519"]);
+    // 额外的 IdentityFile 原位替换，顺序不变
+    assert_eq!(files(&app, "b"), ["/keys/work", "/keys/id_ed25519"]);
+    assert_eq!(files(&app, "c"), ["/keys/other"]);
+}
+
+#[test]
+fn append_mode_keeps_the_old_key() {
+    let dir = TempDir::new().unwrap();
+    let mut app = open_rotation(&dir);
+    press(&mut app, KeyCode::Tab);
+    press(&mut app, KeyCode::Char(' '));
+    assert!(app.key_rotation.as_ref().unwrap().append);
+
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(files(&app, "a"), ["/keys/id_rsa", "/keys/id_ed25519"]);
+    assert_eq!(files(&app, "b"), ["/keys/work", "/keys/id_rsa/", "/keys/id_ed25519"]);
+    let diff = app.generate_diff_lines();
+    assert!(diff.contains(&"+   IdentityFile /keys/id_ed25519".to_string()));
+}
+
+#[test]
+fn missing_key_finding_opens_rotation() {
+    let (_dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('L'));
+    assert_eq!(app.mode, AppMode::LintView);
+    let lint = app.lint.as_mut().unwrap();
+    lint.selected = lint.findings.iter().position(|f| f.rule == "missing-identity-file" && f.host == "a").unwrap();
+
+    press(&mut app, KeyCode::Char('r'));
+
+    assert_eq!(app.mode, AppMode::RotateKey);
+    let state = app.key_rotation.as_ref().unwrap();
+    assert_eq!(state.old_path, "/keys/id_rsa");
+    assert_eq!(state.field, 1);
+}
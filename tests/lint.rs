@@ -0,0 +1,87 @@
+mod common;
+
+use common::{ press, select_host, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{
+    find_dependents, jump_targets, lint_hosts, parse_ssh_config_from_str, retarget_references,
+};
+use ssh_tui::core::{ AppMode, ChangeType };
+
+const CONFIG: &str = "\
+Host bastion
+    HostName jump.example.com
+
+Host app
+    HostName 10.0.0.2
+    ProxyJump deploy@bastion:2222
+
+Host db
+    HostName 10.0.0.3
+    ProxyJump edge,bastion
+
+Host legacy
+    ProxyCommand ssh -W %h:%p bastion
+
+Host direct
+    ProxyJump gw.example.com
+";
+
+#[test]
+fn jump_targets_strip_user_and_port() {
+    assert_eq!(jump_targets("deploy@bastion:2222,ssh://edge:22"), ["bastion", "edge"]);
+    assert!(jump_targets("none").is_empty());
+}
+
+#[test]
+fn dependents_include_proxy_jump_and_proxy_command() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let names: Vec<&str> = find_dependents(&hosts, "bastion")
+        .into_iter()
+        .map(|i| hosts[i].name.as_str())
+        .collect();
+    assert_eq!(names, ["app", "db", "legacy"]);
+}
+
+#[test]
+fn retarget_keeps_user_and_port() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let app = retarget_references(&hosts[1], "bastion", Some("bastion2"));
+    assert_eq!(app.other_options["proxyjump"], "deploy@bastion2:2222");
+    let legacy = retarget_references(&hosts[3], "bastion", Some("bastion2"));
+    assert_eq!(legacy.other_options["proxycommand"], "ssh -W %h:%p bastion2");
+}
+
+#[test]
+fn clearing_removes_only_the_deleted_hop() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert_eq!(retarget_references(&hosts[2], "bastion", None).other_options["proxyjump"], "edge");
+    assert!(!retarget_references(&hosts[1], "bastion", None).other_options.contains_key("proxyjump"));
+    assert!(!retarget_references(&hosts[3], "bastion", None).other_options.contains_key("proxycommand"));
+}
+
+#[test]
+fn missing_jump_host_is_flagged_but_fqdn_is_not() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let findings = lint_hosts(&hosts);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].host, "db");
+    assert_eq!(findings[0].rule, "missing-jump-host");
+}
+
+#[test]
+fn deleting_jump_host_warns_and_can_clear_references() {
+    let (_dir, mut app) = setup(CONFIG);
+
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "bastion");
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfirmDeleteDependents);
+
+    press(&mut app, KeyCode::Char('x'));
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.hosts.iter().all(|h| h.name != "bastion"));
+    assert_eq!(app.pending_changes.iter().filter(|c| matches!(c, ChangeType::Modified { .. })).count(), 3);
+    assert!(app.hosts.iter().all(|h| find_dependents(std::slice::from_ref(h), "bastion").is_empty()));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{ press, screen_rows, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, ListPosition, TreeItem };
+use tempfile::TempDir;
+
+/// 300 台主机：前 100 台在 work 文件夹中，其余在根目录
+fn setup(hosts: usize) -> (TempDir, App) {
+    let config: String = (1..=hosts)
+        .map(|i| {
+            let folder = if i <= 100 { "# @folder: work\n" } else { "" };
+            format!("{}Host host-{:03}\n    HostName 10.0.{}.{}\n\n", folder, i, i / 256, i % 256)
+        })
+        .collect();
+    common::setup(&config)
+}
+
+/// 列表下边框所在的行
+fn bottom_border(rows: &[String]) -> &String {
+    rows.iter().rev().find(|row| row.contains('└')).unwrap()
+}
+
+/// 滚动条滑块所在的行号
+fn thumb_rows(rows: &[String]) -> Vec<usize> {
+    rows.iter().enumerate().filter(|(_, row)| row.contains('┃')).map(|(y, _)| y).collect()
+}
+
+#[test]
+fn the_counter_counts_hosts_and_names_the_folder() {
+    let (_dir, mut app) = setup(300);
+    select_host(&mut app, "host-047");
+    let rows = screen_rows(&mut app, 80, 20);
+    assert!(bottom_border(&rows).contains(" host 47 of 300 (folder: work) "), "{}", bottom_border(&rows));
+
+    // 文件夹行不计数；根目录的主机没有文件夹
+    select_host(&mut app, "host-150");
+    let rows = screen_rows(&mut app, 80, 20);
+    assert!(bottom_border(&rows).contains(" host 150 of 300 "), "{}", bottom_border(&rows));
+    assert!(!bottom_border(&rows).contains("folder"));
+
+    app.list_state.select(Some(0));
+    assert_eq!(app.list_position().label(), "300 hosts (folder: work)");
+}
+
+#[test]
+fn collapsed_folders_still_count_their_hosts() {
+    let (_dir, mut app) = setup(300);
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char(' '));
+    assert!(matches!(app.tree_items[0], TreeItem::Folder { expanded: false, .. }));
+    assert_eq!(app.tree_items.len(), 201);
+
+    select_host(&mut app, "host-101");
+    assert_eq!(app.list_position(), ListPosition { host: Some(101), total: 300, folder: None });
+}
+
+#[test]
+fn the_counter_follows_the_search_results() {
+    let (_dir, mut app) = setup(300);
+    press(&mut app, KeyCode::Char('/'));
+    for c in "host-02".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Down);
+
+    let rows = screen_rows(&mut app, 80, 20);
+    assert!(bottom_border(&rows).contains(" host 1 of 10 (folder: work) "), "{}", bottom_border(&rows));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.list_position().label(), "host 2 of 10 (folder: work)");
+}
+
+#[test]
+fn the_scrollbar_tracks_the_list_offset() {
+    let (_dir, mut app) = setup(300);
+    app.list_state.select(Some(0));
+    let top = thumb_rows(&screen_rows(&mut app, 80, 20));
+    assert!(!top.is_empty());
+
+    select_host(&mut app, "host-300");
+    let rows = screen_rows(&mut app, 80, 20);
+    let bottom = thumb_rows(&rows);
+    assert!(app.list_state.offset() > 0);
+    assert!(bottom[0] > top[0], "{:?} {:?}", top, bottom);
+    // 滚到底时滑块贴着下边框
+    let border = rows.iter().rposition(|row| row.contains('└')).unwrap();
+    assert_eq!(*bottom.last().unwrap(), border - 1);
+
+    // 一屏放得下时没有滚动条
+    let (_dir, mut short) = setup(5);
+    assert!(thumb_rows(&screen_rows(&mut short, 80, 20)).is_empty());
+}
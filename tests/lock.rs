@@ -0,0 +1,137 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ lock_holder, lock_path, ConfigLock, LockStatus, Settings };
+use ssh_tui::core::{ App, AppMode };
+use std::fs;
+use std::process::{ Child, Command };
+use tempfile::TempDir;
+
+/// 另一个 "实例"：一个仍在运行的子进程的 PID
+fn live_process() -> Child {
+    Command::new("sleep").arg("30").spawn().unwrap()
+}
+
+fn stop(mut child: Child) {
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+/// 已经退出的进程的 PID
+fn dead_pid() -> u32 {
+    let mut child = Command::new("true").spawn().unwrap();
+    child.wait().unwrap();
+    child.id()
+}
+
+#[test]
+fn lock_is_acquired_once_and_released_on_drop() {
+    let dir = TempDir::new().unwrap();
+    let path = lock_path(&dir.path().join("config"));
+    assert_eq!(path, dir.path().join(".sshc.lock"));
+
+    let LockStatus::Acquired(lock) = ConfigLock::acquire(&path).unwrap() else {
+        panic!("the lock should be free");
+    };
+    assert_eq!(lock_holder(&path), Some(std::process::id()));
+    assert!(lock.is_held());
+    drop(lock);
+    assert!(!path.exists());
+
+    // 被抢走的锁在丢弃时保留给新的持有者
+    let LockStatus::Acquired(lock) = ConfigLock::acquire(&path).unwrap() else {
+        panic!("the lock should be free");
+    };
+    let other = live_process();
+    fs::write(&path, format!("{}\n", other.id())).unwrap();
+    assert!(!lock.is_held());
+    drop(lock);
+    assert_eq!(lock_holder(&path), Some(other.id()));
+    stop(other);
+}
+
+#[test]
+fn stale_and_corrupt_locks_are_reclaimed() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(".sshc.lock");
+    for content in [format!("{}\n", dead_pid()), "not a pid".to_string()] {
+        fs::write(&path, content).unwrap();
+        let LockStatus::Acquired(lock) = ConfigLock::acquire(&path).unwrap() else {
+            panic!("a stale lock should be reclaimed");
+        };
+        assert_eq!(lock_holder(&path), Some(lock.pid));
+    }
+}
+
+#[test]
+fn a_live_holder_offers_read_only_or_stealing() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    fs::write(&config, "Host web\n    HostName 10.0.0.1\n").unwrap();
+    let path = lock_path(&config);
+    let other = live_process();
+    fs::write(&path, format!("{}\n", other.id())).unwrap();
+    assert!(matches!(ConfigLock::acquire(&path).unwrap(), LockStatus::HeldBy(pid) if pid == other.id()));
+
+    let mut app = App::with_config_path(config, Settings::default()).unwrap();
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::ConfigLocked);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.status.as_ref().unwrap().text.contains(&format!("PID {}", other.id())));
+    assert_eq!(lock_holder(&path), Some(other.id()));
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(lock_holder(&path), Some(std::process::id()));
+    assert!(app.status.as_ref().unwrap().text.starts_with("Took the config lock"));
+
+    // 离开配置管理后释放
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(!path.exists());
+    stop(other);
+}
+
+#[test]
+fn saving_is_refused_after_the_lock_was_stolen() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    fs::write(&config, "Host web\n    HostName 10.0.0.1\n").unwrap();
+    let mut app = App::with_config_path(config.clone(), Settings::default()).unwrap();
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.pending_changes.len(), 1);
+
+    let other = live_process();
+    fs::write(lock_path(&config), format!("{}\n", other.id())).unwrap();
+    let error = app.apply_changes().unwrap_err().to_string();
+    assert!(error.contains(&format!("PID {}", other.id())), "{}", error);
+    assert!(fs::read_to_string(&config).unwrap().contains("Host web"));
+    assert_eq!(app.pending_changes.len(), 1);
+    stop(other);
+}
+
+#[test]
+fn saving_without_a_usable_lock_file_warns() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config");
+    fs::write(&config, "Host web\n    HostName 10.0.0.1\n").unwrap();
+    let mut app = App::with_config_path(config.clone(), Settings::default()).unwrap();
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+
+    // 锁文件的位置被目录占用，无法创建也无法接管
+    fs::remove_file(lock_path(&config)).unwrap();
+    fs::create_dir(lock_path(&config)).unwrap();
+    app.apply_changes().unwrap();
+    assert!(!fs::read_to_string(&config).unwrap().contains("Host web"));
+    let status = app.status.as_ref().unwrap();
+    assert!(status.text.ends_with("saving without a lock"), "{}", status.text);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{ ctrl, press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ lint_hosts, parse_ssh_config_file };
+use ssh_tui::core::{ AppMode, TreeItem };
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+Host bastion
+    HostName bastion.example.com
+
+Match host *.corp exec \"test -f ~/.corp-vpn\"
+    ProxyJump bastion
+
+    User corp
+
+Host build
+    HostName build.corp
+";
+
+#[test]
+fn match_block_is_parsed_as_one_opaque_entry() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+    let hosts = parse_ssh_config_file(&path).unwrap();
+
+    let names: Vec<&str> = hosts.iter().map(|host| host.name.as_str()).collect();
+    assert_eq!(names, ["bastion", "host *.corp exec \"test -f ~/.corp-vpn\"", "build"]);
+    assert!(hosts[1].is_match_block());
+    // Match 中的选项不会被算到前一个主机上
+    assert!(!hosts[0].other_options.contains_key("proxyjump"));
+    assert!(!hosts[0].other_options.contains_key("match"));
+    assert_eq!(hosts[2].hostname.as_deref(), Some("build.corp"));
+}
+
+#[test]
+fn match_blocks_stay_out_of_the_host_list() {
+    let (_dir, app) = setup(CONFIG);
+    let names: Vec<&str> = app.tree_items
+        .iter()
+        .filter_map(|item| match item {
+            TreeItem::Host { host_index } => Some(app.hosts[*host_index].name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, ["bastion", "build"]);
+}
+
+#[test]
+fn match_blocks_are_reported_by_lint_and_the_viewer() {
+    let (_dir, mut app) = setup(CONFIG);
+    let findings = lint_hosts(&app.hosts);
+    assert!(findings.iter().any(|finding| finding.rule == "match-block" && finding.host.starts_with("Match host")));
+
+    press(&mut app, KeyCode::Char('M'));
+    assert_eq!(app.mode, AppMode::ShowMessage);
+    let message = app.message.as_ref().unwrap();
+    assert!(message.lines.iter().any(|line| line.trim() == "User corp"));
+}
+
+#[test]
+fn editing_a_neighbour_keeps_the_match_block_in_place() {
+    let (dir, mut app) = setup(CONFIG);
+    app.list_state.select(Some(0));
+    for code in [KeyCode::Char('e'), KeyCode::Char('e'), KeyCode::Down, KeyCode::Char('x')] {
+        press(&mut app, code);
+    }
+    ctrl(&mut app, 's');
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    press(&mut app, KeyCode::Char('y'));
+
+    let written = fs::read_to_string(dir.path().join("config")).unwrap();
+    let bastion = written.find("Host bastion").unwrap();
+    let block = written.find("Match host *.corp exec \"test -f ~/.corp-vpn\"\n    ProxyJump bastion\n\n    User corp\n").unwrap();
+    let build = written.find("Host build").unwrap();
+    assert!(bastion < block && block < build);
+    assert!(written.contains("HostName bastion.example.comx"));
+}
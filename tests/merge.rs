@@ -0,0 +1,209 @@
+mod common;
+
+use common::{ config_path, press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ parse_ssh_config_from_str, SshHost };
+use ssh_tui::core::{ merge_host, merge_pending, AppMode, ChangeType, MergeChoice, MergeConflict, MergeState };
+use std::fs;
+use std::time::{ Duration, SystemTime };
+
+const BASE: &str = "\
+Host web
+    HostName 10.0.0.1
+    User deploy
+    ServerAliveInterval 30
+
+Host db
+    HostName 10.0.0.2
+
+Host old
+    HostName 10.0.0.3
+";
+
+fn hosts(content: &str) -> Vec<SshHost> {
+    parse_ssh_config_from_str(content)
+}
+
+fn host(hosts: &[SshHost], name: &str) -> SshHost {
+    hosts.iter().find(|host| host.name == name).unwrap().clone()
+}
+
+fn modified(base: &[SshHost], name: &str, edit: impl FnOnce(&mut SshHost)) -> ChangeType {
+    let old = host(base, name);
+    let mut new = old.clone();
+    edit(&mut new);
+    ChangeType::Modified { old, new }
+}
+
+#[test]
+fn disjoint_field_edits_merge() {
+    let base = host(&hosts(BASE), "web");
+    let mut mine = base.clone();
+    mine.user = Some("admin".to_string());
+    let mut theirs = base.clone();
+    theirs.hostname = Some("10.9.9.9".to_string());
+
+    let merged = merge_host(&base, &mine, &theirs).unwrap();
+    assert_eq!(merged.user.as_deref(), Some("admin"));
+    assert_eq!(merged.hostname.as_deref(), Some("10.9.9.9"));
+
+    // 同一字段改成不同值时冲突，改成相同值时不冲突
+    theirs.user = Some("ops".to_string());
+    assert_eq!(merge_host(&base, &mine, &theirs), None);
+    theirs.user = Some("admin".to_string());
+    assert!(merge_host(&base, &mine, &theirs).is_some());
+}
+
+#[test]
+fn options_merge_per_key_in_their_order() {
+    let base = host(&hosts(BASE), "web");
+    let mut mine = base.clone();
+    mine.other_options.insert("serveraliveinterval".to_string(), "60".to_string());
+    mine.other_options.push("localforward".to_string(), "8080 localhost:80".to_string());
+    let mut theirs = base.clone();
+    theirs.other_options.insert("proxyjump".to_string(), "bastion".to_string());
+
+    let merged = merge_host(&base, &mine, &theirs).unwrap();
+    let keys: Vec<&String> = merged.other_options.keys().collect();
+    assert_eq!(keys, ["serveraliveinterval", "proxyjump", "localforward"]);
+    assert_eq!(merged.other_options["serveraliveinterval"], "60");
+
+    theirs.other_options.insert("serveraliveinterval".to_string(), "15".to_string());
+    assert_eq!(merge_host(&base, &mine, &theirs), None);
+}
+
+#[test]
+fn adds_edits_and_deletes_reapply_onto_the_new_file() {
+    let base = hosts(BASE);
+    let disk = hosts(&format!("{}\nHost cache\n    HostName 10.0.0.4\n", BASE.replace("10.0.0.2", "10.0.0.22")));
+    let mut added = SshHost::new("new".to_string());
+    added.hostname = Some("10.0.0.5".to_string());
+    let changes = vec![
+        ChangeType::Added(added),
+        modified(&base, "db", |host| host.user = Some("postgres".to_string())),
+        ChangeType::Deleted(host(&base, "old")),
+    ];
+
+    let result = merge_pending(&disk, &changes);
+    assert!(result.conflicts.is_empty());
+    assert_eq!(result.changes.len(), 3);
+    let names: Vec<&str> = result.hosts.iter().map(|host| host.name.as_str()).collect();
+    assert_eq!(names, ["web", "db", "cache", "new"]);
+    let db = host(&result.hosts, "db");
+    assert_eq!((db.hostname.as_deref(), db.user.as_deref()), (Some("10.0.0.22"), Some("postgres")));
+    // 重新生成的修改以磁盘版本为旧值
+    assert!(matches!(&result.changes[1], ChangeType::Modified { old, .. } if old.hostname.as_deref() == Some("10.0.0.22")));
+}
+
+#[test]
+fn changes_that_cannot_be_reapplied_become_conflicts() {
+    let base = hosts(BASE);
+    let disk = hosts("Host web\n    HostName 10.0.0.1\n    User root\n    ServerAliveInterval 30\n\nHost old\n    HostName 10.0.0.30\n\nHost new\n    HostName 10.1.1.1\n");
+    let mut added = SshHost::new("new".to_string());
+    added.hostname = Some("10.0.0.5".to_string());
+    let changes = vec![
+        modified(&base, "web", |host| host.user = Some("admin".to_string())),
+        modified(&base, "db", |host| host.port = Some("2222".to_string())),
+        ChangeType::Deleted(host(&base, "old")),
+        ChangeType::Added(added.clone()),
+    ];
+
+    let result = merge_pending(&disk, &changes);
+    assert!(result.changes.is_empty());
+    let summary: Vec<(&str, bool, bool)> = result.conflicts
+        .iter()
+        .map(|conflict| (conflict.name.as_str(), conflict.mine.is_some(), conflict.theirs.is_some()))
+        .collect();
+    assert_eq!(summary, [("web", true, true), ("db", true, false), ("old", false, true), ("new", true, true)]);
+}
+
+#[test]
+fn changes_already_on_disk_are_dropped() {
+    let base = hosts(BASE);
+    let disk = hosts("Host web\n    HostName 10.0.0.1\n    User admin\n    ServerAliveInterval 30\n\nHost db\n    HostName 10.0.0.2\n");
+    let changes = vec![
+        modified(&base, "web", |host| host.user = Some("admin".to_string())),
+        ChangeType::Deleted(host(&base, "old")),
+    ];
+
+    let result = merge_pending(&disk, &changes);
+    assert!(result.changes.is_empty() && result.conflicts.is_empty());
+    assert_eq!(result.hosts, disk);
+}
+
+#[test]
+fn later_changes_to_a_conflicted_host_update_mine() {
+    let base = hosts(BASE);
+    let disk = hosts(&BASE.replace("User deploy", "User root"));
+    let first = modified(&base, "web", |host| host.user = Some("admin".to_string()));
+    let ChangeType::Modified { new: after_first, .. } = &first else { unreachable!() };
+    let mut renamed = after_first.clone();
+    renamed.name = "www".to_string();
+    let changes = vec![first.clone(), ChangeType::Modified { old: after_first.clone(), new: renamed }];
+
+    let result = merge_pending(&disk, &changes);
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].name, "www");
+    assert_eq!(result.conflicts[0].mine.as_ref().unwrap().user.as_deref(), Some("admin"));
+}
+
+#[test]
+fn choosing_mine_or_theirs_produces_pending_changes() {
+    let disk = hosts(BASE);
+    let mut mine_web = host(&disk, "web");
+    mine_web.user = Some("admin".to_string());
+    let conflicts = vec![
+        MergeConflict { name: "web".to_string(), mine: Some(mine_web), theirs: Some(host(&disk, "web")) },
+        MergeConflict { name: "old".to_string(), mine: None, theirs: Some(host(&disk, "old")) },
+        MergeConflict { name: "db".to_string(), mine: None, theirs: Some(host(&disk, "db")) },
+    ];
+    let result = ssh_tui::core::MergeResult { hosts: disk.clone(), changes: Vec::new(), conflicts };
+    let mut state = MergeState::new(result, disk.clone(), Vec::new());
+    state.choose(MergeChoice::Mine);
+    state.choose(MergeChoice::Mine);
+    assert!(!state.all_chosen());
+    state.choose(MergeChoice::Theirs);
+    assert!(state.all_chosen());
+
+    let (merged, changes) = state.finish();
+    let names: Vec<&str> = merged.iter().map(|host| host.name.as_str()).collect();
+    assert_eq!(names, ["web", "db"]);
+    assert_eq!(host(&merged, "web").user.as_deref(), Some("admin"));
+    assert!(matches!(&changes[..], [ChangeType::Modified { .. }, ChangeType::Deleted(deleted)] if deleted.name == "old"));
+}
+
+#[test]
+fn reload_offers_a_merge_and_resolves_conflicts_in_the_app() {
+    let (dir, mut app) = setup(BASE);
+    let path = config_path(&dir);
+
+    let index = app.hosts.iter().position(|host| host.name == "web").unwrap();
+    let web = app.hosts[index].clone();
+    let mut edited = web.clone();
+    edited.user = Some("admin".to_string());
+    app.pending_changes.push(ChangeType::Modified { old: web, new: edited.clone() });
+    app.hosts[index] = edited;
+
+    fs::write(&path, BASE.replace("User deploy", "User root").replace("10.0.0.2", "10.0.0.22")).unwrap();
+    let file = fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+    assert!(app.check_disk_changes());
+
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(app.mode, AppMode::ConfirmReload);
+    press(&mut app, KeyCode::Char('m'));
+    assert_eq!(app.mode, AppMode::ResolveConflicts);
+    // 还有未选择的冲突时 Enter 不生效
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ResolveConflicts);
+    press(&mut app, KeyCode::Char('m'));
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_eq!(app.changed_on_disk, None);
+    assert_eq!(host(&app.hosts, "web").user.as_deref(), Some("admin"));
+    assert_eq!(host(&app.hosts, "db").hostname.as_deref(), Some("10.0.0.22"));
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Modified { old, .. }] if old.user.as_deref() == Some("root")));
+    app.apply_changes().unwrap();
+    assert!(fs::read_to_string(&path).unwrap().contains("User admin"));
+}
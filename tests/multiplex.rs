@@ -0,0 +1,84 @@
+use ssh_tui::config::SshHost;
+use ssh_tui::core::{ control_path_configured, prewarm_args, teardown_args, Multiplexer, PrewarmJob, PrewarmOutcome };
+use std::process::Command;
+use std::thread;
+use std::time::{ Duration, Instant };
+
+fn host(name: &str) -> SshHost {
+    let mut host = SshHost::new(name.to_string());
+    host.hostname = Some("10.0.0.1".to_string());
+    host
+}
+
+fn shell(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    command
+}
+
+/// 轮询直到所有预热结束
+fn wait(multiplexer: &mut Multiplexer) -> Vec<(String, PrewarmOutcome)> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut finished = Vec::new();
+    while !multiplexer.jobs.is_empty() && Instant::now() < deadline {
+        finished.extend(multiplexer.poll());
+        thread::sleep(Duration::from_millis(20));
+    }
+    finished
+}
+
+#[test]
+fn prewarm_and_teardown_commands() {
+    let host = host("bastion");
+    assert_eq!(prewarm_args(&host), ["-fN", "-o", "ControlMaster=yes", "bastion"]);
+    assert_eq!(teardown_args(&host), ["-O", "exit", "bastion"]);
+
+    let effective = |path: &str| vec![("controlmaster".to_string(), "auto".to_string()), ("controlpath".to_string(), path.to_string())];
+    assert!(control_path_configured(&effective("~/.ssh/cm-%r@%h:%p")));
+    assert!(!control_path_configured(&effective("none")));
+    assert!(!control_path_configured(&[("controlmaster".to_string(), "auto".to_string())]));
+}
+
+#[test]
+fn successful_prewarm_marks_the_host_live() {
+    let mut multiplexer = Multiplexer::default();
+    multiplexer.jobs.push(PrewarmJob::spawn(host("bastion"), shell("exit 0"), Duration::from_secs(5)).unwrap());
+    assert!(multiplexer.is_pending("bastion"));
+    assert!(multiplexer.progress().unwrap().starts_with("⚡ Pre-warming bastion"));
+
+    assert_eq!(wait(&mut multiplexer), [("bastion".to_string(), PrewarmOutcome::Ready)]);
+    assert!(multiplexer.is_live("bastion") && !multiplexer.is_pending("bastion"));
+    assert_eq!(multiplexer.progress(), None);
+    // 测试中没有真正的主连接，不在丢弃时运行 ssh -O exit
+    multiplexer.live.clear();
+}
+
+#[test]
+fn failures_capture_stderr() {
+    let mut multiplexer = Multiplexer::default();
+    let script = "echo 'bastion: Permission denied (publickey,keyboard-interactive).' >&2; exit 255";
+    multiplexer.jobs.push(PrewarmJob::spawn(host("bastion"), shell(script), Duration::from_secs(5)).unwrap());
+    multiplexer.jobs.push(PrewarmJob::spawn(host("quiet"), shell("exit 1"), Duration::from_secs(5)).unwrap());
+
+    let mut finished = wait(&mut multiplexer);
+    finished.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(finished[0], ("bastion".to_string(), PrewarmOutcome::Failed(vec!["bastion: Permission denied (publickey,keyboard-interactive).".to_string()])));
+    assert!(matches!(&finished[1].1, PrewarmOutcome::Failed(lines) if lines[0].starts_with("ssh exited with")));
+    assert!(multiplexer.live.is_empty());
+}
+
+#[test]
+fn stuck_prewarms_time_out_and_can_be_cancelled() {
+    let mut multiplexer = Multiplexer::default();
+    let started = Instant::now();
+    multiplexer.jobs.push(PrewarmJob::spawn(host("mfa"), shell("echo 'Verification code:' >&2; exec sleep 30"), Duration::from_millis(200)).unwrap());
+    assert_eq!(wait(&mut multiplexer), [("mfa".to_string(), PrewarmOutcome::TimedOut(vec!["Verification code:".to_string()]))]);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!multiplexer.is_live("mfa"));
+
+    // W 取消仍在进行的预热
+    multiplexer.jobs.push(PrewarmJob::spawn(host("slow"), shell("exec sleep 30"), Duration::from_secs(60)).unwrap());
+    assert_eq!(multiplexer.teardown("slow"), Some(Ok(())));
+    assert!(multiplexer.jobs.is_empty());
+    assert_eq!(multiplexer.teardown("slow"), None);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::press;
+use crossterm::event::KeyCode;
+use ssh_tui::config::Settings;
+use ssh_tui::core::{ App, TreeItem };
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: db
+Host db-01
+    HostName 10.0.1.1
+
+# @folder: web
+Host web-01
+    HostName 10.0.0.1
+
+# @folder: web
+Host web-02
+    HostName 10.0.0.2
+";
+
+fn setup(skip: bool) -> (TempDir, App) {
+    let settings = Settings { skip_folder_rows: skip, ..Settings::default() };
+    let (dir, mut app) = common::setup_with(CONFIG, settings);
+    app.list_state.select(Some(0));
+    (dir, app)
+}
+
+/// 选中行的名称：主机名或 "[文件夹名]"
+fn selected(app: &App) -> String {
+    match &app.tree_items[app.list_state.selected().unwrap()] {
+        TreeItem::Host { host_index } => app.hosts[*host_index].name.clone(),
+        TreeItem::Folder { name, .. } => format!("[{}]", name),
+    }
+}
+
+#[test]
+fn folders_are_skipped_and_navigation_wraps() {
+    let (_dir, mut app) = setup(true);
+    let mut visited = Vec::new();
+    for _ in 0..4 {
+        press(&mut app, KeyCode::Down);
+        visited.push(selected(&app));
+    }
+    assert_eq!(visited, ["db-01", "web-01", "web-02", "db-01"]);
+
+    press(&mut app, KeyCode::Up);
+    assert_eq!(selected(&app), "web-02");
+}
+
+#[test]
+fn toggle_key_switches_folder_skipping() {
+    let (_dir, mut app) = setup(false);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(selected(&app), "[web]");
+
+    press(&mut app, KeyCode::Char('F'));
+    press(&mut app, KeyCode::Up);
+    assert_eq!(selected(&app), "db-01");
+}
+
+#[test]
+fn left_and_right_reach_folders() {
+    let (_dir, mut app) = setup(true);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(selected(&app), "web-01");
+
+    press(&mut app, KeyCode::Left);
+    assert_eq!(selected(&app), "[web]");
+    press(&mut app, KeyCode::Left);
+    assert!(matches!(app.tree_items[2], TreeItem::Folder { expanded: false, .. }));
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Right);
+    assert_eq!(selected(&app), "web-01");
+}
+
+#[test]
+fn all_collapsed_falls_back_to_folder_rows() {
+    let (_dir, mut app) = setup(true);
+    press(&mut app, KeyCode::Left);
+    press(&mut app, KeyCode::Down);
+    // 先跳到所属文件夹，再收起
+    press(&mut app, KeyCode::Left);
+    press(&mut app, KeyCode::Left);
+    assert!(app.tree_items.iter().all(|item| matches!(item, TreeItem::Folder { .. })));
+
+    press(&mut app, KeyCode::Up);
+    assert_eq!(selected(&app), "[db]");
+}
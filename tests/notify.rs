@@ -0,0 +1,17 @@
+use ssh_tui::config::Settings;
+use ssh_tui::core::should_notify;
+use std::time::Duration;
+
+#[test]
+fn notifications_are_off_by_default() {
+    let settings = Settings::default();
+    assert_eq!(settings.notify_after_minutes, 0);
+    assert!(!should_notify(Duration::from_secs(24 * 3600), settings.notify_after_minutes));
+}
+
+#[test]
+fn only_sessions_longer_than_the_threshold_notify() {
+    let settings: Settings = toml::from_str("notify_after_minutes = 10").unwrap();
+    assert!(!should_notify(Duration::from_secs(9 * 60 + 59), settings.notify_after_minutes));
+    assert!(should_notify(Duration::from_secs(10 * 60), settings.notify_after_minutes));
+}
@@ -0,0 +1,89 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ drop_long_lines, parse_config_tree_with_limits, ParseError, ParseLimits, Settings };
+use ssh_tui::core::{ App, AppMode };
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::time::{ Duration, Instant };
+use tempfile::TempDir;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn parse_error(result: anyhow::Result<impl Sized>) -> ParseError {
+    match result {
+        Ok(_) => panic!("expected a parse error"),
+        Err(e) => e.downcast_ref::<ParseError>().cloned().unwrap(),
+    }
+}
+
+#[test]
+fn binary_content_fails_fast() {
+    let result = parse_config_tree_with_limits(&fixture("binary_tail.config"), &ParseLimits::default());
+    assert_eq!(parse_error(result), ParseError::Binary { offset: 41 });
+
+    // 应用启动同样失败，而不是显示一堆垃圾主机
+    let error = App::with_config_path(fixture("binary_tail.config"), Settings::default()).err().unwrap();
+    assert!(error.to_string().contains("NUL byte at offset 41"));
+}
+
+#[test]
+fn oversized_files_are_rejected_before_reading() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, format!("Host web\n    HostName 10.0.0.1\n{}", "# padding\n".repeat(10_000))).unwrap();
+    let limits = ParseLimits { max_bytes: 4096, ..ParseLimits::default() };
+
+    let started = Instant::now();
+    let error = parse_error(parse_config_tree_with_limits(&path, &limits));
+    assert_eq!(error, ParseError::TooLarge { size: 100_031, limit: 4096 });
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    // 被 Include 的文件同样受限
+    fs::write(dir.path().join("main"), format!("Include {}\n", path.display())).unwrap();
+    assert!(matches!(parse_error(parse_config_tree_with_limits(&dir.path().join("main"), &limits)), ParseError::TooLarge { .. }));
+}
+
+#[test]
+fn long_lines_are_skipped_and_reported() {
+    let (hosts, _, report) = parse_config_tree_with_limits(&fixture("long_line.config"), &ParseLimits::default()).unwrap();
+    let names: Vec<&str> = hosts.iter().map(|host| host.name.as_str()).collect();
+    assert_eq!(names, ["web", "db"]);
+    assert!(!hosts[0].other_options.contains_key("proxycommand"));
+    assert_eq!(report.long_lines, [(fixture("long_line.config"), 3)]);
+    assert_eq!(report.banner().unwrap(), "⚠ skipped over-long line long_line.config:3 (max_config_line_length) — read-only");
+
+    let (content, skipped) = drop_long_lines("short\nthis is long\nok", 8);
+    assert_eq!((content.as_ref(), skipped), ("short\n\nok", vec![2]));
+}
+
+#[test]
+fn host_count_is_capped_and_the_app_opens_read_only() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    let config: String = (1..=25).map(|i| format!("Host junk-{}\n    HostName 10.0.0.{}\n", i, i)).collect();
+    fs::write(&path, format!("Match host *.internal\n    User ops\n{}", config)).unwrap();
+    let settings = Settings { max_hosts: 10, ..Settings::default() };
+    let limits = settings.parse_limits();
+    assert_eq!(limits.max_hosts, 10);
+
+    let (hosts, _, report) = parse_config_tree_with_limits(&path, &limits).unwrap();
+    assert_eq!(hosts.iter().filter(|host| !host.is_verbatim()).count(), 10);
+    assert!(hosts[0].is_match_block());
+    assert_eq!(report.hidden_hosts, 15);
+    assert_eq!(report.banner().unwrap(), "⚠ only the first 10 of 25 hosts are shown (max_hosts) — read-only");
+
+    let mut app = App::with_config_path(path, settings).unwrap();
+    assert!(app.read_only);
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    assert_eq!(app.mode, AppMode::Normal);
+}
+
+#[test]
+fn defaults_are_generous() {
+    let settings = Settings::default();
+    assert_eq!(settings.parse_limits(), ParseLimits::default());
+    assert_eq!((settings.max_config_bytes, settings.max_config_line_length, settings.max_hosts), (16 * 1024 * 1024, 16 * 1024, 10_000));
+    let settings: Settings = toml::from_str("max_hosts = 50000").unwrap();
+    assert_eq!(settings.max_hosts, 50_000);
+}
@@ -0,0 +1,71 @@
+use ssh_tui::config::{ create_parent_dir, home_candidates, home_dir, resolve_home, set_home_override, sshc_dir_from, SshcDir };
+use ssh_tui::core::App;
+use ssh_tui::utils::SshcError;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+    let vars: Vec<(String, String)> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |name| vars.iter().find(|(k, _)| k == name).map(|(_, v)| OsString::from(v))
+}
+
+#[test]
+fn xdg_variables_override_every_directory() {
+    let home = TempDir::new().unwrap();
+    let vars = [("XDG_CONFIG_HOME", "/xdg/config"), ("XDG_STATE_HOME", "/xdg/state"), ("XDG_DATA_HOME", "/xdg/data")];
+    assert_eq!(sshc_dir_from(SshcDir::Config, home.path(), env(&vars)), Path::new("/xdg/config/sshc"));
+    assert_eq!(sshc_dir_from(SshcDir::State, home.path(), env(&vars)), Path::new("/xdg/state/sshc"));
+    assert_eq!(sshc_dir_from(SshcDir::Data, home.path(), env(&vars)), Path::new("/xdg/data/sshc"));
+}
+
+#[test]
+fn relative_xdg_values_are_ignored() {
+    let home = TempDir::new().unwrap();
+    // 已有的 ~/.config 风格目录在任何平台上都沿用
+    fs::create_dir_all(home.path().join(".local/state/sshc")).unwrap();
+    let dir = sshc_dir_from(SshcDir::State, home.path(), env(&[("XDG_STATE_HOME", "state")]));
+    assert_eq!(dir, home.path().join(".local/state/sshc"));
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn unset_variables_fall_back_to_home() {
+    let home = TempDir::new().unwrap();
+    let none = env(&[]);
+    assert_eq!(sshc_dir_from(SshcDir::Config, home.path(), &none), home.path().join(".config/sshc"));
+    assert_eq!(sshc_dir_from(SshcDir::State, home.path(), &none), home.path().join(".local/state/sshc"));
+    assert_eq!(sshc_dir_from(SshcDir::Data, home.path(), &none), home.path().join(".local/share/sshc"));
+}
+
+#[cfg(unix)]
+#[test]
+fn directories_are_created_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = TempDir::new().unwrap();
+    let file = sshc_dir_from(SshcDir::Data, home.path(), env(&[])).join("trash.toml");
+    create_parent_dir(&file).unwrap();
+    let mode = fs::metadata(file.parent().unwrap()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+    // 已存在时不报错
+    create_parent_dir(&file).unwrap();
+}
+
+#[test]
+fn an_explicit_home_that_is_not_a_directory_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing");
+    set_home_override(missing.clone());
+
+    // 不退回到真实的 home
+    assert_eq!(home_candidates(), vec![missing.clone()]);
+    assert_eq!(home_dir(), None);
+    match resolve_home() {
+        Err(SshcError::HomeNotDirectory { source, path }) => assert_eq!((source, path), ("--home", missing.clone())),
+        other => panic!("{:?}", other),
+    }
+    let err = App::new().err().unwrap();
+    assert_eq!(err.to_string(), format!("Config error: --home is set to {}, which is not a directory", missing.display()));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{ ctrl, setup };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ host_line_matches, host_pattern_matches, match_destination, parse_ssh_config_from_str, split_host_patterns };
+use ssh_tui::core::AppMode;
+
+const CONFIG: &str = "\
+User root
+
+Host web-*
+    User deploy
+    Port 2222
+
+Host web-01 \"web one\"
+    Port 22
+    IdentityFile ~/.ssh/web
+
+Match host db
+    User dba
+
+Host * !bastion
+    IdentityFile ~/.ssh/default
+    ServerAliveInterval 30
+";
+
+#[test]
+fn globs_follow_openssh_semantics() {
+    assert!(host_pattern_matches("web-*", "web-01"));
+    assert!(host_pattern_matches("web-??", "web-01"));
+    assert!(!host_pattern_matches("web-?", "web-01"));
+    assert!(host_pattern_matches("*", ""));
+    // 与 ssh 一样不区分大小写
+    assert!(host_pattern_matches("Web-*", "WEB-01"));
+    assert!(!host_pattern_matches("web", "web-01"));
+}
+
+#[test]
+fn negated_patterns_exclude_the_whole_line() {
+    assert!(host_line_matches("web-* !web-01", "web-02"));
+    assert!(!host_line_matches("web-* !web-01", "web-01"));
+    // 只有否定模式的行不匹配任何目标
+    assert!(!host_line_matches("!web-01", "db"));
+    assert!(host_line_matches("db web-01", "web-01"));
+    assert_eq!(split_host_patterns("a \"b c\" !d"), ["a", "b c", "!d"]);
+}
+
+#[test]
+fn blocks_are_listed_in_file_order_with_first_match_winners() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    let result = match_destination(&hosts, "web-01");
+    let patterns: Vec<Option<&str>> = result.blocks.iter().map(|block| block.patterns.as_deref()).collect();
+    assert_eq!(patterns, [None, Some("web-*"), Some("web-01 \"web one\""), Some("* !bastion")]);
+    assert_eq!(result.skipped_match_blocks, 1);
+
+    let applies = |block: usize, key: &str| result.blocks[block].options.iter().find(|option| option.key == key).unwrap().applies;
+    // 全局的 User 抢先于 web-* 中的 User
+    assert!(applies(0, "User"));
+    assert!(!applies(1, "User"));
+    assert!(applies(1, "Port"));
+    assert!(!applies(2, "Port"));
+    // IdentityFile 可以累加，每个都生效
+    assert!(applies(2, "IdentityFile"));
+    assert!(applies(3, "IdentityFile"));
+    assert!(applies(3, "ServerAliveInterval"));
+
+    let bastion = match_destination(&hosts, "bastion");
+    assert_eq!(bastion.blocks.len(), 1);
+}
+
+#[test]
+fn ctrl_w_prompt_updates_while_typing() {
+    let (_dir, mut app) = setup(CONFIG);
+    ctrl(&mut app, 'w');
+    assert_eq!(app.mode, AppMode::WhatMatches);
+
+    let query = app.destination_query.as_mut().unwrap();
+    query.input.clear();
+    for c in "db".chars() {
+        app.handle_key(KeyEvent::from(KeyCode::Char(c))).unwrap();
+    }
+    let query = app.destination_query.as_ref().unwrap();
+    assert_eq!(query.input, "db");
+    assert_eq!(query.result.blocks.iter().filter_map(|block| block.patterns.as_deref()).collect::<Vec<_>>(), ["* !bastion"]);
+
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.destination_query.is_none());
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::{ screen_rows, setup };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::SshHost;
+use ssh_tui::core::{ App, AppMode, ChangeType };
+use ssh_tui::ui::pending_changes_indicator;
+
+const CONFIG: &str = "Host a\n    HostName 10.0.0.1\n";
+
+fn stage_changes(app: &mut App, count: usize) {
+    for i in 0..count {
+        app.pending_changes.push(ChangeType::Added(SshHost::new(format!("new-{}", i))));
+    }
+}
+
+#[test]
+fn no_indicator_without_changes() {
+    let (_dir, app) = setup(CONFIG);
+    assert_eq!(pending_changes_indicator(&app), None);
+}
+
+#[test]
+fn indicator_in_normal_mode() {
+    let (_dir, mut app) = setup(CONFIG);
+    stage_changes(&mut app, 3);
+    assert_eq!(pending_changes_indicator(&app).as_deref(), Some("● 3 unsaved changes (press e to review)"));
+}
+
+#[test]
+fn indicator_in_config_mode() {
+    let (_dir, mut app) = setup(CONFIG);
+    stage_changes(&mut app, 1);
+    app.mode = AppMode::ConfigManagement;
+    assert_eq!(pending_changes_indicator(&app).as_deref(), Some("● 1 unsaved change (press q to review)"));
+}
+
+#[test]
+fn indicator_in_search_mode() {
+    let (_dir, mut app) = setup(CONFIG);
+    stage_changes(&mut app, 2);
+    app.mode = AppMode::Search;
+    assert_eq!(pending_changes_indicator(&app).as_deref(), Some("● 2 unsaved changes"));
+}
+
+#[test]
+fn e_with_pending_changes_prompts_for_review() {
+    let (_dir, mut app) = setup(CONFIG);
+    stage_changes(&mut app, 1);
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    assert_eq!(app.mode, AppMode::ConfirmReviewPending);
+    // 只有一项时用单数
+    let rows = screen_rows(&mut app, 100, 30);
+    assert!(rows.iter().any(|row| row.contains("You have 1 unsaved change.")), "{}", rows.join("\n"));
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('y'))).unwrap();
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, buffer::Buffer, style::{ Color, Modifier }, Terminal };
+use ssh_tui::cli::parse_args;
+use ssh_tui::config::{ Settings, Theme };
+use ssh_tui::core::{ App, AppMode };
+use ssh_tui::ui::render;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: production
+Host api
+    HostName 10.0.0.1
+
+# @color: red
+Host web
+    HostName 10.0.0.2
+
+#sshc-disabled# Host old
+#sshc-disabled#     HostName 10.0.0.3
+";
+
+const SETTINGS: &str = r#"
+[[environments]]
+labels = ["production"]
+badge = "PRD"
+color = "red"
+"#;
+
+fn setup(theme: Theme) -> (TempDir, App) {
+    let settings: Settings = toml::from_str(SETTINGS).unwrap();
+    let (dir, mut app) = common::setup_with(CONFIG, settings);
+    app.theme = theme;
+    (dir, app)
+}
+
+fn draw(app: &mut App) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// 带颜色的单元格，格式为 (x, y, 字符)
+fn colored_cells(buffer: &Buffer) -> Vec<(u16, u16, String)> {
+    let area = buffer.area;
+    (0..area.height)
+        .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            let cell = buffer.get(x, y);
+            cell.fg != Color::Reset || cell.bg != Color::Reset
+        })
+        .map(|(x, y)| (x, y, buffer.get(x, y).symbol.clone()))
+        .collect()
+}
+
+fn row_text(buffer: &Buffer, y: u16) -> String {
+    (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect()
+}
+
+#[test]
+fn no_color_and_plain_pick_the_monochrome_theme() {
+    assert!(!Theme::select(false, None).plain);
+    assert!(!Theme::select(false, Some("")).plain);
+    assert_eq!(Theme::select(false, Some("1")), Theme::monochrome());
+    assert_eq!(Theme::select(true, None), Theme::monochrome());
+
+    assert!(parse_args(["--plain".to_string()]).unwrap().plain);
+    assert!(!parse_args(Vec::<String>::new()).unwrap().plain);
+}
+
+#[test]
+fn plain_screens_emit_no_colors() {
+    let (_dir, mut app) = setup(Theme::monochrome());
+    select_host(&mut app, "web");
+    let mut screens = vec![("main", draw(&mut app))];
+
+    press(&mut app, KeyCode::Char('L'));
+    screens.push(("lint", draw(&mut app)));
+    press(&mut app, KeyCode::Esc);
+
+    press(&mut app, KeyCode::Char('e'));
+    screens.push(("config", draw(&mut app)));
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    screens.push(("form", draw(&mut app)));
+    press(&mut app, KeyCode::Esc);
+
+    select_host(&mut app, "api");
+    press(&mut app, KeyCode::Char('d'));
+    screens.push(("delete", draw(&mut app)));
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    screens.push(("review", draw(&mut app)));
+
+    for (name, buffer) in &screens {
+        let colored = colored_cells(buffer);
+        assert!(colored.is_empty(), "{} screen has colored cells: {:?}", name, &colored[..colored.len().min(5)]);
+    }
+}
+
+#[test]
+fn plain_selection_uses_reverse_video_and_glyphs() {
+    let (_dir, mut app) = setup(Theme::monochrome());
+    select_host(&mut app, "web");
+    let buffer = draw(&mut app);
+
+    let selected = (0..buffer.area.height).find(|&y| row_text(&buffer, y).contains("web")).unwrap();
+    let start = row_text(&buffer, selected).find("web").unwrap() as u16;
+    assert!(buffer.get(start, selected).modifier.contains(Modifier::REVERSED));
+    // 文件夹靠 [-] 和缩进区分，环境徽章只加粗
+    let folder = (0..buffer.area.height).find(|&y| row_text(&buffer, y).contains("[-] production")).unwrap();
+    let api = row_text(&buffer, folder + 1);
+    let badge = api.find("PRD api").unwrap();
+    assert!(api[..badge].ends_with("  1 "), "{}", api);
+    assert!(buffer.get(api[..badge].chars().count() as u16, folder + 1).modifier.contains(Modifier::BOLD));
+}
+
+#[test]
+fn the_colored_theme_still_colors_the_same_screens() {
+    let (_dir, mut app) = setup(Theme::colored());
+    select_host(&mut app, "web");
+    assert!(!colored_cells(&draw(&mut app)).is_empty());
+
+    // 差异行的前缀在两种主题下相同
+    let (_dir, mut plain) = setup(Theme::monochrome());
+    for app in [&mut app, &mut plain] {
+        press(app, KeyCode::Char('e'));
+        select_host(app, "api");
+        press(app, KeyCode::Char('d'));
+        press(app, KeyCode::Char('y'));
+        press(app, KeyCode::Char('q'));
+    }
+    assert_eq!(app.review_lines, plain.review_lines);
+    assert!(plain.review_lines.iter().any(|line| line.starts_with("- Host api")));
+}
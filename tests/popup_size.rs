@@ -0,0 +1,105 @@
+mod common;
+
+use common::{ press, setup };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, layout::Rect, Terminal };
+use ssh_tui::core::{ AppMode, REVIEW_ZOOM_LIMIT };
+use ssh_tui::ui::{ popup_rect, render, PopupLimits, POPUP_ZOOM_STEP };
+
+fn area(width: u16, height: u16) -> Rect {
+    Rect::new(0, 0, width, height)
+}
+
+fn size(rect: Rect) -> (u16, u16) {
+    (rect.width, rect.height)
+}
+
+fn screen(terminal: &Terminal<TestBackend>) -> Vec<String> {
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect())
+        .collect()
+}
+
+#[test]
+fn percentages_are_clamped_across_terminal_sizes() {
+    let review = PopupLimits::new(90, 80);
+    let confirm = PopupLimits::new(50, 30);
+    // (终端, 审阅弹窗, 确认弹窗)
+    let matrix = [
+        ((30, 10), (30, 8), (30, 8)),
+        ((80, 24), (72, 19), (44, 8)),
+        ((120, 40), (108, 32), (60, 12)),
+        ((200, 60), (140, 48), (100, 18)),
+        ((400, 120), (140, 50), (140, 36)),
+    ];
+    for ((width, height), expected_review, expected_confirm) in matrix {
+        let terminal = area(width, height);
+        for (limits, expected) in [(review, expected_review), (confirm, expected_confirm)] {
+            let rect = popup_rect(limits, 0, terminal);
+            assert_eq!(size(rect), expected, "{}x{}", width, height);
+            // 居中且不超出终端
+            assert_eq!((rect.x, rect.y), ((width - rect.width) / 2, (height - rect.height) / 2));
+            assert!(terminal.union(rect) == terminal);
+        }
+    }
+    // 嵌套区域中同样居中
+    let inner = popup_rect(confirm, 0, Rect::new(10, 5, 100, 30));
+    assert_eq!((inner.x, inner.y, inner.width, inner.height), (35, 15, 50, 9));
+}
+
+#[test]
+fn content_raises_the_minimum_size() {
+    let limits = PopupLimits::new(50, 30).fit(60, 6);
+    assert_eq!(size(popup_rect(limits, 0, area(80, 24))), (64, 10));
+    assert_eq!(size(popup_rect(limits, 0, area(200, 60))), (100, 18));
+    // 终端比内容还小时占满终端
+    assert_eq!(size(popup_rect(limits, 0, area(50, 8))), (50, 8));
+    // 内容超过默认的最大尺寸时以内容为准
+    assert_eq!(size(popup_rect(PopupLimits::new(50, 30).fit(170, 4), 0, area(400, 120))), (174, 36));
+}
+
+#[test]
+fn zoom_grows_past_the_maximum_and_shrinks_to_the_minimum() {
+    let limits = PopupLimits::new(90, 80);
+    let terminal = area(400, 120);
+    assert_eq!(size(popup_rect(limits, 2, terminal)), (140 + 2 * POPUP_ZOOM_STEP.0, 50 + 2 * POPUP_ZOOM_STEP.1));
+    assert_eq!(size(popup_rect(limits, 100, terminal)), (400, 120));
+    assert_eq!(size(popup_rect(limits, -100, terminal)), (44, 8));
+    assert_eq!(size(popup_rect(limits, 3, area(80, 24))), (80, 24));
+}
+
+#[test]
+fn the_review_popup_resizes_and_confirmations_fit_small_terminals() {
+    let (_dir, mut app) = setup("Host web\n    HostName 10.0.0.1\n\nHost db\n    HostName 10.0.0.2\n");
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let base = app.review_height;
+    press(&mut app, KeyCode::Char('+'));
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    assert_eq!(app.review_height, base + POPUP_ZOOM_STEP.1 as usize);
+    for _ in 0..20 {
+        press(&mut app, KeyCode::Char('-'));
+    }
+    assert_eq!(app.review_zoom, -REVIEW_ZOOM_LIMIT);
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    assert_eq!(app.review_height, 12 - 4);
+
+    // 80×24 上放弃编辑的确认按钮保持在一行
+    press(&mut app, KeyCode::Esc);
+    app.list_state.select(None);
+    press(&mut app, KeyCode::Char('a'));
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfirmDiscardEdit);
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let rows = screen(&terminal);
+    assert!(rows.iter().any(|row| row.contains("Yes, discard changes") && row.contains("No, continue editing")), "{}", rows.join("\n"));
+}
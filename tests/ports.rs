@@ -0,0 +1,41 @@
+use ssh_tui::config::{ lint_hosts, parse_ssh_config_with_warnings, LintSeverity };
+use ssh_tui::core::probe_target;
+use ssh_tui::utils::{ lookup_service, resolve_port };
+
+const SERVICES: &str = "\
+# Network services
+ssh             22/tcp                          # SSH Remote Login Protocol
+domain          53/udp
+http            80/tcp          www             # WorldWideWeb HTTP
+";
+
+#[test]
+fn inline_comments_and_duplicates_in_port() {
+    let (hosts, warnings) = parse_ssh_config_with_warnings("Host a\n    Port 2222 # legacy\n    Port 2200\n");
+    assert_eq!(hosts[0].port.as_deref(), Some("2222"));
+    assert_eq!(warnings, ["line 3: duplicate 'Port' is ignored (ssh uses the first value)"]);
+}
+
+#[test]
+fn service_names_are_looked_up() {
+    assert_eq!(lookup_service(SERVICES, "ssh"), Some(22));
+    assert_eq!(lookup_service(SERVICES, "WWW"), Some(80));
+    assert_eq!(lookup_service(SERVICES, "domain"), None);
+    assert_eq!(lookup_service(SERVICES, "gopher"), None);
+
+    assert_eq!(resolve_port(" 2222 "), Some(2222));
+    assert_eq!(resolve_port("0"), None);
+    assert_eq!(resolve_port("no-such-service-here"), None);
+}
+
+#[test]
+fn unresolvable_port_skips_probe_and_fails_lint() {
+    let (hosts, _) = parse_ssh_config_with_warnings("Host a\n    HostName 10.0.0.1\n    Port bogus-port\n");
+
+    let skipped = probe_target(0, &hosts[0]).unwrap_err();
+    assert_eq!(skipped, "unknown port 'bogus-port'");
+
+    let findings = lint_hosts(&hosts);
+    assert_eq!(findings.len(), 1);
+    assert_eq!((findings[0].rule, findings[0].severity), ("non-numeric-port", LintSeverity::Error));
+}
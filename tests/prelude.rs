@@ -0,0 +1,70 @@
+mod common;
+
+use common::{ config_path, ctrl, press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ export_csv, parse_ssh_config_from_str };
+use ssh_tui::core::{ AppMode, TreeItem };
+use std::fs;
+
+const PRELUDE: &str = "\
+# Global defaults
+AddKeysToAgent yes
+
+  # keychain is macOS only
+UseKeychain yes
+";
+
+fn config() -> String {
+    format!("{}\nHost nas\n    HostName nas.lan\n", PRELUDE)
+}
+
+#[test]
+fn leading_options_become_one_prelude_entry() {
+    let hosts = parse_ssh_config_from_str(&config());
+    assert_eq!(hosts.len(), 2);
+    assert_eq!(hosts[0].prelude.as_deref(), Some(PRELUDE));
+    assert!(hosts[1].other_options.is_empty());
+
+    // 只有空行时不生成条目；没有 Host 时也保留
+    assert!(parse_ssh_config_from_str("\n\nHost a\n").iter().all(|host| !host.is_verbatim()));
+    assert!(parse_ssh_config_from_str("ForwardAgent no\n")[0].is_prelude());
+}
+
+#[test]
+fn prelude_is_not_listed_or_exported_as_a_host() {
+    let (_dir, mut app) = setup(&config());
+
+    let hosts: Vec<usize> = app.tree_items
+        .iter()
+        .filter_map(|item| match item {
+            TreeItem::Host { host_index } => Some(*host_index),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(hosts, [1]);
+    assert_eq!(export_csv(&app.hosts).unwrap().lines().count(), 2);
+
+    press(&mut app, KeyCode::Char('M'));
+    assert_eq!(app.mode, AppMode::ShowMessage);
+    assert!(app.message.as_ref().unwrap().lines.iter().any(|line| line.trim() == "UseKeychain yes"));
+}
+
+#[test]
+fn prelude_survives_adding_a_host_and_saving() {
+    let (dir, mut app) = setup(&config());
+    let path = config_path(&dir);
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('a'));
+    for c in "backup".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    ctrl(&mut app, 's');
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    press(&mut app, KeyCode::Char('y'));
+
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with(&format!("{}\nHost nas\n", PRELUDE)), "{}", written);
+    assert!(written.contains("Host backup"));
+}
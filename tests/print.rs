@@ -0,0 +1,80 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::cli::{ parse_args, PrintFormat };
+use ssh_tui::config::{ host_field, host_fields_line, host_json, parse_ssh_config_from_str, Settings };
+use ssh_tui::core::{ App, AppMode };
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: Work
+# @tags: prod, web
+# @env: AWS_PROFILE=prod
+Host web-prod
+    HostName %h.example.com
+    User deploy
+    Port 2222
+    LocalForward 8080 localhost:80
+    LocalForward 8443 localhost:443
+    ProxyJump bastion
+";
+
+#[test]
+fn print_options_parse() {
+    let print = |list: &[&str]| parse_args(list.iter().map(|s| s.to_string())).map(|options| options.print);
+    assert_eq!(print(&[]).unwrap(), None);
+    assert_eq!(print(&["--print"]).unwrap(), Some(PrintFormat::Name));
+    assert_eq!(print(&["--print", "--print-format", "json"]).unwrap(), Some(PrintFormat::Json));
+    assert_eq!(print(&["--print-format=json", "--print"]).unwrap(), Some(PrintFormat::Json));
+    let fields = Some(PrintFormat::Fields(vec!["name".to_string(), "hostname".to_string(), "port".to_string()]));
+    assert_eq!(print(&["--fields", "name, hostname,port"]).unwrap(), fields);
+    assert_eq!(print(&["--fields=name,hostname,port"]).unwrap(), fields);
+
+    assert!(print(&["--print-format", "yaml"]).is_err());
+    assert!(print(&["--fields", "name,password"]).is_err());
+    assert!(print(&["--fields", ","]).is_err());
+    assert!(print(&["--print-format", "json", "--fields", "name"]).is_err());
+}
+
+#[test]
+fn json_output_is_stable() {
+    let host = &parse_ssh_config_from_str(CONFIG)[0];
+    assert_eq!(
+        host_json(host).unwrap(),
+        concat!(
+            r#"{"name":"web-prod","hostname":"%h.example.com","user":"deploy","port":"2222","identity_file":null,"#,
+            r#""other_options":{"localforward":["8080 localhost:80","8443 localhost:443"],"proxyjump":["bastion"]},"#,
+            r#""folder":"Work","display_name":null,"description":null,"visible":true,"tags":["prod","web"],"aliases":[],"style":null,"color":null,"#,
+            r#""env":[["AWS_PROFILE","prod"]],"invalid_env":[],"match_block":null,"prelude":null,"temporary":false,"source":null,"disabled":false,"system":false,"connector":null}"#,
+        )
+    );
+}
+
+#[test]
+fn fields_are_tab_separated() {
+    let mut host = parse_ssh_config_from_str(CONFIG)[0].clone();
+    let fields = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    assert_eq!(host_fields_line(&host, &fields(&["name", "address", "port", "identity_file", "tags"])), "web-prod\tweb-prod.example.com\t2222\t\tprod,web");
+    assert_eq!(host_field(&host, "password"), None);
+
+    host.description = Some("line one\nline\ttwo".to_string());
+    assert_eq!(host_fields_line(&host, &fields(&["description", "visible"])), "line one line two\ttrue");
+}
+
+#[test]
+fn print_mode_picks_the_host_instead_of_connecting() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+    // 生产主机平时需要确认，--print 时不连接所以直接选中
+    let settings = Settings { production_labels: vec!["prod".to_string()], ..Settings::default() };
+    let mut app = App::with_config_path(path, settings).unwrap();
+    app.print_mode = true;
+
+    // 第一行是文件夹，下移到主机
+    app.handle_key(KeyEvent::from(KeyCode::Down)).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_eq!(app.pending_connect, None);
+    assert!(app.should_quit);
+    assert_eq!(app.picked.map(|index| app.hosts[index].name.as_str()), Some("web-prod"));
+}
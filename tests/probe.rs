@@ -0,0 +1,98 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ Settings, SshHost };
+use ssh_tui::core::{ probe_target, probe_tcp, App, AppMode, ProbeRun, ProbeStatus, ProbeTarget };
+use std::fs;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{ Duration, Instant };
+use tempfile::TempDir;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 绑定后立即释放的端口，连接会被拒绝
+fn closed_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+fn host(name: &str, port: u16) -> SshHost {
+    let mut host = SshHost::new(name.to_string());
+    host.hostname = Some("127.0.0.1".to_string());
+    host.port = Some(port.to_string());
+    host
+}
+
+#[test]
+fn probe_reports_open_and_closed_ports() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    assert!(matches!(probe_tcp("127.0.0.1", port, TIMEOUT), ProbeStatus::Reachable(_)));
+    assert!(matches!(probe_tcp("127.0.0.1", closed_port(), TIMEOUT), ProbeStatus::Unreachable(_)));
+}
+
+#[test]
+fn proxied_hosts_are_skipped() {
+    let mut jumped = host("inner", 22);
+    jumped.other_options.insert("proxyjump".to_string(), "bastion".to_string());
+    assert_eq!(probe_target(0, &jumped).unwrap_err(), "via proxy");
+
+    let mut bad_port = host("odd", 22);
+    bad_port.port = Some("no-such-service".to_string());
+    assert!(probe_target(0, &bad_port).is_err());
+}
+
+#[test]
+fn run_returns_every_result_beyond_the_concurrency_cap() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let targets: Vec<ProbeTarget> = (0..40)
+        .map(|key| ProbeTarget { key, address: "127.0.0.1".to_string(), port })
+        .collect();
+
+    let mut run = ProbeRun::start(targets, TIMEOUT);
+    let mut keys = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !run.is_finished() && Instant::now() < deadline {
+        keys.extend(run.poll().into_iter().map(|(key, _)| key));
+        thread::sleep(Duration::from_millis(10));
+    }
+    keys.sort();
+    assert_eq!(keys, (0..40).collect::<Vec<_>>());
+}
+
+#[test]
+fn dashboard_sorts_down_hosts_first_and_cancels_on_exit() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let up = listener.local_addr().unwrap().port();
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    let config = format!(
+        "Host alpha\n    HostName 127.0.0.1\n    Port {}\n\nHost beta\n    HostName 127.0.0.1\n    Port {}\n",
+        up,
+        closed_port()
+    );
+    fs::write(&path, config).unwrap();
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('!'))).unwrap();
+    assert_eq!(app.mode, AppMode::Dashboard);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while app.dashboard_probing() && Instant::now() < deadline {
+        app.tick();
+        thread::sleep(Duration::from_millis(10));
+    }
+    app.tick();
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('s'))).unwrap();
+    let names: Vec<&str> = app.dashboard.as_ref().unwrap().rows
+        .iter()
+        .map(|(index, _)| app.hosts[*index].name.as_str())
+        .collect();
+    assert_eq!(names, ["beta", "alpha"]);
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('r'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.dashboard.is_none());
+    assert!(!app.dashboard_probing());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ protection_rule, ProtectionRule, Settings, SshHost };
+use ssh_tui::core::{ App, AppMode };
+use tempfile::TempDir;
+
+fn labels() -> Vec<String> {
+    vec!["prod".to_string(), "Production".to_string()]
+}
+
+#[test]
+fn folder_and_tags_match_case_insensitively() {
+    let mut host = SshHost::new("db".to_string());
+    assert_eq!(protection_rule(&host, &labels()), None);
+
+    host.tags = vec!["web".to_string(), "PROD".to_string()];
+    assert_eq!(protection_rule(&host, &labels()), Some(ProtectionRule::Tag("PROD".to_string())));
+
+    // 文件夹优先于标签
+    host.folder = Some("production".to_string());
+    assert_eq!(protection_rule(&host, &labels()), Some(ProtectionRule::Folder("production".to_string())));
+
+    assert_eq!(protection_rule(&host, &[]), None);
+}
+
+#[test]
+fn labels_must_match_whole_names() {
+    let mut host = SshHost::new("db".to_string());
+    host.folder = Some("preprod".to_string());
+    host.tags = vec!["prod-like".to_string()];
+    assert_eq!(protection_rule(&host, &labels()), None);
+}
+
+fn setup(strict: bool) -> (TempDir, App) {
+    let settings = Settings { production_labels: labels(), production_type_name: strict, ..Settings::default() };
+    let (dir, mut app) = common::setup_with("# @tags: prod\nHost db-main\n    HostName 10.0.0.1\n\nHost scratch\n    HostName 10.0.0.2\n", settings);
+    select_host(&mut app, "db-main");
+    (dir, app)
+}
+
+#[test]
+fn production_hosts_always_ask_first() {
+    let (_dir, mut app) = setup(false);
+    assert!(!app.settings.confirm_connect);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfirmConnect);
+    assert!(app.pending_connect.is_none());
+
+    press(&mut app, KeyCode::Char('y'));
+    assert!(app.pending_connect.is_some());
+}
+
+#[test]
+fn strict_mode_requires_the_host_name() {
+    let (_dir, mut app) = setup(true);
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfirmConnect);
+    assert!(app.pending_connect.is_none());
+
+    press(&mut app, KeyCode::Backspace);
+    for c in "db-main".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.pending_connect.is_some());
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{ press, select_host, setup };
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+use ssh_tui::core::{ inside_tmux, App, AppMode };
+
+const CONFIG: &str = "\
+Host web-01
+    HostName 10.0.0.1
+
+Host web-02
+    HostName 10.0.0.2
+
+Host db-01
+    HostName 10.0.1.1
+
+Host db-02
+    HostName 10.0.1.2
+";
+
+fn queue(app: &mut App, name: &str) {
+    select_host(app, name);
+    press(app, KeyCode::Char('+'));
+}
+
+fn start(app: &mut App) {
+    app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL)).unwrap();
+}
+
+fn pending_name(app: &App) -> Option<String> {
+    app.pending_connect.map(|i| app.hosts[i].name.clone())
+}
+
+#[test]
+fn plus_toggles_hosts_in_queue() {
+    let (_dir, mut app) = setup(CONFIG);
+    queue(&mut app, "db-02");
+    queue(&mut app, "web-01");
+    assert_eq!(app.connect_queue, ["db-02", "web-01"]);
+
+    queue(&mut app, "db-02");
+    assert_eq!(app.connect_queue, ["web-01"]);
+
+    press(&mut app, KeyCode::Char('-'));
+    assert!(app.connect_queue.is_empty());
+}
+
+#[test]
+fn queue_survives_search() {
+    let (_dir, mut app) = setup(CONFIG);
+    queue(&mut app, "web-02");
+    press(&mut app, KeyCode::Char('/'));
+    for c in "db".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Esc);
+
+    assert_eq!(app.connect_queue, ["web-02"]);
+}
+
+#[test]
+fn running_the_queue_prompts_for_each_next_host() {
+    if inside_tmux() {
+        return;
+    }
+    let (_dir, mut app) = setup(CONFIG);
+    for name in ["db-01", "db-02", "web-02"] {
+        queue(&mut app, name);
+    }
+
+    start(&mut app);
+    assert_eq!(pending_name(&app).as_deref(), Some("db-01"));
+    assert_eq!(app.connect_queue, ["db-02", "web-02"]);
+
+    // 模拟会话结束
+    app.pending_connect = None;
+    app.queue_session_finished();
+    assert_eq!(app.mode, AppMode::QueueNext);
+
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(app.mode, AppMode::QueueNext);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(pending_name(&app).as_deref(), Some("web-02"));
+
+    app.pending_connect = None;
+    app.queue_session_finished();
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(!app.queue_running);
+}
+
+#[test]
+fn stopping_keeps_remaining_hosts_queued() {
+    if inside_tmux() {
+        return;
+    }
+    let (_dir, mut app) = setup(CONFIG);
+    for name in ["web-01", "db-01"] {
+        queue(&mut app, name);
+    }
+
+    start(&mut app);
+    app.pending_connect = None;
+    app.queue_session_finished();
+    press(&mut app, KeyCode::Char('q'));
+
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(!app.queue_running);
+    assert_eq!(app.connect_queue, ["db-01"]);
+}
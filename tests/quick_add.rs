@@ -0,0 +1,58 @@
+use ssh_tui::core::parse_ssh_command;
+
+#[test]
+fn parses_user_host_and_port() {
+    let host = parse_ssh_command("ssh admin@203.0.113.7 -p 2200", &[]).unwrap();
+    assert_eq!(host.name, "203.0.113.7");
+    assert_eq!(host.hostname.as_deref(), Some("203.0.113.7"));
+    assert_eq!(host.user.as_deref(), Some("admin"));
+    assert_eq!(host.port.as_deref(), Some("2200"));
+}
+
+#[test]
+fn parses_options_before_destination() {
+    let host = parse_ssh_command("ssh -i ~/.ssh/deploy -l ops -p2222 -J bastion -o ServerAliveInterval=30 -A db1.prod.example.com uptime", &[]).unwrap();
+    assert_eq!(host.name, "db1");
+    assert_eq!(host.hostname.as_deref(), Some("db1.prod.example.com"));
+    assert_eq!(host.user.as_deref(), Some("ops"));
+    assert_eq!(host.port.as_deref(), Some("2222"));
+    assert_eq!(host.identity_file.as_deref(), Some("~/.ssh/deploy"));
+    assert_eq!(host.other_options["proxyjump"], "bastion");
+    assert_eq!(host.other_options["serveraliveinterval"], "30");
+}
+
+#[test]
+fn repeated_options_are_all_kept() {
+    let command = "ssh -i ~/.ssh/a -i ~/.ssh/b -o SendEnv=LANG -o SendEnv=LC_ALL -L 8080:localhost:80 -L 127.0.0.1:5432:[::1]:5432 -R 9000:app:9000 -D 1080 web.example.com";
+    let host = parse_ssh_command(command, &[]).unwrap();
+    assert_eq!(host.identity_file.as_deref(), Some("~/.ssh/a"));
+    let all = |key| host.other_options.get_all(key).map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(all("identityfile"), ["~/.ssh/b"]);
+    assert_eq!(all("sendenv"), ["LANG", "LC_ALL"]);
+    assert_eq!(all("localforward"), ["8080 localhost:80", "127.0.0.1:5432 [::1]:5432"]);
+    assert_eq!(all("remoteforward"), ["9000 app:9000"]);
+    assert_eq!(all("dynamicforward"), ["1080"]);
+}
+
+#[test]
+fn parses_ssh_url_and_bare_destination() {
+    let host = parse_ssh_command("ssh ssh://git@forge.example.org:2022", &[]).unwrap();
+    assert_eq!((host.user.as_deref(), host.port.as_deref()), (Some("git"), Some("2022")));
+
+    let host = parse_ssh_command("  root@10.1.2.3\n", &[]).unwrap();
+    assert_eq!(host.user.as_deref(), Some("root"));
+}
+
+#[test]
+fn name_avoids_existing_hosts() {
+    let host = parse_ssh_command("ssh web.example.com", &["web"]).unwrap();
+    assert_eq!(host.name, "web-2");
+}
+
+#[test]
+fn irrelevant_text_is_rejected() {
+    assert!(parse_ssh_command("hello world, how are you", &[]).is_none());
+    assert!(parse_ssh_command("ssh -v", &[]).is_none());
+    assert!(parse_ssh_command("ssh -p", &[]).is_none());
+    assert!(parse_ssh_command("", &[]).is_none());
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ ConfigGuard, Settings };
+use ssh_tui::core::{ App, AppMode, ChangeType, GuardAction, GUARD_DELAY };
+use std::time::Instant;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @tags: prod
+Host db-main
+    HostName 10.0.0.1
+
+Host scratch
+    HostName 10.0.0.2
+";
+
+fn setup(settings: Settings) -> (TempDir, App) {
+    let settings = Settings { production_labels: vec!["prod".to_string()], ..settings };
+    common::setup_with(CONFIG, settings)
+}
+
+fn deleted_names(app: &App) -> Vec<String> {
+    app.pending_changes
+        .iter()
+        .filter_map(|change| match change {
+            ChangeType::Deleted(host) => Some(host.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn delete_in_normal_mode_stages_and_enters_config_management() {
+    let (_dir, mut app) = setup(Settings::default());
+    select_host(&mut app, "scratch");
+
+    press(&mut app, KeyCode::Delete);
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+    assert!(!app.delete_confirm_strict());
+    press(&mut app, KeyCode::Char('y'));
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(deleted_names(&app), ["scratch"]);
+    assert!(app.hosts.iter().all(|host| host.name != "scratch"));
+}
+
+#[test]
+fn cancelling_returns_to_normal_mode() {
+    let (_dir, mut app) = setup(Settings::default());
+    select_host(&mut app, "scratch");
+
+    press(&mut app, KeyCode::Delete);
+    press(&mut app, KeyCode::Char('n'));
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.pending_changes.is_empty());
+    assert_eq!(app.delete_target, None);
+
+    // 只读模式下不打开确认
+    app.read_only = true;
+    press(&mut app, KeyCode::Delete);
+    assert_eq!(app.mode, AppMode::Normal);
+}
+
+#[test]
+fn production_hosts_require_typing_the_name() {
+    let (_dir, mut app) = setup(Settings::default());
+    select_host(&mut app, "db-main");
+
+    press(&mut app, KeyCode::Delete);
+    assert!(app.delete_confirm_strict());
+    // y 只是输入的一部分，不会确认
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+    assert!(app.pending_changes.is_empty());
+
+    press(&mut app, KeyCode::Backspace);
+    for c in "db-main".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(deleted_names(&app), ["db-main"]);
+}
+
+#[test]
+fn config_guard_runs_before_the_delete_confirmation() {
+    let (_dir, mut app) = setup(Settings { config_guard: ConfigGuard::Confirm, ..Settings::default() });
+    select_host(&mut app, "scratch");
+
+    press(&mut app, KeyCode::Delete);
+    assert_eq!(app.mode, AppMode::ConfirmGuard);
+    assert_eq!(app.guard.as_ref().unwrap().action, GuardAction::DeleteHost);
+    app.guard.as_mut().unwrap().opened = Instant::now() - GUARD_DELAY;
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+
+    // 在配置管理中按 d 删除仍只需 y 确认
+    app.mode = AppMode::ConfigManagement;
+    select_host(&mut app, "db-main");
+    press(&mut app, KeyCode::Char('d'));
+    assert!(!app.delete_confirm_strict());
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(deleted_names(&app), ["db-main"]);
+}
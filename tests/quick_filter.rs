@@ -0,0 +1,90 @@
+mod common;
+
+use common::{ ctrl, press, select_host, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, AppMode, QuickFilter, TreeItem };
+
+const CONFIG: &str = "\
+# @folder: work
+Host web
+    HostName 10.0.0.1
+    IdentityFile ~/.ssh/web
+
+# @folder: work
+Host bare
+
+Host db
+    HostName 10.0.1.1
+
+# @visible: false
+Host old
+";
+
+fn listed(app: &App) -> Vec<String> {
+    app.tree_items
+        .iter()
+        .filter_map(|item| match item {
+            TreeItem::Host { host_index } => Some(app.hosts[*host_index].name.clone()),
+            TreeItem::Folder { .. } => None,
+        })
+        .collect()
+}
+
+#[test]
+fn filters_stack_and_clear() {
+    let (_dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char('\\'));
+    assert_eq!(app.mode, AppMode::FilterMenu);
+
+    press(&mut app, KeyCode::Char('2'));
+    assert_eq!(listed(&app), ["bare", "db"]);
+
+    press(&mut app, KeyCode::Char('3'));
+    assert_eq!(app.quick_filters, [QuickFilter::MissingIdentityFile, QuickFilter::NoFolder]);
+    assert_eq!(listed(&app), ["db"]);
+
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+    press(&mut app, KeyCode::Esc);
+    assert!(app.quick_filters.is_empty());
+    assert_eq!(listed(&app), ["bare", "web", "db"]);
+}
+
+#[test]
+fn hidden_filter_shows_hidden_hosts() {
+    let (_dir, mut app) = setup(CONFIG);
+    app.toggle_quick_filter(QuickFilter::Hidden);
+    assert_eq!(listed(&app), ["old"]);
+
+    // 其他过滤条件仍不显示隐藏主机
+    app.toggle_quick_filter(QuickFilter::Hidden);
+    app.toggle_quick_filter(QuickFilter::MissingHostName);
+    assert_eq!(listed(&app), ["bare"]);
+}
+
+#[test]
+fn filters_apply_after_the_search_query() {
+    let (_dir, mut app) = setup(CONFIG);
+    app.toggle_quick_filter(QuickFilter::MissingHostName);
+    press(&mut app, KeyCode::Char('/'));
+    for c in "ol".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    assert_eq!(listed(&app), ["old"]);
+}
+
+#[test]
+fn pending_change_filter_tracks_edits() {
+    let (_dir, mut app) = setup(CONFIG);
+    app.toggle_quick_filter(QuickFilter::PendingChange);
+    assert!(listed(&app).is_empty());
+
+    app.toggle_quick_filter(QuickFilter::PendingChange);
+    select_host(&mut app, "db");
+    for code in [KeyCode::Char('e'), KeyCode::Char('e'), KeyCode::Down, KeyCode::Char('9')] {
+        press(&mut app, code);
+    }
+    ctrl(&mut app, 's');
+    app.toggle_quick_filter(QuickFilter::PendingChange);
+    assert_eq!(listed(&app), ["db"]);
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ file_writable, parse_config_tree, Settings };
+use ssh_tui::core::{ App, AppMode, ChangeType };
+use ssh_tui::ui::render;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const MANAGED: &str = "Host web\n    HostName 10.9.0.2\n    User deploy\n";
+
+fn make_read_only(path: &Path) {
+    let mut permissions = fs::metadata(path).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions).unwrap();
+}
+
+/// 主配置 Include 一个只读文件（web）和一个可写文件（db）
+fn setup(dir: &TempDir) -> App {
+    let included = dir.path().join("config.d");
+    fs::create_dir(&included).unwrap();
+    fs::write(included.join("managed"), MANAGED).unwrap();
+    make_read_only(&included.join("managed"));
+    fs::write(included.join("mine"), "Host db\n    HostName 10.0.0.3\n").unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, format!("Include {}/*\n\nHost local\n    HostName 127.0.0.1\n", included.display())).unwrap();
+    App::with_config_path(path, Settings::default()).unwrap()
+}
+
+#[test]
+fn writability_is_detected_per_included_file() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("file");
+    fs::write(&file, "Host a\n").unwrap();
+    assert!(file_writable(&file));
+    assert!(file_writable(&dir.path().join("missing")));
+    make_read_only(&file);
+    assert!(!file_writable(&file));
+
+    let app = setup(&dir);
+    let locked: Vec<(&str, bool)> = app.hosts
+        .iter()
+        .filter(|host| !host.is_verbatim())
+        .map(|host| (host.name.as_str(), host.locked))
+        .collect();
+    assert_eq!(locked, [("local", false), ("web", true), ("db", false)]);
+    let (hosts, _) = parse_config_tree(&dir.path().join("config")).unwrap();
+    assert!(hosts.iter().any(|host| host.name == "web" && host.locked));
+}
+
+#[test]
+fn edit_and_delete_are_blocked_up_front() {
+    let dir = TempDir::new().unwrap();
+    let mut app = setup(&dir);
+    press(&mut app, KeyCode::Char('e'));
+    for key in ['e', 'd', 'x'] {
+        select_host(&mut app, "web");
+        press(&mut app, KeyCode::Char(key));
+        assert_eq!(app.mode, AppMode::ConfirmCopySystem, "{}", key);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.mode, AppMode::ConfigManagement);
+    }
+    assert!(app.pending_changes.is_empty());
+
+    // 可写的被包含文件照常编辑
+    select_host(&mut app, "db");
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+}
+
+#[test]
+fn read_only_hosts_show_a_lock_and_the_copy_offer() {
+    let dir = TempDir::new().unwrap();
+    let mut app = setup(&dir);
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let text = |terminal: &Terminal<TestBackend>| {
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let screen = text(&terminal);
+    assert!(screen.lines().any(|line| line.contains("web (deploy@10.9.0.2) 🔒")), "{}", screen);
+    assert!(!screen.lines().any(|line| line.contains("db (") && line.contains('🔒')));
+
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('e'));
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let screen = text(&terminal);
+    assert!(screen.contains("web-2?"), "{}", screen);
+}
+
+#[test]
+fn copying_stages_a_renamed_host_in_the_main_config() {
+    let dir = TempDir::new().unwrap();
+    let mut app = setup(&dir);
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    let [ChangeType::Added(copy)] = &app.pending_changes[..] else {
+        panic!("expected the copy to be staged");
+    };
+    assert_eq!((copy.name.as_str(), copy.locked, copy.source.as_deref()), ("web-2", false, None));
+    assert_eq!(copy.user.as_deref(), Some("deploy"));
+    // 原条目保留，选中副本
+    assert!(app.hosts.iter().any(|host| host.name == "web" && host.locked));
+    assert_eq!(app.get_selected_host().unwrap().name, "web-2");
+
+    app.apply_changes().unwrap();
+    let main = fs::read_to_string(dir.path().join("config")).unwrap();
+    assert!(main.contains("Host web-2\n    HostName 10.9.0.2"), "{}", main);
+    assert_eq!(fs::read_to_string(dir.path().join("config.d").join("managed")).unwrap(), MANAGED);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{ press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ is_metadata_key, parse_ssh_config_from_str, serialize_host, SshHost, METADATA_KEYS };
+use ssh_tui::core::{ reference_lines, AppMode };
+
+#[test]
+fn every_metadata_key_is_in_the_table() {
+    let mut host = SshHost::new("web".to_string());
+    host.folder = Some("Work".to_string());
+    host.display_name = Some("Web".to_string());
+    host.description = Some("Frontend".to_string());
+    host.visible = false;
+    host.tags = vec!["nginx".to_string()];
+    host.aliases = vec!["www".to_string()];
+    host.style = Some("dim".to_string());
+    host.color = Some("red".to_string());
+    host.env = vec![("AWS_PROFILE".to_string(), "prod".to_string())];
+    host.connector = Some("telnet".to_string());
+
+    // 写出的每个元数据键都在表中，表中的每个键也都会被写出
+    let written: Vec<String> = serialize_host(&host, true).unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix("# @"))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, _)| key.to_string())
+        .collect();
+    for key in &written {
+        assert!(is_metadata_key(key), "# @{} is missing from METADATA_KEYS", key);
+    }
+    for metadata in METADATA_KEYS {
+        assert!(written.iter().any(|key| key == metadata.key), "# @{} is never written", metadata.key);
+    }
+}
+
+#[test]
+fn every_table_key_is_parsed_and_others_are_ignored() {
+    let plain = parse_ssh_config_from_str("Host web\n")[0].clone();
+    for metadata in METADATA_KEYS {
+        let parsed = &parse_ssh_config_from_str(&format!("# @{}: false\nHost web\n", metadata.key))[0];
+        assert_ne!(*parsed, plain, "# @{} has no effect", metadata.key);
+    }
+    assert_eq!(parse_ssh_config_from_str("# @foldr: Work\nHost web\n")[0], plain);
+}
+
+#[test]
+fn the_reference_covers_metadata_options_and_settings() {
+    let lines = reference_lines();
+    let has = |text: &str| lines.iter().any(|line| line.trim() == text);
+    for metadata in METADATA_KEYS {
+        assert!(has(metadata.syntax), "{}", metadata.syntax);
+    }
+    assert!(has("HostName") && has("ServerAliveInterval") && has("StrictHostKeyChecking") && has("ProxyJump"));
+    assert!(has("system_config_paths = []"));
+    assert!(has("config_guard_phrase = \"edit config\""));
+    assert!(has("inventory = (not set)"));
+}
+
+#[test]
+fn the_reference_opens_from_the_about_screen_and_searches() {
+    let (_dir, mut app) = setup("Host web\n");
+    press(&mut app, KeyCode::Char('v'));
+    press(&mut app, KeyCode::Char('m'));
+    assert_eq!(app.mode, AppMode::Reference);
+
+    press(&mut app, KeyCode::Char('/'));
+    for c in "@alias".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    let view = app.reference.as_ref().unwrap();
+    let current = view.search.current.unwrap();
+    assert!(view.lines[current].contains("# @alias:"));
+    assert_eq!(view.scroll, current);
+
+    // 唯一的匹配，n 回绕到它自己
+    press(&mut app, KeyCode::Char('n'));
+    assert_eq!(app.reference.as_ref().unwrap().search.current, Some(current));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Reference);
+    assert!(app.reference.as_ref().unwrap().search.query.is_empty());
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ShowVersion);
+    assert!(app.reference.is_none());
+}
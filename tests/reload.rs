@@ -0,0 +1,82 @@
+mod common;
+
+use common::{ ctrl, press };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, AppMode, StatusLevel };
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const CONFIG: &str = "Host alpha\n    HostName 10.0.0.1\n\nHost beta\n    HostName 10.0.0.2\n";
+
+fn setup() -> (TempDir, PathBuf, App) {
+    let (dir, app) = common::setup(CONFIG);
+    let path = common::config_path(&dir);
+    (dir, path, app)
+}
+
+/// 在配置管理中删除选中的主机并回到 Normal 模式，留下一项未保存变更
+fn stage_delete(app: &mut App) {
+    press(app, KeyCode::Char('e'));
+    press(app, KeyCode::Char('d'));
+    press(app, KeyCode::Char('y'));
+    assert_eq!(app.pending_changes.len(), 1);
+}
+
+#[test]
+fn ctrl_r_reloads_and_reports_host_count() {
+    let (_dir, path, mut app) = setup();
+    app.list_state.select(Some(1));
+    fs::write(&path, format!("Host aaa\n    HostName 10.0.0.9\n\n{}", CONFIG)).unwrap();
+
+    ctrl(&mut app, 'r');
+
+    assert_eq!(app.hosts.len(), 3);
+    assert_eq!(app.get_selected_host().map(|h| h.name.as_str()), Some("beta"));
+    let status = app.status.as_ref().unwrap();
+    assert_eq!(status.level, StatusLevel::Info);
+    assert_eq!(status.text, "Reloaded 3 hosts");
+}
+
+#[test]
+fn pending_changes_ask_first_and_can_be_discarded() {
+    let (_dir, _path, mut app) = setup();
+    stage_delete(&mut app);
+
+    press(&mut app, KeyCode::F(5));
+    assert_eq!(app.mode, AppMode::ConfirmReload);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.pending_changes.len(), 1);
+
+    press(&mut app, KeyCode::F(5));
+    press(&mut app, KeyCode::Char('d'));
+    assert!(app.pending_changes.is_empty());
+    assert_eq!(app.hosts.len(), 2);
+}
+
+#[test]
+fn save_first_writes_changes_before_reloading() {
+    let (_dir, path, mut app) = setup();
+    stage_delete(&mut app);
+
+    press(&mut app, KeyCode::F(5));
+    press(&mut app, KeyCode::Char('s'));
+
+    assert!(app.pending_changes.is_empty());
+    assert_eq!(app.hosts.len(), 1);
+    assert!(!fs::read_to_string(&path).unwrap().contains("Host alpha"));
+}
+
+#[test]
+fn failed_reload_keeps_hosts_in_memory() {
+    let (_dir, path, mut app) = setup();
+    // 配置路径变成目录，读取失败
+    fs::remove_file(&path).unwrap();
+    fs::create_dir(&path).unwrap();
+
+    press(&mut app, KeyCode::F(5));
+
+    assert_eq!(app.hosts.len(), 2);
+    assert_eq!(app.status.as_ref().unwrap().level, StatusLevel::Error);
+}
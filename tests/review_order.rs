@@ -0,0 +1,149 @@
+mod common;
+
+use common::press;
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+use ssh_tui::config::{ parse_ssh_config_from_str, Settings, SshHost };
+use ssh_tui::core::{ replay_changes, App, AppMode, ChangeType };
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn shift(app: &mut App, code: KeyCode) {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::SHIFT)).unwrap();
+}
+
+fn host(name: &str, hostname: &str) -> SshHost {
+    let mut host = SshHost::new(name.to_string());
+    host.hostname = Some(hostname.to_string());
+    host
+}
+
+/// 暂存变更并同步修改主机列表，和表单保存时一样
+fn stage(app: &mut App, change: ChangeType) {
+    match &change {
+        ChangeType::Added(host) => app.hosts.push(host.clone()),
+        ChangeType::Modified { old, new } => {
+            let index = app.hosts.iter().position(|host| host == old).unwrap();
+            app.hosts[index] = new.clone();
+        }
+        ChangeType::Deleted(host) => app.hosts.retain(|h| h != host),
+    }
+    app.pending_changes.push(change);
+}
+
+fn review_app(dir: &TempDir) -> (PathBuf, App) {
+    let path = dir.path().join("config");
+    fs::write(&path, "Host web\n    HostName 10.0.0.1\n").unwrap();
+    let mut app = App::with_config_path(path.clone(), Settings::default()).unwrap();
+    press(&mut app, KeyCode::Char('e'));
+    (path, app)
+}
+
+/// 先把 web 改名为 renamed，再新增主机 added；swap 为 true 时在审阅中把新增移到前面
+fn rename_then_add(dir: &TempDir, renamed: &str, added: &str, swap: bool) -> App {
+    let (_, mut app) = review_app(dir);
+    let web = app.hosts[0].clone();
+    stage(&mut app, ChangeType::Modified { old: web.clone(), new: SshHost { name: renamed.to_string(), ..web } });
+    stage(&mut app, ChangeType::Added(host(added, "10.0.0.9")));
+    app.open_review();
+    if swap {
+        press(&mut app, KeyCode::Tab);
+        shift(&mut app, KeyCode::Up);
+        assert!(matches!(&app.pending_changes[0], ChangeType::Added(_)));
+        assert_eq!(app.review_selected, 0);
+    }
+    app
+}
+
+#[test]
+fn rename_and_add_of_the_same_name_save_the_same_result_in_either_order() {
+    let mut written = Vec::new();
+    for swap in [false, true] {
+        let dir = TempDir::new().unwrap();
+        let mut app = rename_then_add(&dir, "web-old", "web", swap);
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.pending_changes.is_empty());
+        written.push(fs::read_to_string(dir.path().join("config")).unwrap());
+    }
+
+    assert_eq!(written[0], written[1]);
+    let hosts = parse_ssh_config_from_str(&written[0]);
+    let find = |name: &str| hosts.iter().find(|host| host.name == name).unwrap().hostname.clone();
+    // 改名作用于加载时的 web，新增的 web 保持新地址
+    assert_eq!(find("web-old").as_deref(), Some("10.0.0.1"));
+    assert_eq!(find("web").as_deref(), Some("10.0.0.9"));
+}
+
+#[test]
+fn a_rename_shows_the_old_and_new_name_in_either_order() {
+    for swap in [false, true] {
+        let dir = TempDir::new().unwrap();
+        let app = rename_then_add(&dir, "web-old", "web", swap);
+        let diff = app.generate_diff_lines();
+        let rename = diff.iter().position(|line| line == "- Host web").unwrap();
+        assert_eq!(diff[rename + 1], "+ Host web-old");
+        assert!(!diff.iter().any(|line| line.starts_with("~ Host")), "{:?}", diff);
+        // 新增的 web 在改名之前或之后，与审阅中的顺序一致
+        let added = diff.iter().position(|line| line == "+ Host web").unwrap();
+        assert_eq!(added < rename, swap, "{:?}", diff);
+    }
+}
+
+#[test]
+fn two_changes_ending_with_the_same_name_block_the_save() {
+    for swap in [false, true] {
+        let dir = TempDir::new().unwrap();
+        let mut app = rename_then_add(&dir, "api", "api", swap);
+        press(&mut app, KeyCode::Char('y'));
+
+        assert_eq!(app.mode, AppMode::ShowMessage);
+        assert_eq!(app.pending_changes.len(), 2);
+        let message = app.message.as_ref().unwrap().lines.join("\n");
+        assert!(message.contains("Changes 1") && message.contains("and 2"), "{}", message);
+        assert!(message.contains("both end with a host named api"), "{}", message);
+        assert_eq!(fs::read_to_string(dir.path().join("config")).unwrap(), "Host web\n    HostName 10.0.0.1\n");
+    }
+}
+
+#[test]
+fn moving_an_edit_before_its_add_is_reported_and_can_be_undone() {
+    let dir = TempDir::new().unwrap();
+    let (path, mut app) = review_app(&dir);
+    let added = host("db", "10.0.0.2");
+    stage(&mut app, ChangeType::Added(added.clone()));
+    stage(&mut app, ChangeType::Modified { old: added.clone(), new: host("db", "10.0.0.3") });
+    app.open_review();
+
+    press(&mut app, KeyCode::BackTab);
+    assert_eq!(app.review_selected, 1);
+    assert!(app.review_lines[app.review_selected_lines().start].starts_with("~ Host db"));
+    shift(&mut app, KeyCode::Up);
+    assert!(app.status.as_ref().unwrap().text.contains("needs a version of db"));
+    assert!(replay_changes(&app.original_hosts, &app.pending_changes).is_err());
+
+    // 第一条再下移即恢复原顺序，可以正常保存
+    shift(&mut app, KeyCode::Up);
+    assert_eq!(app.review_selected, 0);
+    shift(&mut app, KeyCode::Down);
+    assert_eq!(app.review_selected, 1);
+    assert!(matches!(&app.pending_changes[0], ChangeType::Added(_)));
+    press(&mut app, KeyCode::Char('y'));
+    assert!(fs::read_to_string(&path).unwrap().contains("HostName 10.0.0.3"));
+}
+
+#[test]
+fn replay_skips_temporary_hosts_and_removes_deleted_ones() {
+    let mut base = parse_ssh_config_from_str("Host a\n    HostName 10.0.0.1\n\nHost b\n    HostName 10.0.0.2\n");
+    let mut temporary = host("rescue", "10.0.0.99");
+    temporary.temporary = true;
+    base.push(temporary);
+
+    let changes = [ChangeType::Deleted(base[0].clone()), ChangeType::Added(host("c", "10.0.0.3"))];
+    let hosts = replay_changes(&base, &changes).unwrap();
+    assert_eq!(hosts.iter().map(|host| host.name.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+
+    // 同一台主机删除两次时第二次找不到
+    let error = replay_changes(&base, &[changes[0].clone(), changes[0].clone()]).unwrap_err();
+    assert!(error.starts_with("Change 2 (- a)"), "{}", error);
+}
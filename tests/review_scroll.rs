@@ -0,0 +1,73 @@
+mod common;
+
+use common::{ press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ max_review_scroll, App, AppMode };
+use tempfile::TempDir;
+
+/// 导入 40 个主机后进入审阅，diff 足够长需要滚动
+fn review_with_many_changes() -> (TempDir, App) {
+    let (dir, mut app) = setup("");
+
+    let snippet: String = (1..=40).map(|i| format!("Host node-{:02}\n    HostName 10.0.0.{}\n\n", i, i)).collect();
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('i'));
+    app.handle_paste(&snippet);
+    press(&mut app, KeyCode::F(2));
+    press(&mut app, KeyCode::Enter);
+    app.review_height = 10;
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    (dir, app)
+}
+
+#[test]
+fn max_scroll_keeps_the_last_line_at_the_bottom() {
+    assert_eq!(max_review_scroll(100, 20), 80);
+    assert_eq!(max_review_scroll(100, 1), 99);
+    assert_eq!(max_review_scroll(100, 100), 0);
+    // 内容比区域短时不能滚动
+    assert_eq!(max_review_scroll(10, 30), 0);
+    assert_eq!(max_review_scroll(0, 5), 0);
+    // 尚未渲染时按一行高计算
+    assert_eq!(max_review_scroll(100, 0), 99);
+}
+
+#[test]
+fn scrolling_stops_at_the_end_and_home_end_jump() {
+    let (_dir, mut app) = review_with_many_changes();
+    let last = app.review_lines.len() - app.review_height;
+    for _ in 0..50 {
+        press(&mut app, KeyCode::PageDown);
+    }
+    assert_eq!(app.review_scroll, last);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.review_scroll, last);
+    // 一次 ↑ 就能看到更早的内容
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.review_scroll, last - 1);
+
+    press(&mut app, KeyCode::Home);
+    assert_eq!(app.review_scroll, 0);
+    press(&mut app, KeyCode::End);
+    assert_eq!(app.review_scroll, last);
+}
+
+#[test]
+fn position_survives_a_round_trip_until_the_changes_differ() {
+    let (_dir, mut app) = review_with_many_changes();
+    for _ in 0..3 {
+        press(&mut app, KeyCode::PageDown);
+    }
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    assert_eq!(app.review_scroll, 30);
+
+    // 变更集不同了，从头开始
+    press(&mut app, KeyCode::Esc);
+    app.pending_changes.pop();
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.review_scroll, 0);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{ press, setup, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, AppMode };
+use tempfile::TempDir;
+
+/// 导入 40 个主机后进入审阅，diff 足够长需要滚动
+fn review_with_many_changes() -> (TempDir, App) {
+    let (dir, mut app) = setup("");
+
+    let snippet: String = (1..=40).map(|i| format!("Host node-{:02}\n    HostName 10.0.0.{}\n\n", i, i)).collect();
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('i'));
+    app.handle_paste(&snippet);
+    press(&mut app, KeyCode::F(2));
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    app.review_height = 10;
+    (dir, app)
+}
+
+#[test]
+fn search_jumps_to_match_and_scrolls_it_into_view() {
+    let (_dir, mut app) = review_with_many_changes();
+
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "NODE-30");
+    press(&mut app, KeyCode::Enter);
+
+    let current = app.review_search.current.unwrap();
+    assert!(app.review_lines[current].contains("node-30"));
+    assert!(current >= app.review_scroll && current < app.review_scroll + app.review_height);
+}
+
+#[test]
+fn n_and_shift_n_cycle_through_matches() {
+    let (_dir, mut app) = review_with_many_changes();
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "10.0.0.1");
+    press(&mut app, KeyCode::Enter);
+
+    // 10.0.0.1 和 10.0.0.10-19 共 11 个匹配
+    assert_eq!(app.review_matches().len(), 11);
+    let first = app.review_search.current.unwrap();
+    press(&mut app, KeyCode::Char('N'));
+    assert_eq!(app.review_search.current, app.review_matches().last().copied());
+    press(&mut app, KeyCode::Char('n'));
+    assert_eq!(app.review_search.current, Some(first));
+    // 搜索中 n 不会放弃变更
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    assert_eq!(app.pending_changes.len(), 40);
+}
+
+#[test]
+fn leaving_search_keeps_scroll_and_restores_discard_key() {
+    let (_dir, mut app) = review_with_many_changes();
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "node-35");
+    press(&mut app, KeyCode::Enter);
+    let scroll = app.review_scroll;
+    assert!(scroll > 0);
+
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    assert_eq!(app.review_scroll, scroll);
+    assert!(app.review_search.query.is_empty());
+
+    press(&mut app, KeyCode::Char('n'));
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.pending_changes.is_empty());
+}
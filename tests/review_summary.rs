@@ -0,0 +1,89 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ Settings, SshHost };
+use ssh_tui::core::{ changed_fields, summarize_changes, App, AppMode, ChangeType, SummaryKind };
+use std::fs;
+use tempfile::TempDir;
+
+fn host(name: &str, folder: Option<&str>) -> SshHost {
+    let mut host = SshHost::new(name.to_string());
+    host.hostname = Some(format!("{}.example.com", name));
+    host.folder = folder.map(str::to_string);
+    host
+}
+
+#[test]
+fn changed_fields_lists_basic_fields_metadata_and_options() {
+    let old = host("web-01", Some("work"));
+    let mut new = old.clone();
+    new.port = Some("2222".to_string());
+    new.identity_file = Some("~/.ssh/id_web".to_string());
+    new.tags = vec!["nginx".to_string()];
+    new.other_options.insert("proxyjump".to_string(), "bastion".to_string());
+    new.other_options.insert("localforward".to_string(), "8080 localhost:80".to_string());
+
+    assert_eq!(changed_fields(&old, &new), ["Tags", "Port", "IdentityFile", "LocalForward", "ProxyJump"]);
+    assert!(changed_fields(&old, &old).is_empty());
+}
+
+#[test]
+fn changed_fields_notices_repeated_options_and_renames() {
+    let mut old = host("db", None);
+    old.other_options.push("localforward".to_string(), "5432 localhost:5432".to_string());
+    let mut new = old.clone();
+    new.name = "db-primary".to_string();
+    new.other_options.push("localforward".to_string(), "6432 localhost:6432".to_string());
+
+    assert_eq!(changed_fields(&old, &new), ["Name", "LocalForward"]);
+}
+
+#[test]
+fn summary_counts_hosts_and_collects_folders() {
+    let web = host("web-01", Some("work"));
+    let mut web_port = web.clone();
+    web_port.port = Some("2222".to_string());
+    let mut web_user = web_port.clone();
+    web_user.user = Some("deploy".to_string());
+    let mut moved = host("lab-1", None);
+    moved.folder = Some("lab".to_string());
+
+    let changes = vec![
+        ChangeType::Added(host("new-box", None)),
+        ChangeType::Modified { old: web.clone(), new: web_port.clone() },
+        ChangeType::Deleted(host("old-db", Some("work"))),
+        // 同一主机再次修改时合并字段
+        ChangeType::Modified { old: web_port, new: web_user },
+        ChangeType::Modified { old: host("lab-1", None), new: moved },
+    ];
+    let summary = summarize_changes(&changes);
+
+    assert_eq!(summary.rows.len(), 4);
+    assert_eq!(summary.rows[1].fields, ["Port", "User"]);
+    assert_eq!(summary.rows[3].fields, ["Folder"]);
+    assert_eq!(summary.count(SummaryKind::Modified), 2);
+    assert_eq!(summary.headline(), "1 added, 2 modified, 1 deleted — folders affected: lab, work");
+}
+
+#[test]
+fn review_starts_with_the_summary_and_digits_jump_to_blocks() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    let config: String = (1..=30).map(|i| format!("Host node-{:02}\n    HostName 10.0.0.{}\n\n", i, i)).collect();
+    fs::write(&path, config).unwrap();
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+
+    // 删除前两个主机
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    for _ in 0..2 {
+        app.list_state.select(Some(0));
+        app.handle_key(KeyEvent::from(KeyCode::Char('d'))).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('y'))).unwrap();
+    }
+    app.handle_key(KeyEvent::from(KeyCode::Char('q'))).unwrap();
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+
+    assert_eq!(app.review_lines[0], "Summary: 2 deleted");
+    assert_eq!(app.review_lines[2], "  2. - node-02");
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('2'))).unwrap();
+    assert_eq!(app.review_lines[app.review_scroll], "- Host node-02");
+}
@@ -0,0 +1,52 @@
+use ssh_tui::config::SshHost;
+use ssh_tui::ui::formatted_row;
+
+fn host() -> SshHost {
+    let mut host = SshHost::new("web-01".to_string());
+    host.user = Some("deploy".to_string());
+    host.hostname = Some("10.0.0.1".to_string());
+    host.port = Some("2222".to_string());
+    host.description = Some("primary web server behind the eu load balancer".to_string());
+    host
+}
+
+#[test]
+fn wide_rows_are_unchanged() {
+    let host = host();
+    assert_eq!(formatted_row(&host, 200), host.get_full_display_info());
+}
+
+#[test]
+fn description_is_truncated_before_port() {
+    let row = formatted_row(&host(), 50);
+    assert_eq!(row.chars().count(), 50);
+    assert!(row.starts_with("web-01 (deploy@10.0.0.1 port:2222) # primary"));
+    assert!(row.ends_with('…'));
+}
+
+#[test]
+fn description_is_dropped_when_only_base_fits() {
+    assert_eq!(formatted_row(&host(), 36), "web-01 (deploy@10.0.0.1 port:2222)");
+}
+
+#[test]
+fn narrow_rows_drop_port_then_shorten_target() {
+    let host = host();
+    assert_eq!(formatted_row(&host, 24), "web-01 (deploy@10.0.0.1)");
+    assert_eq!(formatted_row(&host, 16), "web-01 (deploy…)");
+    assert_eq!(formatted_row(&host, 6), "web-01");
+    assert_eq!(formatted_row(&host, 4), "web…");
+    assert_eq!(formatted_row(&host, 0), "");
+}
+
+#[test]
+fn rows_never_exceed_width() {
+    let mut bare = host();
+    bare.user = None;
+    bare.display_name = Some("主机 web".to_string());
+    for host in [host(), bare] {
+        for width in 0..80 {
+            assert!(formatted_row(&host, width).chars().count() <= width, "width {}", width);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::{ ctrl, setup };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ratatui::style::{ Color, Modifier };
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::config::{ lint_hosts, parse_ssh_config_from_str, row_style, serialize_hosts };
+use ssh_tui::core::{ App, ChangeType, ROW_STYLE_FIELD };
+use ssh_tui::ui::render;
+
+const CONFIG: &str = "\
+# @style: dim
+Host legacy
+    HostName 10.0.0.1
+
+# @color: red
+Host prod-db
+    HostName 10.0.0.2
+
+# @style: blink
+# @color: rainbow
+Host odd
+    HostName 10.0.0.3
+
+Host plain
+    HostName 10.0.0.4
+";
+
+#[test]
+fn style_metadata_round_trips() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert_eq!(hosts[0].style.as_deref(), Some("dim"));
+    assert_eq!(hosts[1].color.as_deref(), Some("red"));
+    assert_eq!(serialize_hosts(&hosts).unwrap().trim_end(), CONFIG.trim_end());
+}
+
+#[test]
+fn unknown_values_are_ignored_and_reported() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert!(row_style(&hosts[0]).dim);
+    assert_eq!(row_style(&hosts[1]).color, Some(Color::Red));
+    assert!(row_style(&hosts[2]).is_plain());
+    assert!(row_style(&hosts[3]).is_plain());
+
+    let findings: Vec<_> = lint_hosts(&hosts).into_iter().filter(|finding| finding.rule == "unknown-row-style").collect();
+    assert_eq!(findings.len(), 2);
+    assert!(findings.iter().all(|finding| finding.host == "odd"));
+    assert!(findings[0].message.contains("blink"));
+    assert!(findings[1].message.contains("rainbow"));
+}
+
+#[test]
+fn form_cycles_the_row_style_and_writes_color_metadata() {
+    let (_dir, mut app) = setup("Host web\n    HostName 10.0.0.1\n");
+    app.list_state.select(Some(0));
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::BackTab)).unwrap();
+    assert_eq!(app.editing_host.as_ref().unwrap().current_field, ROW_STYLE_FIELD);
+
+    // 空 → dim → red；输入其他字符不改变取值
+    app.handle_key(KeyEvent::from(KeyCode::Char(' '))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Right)).unwrap();
+    assert_eq!(app.editing_host.as_ref().unwrap().current.row_style, "red");
+    ctrl(&mut app, 's');
+
+    match &app.pending_changes[..] {
+        [ChangeType::Modified { new, .. }] => assert_eq!((new.style.as_deref(), new.color.as_deref()), (None, Some("red"))),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(app.generate_diff_lines().contains(&"+ # @color: red".to_string()));
+}
+
+#[test]
+fn colored_rows_turn_black_when_selected() {
+    let (_dir, mut app) = setup(CONFIG);
+    let row_of = |app: &App, name: &str| {
+        app.tree_items.iter().position(|item| matches!(item, ssh_tui::core::TreeItem::Host { host_index } if app.hosts[*host_index].name == name)).unwrap()
+    };
+    app.list_state.select(Some(row_of(&app, "plain")));
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = terminal.backend().buffer().clone();
+    let cell_of = |text: &str| {
+        (0..20).flat_map(|y| (0..100).map(move |x| (x, y))).find(|&(x, y)| {
+            (0..text.len() as u16).all(|i| buffer.get(x + i, y).symbol == text[i as usize..=i as usize])
+        }).map(|(x, y)| buffer.get(x, y).clone()).unwrap()
+    };
+    assert_eq!(cell_of("prod-db").fg, Color::Red);
+    assert!(cell_of("legacy").modifier.contains(Modifier::ITALIC));
+
+    app.list_state.select(Some(row_of(&app, "prod-db")));
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let selected = (0..20).flat_map(|y| (0..100).map(move |x| (x, y))).find(|&(x, y)| buffer.get(x, y).symbol == "p" && buffer.get(x + 1, y).symbol == "r" && buffer.get(x + 4, y).symbol == "-").unwrap();
+    assert_ne!(buffer.get(selected.0, selected.1).fg, Color::Red);
+}
@@ -0,0 +1,216 @@
+mod common;
+
+use common::{ ctrl, press, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, AppMode, TreeItem };
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: work
+Host web-01
+    HostName 10.0.0.1
+
+# @folder: work
+Host web-02
+    HostName 10.0.0.2
+
+# @folder: work
+Host web-03
+    HostName 10.0.0.3
+
+Host db
+    HostName 10.0.1.1
+
+Host zeta
+    HostName 10.0.1.2
+";
+
+fn setup(content: &str) -> (TempDir, PathBuf, App) {
+    let (dir, app) = common::setup(content);
+    let path = common::config_path(&dir);
+    (dir, path, app)
+}
+
+fn selected_name(app: &App) -> Option<String> {
+    app.get_selected_host().map(|h| h.name.clone())
+}
+
+#[test]
+fn save_keeps_selection_and_allows_continued_editing() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    select_host(&mut app, "web-02");
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('0'));
+    ctrl(&mut app, 's');
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(selected_name(&app).as_deref(), Some("web-02"));
+
+    // 继续编辑同一个主机
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.editing_host.as_ref().unwrap().current.name, "web-02");
+    assert_eq!(app.editing_host.as_ref().unwrap().current.hostname, "10.0.0.20");
+}
+
+#[test]
+fn renamed_host_stays_selected_after_save() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    select_host(&mut app, "db");
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Char('z'));
+    press(&mut app, KeyCode::Char('z'));
+    press(&mut app, KeyCode::Char('z'));
+    ctrl(&mut app, 's');
+
+    assert_eq!(selected_name(&app).as_deref(), Some("zzz"));
+}
+
+#[test]
+fn apply_changes_keeps_selection() {
+    let (_dir, path, mut app) = setup(CONFIG);
+    select_host(&mut app, "web-03");
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('9'));
+    ctrl(&mut app, 's');
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    press(&mut app, KeyCode::Char('y'));
+
+    assert!(app.pending_changes.is_empty());
+    assert!(fs::read_to_string(&path).unwrap().contains("HostName 10.0.0.39"));
+    assert_eq!(selected_name(&app).as_deref(), Some("web-03"));
+}
+
+#[test]
+fn reload_keeps_selection_when_rows_shift() {
+    let (_dir, path, mut app) = setup(CONFIG);
+    select_host(&mut app, "db");
+    let before = app.list_state.selected();
+
+    // 外部新增一个排在前面的主机，db 的行号会变化
+    fs::write(&path, format!("{}\nHost alpha\n    HostName 10.0.2.1\n", CONFIG)).unwrap();
+    app.reload_config().unwrap();
+
+    assert_eq!(selected_name(&app).as_deref(), Some("db"));
+    assert_ne!(app.list_state.selected(), before);
+}
+
+#[test]
+fn reload_falls_back_to_nearest_row_when_host_disappears() {
+    let (_dir, path, mut app) = setup(CONFIG);
+    select_host(&mut app, "zeta");
+    let before = app.list_state.selected().unwrap();
+
+    fs::write(&path, CONFIG.replace("Host zeta\n    HostName 10.0.1.2\n", "")).unwrap();
+    app.reload_config().unwrap();
+
+    assert_eq!(app.list_state.selected(), Some(before.min(app.tree_items.len() - 1)));
+}
+
+#[test]
+fn collapsed_folder_survives_rebuild() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    select_host(&mut app, "web-02");
+    app.toggle_folder_expanded(0);
+    app.list_state.select(Some(0));
+
+    app.discard_changes();
+
+    assert!(matches!(&app.tree_items[0], TreeItem::Folder { expanded: false, .. }));
+    assert_eq!(app.list_state.selected(), Some(0));
+}
+
+#[test]
+fn host_inside_collapsed_folder_selects_folder_row() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    select_host(&mut app, "web-02");
+    app.collapsed_folders.insert("work".to_string());
+
+    app.filter_hosts();
+
+    assert!(matches!(
+        app.tree_items.get(app.list_state.selected().unwrap()),
+        Some(TreeItem::Folder { name, .. }) if name == "work"
+    ));
+}
+
+fn type_query(app: &mut App, query: &str) {
+    press(app, KeyCode::Char('/'));
+    for c in query.chars() {
+        press(app, KeyCode::Char(c));
+    }
+}
+
+#[test]
+fn search_results_keep_folder_grouping() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    type_query(&mut app, "10.0.");
+
+    assert!(matches!(&app.tree_items[0], TreeItem::Folder { name, expanded: true, .. } if name == "work"));
+    let names: Vec<String> = app.tree_items
+        .iter()
+        .filter_map(|item| match item {
+            TreeItem::Host { host_index } => Some(app.hosts[*host_index].name.clone()),
+            TreeItem::Folder { .. } => None,
+        })
+        .collect();
+    assert_eq!(names, ["web-01", "web-02", "web-03", "db", "zeta"]);
+}
+
+#[test]
+fn search_prunes_folders_without_matches() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    app.collapsed_folders.insert("work".to_string());
+    type_query(&mut app, "web-02");
+
+    // 用户收起的文件夹在搜索结果中自动展开，且只保留匹配的主机
+    assert_eq!(app.tree_items.len(), 2);
+    assert!(matches!(&app.tree_items[0], TreeItem::Folder { expanded: true, children_indices, .. } if children_indices.len() == 1));
+}
+
+#[test]
+fn root_only_matches_have_no_folder_rows() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    type_query(&mut app, "db");
+
+    assert_eq!(app.tree_items.len(), 1);
+    assert_eq!(selected_name(&app), Some("db".to_string()));
+}
+
+#[test]
+fn enter_in_grouped_results_connects_selected_host() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    type_query(&mut app, "web-02");
+    select_host(&mut app, "web-02");
+
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.pending_connect.map(|i| app.hosts[i].name.clone()), Some("web-02".to_string()));
+}
+
+#[test]
+fn folder_header_toggles_within_results() {
+    let (_dir, _path, mut app) = setup(CONFIG);
+    type_query(&mut app, "web");
+    press(&mut app, KeyCode::Esc);
+    app.list_state.select(Some(0));
+
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.tree_items.len(), 1);
+    // 搜索内的收起不影响普通树
+    assert!(app.collapsed_folders.is_empty());
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{ ctrl, press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ App, AppMode, ChangeType, DeriveTemplate, Selector, SelectorEvent, CONFIRM_NO, CONFIRM_YES };
+use tempfile::TempDir;
+
+fn config_app() -> (TempDir, App) {
+    let (dir, mut app) = setup("Host web\n    HostName 10.0.0.1\n");
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('e'));
+    (dir, app)
+}
+
+#[test]
+fn arrows_and_tab_move_with_wrap_around() {
+    let mut selector = Selector::new(3);
+    assert_eq!(selector.handle_key(KeyCode::Up), SelectorEvent::Moved);
+    assert_eq!(selector.selected, 2);
+    selector.handle_key(KeyCode::Tab);
+    assert_eq!(selector.selected, 0);
+    selector.handle_key(KeyCode::Down);
+    selector.handle_key(KeyCode::BackTab);
+    assert_eq!(selector.selected, 0);
+
+    selector.handle_key(KeyCode::Down);
+    assert_eq!(selector.handle_key(KeyCode::Enter), SelectorEvent::Accepted(1));
+    assert_eq!(selector.handle_key(KeyCode::Esc), SelectorEvent::Cancelled);
+    assert_eq!(selector.handle_key(KeyCode::Char('x')), SelectorEvent::Unhandled);
+
+    // 空列表只能取消
+    let mut empty = Selector::new(0);
+    assert_eq!(empty.handle_key(KeyCode::Down), SelectorEvent::Unhandled);
+    assert_eq!(empty.handle_key(KeyCode::Enter), SelectorEvent::Unhandled);
+    assert_eq!(empty.handle_key(KeyCode::Esc), SelectorEvent::Cancelled);
+}
+
+#[test]
+fn confirm_defaults_to_no_and_accepts_y_n_accelerators() {
+    let mut confirm = Selector::confirm();
+    assert_eq!(confirm.handle_key(KeyCode::Enter), SelectorEvent::Accepted(CONFIRM_NO));
+    assert_eq!(confirm.handle_key(KeyCode::Char('Y')), SelectorEvent::Accepted(CONFIRM_YES));
+    assert_eq!(confirm.selected, CONFIRM_YES);
+    assert_eq!(confirm.handle_key(KeyCode::Char('n')), SelectorEvent::Accepted(CONFIRM_NO));
+
+    // 加速键超出列表长度时不生效
+    let mut picker = Selector::new(2).with_accelerators(&[('3', 2)]);
+    assert_eq!(picker.handle_key(KeyCode::Char('3')), SelectorEvent::Unhandled);
+    picker.selected = 1;
+    picker.set_len(1);
+    assert_eq!(picker.selected, 0);
+}
+
+#[test]
+fn confirmation_dialogs_share_the_selector_keys() {
+    let (_dir, mut app) = config_app();
+    press(&mut app, KeyCode::Char('d'));
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+    // Enter 默认是 "否"
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.pending_changes.is_empty());
+
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Tab);
+    press(&mut app, KeyCode::Enter);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Deleted(host)] if host.name == "web"));
+
+    // 放弃编辑确认：ESC 回到表单，y 放弃
+    press(&mut app, KeyCode::Char('a'));
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfirmDiscardEdit);
+    assert_eq!(app.confirm.selected, CONFIRM_NO);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::EditingHost);
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.editing_host.is_none());
+}
+
+#[test]
+fn pickers_move_with_tab_and_cancel_with_esc() {
+    let (_dir, mut app) = config_app();
+    press(&mut app, KeyCode::Char('T'));
+    assert_eq!(app.mode, AppMode::DeriveHost);
+    press(&mut app, KeyCode::BackTab);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.derive.as_ref().unwrap().template, Some(DeriveTemplate::JumpOnly));
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.derive.is_none());
+
+    press(&mut app, KeyCode::Char('i'));
+    app.handle_paste("Host a\n    HostName 10.0.0.2\n\nHost b\n    HostName 10.0.0.3\n");
+    ctrl(&mut app, 'd');
+    press(&mut app, KeyCode::Tab);
+    press(&mut app, KeyCode::Char('s'));
+    press(&mut app, KeyCode::Enter);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Added(host)] if host.name == "a"));
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ratatui::{ backend::TestBackend, buffer::Buffer, Terminal };
+use ssh_tui::core::{ find_program, App, AppMode, SshBinary };
+use ssh_tui::ui::render;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+Host web
+    HostName 10.0.0.1
+
+# @connector: telnet
+Host switch-01
+    HostName 10.0.8.2
+";
+
+fn setup() -> (TempDir, App) {
+    let (dir, mut app) = common::setup(CONFIG);
+    app.ssh_binary = SshBinary::Missing;
+    (dir, app)
+}
+
+fn screen(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(140, 20)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    let buffer: &Buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(unix)]
+fn make_program(dir: &Path, name: &str, executable: bool) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(name);
+    fs::write(&path, "#!/bin/sh\n").unwrap();
+    let mode = if executable { 0o755 } else { 0o644 };
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn ssh_is_looked_up_in_path_order() {
+    let dir = TempDir::new().unwrap();
+    let (first, second, third) = (dir.path().join("a"), dir.path().join("b"), dir.path().join("c"));
+    for dir in [&first, &second, &third] {
+        fs::create_dir(dir).unwrap();
+    }
+    // 没有执行权限的同名文件和同名目录被跳过
+    make_program(&first, "ssh", false);
+    fs::create_dir(second.join("ssh")).unwrap();
+    make_program(&third, "ssh", true);
+
+    let path: OsString = std::env::join_paths([&first, &second, &third]).unwrap();
+    assert_eq!(SshBinary::locate_in(Some(&path)), SshBinary::Found(third.join("ssh")));
+    assert_eq!(find_program("ssh", Some(&std::env::join_paths([&first, &second]).unwrap())), None);
+    assert_eq!(SshBinary::locate_in(None), SshBinary::Missing);
+    assert!(!SshBinary::Unchecked.is_missing());
+}
+
+#[test]
+fn connections_are_refused_but_editing_still_works() {
+    let (_dir, mut app) = setup();
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.pending_connect, None);
+    assert!(app.status.as_ref().unwrap().text.starts_with("ssh binary not found"));
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char('>'));
+    assert!(app.status.as_ref().unwrap().text.starts_with("ssh binary not found"));
+    assert_eq!(app.connect_queue, ["web"]);
+
+    // 自定义连接器不需要 ssh
+    select_host(&mut app, "switch-01");
+    press(&mut app, KeyCode::Enter);
+    assert!(app.pending_connect.is_some());
+
+    app.pending_connect = None;
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.pending_changes.len(), 1);
+}
+
+#[test]
+fn missing_ssh_shows_a_banner_even_in_zen_mode() {
+    let (_dir, mut app) = setup();
+    let banner = "ssh binary not found — connections disabled, config editing still works";
+    assert!(screen(&mut app).lines().take(3).any(|line| line.contains(banner)));
+    app.zen = true;
+    assert!(screen(&mut app).lines().take(3).any(|line| line.contains(banner)));
+
+    app.ssh_binary = SshBinary::Found("/usr/bin/ssh".into());
+    assert!(!screen(&mut app).contains("ssh binary not found"));
+}
+
+#[test]
+fn f5_checks_for_ssh_again() {
+    let (_dir, mut app) = setup();
+    press(&mut app, KeyCode::F(5));
+    assert_eq!(app.ssh_binary, SshBinary::locate());
+
+    // 没有查找过时（例如测试中）F5 不改变结果
+    app.ssh_binary = SshBinary::Unchecked;
+    press(&mut app, KeyCode::F(5));
+    assert_eq!(app.ssh_binary, SshBinary::Unchecked);
+}
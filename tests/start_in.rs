@@ -0,0 +1,113 @@
+mod common;
+
+use common::{ ctrl, setup_with };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::cli::{ parse_args, StartIn };
+use ssh_tui::config::{ ConfigGuard, Settings };
+use ssh_tui::core::{ AppMode, ChangeType, GUARD_DELAY };
+use std::fs;
+use std::time::Instant;
+
+const CONFIG: &str = "Host web\n    HostName 10.0.0.1\n";
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn start(list: &[&str]) -> Option<StartIn> {
+    parse_args(args(list)).unwrap().start
+}
+
+#[test]
+fn edit_and_add_flags_are_parsed() {
+    assert_eq!(start(&[]), None);
+    assert_eq!(start(&["--edit"]), Some(StartIn::Config));
+    assert_eq!(start(&["--add"]), Some(StartIn::AddHost(None)));
+    assert_eq!(start(&["--add", "db-02"]), Some(StartIn::AddHost(Some("db-02".to_string()))));
+    assert_eq!(start(&["--add=root@10.0.0.5 -p 2222"]), Some(StartIn::AddHost(Some("root@10.0.0.5 -p 2222".to_string()))));
+    // 名称可省略，后面的选项和子命令不会被当作名称
+    let options = parse_args(args(&["--add", "--plain"])).unwrap();
+    assert_eq!((options.start, options.plain), (Some(StartIn::AddHost(None)), true));
+    let options = parse_args(args(&["--add", "import-csv", "hosts.csv"])).unwrap();
+    assert_eq!(options.start, Some(StartIn::AddHost(None)));
+    assert!(options.command.is_some());
+
+    let error = parse_args(args(&["--edit", "--add"])).unwrap_err().to_string();
+    assert!(error.contains("Use either --edit or --add, not both"), "{}", error);
+}
+
+#[test]
+fn add_prefills_the_form_from_a_name_or_an_ssh_command() {
+    let (_dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.start_adding_host_from(Some("  db-02 "));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    let form = &app.editing_host.as_ref().unwrap().current;
+    assert_eq!((form.name.as_str(), form.hostname.as_str()), ("db-02", ""));
+
+    let (_dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.start_adding_host_from(Some("root@10.0.0.5 -p 2222"));
+    let form = &app.editing_host.as_ref().unwrap().current;
+    assert_eq!((form.hostname.as_str(), form.user.as_str(), form.port.as_str()), ("10.0.0.5", "root", "2222"));
+
+    // 不带名称时是空表单；Esc 回到配置管理
+    let (_dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.start_adding_host_from(None);
+    assert_eq!(app.editing_host.as_ref().unwrap().current.name, "");
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+}
+
+#[test]
+fn cancelling_the_add_form_drops_the_prefilled_options() {
+    let (_dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.start_adding_host_from(Some("ssh -J bastion deploy@10.0.0.9"));
+    assert!(app.new_host_template.is_some());
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('y'))).unwrap();
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.new_host_template.is_none());
+
+    // 之后新建的主机不会带上上一次的跳板机
+    app.handle_key(KeyEvent::from(KeyCode::Char('a'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+    ctrl(&mut app, 's');
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Added(host)] if host.other_options.get("proxyjump").is_none()));
+}
+
+#[test]
+fn added_host_goes_through_pending_changes_and_review() {
+    let (dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.start_adding_host_from(Some("deploy@10.0.0.9"));
+    ctrl(&mut app, 's');
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Added(host)] if host.user.as_deref() == Some("deploy")));
+    // 保存前配置文件不变
+    assert_eq!(fs::read_to_string(dir.path().join("config")).unwrap(), CONFIG);
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('q'))).unwrap();
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+}
+
+#[test]
+fn start_flags_respect_the_guard_and_read_only_mode() {
+    let settings = Settings { config_guard: ConfigGuard::Confirm, ..Settings::default() };
+    let (_dir, mut app) = setup_with(CONFIG, settings);
+    app.start_adding_host_from(Some("db-02"));
+    assert_eq!(app.mode, AppMode::ConfirmGuard);
+    app.guard.as_mut().unwrap().opened = Instant::now() - GUARD_DELAY;
+    app.handle_key(KeyEvent::from(KeyCode::Char('y'))).unwrap();
+    assert_eq!(app.mode, AppMode::EditingHost);
+    assert_eq!(app.editing_host.as_ref().unwrap().current.name, "db-02");
+
+    let (_dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.start_config_management();
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+
+    // 超出解析限制等原因导致只读时不进入编辑
+    let (_dir, mut app) = setup_with(CONFIG, Settings::default());
+    app.read_only = true;
+    app.start_adding_host_from(Some("db-02"));
+    assert_eq!(app.mode, AppMode::Normal);
+    app.start_config_management();
+    assert_eq!(app.mode, AppMode::Normal);
+}
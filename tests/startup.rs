@@ -0,0 +1,90 @@
+use crossterm::event::{ KeyCode, KeyEvent };
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::cli::parse_args;
+use ssh_tui::config::{ set_home_override, History, Settings, TransferPaths };
+use ssh_tui::core::{ App, AppMode, DeferredLoad, StartupProfile };
+use ssh_tui::ui::render;
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+Host alpha
+    HostName 10.0.0.1
+
+Host web
+    HostName 10.0.0.2
+";
+
+#[test]
+fn first_draw_only_needs_the_config_parse() {
+    let home = TempDir::new().unwrap();
+    set_home_override(home.path().to_path_buf());
+    let state = home.path().join(".local/state/sshc");
+    fs::create_dir_all(&state).unwrap();
+    fs::write(state.join("history.toml"), "[hosts.web]\nconnections = 3\n").unwrap();
+    fs::write(state.join("transfer_paths.toml"), "local = [\"/tmp/dist\"]\n").unwrap();
+    let path = home.path().join("config");
+    fs::write(&path, CONFIG).unwrap();
+
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|f| render(f, &mut app)).unwrap();
+    // 首帧时状态文件还没读取
+    assert_eq!(app.deferred, DeferredLoad::ALL);
+    assert!(app.history.hosts.is_empty());
+    assert!(app.transfer_paths.local.is_empty());
+
+    app.handle_key(KeyEvent::from(KeyCode::Down)).unwrap();
+    app.tick();
+    assert_eq!(app.history.hosts["web"].connections, 3);
+    assert_eq!(app.deferred, [DeferredLoad::TransferPaths]);
+    // 加载后选中的主机不变
+    assert_eq!(app.get_selected_host().unwrap().name, "web");
+
+    // 打开传输弹窗前立即加载剩下的部分
+    app.handle_key(KeyEvent::from(KeyCode::Char('t'))).unwrap();
+    assert_eq!(app.mode, AppMode::Transfer);
+    assert!(app.deferred.is_empty());
+    assert_eq!(app.transfer_paths.local, ["/tmp/dist"]);
+    let phases: Vec<&str> = app.startup.phases.iter().map(|(phase, _, _)| phase.as_str()).collect();
+    assert_eq!(phases, ["history", "transfer paths"]);
+}
+
+#[test]
+fn state_recorded_before_loading_is_merged_on_top() {
+    let mut paths = TransferPaths::parse("local = [\"/tmp/a\", \"/tmp/b\"]\n[remote]\nweb = [\"/srv\"]\n");
+    let mut newer = TransferPaths::default();
+    newer.remember_local("/tmp/b");
+    newer.remember_remote("web", "/opt");
+    paths.merge(&newer);
+    assert_eq!(paths.local, ["/tmp/b", "/tmp/a"]);
+    assert_eq!(paths.remote_paths("web"), ["/opt", "/srv"]);
+
+    let mut history: History = toml::from_str("[hosts.web]\nconnections = 3\ntotal_seconds = 60\nlast_exit_code = 1\nlast_connected = 100\n").unwrap();
+    let mut session = History::default();
+    session.record_session("web", Duration::from_secs(5), Some(0));
+    history.merge(&session);
+    let web = &history.hosts["web"];
+    assert_eq!((web.connections, web.total_seconds, web.last_exit_code), (4, 65, Some(0)));
+}
+
+#[test]
+fn profile_report_lists_phases_in_order() {
+    let mut profile = StartupProfile::default();
+    profile.record("config parse", Duration::from_micros(12_340));
+    assert_eq!(profile.time("settings", || 7), 7);
+
+    let report = profile.report();
+    assert_eq!(report[0], "startup profile:");
+    assert!(report[1].starts_with("  config parse         12.3 ms  (done at "));
+    assert!(report[2].starts_with("  settings "));
+    assert_eq!(report.len(), 3);
+}
+
+#[test]
+fn profile_startup_is_a_hidden_flag() {
+    let options = parse_args(["--profile-startup".to_string()]).unwrap();
+    assert!(options.profile_startup);
+    assert!(!ssh_tui::cli::USAGE.contains("--profile-startup"));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{ press, select_host };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ merge_system_hosts, parse_system_configs, ParseLimits, Settings, SshHost };
+use ssh_tui::core::{ App, AppMode, ChangeType };
+use std::fs;
+use tempfile::TempDir;
+
+/// 主配置有 web，系统配置目录中有 web 和 bastion
+fn app_with_system_config(dir: &TempDir) -> App {
+    let system = dir.path().join("ssh_config.d");
+    fs::create_dir(&system).unwrap();
+    fs::write(system.join("10-shared.conf"), "Host bastion\n    HostName 10.9.0.1\n\nHost web\n    HostName 10.9.0.2\n").unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, "Host web\n    HostName 10.0.0.1\n").unwrap();
+    let settings = Settings { system_config_paths: vec![format!("{}/*.conf", system.display())], ..Settings::default() };
+    App::with_config_path(path, settings).unwrap()
+}
+
+#[test]
+fn system_files_are_expanded_and_the_first_path_wins() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.conf"), "Host shared\n    HostName 10.0.0.1\n\nInclude other\n").unwrap();
+    fs::write(dir.path().join("b.conf"), "Host shared\n    HostName 10.0.0.2\n\nHost only-b\n    User ops\n").unwrap();
+    let patterns = [format!("{}/a.conf", dir.path().display()), format!("{}/*.conf", dir.path().display())];
+
+    let (hosts, files) = parse_system_configs(&patterns, &ParseLimits::default());
+    assert_eq!(files, [dir.path().join("a.conf"), dir.path().join("b.conf")]);
+    let names: Vec<_> = hosts.iter().map(|host| host.name.as_str()).collect();
+    assert_eq!(names, ["shared", "only-b"]);
+    assert_eq!(hosts[0].hostname.as_deref(), Some("10.0.0.1"));
+    assert!(hosts.iter().all(|host| host.system));
+    assert_eq!(hosts[1].source.as_deref(), Some(dir.path().join("b.conf").as_path()));
+
+    // 不存在的路径被忽略
+    assert!(parse_system_configs(&["/nonexistent/ssh_config".to_string()], &ParseLimits::default()).0.is_empty());
+}
+
+#[test]
+fn user_hosts_shadow_system_hosts_of_the_same_name() {
+    let system: Vec<SshHost> = ["web", "bastion"]
+        .iter()
+        .map(|name| SshHost { system: true, ..SshHost::new(name.to_string()) })
+        .collect();
+    let mut hosts = vec![SshHost::new("web".to_string())];
+    merge_system_hosts(&mut hosts, &system);
+    let merged: Vec<_> = hosts.iter().map(|host| (host.name.as_str(), host.system)).collect();
+    assert_eq!(merged, [("web", false), ("bastion", true)]);
+
+    // 再次合并先去掉旧的系统主机，不会重复
+    hosts.retain(|host| host.name != "web");
+    merge_system_hosts(&mut hosts, &system);
+    assert_eq!(hosts.iter().filter(|host| host.system).count(), 2);
+}
+
+#[test]
+fn editing_a_system_host_offers_to_copy_it() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_with_system_config(&dir);
+    let web = app.hosts.iter().find(|host| host.name == "web").unwrap();
+    assert!(!web.system);
+    assert!(app.hosts.iter().any(|host| host.name == "bastion" && host.system));
+
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "bastion");
+    press(&mut app, KeyCode::Char('x'));
+    assert_eq!(app.mode, AppMode::ConfirmCopySystem);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(app.pending_changes.is_empty());
+
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::ConfirmCopySystem);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    let [ChangeType::Added(host)] = &app.pending_changes[..] else {
+        panic!("expected the copy to be staged");
+    };
+    assert_eq!((host.name.as_str(), host.system, host.source.as_deref()), ("bastion", false, None));
+    assert_eq!(app.hosts.iter().filter(|host| host.name == "bastion").count(), 1);
+    assert!(app.status.as_ref().unwrap().text.starts_with("Copied bastion"));
+}
+
+#[test]
+fn system_hosts_are_never_written_and_return_after_a_discard() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_with_system_config(&dir);
+    press(&mut app, KeyCode::Char('e'));
+    select_host(&mut app, "bastion");
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    assert!(app.hosts.iter().any(|host| host.name == "bastion" && !host.system));
+
+    app.discard_changes();
+    assert!(app.pending_changes.is_empty());
+    assert!(app.hosts.iter().any(|host| host.name == "bastion" && host.system));
+
+    // 保存其他变更时系统主机不写入用户配置
+    select_host(&mut app, "web");
+    press(&mut app, KeyCode::Char('x'));
+    app.apply_changes().unwrap();
+    let written = fs::read_to_string(dir.path().join("config")).unwrap();
+    assert!(written.contains("Host web") && !written.contains("bastion"), "{}", written);
+    assert!(app.hosts.iter().any(|host| host.name == "bastion" && host.system));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::select_host;
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ Settings, SshHost };
+use ssh_tui::core::{ App, AppMode, ChangeType, TagCheck, TagEditor };
+use std::fs;
+use tempfile::TempDir;
+
+fn host(name: &str, tags: &[&str]) -> SshHost {
+    let mut host = SshHost::new(name.to_string());
+    host.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    host
+}
+
+fn states(editor: &TagEditor) -> Vec<(&str, TagCheck)> {
+    editor.rows.iter().map(|row| (row.tag.as_str(), row.state)).collect()
+}
+
+#[test]
+fn common_tags_are_checked_and_others_partial() {
+    let (a, b) = (host("a", &["prod", "web"]), host("b", &["prod", "db"]));
+    let editor = TagEditor::new(&[&a, &b]);
+    assert_eq!(states(&editor), [("prod", TagCheck::Checked), ("web", TagCheck::Partial), ("db", TagCheck::Partial)]);
+}
+
+#[test]
+fn toggling_cycles_through_the_allowed_states() {
+    let (a, b) = (host("a", &["prod", "web"]), host("b", &["prod"]));
+    let mut editor = TagEditor::new(&[&a, &b]);
+
+    editor.toggle(0);
+    assert_eq!(editor.rows[0].state, TagCheck::Unchecked);
+    editor.toggle(0);
+    assert_eq!(editor.rows[0].state, TagCheck::Checked);
+
+    for expected in [TagCheck::Checked, TagCheck::Unchecked, TagCheck::Partial] {
+        editor.toggle(1);
+        assert_eq!(editor.rows[1].state, expected);
+    }
+}
+
+#[test]
+fn applying_adds_and_removes_per_host() {
+    let (a, b) = (host("a", &["prod", "web"]), host("b", &["db", "prod"]));
+    let mut editor = TagEditor::new(&[&a, &b]);
+    editor.toggle(0); // prod -> 全部移除
+    editor.toggle(1); // web -> 全部添加
+    editor.input = "eu, db".to_string();
+    editor.add_input();
+
+    assert_eq!(editor.apply(&a.tags), ["web", "db", "eu"]);
+    assert_eq!(editor.apply(&b.tags), ["db", "web", "eu"]);
+}
+
+#[test]
+fn only_hosts_whose_tags_change_are_staged() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    fs::write(
+        &path,
+        "# @tags: prod\nHost a\n    HostName 10.0.0.1\n\n# @tags: prod, web\nHost b\n    HostName 10.0.0.2\n\nHost c\n    HostName 10.0.0.3\n"
+    ).unwrap();
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    for name in ["a", "b"] {
+        select_host(&mut app, name);
+        app.handle_key(KeyEvent::from(KeyCode::Char(' '))).unwrap();
+    }
+    assert_eq!(app.marked_hosts, ["a", "b"]);
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('T'))).unwrap();
+    assert_eq!(app.mode, AppMode::EditTags);
+    // web 只在 b 上：勾选后补给 a，b 不变
+    for code in [KeyCode::Down, KeyCode::Char(' '), KeyCode::Enter] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Modified { new, .. }] if new.name == "a"));
+    let diff = app.generate_diff_lines();
+    assert!(diff.contains(&"- # @tags: prod".to_string()));
+    assert!(diff.contains(&"+ # @tags: prod, web".to_string()));
+}
+
+#[test]
+fn the_tag_editor_has_its_own_key() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config");
+    fs::write(&path, "Host a\n    HostName 10.0.0.1\n").unwrap();
+    let mut app = App::with_config_path(path, Settings::default()).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+    app.list_state.select(Some(0));
+
+    // 没有标记时 t 只提示
+    app.handle_key(KeyEvent::from(KeyCode::Char('t'))).unwrap();
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(app.status.as_ref().map(|status| status.text.as_str()), Some("Mark hosts with Space to edit their tags"));
+
+    app.handle_key(KeyEvent::from(KeyCode::Char(' '))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Char('t'))).unwrap();
+    assert_eq!(app.mode, AppMode::EditTags);
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use crossterm::event::{ KeyCode, KeyEvent };
+use ratatui::{ backend::TestBackend, Terminal };
+use ssh_tui::core::{ App, AppMode, DnsStatus, TaskHandle, TaskKind, TaskOutput, TaskRegistry, SPINNER_FRAMES };
+use ssh_tui::ui::render;
+use std::sync::mpsc;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, App) {
+    let (dir, mut app) = common::setup("Host localhost\n    Port 2222\n\nHost web\n    HostName 10.0.0.1\n");
+    app.list_state.select(Some(1));
+    (dir, app)
+}
+
+fn screen(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(140, 24)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..24).map(|y| (0..140).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn completed_tasks_are_collected_once() {
+    let mut tasks = TaskRegistry::default();
+    let (sender, receiver) = mpsc::channel();
+    let id = tasks.track("web", TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver));
+    assert!(tasks.is_pending("web") && tasks.pending_kind("web", TaskKind::EffectiveConfig));
+    assert!(tasks.poll().is_empty());
+
+    sender.send(Ok("user deploy".to_string())).unwrap();
+    let finished = tasks.poll();
+    assert_eq!(finished.len(), 1);
+    assert_eq!((finished[0].0.id, finished[0].0.host.as_str()), (id, "web"));
+    assert_eq!(finished[0].1, TaskOutput::EffectiveConfig(Ok("user deploy".to_string())));
+    assert!(!tasks.is_pending("web"));
+    assert!(tasks.poll().is_empty());
+
+    // 工作线程没有发送结果就退出
+    let (sender, receiver) = mpsc::channel::<Result<String, String>>();
+    tasks.track("db", TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver));
+    drop(sender);
+    assert_eq!(tasks.poll()[0].1, TaskOutput::EffectiveConfig(Err("ssh -G did not return a result".to_string())));
+}
+
+#[test]
+fn cancellation_wins_over_a_result_that_is_not_collected_yet() {
+    let mut tasks = TaskRegistry::default();
+    let (old_sender, receiver) = mpsc::channel();
+    let old = tasks.track("web", TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver));
+    old_sender.send(Ok("stale".to_string())).unwrap();
+
+    let cancelled = tasks.cancel_host("web");
+    assert_eq!(cancelled.iter().map(|task| task.id).collect::<Vec<_>>(), [old]);
+    drop(cancelled);
+    assert!(tasks.poll().is_empty());
+
+    // 重新发起后 id 不同，旧任务迟到的结果送不到任何地方
+    let (sender, receiver) = mpsc::channel();
+    let new = tasks.track("web", TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver));
+    assert_ne!(new, old);
+    assert!(old_sender.send(Ok("late".to_string())).is_err());
+    sender.send(Ok("fresh".to_string())).unwrap();
+    let finished = tasks.poll();
+    assert_eq!(finished[0].0.id, new);
+    assert_eq!(finished[0].1, TaskOutput::EffectiveConfig(Ok("fresh".to_string())));
+
+    // 完成之后再取消什么也不做
+    assert!(tasks.cancel_host("web").is_empty());
+}
+
+#[test]
+fn pending_hosts_show_a_spinner_and_esc_cancels() {
+    let (_dir, mut app) = setup();
+    let (sender, receiver) = mpsc::channel();
+    app.tasks.track("web", TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver));
+    app.tick_tasks();
+
+    let spinner = app.tasks.spinner();
+    assert!(SPINNER_FRAMES.contains(&spinner));
+    let shown = screen(&mut app);
+    assert!(shown.contains(&format!("web (10.0.0.1) {}", spinner)), "{}", shown);
+    assert!(shown.contains("ssh -G 0s (Esc cancels)"));
+    app.tick_tasks();
+    assert_ne!(app.tasks.spinner(), spinner);
+
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    assert!(!app.tasks.is_pending("web"));
+    assert_eq!(app.status.as_ref().unwrap().text, "Cancelled ssh -G for web");
+    assert!(sender.send(Ok(String::new())).is_err());
+    assert!(!screen(&mut app).contains("Esc cancels"));
+}
+
+#[test]
+fn leaving_a_host_cancels_its_tasks() {
+    let (_dir, mut app) = setup();
+    let (sender, receiver) = mpsc::channel();
+    app.tick_tasks();
+    app.tasks.track("web", TaskKind::EffectiveConfig, TaskHandle::EffectiveConfig(receiver));
+
+    app.handle_key(KeyEvent::from(KeyCode::Up)).unwrap();
+    app.tick_tasks();
+    assert!(!app.tasks.is_pending("web"));
+    assert!(sender.send(Ok("user deploy".to_string())).is_err());
+    assert_eq!(app.mode, AppMode::Normal);
+}
+
+#[test]
+fn cancelled_dns_lookups_are_retried_after_leaving() {
+    let (_dir, mut app) = setup();
+    app.list_state.select(Some(0));
+    app.tick_tasks();
+    assert!(app.tasks.pending_kind("localhost", TaskKind::Dns));
+
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    assert_eq!(app.dns_cache["localhost"], DnsStatus::Cancelled);
+    app.tick_tasks();
+    assert!(!app.tasks.is_pending("localhost"));
+    assert!(screen(&mut app).contains("lookup cancelled"));
+
+    app.handle_key(KeyEvent::from(KeyCode::Down)).unwrap();
+    app.tick_tasks();
+    assert!(!app.dns_cache.contains_key("localhost"));
+    app.handle_key(KeyEvent::from(KeyCode::Up)).unwrap();
+    app.tick_tasks();
+    assert_eq!(app.dns_cache["localhost"], DnsStatus::Resolving);
+    assert!(app.tasks.pending_kind("localhost", TaskKind::Dns));
+}
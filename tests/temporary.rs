@@ -0,0 +1,88 @@
+mod common;
+
+use common::{ ctrl, press, setup, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ export_csv, serialize_hosts };
+use ssh_tui::core::{ App, AppMode, ChangeType, TreeItem };
+use std::fs;
+
+const CONFIG: &str = "Host web\n    HostName 10.0.0.1\n";
+
+/// 用 Ctrl-N 新建名为 rescue 的临时主机
+fn add_rescue(app: &mut App) {
+    ctrl(app, 'n');
+    assert_eq!(app.mode, AppMode::EditingHost);
+    type_text(app, "rescue");
+    press(app, KeyCode::Down);
+    type_text(app, "203.0.113.7");
+    ctrl(app, 's');
+}
+
+fn rescue_index(app: &App) -> usize {
+    app.hosts.iter().position(|host| host.name == "rescue").unwrap()
+}
+
+#[test]
+fn temporary_host_is_listed_but_never_staged_or_written() {
+    let (dir, mut app) = setup(CONFIG);
+    add_rescue(&mut app);
+
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.pending_changes.is_empty());
+    let index = rescue_index(&app);
+    assert!(app.hosts[index].temporary);
+    assert!(app.tree_items.iter().any(|item| matches!(item, TreeItem::Host { host_index } if *host_index == index)));
+
+    assert!(!serialize_hosts(&app.hosts).unwrap().contains("rescue"));
+    assert!(!export_csv(&app.hosts).unwrap().contains("rescue"));
+
+    // 临时主机不影响保存，重新加载后仍在
+    app.apply_changes().unwrap();
+    assert!(!fs::read_to_string(dir.path().join("config")).unwrap().contains("rescue"));
+    app.reload_config().unwrap();
+    app.discard_changes();
+    assert_eq!(app.hosts.iter().filter(|host| host.name == "rescue").count(), 1);
+}
+
+#[test]
+fn temporary_host_passes_its_settings_to_ssh() {
+    let (_dir, mut app) = setup(CONFIG);
+    add_rescue(&mut app);
+    let host = &app.hosts[rescue_index(&app)];
+    assert_eq!(host.ssh_args(), ["-o", "HostName=203.0.113.7", "rescue"]);
+    assert_eq!(app.hosts[0].ssh_args(), ["web"]);
+}
+
+#[test]
+fn editing_and_deleting_a_temporary_host_stage_nothing() {
+    let (_dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char('e'));
+    add_rescue(&mut app);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+
+    press(&mut app, KeyCode::Char('e'));
+    type_text(&mut app, "-2");
+    ctrl(&mut app, 's');
+    assert!(app.hosts.iter().any(|host| host.name == "rescue-2" && host.temporary));
+
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    assert!(app.hosts.iter().all(|host| !host.temporary));
+    assert!(app.pending_changes.is_empty());
+}
+
+#[test]
+fn promoting_a_temporary_host_stages_it_for_the_config() {
+    let (dir, mut app) = setup(CONFIG);
+    press(&mut app, KeyCode::Char('e'));
+    add_rescue(&mut app);
+
+    press(&mut app, KeyCode::Char('P'));
+    assert!(!app.hosts[rescue_index(&app)].temporary);
+    assert!(matches!(&app.pending_changes[..], [ChangeType::Added(host)] if host.name == "rescue"));
+
+    press(&mut app, KeyCode::Char('q'));
+    press(&mut app, KeyCode::Char('y'));
+    let written = fs::read_to_string(dir.path().join("config")).unwrap();
+    assert!(written.contains("Host rescue\n    HostName 203.0.113.7"));
+}
@@ -0,0 +1,74 @@
+use ssh_tui::config::{ expand_hostname, expand_tokens, expand_tokens_to_string, SshHost, TokenContext, TokenSegment };
+use ssh_tui::core::{ connecting_message, probe_target };
+
+fn context() -> TokenContext {
+    TokenContext {
+        hostname: "10.0.0.5".to_string(),
+        port: "2222".to_string(),
+        remote_user: "deploy".to_string(),
+        alias: "web".to_string(),
+    }
+}
+
+#[test]
+fn expands_known_tokens() {
+    assert_eq!(
+        expand_tokens_to_string("nc -X connect -x proxy:3128 %h %p", &context()),
+        "nc -X connect -x proxy:3128 10.0.0.5 2222"
+    );
+    assert_eq!(expand_tokens_to_string("%r@%n", &context()), "deploy@web");
+}
+
+#[test]
+fn double_percent_is_literal() {
+    assert_eq!(expand_tokens_to_string("100%% %h", &context()), "100% 10.0.0.5");
+}
+
+#[test]
+fn unknown_tokens_are_kept_and_flagged() {
+    let segments = expand_tokens("ssh -W %h:%p %C", &context());
+    assert_eq!(
+        segments,
+        vec![
+            TokenSegment::Text("ssh -W 10.0.0.5:2222 ".to_string()),
+            TokenSegment::Unknown("%C".to_string())
+        ]
+    );
+}
+
+#[test]
+fn trailing_percent_is_unknown() {
+    let segments = expand_tokens("echo %", &context());
+    assert_eq!(segments.last(), Some(&TokenSegment::Unknown("%".to_string())));
+}
+
+#[test]
+fn context_defaults_follow_ssh() {
+    let host = SshHost::new("bastion".to_string());
+    let context = TokenContext::for_host(&host);
+    assert_eq!(context.hostname, "bastion");
+    assert_eq!(context.port, "22");
+    assert_eq!(context.alias, "bastion");
+}
+
+#[test]
+fn hostname_expands_only_the_alias_token() {
+    assert_eq!(expand_hostname("%h.internal.acme.com", "web"), "web.internal.acme.com");
+    assert_eq!(expand_hostname("%%h-%p", "web"), "%h-%p");
+    assert_eq!(expand_hostname("10.0.0.1", "web"), "10.0.0.1");
+}
+
+#[test]
+fn alias_based_hostname_is_used_for_concrete_addresses() {
+    let mut host = SshHost::new("web".to_string());
+    host.hostname = Some("%h.internal.acme.com".to_string());
+
+    assert_eq!(host.resolved_hostname().as_deref(), Some("web.internal.acme.com"));
+    assert_eq!(host.get_full_display_info(), "web (web.internal.acme.com)");
+    assert_eq!(probe_target(0, &host).unwrap().address, "web.internal.acme.com");
+    assert_eq!(TokenContext::for_host(&host).hostname, "web.internal.acme.com");
+    assert_eq!(connecting_message(&host, None), "Connecting to web (web.internal.acme.com) …");
+    assert!(host.matches_search("internal.acme"));
+    // 传给 ssh 的仍是别名，由 ssh 自己展开
+    assert_eq!(host.ssh_args(), ["web"]);
+}
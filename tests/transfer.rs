@@ -0,0 +1,77 @@
+mod common;
+
+use common::setup;
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::{ TransferPaths, PATH_HISTORY_LEN };
+use ssh_tui::core::{ AppMode, TransferKind, TransferState };
+
+#[test]
+fn recent_paths_are_deduplicated_and_capped() {
+    let mut paths = TransferPaths::default();
+    for i in 0..7 {
+        paths.remember_remote("web", &format!("/opt/app/{}", i));
+    }
+    paths.remember_remote("web", "/opt/app/4");
+    paths.remember_remote("web", "  ");
+
+    let remote = paths.remote_paths("web");
+    assert_eq!(remote.len(), PATH_HISTORY_LEN);
+    assert_eq!(remote[..3], ["/opt/app/4", "/opt/app/6", "/opt/app/5"]);
+    assert!(paths.remote_paths("db").is_empty());
+}
+
+#[test]
+fn corrupt_state_falls_back_to_empty() {
+    assert_eq!(TransferPaths::parse("remote = 3\n[[["), TransferPaths::default());
+
+    let mut paths = TransferPaths::default();
+    paths.remember_local("./target/release/app");
+    paths.remember_remote("web", "/opt/app/releases");
+    assert_eq!(TransferPaths::parse(&toml::to_string(&paths).unwrap()), paths);
+}
+
+#[test]
+fn commands_follow_the_selected_mode() {
+    let mut paths = TransferPaths::default();
+    paths.remember_local("build.tar");
+    paths.remember_remote("web", "/opt/app");
+    let mut state = TransferState::new(0, "web", &paths);
+    assert_eq!((state.local.as_str(), state.remote.as_str()), ("build.tar", "/opt/app"));
+
+    assert_eq!(state.command("web").unwrap(), ("scp", vec!["-r".to_string(), "build.tar".to_string(), "web:/opt/app".to_string()]));
+    state.cycle_kind();
+    assert_eq!(state.kind, TransferKind::Download);
+    assert_eq!(state.command("web").unwrap().1[1], "web:/opt/app");
+    state.cycle_kind();
+    assert_eq!(state.command("web").unwrap(), ("sftp", vec!["web:/opt/app".to_string()]));
+
+    state.kind = TransferKind::Upload;
+    state.local.clear();
+    assert!(state.command("web").is_err());
+}
+
+#[test]
+fn arrow_keys_browse_recent_remote_paths() {
+    let (_dir, mut app) = setup("Host web\n    HostName 10.0.0.1\n");
+    app.transfer_paths = TransferPaths::default();
+    for remote in ["/srv/old", "/opt/app/releases"] {
+        app.transfer_paths.remember_remote("web", remote);
+    }
+    app.list_state.select(Some(0));
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('t'))).unwrap();
+    assert_eq!(app.mode, AppMode::Transfer);
+    assert_eq!(app.transfer.as_ref().unwrap().remote, "/opt/app/releases");
+
+    for code in [KeyCode::Tab, KeyCode::Down, KeyCode::Down] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+    assert_eq!(app.transfer.as_ref().unwrap().remote, "/srv/old");
+    app.handle_key(KeyEvent::from(KeyCode::Up)).unwrap();
+    assert_eq!(app.transfer.as_ref().unwrap().remote, "/opt/app/releases");
+
+    // 本地路径为空时不执行
+    app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+    assert!(!app.pending_transfer);
+    assert!(app.transfer.as_ref().unwrap().error.is_some());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{ ctrl, press, setup };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ lint_hosts, parse_ssh_config_file };
+use ssh_tui::core::{ cycle_strict_host_key, AppMode, ChangeType, STRICT_HOST_KEY_FIELD };
+use std::fs;
+
+const CONFIG: &str = "\
+Host lab-vm
+    HostName 10.9.0.5
+    StrictHostKeyChecking no
+    UserKnownHostsFile /dev/null
+
+Host web
+    HostName 10.0.0.1
+";
+
+#[test]
+fn strict_host_key_values_cycle() {
+    assert_eq!(cycle_strict_host_key("", true), "yes");
+    assert_eq!(cycle_strict_host_key("no", true), "ask");
+    assert_eq!(cycle_strict_host_key("ask", true), "");
+    assert_eq!(cycle_strict_host_key("", false), "ask");
+    assert_eq!(cycle_strict_host_key("off", true), "");
+}
+
+#[test]
+fn trust_fields_are_loaded_and_saved_through_other_options() {
+    let (dir, mut app) = setup(CONFIG);
+    app.list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('e'));
+    let form = &app.editing_host.as_ref().unwrap().current;
+    assert_eq!(form.strict_host_key_checking, "no");
+    assert_eq!(form.user_known_hosts_file, "/dev/null");
+
+    for _ in 0..STRICT_HOST_KEY_FIELD {
+        press(&mut app, KeyCode::Tab);
+    }
+    // no -> ask -> (空) -> yes -> accept-new
+    for _ in 0..4 {
+        press(&mut app, KeyCode::Char(' '));
+    }
+    press(&mut app, KeyCode::Tab);
+    for _ in 0.."/dev/null".len() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    ctrl(&mut app, 's');
+
+    let ChangeType::Modified { new, .. } = &app.pending_changes[0] else {
+        panic!("expected a modification");
+    };
+    assert_eq!(new.other_options["stricthostkeychecking"], "accept-new");
+    assert!(!new.other_options.contains_key("userknownhostsfile"));
+
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    press(&mut app, KeyCode::Char('y'));
+    let written = fs::read_to_string(dir.path().join("config")).unwrap();
+    assert!(written.contains("    StrictHostKeyChecking accept-new\n"));
+}
+
+#[test]
+fn disabled_checking_is_flagged_by_lint() {
+    let (dir, _app) = setup(CONFIG);
+    let hosts = parse_ssh_config_file(&dir.path().join("config")).unwrap();
+    assert!(hosts[0].host_key_checking_disabled());
+    assert!(!hosts[1].host_key_checking_disabled());
+
+    let findings: Vec<_> = lint_hosts(&hosts).into_iter().filter(|f| f.rule == "host-key-checking-disabled").collect();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].host, "lab-vm");
+}
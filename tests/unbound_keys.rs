@@ -0,0 +1,181 @@
+mod common;
+
+use common::press;
+use crossterm::event::{ KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, ModifierKeyCode };
+use ratatui::{ backend::TestBackend, style::Modifier, Terminal };
+use ssh_tui::config::{ Settings, UnboundKeyFeedback };
+use ssh_tui::core::{ App, AppMode, FOOTER_FLASH };
+use ssh_tui::ui::render;
+use std::time::Instant;
+use tempfile::TempDir;
+
+fn setup(feedback: UnboundKeyFeedback) -> (TempDir, App) {
+    let settings = Settings { unbound_key_feedback: feedback, quick_connect_digits: false, ..Settings::default() };
+    common::setup_with("Host web\n    HostName 10.0.0.1\n", settings)
+}
+
+fn status(app: &App) -> Option<&str> {
+    app.status.as_ref().map(|status| status.text.as_str())
+}
+
+#[test]
+fn unbound_keys_on_the_host_list_are_reported() {
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Flash);
+    press(&mut app, KeyCode::Char('z'));
+    assert_eq!(status(&app), Some("unbound key: z — press . for actions"));
+    assert!(app.footer_flashing());
+    // 关闭快速连接时数字键也没有绑定
+    press(&mut app, KeyCode::F(7));
+    assert_eq!(status(&app), Some("unbound key: F7 — press . for actions"));
+    press(&mut app, KeyCode::Char('3'));
+    assert_eq!(status(&app), Some("unbound key: 3 — press . for actions"));
+
+    // 绑定的键（包括登记处中的主机操作）清除提示，不再报告
+    press(&mut app, KeyCode::Char('H'));
+    assert_eq!((status(&app), app.footer_flashing()), (None, false));
+    press(&mut app, KeyCode::Char('+'));
+    assert!(!status(&app).unwrap_or_default().starts_with("unbound key"));
+}
+
+#[test]
+fn config_management_reports_its_own_unbound_keys() {
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Flash);
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    // C 在主列表中是批量连接，在配置管理中没有绑定
+    press(&mut app, KeyCode::Char('C'));
+    assert_eq!(status(&app), Some("unbound key: C — press . for actions"));
+    press(&mut app, KeyCode::Char('d'));
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+}
+
+/// 在当前模式中按一个没有绑定的键：报告它，模式不变
+fn assert_unbound(app: &mut App, key: KeyCode, expected: &str) {
+    let mode = app.mode;
+    press(app, key);
+    assert_eq!((status(app), app.mode), (Some(expected), mode), "{:?} in {:?}", key, mode);
+    app.status = None;
+}
+
+#[test]
+fn popups_report_their_own_unbound_keys() {
+    // 弹窗没有操作菜单，提示中不带 “press .”
+    let (dir, mut app) = setup(UnboundKeyFeedback::Flash);
+    app.views_path = Some(dir.path().join("views.toml"));
+    for (open, mode) in [
+        (KeyCode::Char('\\'), AppMode::FilterMenu),
+        (KeyCode::Char('\''), AppMode::Views),
+        (KeyCode::Char('!'), AppMode::Dashboard),
+        (KeyCode::Char('v'), AppMode::ShowVersion),
+        (KeyCode::Char('.'), AppMode::ActionMenu),
+        (KeyCode::Char(':'), AppMode::CommandPalette),
+        (KeyCode::Char('/'), AppMode::Search),
+    ] {
+        press(&mut app, open);
+        assert_eq!(app.mode, mode);
+        assert_unbound(&mut app, KeyCode::F(9), "unbound key: F9");
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    // 选择列表中没有快捷键的字符同样报告，列表末尾的 Down 不算
+    press(&mut app, KeyCode::Char('\''));
+    assert_unbound(&mut app, KeyCode::Char('z'), "unbound key: z");
+    // 没有视图时 r/u/d 是绑定的键，只是无事可做
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(status(&app), None);
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('!'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(status(&app), None);
+    press(&mut app, KeyCode::Esc);
+
+    // 搜索框和命令面板中的字符是输入
+    for open in [KeyCode::Char('/'), KeyCode::Char(':')] {
+        press(&mut app, open);
+        press(&mut app, KeyCode::Char('z'));
+        assert_eq!(status(&app), None);
+        press(&mut app, KeyCode::Esc);
+    }
+}
+
+#[test]
+fn config_popups_and_the_form_report_unbound_keys() {
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Flash);
+    press(&mut app, KeyCode::Char('e'));
+
+    press(&mut app, KeyCode::Char('d'));
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+    assert_unbound(&mut app, KeyCode::Char('z'), "unbound key: z");
+    press(&mut app, KeyCode::Esc);
+
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.mode, AppMode::EditingHost);
+    assert_unbound(&mut app, KeyCode::F(9), "unbound key: F9");
+    // 表单的文本字段中字符是输入
+    press(&mut app, KeyCode::Char('z'));
+    assert_eq!(status(&app), None);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfirmDiscardEdit);
+    assert_unbound(&mut app, KeyCode::Char('z'), "unbound key: z");
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+
+    // 有待保存的变更时离开配置管理进入审阅
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Char('q'));
+    assert_eq!(app.mode, AppMode::ReviewChanges);
+    assert_unbound(&mut app, KeyCode::F(9), "unbound key: F9");
+
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Bell);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('z'));
+    assert!(app.pending_bell);
+    assert_eq!(app.mode, AppMode::ConfirmDelete);
+}
+
+#[test]
+fn modifiers_and_key_releases_are_ignored() {
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Bell);
+    for code in [KeyCode::Modifier(ModifierKeyCode::LeftShift), KeyCode::CapsLock, KeyCode::Null] {
+        press(&mut app, code);
+        assert!(!app.pending_bell, "{:?}", code);
+    }
+    let release = |code| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Release, state: KeyEventState::NONE };
+    app.handle_key(release(KeyCode::Char('z'))).unwrap();
+    assert!(!app.pending_bell);
+    app.handle_key(release(KeyCode::Char('q'))).unwrap();
+    assert!(!app.should_quit);
+
+    press(&mut app, KeyCode::Char('z'));
+    assert!(app.pending_bell);
+    assert_eq!(status(&app), None);
+
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Off);
+    press(&mut app, KeyCode::Char('z'));
+    assert_eq!((app.pending_bell, status(&app)), (false, None));
+    let settings: Settings = toml::from_str("unbound_key_feedback = \"bell\"").unwrap();
+    assert_eq!(settings.unbound_key_feedback, UnboundKeyFeedback::Bell);
+    assert_eq!(Settings::default().unbound_key_feedback, UnboundKeyFeedback::Flash);
+}
+
+#[test]
+fn the_footer_flashes_briefly_in_inverse() {
+    let (_dir, mut app) = setup(UnboundKeyFeedback::Flash);
+    press(&mut app, KeyCode::Char('z'));
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let footer_modifier = |terminal: &mut Terminal<TestBackend>, app: &mut App| {
+        terminal.draw(|f| render(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let y = (0..buffer.area.height)
+            .find(|&y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol.clone()).collect::<String>().contains("unbound key: z"))
+            .unwrap();
+        buffer.get(2, y).modifier
+    };
+    assert!(footer_modifier(&mut terminal, &mut app).contains(Modifier::REVERSED));
+    // 反色结束后提示仍保留到下一次按键
+    app.footer_flash = Some(Instant::now() - FOOTER_FLASH);
+    assert!(!footer_modifier(&mut terminal, &mut app).contains(Modifier::REVERSED));
+}
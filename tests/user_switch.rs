@@ -0,0 +1,101 @@
+mod common;
+
+use common::{ press, select_host, setup_with };
+use crossterm::event::KeyCode;
+use ssh_tui::config::{ parse_ssh_config_from_str, Settings };
+use ssh_tui::core::{ user_choices, App, AppMode };
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: web
+Host web-1
+    HostName 10.0.0.1
+    User deploy
+
+# @folder: web
+Host web-2
+    HostName 10.0.0.2
+    User admin
+
+# @folder: web
+Host web-3
+    HostName 10.0.0.3
+    User deploy
+
+Host other
+    HostName 10.0.1.1
+    User alice
+";
+
+fn app(settings: Settings) -> (TempDir, App) {
+    let (dir, mut app) = setup_with(CONFIG, settings);
+    select_host(&mut app, "web-1");
+    (dir, app)
+}
+
+#[test]
+fn choices_come_from_the_host_and_its_folder() {
+    let hosts = parse_ssh_config_from_str(CONFIG);
+    assert_eq!(user_choices(&hosts[0], &hosts), ["root", "deploy", "admin"]);
+    // 不在文件夹中的主机只有 root 和自己的用户
+    assert_eq!(user_choices(&hosts[3], &hosts), ["root", "alice"]);
+
+    let args = hosts[0].ssh_args_as(Some("root"));
+    assert_eq!(args, ["-l", "root", "web-1"]);
+}
+
+#[test]
+fn picking_a_user_connects_with_l_and_remembers_it() {
+    let (_dir, mut app) = app(Settings::default());
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(app.mode, AppMode::SwitchUser);
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.mode, AppMode::Normal);
+    assert!(app.pending_connect.is_some());
+    assert_eq!(app.connect_user.as_deref(), Some("root"));
+    assert_eq!(app.session_users.get("web-1").map(String::as_str), Some("root"));
+    // 配置本身不变
+    assert_eq!(app.hosts[0].user.as_deref(), Some("deploy"));
+    assert!(app.pending_changes.is_empty());
+
+    // 再次打开时默认选中上次的用户，U 直接连接
+    app.pending_connect = None;
+    press(&mut app, KeyCode::Char('U'));
+    assert!(app.pending_connect.is_some());
+    assert_eq!(app.connect_user.as_deref(), Some("root"));
+
+    // 普通连接不带用户
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.connect_user, None);
+}
+
+#[test]
+fn free_text_user_and_unremembered_shortcut() {
+    let (_dir, mut app) = app(Settings::default());
+    press(&mut app, KeyCode::Char('U'));
+    assert_eq!(app.mode, AppMode::SwitchUser);
+    for c in "backup".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.connect_user.as_deref(), Some("backup"));
+    assert_eq!(app.session_users.get("web-1").map(String::as_str), Some("backup"));
+}
+
+#[test]
+fn confirmation_carries_the_override_user() {
+    let settings = Settings { confirm_connect: true, ..Settings::default() };
+    let (_dir, mut app) = app(settings);
+    press(&mut app, KeyCode::Char('u'));
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+
+    assert_eq!(app.mode, AppMode::ConfirmConnect);
+    assert_eq!(app.connect_user.as_deref(), Some("admin"));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.connect_user, None);
+    assert_eq!(app.pending_connect, None);
+}
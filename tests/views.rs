@@ -0,0 +1,163 @@
+mod common;
+
+use common::{ press, type_text };
+use crossterm::event::KeyCode;
+use ssh_tui::core::{ load_views, save_views, App, AppMode, QuickFilter, SavedView, ViewState, VIEW_FORMAT_VERSION };
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const CONFIG: &str = "\
+# @folder: work
+Host db-01
+    HostName 10.0.0.1
+
+# @folder: work
+# @hidden
+Host db-02
+    HostName 10.0.0.2
+
+# @folder: work
+Host web-01
+
+Host db-lab
+    HostName 10.0.9.1
+    IdentityFile ~/.ssh/lab
+";
+
+fn setup() -> (TempDir, App, PathBuf) {
+    let (dir, mut app) = common::setup(CONFIG);
+    let views = dir.path().join("data").join("views.toml");
+    app.views_path = Some(views.clone());
+    (dir, app, views)
+}
+
+fn visible_names(app: &App) -> Vec<&str> {
+    app.filtered_hosts.iter().map(|&i| app.hosts[i].name.as_str()).collect()
+}
+
+fn db_view() -> ViewState {
+    ViewState { query: "db".to_string(), quick_filters: vec![QuickFilter::MissingIdentityFile], show_hidden: true }
+}
+
+#[test]
+fn views_are_versioned_and_older_entries_migrate() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("views.toml");
+    let saved = db_view().to_view("work dbs");
+    assert_eq!(saved.version, VIEW_FORMAT_VERSION);
+    assert_eq!(saved.quick_filters, ["missing-identityfile"]);
+
+    // 没有版本号的条目按版本 0 读取，缺少的字段取默认值
+    fs::write(&path, "[[views]]\nname = \"by hand\"\nquick_filters = [\"hidden\", \"no-folder\", \"hidden\"]\n").unwrap();
+    let mut views = load_views(&path).unwrap();
+    assert_eq!(views[0].version, 0);
+    let migrated = views[0].migrate().unwrap();
+    assert_eq!(migrated, ViewState { query: String::new(), quick_filters: vec![QuickFilter::NoFolder, QuickFilter::Hidden], show_hidden: false });
+    assert_eq!(migrated.summary(), "no folder + hidden");
+
+    views.push(saved.clone());
+    save_views(&path, &views).unwrap();
+    assert_eq!(load_views(&path).unwrap()[1], saved);
+    assert_eq!(saved.migrate().unwrap(), db_view());
+    assert_eq!(load_views(&dir.path().join("missing.toml")).unwrap(), Vec::<SavedView>::new());
+}
+
+#[test]
+fn views_that_cannot_be_fully_reproduced_are_refused() {
+    let newer = SavedView { version: VIEW_FORMAT_VERSION + 1, ..db_view().to_view("future") };
+    assert_eq!(
+        newer.migrate().unwrap_err(),
+        format!("View 'future' was saved by a newer sshc (format {}); update sshc to apply it", VIEW_FORMAT_VERSION + 1)
+    );
+    let unknown = SavedView { quick_filters: vec!["stale-key".to_string()], ..db_view().to_view("odd") };
+    assert_eq!(unknown.migrate().unwrap_err(), "View 'odd' uses an unknown filter 'stale-key'");
+
+    // 应用失败时保持当前状态，文件中的视图原样保留
+    let (_dir, mut app, path) = setup();
+    save_views(&path, std::slice::from_ref(&newer)).unwrap();
+    press(&mut app, KeyCode::Char('\''));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::Views);
+    assert_eq!(app.current_view(), ViewState::default());
+    press(&mut app, KeyCode::Char('s'));
+    type_text(&mut app, "mine");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(load_views(&path).unwrap()[0], newer);
+}
+
+#[test]
+fn a_saved_view_restores_query_filters_and_hidden_hosts() {
+    let (_dir, mut app, path) = setup();
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "db");
+    press(&mut app, KeyCode::Esc);
+    app.toggle_quick_filter(QuickFilter::MissingIdentityFile);
+    press(&mut app, KeyCode::Char('H'));
+    let expected = visible_names(&app).into_iter().map(str::to_string).collect::<Vec<_>>();
+    assert_eq!(expected, ["db-01", "db-02"]);
+
+    press(&mut app, KeyCode::Char('\''));
+    press(&mut app, KeyCode::Char('s'));
+    type_text(&mut app, "work dbs");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(load_views(&path).unwrap(), [db_view().to_view("work dbs")]);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::Normal);
+
+    // 清除所有条件后再应用
+    press(&mut app, KeyCode::Char('/'));
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('H'));
+    assert_eq!(app.current_view(), ViewState::default());
+
+    press(&mut app, KeyCode::Char('\''));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_eq!(app.current_view(), db_view());
+    assert_eq!(visible_names(&app), expected);
+}
+
+#[test]
+fn views_are_renamed_updated_and_deleted_in_the_picker() {
+    let (_dir, mut app, path) = setup();
+    save_views(&path, &[db_view().to_view("dbs"), ViewState::default().to_view("all")]).unwrap();
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('\''));
+    assert_eq!(app.mode, AppMode::Views);
+
+    // 重名被拒绝，输入框保持打开
+    press(&mut app, KeyCode::Char('r'));
+    for _ in 0.."dbs".len() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    type_text(&mut app, "ALL");
+    press(&mut app, KeyCode::Enter);
+    assert!(app.view_picker.as_ref().unwrap().prompt.is_some());
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    type_text(&mut app, "pps");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(load_views(&path).unwrap()[0].name, "Apps");
+
+    press(&mut app, KeyCode::Down);
+    app.show_hidden = true;
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(load_views(&path).unwrap()[1], ViewState { show_hidden: true, ..ViewState::default() }.to_view("all"));
+
+    press(&mut app, KeyCode::Up);
+    press(&mut app, KeyCode::Char('d'));
+    let names: Vec<String> = load_views(&path).unwrap().into_iter().map(|view| view.name).collect();
+    assert_eq!(names, ["all"]);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+
+    // 文件损坏时不打开，避免覆盖
+    fs::write(&path, "[[views]\n").unwrap();
+    press(&mut app, KeyCode::Char('\''));
+    assert_eq!(app.mode, AppMode::ConfigManagement);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[[views]\n");
+}
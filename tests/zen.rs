@@ -0,0 +1,57 @@
+mod common;
+
+use common::{ ctrl, screen_rows };
+use crossterm::event::{ KeyCode, KeyEvent };
+use ssh_tui::config::Settings;
+use ssh_tui::core::{ App, AppMode };
+use ssh_tui::ui::search_bar_height;
+use tempfile::TempDir;
+
+fn setup(zen: bool) -> (TempDir, App) {
+    let config: String = (1..=30).map(|i| format!("Host host-{:02}\n    HostName 10.0.0.{}\n\n", i, i)).collect();
+    let settings = Settings { zen_mode: zen, ..Settings::default() };
+    common::setup_with(&config, settings)
+}
+
+#[test]
+fn search_bar_collapses_only_when_idle() {
+    let (_dir, mut app) = setup(true);
+    assert_eq!(search_bar_height(&app), 0);
+
+    app.handle_key(KeyEvent::from(KeyCode::Char('/'))).unwrap();
+    assert_eq!(search_bar_height(&app), 1);
+    app.handle_key(KeyEvent::from(KeyCode::Char('7'))).unwrap();
+    app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+    assert_eq!(search_bar_height(&app), 1);
+
+    app.search_query.clear();
+    ctrl(&mut app, 'z');
+    assert!(!app.zen);
+    assert_eq!(search_bar_height(&app), 3);
+}
+
+#[test]
+fn zen_mode_gives_the_list_the_full_height() {
+    let (_dir, mut app) = setup(false);
+    ctrl(&mut app, 'z');
+    assert!(app.zen);
+
+    let rows = screen_rows(&mut app, 80, 16);
+    // 外边距下第一行就是列表的上边框，页脚仍在倒数第二行
+    assert!(rows[1].contains("SSH Hosts"), "top row: {}", rows[1]);
+    assert!(rows[14].contains("Select"), "footer: {}", rows[14]);
+    assert!(rows[2].contains("host-01"));
+}
+
+#[test]
+fn ctrl_z_still_reverts_fields_in_the_edit_form() {
+    let (_dir, mut app) = setup(false);
+    app.list_state.select(Some(0));
+    for code in [KeyCode::Char('e'), KeyCode::Char('e'), KeyCode::Char('x')] {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+    assert_eq!(app.mode, AppMode::EditingHost);
+    ctrl(&mut app, 'z');
+    assert!(!app.zen);
+    assert_eq!(app.editing_host.as_ref().unwrap().current.name, "host-01");
+}